@@ -83,7 +83,7 @@ where
         // Apply pre-block system contract calls.
         apply_beacon_root_contract_call(
             &self.evm_config,
-            &self.provider.chain_spec(),
+            self.provider.chain_spec().as_ref(),
             block.timestamp,
             block.number,
             block.parent_beacon_block_root,
@@ -91,7 +91,7 @@ where
         )?;
         apply_blockhashes_contract_call(
             &self.evm_config,
-            &self.provider.chain_spec(),
+            self.provider.chain_spec().as_ref(),
             block.timestamp,
             block.number,
             block.parent_hash,
@@ -195,9 +195,12 @@ where
                     healthy_node_client,
                     block.number.into(),
                     true,
+                    None,
                 )
                 .await
-            })?;
+            })?
+            .into_witness()?
+            .witness;
 
             // Write the healthy node witness to the output directory.
             File::create_new(self.output_directory.join(format!(
@@ -150,7 +150,7 @@ where
         // apply pre execution changes
         apply_beacon_root_contract_call(
             &self.evm_config,
-            &self.chain_spec,
+            self.chain_spec.as_ref(),
             block.timestamp,
             block.number,
             block.parent_beacon_block_root,
@@ -158,7 +158,7 @@ where
         )?;
         apply_blockhashes_contract_call(
             &self.evm_config,
-            &self.chain_spec,
+            self.chain_spec.as_ref(),
             block.timestamp,
             block.number,
             block.parent_hash,
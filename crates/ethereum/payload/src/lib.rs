@@ -172,7 +172,7 @@ where
     pre_block_beacon_root_contract_call(
         &mut db,
         &evm_config,
-        &chain_spec,
+        chain_spec.as_ref(),
         &initialized_cfg,
         &initialized_block_env,
         attributes.parent_beacon_block_root,
@@ -190,7 +190,7 @@ where
     pre_block_blockhashes_contract_call(
         &mut db,
         &evm_config,
-        &chain_spec,
+        chain_spec.as_ref(),
         &initialized_cfg,
         &initialized_block_env,
         parent_block.hash(),
@@ -5,7 +5,7 @@ use alloy_eips::eip2935::HISTORY_STORAGE_ADDRESS;
 
 use crate::ConfigureEvm;
 use core::fmt::Display;
-use reth_chainspec::{ChainSpec, EthereumHardforks};
+use reth_chainspec::EthereumHardforks;
 use reth_execution_errors::{BlockExecutionError, BlockValidationError};
 use revm::{interpreter::Host, Database, DatabaseCommit, Evm};
 use revm_primitives::{BlockEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg, ResultAndState, B256};
@@ -17,10 +17,10 @@ use revm_primitives::{BlockEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg, ResultA
 ///
 /// This uses [`apply_blockhashes_contract_call`] to ultimately apply the
 /// blockhash contract state change.
-pub fn pre_block_blockhashes_contract_call<EvmConfig, DB>(
+pub fn pre_block_blockhashes_contract_call<EvmConfig, Spec, DB>(
     db: &mut DB,
     evm_config: &EvmConfig,
-    chain_spec: &ChainSpec,
+    chain_spec: &Spec,
     initialized_cfg: &CfgEnvWithHandlerCfg,
     initialized_block_env: &BlockEnv,
     parent_block_hash: B256,
@@ -29,6 +29,7 @@ where
     DB: Database + DatabaseCommit,
     DB::Error: Display,
     EvmConfig: ConfigureEvm,
+    Spec: EthereumHardforks,
 {
     // Apply the pre-block EIP-2935 contract call
     let mut evm_pre_block = Evm::builder()
@@ -51,7 +52,7 @@ where
 }
 
 /// Applies the pre-block call to the [EIP-2935] blockhashes contract, using the given block,
-/// [`ChainSpec`], and EVM.
+/// chain spec, and EVM.
 ///
 /// If Prague is not activated, or the block is the genesis block, then this is a no-op, and no
 /// state changes are made.
@@ -63,9 +64,9 @@ where
 ///
 /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
 #[inline]
-pub fn transact_blockhashes_contract_call<EvmConfig, EXT, DB>(
+pub fn transact_blockhashes_contract_call<EvmConfig, Spec, EXT, DB>(
     evm_config: &EvmConfig,
-    chain_spec: &ChainSpec,
+    chain_spec: &Spec,
     block_timestamp: u64,
     block_number: u64,
     parent_block_hash: B256,
@@ -75,6 +76,7 @@ where
     DB: Database + DatabaseCommit,
     DB::Error: core::fmt::Display,
     EvmConfig: ConfigureEvm,
+    Spec: EthereumHardforks,
 {
     if !chain_spec.is_prague_active_at_timestamp(block_timestamp) {
         return Ok(None)
@@ -115,16 +117,16 @@ where
 }
 
 /// Applies the pre-block call to the [EIP-2935] blockhashes contract, using the given block,
-/// [`ChainSpec`], and EVM and commits the relevant state changes.
+/// chain spec, and EVM and commits the relevant state changes.
 ///
 /// If Prague is not activated, or the block is the genesis block, then this is a no-op, and no
 /// state changes are made.
 ///
 /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
 #[inline]
-pub fn apply_blockhashes_contract_call<EvmConfig, EXT, DB>(
+pub fn apply_blockhashes_contract_call<EvmConfig, Spec, EXT, DB>(
     evm_config: &EvmConfig,
-    chain_spec: &ChainSpec,
+    chain_spec: &Spec,
     block_timestamp: u64,
     block_number: u64,
     parent_block_hash: B256,
@@ -134,6 +136,7 @@ where
     DB: Database + DatabaseCommit,
     DB::Error: core::fmt::Display,
     EvmConfig: ConfigureEvm,
+    Spec: EthereumHardforks,
 {
     if let Some(res) = transact_blockhashes_contract_call(
         evm_config,
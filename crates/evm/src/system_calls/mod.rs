@@ -9,7 +9,7 @@ use alloy_eips::{
     eip7002::{WithdrawalRequest, WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS},
     eip7251::{ConsolidationRequest, CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS},
 };
-use reth_chainspec::{ChainSpec, EthereumHardforks};
+use reth_chainspec::EthereumHardforks;
 use reth_execution_errors::{BlockExecutionError, BlockValidationError};
 use reth_primitives::{Buf, Request};
 use revm::{interpreter::Host, Database, DatabaseCommit, Evm};
@@ -28,10 +28,10 @@ pub use eip2935::*;
 ///
 /// This uses [`apply_beacon_root_contract_call`] to ultimately apply the beacon root contract state
 /// change.
-pub fn pre_block_beacon_root_contract_call<EvmConfig, DB>(
+pub fn pre_block_beacon_root_contract_call<EvmConfig, Spec, DB>(
     db: &mut DB,
     evm_config: &EvmConfig,
-    chain_spec: &ChainSpec,
+    chain_spec: &Spec,
     initialized_cfg: &CfgEnvWithHandlerCfg,
     initialized_block_env: &BlockEnv,
     parent_beacon_block_root: Option<B256>,
@@ -40,6 +40,7 @@ where
     DB: Database + DatabaseCommit,
     DB::Error: Display,
     EvmConfig: ConfigureEvm,
+    Spec: EthereumHardforks,
 {
     // apply pre-block EIP-4788 contract call
     let mut evm_pre_block = Evm::builder()
@@ -63,16 +64,16 @@ where
 }
 
 /// Applies the pre-block call to the [EIP-4788] beacon block root contract, using the given block,
-/// [`ChainSpec`], EVM.
+/// chain spec and EVM.
 ///
 /// If Cancun is not activated or the block is the genesis block, then this is a no-op, and no
 /// state changes are made.
 ///
 /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
 #[inline]
-pub fn apply_beacon_root_contract_call<EvmConfig, EXT, DB>(
+pub fn apply_beacon_root_contract_call<EvmConfig, Spec, EXT, DB>(
     evm_config: &EvmConfig,
-    chain_spec: &ChainSpec,
+    chain_spec: &Spec,
     block_timestamp: u64,
     block_number: u64,
     parent_beacon_block_root: Option<B256>,
@@ -82,6 +83,7 @@ where
     DB: Database + DatabaseCommit,
     DB::Error: core::fmt::Display,
     EvmConfig: ConfigureEvm,
+    Spec: EthereumHardforks,
 {
     if !chain_spec.is_cancun_active_at_timestamp(block_timestamp) {
         return Ok(())
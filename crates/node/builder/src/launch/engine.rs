@@ -32,6 +32,7 @@ use reth_node_core::{
 };
 use reth_node_events::{cl::ConsensusLayerHealthEvents, node};
 use reth_provider::providers::BlockchainProvider2;
+use reth_rpc::{PersistenceSettings, PersistenceSettingsHandle};
 use reth_rpc_engine_api::{capabilities::EngineCapabilities, EngineApi};
 use reth_rpc_types::{engine::ClientVersionV1, WithOtherFields};
 use reth_tasks::TaskExecutor;
@@ -43,7 +44,7 @@ use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use crate::{
     hooks::NodeHooks,
-    rpc::{launch_rpc_servers, EthApiBuilderProvider},
+    rpc::{launch_rpc_servers_with_persistence_settings, EthApiBuilderProvider},
     setup::build_networked_pipeline,
     AddOns, ExExLauncher, FullNode, LaunchContext, LaunchNode, NodeAdapter,
     NodeBuilderWithComponents, NodeComponents, NodeComponentsBuilder, NodeHandle, NodeTypesAdapter,
@@ -209,6 +210,14 @@ where
         info!(target: "reth::cli", prune_config=?ctx.prune_config().unwrap_or_default(), "Pruner initialized");
 
         // Configure the consensus engine
+        let tree_config = TreeConfig::default();
+        // the `debug` API reports the persistence settings of the engine, which persists based on
+        // the block thresholds only and doesn't read adjusted settings
+        let persistence_settings = PersistenceSettingsHandle::read_only(PersistenceSettings {
+            flush_interval: None,
+            memory_block_buffer_target: tree_config.memory_block_buffer_target(),
+            persistence_threshold: tree_config.persistence_threshold(),
+        });
         let mut eth_service = EngineService::new(
             ctx.consensus(),
             ctx.components().block_executor().clone(),
@@ -221,7 +230,7 @@ where
             ctx.blockchain_db().clone(),
             pruner,
             ctx.components().payload_builder().clone(),
-            TreeConfig::default(),
+            tree_config,
             ctx.invalid_block_hook()?,
             ctx.sync_metrics_tx(),
         );
@@ -279,11 +288,12 @@ where
         let jwt_secret = ctx.auth_jwt_secret()?;
 
         // Start RPC servers
-        let (rpc_server_handles, rpc_registry) = launch_rpc_servers(
+        let (rpc_server_handles, rpc_registry) = launch_rpc_servers_with_persistence_settings(
             ctx.node_adapter().clone(),
             engine_api,
             ctx.node_config(),
             jwt_secret,
+            Some(persistence_settings),
             rpc,
         )
         .await?;
@@ -36,6 +36,7 @@ use reth_node_core::{
 use reth_node_events::{cl::ConsensusLayerHealthEvents, node};
 use reth_primitives::format_ether;
 use reth_provider::providers::BlockchainProvider;
+use reth_rpc::{PersistenceSettings, PersistenceSettingsHandle};
 use reth_rpc_engine_api::{capabilities::EngineCapabilities, EngineApi};
 use reth_rpc_types::{engine::ClientVersionV1, WithOtherFields};
 use reth_tasks::TaskExecutor;
@@ -49,7 +50,7 @@ use crate::{
     components::{NodeComponents, NodeComponentsBuilder},
     hooks::NodeHooks,
     node::FullNode,
-    rpc::EthApiBuilderProvider,
+    rpc::{launch_rpc_servers_with_persistence_settings, EthApiBuilderProvider},
     AddOns, NodeBuilderWithComponents, NodeHandle,
 };
 
@@ -362,12 +363,21 @@ where
         // extract the jwt secret from the args if possible
         let jwt_secret = ctx.auth_jwt_secret()?;
 
+        // the legacy engine persists canonical blocks immediately, which can't be adjusted at
+        // runtime
+        let persistence_settings = PersistenceSettingsHandle::read_only(PersistenceSettings {
+            flush_interval: None,
+            memory_block_buffer_target: 0,
+            persistence_threshold: 0,
+        });
+
         // Start RPC servers
-        let (rpc_server_handles, rpc_registry) = crate::rpc::launch_rpc_servers(
+        let (rpc_server_handles, rpc_registry) = launch_rpc_servers_with_persistence_settings(
             ctx.node_adapter().clone(),
             engine_api,
             ctx.node_config(),
             jwt_secret,
+            Some(persistence_settings),
             rpc,
         )
         .await?;
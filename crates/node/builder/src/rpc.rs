@@ -17,6 +17,7 @@ use reth_node_core::{
     },
 };
 use reth_payload_builder::PayloadBuilderHandle;
+use reth_rpc::PersistenceSettingsHandle;
 use reth_rpc_builder::{
     auth::{AuthRpcModule, AuthServerHandle},
     config::RethRpcServerConfig,
@@ -300,6 +301,33 @@ pub async fn launch_rpc_servers<Node, Engine, EthApi>(
     jwt_secret: JwtSecret,
     add_ons: RpcAddOns<Node, EthApi>,
 ) -> eyre::Result<(RethRpcServerHandles, RpcRegistry<Node, EthApi>)>
+where
+    Node: FullNodeComponents<Types: NodeTypesWithDB<ChainSpec = ChainSpec>> + Clone,
+    Engine: EngineApiServer<<Node::Types as NodeTypesWithEngine>::Engine>,
+    EthApi: EthApiBuilderProvider<Node>
+        + FullEthApiServer<
+            NetworkTypes: alloy_network::Network<
+                TransactionResponse = WithOtherFields<reth_rpc_types::Transaction>,
+                ReceiptResponse = AnyTransactionReceipt,
+            >,
+        >,
+{
+    launch_rpc_servers_with_persistence_settings(
+        node, engine_api, config, jwt_secret, None, add_ons,
+    )
+    .await
+}
+
+/// Launch the rpc servers like [`launch_rpc_servers`], with the `debug` API reporting the
+/// persistence settings of the given handle, if any.
+pub(crate) async fn launch_rpc_servers_with_persistence_settings<Node, Engine, EthApi>(
+    node: Node,
+    engine_api: Engine,
+    config: &NodeConfig,
+    jwt_secret: JwtSecret,
+    persistence_settings: Option<PersistenceSettingsHandle>,
+    add_ons: RpcAddOns<Node, EthApi>,
+) -> eyre::Result<(RethRpcServerHandles, RpcRegistry<Node, EthApi>)>
 where
     Node: FullNodeComponents<Types: NodeTypesWithDB<ChainSpec = ChainSpec>> + Clone,
     Engine: EngineApiServer<<Node::Types as NodeTypesWithEngine>::Engine>,
@@ -312,7 +340,11 @@ where
         >,
 {
     let auth_config = config.rpc.auth_server_config(jwt_secret)?;
-    let module_config = config.rpc.transport_rpc_module_config();
+    let mut module_config = config.rpc.transport_rpc_module_config();
+    if let Some(settings) = persistence_settings {
+        let rpc_config = module_config.config_mut().get_or_insert_with(Default::default);
+        rpc_config.debug_mut().persistence_settings = Some(settings);
+    }
     debug!(target: "reth::cli", http=?module_config.http(), ws=?module_config.ws(), "Using RPC module config");
 
     let (mut modules, mut auth_module, registry) = RpcModuleBuilder::default()
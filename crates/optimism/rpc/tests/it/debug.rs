@@ -0,0 +1,139 @@
+//! Tracing of OP blocks with the `debug` API of reth-rpc.
+
+use alloy_primitives::{address, hex, Address, B256, U256};
+use reth_evm_optimism::OptimismEvmConfig;
+use reth_network_api::noop::NoopNetwork;
+use reth_optimism_chainspec::OP_MAINNET;
+use reth_primitives::{
+    constants::ETHEREUM_BLOCK_GAS_LIMIT, Block, BlockNumberOrTag, Header, Signature, Transaction,
+    TransactionSigned, TxDeposit, TxKind,
+};
+use reth_provider::test_utils::MockEthProvider;
+use reth_rpc::{DebugApi, EthApi};
+use reth_rpc_eth_types::{EthStateCache, FeeHistoryCache, FeeHistoryCacheConfig, GasPriceOracle};
+use reth_rpc_server_types::constants::{DEFAULT_ETH_PROOF_WINDOW, DEFAULT_PROOF_PERMITS};
+use reth_rpc_types::trace::geth::{
+    GethDebugBuiltInTracerType, GethDebugTracerConfig, GethDebugTracingOptions, GethTrace,
+    PreStateFrame, TraceResult,
+};
+use reth_tasks::pool::{BlockingTaskGuard, BlockingTaskPool};
+use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+use std::sync::Arc;
+
+type OpDebugApi =
+    DebugApi<MockEthProvider, EthApi<MockEthProvider, TestPool, NoopNetwork, OptimismEvmConfig>>;
+
+/// OP Mainnet transaction at index 0 in block 124665056, the L1 attributes deposit.
+///
+/// <https://optimistic.etherscan.io/tx/0x312e290cf36df704a2217b015d6455396830b0ce678b860ebfcc30f41403d7b1>
+const TX_SET_L1_BLOCK_OP_MAINNET_BLOCK_124665056: [u8; 251] = hex!("7ef8f8a0683079df94aa5b9cf86687d739a60a9b4f0835e520ec4d664e2e415dca17a6df94deaddeaddeaddeaddeaddeaddeaddeaddead00019442000000000000000000000000000000000000158080830f424080b8a4440a5e200000146b000f79c500000000000000040000000066d052e700000000013ad8a3000000000000000000000000000000000000000000000000000000003ef1278700000000000000000000000000000000000000000000000000000000000000012fdf87b89884a61e74b322bbcf60386f543bfae7827725efaaf0ab1de2294a590000000000000000000000006887246668a3b87f54deb3b94ba47a6f63f32985");
+
+/// Timestamp of OP mainnet block 124665056.
+///
+/// <https://optimistic.etherscan.io/block/124665056>
+const BLOCK_124665056_TIMESTAMP: u64 = 1724928889;
+
+/// The sender of the L1 attributes deposit.
+const L1_ATTRIBUTES_DEPOSITOR: Address = address!("deaddeaddeaddeaddeaddeaddeaddeaddead0001");
+
+/// The `L1Block` predeploy.
+const L1_BLOCK_PREDEPLOY: Address = address!("4200000000000000000000000000000000000015");
+
+/// Amount minted by the user deposit.
+const MINT: u128 = 1_000_000_000_000_000_000;
+
+/// Creates a debug API with the OP EVM config on top of a mock provider that holds an OP block at
+/// number 1, which consists of the L1 attributes deposit and a user deposit that mints ETH and
+/// transfers half of it.
+fn debug_api_with_op_block(depositor: Address, recipient: Address) -> OpDebugApi {
+    let provider =
+        MockEthProvider { chain_spec: Arc::new(OP_MAINNET.inner.clone()), ..Default::default() };
+
+    let genesis = Header { gas_limit: ETHEREUM_BLOCK_GAS_LIMIT, ..Default::default() };
+    let genesis_hash = genesis.hash_slow();
+    provider.add_block(genesis_hash, Block { header: genesis, ..Default::default() });
+
+    let l1_attributes = TransactionSigned::decode_enveloped(
+        &mut TX_SET_L1_BLOCK_OP_MAINNET_BLOCK_124665056.as_slice(),
+    )
+    .unwrap();
+    let deposit = TransactionSigned::from_transaction_and_signature(
+        Transaction::Deposit(TxDeposit {
+            source_hash: B256::random(),
+            from: depositor,
+            to: TxKind::Call(recipient),
+            mint: Some(MINT),
+            value: U256::from(MINT / 2),
+            gas_limit: 100_000,
+            ..Default::default()
+        }),
+        Signature::optimism_deposit_tx_signature(),
+    );
+    let header = Header {
+        number: 1,
+        parent_hash: genesis_hash,
+        timestamp: BLOCK_124665056_TIMESTAMP,
+        gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+        ..Default::default()
+    };
+    provider.add_block(
+        header.hash_slow(),
+        Block { header, body: vec![l1_attributes, deposit], ..Default::default() },
+    );
+
+    let evm_config = OptimismEvmConfig::new(OP_MAINNET.clone());
+    let cache = EthStateCache::spawn(provider.clone(), Default::default(), evm_config.clone());
+    let eth_api = EthApi::new(
+        provider.clone(),
+        testing_pool(),
+        NoopNetwork::default(),
+        cache.clone(),
+        GasPriceOracle::new(provider.clone(), Default::default(), cache.clone()),
+        ETHEREUM_BLOCK_GAS_LIMIT,
+        DEFAULT_ETH_PROOF_WINDOW,
+        BlockingTaskPool::build().expect("failed to build tracing pool"),
+        FeeHistoryCache::new(cache, FeeHistoryCacheConfig::default()),
+        evm_config,
+        DEFAULT_PROOF_PERMITS,
+    );
+
+    DebugApi::new(provider, eth_api, BlockingTaskGuard::new(4))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trace_op_block_with_deposits() {
+    let depositor = Address::random();
+    let recipient = Address::random();
+    let debug_api = debug_api_with_op_block(depositor, recipient);
+    let block = BlockNumberOrTag::Number(1).into();
+
+    let opts = GethDebugTracingOptions::default()
+        .with_tracer(GethDebugBuiltInTracerType::CallTracer.into());
+    let results = debug_api.debug_trace_block(block, opts.into()).await.unwrap();
+    assert_eq!(results.len(), 2);
+    let TraceResult::Success { result: GethTrace::CallTracer(frame), .. } = &results[0] else {
+        panic!("expected call trace of the L1 attributes deposit, got {:?}", results[0])
+    };
+    assert_eq!(frame.from, L1_ATTRIBUTES_DEPOSITOR);
+    assert_eq!(frame.to, Some(L1_BLOCK_PREDEPLOY));
+    assert!(frame.error.is_none());
+
+    // the depositor has no balance, the transfer is only covered by the minted amount
+    let mut opts = GethDebugTracingOptions::default()
+        .with_tracer(GethDebugBuiltInTracerType::PreStateTracer.into());
+    opts.tracer_config = GethDebugTracerConfig(serde_json::json!({ "diffMode": true }));
+    let results = debug_api.debug_trace_block(block, opts.clone().into()).await.unwrap();
+    let TraceResult::Success {
+        result: GethTrace::PreStateTracer(PreStateFrame::Diff(diff)),
+        tx_hash: Some(deposit_hash),
+    } = &results[1]
+    else {
+        panic!("expected prestate diff of the user deposit, got {:?}", results[1])
+    };
+    assert_eq!(diff.post[&depositor].balance, Some(U256::from(MINT / 2)));
+    assert_eq!(diff.post[&recipient].balance, Some(U256::from(MINT / 2)));
+
+    // the deposit is traced the same on its own
+    let trace = debug_api.debug_trace_transaction(*deposit_hash, opts).await.unwrap();
+    assert_eq!(trace, GethTrace::PreStateTracer(PreStateFrame::Diff(diff.clone())));
+}
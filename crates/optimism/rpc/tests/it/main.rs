@@ -0,0 +1,4 @@
+#[cfg(feature = "optimism")]
+mod debug;
+
+const fn main() {}
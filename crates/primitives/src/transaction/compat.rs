@@ -100,6 +100,8 @@ impl FillTxEnv for TransactionSigned {
                 tx_env.data = tx.input.clone();
                 tx_env.chain_id = None;
                 tx_env.nonce = None;
+                tx_env.blob_hashes.clear();
+                tx_env.max_fee_per_blob_gas.take();
                 tx_env.authorization_list = None;
 
                 tx_env.optimism = revm_primitives::OptimismFields {
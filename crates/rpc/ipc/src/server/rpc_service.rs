@@ -58,7 +58,10 @@ impl<'a> RpcServiceT<'a> for RpcService {
         let params = req.params();
         let name = req.method_name();
         let id = req.id().clone();
-        let extensions = Extensions::new();
+        // like the connections of the http and ws servers, identify the connection the request
+        // was sent on
+        let mut extensions = Extensions::new();
+        extensions.insert(conn_id);
 
         match self.methods.method_with_name(name) {
             None => {
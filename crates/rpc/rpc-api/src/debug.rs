@@ -2,12 +2,17 @@ use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{Address, Bytes, B256};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use reth_rpc_types::{
-    debug::ExecutionWitness,
-    trace::geth::{
-        BlockTraceResult, GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
-        TraceResult,
+    debug::{
+        BlockAccessList, BlockReplayReport, BlockTraceResult, DbTables, DebugBundle,
+        DebugExecutionWitness, DebugSubscriptionKind, DebugTraceBlockResponse,
+        DebugTraceBundleResponse, DebugTraceCallResponse, DebugTraceTransactionResponse,
+        DebugTracingBlockOptions, DebugTracingCallManyOptions, DebugTracingCallOptions,
+        DebugTracingTransactionOptions, ExecutionWitnessOptions, ExecutionWitnessResponse,
+        ExecutionWitnessResult, ModifiedTraceResult, StatelessExecutionResult, TraceCostEstimate,
+        TraceCostTarget, TransactionModification, TrieFlushInterval,
     },
-    Block, Bundle, StateContext, TransactionRequest,
+    trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace},
+    Block, StateContext, TransactionRequest,
 };
 
 /// Debug rpc interface.
@@ -15,33 +20,76 @@ use reth_rpc_types::{
 #[cfg_attr(feature = "client", rpc(server, client, namespace = "debug"))]
 pub trait DebugApi {
     /// Returns an RLP-encoded header.
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally. Returns an
+    /// error if the header is unknown, or if there is no pending block.
     #[method(name = "getRawHeader")]
     async fn raw_header(&self, block_id: BlockId) -> RpcResult<Bytes>;
 
+    /// Returns up to `count` consecutive RLP-encoded canonical headers, starting at `start`.
+    ///
+    /// Fewer headers are returned if the range extends past the chain tip. Returns an error if
+    /// `count` exceeds the configured maximum.
+    #[method(name = "getRawHeaders")]
+    async fn raw_headers(&self, start: BlockNumberOrTag, count: u64) -> RpcResult<Vec<Bytes>>;
+
     /// Returns an RLP-encoded block.
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally.
     #[method(name = "getRawBlock")]
     async fn raw_block(&self, block_id: BlockId) -> RpcResult<Bytes>;
 
     /// Returns a EIP-2718 binary-encoded transaction.
     ///
-    /// If this is a pooled EIP-4844 transaction, the blob sidecar is included.
+    /// If this is a pooled EIP-4844 transaction, the blob sidecar is included, unless
+    /// `exclude_sidecar` is set. Then the canonical encoding is returned, as for mined
+    /// transactions.
     #[method(name = "getRawTransaction")]
-    async fn raw_transaction(&self, hash: B256) -> RpcResult<Option<Bytes>>;
+    async fn raw_transaction(
+        &self,
+        hash: B256,
+        exclude_sidecar: Option<bool>,
+    ) -> RpcResult<Option<Bytes>>;
 
     /// Returns an array of EIP-2718 binary-encoded transactions for the given [`BlockId`].
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally. Returns an
+    /// error if the block is unknown.
     #[method(name = "getRawTransactions")]
     async fn raw_transactions(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>>;
 
     /// Returns an array of EIP-2718 binary-encoded receipts.
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally. Returns an
+    /// error if the block is unknown.
     #[method(name = "getRawReceipts")]
     async fn raw_receipts(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>>;
 
+    /// Returns the EIP-2718 binary-encoded receipt of the transaction with the given hash.
+    ///
+    /// Returns `null` if the transaction is unknown or not yet included in a block.
+    #[method(name = "getRawReceipt")]
+    async fn raw_receipt(&self, hash: B256) -> RpcResult<Option<Bytes>>;
+
+    /// Returns an array of RLP-encoded EIP-4844 blob sidecars of the blob transactions of the
+    /// given block, in transaction order.
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally. Returns an
+    /// error if the block is unknown, or if the sidecar of a transaction is no longer available,
+    /// e.g. because it was removed after its block was finalized.
+    #[method(name = "getBlobSidecars")]
+    async fn raw_blob_sidecars(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>>;
+
     /// Returns an array of recent bad blocks that the client has seen on the network.
     #[method(name = "getBadBlocks")]
     async fn bad_blocks(&self) -> RpcResult<Vec<Block>>;
 
     /// Returns the structured logs created during the execution of EVM between two blocks
     /// (excluding start) as a JSON object.
+    ///
+    /// Each traced block carries the same
+    /// [`BlockTraceContext`](reth_rpc_types::debug::BlockTraceContext) as the block traces with
+    /// `includeBlockContext`.
     #[method(name = "traceChain")]
     async fn debug_trace_chain(
         &self,
@@ -54,46 +102,157 @@ pub trait DebugApi {
     ///
     /// This expects an rlp encoded block
     ///
-    /// Note, the parent of this block must be present, or it will fail. For the second parameter
-    /// see [GethDebugTracingOptions] reference.
-    #[method(name = "traceBlock")]
+    /// Note, the parent of this block and its state must be present, or it will fail. For the
+    /// second parameter see [DebugTracingBlockOptions] reference, which extends the
+    /// [GethDebugTracingOptions] with a filter for the transactions to trace. Requested
+    /// transactions that are not part of the block are reported as an error result after the
+    /// traced transactions.
+    ///
+    /// The optional third parameter selects the block whose state the block is traced on top of,
+    /// instead of the parent referenced by the block header. The block env is still derived from
+    /// the header of the given block.
+    ///
+    /// The results are returned in block order, or keyed by transaction hash if requested via the
+    /// `resultFormat` option.
+    ///
+    /// The node may limit the gas used of the blocks that are traced with each tracer, blocks
+    /// beyond the limit are rejected before they're executed. If the node allows it, the limit can
+    /// be overridden via the `force` option. This also applies to `debug_traceBlockByHash` and
+    /// `debug_traceBlockByNumber`.
+    #[method(name = "traceBlock", with_extensions)]
     async fn debug_trace_block(
         &self,
         rlp_block: Bytes,
-        opts: Option<GethDebugTracingOptions>,
-    ) -> RpcResult<Vec<TraceResult>>;
+        opts: Option<DebugTracingBlockOptions>,
+        parent: Option<BlockId>,
+    ) -> RpcResult<DebugTraceBlockResponse>;
 
     /// Similar to `debug_traceBlock`, `debug_traceBlockByHash` accepts a block hash and will replay
     /// the block that is already present in the database. For the second parameter see
-    /// [GethDebugTracingOptions].
-    #[method(name = "traceBlockByHash")]
+    /// [DebugTracingBlockOptions].
+    ///
+    /// Identical requests that arrive while a block is traced await the result of the first one
+    /// instead of tracing the block again. This also applies to `debug_traceTransaction`.
+    ///
+    /// If `includeBlockContext` is set, the results are returned in an object with the hash,
+    /// number, timestamp, base fee, gas used and transaction count of the block, so that they can
+    /// be post-processed without fetching the block. This also applies to `debug_traceBlock` and
+    /// `debug_traceBlockByNumber`.
+    #[method(name = "traceBlockByHash", with_extensions)]
     async fn debug_trace_block_by_hash(
         &self,
         block: B256,
-        opts: Option<GethDebugTracingOptions>,
-    ) -> RpcResult<Vec<TraceResult>>;
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> RpcResult<DebugTraceBlockResponse>;
 
     /// Similar to `debug_traceBlockByHash`, `debug_traceBlockByNumber` accepts a block number
     /// [BlockNumberOrTag] and will replay the block that is already present in the database.
-    /// For the second parameter see [GethDebugTracingOptions].
-    #[method(name = "traceBlockByNumber")]
+    /// For the second parameter see [DebugTracingBlockOptions].
+    ///
+    /// While the node is syncing, blocks at or beyond the block the state is executed up to are
+    /// rejected with the sync progress of the node, unless `allowWhileSyncing` is set in the
+    /// options. This also applies to `debug_traceBlockByHash`, `debug_traceCall` and
+    /// `debug_traceCallMany`.
+    ///
+    /// Blocks whose traces are too large for a single response can be traced in pages by setting
+    /// `maxTransactions` or `maxResponseBytes`. Each page is returned with a `nextCursor` unless
+    /// it's the last one, and the next page is traced by passing it as the `cursor` option. This
+    /// also applies to `debug_traceBlock` and `debug_traceBlockByHash`.
+    #[method(name = "traceBlockByNumber", with_extensions)]
     async fn debug_trace_block_by_number(
         &self,
         block: BlockNumberOrTag,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> RpcResult<DebugTraceBlockResponse>;
+
+    /// The `debug_traceBlockWithModifications` method replays the block on top of the state of its
+    /// parent with the given transactions replaced or dropped, and traces all transactions with
+    /// the given options.
+    ///
+    /// A replacement is executed like `debug_traceCall`, unsigned and from the sender of the
+    /// transaction it replaces. The following transactions are executed on top of the modified
+    /// state, so they may behave differently than in the block. The results are returned in the
+    /// order of the block, the modified and dropped positions are marked as such. Fails if an
+    /// index is out of range or modified more than once, or if a replacement sets another sender.
+    #[method(name = "traceBlockWithModifications", with_extensions)]
+    async fn debug_trace_block_with_modifications(
+        &self,
+        block: BlockId,
+        modifications: Vec<TransactionModification>,
         opts: Option<GethDebugTracingOptions>,
-    ) -> RpcResult<Vec<TraceResult>>;
+    ) -> RpcResult<Vec<ModifiedTraceResult>>;
 
     /// The `debug_traceTransaction` debugging method will attempt to run the transaction in the
     /// exact same manner as it was executed on the network. It will replay any transaction that
     /// may have been executed prior to this one before it will finally attempt to execute the
     /// transaction that corresponds to the given hash.
-    #[method(name = "traceTransaction")]
+    ///
+    /// If no tracer is specified, the struct logger additionally accepts the settings of the
+    /// [`StructLoggerConfig`](reth_rpc_types::debug::StructLoggerConfig) via the `tracerConfig`,
+    /// e.g. an `opcodeFilter` or `recordAccessStatus`.
+    ///
+    /// In addition to the geth tracers, the
+    /// [`FLAMEGRAPH_TRACER`](reth_rpc_types::debug::FLAMEGRAPH_TRACER) can be used to get the
+    /// call frames in the folded stack format.
+    ///
+    /// If `returnStateChanges` is set, the trace is returned along with the state changes of the
+    /// transaction, in the shape of the prestate tracer's diff mode. If `includeStats` is set, it's
+    /// returned along with the counters of the traced execution, like the number of EVM steps and
+    /// database reads.
+    ///
+    /// If enabled on the node, transactions that are only in the pool are traced speculatively on
+    /// top of the latest state. Their response is marked as `pending`, see
+    /// [`PendingTransactionTrace`](reth_rpc_types::debug::PendingTransactionTrace).
+    #[method(name = "traceTransaction", with_extensions)]
     async fn debug_trace_transaction(
         &self,
         tx_hash: B256,
+        opts: Option<DebugTracingTransactionOptions>,
+    ) -> RpcResult<DebugTraceTransactionResponse>;
+
+    /// The `debug_traceTransactionInBlock` method traces the transaction like
+    /// `debug_traceTransaction`, but in the block with the given hash, regardless of whether the
+    /// block is canonical.
+    ///
+    /// This allows tracing transactions of blocks that were reorged out, as long as the block and
+    /// the state of its parent are still available. Fails if the block doesn't include the
+    /// transaction.
+    #[method(name = "traceTransactionInBlock", with_extensions)]
+    async fn debug_trace_transaction_in_block(
+        &self,
+        tx_hash: B256,
+        block_hash: B256,
         opts: Option<GethDebugTracingOptions>,
     ) -> RpcResult<GethTrace>;
 
+    /// The `debug_traceRawTransaction` method decodes the given EIP-2718 encoded signed
+    /// transaction and traces its execution on top of the state of the given block, or the latest
+    /// block, without submitting it.
+    ///
+    /// The transaction is executed with the same checks as regular execution, EIP-4844
+    /// transactions can be provided with or without the blob sidecar.
+    #[method(name = "traceRawTransaction", with_extensions)]
+    async fn debug_trace_raw_transaction(
+        &self,
+        rlp_tx: Bytes,
+        block_id: Option<BlockId>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> RpcResult<GethTrace>;
+
+    /// The `debug_estimateTraceCost` method estimates the cost of tracing the block, or the
+    /// transaction with the given hash, with the given options, without tracing it.
+    ///
+    /// Only the header and the receipts of the block are read, and no tracing permit is taken.
+    /// The number and the size of the struct logs of the trace are estimated from the gas used,
+    /// the actual size of the traces of an identical request is returned if they're cached. If the
+    /// limits of the node would reject the trace, `wouldBeRejected` is set along with the reason.
+    #[method(name = "estimateTraceCost")]
+    async fn debug_estimate_trace_cost(
+        &self,
+        target: TraceCostTarget,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> RpcResult<TraceCostEstimate>;
+
     /// The `debug_traceCall` method lets you run an `eth_call` within the context of the given
     /// block execution using the final state of parent block as the base.
     ///
@@ -103,50 +262,200 @@ pub trait DebugApi {
     /// The trace can be configured similar to `debug_traceTransaction`,
     /// see [GethDebugTracingOptions]. The method returns the same output as
     /// `debug_traceTransaction`.
-    #[method(name = "traceCall")]
+    ///
+    /// If a `txIndex` is set in the options, the call is executed on top of the state after the
+    /// first `txIndex` transactions of the given block, instead of the final state of the block.
+    /// Indexes beyond the number of transactions in the block are rejected.
+    ///
+    /// If `buildPendingFromPool` is set in the options, the call is traced on top of a pending
+    /// block that is built from the best transactions of the pool, and the hashes of the included
+    /// pool transactions are returned alongside the trace.
+    ///
+    /// The basefee and balance checks of the call can be relaxed via `disableBaseFeeCheck` and
+    /// `disableBalanceCheck`.
+    ///
+    /// If `returnStateDiff`, `includeAccessList`, `withProof` or `includeStats` are set in the
+    /// options, the state changes, the access list, the merkle proofs of the accessed accounts or
+    /// the counters of the traced execution are returned alongside the trace.
+    ///
+    /// If `estimateGas` is set in the options and the request doesn't specify a gas limit, the
+    /// call is traced with the estimated gas limit instead of the gas cap, the used gas limit is
+    /// returned alongside the trace.
+    #[method(name = "traceCall", with_extensions)]
     async fn debug_trace_call(
         &self,
         request: TransactionRequest,
         block_id: Option<BlockId>,
-        opts: Option<GethDebugTracingCallOptions>,
-    ) -> RpcResult<GethTrace>;
+        opts: Option<DebugTracingCallOptions>,
+    ) -> RpcResult<DebugTraceCallResponse>;
 
     /// The `debug_traceCallMany` method lets you run an `eth_callMany` within the context of the
     /// given block execution using the final state of parent block as the base followed by n
     /// transactions.
     ///
     /// The first argument is a list of bundles. Each bundle can overwrite the block headers. This
-    /// will affect all transaction in that bundle.
+    /// will affect all transaction in that bundle. The transactions of a bundle are either call
+    /// requests or raw signed transactions, which are executed as is.
     /// BlockNumber and transaction_index are optional. Transaction_index
     /// specifies the number of tx in the block to replay and -1 means all transactions should be
-    /// replayed.
+    /// replayed. Indexes beyond the number of transactions in the block are rejected.
     /// The trace can be configured similar to `debug_traceTransaction`.
     /// State override apply to all bundles.
+    /// The basefee and balance checks of the traced calls can be relaxed via
+    /// `disableBaseFeeCheck` and `disableBalanceCheck`.
     ///
     /// This methods is similar to many `eth_callMany`, hence this returns nested lists of traces.
     /// Where the length of the outer list is the number of bundles and the length of the inner list
     /// (`Vec<GethTrace>`) is the number of transactions in the bundle.
-    #[method(name = "traceCallMany")]
+    ///
+    /// If `returnStateDiff` is set in the options, the net state changes of each bundle are
+    /// returned alongside its traces.
+    ///
+    /// If `independentBundles` is set in the options, each bundle is traced on the state after
+    /// the replayed transactions, without the changes of the preceding bundles.
+    #[method(name = "traceCallMany", with_extensions)]
     async fn debug_trace_call_many(
         &self,
-        bundles: Vec<Bundle>,
+        bundles: Vec<DebugBundle>,
         state_context: Option<StateContext>,
-        opts: Option<GethDebugTracingCallOptions>,
-    ) -> RpcResult<Vec<Vec<GethTrace>>>;
+        opts: Option<DebugTracingCallManyOptions>,
+    ) -> RpcResult<Vec<DebugTraceBundleResponse>>;
 
     /// The `debug_executionWitness` method allows for re-execution of a block with the purpose of
     /// generating an execution witness. The witness comprises of a map of all hashed trie nodes
     /// to their preimages that were required during the execution of the block, including during
     /// state root recomputation.
     ///
-    /// The first argument is the block number or block hash. The second argument is a boolean
-    /// indicating whether to include the preimages of keys in the response.
-    #[method(name = "executionWitness")]
+    /// The first argument is the block number, tag or hash. Blocks that aren't canonical can be
+    /// requested by hash, as long as they're known locally. The second argument is a boolean
+    /// indicating whether to include the preimages of keys in the response. The optional third
+    /// argument holds the `ExecutionWitnessOptions`, e.g. `includeCodes` to include the bytecode
+    /// of all contracts whose code was loaded during the execution of the block, or `verify` to
+    /// verify the post-state root and gas used of the re-execution against the block header.
+    ///
+    /// The `encoding` of the options selects the `WitnessEncoding` of the response. By default,
+    /// the witness is returned as JSON object. Large witnesses can be requested as `raw` or
+    /// `snappy` compressed binary framing of the trie nodes, preimages, codes and headers instead,
+    /// which is returned as a single blob alongside a JSON header with the numbers of entries and
+    /// the uncompressed size, see `EncodedExecutionWitness`.
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally. Since it may
+    /// be rebuilt between requests, the response includes the hash of the witnessed block.
+    #[method(name = "executionWitness", with_extensions)]
     async fn debug_execution_witness(
         &self,
-        block: BlockNumberOrTag,
+        block: BlockId,
+        include_preimages: bool,
+        opts: Option<ExecutionWitnessOptions>,
+    ) -> RpcResult<ExecutionWitnessResponse>;
+
+    /// Generates the execution witness of the given RLP encoded block on top of the state of its
+    /// parent, like `debug_executionWitness`, e.g. for payloads that were built externally.
+    ///
+    /// The arguments following the block are those of `debug_executionWitness`. Raw blocks are
+    /// never cached, so `noCache` has no effect.
+    ///
+    /// The response includes the hash of the witnessed block. The block isn't imported, and
+    /// nothing about it is persisted.
+    #[method(
+        name = "executionWitnessRaw",
+        aliases = ["debug_executionWitnessRawBlock"],
+        with_extensions
+    )]
+    async fn debug_execution_witness_raw(
+        &self,
+        rlp_block: Bytes,
+        include_preimages: bool,
+        opts: Option<ExecutionWitnessOptions>,
+    ) -> RpcResult<ExecutionWitnessResponse>;
+
+    /// Executes the given RLP encoded block using only the given execution witness as its state,
+    /// and compares the resulting state root, receipts root and gas used to the header.
+    ///
+    /// This validates that a witness, e.g. of `debug_executionWitness`, is sufficient for the
+    /// stateless execution of the block. Data that the execution requires but that is missing in
+    /// the witness is reported. The parent header of the block has to be known to the node.
+    #[method(name = "executeWitness", with_extensions)]
+    async fn debug_execute_witness(
+        &self,
+        rlp_block: Bytes,
+        witness: DebugExecutionWitness,
+    ) -> RpcResult<StatelessExecutionResult>;
+
+    /// Generates the execution witnesses of all blocks in the inclusive range from `start` to
+    /// `end`, like `debug_executionWitness`.
+    ///
+    /// The witnesses are returned in block order. A block whose witness can't be generated is
+    /// reported with its error instead, without failing the whole request. The number of blocks
+    /// per request is limited by the node.
+    #[method(name = "executionWitnessRange", with_extensions)]
+    async fn debug_execution_witness_range(
+        &self,
+        start: BlockNumberOrTag,
+        end: BlockNumberOrTag,
         include_preimages: bool,
-    ) -> RpcResult<ExecutionWitness>;
+    ) -> RpcResult<Vec<ExecutionWitnessResult>>;
+
+    /// Re-executes the block, like `debug_executionWitness`, and returns the accounts and storage
+    /// slots accessed by its transactions, system calls and withdrawals.
+    ///
+    /// Each account is listed once, with the slots it only reads separated from the slots the
+    /// block changes, see `AccountAccess`. No trie nodes are collected, so this is much cheaper
+    /// than generating the witness of the block.
+    #[method(name = "getBlockAccessList", with_extensions)]
+    async fn debug_get_block_access_list(&self, block: BlockId) -> RpcResult<BlockAccessList>;
+
+    /// Re-executes the block, including its system calls and withdrawals, on top of the state of
+    /// its parent, and compares the results to the block.
+    ///
+    /// The report compares the state root, receipts root and gas used to the header, identifies
+    /// the first transaction whose receipt differs from the stored receipt, and lists the accounts
+    /// whose re-executed state differs from the stored post-state of the block. Given a hash,
+    /// blocks that aren't canonical can be replayed as well.
+    #[method(name = "replayBlock", with_extensions)]
+    async fn debug_replay_block(&self, block: BlockId) -> RpcResult<BlockReplayReport>;
+
+    /// Creates a subscription of the given kind.
+    ///
+    /// The `newBlockTraces` subscription traces every new canonical block with the given tracing
+    /// options and pushes the traces of its transactions, see `BlockTraces`. The blocks that
+    /// become canonical by a reorg are pushed with the `reorg` marker. If the subscriber doesn't
+    /// keep up, blocks are skipped instead of buffered, and the number of skipped blocks is pushed
+    /// before the next traces.
+    ///
+    /// The subscriptions of a connection are limited, and each subscription counts as an
+    /// in-flight request against the limits of its client until it ends.
+    #[subscription(
+        name = "subscribe" => "subscription",
+        unsubscribe = "unsubscribe",
+        item = reth_rpc_types::debug::DebugSubscriptionResult,
+        with_extensions
+    )]
+    async fn debug_subscribe(
+        &self,
+        kind: DebugSubscriptionKind,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult;
+
+    /// Traces the block like `debug_traceBlockByHash` and `debug_traceBlockByNumber`, and pushes
+    /// the result, see `TraceBlockNotification`.
+    ///
+    /// With `progress` set in the options, the number of traced transactions, the hash of the
+    /// current transaction and the elapsed time are pushed while the block is traced, at most
+    /// every 500ms. Like all subscriptions, this is only served over websocket and IPC.
+    /// Unsubscribing cancels the trace. The trace counts against the subscriptions of the
+    /// connection and the limits of the client like `debug_subscribe`.
+    #[subscription(
+        name = "subscribeTraceBlock" => "traceBlockSubscription",
+        unsubscribe = "unsubscribeTraceBlock",
+        item = reth_rpc_types::debug::TraceBlockNotification,
+        with_extensions
+    )]
+    async fn debug_subscribe_trace_block(
+        &self,
+        block: BlockId,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult;
 
     /// Sets the logging backtrace location. When a backtrace location is set and a log message is
     /// emitted at that location, the stack of the goroutine executing the log statement will
@@ -185,6 +494,17 @@ pub trait DebugApi {
     #[method(name = "chaindbProperty")]
     async fn debug_chaindb_property(&self, property: String) -> RpcResult<()>;
 
+    /// Returns the bytecode with the given code hash, or `null` if it is unknown.
+    ///
+    /// Bytecodes are immutable, so the block id is accepted for parity with geth but not needed
+    /// for the lookup.
+    #[method(name = "codeByHash")]
+    async fn debug_code_by_hash(
+        &self,
+        hash: B256,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<Option<Bytes>>;
+
     /// Turns on CPU profiling for the given duration and writes profile data to disk.
     #[method(name = "cpuProfile")]
     async fn debug_cpu_profile(&self, file: String, seconds: u64) -> RpcResult<()>;
@@ -203,6 +523,13 @@ pub trait DebugApi {
     #[method(name = "dbGet")]
     async fn debug_db_get(&self, key: String) -> RpcResult<()>;
 
+    /// Returns the tables of the database with their number of entries and sizes, and the block
+    /// ranges of the static files of each segment.
+    ///
+    /// Returns an error unless the method is enabled by the operator.
+    #[method(name = "dbTables")]
+    async fn debug_db_tables(&self) -> RpcResult<DbTables>;
+
     /// Retrieves the state that corresponds to the block number and returns a list of accounts
     /// (including storage and code).
     #[method(name = "dumpBlock")]
@@ -308,9 +635,19 @@ pub trait DebugApi {
     /// Configures how often in-memory state tries are persisted to disk. The interval needs to be
     /// in a format parsable by a time.Duration. Note that the interval is not wall-clock time.
     /// Rather it is accumulated block processing time after which the state should be flushed.
+    ///
+    /// The engines of the node persist the state based on block thresholds only, so this is
+    /// rejected unless the node is configured with adjustable persistence settings.
     #[method(name = "setTrieFlushInterval")]
     async fn debug_set_trie_flush_interval(&self, interval: String) -> RpcResult<()>;
 
+    /// Returns the effective persistence settings of the node: the interval configured via
+    /// `debug_setTrieFlushInterval`, or the startup configuration if it was never called, and the
+    /// block thresholds the in-memory state is persisted at. The interval is omitted if the state
+    /// is persisted based on the block thresholds only.
+    #[method(name = "getTrieFlushInterval")]
+    async fn debug_get_trie_flush_interval(&self) -> RpcResult<TrieFlushInterval>;
+
     /// Returns a printed representation of the stacks of all goroutines.
     #[method(name = "stacks")]
     async fn debug_stacks(&self) -> RpcResult<()>;
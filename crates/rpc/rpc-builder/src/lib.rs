@@ -136,12 +136,13 @@
 use std::{
     collections::HashMap,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use error::{ConflictingModules, RpcError, ServerKind};
 use eth::DynEthApiBuilder;
-use http::{header::AUTHORIZATION, HeaderMap};
+use http::{header::AUTHORIZATION, HeaderMap, HeaderName};
 use jsonrpsee::{
     core::RegisterMethodError,
     server::{
@@ -159,7 +160,8 @@ use reth_provider::{
     EvmEnvProvider, FullRpcProvider, StateProviderFactory,
 };
 use reth_rpc::{
-    AdminApi, DebugApi, EngineEthApi, EthBundle, NetApi, OtterscanApi, RPCApi, RethApi, TraceApi,
+    AdminApi, ClientKeyLayer, DebugApi, DebugApiCaches, DebugApiConfig, DebugAuditSink,
+    EngineEthApi, EthBundle, JsonlAuditSink, NetApi, OtterscanApi, RPCApi, RethApi, TraceApi,
     TxPoolApi, Web3Api,
 };
 use reth_rpc_api::servers::*;
@@ -549,6 +551,9 @@ impl Default for RpcModuleBuilder<(), (), (), (), (), ()> {
 pub struct RpcModuleConfig {
     /// `eth` namespace settings
     eth: EthConfig,
+    /// `debug` namespace settings
+    #[serde(default)]
+    debug: DebugApiConfig,
 }
 
 // === impl RpcModuleConfig ===
@@ -561,7 +566,7 @@ impl RpcModuleConfig {
 
     /// Returns a new RPC module config given the eth namespace config
     pub const fn new(eth: EthConfig) -> Self {
-        Self { eth }
+        Self { eth, debug: DebugApiConfig::new() }
     }
 
     /// Get a reference to the eth namespace config
@@ -573,12 +578,23 @@ impl RpcModuleConfig {
     pub fn eth_mut(&mut self) -> &mut EthConfig {
         &mut self.eth
     }
+
+    /// Get a reference to the debug namespace config
+    pub const fn debug(&self) -> &DebugApiConfig {
+        &self.debug
+    }
+
+    /// Get a mutable reference to the debug namespace config
+    pub fn debug_mut(&mut self) -> &mut DebugApiConfig {
+        &mut self.debug
+    }
 }
 
 /// Configures [`RpcModuleConfig`]
 #[derive(Clone, Debug, Default)]
 pub struct RpcModuleConfigBuilder {
     eth: Option<EthConfig>,
+    debug: Option<DebugApiConfig>,
 }
 
 // === impl RpcModuleConfigBuilder ===
@@ -590,10 +606,16 @@ impl RpcModuleConfigBuilder {
         self
     }
 
+    /// Configures a custom debug namespace config
+    pub fn debug(mut self, debug: DebugApiConfig) -> Self {
+        self.debug = Some(debug);
+        self
+    }
+
     /// Consumes the type and creates the [`RpcModuleConfig`]
     pub fn build(self) -> RpcModuleConfig {
-        let Self { eth } = self;
-        RpcModuleConfig { eth: eth.unwrap_or_default() }
+        let Self { eth, debug } = self;
+        RpcModuleConfig { eth: eth.unwrap_or_default(), debug: debug.unwrap_or_default() }
     }
 
     /// Get a reference to the eth namespace config, if any
@@ -610,6 +632,16 @@ impl RpcModuleConfigBuilder {
     pub fn eth_mut_or_default(&mut self) -> &mut EthConfig {
         self.eth.get_or_insert_with(EthConfig::default)
     }
+
+    /// Get a reference to the debug namespace config, if any
+    pub const fn get_debug(&self) -> &Option<DebugApiConfig> {
+        &self.debug
+    }
+
+    /// Get a mutable reference to the debug namespace config, if any
+    pub fn debug_mut(&mut self) -> &mut Option<DebugApiConfig> {
+        &mut self.debug
+    }
 }
 
 /// A Helper type the holds instances of the configured modules.
@@ -624,6 +656,12 @@ pub struct RpcRegistryInner<Provider, Pool, Network, Tasks, Events, EthApi> {
     eth: EthHandlers<Provider, Pool, Network, Events, EthApi>,
     /// to put trace calls behind semaphore
     blocking_pool_guard: BlockingTaskGuard,
+    /// `debug` namespace settings
+    debug_config: DebugApiConfig,
+    /// The caches shared by all `debug` namespace handlers
+    debug_caches: DebugApiCaches,
+    /// The audit log shared by all `debug` namespace handlers, if enabled
+    debug_audit_sink: Option<Arc<dyn DebugAuditSink>>,
     /// Contains the [Methods] of a module
     modules: HashMap<RethRpcModule, Methods>,
 }
@@ -641,6 +679,11 @@ where
     EthApi: 'static,
 {
     /// Creates a new, empty instance.
+    ///
+    /// # Panics
+    ///
+    /// If the `debug` namespace config is invalid for the tracing permits of the `eth` config, or
+    /// if its audit log can't be opened.
     #[allow(clippy::too_many_arguments)]
     pub fn new<EvmConfig>(
         provider: Provider,
@@ -677,6 +720,18 @@ where
         )
         .build();
 
+        if let Err(err) = config.debug.validate(config.eth.max_tracing_requests) {
+            panic!("invalid debug config: {err}")
+        }
+        let debug_caches = DebugApiCaches::new(&config.debug);
+        debug_caches.spawn_reorg_task(&executor, &events);
+        let debug_audit_sink = config.debug.audit_log.as_ref().map(|audit_log| {
+            let sink = JsonlAuditSink::open(audit_log).unwrap_or_else(|err| {
+                panic!("failed to open debug audit log {}: {err}", audit_log.path.display())
+            });
+            Arc::new(sink) as Arc<dyn DebugAuditSink>
+        });
+
         Self {
             provider,
             pool,
@@ -685,6 +740,9 @@ where
             executor,
             modules: Default::default(),
             blocking_pool_guard,
+            debug_config: config.debug,
+            debug_caches,
+            debug_audit_sink,
             events,
         }
     }
@@ -840,6 +898,7 @@ where
     pub fn register_debug(&mut self) -> &mut Self
     where
         EthApi: EthApiSpec + EthTransactions + TraceExt,
+        Events: CanonStateSubscriptions + Clone + 'static,
     {
         let debug_api = self.debug_api();
         self.modules.insert(RethRpcModule::Debug, debug_api.into_rpc().into());
@@ -938,7 +997,9 @@ where
         OtterscanApi::new(eth_api)
     }
 
-    /// Instantiates `DebugApi`
+    /// Instantiates `DebugApi` with the configured settings and the caches of the registry, whose
+    /// reorg tasks are spawned once when the registry is created. Its subscriptions are served
+    /// from the canonical state notifications of the registry, on tasks spawned by its executor.
     ///
     /// # Panics
     ///
@@ -946,9 +1007,31 @@ where
     pub fn debug_api(&self) -> DebugApi<Provider, EthApi>
     where
         EthApi: EthApiSpec + EthTransactions + TraceExt,
+        Events: CanonStateSubscriptions + Clone + 'static,
     {
-        let eth_api = self.eth_api().clone();
-        DebugApi::new(self.provider.clone(), eth_api, self.blocking_pool_guard.clone())
+        self.new_debug_api(self.eth_api().clone())
+    }
+
+    /// Instantiates `DebugApi` on top of the given `eth` API, with the configured settings and the
+    /// caches and audit log of the registry.
+    fn new_debug_api(&self, eth_api: EthApi) -> DebugApi<Provider, EthApi>
+    where
+        Events: CanonStateSubscriptions + Clone + 'static,
+    {
+        let debug_api = DebugApi::with_settings(
+            self.provider.clone(),
+            eth_api,
+            self.blocking_pool_guard.clone(),
+            self.debug_config.clone(),
+        )
+        .expect("the debug config is validated when the registry is created")
+        .with_caches(self.debug_caches.clone())
+        .with_canonical_state_events(self.events.clone())
+        .with_subscription_task_spawner(Box::new(self.executor.clone()));
+        match &self.debug_audit_sink {
+            Some(sink) => debug_api.with_audit_sink(sink.clone()),
+            None => debug_api,
+        }
     }
 
     /// Instantiates `NetApi`
@@ -1069,13 +1152,9 @@ where
                                 .into_rpc()
                                 .into()
                         }
-                        RethRpcModule::Debug => DebugApi::new(
-                            self.provider.clone(),
-                            eth_api.clone(),
-                            self.blocking_pool_guard.clone(),
-                        )
-                        .into_rpc()
-                        .into(),
+                        RethRpcModule::Debug => {
+                            self.new_debug_api(eth_api.clone()).into_rpc().into()
+                        }
                         RethRpcModule::Eth => {
                             // merge all eth handlers
                             let mut module = eth_api.clone().into_rpc();
@@ -1159,6 +1238,8 @@ pub struct RpcServerConfig<RpcMiddleware = Identity> {
     ipc_endpoint: Option<String>,
     /// JWT secret for authentication
     jwt_secret: Option<JwtSecret>,
+    /// The header the API keys of http and ws clients are read from
+    client_key_header: Option<HeaderName>,
     /// Configurable RPC middleware
     rpc_middleware: RpcServiceBuilder<RpcMiddleware>,
 }
@@ -1178,6 +1259,7 @@ impl Default for RpcServerConfig<Identity> {
             ipc_server_config: None,
             ipc_endpoint: None,
             jwt_secret: None,
+            client_key_header: None,
             rpc_middleware: RpcServiceBuilder::new(),
         }
     }
@@ -1241,6 +1323,7 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             ipc_server_config: self.ipc_server_config,
             ipc_endpoint: self.ipc_endpoint,
             jwt_secret: self.jwt_secret,
+            client_key_header: self.client_key_header,
             rpc_middleware,
         }
     }
@@ -1314,6 +1397,17 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         self
     }
 
+    /// Configures the header the API keys of http and ws clients are read from.
+    ///
+    /// The keys are passed on to the methods, so that the `debug` API can limit the requests of
+    /// each key, see [`ClientLimitsConfig`](reth_rpc::ClientLimitsConfig). If unset, the
+    /// [`api_key_header`](reth_rpc::ClientLimitsConfig::api_key_header) of the `debug` config of
+    /// the started modules is used.
+    pub fn with_client_key_header(mut self, header: Option<HeaderName>) -> Self {
+        self.client_key_header = header;
+        self
+    }
+
     /// Returns true if any server is configured.
     ///
     /// If no server is configured, no server will be launched on [`RpcServerConfig::start`].
@@ -1348,6 +1442,28 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         jwt_secret.map(|secret| AuthLayer::new(JwtAuthValidator::new(secret)))
     }
 
+    /// Returns the header the API keys of clients are read from, the configured one or the one
+    /// of the `debug` config of the modules.
+    fn client_key_header(
+        &self,
+        modules: &TransportRpcModules,
+    ) -> Result<Option<HeaderName>, RpcError> {
+        if let Some(header) = &self.client_key_header {
+            return Ok(Some(header.clone()))
+        }
+        let header = modules
+            .config
+            .config()
+            .and_then(|config| config.debug().client_limits.api_key_header.as_deref());
+        header
+            .map(|header| {
+                HeaderName::try_from(header).map_err(|err| {
+                    RpcError::Custom(format!("invalid client key header {header}: {err}"))
+                })
+            })
+            .transpose()
+    }
+
     /// Builds and starts the configured server(s): http, ws, ipc.
     ///
     /// If both http and ws are on the same port, they are combined into one server.
@@ -1373,6 +1489,8 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             constants::DEFAULT_WS_RPC_PORT,
         )));
 
+        let client_key_layer = self.client_key_header(modules)?.map(ClientKeyLayer::new);
+
         let metrics = modules.ipc.as_ref().map(RpcRequestMetrics::ipc).unwrap_or_default();
         let ipc_path =
             self.ipc_endpoint.clone().unwrap_or_else(|| constants::DEFAULT_IPC_ENDPOINT.into());
@@ -1412,7 +1530,8 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                     .set_http_middleware(
                         tower::ServiceBuilder::new()
                             .option_layer(Self::maybe_cors_layer(cors)?)
-                            .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                            .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                            .option_layer(client_key_layer.clone()),
                     )
                     .set_rpc_middleware(
                         self.rpc_middleware.clone().layer(
@@ -1460,7 +1579,8 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                 .set_http_middleware(
                     tower::ServiceBuilder::new()
                         .option_layer(Self::maybe_cors_layer(self.ws_cors_domains.clone())?)
-                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                        .option_layer(client_key_layer.clone()),
                 )
                 .set_rpc_middleware(
                     self.rpc_middleware
@@ -1485,7 +1605,8 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                 .set_http_middleware(
                     tower::ServiceBuilder::new()
                         .option_layer(Self::maybe_cors_layer(self.http_cors_domains.clone())?)
-                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                        .option_layer(client_key_layer.clone()),
                 )
                 .set_rpc_middleware(
                     self.rpc_middleware.clone().layer(
@@ -3,6 +3,7 @@ use reth_metrics::{
     metrics::{Counter, Histogram},
     Metrics,
 };
+use reth_rpc::{DebugRpcFuture, DebugRpcLayer, DebugRpcService};
 use std::{
     collections::HashMap,
     future::Future,
@@ -20,9 +21,15 @@ use tower::Layer;
 ///   for WS and IPC)
 /// - Request metrics: metrics for each RPC method (e.g. number of calls started, time taken to
 ///   process a call)
+///
+/// The requests of transports that serve the `debug` API are also passed through the
+/// [`DebugRpcLayer`], so that the layer wraps the server's service without changing the type
+/// the configurable RPC middleware wraps.
 #[derive(Default, Debug, Clone)]
 pub(crate) struct RpcRequestMetrics {
     inner: Arc<RpcServerMetricsInner>,
+    /// Whether the module of the transport has methods of the `debug` API.
+    debug: bool,
 }
 
 impl RpcRequestMetrics {
@@ -34,6 +41,7 @@ impl RpcRequestMetrics {
                     (method, RpcServerCallMetrics::new_with_labels(&[("method", method)]))
                 })),
             }),
+            debug: module.method_names().any(|method| method.starts_with("debug_")),
         }
     }
 
@@ -65,7 +73,9 @@ impl<S> Layer<S> for RpcRequestMetrics {
     type Service = RpcRequestMetricsService<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        RpcRequestMetricsService::new(inner, self.clone())
+        let debug_layer =
+            if self.debug { DebugRpcLayer::default() } else { DebugRpcLayer::disabled() };
+        RpcRequestMetricsService::new(debug_layer.layer(inner), self.clone())
     }
 }
 
@@ -85,12 +95,13 @@ struct RpcServerMetricsInner {
 pub struct RpcRequestMetricsService<S> {
     /// The metrics collector for RPC requests
     metrics: RpcRequestMetrics,
-    /// The inner service being wrapped
-    inner: S,
+    /// The inner service being wrapped, with the [`DebugRpcLayer`] if the transport serves the
+    /// `debug` API
+    inner: DebugRpcService<S>,
 }
 
 impl<S> RpcRequestMetricsService<S> {
-    pub(crate) fn new(service: S, metrics: RpcRequestMetrics) -> Self {
+    pub(crate) fn new(service: DebugRpcService<S>, metrics: RpcRequestMetrics) -> Self {
         // this instance is kept alive for the duration of the connection
         metrics.inner.connection_metrics.connections_opened_total.increment(1);
         Self { inner: service, metrics }
@@ -101,7 +112,7 @@ impl<'a, S> RpcServiceT<'a> for RpcRequestMetricsService<S>
 where
     S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
 {
-    type Future = MeteredRequestFuture<S::Future>;
+    type Future = MeteredRequestFuture<DebugRpcFuture<S::Future>>;
 
     fn call(&self, req: Request<'a>) -> Self::Future {
         self.metrics.inner.connection_metrics.requests_started_total.increment(1);
@@ -1,24 +1,27 @@
 #![allow(unreachable_pub)]
 //! Standalone http tests
 
-use crate::utils::{launch_http, launch_http_ws, launch_ws};
+use crate::utils::{launch_http, launch_http_ws, launch_ws, test_address, test_rpc_builder};
 use alloy_primitives::{hex_literal::hex, Address, Bytes, TxHash, B256, B64, U256, U64};
 use jsonrpsee::{
     core::{
         client::{ClientT, SubscriptionClientT},
         params::ArrayParams,
     },
-    http_client::HttpClient,
+    http_client::{HeaderMap, HttpClient, HttpClientBuilder},
     rpc_params,
     types::error::ErrorCode,
 };
 use reth_network_peers::NodeRecord;
 use reth_primitives::{BlockId, BlockNumberOrTag, Receipt};
+use reth_rpc::{ClientLimitsConfig, DebugApiConfig, EthApi};
 use reth_rpc_api::{
     clients::{AdminApiClient, EthApiClient},
     DebugApiClient, EthFilterApiClient, NetApiClient, OtterscanClient, TraceApiClient,
     Web3ApiClient,
 };
+use reth_rpc_builder::{RpcModuleConfig, RpcServerConfig, TransportRpcModuleConfig};
+use reth_rpc_eth_types::RATE_LIMIT_EXCEEDED_CODE;
 use reth_rpc_server_types::RethRpcModule;
 use reth_rpc_types::{
     trace::filter::TraceFilter, Block, FeeHistory, Filter, Index, Log,
@@ -330,10 +333,10 @@ where
 {
     let block_id = BlockId::Number(BlockNumberOrTag::default());
 
-    DebugApiClient::raw_header(client, block_id).await.unwrap();
+    DebugApiClient::raw_header(client, block_id).await.unwrap_err();
     DebugApiClient::raw_block(client, block_id).await.unwrap_err();
-    DebugApiClient::raw_transaction(client, B256::default()).await.unwrap();
-    DebugApiClient::raw_receipts(client, block_id).await.unwrap();
+    DebugApiClient::raw_transaction(client, B256::default(), None).await.unwrap();
+    DebugApiClient::raw_receipts(client, block_id).await.unwrap_err();
     assert!(is_unimplemented(DebugApiClient::bad_blocks(client).await.err().unwrap()));
 }
 
@@ -529,6 +532,55 @@ async fn test_call_debug_functions_http_and_ws() {
     test_basic_debug_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_client_limits_by_api_key_http() {
+    reth_tracing::init_test_tracing();
+
+    let debug = DebugApiConfig::default().client_limits(ClientLimitsConfig {
+        max_requests_per_minute: Some(1),
+        api_key_header: Some("x-api-key".to_string()),
+        api_keys: ["client".to_string()].into(),
+        ..Default::default()
+    });
+    let config = RpcModuleConfig::builder().debug(debug).build();
+    let modules = test_rpc_builder().build(
+        TransportRpcModuleConfig::set_http(vec![RethRpcModule::Debug]).with_config(config),
+        Box::new(EthApi::with_spawner),
+    );
+    let handle = RpcServerConfig::http(Default::default())
+        .with_http_address(test_address())
+        .start(&modules)
+        .await
+        .unwrap();
+    let url = handle.http_url().unwrap();
+    let client = |key: Option<&str>| {
+        let headers = key.map(|key| [("x-api-key".parse().unwrap(), key.parse().unwrap())]);
+        HttpClientBuilder::default()
+            .set_headers(headers.into_iter().flatten().collect::<HeaderMap>())
+            .build(&url)
+            .unwrap()
+    };
+    let is_rate_limited = |err: jsonrpsee::core::client::Error| match err {
+        jsonrpsee::core::client::Error::Call(err) => err.code() == RATE_LIMIT_EXCEEDED_CODE,
+        _ => false,
+    };
+
+    // the transaction is unknown, but the request counts against the limit of the key
+    let err = DebugApiClient::debug_trace_transaction(&client(Some("client")), B256::ZERO, None)
+        .await
+        .unwrap_err();
+    assert!(!is_rate_limited(err));
+    // the limit of the key applies across connections
+    let err = DebugApiClient::debug_trace_transaction(&client(Some("client")), B256::ZERO, None)
+        .await
+        .unwrap_err();
+    assert!(is_rate_limited(err));
+    // clients without a known key are limited by their connection
+    let err =
+        DebugApiClient::debug_trace_transaction(&client(None), B256::ZERO, None).await.unwrap_err();
+    assert!(!is_rate_limited(err));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_net_functions_http() {
     reth_tracing::init_test_tracing();
@@ -4,9 +4,10 @@
 use futures::Future;
 use reth_rpc_eth_types::EthApiError;
 use reth_tasks::{
-    pool::{BlockingTaskGuard, BlockingTaskPool},
+    pool::{BlockingTaskGuard, BlockingTaskPool, TokioBlockingTaskError},
     TaskSpawner,
 };
+use std::any::Any;
 use tokio::sync::{oneshot, AcquireError, OwnedSemaphorePermit};
 
 use crate::EthApiTypes;
@@ -65,6 +66,8 @@ pub trait SpawnBlocking: EthApiTypes + Clone + Send + Sync + 'static {
     /// Note: This is expected for futures that are predominantly CPU bound, as it uses `rayon`
     /// under the hood, for blocking IO futures use [`spawn_blocking`](Self::spawn_blocking_io). See
     /// <https://ryhl.io/blog/async-what-is-blocking/>.
+    ///
+    /// If the task panics, this fails with [`EthApiError::BlockingTaskPanicked`].
     fn spawn_tracing<F, R>(&self, f: F) -> impl Future<Output = Result<R, Self::Error>> + Send
     where
         F: FnOnce(Self) -> Result<R, Self::Error> + Send + 'static,
@@ -72,6 +75,23 @@ pub trait SpawnBlocking: EthApiTypes + Clone + Send + Sync + 'static {
     {
         let this = self.clone();
         let fut = self.tracing_task_pool().spawn(move || f(this));
-        async move { fut.await.map_err(|_| EthApiError::InternalBlockingTaskError)? }
+        async move { fut.await.map_err(blocking_task_error)? }
+    }
+}
+
+/// Converts the error of a task of a [`BlockingTaskPool`], which is the payload of the panic of
+/// the task, into an [`EthApiError`].
+pub fn blocking_task_error(err: Box<dyn Any + Send>) -> EthApiError {
+    if err.is::<TokioBlockingTaskError>() {
+        // the task was dropped without completing
+        return EthApiError::InternalBlockingTaskError
     }
+    let msg = match err.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(err) => match err.downcast::<&str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    };
+    EthApiError::BlockingTaskPanicked(msg)
 }
@@ -7,6 +7,7 @@ use futures::Future;
 use reth_chainspec::MIN_TRANSACTION_GAS;
 use reth_evm::{ConfigureEvm, ConfigureEvmEnv};
 use reth_primitives::{
+    constants::eip4844::MAX_BLOBS_PER_BLOCK,
     revm_primitives::{
         BlockEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg, ExecutionResult, HaltReason,
         ResultAndState, TransactTo, TxEnv,
@@ -20,8 +21,9 @@ use reth_rpc_eth_types::{
     cache::db::{StateCacheDbRefMutWrapper, StateProviderTraitObjWrapper},
     error::ensure_success,
     revm_utils::{
-        apply_block_overrides, apply_state_overrides, caller_gas_allowance,
-        cap_tx_gas_limit_with_caller_allowance, get_precompiles, CallFees,
+        apply_block_hash_overrides, apply_block_overrides, apply_state_overrides,
+        caller_gas_allowance, cap_tx_gas_limit_with_caller_allowance, get_precompiles,
+        validate_authorization_list, CallFees,
     },
     EthApiError, RevertError, RpcInvalidTransactionError, StateCacheDb,
 };
@@ -539,12 +541,37 @@ pub trait Call: LoadState + SpawnBlocking {
     ///  - `nonce` is set to `None`
     fn estimate_gas_with<S>(
         &self,
-        mut cfg: CfgEnvWithHandlerCfg,
+        cfg: CfgEnvWithHandlerCfg,
         block: BlockEnv,
-        mut request: TransactionRequest,
+        request: TransactionRequest,
         state: S,
         state_override: Option<StateOverride>,
     ) -> Result<U256, Self::Error>
+    where
+        S: StateProvider,
+    {
+        let mut db = CacheDB::new(StateProviderDatabase::new(state));
+
+        // Apply any state overrides if specified.
+        if let Some(state_override) = state_override {
+            apply_state_overrides(state_override, &mut db).map_err(Self::Error::from_eth_err)?;
+        }
+
+        self.estimate_gas_with_db(cfg, block, request, &mut db)
+    }
+
+    /// Estimates the gas usage of the `request` on top of the given database.
+    ///
+    /// This is the same as [`Call::estimate_gas_with`], but allows reusing a database that was
+    /// already prepared, e.g. with state overrides or previously executed transactions. The
+    /// database is not modified.
+    fn estimate_gas_with_db<S>(
+        &self,
+        mut cfg: CfgEnvWithHandlerCfg,
+        block: BlockEnv,
+        mut request: TransactionRequest,
+        db: &mut CacheDB<StateProviderDatabase<S>>,
+    ) -> Result<U256, Self::Error>
     where
         S: StateProvider,
     {
@@ -573,12 +600,6 @@ pub trait Call: LoadState + SpawnBlocking {
 
         // Configure the evm env
         let mut env = self.build_call_evm_env(cfg, block, request)?;
-        let mut db = CacheDB::new(StateProviderDatabase::new(state));
-
-        // Apply any state overrides if specified.
-        if let Some(state_override) = state_override {
-            apply_state_overrides(state_override, &mut db).map_err(Self::Error::from_eth_err)?;
-        }
 
         // Optimize for simple transfer transactions, potentially reducing the gas estimate.
         if env.tx.data.is_empty() {
@@ -593,7 +614,7 @@ pub trait Call: LoadState + SpawnBlocking {
                         // with the minimum gas limit to make sure.
                         let mut env = env.clone();
                         env.tx.gas_limit = MIN_TRANSACTION_GAS;
-                        if let Ok((res, _)) = self.transact(&mut db, env) {
+                        if let Ok((res, _)) = self.transact(&mut *db, env) {
                             if res.result.is_success() {
                                 return Ok(U256::from(MIN_TRANSACTION_GAS))
                             }
@@ -606,10 +627,10 @@ pub trait Call: LoadState + SpawnBlocking {
         // Check funds of the sender (only useful to check if transaction gas price is more than 0).
         //
         // The caller allowance is check by doing `(account.balance - tx.value) / tx.gas_price`
-        if env.tx.gas_price > U256::ZERO {
+        if env.tx.gas_price > U256::ZERO && !env.cfg.is_balance_check_disabled() {
             // cap the highest gas limit by max gas caller can afford with given gas price
             highest_gas_limit = highest_gas_limit
-                .min(caller_gas_allowance(&mut db, &env.tx).map_err(Self::Error::from_eth_err)?);
+                .min(caller_gas_allowance(&mut *db, &env.tx).map_err(Self::Error::from_eth_err)?);
         }
 
         // We can now normalize the highest gas limit to a u64
@@ -622,7 +643,7 @@ pub trait Call: LoadState + SpawnBlocking {
         trace!(target: "rpc::eth::estimate", ?env, "Starting gas estimation");
 
         // Execute the transaction with the highest possible gas limit.
-        let (mut res, mut env) = match self.transact(&mut db, env.clone()) {
+        let (mut res, mut env) = match self.transact(&mut *db, env.clone()) {
             // Handle the exceptional case where the transaction initialization uses too much gas.
             // If the gas price or gas limit was specified in the request, retry the transaction
             // with the block's gas limit to determine if the failure was due to
@@ -631,7 +652,7 @@ pub trait Call: LoadState + SpawnBlocking {
                 if err.is_gas_too_high() &&
                     (tx_request_gas_limit.is_some() || tx_request_gas_price.is_some()) =>
             {
-                return Err(self.map_out_of_gas_err(block_env_gas_limit, env, &mut db))
+                return Err(self.map_out_of_gas_err(block_env_gas_limit, env, db))
             }
             // Propagate other results (successful or other errors).
             ethres => ethres?,
@@ -648,7 +669,7 @@ pub trait Call: LoadState + SpawnBlocking {
                 // if price or limit was included in the request then we can execute the request
                 // again with the block's gas limit to check if revert is gas related or not
                 return if tx_request_gas_limit.is_some() || tx_request_gas_price.is_some() {
-                    Err(self.map_out_of_gas_err(block_env_gas_limit, env, &mut db))
+                    Err(self.map_out_of_gas_err(block_env_gas_limit, env, db))
                 } else {
                     // the transaction did revert
                     Err(RpcInvalidTransactionError::Revert(RevertError::new(output)).into_eth_err())
@@ -681,7 +702,7 @@ pub trait Call: LoadState + SpawnBlocking {
             env.tx.gas_limit = optimistic_gas_limit;
             // Re-execute the transaction with the new gas limit and update the result and
             // environment.
-            (res, env) = self.transact(&mut db, env)?;
+            (res, env) = self.transact(&mut *db, env)?;
             // Update the gas used based on the new result.
             gas_used = res.result.gas_used();
             // Update the gas limit estimates (highest and lowest) based on the execution result.
@@ -716,7 +737,7 @@ pub trait Call: LoadState + SpawnBlocking {
             env.tx.gas_limit = mid_gas_limit;
 
             // Execute transaction and handle potential gas errors, adjusting limits accordingly.
-            match self.transact(&mut db, env.clone()) {
+            match self.transact(&mut *db, env.clone()) {
                 Err(err) if err.is_gas_too_high() => {
                     // Increase the lowest gas limit if gas is too high
                     lowest_gas_limit = mid_gas_limit;
@@ -832,11 +853,32 @@ pub trait Call: LoadState + SpawnBlocking {
     fn create_txn_env(
         &self,
         block_env: &BlockEnv,
-        request: TransactionRequest,
+        mut request: TransactionRequest,
     ) -> Result<TxEnv, Self::Error> {
-        // Ensure that if versioned hashes are set, they're not empty
-        if request.blob_versioned_hashes.as_ref().map_or(false, |hashes| hashes.is_empty()) {
-            return Err(RpcInvalidTransactionError::BlobTransactionMissingBlobHashes.into_eth_err())
+        // If the request carries a sidecar but no versioned hashes, derive them from the sidecar's
+        // commitments so that `BLOBHASH` and blob gas accounting behave like a real blob
+        // transaction
+        if request.blob_versioned_hashes.is_none() {
+            if let Some(sidecar) = request.sidecar.as_ref() {
+                request.blob_versioned_hashes = Some(sidecar.versioned_hashes().collect());
+            }
+        }
+
+        if let Some(hashes) = request.blob_versioned_hashes.as_ref() {
+            // Ensure that if versioned hashes are set, they're not empty
+            if hashes.is_empty() {
+                return Err(
+                    RpcInvalidTransactionError::BlobTransactionMissingBlobHashes.into_eth_err()
+                )
+            }
+            // Ensure the number of blobs doesn't exceed the per block limit
+            if hashes.len() > MAX_BLOBS_PER_BLOCK {
+                return Err(RpcInvalidTransactionError::TooManyBlobs {
+                    max: MAX_BLOBS_PER_BLOCK,
+                    have: hashes.len(),
+                }
+                .into_eth_err())
+            }
         }
 
         let TransactionRequest {
@@ -909,6 +951,10 @@ pub trait Call: LoadState + SpawnBlocking {
         block: BlockEnv,
         request: TransactionRequest,
     ) -> Result<EnvWithHandlerCfg, Self::Error> {
+        if let Some(authorization_list) = request.authorization_list.as_deref() {
+            validate_authorization_list(authorization_list, cfg.chain_id)
+                .map_err(Self::Error::from_eth_err)?;
+        }
         let tx = self.create_txn_env(&block, request)?;
         Ok(EnvWithHandlerCfg::new_with_cfg_env(cfg, block, tx))
     }
@@ -960,9 +1006,9 @@ pub trait Call: LoadState + SpawnBlocking {
         // create the evm env via `build_call_evm_env`, e.g. basefee
         if let Some(mut block_overrides) = overrides.block {
             if let Some(block_hashes) = block_overrides.block_hash.take() {
-                // override block hashes
-                db.block_hashes
-                    .extend(block_hashes.into_iter().map(|(num, hash)| (U256::from(num), hash)))
+                // override block hashes, for both the `BLOCKHASH` opcode and the EIP-2935 history
+                // contract
+                apply_block_hash_overrides(block_hashes, cfg.handler_cfg.spec_id, db)?;
             }
             apply_block_overrides(*block_overrides, &mut block);
         }
@@ -977,14 +1023,15 @@ pub trait Call: LoadState + SpawnBlocking {
 
         if request_gas.is_none() {
             // No gas limit was provided in the request, so we need to cap the transaction gas limit
-            if env.tx.gas_price > U256::ZERO {
+            if env.tx.gas_price > U256::ZERO && !env.cfg.is_balance_check_disabled() {
                 // If gas price is specified, cap transaction gas limit with caller allowance
                 trace!(target: "rpc::eth::call", ?env, "Applying gas limit cap with caller allowance");
                 cap_tx_gas_limit_with_caller_allowance(db, &mut env.tx)?;
             } else {
-                // If no gas price is specified, use maximum allowed gas limit. The reason for this
-                // is that both Erigon and Geth use pre-configured gas cap even if
-                // it's possible to derive the gas limit from the block:
+                // If no gas price is specified, or the balance check is disabled, use maximum
+                // allowed gas limit. The reason for this is that both Erigon and Geth use
+                // pre-configured gas cap even if it's possible to derive the gas limit from the
+                // block:
                 // <https://github.com/ledgerwatch/erigon/blob/eae2d9a79cb70dbe30b3a6b79c436872e4605458/cmd/rpcdaemon/commands/trace_adhoc.go#L956
                 // https://github.com/ledgerwatch/erigon/blob/eae2d9a79cb70dbe30b3a6b79c436872e4605458/eth/ethconfig/config.go#L94>
                 trace!(target: "rpc::eth::call", ?env, "Applying gas limit cap as the maximum gas limit");
@@ -2,15 +2,15 @@
 
 use std::time::Duration;
 
-use alloy_primitives::{Address, Bytes, U256};
+use alloy_primitives::{Address, BlockNumber, Bytes, B256, U256};
 use alloy_sol_types::decode_revert_reason;
 use reth_errors::RethError;
-use reth_primitives::{revm_primitives::InvalidHeader, BlockId};
+use reth_primitives::{revm_primitives::InvalidHeader, BlockId, BlockNumberOrTag};
 use reth_rpc_server_types::result::{
     block_id_to_str, internal_rpc_err, invalid_params_rpc_err, rpc_err, rpc_error_with_code,
 };
 use reth_rpc_types::{
-    error::EthRpcErrorCode, request::TransactionInputError, BlockError, ToRpcError,
+    error::EthRpcErrorCode, request::TransactionInputError, BlockError, Stage, ToRpcError,
 };
 use reth_transaction_pool::error::{
     Eip4844PoolTransactionError, Eip7702PoolTransactionError, InvalidPoolTransactionError,
@@ -20,6 +20,62 @@ use revm::primitives::{EVMError, ExecutionResult, HaltReason, InvalidTransaction
 use revm_inspectors::tracing::MuxError;
 use tracing::error;
 
+/// The error code of [`EthApiError::HistoricalStateUnavailable`], the code of pruned history
+/// unavailability proposed alongside EIP-4444.
+pub const HISTORICAL_STATE_UNAVAILABLE_CODE: i32 = 4444;
+
+/// The data of the RPC error of [`EthApiError::HistoricalStateUnavailable`].
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoricalStateUnavailableData {
+    block: BlockNumber,
+    earliest_available_block: BlockNumber,
+}
+
+/// The error code of [`EthApiError::NodeSyncing`], the resource unavailable code of EIP-1474.
+pub const NODE_SYNCING_CODE: i32 = -32002;
+
+/// The data of the RPC error of [`EthApiError::NodeSyncing`].
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeSyncingData {
+    executed_block: BlockNumber,
+    stages: Vec<Stage>,
+}
+
+/// The error code of [`EthApiError::RateLimitExceeded`], the limit exceeded code of EIP-1474.
+pub const RATE_LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// The data of the RPC error of [`EthApiError::RateLimitExceeded`].
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitExceededData {
+    limit: RequestLimit,
+    retry_after_ms: u64,
+}
+
+/// A limit of the requests of a client, see [`EthApiError::RateLimitExceeded`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RequestLimit {
+    /// The maximum number of concurrent requests.
+    Concurrent(u32),
+    /// The maximum number of requests per minute.
+    PerMinute(u32),
+    /// The maximum number of subscriptions of a connection.
+    Subscriptions(u32),
+}
+
+impl std::fmt::Display for RequestLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Concurrent(max) => write!(f, "{max} concurrent requests"),
+            Self::PerMinute(max) => write!(f, "{max} requests per minute"),
+            Self::Subscriptions(max) => write!(f, "{max} subscriptions per connection"),
+        }
+    }
+}
+
 /// Result alias
 pub type EthResult<T> = Result<T, EthApiError>;
 
@@ -41,6 +97,45 @@ pub enum EthApiError {
     /// Header not found for block hash/number/tag
     #[error("header not found")]
     HeaderNotFound(BlockId),
+    /// The block of a tag is not known yet, e.g. `safe` or `finalized` before the node received
+    /// forkchoice state
+    #[error("{0} block not available")]
+    BlockTagNotAvailable(BlockNumberOrTag),
+    /// The parent of a block to execute on top of is not known
+    #[error("parent block {0} not found")]
+    ParentBlockNotFound(B256),
+    /// The state of the parent of a block to execute on top of is not available, e.g. because it
+    /// was pruned
+    #[error("state of parent block {0} unavailable")]
+    ParentStateUnavailable(B256),
+    /// The state of a historical block is not available because the history of the state was
+    /// pruned
+    #[error(
+        "historical state for block {block} is unavailable; earliest available state is block \
+         {earliest}"
+    )]
+    HistoricalStateUnavailable {
+        /// The number of the block whose state was requested.
+        block: BlockNumber,
+        /// The number of the earliest block whose state is available.
+        earliest: BlockNumber,
+    },
+    /// The node is syncing and the requested block is at or beyond the block its state is
+    /// executed up to, so the block may not be part of the canonical chain
+    #[error("node is syncing, state is executed up to block {executed}")]
+    NodeSyncing {
+        /// The number of the block the state of the node is executed up to.
+        executed: BlockNumber,
+        /// The progress of the sync stages.
+        stages: Vec<Stage>,
+    },
+    /// The transaction is not included in the block it was expected in
+    #[error("transaction {0} not found in block {1}")]
+    TransactionNotInBlock(B256, B256),
+    /// The blob sidecar of a transaction is not available, e.g. because it was removed from the
+    /// blob store after its block was finalized
+    #[error("blob sidecar of transaction {0} unavailable")]
+    BlobSidecarUnavailable(B256),
     /// Header range not found for start block hash/number/tag to end block hash/number/tag
     #[error("header range not found, start block {0:?}, end block {1:?}")]
     HeaderRangeNotFound(BlockId, BlockId),
@@ -98,13 +193,28 @@ pub enum EthApiError {
     InvalidRewardPercentiles,
     /// Error thrown when a spawned blocking task failed to deliver an anticipated response.
     ///
-    /// This only happens if the blocking task panics and is aborted before it can return a
-    /// response back to the request handler.
+    /// This only happens if the blocking task is dropped before it can return a response back to
+    /// the request handler, panics are reported as [`EthApiError::BlockingTaskPanicked`].
     #[error("internal blocking task error")]
     InternalBlockingTaskError,
+    /// Error thrown when a spawned blocking task panicked, with the message of the panic.
+    #[error("blocking task panicked: {0}")]
+    BlockingTaskPanicked(String),
     /// Error thrown when a spawned blocking task failed to deliver an anticipated response
     #[error("internal eth error")]
     InternalEthError,
+    /// Error thrown when a tracing call is aborted because its request was dropped, e.g. because
+    /// the client disconnected
+    #[error("tracing cancelled")]
+    TracingCancelled,
+    /// Error thrown when a client exceeds its limit of concurrent or per-minute requests
+    #[error("rate limit exceeded, at most {limit}, retry after {retry_after:?}")]
+    RateLimitExceeded {
+        /// The exceeded limit.
+        limit: RequestLimit,
+        /// The suggested time to wait before retrying the request.
+        retry_after: Duration,
+    },
     /// Error thrown when a (tracing) call exceeds the configured timeout
     #[error("execution aborted (timeout = {0:?})")]
     ExecutionTimedOut(Duration),
@@ -126,6 +236,9 @@ pub enum EthApiError {
     /// Error thrown when tracing with a muxTracer fails
     #[error(transparent)]
     MuxTracerError(#[from] MuxError),
+    /// Errors of the `debug` namespace
+    #[error(transparent)]
+    Debug(#[from] DebugApiError),
     /// Any other error
     #[error("{0}")]
     Other(Box<dyn ToRpcError>),
@@ -166,7 +279,12 @@ impl From<EthApiError> for jsonrpsee_types::error::ErrorObject<'static> {
             EthApiError::EvmCustom(_) |
             EthApiError::EvmPrecompile(_) |
             EthApiError::InvalidRewardPercentiles => internal_rpc_err(error.to_string()),
-            EthApiError::UnknownBlockOrTxIndex => {
+            EthApiError::UnknownBlockOrTxIndex |
+            EthApiError::BlockTagNotAvailable(_) |
+            EthApiError::ParentBlockNotFound(_) |
+            EthApiError::ParentStateUnavailable(_) |
+            EthApiError::TransactionNotInBlock(..) |
+            EthApiError::BlobSidecarUnavailable(_) => {
                 rpc_error_with_code(EthRpcErrorCode::ResourceNotFound.code(), error.to_string())
             }
             // TODO(onbjerg): We rewrite the error message here because op-node does string matching
@@ -196,12 +314,41 @@ impl From<EthApiError> for jsonrpsee_types::error::ErrorObject<'static> {
                 jsonrpsee_types::error::CALL_EXECUTION_FAILED_CODE,
                 err.to_string(),
             ),
-            err @ (EthApiError::InternalBlockingTaskError | EthApiError::InternalEthError) => {
-                internal_rpc_err(err.to_string())
+            err @ (EthApiError::InternalBlockingTaskError |
+            EthApiError::BlockingTaskPanicked(_) |
+            EthApiError::InternalEthError |
+            EthApiError::TracingCancelled) => internal_rpc_err(err.to_string()),
+            EthApiError::HistoricalStateUnavailable { block, earliest } => {
+                jsonrpsee_types::error::ErrorObject::owned(
+                    HISTORICAL_STATE_UNAVAILABLE_CODE,
+                    error.to_string(),
+                    Some(HistoricalStateUnavailableData {
+                        block,
+                        earliest_available_block: earliest,
+                    }),
+                )
+            }
+            EthApiError::NodeSyncing { executed, ref stages } => {
+                jsonrpsee_types::error::ErrorObject::owned(
+                    NODE_SYNCING_CODE,
+                    error.to_string(),
+                    Some(NodeSyncingData { executed_block: executed, stages: stages.clone() }),
+                )
+            }
+            EthApiError::RateLimitExceeded { limit, retry_after } => {
+                jsonrpsee_types::error::ErrorObject::owned(
+                    RATE_LIMIT_EXCEEDED_CODE,
+                    error.to_string(),
+                    Some(RateLimitExceededData {
+                        limit,
+                        retry_after_ms: retry_after.as_millis() as u64,
+                    }),
+                )
             }
             err @ EthApiError::TransactionInputError(_) => invalid_params_rpc_err(err.to_string()),
             EthApiError::Other(err) => err.to_rpc_error(),
             EthApiError::MuxTracerError(msg) => internal_rpc_err(msg.to_string()),
+            EthApiError::Debug(err) => err.into(),
         }
     }
 }
@@ -408,6 +555,23 @@ pub enum RpcInvalidTransactionError {
     /// EIP-7702 transaction has invalid fields set.
     #[error("EIP-7702 authorization list has invalid fields")]
     AuthorizationListInvalidFields,
+    /// EIP-7702 transaction has an empty authorization list.
+    #[error("EIP-7702 authorization list is empty")]
+    EmptyAuthorizationList,
+    /// EIP-7702 authorization tuple targets a different chain.
+    #[error("EIP-7702 authorization {index} has invalid chain ID {chain_id}")]
+    AuthorizationInvalidChainId {
+        /// The index of the authorization in the list.
+        index: usize,
+        /// The chain ID of the authorization.
+        chain_id: U256,
+    },
+    /// EIP-7702 authorization tuple has a nonce that can't be incremented.
+    #[error("EIP-7702 authorization {index} has max nonce value")]
+    AuthorizationNonceMaxValue {
+        /// The index of the authorization in the list.
+        index: usize,
+    },
     /// Any other error
     #[error("{0}")]
     Other(Box<dyn ToRpcError>),
@@ -720,6 +884,218 @@ pub enum SignError {
     NoChainId,
 }
 
+/// The error codes of the failures of the `debug` namespace, see [`DebugApiError`].
+///
+/// Each class of failures has its own code. The codes of the `debug` namespace are in the range
+/// `-39001..=-39099`, except for the exceeded tracer capacity, which keeps the server is busy
+/// code of `jsonrpsee` that clients retry on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugRpcErrorCode {
+    /// The requested block is unknown.
+    BlockNotFound,
+    /// The requested transaction is unknown.
+    TransactionNotFound,
+    /// The config of the tracer doesn't match the tracer.
+    InvalidTracerConfig,
+    /// The tracer is disabled, or not supported by this build of the node.
+    UnsupportedTracer,
+    /// A raw block or transaction couldn't be decoded.
+    InvalidRlp,
+    /// No tracing permits are handed out anymore.
+    TracingUnavailable,
+    /// No tracing permit became available within the maximum wait.
+    TracerCapacityExceeded,
+    /// The traces would exceed the memory budget of all in-flight traces.
+    TraceMemoryBudgetExceeded,
+    /// The gas used of the block exceeds the limit of the tracer.
+    BlockTooLargeForTracer,
+}
+
+impl DebugRpcErrorCode {
+    /// Returns the error code of the failure class.
+    pub const fn code(&self) -> i32 {
+        match *self {
+            Self::BlockNotFound => -39001,
+            Self::TransactionNotFound => -39002,
+            Self::InvalidTracerConfig => -39003,
+            Self::UnsupportedTracer => -39004,
+            Self::InvalidRlp => -39005,
+            Self::TracingUnavailable => -39006,
+            Self::TraceMemoryBudgetExceeded => -39007,
+            Self::BlockTooLargeForTracer => -39008,
+            Self::TracerCapacityExceeded => jsonrpsee_types::error::SERVER_IS_BUSY_CODE,
+        }
+    }
+}
+
+/// Errors of the `debug` namespace.
+///
+/// The RPC error of each failure has the [`DebugRpcErrorCode`] of its class, and its `data` holds
+/// the machine-readable fields of the failure, tagged with its `kind`. The messages are the ones
+/// of the failures before they had dedicated codes, so clients matching on them keep working.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DebugApiError {
+    /// The requested block is unknown
+    #[error("block not found: {}", block_id_to_str(*.0))]
+    BlockNotFound(BlockId),
+    /// The requested transaction is unknown, neither in a block nor in the pool
+    #[error("transaction not found")]
+    TransactionNotFound(B256),
+    /// The config of the tracer doesn't match the tracer
+    #[error("invalid tracer config")]
+    InvalidTracerConfig {
+        /// The name of the tracer.
+        tracer: &'static str,
+    },
+    /// The tracer is disabled by the operator
+    #[error("tracer disabled by operator: {tracer}")]
+    TracerDisabled {
+        /// The name of the tracer.
+        tracer: &'static str,
+    },
+    /// JS tracers aren't supported by this build of the node
+    #[error("JS Tracer is not enabled")]
+    JsTracerNotEnabled,
+    /// A raw block couldn't be decoded
+    #[error("failed to decode raw block {reason}")]
+    InvalidRawBlock {
+        /// The reason the RLP decoding failed.
+        reason: String,
+    },
+    /// A raw transaction couldn't be decoded
+    #[error("failed to decode signed transaction")]
+    InvalidRawTransaction,
+    /// No permit for a tracing call can be acquired because the tracing guard was closed, e.g.
+    /// because the node is shutting down
+    #[error("tracing unavailable, the node is shutting down")]
+    TracingUnavailable,
+    /// No permit for a tracing call became available within the configured maximum wait,
+    /// because the tracer is saturated
+    #[error("tracer capacity exceeded, {queued} requests queued, retry after {retry_after:?}")]
+    TracerCapacityExceeded {
+        /// The number of requests waiting for a permit.
+        queued: usize,
+        /// The suggested time to wait before retrying the request.
+        retry_after: Duration,
+    },
+    /// The traces of a tracing call would exceed the memory budget of all in-flight traces
+    #[error("trace memory budget exceeded")]
+    TraceMemoryBudgetExceeded {
+        /// The configured budget, in bytes.
+        limit: usize,
+    },
+    /// The gas used of a traced block exceeds the limit the operator configured for the tracer
+    #[error("block too large for this tracer, limit is {limit}")]
+    BlockTooLargeForTracer {
+        /// The name of the tracer.
+        tracer: &'static str,
+        /// The configured limit of the gas used of traced blocks.
+        limit: u64,
+        /// The gas used of the block.
+        gas_used: u64,
+    },
+}
+
+impl DebugApiError {
+    /// Returns the error code of the class of the failure.
+    pub const fn code(&self) -> DebugRpcErrorCode {
+        match self {
+            Self::BlockNotFound(_) => DebugRpcErrorCode::BlockNotFound,
+            Self::TransactionNotFound(_) => DebugRpcErrorCode::TransactionNotFound,
+            Self::InvalidTracerConfig { .. } => DebugRpcErrorCode::InvalidTracerConfig,
+            Self::TracerDisabled { .. } | Self::JsTracerNotEnabled => {
+                DebugRpcErrorCode::UnsupportedTracer
+            }
+            Self::InvalidRawBlock { .. } | Self::InvalidRawTransaction => {
+                DebugRpcErrorCode::InvalidRlp
+            }
+            Self::TracingUnavailable => DebugRpcErrorCode::TracingUnavailable,
+            Self::TracerCapacityExceeded { .. } => DebugRpcErrorCode::TracerCapacityExceeded,
+            Self::TraceMemoryBudgetExceeded { .. } => DebugRpcErrorCode::TraceMemoryBudgetExceeded,
+            Self::BlockTooLargeForTracer { .. } => DebugRpcErrorCode::BlockTooLargeForTracer,
+        }
+    }
+}
+
+impl From<DebugApiError> for jsonrpsee_types::error::ErrorObject<'static> {
+    fn from(error: DebugApiError) -> Self {
+        let data = match &error {
+            DebugApiError::BlockNotFound(block) => DebugErrorData::BlockNotFound { block: *block },
+            DebugApiError::TransactionNotFound(tx_hash) => {
+                DebugErrorData::TransactionNotFound { tx_hash: *tx_hash }
+            }
+            DebugApiError::InvalidTracerConfig { tracer } => {
+                DebugErrorData::InvalidTracerConfig { tracer }
+            }
+            DebugApiError::TracerDisabled { tracer } => DebugErrorData::TracerDisabled { tracer },
+            DebugApiError::JsTracerNotEnabled => DebugErrorData::TracerNotEnabled { tracer: "js" },
+            DebugApiError::InvalidRawBlock { reason } => {
+                DebugErrorData::InvalidRlp { item: "block", reason: Some(reason.as_str()) }
+            }
+            DebugApiError::InvalidRawTransaction => {
+                DebugErrorData::InvalidRlp { item: "transaction", reason: None }
+            }
+            DebugApiError::TracingUnavailable => DebugErrorData::TracingUnavailable,
+            DebugApiError::TracerCapacityExceeded { queued, retry_after } => {
+                DebugErrorData::TracerCapacityExceeded {
+                    queued: *queued,
+                    retry_after_ms: retry_after.as_millis() as u64,
+                }
+            }
+            DebugApiError::TraceMemoryBudgetExceeded { limit } => {
+                DebugErrorData::TraceMemoryBudgetExceeded { limit: *limit }
+            }
+            DebugApiError::BlockTooLargeForTracer { tracer, limit, gas_used } => {
+                DebugErrorData::BlockTooLargeForTracer {
+                    tracer,
+                    limit: *limit,
+                    gas_used: *gas_used,
+                }
+            }
+        };
+        Self::owned(error.code().code(), error.to_string(), Some(data))
+    }
+}
+
+/// The data of the RPC errors of [`DebugApiError`].
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+enum DebugErrorData<'a> {
+    BlockNotFound {
+        block: BlockId,
+    },
+    TransactionNotFound {
+        tx_hash: B256,
+    },
+    InvalidTracerConfig {
+        tracer: &'a str,
+    },
+    TracerDisabled {
+        tracer: &'a str,
+    },
+    TracerNotEnabled {
+        tracer: &'a str,
+    },
+    InvalidRlp {
+        item: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'a str>,
+    },
+    TracingUnavailable,
+    TracerCapacityExceeded {
+        queued: usize,
+        retry_after_ms: u64,
+    },
+    TraceMemoryBudgetExceeded {
+        limit: usize,
+    },
+    BlockTooLargeForTracer {
+        tracer: &'a str,
+        limit: u64,
+        gas_used: u64,
+    },
+}
+
 /// Converts the evm [`ExecutionResult`] into a result where `Ok` variant is the output bytes if it
 /// is [`ExecutionResult::Success`].
 pub fn ensure_success(result: ExecutionResult) -> EthResult<Bytes> {
@@ -773,4 +1149,134 @@ mod tests {
             EthApiError::HeaderNotFound(BlockId::finalized()).into();
         assert_eq!(err.message(), "block not found: finalized");
     }
+
+    #[test]
+    fn debug_errors() {
+        let tx_hash = B256::with_last_byte(1);
+        let cases = [
+            (
+                DebugApiError::BlockNotFound(BlockId::number(5)),
+                -39001,
+                "block not found: number 0x5",
+                r#"{"kind":"blockNotFound","block":"0x5"}"#.to_string(),
+            ),
+            (
+                DebugApiError::TransactionNotFound(tx_hash),
+                -39002,
+                "transaction not found",
+                format!(r#"{{"kind":"transactionNotFound","txHash":"{tx_hash}"}}"#),
+            ),
+            (
+                DebugApiError::InvalidTracerConfig { tracer: "callTracer" },
+                -39003,
+                "invalid tracer config",
+                r#"{"kind":"invalidTracerConfig","tracer":"callTracer"}"#.to_string(),
+            ),
+            (
+                DebugApiError::TracerDisabled { tracer: "structLogger" },
+                -39004,
+                "tracer disabled by operator: structLogger",
+                r#"{"kind":"tracerDisabled","tracer":"structLogger"}"#.to_string(),
+            ),
+            (
+                DebugApiError::JsTracerNotEnabled,
+                -39004,
+                "JS Tracer is not enabled",
+                r#"{"kind":"tracerNotEnabled","tracer":"js"}"#.to_string(),
+            ),
+            (
+                DebugApiError::InvalidRawBlock { reason: "unexpected end of input".to_string() },
+                -39005,
+                "failed to decode raw block unexpected end of input",
+                r#"{"kind":"invalidRlp","item":"block","reason":"unexpected end of input"}"#
+                    .to_string(),
+            ),
+            (
+                DebugApiError::InvalidRawTransaction,
+                -39005,
+                "failed to decode signed transaction",
+                r#"{"kind":"invalidRlp","item":"transaction"}"#.to_string(),
+            ),
+            (
+                DebugApiError::TracingUnavailable,
+                -39006,
+                "tracing unavailable, the node is shutting down",
+                r#"{"kind":"tracingUnavailable"}"#.to_string(),
+            ),
+            (
+                DebugApiError::TracerCapacityExceeded {
+                    queued: 3,
+                    retry_after: Duration::from_secs(10),
+                },
+                jsonrpsee_types::error::SERVER_IS_BUSY_CODE,
+                "tracer capacity exceeded, 3 requests queued, retry after 10s",
+                r#"{"kind":"tracerCapacityExceeded","queued":3,"retryAfterMs":10000}"#.to_string(),
+            ),
+            (
+                DebugApiError::TraceMemoryBudgetExceeded { limit: 1024 },
+                -39007,
+                "trace memory budget exceeded",
+                r#"{"kind":"traceMemoryBudgetExceeded","limit":1024}"#.to_string(),
+            ),
+            (
+                DebugApiError::BlockTooLargeForTracer {
+                    tracer: "callTracer",
+                    limit: 1,
+                    gas_used: 2,
+                },
+                -39008,
+                "block too large for this tracer, limit is 1",
+                r#"{"kind":"blockTooLargeForTracer","tracer":"callTracer","limit":1,"gasUsed":2}"#
+                    .to_string(),
+            ),
+        ];
+        for (error, code, message, data) in cases {
+            assert_eq!(error.code().code(), code, "{error:?}");
+            // the errors keep their code once they're converted into `eth` errors
+            let err: jsonrpsee_types::error::ErrorObject<'static> = EthApiError::from(error).into();
+            assert_eq!(err.code(), code);
+            assert_eq!(err.message(), message);
+            assert_eq!(err.data().unwrap().get(), data);
+        }
+    }
+
+    #[test]
+    fn rate_limit_exceeded_error() {
+        let err: jsonrpsee_types::error::ErrorObject<'static> = EthApiError::RateLimitExceeded {
+            limit: RequestLimit::PerMinute(60),
+            retry_after: Duration::from_millis(1500),
+        }
+        .into();
+        assert_eq!(err.code(), RATE_LIMIT_EXCEEDED_CODE);
+        assert_eq!(
+            err.message(),
+            "rate limit exceeded, at most 60 requests per minute, retry after 1.5s"
+        );
+        assert_eq!(err.data().unwrap().get(), r#"{"limit":{"perMinute":60},"retryAfterMs":1500}"#);
+    }
+
+    #[test]
+    fn historical_state_unavailable_error() {
+        let err: jsonrpsee_types::error::ErrorObject<'static> =
+            EthApiError::HistoricalStateUnavailable { block: 10, earliest: 90 }.into();
+        assert_eq!(err.code(), HISTORICAL_STATE_UNAVAILABLE_CODE);
+        assert_eq!(
+            err.message(),
+            "historical state for block 10 is unavailable; earliest available state is block 90"
+        );
+        assert_eq!(err.data().unwrap().get(), r#"{"block":10,"earliestAvailableBlock":90}"#);
+    }
+
+    #[test]
+    fn node_syncing_error() {
+        let stages = vec![Stage { name: "Execution".to_string(), block: 5 }];
+        let err: jsonrpsee_types::error::ErrorObject<'static> =
+            EthApiError::NodeSyncing { executed: 5, stages }.into();
+        assert_eq!(err.code(), NODE_SYNCING_CODE);
+        assert_eq!(err.message(), "node is syncing, state is executed up to block 5");
+        assert_eq!(
+            err.data().unwrap().get(),
+            r#"{"executedBlock":5,"stages":[{"name":"Execution","block":"0x5"}]}"#
+        );
+    }
 }
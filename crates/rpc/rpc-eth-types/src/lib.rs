@@ -29,7 +29,11 @@ pub use cache::{
     config::EthStateCacheConfig, db::StateCacheDb, multi_consumer::MultiConsumerLruCache,
     EthStateCache,
 };
-pub use error::{EthApiError, EthResult, RevertError, RpcInvalidTransactionError, SignError};
+pub use error::{
+    DebugApiError, DebugRpcErrorCode, EthApiError, EthResult, RequestLimit, RevertError,
+    RpcInvalidTransactionError, SignError, HISTORICAL_STATE_UNAVAILABLE_CODE, NODE_SYNCING_CODE,
+    RATE_LIMIT_EXCEEDED_CODE,
+};
 pub use fee_history::{FeeHistoryCache, FeeHistoryCacheConfig, FeeHistoryEntry};
 pub use gas_oracle::{
     GasCap, GasPriceOracle, GasPriceOracleConfig, GasPriceOracleResult, RPC_DEFAULT_GAS_CAP,
@@ -1,5 +1,9 @@
 //! utilities for working with revm
 
+use alloy_eips::{
+    eip2935::{HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS},
+    eip7702::SignedAuthorization,
+};
 use alloy_primitives::{Address, B256, U256};
 use reth_rpc_types::{
     state::{AccountOverride, StateOverride},
@@ -12,7 +16,7 @@ use revm::{
     Database,
 };
 use revm_primitives::BlockEnv;
-use std::cmp::min;
+use std::{cmp::min, collections::BTreeMap};
 
 use super::{EthApiError, EthResult, RpcInvalidTransactionError};
 
@@ -66,6 +70,35 @@ where
         .unwrap_or_default())
 }
 
+/// Validates the [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) authorization list of a
+/// [`reth_rpc_types::TransactionRequest`].
+///
+/// Each authorization tuple must either target the given chain ID or any chain (chain ID `0`), and
+/// its nonce must not be the max value.
+pub fn validate_authorization_list(
+    authorization_list: &[SignedAuthorization],
+    chain_id: u64,
+) -> EthResult<()> {
+    if authorization_list.is_empty() {
+        return Err(RpcInvalidTransactionError::EmptyAuthorizationList.into())
+    }
+
+    for (index, authorization) in authorization_list.iter().enumerate() {
+        if !authorization.chain_id.is_zero() && authorization.chain_id != U256::from(chain_id) {
+            return Err(RpcInvalidTransactionError::AuthorizationInvalidChainId {
+                index,
+                chain_id: authorization.chain_id,
+            }
+            .into())
+        }
+        if authorization.nonce() == u64::MAX {
+            return Err(RpcInvalidTransactionError::AuthorizationNonceMaxValue { index }.into())
+        }
+    }
+
+    Ok(())
+}
+
 /// Helper type for representing the fees of a [`reth_rpc_types::TransactionRequest`]
 #[derive(Debug)]
 pub struct CallFees {
@@ -241,6 +274,36 @@ pub fn apply_block_overrides(overrides: BlockOverrides, env: &mut BlockEnv) {
     }
 }
 
+/// Applies the given block hash overrides to the [`CacheDB`].
+///
+/// The overridden hashes are served by the `BLOCKHASH` opcode via the [`CacheDB`]'s block hash
+/// cache, any block number that is not overridden falls through to the underlying database.
+///
+/// If [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935) is active for the given [`SpecId`], the
+/// hashes are additionally written to the ring buffer of the history storage contract, so that
+/// lookups against the system contract observe the same overrides.
+pub fn apply_block_hash_overrides<DB>(
+    block_hashes: BTreeMap<u64, B256>,
+    spec_id: SpecId,
+    db: &mut CacheDB<DB>,
+) -> EthResult<()>
+where
+    DB: DatabaseRef,
+    EthApiError: From<<DB as DatabaseRef>::Error>,
+{
+    let is_prague = spec_id.is_enabled_in(SpecId::PRAGUE);
+    for (number, hash) in block_hashes {
+        db.block_hashes.insert(U256::from(number), hash);
+
+        if is_prague {
+            // the history contract stores the hashes in a ring buffer keyed by `number % window`
+            let slot = U256::from(number % HISTORY_SERVE_WINDOW as u64);
+            db.insert_account_storage(HISTORY_STORAGE_ADDRESS, slot, U256::from_be_bytes(hash.0))?;
+        }
+    }
+    Ok(())
+}
+
 /// Applies the given state overrides (a set of [`AccountOverride`]) to the [`CacheDB`].
 pub fn apply_state_overrides<DB>(overrides: StateOverride, db: &mut CacheDB<DB>) -> EthResult<()>
 where
@@ -280,8 +343,8 @@ where
     db.insert_account_info(account, account_info);
 
     // We ensure that not both state and state_diff are set.
-    // If state is set, we must mark the account as "NewlyCreated", so that the old storage
-    // isn't read from
+    // If state is set, the storage of the account is replaced, so that slots that aren't listed
+    // read as zero instead of being read from the database
     match (account_override.state, account_override.state_diff) {
         (Some(_), Some(_)) => return Err(EthApiError::BothStateAndStateDiffInOverride(account)),
         (None, None) => {
@@ -1,10 +1,13 @@
 //! Commonly used code snippets
 
 use alloy_primitives::Bytes;
-use reth_primitives::{PooledTransactionsElement, PooledTransactionsElementEcRecovered};
+use reth_primitives::{
+    PooledTransactionsElement, PooledTransactionsElementEcRecovered, TransactionSigned,
+    TransactionSignedEcRecovered, TxType,
+};
 use std::future::Future;
 
-use super::{EthApiError, EthResult};
+use super::{EthApiError, EthResult, RpcInvalidTransactionError};
 
 /// Recovers a [`PooledTransactionsElementEcRecovered`] from an enveloped encoded byte stream.
 ///
@@ -20,6 +23,33 @@ pub fn recover_raw_transaction(data: Bytes) -> EthResult<PooledTransactionsEleme
     transaction.try_into_ecrecovered().or(Err(EthApiError::InvalidTransactionSignature))
 }
 
+/// Recovers a [`TransactionSignedEcRecovered`] from an enveloped encoded byte stream.
+///
+/// Unlike [`recover_raw_transaction`], this accepts the canonical encoding of EIP-4844 transactions
+/// without the blob sidecar, as well as the pooled encoding with the sidecar, which is dropped
+/// since it isn't required for execution.
+///
+/// See [`TransactionSigned::decode_enveloped`]
+pub fn recover_raw_signed_transaction(data: Bytes) -> EthResult<TransactionSignedEcRecovered> {
+    if data.is_empty() {
+        return Err(EthApiError::EmptyRawTransactionData)
+    }
+
+    // typed transactions start with the type byte, legacy transactions with a list header
+    if data[0] < 0xc0 && TxType::try_from(data[0]).is_err() {
+        return Err(RpcInvalidTransactionError::TxTypeNotSupported.into())
+    }
+
+    let transaction = TransactionSigned::decode_enveloped(&mut data.as_ref())
+        .or_else(|_| {
+            PooledTransactionsElement::decode_enveloped(&mut data.as_ref())
+                .map(PooledTransactionsElement::into_transaction)
+        })
+        .map_err(|_| EthApiError::FailedToDecodeSignedTransaction)?;
+
+    transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)
+}
+
 /// Performs a binary search within a given block range to find the desired block number.
 ///
 /// The binary search is performed by calling the provided asynchronous `check` closure on the
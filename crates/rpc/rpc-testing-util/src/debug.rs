@@ -134,13 +134,15 @@ where
             futures::stream::iter(blocks.into_iter().map(move |(block, opts)| async move {
                 let trace_future = match block {
                     BlockId::Hash(hash) => {
-                        self.debug_trace_block_by_hash(hash.block_hash, opts.clone())
+                        self.debug_trace_block_by_hash(hash.block_hash, opts.map(Into::into))
+                    }
+                    BlockId::Number(tag) => {
+                        self.debug_trace_block_by_number(tag, opts.map(Into::into))
                     }
-                    BlockId::Number(tag) => self.debug_trace_block_by_number(tag, opts.clone()),
                 };
 
                 match trace_future.await {
-                    Ok(result) => Ok((result, block)),
+                    Ok(result) => Ok((result.into_results(), block)),
                     Err(err) => Err((err, block)),
                 }
             }))
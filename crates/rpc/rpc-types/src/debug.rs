@@ -0,0 +1,3174 @@
+//! Types for the `debug` namespace.
+
+pub use alloy_rpc_types_debug::*;
+
+use alloy_eips::{eip2930::AccessList, BlockId};
+use alloy_primitives::{hex, keccak256, Address, Bloom, Bytes, B256, U256};
+use alloy_rpc_types::{BlockOverrides, Bundle, EIP1186AccountProofResponse, TransactionRequest};
+use alloy_rpc_types_trace::geth::{
+    DefaultFrame, DiffMode, GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
+    StructLog, TraceResult,
+};
+use serde::{
+    de::{IgnoredAny, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// Name of the flamegraph tracer, which can be selected instead of a JS tracer.
+///
+/// This tracer returns the call frames of a transaction as folded stack lines, e.g.
+/// `0xA;0xB:0x70a08231 2103`, weighted by the gas consumed exclusively in each frame. The lines
+/// can be fed directly into flamegraph tools like inferno or speedscope.
+pub const FLAMEGRAPH_TRACER: &str = "flamegraphTracer";
+
+/// Options for `debug_traceCall`.
+///
+/// This extends the [`GethDebugTracingCallOptions`] with additional settings supported by reth.
+/// All additional settings are optional, so that plain geth options deserialize unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTracingCallOptions {
+    /// The precompiles moved by the state overrides of the call options.
+    // deserialized before the call options, which consume the state overrides
+    #[serde(flatten)]
+    pub precompile_moves: PrecompileMoves,
+    /// The geth call tracing options.
+    #[serde(flatten)]
+    pub call_options: GethDebugTracingCallOptions,
+    /// The index of the transaction in the block after which the call should be executed.
+    ///
+    /// `0` executes the call on the parent state of the block, the number of transactions in the
+    /// block executes the call on top of the state after all transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub tx_index: Option<u64>,
+    /// Whether to trace the call on top of a pending block that is built anew from the best
+    /// transactions of the pool, instead of on top of the pending block of the node.
+    ///
+    /// This requires the `pending` block. The hashes of the pool transactions the call is traced
+    /// on top of are returned alongside the trace.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub build_pending_from_pool: bool,
+    /// Whether to trace the call while the node is syncing, even if the block is at or beyond the
+    /// block the state of the node is executed up to.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_while_syncing: bool,
+    /// The additional data to return alongside the trace.
+    #[serde(flatten)]
+    pub extras: TraceCallExtras,
+    /// The checks to relax for the call.
+    #[serde(flatten)]
+    pub checks: TraceCallChecks,
+    /// The overrides of the EVM configuration the call is executed with.
+    ///
+    /// The spec the call was executed with is returned alongside the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg_overrides: Option<CfgOverrides>,
+}
+
+/// A bundle of `debug_traceCallMany`.
+///
+/// This extends the [`Bundle`] of `eth_callMany` with support for raw signed transactions, so that
+/// plain bundles deserialize unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugBundle {
+    /// The transactions of the bundle.
+    pub transactions: Vec<BundleTransaction>,
+    /// The block overrides that apply to all transactions of the bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_override: Option<BlockOverrides>,
+}
+
+impl From<Bundle> for DebugBundle {
+    fn from(bundle: Bundle) -> Self {
+        Self {
+            transactions: bundle.transactions.into_iter().map(Into::into).collect(),
+            block_override: bundle.block_override,
+        }
+    }
+}
+
+/// A transaction of a [`DebugBundle`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BundleTransaction {
+    /// A call, executed like `eth_call`.
+    Request(Box<TransactionRequest>),
+    /// The [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) encoding of a signed transaction,
+    /// executed as is.
+    Raw(Bytes),
+}
+
+impl BundleTransaction {
+    /// Returns the call if this is a [`TransactionRequest`].
+    pub fn as_request(&self) -> Option<&TransactionRequest> {
+        match self {
+            Self::Request(request) => Some(request),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+impl From<TransactionRequest> for BundleTransaction {
+    fn from(request: TransactionRequest) -> Self {
+        Self::Request(Box::new(request))
+    }
+}
+
+impl From<Bytes> for BundleTransaction {
+    fn from(raw: Bytes) -> Self {
+        Self::Raw(raw)
+    }
+}
+
+/// Options for `debug_traceCallMany`.
+///
+/// This extends the [`GethDebugTracingCallOptions`] with additional settings supported by reth.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTracingCallManyOptions {
+    /// The precompiles moved by the state overrides of the call options.
+    // deserialized before the call options, which consume the state overrides
+    #[serde(flatten)]
+    pub precompile_moves: PrecompileMoves,
+    /// The geth call tracing options.
+    #[serde(flatten)]
+    pub call_options: GethDebugTracingCallOptions,
+    /// The checks to relax for all traced calls.
+    #[serde(flatten)]
+    pub checks: TraceCallChecks,
+    /// Whether to apply the state overrides of the call options before each bundle, instead of
+    /// only before the first one.
+    ///
+    /// The overrides are applied on top of the state left by the preceding bundles, accounts
+    /// that aren't overridden keep their changes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub state_overrides_per_bundle: bool,
+    /// Whether to return the net state changes of each bundle alongside its traces.
+    ///
+    /// The changes of a bundle are those its transactions committed on top of the state left by
+    /// the preceding bundles.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub return_state_diff: bool,
+    /// Whether to trace each bundle on the state left by the replayed transactions of the block,
+    /// instead of on top of the changes of the preceding bundles.
+    ///
+    /// All bundles are executed at the same block, unless their block overrides position them,
+    /// and the state overrides are applied before each of them. The bundles may be traced in
+    /// parallel.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub independent_bundles: bool,
+    /// Whether to trace the bundles while the node is syncing, even if the block is at or beyond
+    /// the block the state of the node is executed up to.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_while_syncing: bool,
+    /// The overrides of the EVM configuration the bundles are executed with.
+    ///
+    /// The transactions of the block that are replayed are executed with the configuration of
+    /// the block. The spec the bundles were executed with is returned alongside the traces of
+    /// each bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg_overrides: Option<CfgOverrides>,
+}
+
+impl From<GethDebugTracingCallOptions> for DebugTracingCallManyOptions {
+    fn from(call_options: GethDebugTracingCallOptions) -> Self {
+        Self { call_options, ..Default::default() }
+    }
+}
+
+/// The precompiles moved by the `movePrecompileToAddress` fields of the account overrides of a
+/// traced call, from their original address to the address they're moved to.
+///
+/// The account overrides of the [`GethDebugTracingCallOptions`] don't support the field, so the
+/// moves are read from the `stateOverrides` of the options separately when they're deserialized.
+/// They're not serialized.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrecompileMoves(pub BTreeMap<Address, Address>);
+
+impl PrecompileMoves {
+    /// Returns true if no precompile is moved.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<(Address, Address)> for PrecompileMoves {
+    fn from_iter<T: IntoIterator<Item = (Address, Address)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Serialize for PrecompileMoves {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_map(Some(0))?.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for PrecompileMoves {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct MovedAccount {
+            #[serde(default)]
+            move_precompile_to_address: Option<Address>,
+        }
+
+        struct MovesVisitor;
+
+        impl<'de> Visitor<'de> for MovesVisitor {
+            type Value = PrecompileMoves;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("call tracing options")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut moves = PrecompileMoves::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key != "stateOverrides" {
+                        map.next_value::<IgnoredAny>()?;
+                        continue
+                    }
+                    let overrides = map.next_value::<Option<BTreeMap<Address, MovedAccount>>>()?;
+                    moves.0.extend(overrides.into_iter().flatten().filter_map(
+                        |(address, account)| Some((address, account.move_precompile_to_address?)),
+                    ));
+                }
+                Ok(moves)
+            }
+        }
+
+        deserializer.deserialize_map(MovesVisitor)
+    }
+}
+
+/// Options for the block tracing methods, e.g. `debug_traceBlockByNumber`.
+///
+/// This extends the [`GethDebugTracingOptions`] with additional settings supported by reth.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTracingBlockOptions {
+    /// The geth tracing options.
+    #[serde(flatten)]
+    pub tracing_options: GethDebugTracingOptions,
+    /// The hashes of the transactions to trace, if not all transactions of the block.
+    ///
+    /// The transactions that precede them in the block are still executed, but not traced.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tx_hashes: Vec<B256>,
+    /// The shape of the response.
+    #[serde(default, skip_serializing_if = "TraceResultFormat::is_array")]
+    pub result_format: TraceResultFormat,
+    /// Whether to bypass the trace cache of the node, if enabled, and always trace the block.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_cache: bool,
+    /// Whether to apply the post-block changes, e.g. the withdrawals, after the last transaction
+    /// and report them as a [`PostBlockTrace`] in the `postBlock` field of the response, see
+    /// [`DebugTraceBlockResponse::WithPostBlock`].
+    ///
+    /// This is ignored if only a subset of the transactions is traced.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_post_block: bool,
+    /// Whether to attach the [`TraceStats`] of each traced transaction to its result and return
+    /// them together with a [`BlockTraceSummary`], see [`DebugTraceBlockResponse::Verbose`].
+    ///
+    /// The results are returned in block order, regardless of the result format, and the trace
+    /// cache of the node is bypassed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub verbose: bool,
+    /// Whether to trace the block while the node is syncing, even if it's at or beyond the block
+    /// the state of the node is executed up to.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_while_syncing: bool,
+    /// Whether to add the [`ExecutionStats`] of the request to the [`BlockTraceSummary`].
+    ///
+    /// This implies [`Self::verbose`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_execution_stats: bool,
+    /// Whether to add the [`TraceCounters`] of each traced transaction to its [`TraceStats`].
+    ///
+    /// This implies [`Self::verbose`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_stats: bool,
+    /// Whether to push the [`TraceProgress`] of the trace before its result.
+    ///
+    /// This is only honoured by the `debug_subscribeTraceBlock` subscription, which is served
+    /// over websocket and IPC. The block tracing methods ignore it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub progress: bool,
+    /// Whether to trace the block even if its gas used exceeds the limit of the node for the
+    /// tracer.
+    ///
+    /// This is only honoured if the node allows requests to override the limits.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+    /// Whether to return the results together with the [`BlockTraceContext`] of the traced
+    /// block, see [`DebugTraceBlockResponse::WithContext`].
+    ///
+    /// The results are returned in block order, regardless of the result format. In verbose mode,
+    /// the context is added to the [`VerboseBlockTrace`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_block_context: bool,
+    /// The cursor of the page to trace, as returned with the previous page of the block, see
+    /// [`DebugTraceBlockResponse::Page`].
+    ///
+    /// Setting this, [`Self::max_transactions`] or [`Self::max_response_bytes`] traces the block
+    /// in pages. This can't be combined with [`Self::tx_hashes`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<BlockTraceCursor>,
+    /// The maximum number of transactions traced in a page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transactions: Option<u64>,
+    /// The maximum total size of the JSON encoding of the results of a page, in bytes.
+    ///
+    /// A page holds at least one result, even if it exceeds the limit on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_bytes: Option<u64>,
+}
+
+impl DebugTracingBlockOptions {
+    /// Returns true if the block is traced in pages, see [`Self::cursor`].
+    pub const fn is_paginated(&self) -> bool {
+        self.cursor.is_some() || self.max_transactions.is_some() || self.max_response_bytes.is_some()
+    }
+}
+
+impl From<GethDebugTracingOptions> for DebugTracingBlockOptions {
+    fn from(tracing_options: GethDebugTracingOptions) -> Self {
+        Self { tracing_options, ..Default::default() }
+    }
+}
+
+/// The position a paginated block trace continues at.
+///
+/// The cursor is opaque to clients, it's serialized as a hex string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlockTraceCursor {
+    /// The hash of the traced block.
+    pub block_hash: B256,
+    /// The index of the first transaction of the page.
+    pub tx_index: u64,
+}
+
+impl Serialize for BlockTraceCursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let cursor = [self.block_hash.as_slice(), &self.tx_index.to_be_bytes()].concat();
+        serializer.serialize_str(&hex::encode_prefixed(cursor))
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockTraceCursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cursor = Bytes::deserialize(deserializer)?;
+        let Some((block_hash, tx_index)) = cursor.split_first_chunk::<32>() else {
+            return Err(serde::de::Error::custom("invalid cursor"))
+        };
+        let tx_index = <[u8; 8]>::try_from(tx_index)
+            .map_err(|_| serde::de::Error::custom("invalid cursor"))?;
+        Ok(Self { block_hash: B256::from(*block_hash), tx_index: u64::from_be_bytes(tx_index) })
+    }
+}
+
+/// The shape of the response of the block tracing methods.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceResultFormat {
+    /// The results in block order, same as geth.
+    #[default]
+    Array,
+    /// The results keyed by transaction hash.
+    Map,
+}
+
+impl TraceResultFormat {
+    /// Returns true if this is the array format.
+    pub const fn is_array(&self) -> bool {
+        matches!(self, Self::Array)
+    }
+}
+
+/// Response of the block tracing methods, e.g. `debug_traceBlockByNumber`.
+///
+/// The shape is selected via the [`TraceResultFormat`] of the [`DebugTracingBlockOptions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DebugTraceBlockResponse {
+    /// The results in block order.
+    Array(Vec<TraceResult>),
+    /// The results keyed by transaction hash.
+    Map(BTreeMap<B256, TraceResult>),
+    /// The results in block order with their statistics, if verbose mode is enabled.
+    Verbose(VerboseBlockTrace),
+    /// The results in block order with the context of the block, if requested via
+    /// [`DebugTracingBlockOptions::include_block_context`].
+    WithContext(BlockTraceWithContext),
+    /// The results in the array or map format with the post-block changes of the block, if
+    /// requested via [`DebugTracingBlockOptions::include_post_block`].
+    ///
+    /// The other formats report the post-block changes in a field of their own.
+    WithPostBlock(BlockTraceWithPostBlock),
+    /// A page of the results in block order, if the block is traced in pages, see
+    /// [`DebugTracingBlockOptions::cursor`].
+    Page(BlockTracePage),
+}
+
+impl DebugTraceBlockResponse {
+    /// Creates the response for the given results in the given format.
+    ///
+    /// Results without a transaction hash are omitted from the map format.
+    pub fn new(results: Vec<TraceResult>, format: TraceResultFormat) -> Self {
+        match format {
+            TraceResultFormat::Array => Self::Array(results),
+            TraceResultFormat::Map => Self::Map(
+                results
+                    .into_iter()
+                    .filter_map(|res| {
+                        let tx_hash = match &res {
+                            TraceResult::Success { tx_hash, .. } |
+                            TraceResult::Error { tx_hash, .. } => *tx_hash,
+                        };
+                        Some((tx_hash?, res))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Creates the response for the given results with their statistics.
+    ///
+    /// In verbose mode, the results are returned with their statistics and a summary. Otherwise,
+    /// the statistics are dropped and the response has the given format.
+    pub fn with_stats(
+        results: Vec<TraceResultWithStats>,
+        format: TraceResultFormat,
+        verbose: bool,
+    ) -> Self {
+        if verbose {
+            return Self::Verbose(VerboseBlockTrace::new(results))
+        }
+        Self::new(results.into_iter().map(|res| res.result).collect(), format)
+    }
+
+    /// Attaches the given post-block changes to the response, if any.
+    ///
+    /// The array and map formats are wrapped in [`Self::WithPostBlock`].
+    pub fn with_post_block(self, post_block: Option<PostBlockTrace>) -> Self {
+        let Some(post_block) = post_block else { return self };
+        match self {
+            Self::Verbose(trace) => {
+                Self::Verbose(VerboseBlockTrace { post_block: Some(post_block), ..trace })
+            }
+            Self::WithContext(trace) => {
+                Self::WithContext(BlockTraceWithContext { post_block: Some(post_block), ..trace })
+            }
+            Self::WithPostBlock(trace) => {
+                Self::WithPostBlock(BlockTraceWithPostBlock { post_block, ..trace })
+            }
+            Self::Page(page) => Self::Page(BlockTracePage { post_block: Some(post_block), ..page }),
+            results => Self::WithPostBlock(BlockTraceWithPostBlock {
+                results: Box::new(results),
+                post_block,
+            }),
+        }
+    }
+
+    /// Returns the post-block changes of the response, if any.
+    pub const fn post_block(&self) -> Option<&PostBlockTrace> {
+        match self {
+            Self::Array(_) | Self::Map(_) => None,
+            Self::Verbose(trace) => trace.post_block.as_ref(),
+            Self::WithContext(trace) => trace.post_block.as_ref(),
+            Self::WithPostBlock(trace) => Some(&trace.post_block),
+            Self::Page(page) => page.post_block.as_ref(),
+        }
+    }
+
+    /// Consumes the response and returns the results, in block order for the array, verbose and
+    /// context formats and ordered by transaction hash for the map format.
+    pub fn into_results(self) -> Vec<TraceResult> {
+        match self {
+            Self::Array(results) => results,
+            Self::Map(results) => results.into_values().collect(),
+            Self::Verbose(trace) => trace.results.into_iter().map(|res| res.result).collect(),
+            Self::WithContext(trace) => trace.results,
+            Self::WithPostBlock(trace) => trace.results.into_results(),
+            Self::Page(page) => page.results.into_iter().map(|res| res.result).collect(),
+        }
+    }
+}
+
+impl From<Vec<TraceResult>> for DebugTraceBlockResponse {
+    fn from(results: Vec<TraceResult>) -> Self {
+        Self::Array(results)
+    }
+}
+
+/// A [`TraceResult`] with the statistics of the traced transaction, if requested.
+///
+/// Without statistics, this serializes the same as the plain result.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceResultWithStats {
+    /// The trace result.
+    #[serde(flatten)]
+    pub result: TraceResult,
+    /// The statistics of the traced transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TraceStats>,
+}
+
+impl From<TraceResult> for TraceResultWithStats {
+    fn from(result: TraceResult) -> Self {
+        Self { result, stats: None }
+    }
+}
+
+/// Statistics of a traced transaction, reported in verbose mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStats {
+    /// The wall-clock time spent executing and tracing the transaction, in microseconds.
+    pub duration_micros: u64,
+    /// The gas used by the transaction.
+    ///
+    /// This is zero if the tracer didn't execute the transaction, e.g. the noop tracer, or if
+    /// tracing it failed.
+    #[serde(with = "alloy_serde::quantity")]
+    pub gas_used: u64,
+    /// The size of the serialized trace result, in bytes.
+    pub trace_size: u64,
+    /// The counters of the execution of the transaction, if requested via
+    /// [`DebugTracingBlockOptions::include_stats`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counters: Option<TraceCounters>,
+}
+
+/// Counters of the traced executions of a request, returned if `includeStats` is set.
+///
+/// Only the traced executions are counted, not the transactions that are replayed before them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCounters {
+    /// The number of EVM steps executed.
+    pub steps: u64,
+    /// The size of the JSON encoding of the recorded struct logs, in bytes.
+    ///
+    /// This is zero for all tracers other than the struct logger.
+    pub struct_log_bytes: u64,
+    /// The number of calls and creations executed below the top call.
+    pub subcalls: u64,
+    /// The number of accounts, storage slots and bytecodes read from the state provider, i.e. not
+    /// served by the cache of the request.
+    pub database_reads: u64,
+    /// The time spent reading from the state provider during execution, in microseconds.
+    pub state_load_micros: u64,
+    /// The time spent executing the EVM, excluding the reads from the state provider, in
+    /// microseconds.
+    pub evm_execution_micros: u64,
+}
+
+/// Statistics of the database lookups of the executions of a tracing request.
+///
+/// A lookup is either served by the in-memory cache of the request, which holds the state that
+/// was already read or written by preceding executions, or falls through to the state provider of
+/// the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStats {
+    /// The number of account lookups served by the cache.
+    pub account_cache_hits: u64,
+    /// The number of accounts read from the state provider.
+    pub account_provider_reads: u64,
+    /// The number of storage slot lookups served by the cache.
+    pub storage_cache_hits: u64,
+    /// The number of storage slots read from the state provider.
+    pub storage_provider_reads: u64,
+    /// The number of bytecode lookups served by the cache.
+    pub code_cache_hits: u64,
+    /// The number of bytecodes read from the state provider.
+    pub code_provider_reads: u64,
+    /// The total time spent reading from the state provider, in microseconds.
+    pub provider_read_micros: u64,
+}
+
+/// The traces of a block with their statistics, see [`DebugTracingBlockOptions::verbose`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VerboseBlockTrace {
+    /// The results in block order.
+    pub results: Vec<TraceResultWithStats>,
+    /// The summary of the statistics of all results.
+    pub summary: BlockTraceSummary,
+    /// The context of the traced block, if requested via
+    /// [`DebugTracingBlockOptions::include_block_context`].
+    #[serde(flatten)]
+    pub context: Option<BlockTraceContext>,
+    /// The post-block changes of the block, if requested via
+    /// [`DebugTracingBlockOptions::include_post_block`].
+    #[serde(default, rename = "postBlock", skip_serializing_if = "Option::is_none")]
+    pub post_block: Option<PostBlockTrace>,
+}
+
+impl VerboseBlockTrace {
+    /// Creates the verbose trace for the given results and summarizes their statistics.
+    pub fn new(results: Vec<TraceResultWithStats>) -> Self {
+        let summary = BlockTraceSummary::new(&results);
+        Self { results, summary, context: None, post_block: None }
+    }
+}
+
+/// The context of a traced block, for post-processing its traces without fetching the block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTraceContext {
+    /// The hash of the block.
+    pub hash: B256,
+    /// The number of the block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub number: u64,
+    /// The timestamp of the block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub timestamp: u64,
+    /// The base fee of the block, if it's after London.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub base_fee_per_gas: Option<u64>,
+    /// The gas used by all transactions of the block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub gas_used: u64,
+    /// The number of transactions of the block, including the ones that weren't traced.
+    #[serde(with = "alloy_serde::quantity")]
+    pub transaction_count: u64,
+}
+
+/// The traces of a block with the context of the block, see
+/// [`DebugTracingBlockOptions::include_block_context`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockTraceWithContext {
+    /// The context of the block.
+    #[serde(flatten)]
+    pub context: BlockTraceContext,
+    /// The results in block order.
+    pub results: Vec<TraceResult>,
+    /// The post-block changes of the block, if requested via
+    /// [`DebugTracingBlockOptions::include_post_block`].
+    #[serde(default, rename = "postBlock", skip_serializing_if = "Option::is_none")]
+    pub post_block: Option<PostBlockTrace>,
+}
+
+/// The traces of a block in the array or map format with the post-block changes of the block, see
+/// [`DebugTracingBlockOptions::include_post_block`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTraceWithPostBlock {
+    /// The results, either [`DebugTraceBlockResponse::Array`] or [`DebugTraceBlockResponse::Map`].
+    pub results: Box<DebugTraceBlockResponse>,
+    /// The post-block changes of the block.
+    pub post_block: PostBlockTrace,
+}
+
+/// A page of the traces of a block, see [`DebugTracingBlockOptions::cursor`].
+///
+/// In verbose mode, the statistics are attached to the results, but there's no summary, and the
+/// context of the block isn't returned with pages. The post-block changes, if requested, are
+/// reported with the last page only.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTracePage {
+    /// The results of the traced transactions of the page, in block order.
+    pub results: Vec<TraceResultWithStats>,
+    /// The cursor of the next page, if the page isn't the last one of the block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<BlockTraceCursor>,
+    /// The post-block changes of the block, if requested and this is the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_block: Option<PostBlockTrace>,
+}
+
+/// The traces of a block of `debug_traceChain`.
+///
+/// Like geth's result, this carries the hash of the block and its traces, along with the same
+/// [`BlockTraceContext`] as the block traces of
+/// [`DebugTracingBlockOptions::include_block_context`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockTraceResult {
+    /// The context of the block.
+    #[serde(flatten)]
+    pub context: BlockTraceContext,
+    /// The results in block order.
+    pub traces: Vec<TraceResult>,
+}
+
+/// Summary of the statistics of the traced transactions of a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTraceSummary {
+    /// The total time spent executing and tracing the transactions, in microseconds.
+    ///
+    /// This is the sum over all traced transactions, so it exceeds the wall-clock time of the
+    /// request if the block was traced in parallel.
+    pub total_duration_micros: u64,
+    /// The hash of the transaction that took the longest to trace, if any was traced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slowest_tx_hash: Option<B256>,
+    /// The database lookups of the request, if requested via
+    /// [`DebugTracingBlockOptions::include_execution_stats`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_stats: Option<ExecutionStats>,
+}
+
+impl BlockTraceSummary {
+    /// Summarizes the statistics of the given results.
+    ///
+    /// Results without statistics are ignored.
+    pub fn new(results: &[TraceResultWithStats]) -> Self {
+        let stats = results
+            .iter()
+            .filter_map(|res| {
+                let tx_hash = match &res.result {
+                    TraceResult::Success { tx_hash, .. } | TraceResult::Error { tx_hash, .. } => {
+                        *tx_hash
+                    }
+                };
+                Some((res.stats?, tx_hash))
+            })
+            .collect::<Vec<_>>();
+        Self {
+            total_duration_micros: stats.iter().map(|(stats, _)| stats.duration_micros).sum(),
+            slowest_tx_hash: stats
+                .iter()
+                .max_by_key(|(stats, _)| stats.duration_micros)
+                .and_then(|(_, tx_hash)| *tx_hash),
+            execution_stats: None,
+        }
+    }
+}
+
+/// The post-block changes of a traced block, as applied after its last transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostBlockTrace {
+    /// The balance credits of the withdrawals of the block, in order.
+    ///
+    /// Withdrawals of zero amount are omitted.
+    pub withdrawals: Vec<WithdrawalCredit>,
+}
+
+/// The balance credit of a withdrawal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalCredit {
+    /// The recipient of the withdrawal.
+    pub address: Address,
+    /// The credited amount, in wei.
+    pub amount: U256,
+    /// The balance of the recipient after the credit.
+    pub balance: U256,
+}
+
+/// Checks of a traced call that can be relaxed, similar to `eth_call`.
+///
+/// By default, all checks are performed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCallChecks {
+    /// Whether to skip the check that the gas price of the call covers the basefee of the block.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable_base_fee_check: bool,
+    /// Whether to skip the check that the balance of the sender covers the gas and value of the
+    /// call.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable_balance_check: bool,
+}
+
+/// Overrides of the EVM configuration traced calls are executed with, e.g. to trace a call as if a
+/// hardfork was already active at the block, or not active yet.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CfgOverrides {
+    /// The name of the hardfork whose spec the calls are executed with, e.g. `prague`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    /// The chain id the calls are executed with.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub chain_id: Option<u64>,
+    /// The blob base fee the calls are executed with, which the block overrides don't support.
+    ///
+    /// The blob gas of the calls is charged at this price instead of the blob gas price of the
+    /// block.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub blob_base_fee: Option<u128>,
+}
+
+/// Additional data that can be requested alongside the trace of a call.
+///
+/// All of this is derived from the same execution that produced the trace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCallExtras {
+    /// Whether to return the state changes of the call.
+    ///
+    /// The diff is computed against the state the call was executed on, including any state
+    /// overrides.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub return_state_diff: bool,
+    /// Whether to return the [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) access list of
+    /// the accounts and storage slots accessed by the call.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_access_list: bool,
+    /// Whether to return the merkle proofs of the accounts and storage slots accessed by the
+    /// call.
+    ///
+    /// The proofs are generated against the state root of the state the call was executed on,
+    /// without any state overrides.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub with_proof: bool,
+    /// Whether to estimate the gas limit of the call if none is specified, instead of using the
+    /// configured gas cap.
+    ///
+    /// The used gas limit is returned alongside the trace.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimate_gas: bool,
+    /// Whether to return the [`ExecutionStats`] of the request, including the execution of the
+    /// transactions the call is traced on top of.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_execution_stats: bool,
+    /// Whether to return the [`TraceCounters`] of the traced call.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_stats: bool,
+}
+
+impl TraceCallExtras {
+    /// Returns true if no additional data is requested.
+    pub const fn is_empty(&self) -> bool {
+        !self.return_state_diff &&
+            !self.include_access_list &&
+            !self.with_proof &&
+            !self.estimate_gas &&
+            !self.include_execution_stats &&
+            !self.include_stats
+    }
+}
+
+impl From<GethDebugTracingCallOptions> for DebugTracingCallOptions {
+    fn from(call_options: GethDebugTracingCallOptions) -> Self {
+        Self { call_options, ..Default::default() }
+    }
+}
+
+/// Settings of the default struct logger supported by reth in addition to the
+/// [`GethDefaultTracingOptions`](alloy_rpc_types_trace::geth::GethDefaultTracingOptions).
+///
+/// These are passed via the `tracerConfig` of the tracing options if no tracer is specified.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLoggerConfig {
+    /// The names of the opcodes to record steps for, e.g. `SLOAD`.
+    ///
+    /// If empty, all steps are recorded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub opcode_filter: Vec<String>,
+    /// Whether to annotate the steps that access an account or storage slot with their
+    /// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929) access status, `cold` or `warm`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub record_access_status: bool,
+}
+
+/// The [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929) access status of an account or storage
+/// slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessStatus {
+    /// The account or slot wasn't accessed before, so the access paid the cold cost.
+    Cold,
+    /// The account or slot was already accessed, or pre-warmed via the access list.
+    Warm,
+}
+
+impl AccessStatus {
+    /// Returns the status of an account or slot that is warm if `is_warm` is set.
+    pub const fn new(is_warm: bool) -> Self {
+        if is_warm {
+            Self::Warm
+        } else {
+            Self::Cold
+        }
+    }
+}
+
+/// A struct log of the default struct logger, with the [`AccessStatus`] of the step.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessStructLog {
+    /// The struct log of the step.
+    #[serde(flatten)]
+    pub log: StructLog,
+    /// The access status of the account or storage slot the step accessed, if it executes an
+    /// accessing opcode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<AccessStatus>,
+}
+
+/// The [`DefaultFrame`] of the default struct logger with the [`AccessStatus`] of its steps, as
+/// returned if [`StructLoggerConfig::record_access_status`] is set.
+///
+/// This is returned as JS trace, since the struct logs of a [`GethTrace`] can't be extended.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessStatusFrame {
+    /// Whether the transaction failed.
+    pub failed: bool,
+    /// How much gas was used.
+    pub gas: u64,
+    /// The output of the transaction, without `0x` prefix like the [`DefaultFrame`].
+    #[serde(serialize_with = "alloy_serde::serialize_hex_string_no_prefix")]
+    pub return_value: Bytes,
+    /// The recorded steps, with their access status.
+    pub struct_logs: Vec<AccessStructLog>,
+}
+
+impl AccessStatusFrame {
+    /// Creates the frame from the [`DefaultFrame`], with the given access status of each of its
+    /// steps.
+    pub fn new(
+        frame: DefaultFrame,
+        access: impl IntoIterator<Item = Option<AccessStatus>>,
+    ) -> Self {
+        let DefaultFrame { failed, gas, return_value, struct_logs } = frame;
+        let struct_logs = struct_logs
+            .into_iter()
+            .zip(access.into_iter().chain(std::iter::repeat(None)))
+            .map(|(log, access)| AccessStructLog { log, access })
+            .collect();
+        Self { failed, gas, return_value, struct_logs }
+    }
+}
+
+/// Response of `debug_traceCall`.
+///
+/// This is the plain [`GethTrace`] unless additional data was requested via the
+/// [`DebugTracingCallOptions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DebugTraceCallResponse {
+    /// The trace with the additional requested data.
+    WithExtras(DebugTraceCallResult),
+    /// The trace, same as geth.
+    Trace(GethTrace),
+}
+
+impl DebugTraceCallResponse {
+    /// Returns the trace of the call.
+    pub const fn trace(&self) -> &GethTrace {
+        match self {
+            Self::WithExtras(res) => &res.trace,
+            Self::Trace(trace) => trace,
+        }
+    }
+
+    /// Consumes the response and returns the trace of the call.
+    pub fn into_trace(self) -> GethTrace {
+        match self {
+            Self::WithExtras(res) => res.trace,
+            Self::Trace(trace) => trace,
+        }
+    }
+
+    /// Consumes the response and returns the trace with the additional data, if any.
+    pub fn into_result(self) -> DebugTraceCallResult {
+        match self {
+            Self::WithExtras(res) => res,
+            Self::Trace(trace) => DebugTraceCallResult::new(trace),
+        }
+    }
+}
+
+impl From<GethTrace> for DebugTraceCallResponse {
+    fn from(trace: GethTrace) -> Self {
+        Self::Trace(trace)
+    }
+}
+
+impl From<DebugTraceCallResult> for DebugTraceCallResponse {
+    fn from(res: DebugTraceCallResult) -> Self {
+        Self::WithExtras(res)
+    }
+}
+
+/// The trace of a call along with the additional data requested via the
+/// [`DebugTracingCallOptions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTraceCallResult {
+    /// The trace frame produced by the configured tracer.
+    pub trace: GethTrace,
+    /// The state changes of the call, in the same shape as the prestate tracer's diff mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<DiffMode>,
+    /// The access list of the accounts and storage slots accessed by the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    /// The proofs of the accounts and storage slots accessed by the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proofs: Option<Vec<EIP1186AccountProofResponse>>,
+    /// The gas limit the call was executed with.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub gas_limit: Option<u64>,
+    /// The hashes of the pool transactions the call was traced on top of, if the pending block
+    /// was built from the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_transactions: Option<Vec<B256>>,
+    /// The database lookups of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_stats: Option<ExecutionStats>,
+    /// The name of the hardfork whose spec the call was executed with, if the cfg was
+    /// overridden.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    /// The counters of the traced call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TraceCounters>,
+}
+
+impl DebugTraceCallResult {
+    /// Creates a new result with the given trace and no additional data.
+    pub const fn new(trace: GethTrace) -> Self {
+        Self {
+            trace,
+            state_diff: None,
+            access_list: None,
+            proofs: None,
+            gas_limit: None,
+            pool_transactions: None,
+            execution_stats: None,
+            spec: None,
+            stats: None,
+        }
+    }
+}
+
+/// Response of `debug_traceCallMany` for a bundle.
+///
+/// These are the plain traces of the transactions of the bundle unless additional data was
+/// requested via the [`DebugTracingCallManyOptions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DebugTraceBundleResponse {
+    /// The traces with the additional requested data.
+    WithExtras(DebugTraceBundleResult),
+    /// The traces of the transactions, same as geth.
+    Traces(Vec<GethTrace>),
+}
+
+impl DebugTraceBundleResponse {
+    /// Returns the traces of the transactions of the bundle.
+    pub fn traces(&self) -> &[GethTrace] {
+        match self {
+            Self::WithExtras(res) => &res.traces,
+            Self::Traces(traces) => traces,
+        }
+    }
+
+    /// Consumes the response and returns the traces of the transactions of the bundle.
+    pub fn into_traces(self) -> Vec<GethTrace> {
+        match self {
+            Self::WithExtras(res) => res.traces,
+            Self::Traces(traces) => traces,
+        }
+    }
+}
+
+impl From<Vec<GethTrace>> for DebugTraceBundleResponse {
+    fn from(traces: Vec<GethTrace>) -> Self {
+        Self::Traces(traces)
+    }
+}
+
+impl From<DebugTraceBundleResult> for DebugTraceBundleResponse {
+    fn from(res: DebugTraceBundleResult) -> Self {
+        Self::WithExtras(res)
+    }
+}
+
+/// The traces of a bundle along with the additional data requested via the
+/// [`DebugTracingCallManyOptions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTraceBundleResult {
+    /// The traces of the transactions of the bundle.
+    pub traces: Vec<GethTrace>,
+    /// The net state changes of the bundle, by account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<BTreeMap<Address, AccountDiff>>,
+    /// The name of the hardfork whose spec the bundle was executed with, if the cfg was
+    /// overridden.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+}
+
+/// Options for `debug_traceTransaction`.
+///
+/// This extends the [`GethDebugTracingOptions`] with additional settings supported by reth, so
+/// that plain geth options deserialize unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTracingTransactionOptions {
+    /// The geth tracing options.
+    #[serde(flatten)]
+    pub tracing_options: GethDebugTracingOptions,
+    /// Whether to return the [`TransactionStateChanges`] of the transaction alongside the trace,
+    /// see [`DebugTraceTransactionResponse::WithExtras`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub return_state_changes: bool,
+    /// Whether to return the [`TraceCounters`] of the traced transaction alongside the trace.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_stats: bool,
+}
+
+impl From<GethDebugTracingOptions> for DebugTracingTransactionOptions {
+    fn from(tracing_options: GethDebugTracingOptions) -> Self {
+        Self { tracing_options, ..Default::default() }
+    }
+}
+
+/// Response of `debug_traceTransaction`.
+///
+/// This is the plain [`GethTrace`] unless additional data was requested via the
+/// [`DebugTracingTransactionOptions`], or the transaction is only in the pool.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DebugTraceTransactionResponse {
+    /// The speculative trace of a transaction that's only in the pool.
+    // deserialized first, the other variants don't require a `pending` field
+    Pending(PendingTransactionTrace),
+    /// The trace with the additional requested data.
+    WithExtras(DebugTraceTransactionResult),
+    /// The trace, same as geth.
+    Trace(GethTrace),
+}
+
+impl DebugTraceTransactionResponse {
+    /// Returns the trace of the transaction, unless it's a pool transaction that couldn't be
+    /// executed.
+    pub const fn trace(&self) -> Option<&GethTrace> {
+        match self {
+            Self::Pending(res) => res.result.as_ref(),
+            Self::WithExtras(res) => Some(&res.trace),
+            Self::Trace(trace) => Some(trace),
+        }
+    }
+
+    /// Consumes the response and returns the trace of the transaction, or the error of a pool
+    /// transaction that couldn't be executed.
+    pub fn into_trace(self) -> Result<GethTrace, String> {
+        match self {
+            Self::Pending(res) => res.result.ok_or_else(|| res.error.unwrap_or_default()),
+            Self::WithExtras(res) => Ok(res.trace),
+            Self::Trace(trace) => Ok(trace),
+        }
+    }
+}
+
+impl From<GethTrace> for DebugTraceTransactionResponse {
+    fn from(trace: GethTrace) -> Self {
+        Self::Trace(trace)
+    }
+}
+
+impl From<DebugTraceTransactionResult> for DebugTraceTransactionResponse {
+    fn from(res: DebugTraceTransactionResult) -> Self {
+        Self::WithExtras(res)
+    }
+}
+
+impl From<PendingTransactionTrace> for DebugTraceTransactionResponse {
+    fn from(res: PendingTransactionTrace) -> Self {
+        Self::Pending(res)
+    }
+}
+
+/// The speculative trace of a transaction that's only in the pool.
+///
+/// The transaction is executed in the environment of the pending block on top of the latest
+/// state, without the transactions that would precede it in the pending block. If it can't be
+/// executed there, e.g. because of a nonce gap, the error is returned instead of the trace.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransactionTrace {
+    /// Always `true`, marks the trace as speculative.
+    pub pending: bool,
+    /// The trace frame produced by the configured tracer, if the transaction was executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GethTrace>,
+    /// The error the transaction failed with, if it couldn't be executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The state changes of the transaction, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_changes: Option<TransactionStateChanges>,
+    /// The counters of the traced transaction, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TraceCounters>,
+}
+
+/// The trace of a transaction along with the additional data requested via the
+/// [`DebugTracingTransactionOptions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTraceTransactionResult {
+    /// The trace frame produced by the configured tracer.
+    pub trace: GethTrace,
+    /// The state changes of the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_changes: Option<TransactionStateChanges>,
+    /// The counters of the traced transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TraceCounters>,
+}
+
+/// The state changes of a transaction.
+///
+/// The changes are in the same shape as the prestate tracer's diff mode: accounts that were
+/// created are only included in `post`, and accounts that were selfdestructed only in `pre`.
+/// Both are additionally listed by address.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionStateChanges {
+    /// The state of the changed accounts before and after the transaction.
+    #[serde(flatten)]
+    pub diff: DiffMode,
+    /// The accounts created by the transaction.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub created: BTreeSet<Address>,
+    /// The accounts selfdestructed by the transaction.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub selfdestructed: BTreeSet<Address>,
+}
+
+/// The net changes of an account, only including the fields that changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountDiff {
+    /// The change of the balance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<ValueChange<U256>>,
+    /// The change of the nonce.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<ValueChange<u64>>,
+    /// The change of the code, which is empty for accounts without code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ValueChange<Bytes>>,
+    /// The changes of the storage slots, by slot.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<B256, ValueChange<B256>>,
+}
+
+impl AccountDiff {
+    /// Returns true if nothing changed.
+    pub const fn is_empty(&self) -> bool {
+        self.balance.is_none() &&
+            self.nonce.is_none() &&
+            self.code.is_none() &&
+            self.storage.is_empty()
+    }
+}
+
+/// A value before and after a change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueChange<T> {
+    /// The value before the change.
+    pub from: T,
+    /// The value after the change.
+    pub to: T,
+}
+
+impl<T: PartialEq> ValueChange<T> {
+    /// Returns the change from `from` to `to`, or `None` if the values are equal.
+    pub fn new(from: T, to: T) -> Option<Self> {
+        (from != to).then_some(Self { from, to })
+    }
+}
+
+/// Response of `debug_executionWitness`.
+///
+/// This extends the [`ExecutionWitness`] with additional data supported by reth. The additional
+/// data is only included if requested, so that plain witnesses serialize unchanged.
+///
+/// The serialization is deterministic: the entries of all maps are serialized in ascending order
+/// of their keys, i.e. the trie nodes by node hash, the preimages by hashed key and the codes by
+/// code hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugExecutionWitness {
+    /// The trie nodes and optional key preimages.
+    #[serde(flatten, serialize_with = "serialize_sorted_witness")]
+    pub witness: ExecutionWitness,
+    /// Map of the code hashes of all contracts whose code was loaded during the execution of the
+    /// block to their bytecode, including code that was only inspected, e.g. via `EXTCODESIZE`.
+    ///
+    /// `keccak(code) => code`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codes: Option<BTreeMap<B256, Bytes>>,
+    /// The RLP encoded headers of the contiguous chain of ancestors from the oldest block whose
+    /// hash was accessed via `BLOCKHASH` up to the parent block, in ascending order.
+    ///
+    /// These are only included if required to prove the accessed block hashes, i.e. if the
+    /// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935) history contract isn't active yet.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<Bytes>,
+    /// The result of the verification of the re-execution against the block, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<WitnessVerification>,
+    /// The hash of the witnessed block, if it was requested as `pending` or as a raw block.
+    ///
+    /// The pending block may be rebuilt between requests, so this identifies the block the
+    /// witness is for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    /// The hashed keys of the accounts and storage slots of the trie nodes of the witness whose
+    /// preimages are unknown, in ascending order, if the preimages were verified.
+    ///
+    /// This includes the keys of leaves that are only part of the witness to prove the absence of
+    /// other keys, since their preimages aren't known to the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing_preimages: Option<Vec<B256>>,
+}
+
+impl From<ExecutionWitness> for DebugExecutionWitness {
+    fn from(witness: ExecutionWitness) -> Self {
+        Self {
+            witness,
+            codes: None,
+            headers: Vec::new(),
+            verification: None,
+            block_hash: None,
+            missing_preimages: None,
+        }
+    }
+}
+
+/// Serializes the [`ExecutionWitness`] like its own implementation, but with the entries of its
+/// maps in ascending order of their keys.
+fn serialize_sorted_witness<S: Serializer>(
+    witness: &ExecutionWitness,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct SortedExecutionWitness<'a> {
+        witness: BTreeMap<&'a B256, &'a Bytes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        state_preimages: Option<BTreeMap<&'a B256, &'a Bytes>>,
+    }
+
+    SortedExecutionWitness {
+        witness: witness.witness.iter().collect(),
+        state_preimages: witness.state_preimages.as_ref().map(|keys| keys.iter().collect()),
+    }
+    .serialize(serializer)
+}
+
+/// Options for `debug_executionWitness`.
+///
+/// All settings are optional and disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExecutionWitnessOptions {
+    /// Whether to include the bytecode of all contracts whose code was loaded during the execution
+    /// of the block, keyed by code hash.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub include_codes: bool,
+    /// Whether to verify the post-state root and gas used of the re-execution against the block
+    /// header, see [`WitnessVerification`].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub verify: bool,
+    /// Whether to bypass the witness cache of the node.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub no_cache: bool,
+    /// The encoding of the response.
+    pub encoding: WitnessEncoding,
+    /// Whether to verify that the preimages cover the hashed keys of all account and storage
+    /// leaves of the returned trie nodes.
+    ///
+    /// The keys lacking a preimage are returned as `missing_preimages`, instead of a silently
+    /// incomplete map of preimages.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub verify_preimages: bool,
+}
+
+/// Encoding of the response of `debug_executionWitness`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WitnessEncoding {
+    /// The [`DebugExecutionWitness`] as JSON object.
+    #[default]
+    Json,
+    /// The binary framing of the witness, see [`EncodedExecutionWitness`].
+    Raw,
+    /// The binary framing of the witness, compressed with the snappy block format.
+    Snappy,
+}
+
+/// Response of `debug_executionWitness`, depending on the requested [`WitnessEncoding`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExecutionWitnessResponse {
+    /// The binary encoded witness.
+    Encoded(EncodedExecutionWitness),
+    /// The witness as JSON object.
+    Witness(DebugExecutionWitness),
+}
+
+impl ExecutionWitnessResponse {
+    /// Returns the witness, decoding it if it's binary encoded.
+    pub fn into_witness(self) -> Result<DebugExecutionWitness, WitnessEncodingError> {
+        match self {
+            Self::Witness(witness) => Ok(witness),
+            Self::Encoded(encoded) => encoded.decode(),
+        }
+    }
+}
+
+impl DebugExecutionWitness {
+    /// Returns the response of `debug_executionWitness` for the witness in the given encoding.
+    pub fn encode(
+        self,
+        encoding: WitnessEncoding,
+    ) -> Result<ExecutionWitnessResponse, WitnessEncodingError> {
+        if encoding == WitnessEncoding::Json {
+            return Ok(ExecutionWitnessResponse::Witness(self))
+        }
+
+        let Self {
+            witness: ExecutionWitness { witness: state, state_preimages },
+            codes,
+            headers,
+            verification,
+            block_hash,
+            missing_preimages,
+        } = self;
+        // the entries are encoded in the same order as they are serialized
+        let state = BTreeMap::from_iter(state);
+        let state_preimages = state_preimages.map(BTreeMap::from_iter);
+
+        let mut data = Vec::new();
+        for node in state.values() {
+            write_value(&mut data, node)?;
+        }
+        for (key, preimage) in state_preimages.iter().flatten() {
+            data.extend_from_slice(key.as_slice());
+            write_value(&mut data, preimage)?;
+        }
+        for code in codes.iter().flat_map(BTreeMap::values) {
+            write_value(&mut data, code)?;
+        }
+        for header in &headers {
+            write_value(&mut data, header)?;
+        }
+
+        let uncompressed_size = data.len() as u64;
+        if encoding == WitnessEncoding::Snappy {
+            data = snap::raw::Encoder::new().compress_vec(&data)?;
+        }
+        Ok(ExecutionWitnessResponse::Encoded(EncodedExecutionWitness {
+            encoding,
+            state_nodes: state.len() as u64,
+            preimages: state_preimages.map(|preimages| preimages.len() as u64),
+            codes: codes.map(|codes| codes.len() as u64),
+            headers: headers.len() as u64,
+            uncompressed_size,
+            verification,
+            block_hash,
+            missing_preimages,
+            data: data.into(),
+        }))
+    }
+}
+
+/// A binary encoded execution witness of `debug_executionWitness`.
+///
+/// The binary framing concatenates the entries of the witness in this order: the state trie
+/// nodes, the key preimages, the codes and the headers. Every value is prefixed with its length as
+/// big-endian `u32`, and every preimage additionally with its key. Trie nodes and codes are keyed
+/// by their keccak hash, so their keys are omitted and recomputed on decoding. The numbers of the
+/// entries are part of the JSON header.
+///
+/// Like the serialization of [`DebugExecutionWitness`], the encoding is deterministic: the trie
+/// nodes, preimages and codes are encoded in ascending order of their keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodedExecutionWitness {
+    /// The encoding of the data, either [`WitnessEncoding::Raw`] or [`WitnessEncoding::Snappy`].
+    pub encoding: WitnessEncoding,
+    /// The number of state trie nodes.
+    #[serde(with = "alloy_serde::quantity")]
+    pub state_nodes: u64,
+    /// The number of key preimages, if they are included.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub preimages: Option<u64>,
+    /// The number of codes, if they are included.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "alloy_serde::quantity::opt")]
+    pub codes: Option<u64>,
+    /// The number of headers.
+    #[serde(with = "alloy_serde::quantity")]
+    pub headers: u64,
+    /// The size of the binary framing before compression.
+    #[serde(with = "alloy_serde::quantity")]
+    pub uncompressed_size: u64,
+    /// See [`DebugExecutionWitness::verification`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<WitnessVerification>,
+    /// See [`DebugExecutionWitness::block_hash`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    /// See [`DebugExecutionWitness::missing_preimages`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing_preimages: Option<Vec<B256>>,
+    /// The encoded entries of the witness.
+    pub data: Bytes,
+}
+
+impl EncodedExecutionWitness {
+    /// Decodes the witness.
+    pub fn decode(&self) -> Result<DebugExecutionWitness, WitnessEncodingError> {
+        let decompressed;
+        let mut data = match self.encoding {
+            WitnessEncoding::Json => return Err(WitnessEncodingError::NotBinary),
+            WitnessEncoding::Raw => self.data.as_ref(),
+            WitnessEncoding::Snappy => {
+                decompressed = snap::raw::Decoder::new().decompress_vec(&self.data)?;
+                decompressed.as_slice()
+            }
+        };
+        if data.len() as u64 != self.uncompressed_size {
+            return Err(WitnessEncodingError::SizeMismatch {
+                expected: self.uncompressed_size,
+                actual: data.len() as u64,
+            })
+        }
+
+        let state = (0..self.state_nodes)
+            .map(|_| read_value(&mut data).map(|node| (keccak256(&node), node)))
+            .collect::<Result<_, _>>()?;
+        let state_preimages = self
+            .preimages
+            .map(|count| {
+                (0..count)
+                    .map(|_| Ok((B256::from_slice(take(&mut data, 32)?), read_value(&mut data)?)))
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?;
+        let codes = self
+            .codes
+            .map(|count| {
+                (0..count)
+                    .map(|_| read_value(&mut data).map(|code| (keccak256(&code), code)))
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?;
+        let headers = (0..self.headers).map(|_| read_value(&mut data)).collect::<Result<_, _>>()?;
+        if !data.is_empty() {
+            return Err(WitnessEncodingError::TrailingBytes(data.len()))
+        }
+
+        Ok(DebugExecutionWitness {
+            witness: ExecutionWitness { witness: state, state_preimages },
+            codes,
+            headers,
+            verification: self.verification.clone(),
+            block_hash: self.block_hash,
+            missing_preimages: self.missing_preimages.clone(),
+        })
+    }
+}
+
+/// Errors of the binary encoding of execution witnesses, see [`EncodedExecutionWitness`].
+#[derive(Debug, thiserror::Error)]
+pub enum WitnessEncodingError {
+    /// A value of the witness exceeds the maximum length of the framing.
+    #[error("witness value of {0} bytes exceeds the maximum length")]
+    TooLarge(usize),
+    /// The witness is a JSON object.
+    #[error("JSON witnesses aren't binary encoded")]
+    NotBinary,
+    /// The data ends before all entries of the header are decoded.
+    #[error("witness data is truncated")]
+    Truncated,
+    /// The data has bytes after all entries of the header.
+    #[error("witness data has {0} trailing bytes")]
+    TrailingBytes(usize),
+    /// The size of the uncompressed data differs from the header.
+    #[error("witness data has {actual} bytes, expected {expected}")]
+    SizeMismatch {
+        /// The size of the header.
+        expected: u64,
+        /// The size of the data.
+        actual: u64,
+    },
+    /// The snappy compression or decompression failed.
+    #[error(transparent)]
+    Snappy(#[from] snap::Error),
+}
+
+/// Appends the value, prefixed with its length, to the binary framing of a witness.
+fn write_value(data: &mut Vec<u8>, value: &[u8]) -> Result<(), WitnessEncodingError> {
+    let len =
+        u32::try_from(value.len()).map_err(|_| WitnessEncodingError::TooLarge(value.len()))?;
+    data.extend_from_slice(&len.to_be_bytes());
+    data.extend_from_slice(value);
+    Ok(())
+}
+
+/// Reads a value, prefixed with its length, from the binary framing of a witness.
+fn read_value(data: &mut &[u8]) -> Result<Bytes, WitnessEncodingError> {
+    let mut len = [0; 4];
+    len.copy_from_slice(take(data, len.len())?);
+    take(data, u32::from_be_bytes(len) as usize).map(Bytes::copy_from_slice)
+}
+
+/// Splits off the given number of bytes from the binary framing of a witness.
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], WitnessEncodingError> {
+    if data.len() < len {
+        return Err(WitnessEncodingError::Truncated)
+    }
+    let (value, rest) = data.split_at(len);
+    *data = rest;
+    Ok(value)
+}
+
+/// The result of the verification of the re-execution of a block of `debug_executionWitness`
+/// against the header of the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum WitnessVerification {
+    /// The re-execution leads to the state root and gas used of the header.
+    Verified {
+        /// The verified post-state root of the block.
+        #[serde(rename = "stateRoot")]
+        state_root: B256,
+    },
+    /// The re-execution diverges from the block.
+    Mismatch(Box<WitnessMismatch>),
+}
+
+/// Diagnostics of a re-execution of a block that diverges from the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessMismatch {
+    /// The state root of the header.
+    pub expected_state_root: B256,
+    /// The state root computed from the re-execution.
+    pub computed_state_root: B256,
+    /// The gas used of the header.
+    #[serde(with = "alloy_serde::quantity")]
+    pub expected_gas_used: u64,
+    /// The gas used by the re-executed transactions.
+    #[serde(with = "alloy_serde::quantity")]
+    pub computed_gas_used: u64,
+    /// The accounts accessed by the block whose re-executed values differ from the stored
+    /// post-state of the block, in ascending order.
+    ///
+    /// `None` if the post-state of the block isn't available, e.g. because it was never executed
+    /// by the node.
+    pub diverged_accounts: Option<Vec<Address>>,
+    /// The index of the first transaction whose re-executed receipt differs from the stored
+    /// receipt, i.e. the first transaction after which the divergence is detectable.
+    ///
+    /// `None` if the receipts of the block aren't available or all receipts match, in which case
+    /// the divergence is in the pre- or post-block changes.
+    #[serde(default, with = "alloy_serde::quantity::opt")]
+    pub first_diverged_transaction: Option<u64>,
+}
+
+/// The execution witness of a block of a `debug_executionWitnessRange` request, or the error that
+/// prevented its generation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExecutionWitnessResult {
+    /// The witness of the block.
+    Success {
+        /// The number of the block.
+        #[serde(rename = "blockNumber", with = "alloy_serde::quantity")]
+        block_number: u64,
+        /// The execution witness.
+        result: DebugExecutionWitness,
+    },
+    /// The error that prevented the generation of the witness.
+    Error {
+        /// The number of the block.
+        #[serde(rename = "blockNumber", with = "alloy_serde::quantity")]
+        block_number: u64,
+        /// The error message.
+        error: String,
+    },
+}
+
+impl ExecutionWitnessResult {
+    /// Returns the number of the block.
+    pub const fn block_number(&self) -> u64 {
+        match self {
+            Self::Success { block_number, .. } | Self::Error { block_number, .. } => *block_number,
+        }
+    }
+}
+
+/// The result of `debug_executeWitness`, the stateless execution of a block against an execution
+/// witness.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum StatelessExecutionResult {
+    /// The execution leads to the state root, receipts root and gas used of the header.
+    Valid {
+        /// The post-state root of the block.
+        #[serde(rename = "stateRoot")]
+        state_root: B256,
+        /// The receipts root of the block.
+        #[serde(rename = "receiptsRoot")]
+        receipts_root: B256,
+        /// The gas used by the block.
+        #[serde(rename = "gasUsed", with = "alloy_serde::quantity")]
+        gas_used: u64,
+    },
+    /// The execution diverges from the header.
+    Mismatch(Box<StatelessExecutionMismatch>),
+    /// The witness lacks data that is required by the execution.
+    Incomplete {
+        /// The data missing in the witness, in the order it was accessed.
+        #[serde(rename = "missingKeys")]
+        missing_keys: Vec<MissingWitnessKey>,
+    },
+}
+
+/// The values of a stateless execution of a block that diverges from the header of the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatelessExecutionMismatch {
+    /// The state root of the header.
+    pub expected_state_root: B256,
+    /// The state root computed from the execution.
+    pub computed_state_root: B256,
+    /// The receipts root of the header.
+    pub expected_receipts_root: B256,
+    /// The receipts root computed from the execution.
+    pub computed_receipts_root: B256,
+    /// The gas used of the header.
+    #[serde(with = "alloy_serde::quantity")]
+    pub expected_gas_used: u64,
+    /// The gas used by the executed transactions.
+    #[serde(with = "alloy_serde::quantity")]
+    pub computed_gas_used: u64,
+}
+
+/// Data that is required by the stateless execution of a block, but missing in the execution
+/// witness.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MissingWitnessKey {
+    /// A node of the state trie on the path to an account.
+    AccountNode {
+        /// The accessed account.
+        address: Address,
+        /// The hash of the missing node.
+        hash: B256,
+    },
+    /// A node of the storage trie of an account on the path to a storage slot.
+    StorageNode {
+        /// The account of the storage trie.
+        address: Address,
+        /// The accessed storage slot.
+        slot: B256,
+        /// The hash of the missing node.
+        hash: B256,
+    },
+    /// The bytecode of a contract.
+    Code {
+        /// The hash of the missing code.
+        #[serde(rename = "codeHash")]
+        code_hash: B256,
+    },
+    /// The hash of an ancestor block, accessed via `BLOCKHASH`.
+    BlockHash {
+        /// The number of the block.
+        #[serde(with = "alloy_serde::quantity")]
+        number: u64,
+    },
+}
+
+impl fmt::Display for MissingWitnessKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AccountNode { address, hash } => {
+                write!(f, "trie node {hash} on the path to account {address}")
+            }
+            Self::StorageNode { address, slot, hash } => {
+                write!(
+                    f,
+                    "storage trie node {hash} on the path to slot {slot} of account {address}"
+                )
+            }
+            Self::Code { code_hash } => write!(f, "code {code_hash}"),
+            Self::BlockHash { number } => write!(f, "hash of block {number}"),
+        }
+    }
+}
+
+/// The accounts and storage slots accessed by the execution of a block, including its system
+/// calls and withdrawals, as returned by `debug_getBlockAccessList`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockAccessList {
+    /// The accessed accounts, ordered by address.
+    pub accounts: Vec<AccountAccess>,
+}
+
+/// The accesses of the execution of a block to an account.
+///
+/// An account or slot counts as written if the block changes it. Accounts and slots that the
+/// block only reads, or writes back to their original values, count as read.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountAccess {
+    /// The address of the account.
+    pub address: Address,
+    /// Whether the block changes the balance, nonce or code of the account, or destroys it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub written: bool,
+    /// The slots of the account that are only read, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub storage_reads: Vec<B256>,
+    /// The slots of the account whose values are changed, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub storage_writes: Vec<B256>,
+}
+
+/// The result of `debug_replayBlock`, the comparison of the re-execution of a block with the
+/// header and the stored receipts and post-state of the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReplayReport {
+    /// Whether the re-execution leads to the state root, receipts root and gas used of the
+    /// header, and to the stored receipts.
+    pub matches: bool,
+    /// The hash of the replayed block.
+    pub block_hash: B256,
+    /// The number of the replayed block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub block_number: u64,
+    /// The state root of the header.
+    pub expected_state_root: B256,
+    /// The state root computed from the re-execution.
+    pub computed_state_root: B256,
+    /// The receipts root of the header.
+    pub expected_receipts_root: B256,
+    /// The receipts root computed from the re-executed receipts.
+    pub computed_receipts_root: B256,
+    /// The gas used of the header.
+    #[serde(with = "alloy_serde::quantity")]
+    pub expected_gas_used: u64,
+    /// The gas used by the re-executed transactions.
+    #[serde(with = "alloy_serde::quantity")]
+    pub computed_gas_used: u64,
+    /// The first transaction whose re-executed receipt differs from the stored receipt.
+    ///
+    /// `None` if the receipts of the block aren't available or all receipts match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_diverged_receipt: Option<ReceiptDivergence>,
+    /// The accounts accessed by the block whose re-executed values differ from the stored
+    /// post-state of the block, in ascending order.
+    ///
+    /// Empty if the computed state root matches the header, `None` if it doesn't and the
+    /// post-state of the block isn't available, e.g. because it was never executed by the node.
+    pub diverged_accounts: Option<Vec<Address>>,
+}
+
+/// A receipt of a replayed block that differs from the stored receipt of the transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptDivergence {
+    /// The index of the transaction in the block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub transaction_index: u64,
+    /// The stored receipt.
+    pub expected: ReplayedReceipt,
+    /// The re-executed receipt.
+    pub computed: ReplayedReceipt,
+}
+
+/// The fields of a receipt that are compared by `debug_replayBlock`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayedReceipt {
+    /// Whether the transaction succeeded.
+    pub status: bool,
+    /// The gas used by the transaction.
+    #[serde(with = "alloy_serde::quantity")]
+    pub gas_used: u64,
+    /// The gas used by the block up to and including the transaction.
+    #[serde(with = "alloy_serde::quantity")]
+    pub cumulative_gas_used: u64,
+    /// The bloom filter of the logs of the transaction.
+    pub logs_bloom: Bloom,
+}
+
+/// A modification of a transaction of the block traced by `debug_traceBlockWithModifications`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionModification {
+    /// The index of the transaction in the block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub index: u64,
+    /// The call that is executed instead of the transaction, from the sender of the transaction,
+    /// or `None` to drop the transaction.
+    pub replacement: Option<TransactionRequest>,
+}
+
+/// How a transaction of the block traced by `debug_traceBlockWithModifications` was modified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionModificationKind {
+    /// The transaction was replaced by a call.
+    Replaced,
+    /// The transaction was dropped, it has no trace.
+    Dropped,
+}
+
+/// The trace of a position of the block traced by `debug_traceBlockWithModifications`.
+///
+/// This serializes like the [`TraceResult`] of the transaction, with the marker of the
+/// modification of the position, if any.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModifiedTraceResult {
+    /// The hash of the transaction of the block at this position.
+    pub tx_hash: B256,
+    /// The modification of the transaction, `None` if it was executed as is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modification: Option<TransactionModificationKind>,
+    /// The trace of the transaction, or of its replacement.
+    ///
+    /// `None` if the transaction was dropped, or if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GethTrace>,
+    /// The error the transaction, or its replacement, failed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The kinds of subscriptions of `debug_subscribe`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DebugSubscriptionKind {
+    /// The traces of every new canonical block, see [`BlockTraces`].
+    NewBlockTraces,
+}
+
+/// A notification of a `debug_subscribe` subscription.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DebugSubscriptionResult {
+    /// The traces of a new canonical block.
+    BlockTraces(BlockTraces),
+    /// The blocks that were not traced because the subscriber lagged behind.
+    SkippedBlocks(SkippedBlocks),
+}
+
+/// The traces of the transactions of a new canonical block, pushed by the `newBlockTraces`
+/// subscription.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTraces {
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The number of the block.
+    #[serde(with = "alloy_serde::quantity")]
+    pub block_number: u64,
+    /// The traces of the transactions of the block, in block order.
+    pub traces: Vec<TraceResult>,
+    /// Whether the block became canonical by a reorg, replacing previously pushed blocks.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reorg: bool,
+}
+
+/// The number of new canonical blocks that were not traced because the subscriber didn't keep up
+/// with the notifications, pushed before the traces of the next block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedBlocks {
+    /// The number of skipped blocks.
+    #[serde(with = "alloy_serde::quantity")]
+    pub skipped_blocks: u64,
+}
+
+/// A notification of a `debug_subscribeTraceBlock` subscription.
+///
+/// If requested, the progress of the trace is pushed while the block is traced. The subscription
+/// ends after the result or the error of the trace.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceBlockNotification {
+    /// The progress of the trace.
+    Progress(TraceProgress),
+    /// The result of the trace, same as the response of `debug_traceBlockByHash`.
+    Result(DebugTraceBlockResponse),
+    /// The error the trace failed with.
+    Error(String),
+}
+
+/// The progress of a block trace, pushed by `debug_subscribeTraceBlock`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceProgress {
+    /// The number of transactions traced so far.
+    #[serde(with = "alloy_serde::quantity")]
+    pub traced_transactions: u64,
+    /// The hash of the transaction that was traced last, or is currently traced.
+    pub current_transaction: Option<B256>,
+    /// The time since the trace was requested, in microseconds.
+    pub elapsed_micros: u64,
+}
+
+/// The block or transaction whose trace cost is estimated by `debug_estimateTraceCost`.
+///
+/// A hash is taken to be the hash of a transaction, blocks are identified by number, tag, or the
+/// EIP-1898 `{ "blockHash": .. }` object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraceCostTarget {
+    /// The transaction with the hash.
+    Transaction(B256),
+    /// The block.
+    Block(BlockId),
+}
+
+/// The estimated cost of a trace, returned by `debug_estimateTraceCost`.
+///
+/// The number of struct logs is estimated from the gas used, the actual number depends on the
+/// executed opcodes. Their size is estimated like the trace memory budget of the node does.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCostEstimate {
+    /// The gas used by the block or the transaction.
+    #[serde(with = "alloy_serde::quantity")]
+    pub gas_used: u64,
+    /// The number of transactions of the block, or one for a transaction.
+    #[serde(with = "alloy_serde::quantity")]
+    pub transaction_count: u64,
+    /// The estimated number of struct logs of the trace, if traced with the struct logger.
+    pub estimated_struct_logs: u64,
+    /// The estimated size of the struct logs, in bytes.
+    pub estimated_size: u64,
+    /// The estimated memory of the traces of an identical request, in bytes, if they're in the
+    /// trace cache of the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_size: Option<u64>,
+    /// Whether the node would reject the trace.
+    pub would_be_rejected: bool,
+    /// The error the trace would be rejected with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// The effective persistence settings of the node, returned by `debug_getTrieFlushInterval`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrieFlushInterval {
+    /// The accumulated block processing time after which the in-memory state is persisted, as a
+    /// Go duration string like `1h0m0s`, unset if it's persisted based on the block thresholds
+    /// only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    /// The number of blocks that are kept in memory once the in-memory state is persisted.
+    #[serde(with = "alloy_serde::quantity")]
+    pub memory_block_buffer_target: u64,
+    /// The number of blocks beyond the buffer target after which the in-memory state is persisted.
+    #[serde(with = "alloy_serde::quantity")]
+    pub persistence_threshold: u64,
+    /// Whether the settings can't be changed via `debug_setTrieFlushInterval`.
+    pub read_only: bool,
+}
+
+/// The tables of the database and the static files of the node, returned by `debug_dbTables`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTables {
+    /// The tables of the database, ordered by name.
+    pub tables: Vec<DbTableStats>,
+    /// The segments of the static files, with the block ranges of their files.
+    pub static_files: Vec<StaticFileSegmentRanges>,
+}
+
+/// The statistics of a table of the database.
+///
+/// The database only keeps track of the pages of a table, so that the sizes are those of its
+/// pages rather than the exact sizes of its keys and values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTableStats {
+    /// The name of the table.
+    pub name: String,
+    /// Whether the table stores multiple values per key.
+    pub dupsort: bool,
+    /// The number of entries of the table.
+    #[serde(with = "alloy_serde::quantity")]
+    pub entries: u64,
+    /// The size of the pages holding the keys and values of the table, in bytes.
+    #[serde(with = "alloy_serde::quantity")]
+    pub leaf_size: u64,
+    /// The size of the pages holding the index of the table, in bytes.
+    #[serde(with = "alloy_serde::quantity")]
+    pub branch_size: u64,
+    /// The size of the pages holding values that don't fit into a single page, in bytes.
+    #[serde(with = "alloy_serde::quantity")]
+    pub overflow_size: u64,
+    /// The total size of the table, in bytes.
+    #[serde(with = "alloy_serde::quantity")]
+    pub total_size: u64,
+}
+
+/// The static files of a segment, e.g. `headers`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticFileSegmentRanges {
+    /// The name of the segment.
+    pub segment: String,
+    /// The inclusive block ranges of the files of the segment, in ascending order.
+    pub block_ranges: Vec<BlockRange>,
+}
+
+/// An inclusive range of blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockRange {
+    /// The first block of the range.
+    #[serde(with = "alloy_serde::quantity")]
+    pub start: u64,
+    /// The last block of the range.
+    #[serde(with = "alloy_serde::quantity")]
+    pub end: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn serialize_block_access_list() {
+        let list = BlockAccessList {
+            accounts: vec![
+                AccountAccess { address: Address::with_last_byte(1), ..Default::default() },
+                AccountAccess {
+                    address: Address::with_last_byte(2),
+                    written: true,
+                    storage_reads: vec![B256::with_last_byte(1)],
+                    storage_writes: vec![B256::ZERO],
+                },
+            ],
+        };
+        let json = serde_json::to_value(&list).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "accounts": [
+                    { "address": Address::with_last_byte(1) },
+                    {
+                        "address": Address::with_last_byte(2),
+                        "written": true,
+                        "storageReads": [B256::with_last_byte(1)],
+                        "storageWrites": [B256::ZERO],
+                    },
+                ]
+            })
+        );
+        assert_eq!(serde_json::from_value::<BlockAccessList>(json).unwrap(), list);
+    }
+
+    #[test]
+    fn deserialize_trace_cost_target() {
+        let hash = B256::with_last_byte(1);
+        let target = |json| serde_json::from_value::<TraceCostTarget>(json).unwrap();
+        assert_eq!(target(serde_json::json!(hash)), TraceCostTarget::Transaction(hash));
+        assert_eq!(target(serde_json::json!("latest")), TraceCostTarget::Block(BlockId::latest()));
+        assert_eq!(target(serde_json::json!("0x5")), TraceCostTarget::Block(BlockId::number(5)));
+        assert_eq!(
+            target(serde_json::json!({ "blockHash": hash })),
+            TraceCostTarget::Block(BlockId::hash(hash))
+        );
+    }
+
+    #[test]
+    fn deserialize_geth_call_options() {
+        let s = r#"{"tracer": "callTracer", "tracerConfig": {"onlyTopCall": true}}"#;
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        assert_eq!(opts.call_options, serde_json::from_str(s).unwrap());
+        assert_eq!(opts.tx_index, None);
+        assert!(opts.extras.is_empty());
+    }
+
+    #[test]
+    fn deserialize_tx_index() {
+        let s = r#"{"tracer": "callTracer", "txIndex": "0x2"}"#;
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        assert_eq!(opts.tx_index, Some(2));
+        assert!(opts.call_options.tracing_options.tracer.is_some());
+    }
+
+    #[test]
+    fn deserialize_build_pending_from_pool() {
+        let s = r#"{"tracer": "callTracer", "buildPendingFromPool": true}"#;
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        assert!(opts.build_pending_from_pool);
+        assert!(opts.extras.is_empty());
+        assert_eq!(serde_json::to_value(&opts).unwrap()["buildPendingFromPool"], true);
+
+        let res = DebugTraceCallResult {
+            pool_transactions: Some(vec![B256::with_last_byte(1)]),
+            ..DebugTraceCallResult::new(GethTrace::Default(Default::default()))
+        };
+        let json = serde_json::to_value(&res).unwrap();
+        assert_eq!(json["poolTransactions"][0], B256::with_last_byte(1).to_string());
+        assert_eq!(serde_json::from_value::<DebugTraceCallResponse>(json).unwrap(), res.into());
+    }
+
+    #[test]
+    fn serde_cfg_overrides() {
+        let s = r#"{"tracer": "callTracer",
+            "cfgOverrides": {"spec": "prague", "chainId": "0x1", "blobBaseFee": "0x7"}}"#;
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        let cfg_overrides = CfgOverrides {
+            spec: Some("prague".to_string()),
+            chain_id: Some(1),
+            blob_base_fee: Some(7),
+        };
+        assert_eq!(opts.cfg_overrides, Some(cfg_overrides.clone()));
+        assert!(opts.call_options.tracing_options.tracer.is_some());
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>(s).unwrap();
+        assert_eq!(opts.cfg_overrides, Some(cfg_overrides));
+
+        let res = DebugTraceCallResult {
+            spec: Some("Prague".to_string()),
+            ..DebugTraceCallResult::new(GethTrace::Default(Default::default()))
+        };
+        let json = serde_json::to_value(&res).unwrap();
+        assert_eq!(json["spec"], "Prague");
+        assert_eq!(serde_json::from_value::<DebugTraceCallResponse>(json).unwrap(), res.into());
+    }
+
+    #[test]
+    fn deserialize_block_options() {
+        let s = r#"{"tracer": "callTracer", "txHashes": ["0x0000000000000000000000000000000000000000000000000000000000000001"]}"#;
+        let opts = serde_json::from_str::<DebugTracingBlockOptions>(s).unwrap();
+        assert_eq!(opts.tx_hashes, vec![B256::with_last_byte(1)]);
+        assert!(opts.tracing_options.tracer.is_some());
+
+        let opts = serde_json::from_str::<DebugTracingBlockOptions>("{}").unwrap();
+        assert!(opts.tx_hashes.is_empty());
+        assert_eq!(opts.result_format, TraceResultFormat::Array);
+        assert!(!opts.no_cache);
+        assert!(!opts.include_post_block);
+        assert!(!opts.verbose);
+
+        let opts =
+            serde_json::from_str::<DebugTracingBlockOptions>(r#"{"resultFormat": "map"}"#).unwrap();
+        assert_eq!(opts.result_format, TraceResultFormat::Map);
+
+        let opts =
+            serde_json::from_str::<DebugTracingBlockOptions>(r#"{"includePostBlock": true}"#)
+                .unwrap();
+        assert!(opts.include_post_block);
+    }
+
+    #[test]
+    fn serialize_post_block_trace() {
+        let trace = PostBlockTrace {
+            withdrawals: vec![WithdrawalCredit {
+                address: Address::with_last_byte(1),
+                amount: U256::from(2),
+                balance: U256::from(3),
+            }],
+        };
+        let value = serde_json::to_value(&trace).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "withdrawals": [{
+                    "address": Address::with_last_byte(1),
+                    "amount": "0x2",
+                    "balance": "0x3"
+                }]
+            })
+        );
+
+        let result = TraceResult::Error {
+            error: "error".to_string(),
+            tx_hash: Some(B256::with_last_byte(1)),
+        };
+        // the array and map formats are wrapped, so that the results keep their shape
+        for format in [TraceResultFormat::Array, TraceResultFormat::Map] {
+            let results = DebugTraceBlockResponse::new(vec![result.clone()], format);
+            let res = results.clone().with_post_block(Some(trace.clone()));
+            assert_eq!(res.post_block(), Some(&trace));
+            let json = serde_json::to_value(&res).unwrap();
+            assert_eq!(json["results"], serde_json::to_value(&results).unwrap());
+            assert_eq!(json["postBlock"], value);
+            assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(json).unwrap(), res);
+            assert_eq!(res.into_results(), vec![result.clone()]);
+        }
+
+        // the other formats report them in a field of their own
+        let res = DebugTraceBlockResponse::Verbose(VerboseBlockTrace::new(vec![result.into()]))
+            .with_post_block(Some(trace.clone()));
+        assert_eq!(res.post_block(), Some(&trace));
+        let json = serde_json::to_value(&res).unwrap();
+        assert_eq!(json["postBlock"], value);
+        assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(json).unwrap(), res);
+
+        let res = DebugTraceBlockResponse::Array(Vec::new());
+        assert_eq!(res.clone().with_post_block(None), res);
+        assert_eq!(res.post_block(), None);
+    }
+
+    #[test]
+    fn serde_trace_block_response() {
+        let results = vec![
+            TraceResult::Success {
+                result: GethTrace::Default(Default::default()),
+                tx_hash: Some(B256::with_last_byte(2)),
+            },
+            TraceResult::Error {
+                error: "error".to_string(),
+                tx_hash: Some(B256::with_last_byte(1)),
+            },
+        ];
+
+        let res = DebugTraceBlockResponse::new(results.clone(), TraceResultFormat::Array);
+        let s = serde_json::to_string(&res).unwrap();
+        assert!(s.starts_with('['));
+        assert_eq!(serde_json::from_str::<DebugTraceBlockResponse>(&s).unwrap(), res);
+
+        // the map is ordered by transaction hash
+        let res = DebugTraceBlockResponse::new(results, TraceResultFormat::Map);
+        let s = serde_json::to_string(&res).unwrap();
+        let first = format!(r#"{{"{}":{{"error":"error""#, B256::with_last_byte(1));
+        assert!(s.starts_with(&first));
+        assert_eq!(serde_json::from_str::<DebugTraceBlockResponse>(&s).unwrap(), res);
+    }
+
+    #[test]
+    fn serde_verbose_trace_block_response() {
+        let result = TraceResult::Error {
+            error: "error".to_string(),
+            tx_hash: Some(B256::with_last_byte(1)),
+        };
+        let stats =
+            TraceStats { duration_micros: 7, gas_used: 21000, trace_size: 3, counters: None };
+        let results = vec![
+            TraceResultWithStats { result: result.clone(), stats: Some(stats) },
+            TraceResultWithStats {
+                result: TraceResult::Success {
+                    result: GethTrace::Default(Default::default()),
+                    tx_hash: Some(B256::with_last_byte(2)),
+                },
+                stats: Some(TraceStats { duration_micros: 9, ..stats }),
+            },
+        ];
+
+        // the results without stats are plain geth results
+        let res =
+            DebugTraceBlockResponse::with_stats(results.clone(), TraceResultFormat::Array, false);
+        assert_eq!(
+            res,
+            DebugTraceBlockResponse::Array(results.iter().map(|res| res.result.clone()).collect())
+        );
+        assert_eq!(
+            serde_json::to_value(TraceResultWithStats::from(result.clone())).unwrap(),
+            serde_json::to_value(&result).unwrap()
+        );
+
+        let res = DebugTraceBlockResponse::with_stats(results, TraceResultFormat::Map, true);
+        let DebugTraceBlockResponse::Verbose(trace) = &res else {
+            panic!("expected verbose response")
+        };
+        assert_eq!(
+            trace.summary,
+            BlockTraceSummary {
+                total_duration_micros: 16,
+                slowest_tx_hash: Some(B256::with_last_byte(2)),
+                execution_stats: None,
+            }
+        );
+
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(
+            value["results"][0],
+            serde_json::json!({
+                "error": "error",
+                "txHash": B256::with_last_byte(1),
+                "stats": { "durationMicros": 7, "gasUsed": "0x5208", "traceSize": 3 }
+            })
+        );
+        assert_eq!(value["summary"]["totalDurationMicros"], 16);
+        assert!(value["summary"].get("executionStats").is_none());
+        assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(value).unwrap(), res);
+
+        let mut res = res;
+        let DebugTraceBlockResponse::Verbose(trace) = &mut res else { unreachable!() };
+        trace.summary.execution_stats =
+            Some(ExecutionStats { account_provider_reads: 2, ..Default::default() });
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value["summary"]["executionStats"]["accountProviderReads"], 2);
+        assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(value).unwrap(), res);
+
+        let DebugTraceBlockResponse::Verbose(trace) = &mut res else { unreachable!() };
+        trace.results[0].stats.as_mut().unwrap().counters =
+            Some(TraceCounters { steps: 10, subcalls: 1, ..Default::default() });
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value["results"][0]["stats"]["counters"]["steps"], 10);
+        assert_eq!(value["results"][0]["stats"]["counters"]["subcalls"], 1);
+        assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(value).unwrap(), res);
+    }
+
+    #[test]
+    fn serde_trace_block_response_with_context() {
+        let context = BlockTraceContext {
+            hash: B256::with_last_byte(9),
+            number: 10,
+            timestamp: 12,
+            base_fee_per_gas: Some(7),
+            gas_used: 21000,
+            transaction_count: 2,
+        };
+        let result = TraceResult::Error {
+            error: "error".to_string(),
+            tx_hash: Some(B256::with_last_byte(1)),
+        };
+        let res = DebugTraceBlockResponse::WithContext(BlockTraceWithContext {
+            context,
+            results: vec![result.clone()],
+            post_block: None,
+        });
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "hash": B256::with_last_byte(9),
+                "number": "0xa",
+                "timestamp": "0xc",
+                "baseFeePerGas": "0x7",
+                "gasUsed": "0x5208",
+                "transactionCount": "0x2",
+                "results": [{ "error": "error", "txHash": B256::with_last_byte(1) }]
+            })
+        );
+        assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(value).unwrap(), res);
+        assert_eq!(res.into_results(), vec![result.clone()]);
+
+        // the verbose trace carries the same context
+        let mut trace = VerboseBlockTrace::new(vec![result.clone().into()]);
+        let value = serde_json::to_value(&trace).unwrap();
+        assert!(value.get("hash").is_none());
+        trace.context = Some(BlockTraceContext { base_fee_per_gas: None, ..context });
+        let value = serde_json::to_value(DebugTraceBlockResponse::Verbose(trace.clone())).unwrap();
+        assert_eq!(value["number"], "0xa");
+        assert!(value.get("baseFeePerGas").is_none());
+        assert_eq!(
+            serde_json::from_value::<DebugTraceBlockResponse>(value).unwrap(),
+            DebugTraceBlockResponse::Verbose(trace)
+        );
+
+        // the traces of `debug_traceChain` carry the same context
+        let chain = BlockTraceResult { context, traces: vec![result] };
+        let value = serde_json::to_value(&chain).unwrap();
+        assert_eq!(value["hash"], serde_json::json!(B256::with_last_byte(9)));
+        assert_eq!(value["transactionCount"], "0x2");
+        assert_eq!(value["traces"][0]["error"], "error");
+        assert_eq!(serde_json::from_value::<BlockTraceResult>(value).unwrap(), chain);
+    }
+
+    #[test]
+    fn serde_trace_block_page() {
+        let cursor = BlockTraceCursor { block_hash: B256::with_last_byte(9), tx_index: 258 };
+        let json = serde_json::to_value(cursor).unwrap();
+        assert_eq!(json, format!("0x{}{}", "00".repeat(31), "090000000000000102"));
+        assert_eq!(serde_json::from_value::<BlockTraceCursor>(json).unwrap(), cursor);
+        assert!(serde_json::from_str::<BlockTraceCursor>(r#""0x0102""#).is_err());
+
+        let opts: DebugTracingBlockOptions =
+            serde_json::from_value(serde_json::json!({ "maxTransactions": 2 })).unwrap();
+        assert!(opts.is_paginated());
+        assert!(!DebugTracingBlockOptions::default().is_paginated());
+
+        let result = TraceResult::Error {
+            error: "error".to_string(),
+            tx_hash: Some(B256::with_last_byte(1)),
+        };
+        for next_cursor in [Some(cursor), None] {
+            let res = DebugTraceBlockResponse::Page(BlockTracePage {
+                results: vec![result.clone().into()],
+                next_cursor,
+                post_block: None,
+            });
+            let value = serde_json::to_value(&res).unwrap();
+            assert_eq!(value["results"][0]["error"], "error");
+            assert_eq!(value.get("nextCursor").is_some(), next_cursor.is_some());
+            assert_eq!(serde_json::from_value::<DebugTraceBlockResponse>(value).unwrap(), res);
+            assert_eq!(res.into_results(), vec![result.clone()]);
+        }
+    }
+
+    #[test]
+    fn serde_execution_witness_codes() {
+        let witness = ExecutionWitness {
+            witness: HashMap::from([(B256::with_last_byte(1), Bytes::from_static(&[1]))]),
+            state_preimages: None,
+        };
+
+        // without codes, this is the plain witness
+        let res = DebugExecutionWitness::from(witness.clone());
+        assert_eq!(serde_json::to_value(&res).unwrap(), serde_json::to_value(&witness).unwrap());
+
+        let codes = BTreeMap::from([(B256::with_last_byte(2), Bytes::from_static(&[0x60]))]);
+        let res = DebugExecutionWitness { witness, codes: Some(codes), ..Default::default() };
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value["codes"][B256::with_last_byte(2).to_string()], "0x60");
+        assert_eq!(serde_json::from_value::<DebugExecutionWitness>(value).unwrap(), res);
+    }
+
+    #[test]
+    fn serde_execution_witness_options() {
+        let opts = serde_json::from_str::<ExecutionWitnessOptions>("{}").unwrap();
+        assert_eq!(opts, ExecutionWitnessOptions::default());
+
+        let s = r#"{"includeCodes": true, "noCache": true, "encoding": "snappy"}"#;
+        let opts = serde_json::from_str::<ExecutionWitnessOptions>(s).unwrap();
+        let expected = ExecutionWitnessOptions {
+            include_codes: true,
+            no_cache: true,
+            encoding: WitnessEncoding::Snappy,
+            ..Default::default()
+        };
+        assert_eq!(opts, expected);
+        assert_eq!(
+            serde_json::to_value(opts).unwrap(),
+            serde_json::from_str::<serde_json::Value>(s).unwrap()
+        );
+    }
+
+    #[test]
+    fn serde_witness_verification() {
+        let verified = WitnessVerification::Verified { state_root: B256::with_last_byte(1) };
+        let value = serde_json::to_value(&verified).unwrap();
+        assert_eq!(value["status"], "verified");
+        assert_eq!(value["stateRoot"], B256::with_last_byte(1).to_string());
+
+        let mismatch = WitnessVerification::Mismatch(Box::new(WitnessMismatch {
+            expected_state_root: B256::with_last_byte(1),
+            computed_state_root: B256::with_last_byte(2),
+            expected_gas_used: 21_000,
+            computed_gas_used: 42_000,
+            diverged_accounts: Some(vec![Address::with_last_byte(3)]),
+            first_diverged_transaction: Some(1),
+        }));
+        let value = serde_json::to_value(&mismatch).unwrap();
+        assert_eq!(value["status"], "mismatch");
+        assert_eq!(value["computedGasUsed"], "0xa410");
+        assert_eq!(value["firstDivergedTransaction"], "0x1");
+        assert_eq!(serde_json::from_value::<WitnessVerification>(value).unwrap(), mismatch);
+    }
+
+    #[test]
+    fn serde_stateless_execution_result() {
+        let res = StatelessExecutionResult::Valid {
+            state_root: B256::with_last_byte(1),
+            receipts_root: B256::with_last_byte(2),
+            gas_used: 21_000,
+        };
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value["status"], "valid");
+        assert_eq!(value["gasUsed"], "0x5208");
+        assert_eq!(serde_json::from_value::<StatelessExecutionResult>(value).unwrap(), res);
+
+        let res = StatelessExecutionResult::Incomplete {
+            missing_keys: vec![
+                MissingWitnessKey::StorageNode {
+                    address: Address::with_last_byte(1),
+                    slot: B256::ZERO,
+                    hash: B256::with_last_byte(3),
+                },
+                MissingWitnessKey::BlockHash { number: 16 },
+            ],
+        };
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value["status"], "incomplete");
+        assert_eq!(value["missingKeys"][0]["type"], "storageNode");
+        assert_eq!(value["missingKeys"][1]["number"], "0x10");
+        assert_eq!(serde_json::from_value::<StatelessExecutionResult>(value).unwrap(), res);
+    }
+
+    #[test]
+    fn serde_trie_flush_interval() {
+        let interval = TrieFlushInterval {
+            interval: Some("1m30s".to_string()),
+            memory_block_buffer_target: 2,
+            persistence_threshold: 16,
+            read_only: true,
+        };
+        let value = serde_json::to_value(&interval).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "interval": "1m30s",
+                "memoryBlockBufferTarget": "0x2",
+                "persistenceThreshold": "0x10",
+                "readOnly": true,
+            })
+        );
+        assert_eq!(serde_json::from_value::<TrieFlushInterval>(value).unwrap(), interval);
+
+        let interval = TrieFlushInterval { interval: None, ..interval };
+        let value = serde_json::to_value(&interval).unwrap();
+        assert!(value.get("interval").is_none());
+        assert_eq!(serde_json::from_value::<TrieFlushInterval>(value).unwrap(), interval);
+    }
+
+    #[test]
+    fn serde_db_tables() {
+        let tables = DbTables {
+            tables: vec![DbTableStats {
+                name: "PlainStorageState".to_string(),
+                dupsort: true,
+                entries: 3,
+                leaf_size: 4096,
+                branch_size: 0,
+                overflow_size: 0,
+                total_size: 4096,
+            }],
+            static_files: vec![StaticFileSegmentRanges {
+                segment: "headers".to_string(),
+                block_ranges: vec![
+                    BlockRange { start: 0, end: 499_999 },
+                    BlockRange { start: 500_000, end: 500_010 },
+                ],
+            }],
+        };
+        let value = serde_json::to_value(&tables).unwrap();
+        assert_eq!(value["tables"][0]["dupsort"], true);
+        assert_eq!(value["tables"][0]["totalSize"], "0x1000");
+        assert_eq!(value["staticFiles"][0]["segment"], "headers");
+        assert_eq!(value["staticFiles"][0]["blockRanges"][1]["start"], "0x7a120");
+        assert_eq!(serde_json::from_value::<DbTables>(value).unwrap(), tables);
+    }
+
+    #[test]
+    fn serde_block_replay_report() {
+        let receipt = ReplayedReceipt {
+            status: true,
+            gas_used: 21_000,
+            cumulative_gas_used: 42_000,
+            logs_bloom: Bloom::default(),
+        };
+        let report = BlockReplayReport {
+            matches: false,
+            block_hash: B256::with_last_byte(1),
+            block_number: 16,
+            expected_state_root: B256::with_last_byte(2),
+            computed_state_root: B256::with_last_byte(3),
+            expected_receipts_root: B256::with_last_byte(4),
+            computed_receipts_root: B256::with_last_byte(5),
+            expected_gas_used: 42_000,
+            computed_gas_used: 43_000,
+            first_diverged_receipt: Some(ReceiptDivergence {
+                transaction_index: 1,
+                expected: receipt.clone(),
+                computed: ReplayedReceipt { status: false, gas_used: 22_000, ..receipt },
+            }),
+            diverged_accounts: None,
+        };
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["blockNumber"], "0x10");
+        assert_eq!(value["firstDivergedReceipt"]["transactionIndex"], "0x1");
+        assert_eq!(value["firstDivergedReceipt"]["computed"]["status"], false);
+        assert_eq!(value["firstDivergedReceipt"]["expected"]["cumulativeGasUsed"], "0xa410");
+        assert!(value["divergedAccounts"].is_null());
+        assert_eq!(serde_json::from_value::<BlockReplayReport>(value).unwrap(), report);
+    }
+
+    #[test]
+    fn serde_execution_witness_result() {
+        let res = ExecutionWitnessResult::Error { block_number: 2, error: "error".to_string() };
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value, serde_json::json!({ "blockNumber": "0x2", "error": "error" }));
+        assert_eq!(serde_json::from_value::<ExecutionWitnessResult>(value).unwrap(), res);
+
+        let res = ExecutionWitnessResult::Success { block_number: 1, result: Default::default() };
+        let value = serde_json::to_value(&res).unwrap();
+        assert_eq!(value["blockNumber"], "0x1");
+        assert_eq!(serde_json::from_value::<ExecutionWitnessResult>(value).unwrap(), res);
+    }
+
+    #[test]
+    fn deserialize_extras() {
+        let s = r#"{"tracer": "callTracer", "returnStateDiff": true, "includeAccessList": true}"#;
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        assert!(opts.extras.return_state_diff);
+        assert!(opts.extras.include_access_list);
+        assert!(opts.call_options.tracing_options.tracer.is_some());
+    }
+
+    #[test]
+    fn deserialize_checks() {
+        let s =
+            r#"{"tracer": "callTracer", "disableBaseFeeCheck": true, "disableBalanceCheck": true}"#;
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        assert!(opts.checks.disable_base_fee_check);
+        assert!(opts.checks.disable_balance_check);
+
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>(s).unwrap();
+        assert!(opts.checks.disable_base_fee_check);
+        assert!(opts.checks.disable_balance_check);
+        assert!(opts.call_options.tracing_options.tracer.is_some());
+        assert!(!opts.state_overrides_per_bundle);
+    }
+
+    #[test]
+    fn deserialize_mixed_bundle() {
+        let request = r#"{"to": "0x0000000000000000000000000000000000000001"}"#;
+        let s = format!(r#"{{"transactions": [{request}, "0x02f8"]}}"#);
+        let bundle = serde_json::from_str::<DebugBundle>(&s).unwrap();
+        assert!(matches!(
+            bundle.transactions.as_slice(),
+            [BundleTransaction::Request(_), BundleTransaction::Raw(raw)] if raw[..] == [0x02, 0xf8]
+        ));
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(serde_json::from_str::<DebugBundle>(&json).unwrap(), bundle);
+
+        // plain bundles deserialize unchanged
+        let s = format!(r#"{{"transactions": [{request}]}}"#);
+        let plain = serde_json::from_str::<Bundle>(&s).unwrap();
+        assert_eq!(serde_json::from_str::<DebugBundle>(&s).unwrap(), plain.into());
+    }
+
+    #[test]
+    fn deserialize_state_overrides_per_bundle() {
+        let s = r#"{"stateOverrides": {}, "stateOverridesPerBundle": true}"#;
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>(s).unwrap();
+        assert!(opts.state_overrides_per_bundle);
+        assert!(opts.call_options.state_overrides.is_some());
+        assert_eq!(serde_json::to_value(&opts).unwrap()["stateOverridesPerBundle"], true);
+    }
+
+    #[test]
+    fn deserialize_independent_bundles() {
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>("{}").unwrap();
+        assert!(!opts.independent_bundles);
+
+        let s = r#"{"tracer": "callTracer", "independentBundles": true}"#;
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>(s).unwrap();
+        assert!(opts.independent_bundles);
+        assert_eq!(serde_json::to_value(&opts).unwrap()["independentBundles"], true);
+    }
+
+    #[test]
+    fn deserialize_allow_while_syncing() {
+        let s = r#"{"tracer": "callTracer", "allowWhileSyncing": true}"#;
+        let opts = serde_json::from_str::<DebugTracingBlockOptions>(s).unwrap();
+        assert!(opts.allow_while_syncing);
+        assert_eq!(serde_json::to_value(&opts).unwrap()["allowWhileSyncing"], true);
+        assert!(serde_json::from_str::<DebugTracingCallOptions>(s).unwrap().allow_while_syncing);
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>(s).unwrap();
+        assert!(opts.allow_while_syncing);
+
+        let opts = serde_json::from_str::<DebugTracingBlockOptions>("{}").unwrap();
+        assert!(!opts.allow_while_syncing);
+        assert!(serde_json::to_value(&opts).unwrap().get("allowWhileSyncing").is_none());
+    }
+
+    #[test]
+    fn serde_trace_bundle_response() {
+        let s = r#"[{"failed":false,"gas":21000,"returnValue":"","structLogs":[]}]"#;
+        let res = serde_json::from_str::<DebugTraceBundleResponse>(s).unwrap();
+        assert!(matches!(res, DebugTraceBundleResponse::Traces(_)));
+        assert_eq!(res.traces().len(), 1);
+
+        let diff = AccountDiff { nonce: ValueChange::new(0, 1), ..Default::default() };
+        assert!(ValueChange::new(B256::ZERO, B256::ZERO).is_none());
+        let res = DebugTraceBundleResponse::from(DebugTraceBundleResult {
+            traces: Vec::new(),
+            state_diff: Some(BTreeMap::from([(Address::with_last_byte(1), diff)])),
+            spec: None,
+        });
+        let json = serde_json::to_value(&res).unwrap();
+        assert_eq!(json["traces"], serde_json::json!([]));
+        let accounts = json["stateDiff"].as_object().unwrap();
+        assert_eq!(
+            accounts.values().collect::<Vec<_>>(),
+            [&serde_json::json!({ "nonce": { "from": 0, "to": 1 } })]
+        );
+        assert_eq!(serde_json::from_value::<DebugTraceBundleResponse>(json).unwrap(), res);
+    }
+
+    #[test]
+    fn serde_trace_transaction_response() {
+        let opts: DebugTracingTransactionOptions =
+            serde_json::from_str(r#"{"tracer":"prestateTracer","returnStateChanges":true}"#)
+                .unwrap();
+        assert!(opts.return_state_changes);
+        assert!(opts.tracing_options.tracer.is_some());
+
+        let s = r#"{"failed":false,"gas":21000,"returnValue":"","structLogs":[]}"#;
+        let res = serde_json::from_str::<DebugTraceTransactionResponse>(s).unwrap();
+        assert!(matches!(res, DebugTraceTransactionResponse::Trace(_)));
+
+        let created = Address::with_last_byte(1);
+        let res = DebugTraceTransactionResponse::from(DebugTraceTransactionResult {
+            trace: res.into_trace().unwrap(),
+            state_changes: Some(TransactionStateChanges {
+                diff: DiffMode {
+                    pre: BTreeMap::new(),
+                    post: BTreeMap::from([(created, Default::default())]),
+                },
+                created: BTreeSet::from([created]),
+                selfdestructed: BTreeSet::new(),
+            }),
+            stats: None,
+        });
+        let json = serde_json::to_value(&res).unwrap();
+        let changes = &json["stateChanges"];
+        assert_eq!(changes["pre"], serde_json::json!({}));
+        assert_eq!(changes["post"].as_object().unwrap().len(), 1);
+        assert_eq!(changes["created"], serde_json::json!([created]));
+        assert!(changes.get("selfdestructed").is_none());
+        assert!(json.get("stats").is_none());
+        assert_eq!(serde_json::from_value::<DebugTraceTransactionResponse>(json).unwrap(), res);
+
+        // the stats can be returned without the state changes
+        let opts: DebugTracingTransactionOptions =
+            serde_json::from_str(r#"{"includeStats":true}"#).unwrap();
+        assert!(opts.include_stats && !opts.return_state_changes);
+        let res = DebugTraceTransactionResponse::from(DebugTraceTransactionResult {
+            trace: res.into_trace().unwrap(),
+            state_changes: None,
+            stats: Some(TraceCounters { steps: 3, struct_log_bytes: 120, ..Default::default() }),
+        });
+        let json = serde_json::to_value(&res).unwrap();
+        assert!(json.get("stateChanges").is_none());
+        assert_eq!(
+            json["stats"],
+            serde_json::json!({
+                "steps": 3,
+                "structLogBytes": 120,
+                "subcalls": 0,
+                "databaseReads": 0,
+                "stateLoadMicros": 0,
+                "evmExecutionMicros": 0
+            })
+        );
+        assert_eq!(serde_json::from_value::<DebugTraceTransactionResponse>(json).unwrap(), res);
+
+        // pool transactions are marked as pending, including those that couldn't be executed
+        let pending = DebugTraceTransactionResponse::Pending(PendingTransactionTrace {
+            pending: true,
+            result: None,
+            error: Some("nonce too high".to_string()),
+            state_changes: None,
+            stats: None,
+        });
+        let json = serde_json::to_value(&pending).unwrap();
+        assert_eq!(json, serde_json::json!({ "pending": true, "error": "nonce too high" }));
+        assert_eq!(serde_json::from_value::<DebugTraceTransactionResponse>(json).unwrap(), pending);
+        assert_eq!(pending.into_trace(), Err("nonce too high".to_string()));
+    }
+
+    #[test]
+    fn deserialize_struct_logger_config() {
+        let s =
+            r#"{"disableStorage": true, "tracerConfig": {"opcodeFilter": ["SLOAD", "SSTORE"]}}"#;
+        let opts = serde_json::from_str::<GethDebugTracingOptions>(s).unwrap();
+        let config = opts.tracer_config.from_value::<StructLoggerConfig>().unwrap();
+        assert_eq!(config.opcode_filter, vec!["SLOAD", "SSTORE"]);
+        assert!(!config.record_access_status);
+
+        let s = r#"{"tracerConfig": {"recordAccessStatus": true}}"#;
+        let opts = serde_json::from_str::<GethDebugTracingOptions>(s).unwrap();
+        let config = opts.tracer_config.from_value::<StructLoggerConfig>().unwrap();
+        assert!(config.record_access_status);
+    }
+
+    #[test]
+    fn serde_access_status_frame() {
+        let s = r#"{"failed":false,"gas":2103,"returnValue":"00","structLogs":[
+            {"pc":2,"op":"SLOAD","gas":100000,"gasCost":2100,"depth":1,"access":"cold"},
+            {"pc":3,"op":"POP","gas":97900,"gasCost":2,"depth":1}
+        ]}"#;
+        let frame = serde_json::from_str::<AccessStatusFrame>(s).unwrap();
+        let access = frame.struct_logs.iter().map(|log| log.access).collect::<Vec<_>>();
+        assert_eq!(access, vec![Some(AccessStatus::Cold), None]);
+        assert_eq!(
+            serde_json::to_value(&frame).unwrap(),
+            serde_json::from_str::<serde_json::Value>(s).unwrap()
+        );
+
+        // the plain frame ignores the access status
+        let plain = serde_json::from_str::<DefaultFrame>(s).unwrap();
+        assert_eq!(AccessStatusFrame::new(plain, access), frame);
+    }
+
+    #[test]
+    fn serde_trace_call_response() {
+        let s = r#"{"failed":false,"gas":21000,"returnValue":"","structLogs":[]}"#;
+        let res = serde_json::from_str::<DebugTraceCallResponse>(s).unwrap();
+        assert!(matches!(res, DebugTraceCallResponse::Trace(GethTrace::Default(_))));
+
+        let s = r#"{"trace":{"failed":false,"gas":21000,"returnValue":"","structLogs":[]},"stateDiff":{"pre":{},"post":{}}}"#;
+        let res = serde_json::from_str::<DebugTraceCallResponse>(s).unwrap();
+        let DebugTraceCallResponse::WithExtras(res) = res else { panic!("expected extras") };
+        assert!(res.state_diff.is_some());
+        assert!(matches!(res.trace, GethTrace::Default(_)));
+    }
+
+    #[test]
+    fn deserialize_precompile_moves() {
+        let s = r#"{
+            "tracer": "callTracer",
+            "stateOverrides": {
+                "0x0000000000000000000000000000000000000004": {
+                    "code": "0x00",
+                    "movePrecompileToAddress": "0x0000000000000000000000000000000000000099"
+                },
+                "0x0000000000000000000000000000000000005678": { "balance": "0x1" }
+            }
+        }"#;
+        let identity = Address::with_last_byte(4);
+        let moves = PrecompileMoves::from_iter([(identity, Address::with_last_byte(0x99))]);
+
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(s).unwrap();
+        assert_eq!(opts.precompile_moves, moves);
+        // the state overrides are still deserialized as well
+        let state_overrides = opts.call_options.state_overrides.unwrap();
+        assert_eq!(state_overrides.len(), 2);
+        assert_eq!(state_overrides[&identity].code, Some(Bytes::from_static(&[0])));
+        assert!(opts.call_options.tracing_options.tracer.is_some());
+
+        let opts = serde_json::from_str::<DebugTracingCallManyOptions>(s).unwrap();
+        assert_eq!(opts.precompile_moves, moves);
+        assert_eq!(opts.call_options.state_overrides.unwrap().len(), 2);
+
+        let opts = serde_json::from_str::<DebugTracingCallOptions>(r#"{"stateOverrides":null}"#);
+        assert!(opts.unwrap().precompile_moves.is_empty());
+    }
+
+    fn nontrivial_witness() -> DebugExecutionWitness {
+        let value = |i: u64, len: usize| -> Bytes {
+            [&i.to_be_bytes()[..], &vec![0xab; len][..]].concat().into()
+        };
+        let state = (0..100).map(|i| value(i, i as usize * 7)).map(|node| (keccak256(&node), node));
+        let preimages = (0..20).map(|i| (B256::with_last_byte(i as u8), value(i, 12)));
+        let codes = (0..5).map(|i| value(i, 1000)).map(|code| (keccak256(&code), code));
+        DebugExecutionWitness {
+            witness: ExecutionWitness {
+                witness: state.collect(),
+                state_preimages: Some(preimages.collect()),
+            },
+            codes: Some(codes.collect()),
+            headers: vec![value(1, 500), Bytes::new()],
+            verification: Some(WitnessVerification::Verified { state_root: B256::random() }),
+            block_hash: Some(B256::random()),
+            missing_preimages: Some(vec![B256::with_last_byte(0xff)]),
+        }
+    }
+
+    #[test]
+    fn encoded_execution_witness_round_trip() {
+        let witness = nontrivial_witness();
+        let res = witness.clone().encode(WitnessEncoding::Json).unwrap();
+        assert_eq!(res, ExecutionWitnessResponse::Witness(witness.clone()));
+
+        let mut sizes = Vec::new();
+        for encoding in [WitnessEncoding::Raw, WitnessEncoding::Snappy] {
+            let res = witness.clone().encode(encoding).unwrap();
+            let ExecutionWitnessResponse::Encoded(encoded) = &res else {
+                panic!("expected encoded witness, got {res:?}")
+            };
+            assert_eq!(
+                (encoded.state_nodes, encoded.preimages, encoded.codes),
+                (100, Some(20), Some(5))
+            );
+            sizes.push(encoded.data.len());
+
+            let value = serde_json::to_value(&res).unwrap();
+            assert_eq!(value["encoding"], serde_json::to_value(encoding).unwrap());
+            let res = serde_json::from_value::<ExecutionWitnessResponse>(value).unwrap();
+            assert_eq!(res.into_witness().unwrap(), witness);
+        }
+        // the repetitive values compress well
+        assert!(sizes[1] < sizes[0] / 2);
+
+        // optional entries stay absent
+        let witness = DebugExecutionWitness::default();
+        let res = witness.clone().encode(WitnessEncoding::Snappy).unwrap();
+        assert_eq!(res.into_witness().unwrap(), witness);
+    }
+
+    #[test]
+    fn serialize_execution_witness_sorted() {
+        let witness = nontrivial_witness();
+        let json = serde_json::to_string(&witness).unwrap();
+
+        // the maps of an equal witness are rebuilt with different hashers, so they iterate in a
+        // different order
+        let mut rebuilt = witness.clone();
+        rebuilt.witness.witness = witness.witness.witness.clone().into_iter().collect();
+        rebuilt.witness.state_preimages =
+            witness.witness.state_preimages.clone().map(|keys| keys.into_iter().collect());
+        assert_eq!(serde_json::to_string(&rebuilt).unwrap(), json);
+        assert_eq!(serde_json::from_str::<DebugExecutionWitness>(&json).unwrap(), witness);
+
+        // the trie nodes are serialized in ascending order of their hashes
+        let mut hashes = witness.witness.witness.keys().collect::<Vec<_>>();
+        hashes.sort();
+        let positions =
+            hashes.iter().map(|hash| json.find(&hash.to_string()).unwrap()).collect::<Vec<_>>();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // the binary encoding is deterministic as well
+        assert_eq!(
+            rebuilt.encode(WitnessEncoding::Raw).unwrap(),
+            witness.encode(WitnessEncoding::Raw).unwrap()
+        );
+    }
+
+    #[test]
+    fn encoded_execution_witness_errors() {
+        let res = nontrivial_witness().encode(WitnessEncoding::Raw).unwrap();
+        let ExecutionWitnessResponse::Encoded(encoded) = res else { panic!("expected encoded") };
+
+        let mut truncated = encoded.clone();
+        truncated.data = encoded.data.slice(..encoded.data.len() - 1);
+        truncated.uncompressed_size -= 1;
+        assert!(matches!(truncated.decode(), Err(WitnessEncodingError::Truncated)));
+
+        let mut trailing = encoded.clone();
+        trailing.headers -= 1;
+        assert!(matches!(trailing.decode(), Err(WitnessEncodingError::TrailingBytes(4))));
+
+        let mut resized = encoded.clone();
+        resized.uncompressed_size += 1;
+        let size = encoded.uncompressed_size;
+        assert!(matches!(
+            resized.decode(),
+            Err(WitnessEncodingError::SizeMismatch { actual, .. }) if actual == size
+        ));
+
+        let mut json = encoded;
+        json.encoding = WitnessEncoding::Json;
+        assert!(matches!(json.decode(), Err(WitnessEncodingError::NotBinary)));
+    }
+
+    #[test]
+    fn serialize_subscription_results() {
+        let kind = serde_json::from_str::<DebugSubscriptionKind>(r#""newBlockTraces""#).unwrap();
+        assert_eq!(kind, DebugSubscriptionKind::NewBlockTraces);
+
+        let traces = BlockTraces {
+            block_hash: B256::with_last_byte(1),
+            block_number: 16,
+            traces: Vec::new(),
+            reorg: false,
+        };
+        let json =
+            serde_json::to_value(DebugSubscriptionResult::BlockTraces(traces.clone())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "blockHash": B256::with_last_byte(1),
+                "blockNumber": "0x10",
+                "traces": [],
+            })
+        );
+        let reorged = BlockTraces { reorg: true, ..traces };
+        let json =
+            serde_json::to_value(DebugSubscriptionResult::BlockTraces(reorged.clone())).unwrap();
+        assert_eq!(json["reorg"], true);
+        assert_eq!(
+            serde_json::from_value::<DebugSubscriptionResult>(json).unwrap(),
+            DebugSubscriptionResult::BlockTraces(reorged)
+        );
+
+        let skipped = DebugSubscriptionResult::SkippedBlocks(SkippedBlocks { skipped_blocks: 3 });
+        let json = serde_json::to_value(&skipped).unwrap();
+        assert_eq!(json, serde_json::json!({ "skippedBlocks": "0x3" }));
+        assert_eq!(serde_json::from_value::<DebugSubscriptionResult>(json).unwrap(), skipped);
+    }
+
+    #[test]
+    fn serde_trace_block_notifications() {
+        let opts: DebugTracingBlockOptions =
+            serde_json::from_str(r#"{"tracer":"callTracer","progress":true}"#).unwrap();
+        assert!(opts.progress);
+
+        let progress = TraceBlockNotification::Progress(TraceProgress {
+            traced_transactions: 2,
+            current_transaction: Some(B256::with_last_byte(3)),
+            elapsed_micros: 500_000,
+        });
+        let json = serde_json::to_value(&progress).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "progress": {
+                    "tracedTransactions": "0x2",
+                    "currentTransaction": B256::with_last_byte(3),
+                    "elapsedMicros": 500_000,
+                }
+            })
+        );
+        assert_eq!(serde_json::from_value::<TraceBlockNotification>(json).unwrap(), progress);
+
+        let result = TraceBlockNotification::Result(DebugTraceBlockResponse::Array(Vec::new()));
+        assert_eq!(serde_json::to_value(&result).unwrap(), serde_json::json!({ "result": [] }));
+        let error = TraceBlockNotification::Error("tracing cancelled".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "error": "tracing cancelled" })
+        );
+    }
+
+    #[test]
+    fn serde_transaction_modifications() {
+        let modifications: Vec<TransactionModification> =
+            serde_json::from_value(serde_json::json!([
+                { "index": "0x1", "replacement": { "input": "0x01" } },
+                { "index": "0x3", "replacement": null },
+            ]))
+            .unwrap();
+        assert_eq!(modifications[0].index, 1);
+        assert_eq!(
+            modifications[0].replacement.as_ref().unwrap().input.input(),
+            Some(&Bytes::from_static(&[1]))
+        );
+        assert_eq!(modifications[1], TransactionModification { index: 3, replacement: None });
+
+        let tx_hash = B256::with_last_byte(1);
+        let dropped = ModifiedTraceResult {
+            tx_hash,
+            modification: Some(TransactionModificationKind::Dropped),
+            result: None,
+            error: None,
+        };
+        let json = serde_json::to_value(&dropped).unwrap();
+        assert_eq!(json, serde_json::json!({ "txHash": tx_hash, "modification": "dropped" }));
+        assert_eq!(serde_json::from_value::<ModifiedTraceResult>(json).unwrap(), dropped);
+
+        // unmodified transactions serialize like the results of `debug_traceBlock`
+        let failed =
+            TraceResult::Error { error: "nonce too high".to_string(), tx_hash: Some(tx_hash) };
+        let unmodified = ModifiedTraceResult {
+            tx_hash,
+            modification: None,
+            result: None,
+            error: Some("nonce too high".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(unmodified).unwrap(),
+            serde_json::to_value(failed).unwrap()
+        );
+    }
+}
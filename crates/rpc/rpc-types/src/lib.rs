@@ -46,8 +46,7 @@ pub use alloy_rpc_types_beacon as beacon;
 // re-export txpool
 pub use alloy_rpc_types_txpool as txpool;
 
-// re-export debug
-pub use alloy_rpc_types_debug as debug;
+pub mod debug;
 
 // Ethereum specific rpc types related to typed transaction requests and the engine API.
 #[cfg(feature = "jsonrpsee-types")]
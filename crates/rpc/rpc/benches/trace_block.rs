@@ -0,0 +1,287 @@
+#![allow(missing_docs, unreachable_pub)]
+use alloy_primitives::{hex, Address, Bytes, TxKind, B256, U256};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use reth_evm_ethereum::EthEvmConfig;
+use reth_network_api::noop::NoopNetwork;
+use reth_primitives::{
+    constants::ETHEREUM_BLOCK_GAS_LIMIT, Block, BlockNumberOrTag, Header, Transaction, TxLegacy,
+};
+use reth_provider::{
+    test_utils::{ExtendedAccount, MockEthProvider},
+    ChainSpecProvider,
+};
+use reth_rpc::{
+    DebugApi, EthApi, StateReadCache, StateSnapshotCache, DEFAULT_STATE_READ_CACHE_MAX_BYTES,
+    DEFAULT_STATE_SNAPSHOT_CACHE_MAX_BYTES,
+};
+use reth_rpc_eth_api::helpers::EthCall;
+use reth_rpc_eth_types::{EthStateCache, FeeHistoryCache, FeeHistoryCacheConfig, GasPriceOracle};
+use reth_rpc_server_types::constants::{DEFAULT_ETH_PROOF_WINDOW, DEFAULT_PROOF_PERMITS};
+use reth_rpc_types::{
+    debug::DebugTracingCallOptions,
+    trace::geth::{GethDebugBuiltInTracerType, GethDebugTracerConfig, GethDebugTracingOptions},
+    TransactionRequest,
+};
+use reth_tasks::pool::{BlockingTaskGuard, BlockingTaskPool};
+use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+
+type BenchEthApi = EthApi<MockEthProvider, TestPool, NoopNetwork, EthEvmConfig>;
+
+/// The number of transactions in the traced block.
+const BLOCK_TRANSACTIONS: u64 = 500;
+
+/// The number of transactions of the block that are traced one by one.
+const TRACED_TRANSACTIONS: usize = 50;
+
+/// The number of subcalls of a call to [`CALL_LOOP`].
+const LOOP_CALLS: u64 = 1000;
+
+/// A contract that calls an empty account [`LOOP_CALLS`] times.
+const CALL_LOOP: Address = Address::with_last_byte(0xca);
+
+/// Creates a debug API on top of a mock provider that holds a block of counter increments at
+/// number 1, with an `eth` API that executes its calls on the given tracing pool.
+///
+/// Returns the API and the hashes of the transactions of the block.
+fn debug_api_with_block(
+    tracing_pool: BlockingTaskPool,
+) -> (DebugApi<MockEthProvider, BenchEthApi>, Vec<B256>) {
+    let provider = MockEthProvider::default();
+
+    // SSTORE(0, SLOAD(0) + 1) STOP
+    let counter = Address::random();
+    provider.add_account(
+        counter,
+        ExtendedAccount::new(0, U256::ZERO)
+            .with_bytecode(Bytes::from_static(&hex!("60005460010160005500"))),
+    );
+    // PUSH2 LOOP_CALLS JUMPDEST POP(CALL(GAS, 0xcb, 0, 0, 0, 0, 0)) PUSH1 1 SWAP1 SUB DUP1
+    // PUSH1 3 JUMPI STOP
+    let call_loop = [
+        &[0x61][..],
+        &(LOOP_CALLS as u16).to_be_bytes(),
+        &hex!("5b6000600060006000600073"),
+        Address::with_last_byte(0xcb).as_slice(),
+        &hex!("5af150600190038060035700"),
+    ]
+    .concat();
+    provider.add_account(
+        CALL_LOOP,
+        ExtendedAccount::new(0, U256::ZERO).with_bytecode(call_loop.into()),
+    );
+
+    let genesis = Header { gas_limit: ETHEREUM_BLOCK_GAS_LIMIT, ..Default::default() };
+    let genesis_hash = genesis.hash_slow();
+    provider.add_block(genesis_hash, Block { header: genesis, ..Default::default() });
+
+    let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+    let body = (0..BLOCK_TRANSACTIONS)
+        .map(|nonce| {
+            let tx = Transaction::Legacy(TxLegacy {
+                nonce,
+                gas_limit: 100_000,
+                to: TxKind::Call(counter),
+                ..Default::default()
+            });
+            sign_tx_with_key_pair(key_pair, tx)
+        })
+        .collect::<Vec<_>>();
+    let tx_hashes = body.iter().map(|tx| tx.hash).collect();
+    let header = Header {
+        number: 1,
+        parent_hash: genesis_hash,
+        gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+        ..Default::default()
+    };
+    provider.add_block(header.hash_slow(), Block { header, body, ..Default::default() });
+
+    let evm_config = EthEvmConfig::new(provider.chain_spec());
+    let cache = EthStateCache::spawn(provider.clone(), Default::default(), evm_config.clone());
+    let eth_api = EthApi::new(
+        provider.clone(),
+        testing_pool(),
+        NoopNetwork::default(),
+        cache.clone(),
+        GasPriceOracle::new(provider.clone(), Default::default(), cache.clone()),
+        ETHEREUM_BLOCK_GAS_LIMIT,
+        DEFAULT_ETH_PROOF_WINDOW,
+        tracing_pool,
+        FeeHistoryCache::new(cache, FeeHistoryCacheConfig::default()),
+        evm_config,
+        DEFAULT_PROOF_PERMITS,
+    );
+
+    (DebugApi::new(provider, eth_api, BlockingTaskGuard::new(4)), tx_hashes)
+}
+
+pub fn trace_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Trace Block");
+    group.sample_size(10);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    // the state cache of the eth api is spawned on the runtime
+    let (debug_api, _) = runtime.block_on(async {
+        debug_api_with_block(BlockingTaskPool::build().expect("failed to build tracing pool"))
+    });
+
+    let tracers = [
+        ("struct logger", GethDebugTracingOptions::default()),
+        (
+            "call tracer",
+            GethDebugTracingOptions::default()
+                .with_tracer(GethDebugBuiltInTracerType::CallTracer.into()),
+        ),
+    ];
+    for (name, opts) in tracers {
+        group.bench_function(BenchmarkId::new(name, BLOCK_TRANSACTIONS), |b| {
+            b.to_async(&runtime).iter(|| {
+                debug_api.debug_trace_block(BlockNumberOrTag::Number(1).into(), opts.clone().into())
+            })
+        });
+    }
+}
+
+/// Measures tracing a call with many subcalls with the `callTracer`, with and without
+/// `onlyTopCall`, which doesn't record the subcalls.
+pub fn trace_call_only_top_call(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Trace Call With Subcalls");
+    group.sample_size(10);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let (debug_api, _) = runtime.block_on(async {
+        debug_api_with_block(BlockingTaskPool::build().expect("failed to build tracing pool"))
+    });
+
+    let call = TransactionRequest {
+        from: Some(Address::random()),
+        to: Some(TxKind::Call(CALL_LOOP)),
+        ..Default::default()
+    };
+    for only_top_call in [false, true] {
+        let mut opts = DebugTracingCallOptions::default();
+        opts.call_options.tracing_options = GethDebugTracingOptions {
+            tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+            tracer_config: GethDebugTracerConfig(
+                serde_json::json!({ "onlyTopCall": only_top_call }),
+            ),
+            ..Default::default()
+        };
+        let name = if only_top_call { "only top call" } else { "all calls" };
+        group.bench_function(BenchmarkId::new(name, LOOP_CALLS), |b| {
+            b.to_async(&runtime)
+                .iter(|| debug_api.debug_trace_call(call.clone(), None, opts.clone()))
+        });
+    }
+}
+
+/// Measures the latency of `eth_call`s while block traces occupy all trace permits, with the
+/// block traces executed on the tracing pool of the `eth` API or on a dedicated pool.
+pub fn eth_call_while_tracing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Eth Call While Tracing Blocks");
+    group.sample_size(20);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    for dedicated in [false, true] {
+        let debug_api = runtime.block_on(async {
+            // a small pool, so that the block traces saturate it
+            let pool = BlockingTaskPool::builder().num_threads(2).build().unwrap();
+            let (debug_api, _) = debug_api_with_block(BlockingTaskPool::new(pool));
+            if dedicated {
+                debug_api.with_dedicated_trace_pool(None).unwrap()
+            } else {
+                debug_api
+            }
+        });
+
+        let block_traces = (0..4)
+            .map(|_| {
+                let debug_api = debug_api.clone();
+                runtime.spawn(async move {
+                    loop {
+                        let block = BlockNumberOrTag::Number(1).into();
+                        let _ = debug_api.debug_trace_block(block, Default::default()).await;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let eth_api = debug_api.eth_api().clone();
+        let name = if dedicated { "dedicated trace pool" } else { "shared tracing pool" };
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| {
+                EthCall::call(&eth_api, TransactionRequest::default(), None, Default::default())
+            })
+        });
+
+        block_traces.iter().for_each(|task| task.abort());
+    }
+}
+
+/// Measures tracing the transactions at the start of the block one by one, as e.g. an indexer
+/// following the tip does, with and without sharing the reads of the parent state.
+pub fn trace_block_transactions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Trace Block Transactions");
+    group.sample_size(10);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    for cached in [false, true] {
+        let (debug_api, tx_hashes) = runtime.block_on(async {
+            debug_api_with_block(BlockingTaskPool::build().expect("failed to build tracing pool"))
+        });
+        let debug_api = if cached {
+            debug_api.with_state_read_cache(StateReadCache::new(DEFAULT_STATE_READ_CACHE_MAX_BYTES))
+        } else {
+            debug_api
+        };
+
+        let name = if cached { "state read cache" } else { "no cache" };
+        group.bench_function(BenchmarkId::new(name, TRACED_TRANSACTIONS), |b| {
+            b.to_async(&runtime).iter(|| async {
+                for tx_hash in &tx_hashes[..TRACED_TRANSACTIONS] {
+                    debug_api.debug_trace_transaction(*tx_hash, Default::default()).await.unwrap();
+                }
+            })
+        });
+    }
+}
+
+/// Measures tracing every transaction of the block one by one in block order, with and without
+/// resuming each trace from the cached state before the previously traced transaction.
+pub fn trace_all_block_transactions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Trace All Block Transactions");
+    group.sample_size(10);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    for cached in [false, true] {
+        let (debug_api, tx_hashes) = runtime.block_on(async {
+            debug_api_with_block(BlockingTaskPool::build().expect("failed to build tracing pool"))
+        });
+        let debug_api = if cached {
+            debug_api.with_state_snapshot_cache(StateSnapshotCache::new(
+                DEFAULT_STATE_SNAPSHOT_CACHE_MAX_BYTES,
+            ))
+        } else {
+            debug_api
+        };
+
+        let name = if cached { "state snapshot cache" } else { "no cache" };
+        group.bench_function(BenchmarkId::new(name, BLOCK_TRANSACTIONS), |b| {
+            b.to_async(&runtime).iter(|| async {
+                for tx_hash in &tx_hashes {
+                    debug_api.debug_trace_transaction(*tx_hash, Default::default()).await.unwrap();
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(
+    trace,
+    trace_block,
+    trace_call_only_top_call,
+    eth_call_while_tracing,
+    trace_block_transactions,
+    trace_all_block_transactions
+);
+criterion_main!(trace);
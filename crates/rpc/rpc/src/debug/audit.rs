@@ -0,0 +1,286 @@
+//! Audit log of the requests of the `debug` API.
+
+use super::TracerKind;
+use alloy_primitives::B256;
+use jsonrpsee::core::RpcResult;
+use reth_metrics::{metrics::Counter, Metrics};
+use reth_primitives::BlockId;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+/// Default number of entries of the audit log that are buffered before they're dropped.
+pub const DEFAULT_AUDIT_LOG_BUFFER: usize = 1024;
+
+/// A sink for the audit log of the `debug` API, see
+/// [`DebugApi::with_audit_sink`](super::DebugApi::with_audit_sink).
+///
+/// Entries are recorded at the end of each audited request on the task that served it, so
+/// implementations must not block.
+pub trait DebugAuditSink: Send + Sync + fmt::Debug {
+    /// Records an entry of the audit log.
+    fn record(&self, entry: DebugAuditEntry);
+}
+
+/// An entry of the audit log, recorded for each tracing request of the `debug` API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugAuditEntry {
+    /// The time the request arrived, in milliseconds since the UNIX epoch.
+    pub timestamp_millis: u64,
+    /// The RPC method of the request.
+    pub method: String,
+    /// The parameters of the request.
+    pub params: DebugAuditParams,
+    /// The time the request took, in microseconds.
+    pub duration_micros: u64,
+    /// The estimated size of the response, in bytes, if the request succeeded and its traces
+    /// were accounted against the trace memory budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_size: Option<u64>,
+    /// The outcome of the request.
+    pub outcome: DebugAuditOutcome,
+}
+
+impl DebugAuditEntry {
+    /// Creates the entry of a request that arrived at the given time and took the given duration,
+    /// with the estimated size of its response.
+    pub(crate) fn new<T>(
+        method: &'static str,
+        params: DebugAuditParams,
+        arrived: SystemTime,
+        duration: Duration,
+        result: &RpcResult<T>,
+        response_size: Option<u64>,
+    ) -> Self {
+        let (response_size, outcome) = match result {
+            Ok(_) => (response_size, DebugAuditOutcome::Success),
+            Err(err) => (
+                None,
+                DebugAuditOutcome::Error { code: err.code(), message: err.message().to_string() },
+            ),
+        };
+        Self {
+            timestamp_millis: arrived
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |timestamp| timestamp.as_millis() as u64),
+            method: method.to_string(),
+            params,
+            duration_micros: duration.as_micros() as u64,
+            response_size,
+            outcome,
+        }
+    }
+}
+
+/// The parameters of a request, as recorded in the audit log.
+///
+/// Only the identifiers of the traced block or transaction and the kind of the tracer are
+/// recorded, never the calldata of calls, the code of JS tracers or the contents of overrides.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugAuditParams {
+    /// The requested block, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<BlockId>,
+    /// The requested transaction, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<B256>,
+    /// The kind of the tracer, if the request traces with one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracer: Option<TracerKind>,
+}
+
+/// The outcome of a request, as recorded in the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum DebugAuditOutcome {
+    /// The request succeeded.
+    Success,
+    /// The request failed with the given error.
+    Error {
+        /// The code of the error.
+        code: i32,
+        /// The message of the error.
+        message: String,
+    },
+}
+
+/// Configuration of the [`JsonlAuditSink`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditLogConfig {
+    /// The file the entries are appended to, which is created if it doesn't exist.
+    pub path: PathBuf,
+    /// The number of entries that are buffered while the file is written, further entries are
+    /// dropped.
+    #[serde(default = "default_audit_log_buffer")]
+    pub buffer: usize,
+}
+
+impl AuditLogConfig {
+    /// Creates the config of an audit log that is appended to the given file.
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path, buffer: DEFAULT_AUDIT_LOG_BUFFER }
+    }
+}
+
+const fn default_audit_log_buffer() -> usize {
+    DEFAULT_AUDIT_LOG_BUFFER
+}
+
+/// A [`DebugAuditSink`] that appends the entries to a file, one JSON object per line.
+///
+/// The entries are written on a dedicated thread. Recording an entry never blocks: entries that
+/// don't fit into the buffer are dropped and counted instead.
+#[derive(Debug)]
+pub struct JsonlAuditSink {
+    entries: SyncSender<DebugAuditEntry>,
+    dropped: AtomicU64,
+    metrics: AuditLogMetrics,
+}
+
+impl JsonlAuditSink {
+    /// Opens the file of the config for appending and spawns the thread that writes it.
+    pub fn open(config: &AuditLogConfig) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&config.path)?;
+        let (entries, receiver) = mpsc::sync_channel(config.buffer.max(1));
+        std::thread::Builder::new()
+            .name("debug-audit-log".to_string())
+            .spawn(move || write_entries(receiver, file))?;
+        Ok(Self { entries, dropped: AtomicU64::new(0), metrics: Default::default() })
+    }
+
+    /// Returns the number of entries that were dropped because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl DebugAuditSink for JsonlAuditSink {
+    fn record(&self, entry: DebugAuditEntry) {
+        match self.entries.try_send(entry) {
+            Ok(()) => self.metrics.entries_total.increment(1),
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                self.metrics.dropped_entries_total.increment(1);
+            }
+        }
+    }
+}
+
+/// Writes the received entries to the file until the sink is dropped, flushing whenever no
+/// further entries are buffered.
+fn write_entries(entries: Receiver<DebugAuditEntry>, file: File) {
+    let mut file = BufWriter::new(file);
+    while let Ok(entry) = entries.recv() {
+        for entry in std::iter::once(entry).chain(entries.try_iter()) {
+            let res = serde_json::to_writer(&mut file, &entry)
+                .map_err(io::Error::from)
+                .and_then(|()| file.write_all(b"\n"));
+            if let Err(err) = res {
+                warn!(target: "rpc::debug", %err, "failed to write audit log entry");
+            }
+        }
+        if let Err(err) = file.flush() {
+            warn!(target: "rpc::debug", %err, "failed to flush audit log");
+        }
+    }
+}
+
+/// Metrics of the [`JsonlAuditSink`].
+#[derive(Metrics)]
+#[metrics(scope = "rpc.debug.audit_log")]
+struct AuditLogMetrics {
+    /// The number of entries that were buffered to be written.
+    entries_total: Counter,
+    /// The number of entries that were dropped because the buffer was full.
+    dropped_entries_total: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jsonl_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AuditLogConfig::new(dir.path().join("audit.jsonl"));
+        let entry = DebugAuditEntry {
+            timestamp_millis: 1,
+            method: "debug_traceTransaction".to_string(),
+            params: DebugAuditParams {
+                tx_hash: Some(B256::with_last_byte(1)),
+                tracer: Some(TracerKind::CallTracer),
+                ..Default::default()
+            },
+            duration_micros: 2,
+            response_size: Some(3),
+            outcome: DebugAuditOutcome::Success,
+        };
+        let failed = DebugAuditEntry {
+            method: "debug_traceBlockByNumber".to_string(),
+            params: DebugAuditParams { block: Some(BlockId::latest()), ..Default::default() },
+            response_size: None,
+            outcome: DebugAuditOutcome::Error { code: -32000, message: "failed".to_string() },
+            ..entry.clone()
+        };
+
+        let sink = JsonlAuditSink::open(&config).unwrap();
+        sink.record(entry.clone());
+        sink.record(failed.clone());
+        // the file is written once the writer thread has written all buffered entries
+        drop(sink);
+        let mut lines = Vec::new();
+        for _ in 0..100 {
+            lines = std::fs::read_to_string(&config.path)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<DebugAuditEntry>(line).unwrap())
+                .collect::<Vec<_>>();
+            if lines.len() == 2 {
+                break
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(lines, vec![entry.clone(), failed]);
+
+        let line = serde_json::to_value(&entry).unwrap();
+        assert_eq!(line["params"]["tracer"], "callTracer");
+        assert_eq!(line["outcome"], serde_json::json!({ "status": "success" }));
+        assert!(line["params"].get("block").is_none());
+    }
+
+    #[test]
+    fn drop_entries_if_full() {
+        let (entries, receiver) = mpsc::sync_channel(1);
+        let sink =
+            JsonlAuditSink { entries, dropped: AtomicU64::new(0), metrics: Default::default() };
+        let entry = DebugAuditEntry::new(
+            "debug_replayBlock",
+            Default::default(),
+            UNIX_EPOCH + Duration::from_millis(5),
+            Duration::from_micros(7),
+            &RpcResult::Ok(()),
+            Some(3),
+        );
+        assert_eq!(entry.timestamp_millis, 5);
+        assert_eq!(entry.response_size, Some(3));
+
+        // nothing is written, so only the first entry fits into the buffer
+        for _ in 0..3 {
+            sink.record(entry.clone());
+        }
+        assert_eq!(sink.dropped(), 2);
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+}
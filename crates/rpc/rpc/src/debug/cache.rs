@@ -0,0 +1,159 @@
+//! Cache for the traces of recently traced blocks.
+
+use super::lru::ByteBoundedLru;
+use alloy_primitives::B256;
+use reth_rpc_types::{
+    debug::PostBlockTrace,
+    trace::geth::{GethDebugTracingOptions, TraceResult},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+
+/// Default maximum number of cached block traces.
+pub const DEFAULT_TRACE_CACHE_MAX_ENTRIES: u32 = 64;
+
+/// Default maximum total size of the cached block traces, in bytes.
+pub const DEFAULT_TRACE_CACHE_MAX_BYTES: usize = 256 * 1024 * 1024;
+
+/// Configuration of the [`TraceCache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraceCacheConfig {
+    /// The maximum number of cached block traces.
+    pub max_entries: u32,
+    /// The maximum total size of the cached block traces, measured by the memory they reserve
+    /// while they're traced.
+    pub max_bytes: usize,
+}
+
+impl Default for TraceCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_TRACE_CACHE_MAX_ENTRIES,
+            max_bytes: DEFAULT_TRACE_CACHE_MAX_BYTES,
+        }
+    }
+}
+
+/// Key of cached block traces: the block hash and the canonical JSON encoding of the tracing
+/// options, the selected transactions and whether the post-block changes are included.
+pub(crate) type TraceCacheKey = (B256, String);
+
+/// An LRU cache for the traces of recently traced blocks.
+///
+/// The traces are keyed by the block hash and the tracing options, so identical requests for the
+/// same block are only traced once. The cache is bounded by the number of entries and by the total
+/// size of the cached traces.
+#[derive(Clone, Debug)]
+pub struct TraceCache {
+    entries: ByteBoundedLru<TraceCacheKey, Arc<BlockTraces>>,
+}
+
+impl TraceCache {
+    /// Creates a new, empty cache with the given limits.
+    pub fn new(config: TraceCacheConfig) -> Self {
+        let entries =
+            ByteBoundedLru::new("rpc.debug.trace_cache", config.max_entries, config.max_bytes);
+        Self { entries }
+    }
+
+    /// Returns the key of the traces of the given block for the given options.
+    ///
+    /// The options are expected to have the default tracer applied already.
+    pub(crate) fn key(
+        block_hash: B256,
+        opts: &GethDebugTracingOptions,
+        tx_hashes: &[B256],
+        include_post_block: bool,
+    ) -> Option<TraceCacheKey> {
+        // the tracer config is a json value, which has an ordered map representation, so equal
+        // options always have the same encoding
+        serde_json::to_string(&(opts, tx_hashes, include_post_block))
+            .ok()
+            .map(|opts| (block_hash, opts))
+    }
+
+    /// Returns the cached traces for the given key.
+    pub(crate) fn get(&self, key: &TraceCacheKey) -> Option<Arc<BlockTraces>> {
+        self.entries.get(key)
+    }
+
+    /// Returns the size of the cached traces for the given key, without marking them as recently
+    /// used.
+    pub(crate) fn size(&self, key: &TraceCacheKey) -> Option<usize> {
+        self.entries.size(key)
+    }
+
+    /// Caches the given traces of the given estimated size, evicting the least recently used
+    /// traces if the cache is full.
+    ///
+    /// Traces that exceed the size limit of the cache on their own are not cached.
+    pub(crate) fn insert(&self, key: TraceCacheKey, traces: BlockTraces, size: usize) {
+        self.entries.insert(key, Arc::new(traces), size);
+    }
+
+    /// Removes the cached traces of the given blocks.
+    pub fn remove_blocks(&self, block_hashes: &HashSet<B256>) {
+        self.entries.remove_blocks(block_hashes);
+    }
+
+    /// Returns the number of cached block traces.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// The traces of a block, as cached by the [`TraceCache`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct BlockTraces {
+    /// The results in block order.
+    pub(crate) results: Vec<TraceResult>,
+    /// The post-block changes, if requested.
+    pub(crate) post_block: Option<PostBlockTrace>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(n: u8) -> BlockTraces {
+        let results = (0..n)
+            .map(|i| TraceResult::Error {
+                error: "error".to_string(),
+                tx_hash: Some(B256::with_last_byte(i)),
+            })
+            .collect();
+        BlockTraces { results, post_block: None }
+    }
+
+    #[test]
+    fn cache_keyed_by_block_and_options() {
+        let cache = TraceCache::new(Default::default());
+        let opts = GethDebugTracingOptions::default();
+        let key = TraceCache::key(B256::with_last_byte(1), &opts, &[], false).unwrap();
+
+        assert!(cache.get(&key).is_none());
+        cache.insert(key.clone(), results(2), 100);
+        assert_eq!(*cache.get(&key).unwrap(), results(2));
+        assert_eq!(cache.size(&key), Some(100));
+
+        let other_block = TraceCache::key(B256::with_last_byte(2), &opts, &[], false).unwrap();
+        assert!(cache.get(&other_block).is_none());
+        let other_opts =
+            TraceCache::key(B256::with_last_byte(1), &opts, &[B256::with_last_byte(1)], false)
+                .unwrap();
+        assert!(cache.get(&other_opts).is_none());
+        let post_block = TraceCache::key(B256::with_last_byte(1), &opts, &[], true).unwrap();
+        assert!(cache.get(&post_block).is_none());
+        let mut storage_disabled = opts;
+        storage_disabled.config.disable_storage = Some(true);
+        let storage_disabled =
+            TraceCache::key(B256::with_last_byte(1), &storage_disabled, &[], false).unwrap();
+        assert!(cache.get(&storage_disabled).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+//! Cooperative cancellation of tracing work whose request was dropped.
+
+use reth_rpc_eth_types::EthApiError;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Cancels its [`Cancellation`] when dropped.
+///
+/// The server drops the future of a request if the client disconnects or the request times out.
+/// A guard that is held by the future, while the blocking task of the request checks the
+/// [`Cancellation`] between units of work, stops the work nobody is going to receive.
+#[derive(Debug, Default)]
+pub(crate) struct CancelOnDrop(Cancellation);
+
+impl CancelOnDrop {
+    /// Returns the cancellation that is cancelled once this is dropped.
+    pub(crate) fn cancellation(&self) -> Cancellation {
+        self.0.clone()
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Signals that the request of a blocking task was dropped.
+///
+/// A default cancellation is never cancelled.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Fails with [`EthApiError::TracingCancelled`] if the request was dropped.
+    pub(crate) fn check(&self) -> Result<(), EthApiError> {
+        if self.0.load(Ordering::Relaxed) {
+            return Err(EthApiError::TracingCancelled)
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_on_drop() {
+        let guard = CancelOnDrop::default();
+        let cancellation = guard.cancellation();
+        assert!(cancellation.check().is_ok());
+
+        drop(guard);
+        assert!(matches!(cancellation.check(), Err(EthApiError::TracingCancelled)));
+    }
+}
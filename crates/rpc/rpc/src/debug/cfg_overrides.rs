@@ -0,0 +1,123 @@
+//! Overrides of the EVM configuration of traced calls.
+
+use alloy_primitives::B256;
+use reth_chainspec::EthereumHardfork;
+use reth_rpc_eth_types::EthApiError;
+use reth_rpc_types::debug::CfgOverrides;
+use revm::primitives::{BlobExcessGasAndPrice, BlockEnv, CfgEnvWithHandlerCfg, SpecId};
+
+/// The spec of each Ethereum hardfork.
+const HARDFORK_SPECS: [(EthereumHardfork, SpecId); 18] = [
+    (EthereumHardfork::Frontier, SpecId::FRONTIER),
+    (EthereumHardfork::Homestead, SpecId::HOMESTEAD),
+    (EthereumHardfork::Dao, SpecId::DAO_FORK),
+    (EthereumHardfork::Tangerine, SpecId::TANGERINE),
+    (EthereumHardfork::SpuriousDragon, SpecId::SPURIOUS_DRAGON),
+    (EthereumHardfork::Byzantium, SpecId::BYZANTIUM),
+    (EthereumHardfork::Constantinople, SpecId::CONSTANTINOPLE),
+    (EthereumHardfork::Petersburg, SpecId::PETERSBURG),
+    (EthereumHardfork::Istanbul, SpecId::ISTANBUL),
+    (EthereumHardfork::MuirGlacier, SpecId::MUIR_GLACIER),
+    (EthereumHardfork::Berlin, SpecId::BERLIN),
+    (EthereumHardfork::London, SpecId::LONDON),
+    (EthereumHardfork::ArrowGlacier, SpecId::ARROW_GLACIER),
+    (EthereumHardfork::GrayGlacier, SpecId::GRAY_GLACIER),
+    (EthereumHardfork::Paris, SpecId::MERGE),
+    (EthereumHardfork::Shanghai, SpecId::SHANGHAI),
+    (EthereumHardfork::Cancun, SpecId::CANCUN),
+    (EthereumHardfork::Prague, SpecId::PRAGUE),
+];
+
+/// Applies the cfg overrides of traced calls to the environment they're executed in.
+///
+/// The spec is given by the name of a hardfork, regardless of case. If the spec requires fields
+/// of the block env that the block predates, they're set to zero, i.e. the prevrandao since the
+/// merge and the excess blob gas since Cancun. The blob base fee replaces the blob gas price of
+/// the block, but keeps its excess blob gas.
+///
+/// Fails with [`EthApiError::InvalidParams`] if the EVM has no spec for the hardfork.
+pub(crate) fn apply_cfg_overrides(
+    overrides: &CfgOverrides,
+    cfg: &mut CfgEnvWithHandlerCfg,
+    block_env: &mut BlockEnv,
+) -> Result<(), EthApiError> {
+    if let Some(spec) = &overrides.spec {
+        let spec_id = spec
+            .parse::<EthereumHardfork>()
+            .ok()
+            .and_then(|hardfork| HARDFORK_SPECS.iter().find(|(fork, _)| *fork == hardfork))
+            .map(|(_, spec_id)| *spec_id)
+            .ok_or_else(|| EthApiError::InvalidParams(format!("unknown spec: {spec}")))?;
+        cfg.handler_cfg.spec_id = spec_id;
+
+        if spec_id >= SpecId::MERGE && block_env.prevrandao.is_none() {
+            block_env.prevrandao = Some(B256::ZERO);
+        }
+        if spec_id >= SpecId::CANCUN && block_env.blob_excess_gas_and_price.is_none() {
+            block_env.set_blob_excess_gas_and_price(0);
+        }
+    }
+    if let Some(chain_id) = overrides.chain_id {
+        cfg.cfg_env.chain_id = chain_id;
+    }
+    if let Some(blob_gasprice) = overrides.blob_base_fee {
+        let excess_blob_gas = block_env.get_blob_excess_gas().unwrap_or_default();
+        block_env.blob_excess_gas_and_price =
+            Some(BlobExcessGasAndPrice { excess_blob_gas, blob_gasprice });
+    }
+    Ok(())
+}
+
+/// Returns the name of the hardfork of the spec, as returned alongside the traces of calls with
+/// cfg overrides.
+pub(crate) fn spec_name(spec_id: SpecId) -> String {
+    HARDFORK_SPECS
+        .iter()
+        .find(|(_, spec)| *spec == spec_id)
+        .map_or_else(|| format!("{spec_id:?}"), |(hardfork, _)| hardfork.name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cfg_overrides() {
+        let mut cfg = CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::LONDON);
+        // a block before the merge
+        let pre_merge =
+            BlockEnv { prevrandao: None, blob_excess_gas_and_price: None, ..Default::default() };
+        let mut block_env = pre_merge.clone();
+        let overrides = CfgOverrides {
+            spec: Some("PRAGUE".to_string()),
+            chain_id: Some(7),
+            ..Default::default()
+        };
+        apply_cfg_overrides(&overrides, &mut cfg, &mut block_env).unwrap();
+        assert_eq!(cfg.handler_cfg.spec_id, SpecId::PRAGUE);
+        assert_eq!(cfg.cfg_env.chain_id, 7);
+        assert_eq!(block_env.prevrandao, Some(B256::ZERO));
+        assert!(block_env.blob_excess_gas_and_price.is_some());
+        assert_eq!(spec_name(cfg.handler_cfg.spec_id), "Prague");
+
+        // older specs don't need any fields of the block env
+        let mut block_env = pre_merge.clone();
+        let overrides = CfgOverrides { spec: Some("byzantium".to_string()), ..Default::default() };
+        apply_cfg_overrides(&overrides, &mut cfg, &mut block_env).unwrap();
+        assert_eq!(cfg.handler_cfg.spec_id, SpecId::BYZANTIUM);
+        assert_eq!(cfg.cfg_env.chain_id, 7);
+        assert_eq!(block_env, pre_merge);
+
+        let overrides = CfgOverrides { spec: Some("osaka".to_string()), ..Default::default() };
+        let err = apply_cfg_overrides(&overrides, &mut cfg, &mut block_env).unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(msg) if msg == "unknown spec: osaka"));
+
+        // the blob base fee replaces the blob gas price of the block
+        let mut block_env = BlockEnv::default();
+        block_env.set_blob_excess_gas_and_price(1 << 20);
+        let overrides = CfgOverrides { blob_base_fee: Some(7), ..Default::default() };
+        apply_cfg_overrides(&overrides, &mut cfg, &mut block_env).unwrap();
+        assert_eq!(block_env.get_blob_gasprice(), Some(7));
+        assert_eq!(block_env.get_blob_excess_gas(), Some(1 << 20));
+    }
+}
@@ -0,0 +1,452 @@
+//! Limits of the concurrent and per-minute requests of each client of the `debug` API.
+
+use super::middleware::RpcConnection;
+use http::{HeaderName, Request};
+use jsonrpsee::{ConnectionId, Extensions};
+use parking_lot::Mutex;
+use reth_metrics::{metrics::Counter, Metrics};
+use reth_rpc_eth_types::{EthApiError, RequestLimit};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, BTreeSet, HashMap},
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// The window the requests per minute of a client are counted in.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// The suggested time to wait before retrying a request that exceeded the concurrency limit of
+/// its client.
+const CONCURRENCY_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Configuration of the limits of the tracing requests of each client of the `debug` API.
+///
+/// Clients are identified by their API key if [`Self::api_key_header`] is set and the request
+/// sends one of the [`Self::api_keys`] in the header, and by their connection otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientLimitsConfig {
+    /// The maximum number of concurrent tracing requests of a client, unlimited if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<u32>,
+    /// The maximum number of tracing requests of a client per minute, unlimited if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_requests_per_minute: Option<u32>,
+    /// The HTTP header that holds the API key of a client.
+    ///
+    /// The header is only visible to the `debug` API if the server passes it on with the
+    /// [`ClientKeyLayer`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_header: Option<String>,
+    /// The API keys of the clients that are limited by their key, across their connections.
+    ///
+    /// Requests with other keys are limited by their connection, so that clients can't get a new
+    /// budget by sending a new key.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub api_keys: BTreeSet<String>,
+    /// The API keys of the clients that are exempt from the limits.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub allowlist: BTreeSet<String>,
+}
+
+impl ClientLimitsConfig {
+    /// Returns a config without any limits.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_concurrent_requests: None,
+            max_requests_per_minute: None,
+            api_key_header: None,
+            api_keys: BTreeSet::new(),
+            allowlist: BTreeSet::new(),
+        }
+    }
+
+    /// Returns true if any limit is configured.
+    pub const fn is_limited(&self) -> bool {
+        self.max_concurrent_requests.is_some() || self.max_requests_per_minute.is_some()
+    }
+}
+
+impl Default for ClientLimitsConfig {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// The API key of a client, as sent in the header of the [`ClientKeyLayer`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientKey(pub String);
+
+/// A layer for the HTTP middleware of the server that passes the API key in the given header on
+/// to the methods of the `debug` API, see [`ClientLimitsConfig::api_key_header`].
+///
+/// Websocket connections are identified by the key of their upgrade request.
+#[derive(Clone, Debug)]
+pub struct ClientKeyLayer {
+    header: HeaderName,
+}
+
+impl ClientKeyLayer {
+    /// Creates a layer that reads the API key from the given header.
+    pub const fn new(header: HeaderName) -> Self {
+        Self { header }
+    }
+}
+
+impl<S> Layer<S> for ClientKeyLayer {
+    type Service = ClientKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientKeyService { inner, header: self.header.clone() }
+    }
+}
+
+/// The service of the [`ClientKeyLayer`].
+#[derive(Clone, Debug)]
+pub struct ClientKeyService<S> {
+    inner: S,
+    header: HeaderName,
+}
+
+impl<S, B> Service<Request<B>> for ClientKeyService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let key = request.headers().get(&self.header).and_then(|key| key.to_str().ok());
+        if let Some(key) = key.map(|key| ClientKey(key.to_string())) {
+            request.extensions_mut().insert(key);
+        }
+        self.inner.call(request)
+    }
+}
+
+/// The identity of a client.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ClientIdentity {
+    /// A client identified by its API key.
+    ApiKey(String),
+    /// A client identified by its connection.
+    Connection(ConnectionId),
+}
+
+/// The accounting of the requests of each client.
+///
+/// A client is tracked while it has requests in flight and until the window of its requests per
+/// minute expires. The accounting of a connection is dropped once it's closed if the server has
+/// the [`DebugRpcLayer`](super::DebugRpcLayer), and within a minute after it closed otherwise.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ClientLimits {
+    inner: Arc<Mutex<ClientLimitsInner>>,
+}
+
+impl ClientLimits {
+    /// Configures the limits, the accounting of the clients so far is discarded.
+    pub(crate) fn configure(&self, config: ClientLimitsConfig) {
+        let mut inner = self.inner.lock();
+        inner.config = config;
+        inner.clients.clear();
+    }
+
+    /// Accounts a request of the client the request with the given extensions is from.
+    ///
+    /// Returns the guard of the request, which releases its concurrency slot once dropped, or
+    /// `None` if the client isn't limited, e.g. if the request has no connection because it's
+    /// called in-process. Fails with [`EthApiError::RateLimitExceeded`] if the request exceeds a
+    /// limit of the client.
+    pub(crate) fn acquire(&self, ext: &Extensions) -> Result<Option<ClientRequest>, EthApiError> {
+        let mut inner = self.inner.lock();
+        let ClientLimitsInner { config, clients, last_pruned, metrics } = &mut *inner;
+        if !config.is_limited() {
+            return Ok(None)
+        }
+        let key = config.api_key_header.is_some().then(|| ext.get::<ClientKey>()).flatten();
+        let identity = match (key, ext.get::<ConnectionId>()) {
+            (Some(ClientKey(key)), _) if config.allowlist.contains(key) => return Ok(None),
+            (Some(ClientKey(key)), _) if config.api_keys.contains(key) => {
+                ClientIdentity::ApiKey(key.clone())
+            }
+            (_, Some(conn_id)) => ClientIdentity::Connection(*conn_id),
+            (_, None) => return Ok(None),
+        };
+
+        let now = Instant::now();
+        if now.duration_since(*last_pruned) >= RATE_WINDOW {
+            clients.retain(|_, usage| {
+                usage.in_flight > 0 || now.duration_since(usage.window_start) < RATE_WINDOW
+            });
+            *last_pruned = now;
+        }
+
+        let usage = match clients.entry(identity.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let connection = ext.get::<RpcConnection>();
+                if let (ClientIdentity::Connection(_), Some(connection)) = (&identity, connection) {
+                    let (limits, identity) = (Arc::downgrade(&self.inner), identity.clone());
+                    connection.on_close(move || {
+                        if let Some(limits) = limits.upgrade() {
+                            limits.lock().clients.remove(&identity);
+                        }
+                    });
+                }
+                entry.insert(ClientUsage { in_flight: 0, window_start: now, requests: 0 })
+            }
+        };
+        if now.duration_since(usage.window_start) >= RATE_WINDOW {
+            usage.window_start = now;
+            usage.requests = 0;
+        }
+        if let Some(max) = config.max_concurrent_requests {
+            if usage.in_flight >= max {
+                metrics.class(&identity).concurrency_rejections_total.increment(1);
+                return Err(EthApiError::RateLimitExceeded {
+                    limit: RequestLimit::Concurrent(max),
+                    retry_after: CONCURRENCY_RETRY_AFTER,
+                })
+            }
+        }
+        if let Some(max) = config.max_requests_per_minute {
+            if usage.requests >= max {
+                metrics.class(&identity).rate_rejections_total.increment(1);
+                return Err(EthApiError::RateLimitExceeded {
+                    limit: RequestLimit::PerMinute(max),
+                    retry_after: RATE_WINDOW.saturating_sub(now.duration_since(usage.window_start)),
+                })
+            }
+        }
+        usage.in_flight += 1;
+        usage.requests += 1;
+        Ok(Some(ClientRequest { limits: self.clone(), identity }))
+    }
+}
+
+/// The limits, and the usage of each client.
+#[derive(Debug)]
+struct ClientLimitsInner {
+    config: ClientLimitsConfig,
+    clients: HashMap<ClientIdentity, ClientUsage>,
+    /// The last time the clients that are no longer tracked were removed.
+    last_pruned: Instant,
+    metrics: ClientLimitsMetrics,
+}
+
+impl Default for ClientLimitsInner {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            clients: HashMap::new(),
+            last_pruned: Instant::now(),
+            metrics: Default::default(),
+        }
+    }
+}
+
+/// The usage of a client.
+#[derive(Debug)]
+struct ClientUsage {
+    /// The number of requests in flight.
+    in_flight: u32,
+    /// The start of the current window of the requests per minute.
+    window_start: Instant,
+    /// The number of requests in the current window.
+    requests: u32,
+}
+
+/// A request that's accounted against the limits of its client, until it's dropped.
+#[derive(Debug)]
+pub(crate) struct ClientRequest {
+    limits: ClientLimits,
+    identity: ClientIdentity,
+}
+
+impl Drop for ClientRequest {
+    fn drop(&mut self) {
+        let mut inner = self.limits.inner.lock();
+        if let Some(usage) = inner.clients.get_mut(&self.identity) {
+            usage.in_flight = usage.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+/// Metrics of the limits of the clients, by class of identity.
+#[derive(Debug)]
+struct ClientLimitsMetrics {
+    connection: ClientClassMetrics,
+    api_key: ClientClassMetrics,
+}
+
+impl ClientLimitsMetrics {
+    /// Returns the metrics of the class of the identity.
+    const fn class(&self, identity: &ClientIdentity) -> &ClientClassMetrics {
+        match identity {
+            ClientIdentity::Connection(_) => &self.connection,
+            ClientIdentity::ApiKey(_) => &self.api_key,
+        }
+    }
+}
+
+impl Default for ClientLimitsMetrics {
+    fn default() -> Self {
+        Self {
+            connection: ClientClassMetrics::new_with_labels(&[("class", "connection")]),
+            api_key: ClientClassMetrics::new_with_labels(&[("class", "apiKey")]),
+        }
+    }
+}
+
+/// Metrics of the limits of the clients of a class of identities.
+#[derive(Metrics)]
+#[metrics(scope = "rpc.debug.client_limits")]
+struct ClientClassMetrics {
+    /// The number of requests that were rejected because their client exceeded its concurrency
+    /// limit.
+    concurrency_rejections_total: Counter,
+    /// The number of requests that were rejected because their client exceeded its requests per
+    /// minute.
+    rate_rejections_total: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn connection(id: usize) -> Extensions {
+        let mut ext = Extensions::new();
+        ext.insert(ConnectionId(id));
+        ext
+    }
+
+    #[test]
+    fn limit_concurrent_requests() {
+        let limits = ClientLimits::default();
+        limits.configure(ClientLimitsConfig {
+            max_concurrent_requests: Some(1),
+            ..Default::default()
+        });
+        let first = limits.acquire(&connection(1)).unwrap();
+        assert!(first.is_some());
+        let err = limits.acquire(&connection(1)).unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::RateLimitExceeded { limit: RequestLimit::Concurrent(1), retry_after }
+                if retry_after == CONCURRENCY_RETRY_AFTER
+        ));
+        // other connections aren't affected, and requests without a connection aren't limited
+        assert!(limits.acquire(&connection(2)).unwrap().is_some());
+        assert!(limits.acquire(&Extensions::new()).unwrap().is_none());
+
+        drop(first);
+        assert!(limits.acquire(&connection(1)).unwrap().is_some());
+    }
+
+    #[test]
+    fn limit_requests_per_minute_by_api_key() {
+        let limits = ClientLimits::default();
+        limits.configure(ClientLimitsConfig {
+            max_requests_per_minute: Some(2),
+            api_key_header: Some("x-api-key".to_string()),
+            api_keys: ["a".to_string(), "b".to_string()].into(),
+            allowlist: ["trusted".to_string()].into(),
+            ..Default::default()
+        });
+        let key = |conn_id, key: &str| {
+            let mut ext = connection(conn_id);
+            ext.insert(ClientKey(key.to_string()));
+            ext
+        };
+
+        // the requests of a key are counted across connections
+        limits.acquire(&key(1, "a")).unwrap();
+        limits.acquire(&key(2, "a")).unwrap();
+        let err = limits.acquire(&key(3, "a")).unwrap_err();
+        let EthApiError::RateLimitExceeded { limit, retry_after } = err else {
+            panic!("expected rate limit error, got {err:?}")
+        };
+        assert_eq!(limit, RequestLimit::PerMinute(2));
+        assert!(retry_after > Duration::ZERO && retry_after <= RATE_WINDOW);
+        limits.acquire(&key(1, "b")).unwrap();
+        limits.acquire(&connection(1)).unwrap();
+        for _ in 0..3 {
+            assert!(limits.acquire(&key(1, "trusted")).unwrap().is_none());
+        }
+        // unknown keys are limited by their connection, instead of getting a budget of their own
+        limits.acquire(&key(1, "c")).unwrap();
+        assert!(limits.acquire(&key(1, "d")).is_err());
+        // the window of a client restarts once it expired
+        limits
+            .inner
+            .lock()
+            .clients
+            .values_mut()
+            .for_each(|usage| usage.window_start -= RATE_WINDOW);
+        limits.acquire(&key(3, "a")).unwrap();
+    }
+
+    #[test]
+    fn drop_closed_connections() {
+        let limits = ClientLimits::default();
+        limits.configure(ClientLimitsConfig {
+            max_requests_per_minute: Some(10),
+            ..Default::default()
+        });
+        let open = |id| {
+            let mut ext = connection(id);
+            ext.insert(RpcConnection::default());
+            ext
+        };
+        let (first, second) = (open(1), open(2));
+        drop(limits.acquire(&first).unwrap());
+        let in_flight = limits.acquire(&first).unwrap();
+        drop(limits.acquire(&second).unwrap());
+        assert_eq!(limits.inner.lock().clients.len(), 2);
+
+        // the accounting of a connection is dropped once it's closed, even with requests in flight
+        drop(first);
+        let clients = limits.inner.lock().clients.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(clients, [ClientIdentity::Connection(ConnectionId(2))]);
+        drop(in_flight);
+        drop(second);
+        assert!(limits.inner.lock().clients.is_empty());
+    }
+
+    #[test]
+    fn prune_idle_clients() {
+        let limits = ClientLimits::default();
+        limits.configure(ClientLimitsConfig {
+            max_requests_per_minute: Some(10),
+            ..Default::default()
+        });
+        let in_flight = limits.acquire(&connection(1)).unwrap();
+        drop(limits.acquire(&connection(2)).unwrap());
+        {
+            let mut inner = limits.inner.lock();
+            inner.clients.values_mut().for_each(|usage| usage.window_start -= RATE_WINDOW);
+            inner.last_pruned -= RATE_WINDOW;
+        }
+        // the closed connection is dropped, the one with a request in flight is kept
+        limits.acquire(&connection(3)).unwrap();
+        let clients = limits.inner.lock().clients.keys().cloned().collect::<HashSet<_>>();
+        drop(in_flight);
+        assert_eq!(
+            clients,
+            [
+                ClientIdentity::Connection(ConnectionId(1)),
+                ClientIdentity::Connection(ConnectionId(3))
+            ]
+            .into()
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Coalescing of identical concurrent tracing requests.
+
+use alloy_primitives::B256;
+use futures::{
+    future::{BoxFuture, Shared, WeakShared},
+    FutureExt,
+};
+use jsonrpsee::core::RpcResult;
+use parking_lot::Mutex;
+use reth_metrics::metrics::Counter;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// The key of a tracing request, identical requests have equal keys.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct RequestKey {
+    /// The RPC method of the request.
+    method: &'static str,
+    /// The hash of the traced transaction or block.
+    hash: B256,
+    /// The JSON encoding of the options of the request, with the keys of all objects sorted.
+    options: String,
+}
+
+impl RequestKey {
+    /// Creates the key of a request of the method for the transaction or block with the hash.
+    pub(crate) fn new<T: Serialize>(method: &'static str, hash: B256, options: &T) -> Self {
+        let mut options = serde_json::to_value(options).unwrap_or_default();
+        sort_keys(&mut options);
+        Self { method, hash, options: options.to_string() }
+    }
+}
+
+/// Sorts the keys of all objects of the value, so that options given in a different order are
+/// equal.
+fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_keys);
+        }
+        Value::Array(values) => values.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+type SharedRequest<T> = Shared<BoxFuture<'static, RpcResult<T>>>;
+
+/// The in-flight requests with responses of type `T`.
+///
+/// Identical requests that arrive while a request is in flight await its result instead of being
+/// executed. The result is shared with all of them, but not retained once the request completes,
+/// that's left to the caches.
+#[derive(Debug)]
+pub(crate) struct InFlightRequests<T> {
+    requests: Arc<Mutex<Requests<T>>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> InFlightRequests<T> {
+    /// Returns the result of the identical request that is in flight, if any, or executes the
+    /// request otherwise.
+    ///
+    /// The request is executed as long as any of the requests awaiting it is, and cancelled once
+    /// all of them are dropped.
+    pub(crate) fn coalesce<F>(
+        &self,
+        key: RequestKey,
+        coalesced: &Counter,
+        request: F,
+    ) -> Coalesced<T>
+    where
+        F: Future<Output = RpcResult<T>> + Send + 'static,
+    {
+        let mut requests = self.requests.lock();
+        if let Some(in_flight) = requests.in_flight.get(&key) {
+            if let Some(request) = in_flight.request.upgrade() {
+                coalesced.increment(1);
+                let id = in_flight.id;
+                return Coalesced { request, id, key, requests: Arc::clone(&self.requests) }
+            }
+        }
+
+        requests.next_id += 1;
+        let id = requests.next_id;
+        let (done, done_key) = (Arc::clone(&self.requests), key.clone());
+        let request = async move {
+            let result = request.await;
+            done.lock().remove(&done_key, id);
+            result
+        }
+        .boxed()
+        .shared();
+        let weak = request.downgrade().expect("request is not polled yet");
+        requests.in_flight.insert(key.clone(), InFlight { id, request: weak });
+        Coalesced { request, id, key, requests: Arc::clone(&self.requests) }
+    }
+}
+
+impl<T> Default for InFlightRequests<T> {
+    fn default() -> Self {
+        Self { requests: Arc::new(Mutex::new(Requests { next_id: 0, in_flight: HashMap::new() })) }
+    }
+}
+
+/// The in-flight requests, and the id of the last executed one.
+struct Requests<T> {
+    next_id: u64,
+    in_flight: HashMap<RequestKey, InFlight<T>>,
+}
+
+impl<T> Requests<T> {
+    /// Removes the in-flight request with the key, if it's the one with the id.
+    fn remove(&mut self, key: &RequestKey, id: u64) {
+        if self.in_flight.get(key).is_some_and(|in_flight| in_flight.id == id) {
+            self.in_flight.remove(key);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Requests<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Requests").field("in_flight", &self.in_flight.len()).finish()
+    }
+}
+
+/// An in-flight request.
+struct InFlight<T> {
+    /// The id of the request, which distinguishes it from identical requests executed later.
+    id: u64,
+    /// The request, which is only kept alive by the requests awaiting it.
+    request: WeakShared<BoxFuture<'static, RpcResult<T>>>,
+}
+
+/// A request awaiting the result of an in-flight request.
+#[must_use = "futures do nothing unless polled"]
+pub(crate) struct Coalesced<T> {
+    request: SharedRequest<T>,
+    id: u64,
+    key: RequestKey,
+    requests: Arc<Mutex<Requests<T>>>,
+}
+
+impl<T: Clone> Future for Coalesced<T> {
+    type Output = RpcResult<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.request.poll_unpin(cx)
+    }
+}
+
+impl<T> Drop for Coalesced<T> {
+    fn drop(&mut self) {
+        // the request is cancelled with its last waiter, so later identical requests must not
+        // await it
+        let mut requests = self.requests.lock();
+        if self.request.strong_count() == Some(1) {
+            requests.remove(&self.key, self.id);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Coalesced<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Coalesced").field("key", &self.key).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::types::ErrorObject;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::oneshot;
+
+    /// Returns a request that counts its executions and completes with the result sent to it.
+    fn request(
+        executions: &Arc<AtomicUsize>,
+    ) -> (oneshot::Sender<RpcResult<u64>>, impl Future<Output = RpcResult<u64>> + Send + 'static)
+    {
+        let (tx, rx) = oneshot::channel();
+        let executions = Arc::clone(executions);
+        (tx, async move {
+            executions.fetch_add(1, Ordering::Relaxed);
+            rx.await.unwrap()
+        })
+    }
+
+    #[test]
+    fn canonical_keys() {
+        let key = |method, hash, json: &str| {
+            RequestKey::new(method, hash, &serde_json::from_str::<Value>(json).unwrap())
+        };
+        let tx = "debug_traceTransaction";
+        assert_eq!(
+            key(tx, B256::ZERO, r#"{"tracer":"callTracer","tracerConfig":{"a":1,"b":2}}"#),
+            key(tx, B256::ZERO, r#"{"tracerConfig":{"b":2,"a":1},"tracer":"callTracer"}"#)
+        );
+        assert_ne!(key(tx, B256::ZERO, "{}"), key("debug_traceBlockByHash", B256::ZERO, "{}"));
+        assert_ne!(key(tx, B256::ZERO, "{}"), key(tx, B256::with_last_byte(1), "{}"));
+    }
+
+    #[tokio::test]
+    async fn coalesce_identical_requests() {
+        let in_flight = InFlightRequests::default();
+        let key = RequestKey::new("debug_traceTransaction", B256::ZERO, &());
+        let executions = Arc::new(AtomicUsize::new(0));
+
+        let (tx, first) = request(&executions);
+        let first = in_flight.coalesce(key.clone(), &Counter::noop(), first);
+        let (_, second) = request(&executions);
+        let second = in_flight.coalesce(key.clone(), &Counter::noop(), second);
+        // other requests are executed separately
+        let (other_tx, other) = request(&executions);
+        let other_key = RequestKey::new("debug_traceTransaction", B256::with_last_byte(1), &());
+        let other = in_flight.coalesce(other_key, &Counter::noop(), other);
+
+        tx.send(Ok(1)).unwrap();
+        other_tx.send(Ok(2)).unwrap();
+        assert_eq!((first.await.unwrap(), second.await.unwrap()), (1, 1));
+        assert_eq!(other.await.unwrap(), 2);
+        assert_eq!(executions.load(Ordering::Relaxed), 2);
+
+        // the result isn't retained
+        let (tx, third) = request(&executions);
+        let third = in_flight.coalesce(key, &Counter::noop(), third);
+        tx.send(Err(ErrorObject::owned(-32000, "failed", None::<()>))).unwrap();
+        assert_eq!(third.await.unwrap_err().message(), "failed");
+        assert_eq!(executions.load(Ordering::Relaxed), 3);
+        assert!(in_flight.requests.lock().in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn errors_propagate_to_all_waiters() {
+        let in_flight = InFlightRequests::<u64>::default();
+        let key = RequestKey::new("debug_traceBlockByHash", B256::ZERO, &());
+        let executions = Arc::new(AtomicUsize::new(0));
+
+        let (tx, first) = request(&executions);
+        let first = in_flight.coalesce(key.clone(), &Counter::noop(), first);
+        let (_, second) = request(&executions);
+        let second = in_flight.coalesce(key, &Counter::noop(), second);
+        tx.send(Err(ErrorObject::owned(-32000, "failed", None::<()>))).unwrap();
+        let (first, second) = tokio::join!(first, second);
+        assert_eq!(first.unwrap_err().message(), "failed");
+        assert_eq!(second.unwrap_err().message(), "failed");
+        assert_eq!(executions.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn cancel_with_last_waiter() {
+        let in_flight = InFlightRequests::default();
+        let key = RequestKey::new("debug_traceTransaction", B256::ZERO, &());
+        let executions = Arc::new(AtomicUsize::new(0));
+
+        // the first waiter is dropped after the request started, the second still gets the
+        // result
+        let (tx, first) = request(&executions);
+        let mut first = in_flight.coalesce(key.clone(), &Counter::noop(), first);
+        assert!(futures::poll!(first).is_pending());
+        let (_, second) = request(&executions);
+        let second = in_flight.coalesce(key.clone(), &Counter::noop(), second);
+        drop(first);
+        tx.send(Ok(1)).unwrap();
+        assert_eq!(second.await.unwrap(), 1);
+        assert_eq!(executions.load(Ordering::Relaxed), 1);
+
+        // once all waiters are dropped, the request is cancelled and executed anew
+        let (tx, first) = request(&executions);
+        let mut first = in_flight.coalesce(key.clone(), &Counter::noop(), first);
+        assert!(futures::poll!(first).is_pending());
+        drop(first);
+        assert!(tx.is_closed());
+        let (tx, second) = request(&executions);
+        let second = in_flight.coalesce(key, &Counter::noop(), second);
+        tx.send(Ok(2)).unwrap();
+        assert_eq!(second.await.unwrap(), 2);
+        assert_eq!(executions.load(Ordering::Relaxed), 3);
+    }
+}
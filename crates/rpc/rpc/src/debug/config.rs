@@ -0,0 +1,409 @@
+//! Configuration of the `debug` API.
+
+use super::{
+    AuditLogConfig, ClientLimitsConfig, PersistenceSettingsHandle, TraceCacheConfig, TraceClass,
+    TracerKind, TracerPolicy, WitnessCacheConfig, DEFAULT_MAX_EXECUTION_WITNESS_RANGE,
+    DEFAULT_MAX_RAW_HEADERS, DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION, DEFAULT_MAX_TRACE_MEMORY,
+    DEFAULT_MAX_TRACE_PERMIT_WAIT,
+};
+use reth_rpc_types::trace::geth::{GethDebugTracerConfig, GethDebugTracerType};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io, time::Duration};
+
+/// Settings of the [`DebugApi`](super::DebugApi).
+///
+/// The defaults match a [`DebugApi`](super::DebugApi) created with
+/// [`DebugApi::new`](super::DebugApi::new). Unset fields of a deserialized config take their
+/// defaults, so the config can be embedded in the node config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugApiConfig {
+    /// The tracer to use if none is specified in the tracing options, the struct logger if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_tracer: Option<GethDebugTracerType>,
+    /// The config of the default tracer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_tracer_config: Option<GethDebugTracerConfig>,
+    /// The tracers requests may use, all tracers if unset.
+    pub tracer_policy: TracerPolicy,
+    /// The maximum number of threads the transactions of a block are traced on.
+    pub trace_block_parallelism: usize,
+    /// Settings of the cache for the traces of recently traced blocks, disabled if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_cache: Option<TraceCacheConfig>,
+    /// The maximum size of the cache for the intermediate states of recently traced blocks, in
+    /// bytes, disabled if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_snapshot_cache_max_bytes: Option<usize>,
+    /// The maximum size of the cache for the state read by requests on top of recent blocks, in
+    /// bytes, disabled if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_read_cache_max_bytes: Option<usize>,
+    /// Settings of the cache for recently generated execution witnesses, disabled if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witness_cache: Option<WitnessCacheConfig>,
+    /// The maximum number of blocks of a `debug_executionWitnessRange` request.
+    pub max_execution_witness_range: u64,
+    /// The maximum number of headers of a `debug_getRawHeaders` request.
+    pub max_raw_headers: u64,
+    /// The maximum number of `debug` subscriptions of a connection.
+    pub max_subscriptions_per_connection: u32,
+    /// The number of concurrent requests of the classes that are limited separately, instead of
+    /// sharing the tracing permits of the node.
+    pub trace_permits: BTreeMap<TraceClass, usize>,
+    /// The number of the shared tracing permits reserved for call and transaction traces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserved_call_permits: Option<usize>,
+    /// The maximum time a request waits for a trace permit.
+    #[serde(with = "humantime_serde")]
+    pub max_trace_permit_wait: Duration,
+    /// The maximum estimated memory of the traces of all in-flight requests, in bytes.
+    pub max_trace_memory: usize,
+    /// The gas limit of the calls traced by `debug_traceCall` and `debug_traceCallMany`, the gas
+    /// cap of `eth_call` if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_cap: Option<u64>,
+    /// Whether `debug_traceTransaction` traces transactions that are only in the pool,
+    /// speculatively on top of the latest state, instead of failing with `TransactionNotFound`.
+    pub trace_pool_transactions: bool,
+    /// Whether `debug_dbTables` is enabled, which exposes the sizes of the tables of the
+    /// database. Disabled by default.
+    pub db_tables: bool,
+    /// The maximum gas used of the blocks that are traced with the tracers of each kind, blocks
+    /// traced with tracers without a limit aren't limited.
+    pub max_block_gas: BTreeMap<TracerKind, u64>,
+    /// Whether block traces may exceed the limits of [`Self::max_block_gas`] if they're forced by
+    /// the request.
+    pub allow_block_gas_override: bool,
+    /// Settings of the audit log of the tracing requests, disabled if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<AuditLogConfig>,
+    /// The limits of the tracing requests of each client, unlimited by default.
+    pub client_limits: ClientLimitsConfig,
+    /// The handle to the persistence settings of the node, which the node builds from the config
+    /// of its engine. `debug_getTrieFlushInterval` and `debug_setTrieFlushInterval` are
+    /// unsupported if unset.
+    #[serde(skip)]
+    pub persistence_settings: Option<PersistenceSettingsHandle>,
+}
+
+impl Default for DebugApiConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugApiConfig {
+    /// Returns the default settings.
+    pub const fn new() -> Self {
+        Self {
+            default_tracer: None,
+            default_tracer_config: None,
+            tracer_policy: TracerPolicy::allow_all(),
+            trace_block_parallelism: 1,
+            trace_cache: None,
+            state_snapshot_cache_max_bytes: None,
+            state_read_cache_max_bytes: None,
+            witness_cache: None,
+            max_execution_witness_range: DEFAULT_MAX_EXECUTION_WITNESS_RANGE,
+            max_raw_headers: DEFAULT_MAX_RAW_HEADERS,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            trace_permits: BTreeMap::new(),
+            reserved_call_permits: None,
+            max_trace_permit_wait: DEFAULT_MAX_TRACE_PERMIT_WAIT,
+            max_trace_memory: DEFAULT_MAX_TRACE_MEMORY,
+            gas_cap: None,
+            trace_pool_transactions: false,
+            db_tables: false,
+            max_block_gas: BTreeMap::new(),
+            allow_block_gas_override: false,
+            audit_log: None,
+            client_limits: ClientLimitsConfig::unlimited(),
+            persistence_settings: None,
+        }
+    }
+
+    /// Configures the tracer, and its config, that is used if no tracer is specified in the
+    /// tracing options of a request
+    pub fn default_tracer(
+        mut self,
+        tracer: GethDebugTracerType,
+        config: GethDebugTracerConfig,
+    ) -> Self {
+        self.default_tracer = Some(tracer);
+        self.default_tracer_config = Some(config);
+        self
+    }
+
+    /// Restricts the tracers requests may use
+    pub fn tracer_policy(mut self, policy: TracerPolicy) -> Self {
+        self.tracer_policy = policy;
+        self
+    }
+
+    /// Configures the maximum number of threads the transactions of a block are traced on
+    pub const fn trace_block_parallelism(mut self, parallelism: usize) -> Self {
+        self.trace_block_parallelism = parallelism;
+        self
+    }
+
+    /// Enables the cache for the traces of recently traced blocks
+    pub const fn trace_cache(mut self, config: TraceCacheConfig) -> Self {
+        self.trace_cache = Some(config);
+        self
+    }
+
+    /// Enables the cache for the intermediate states of recently traced blocks
+    pub const fn state_snapshot_cache(mut self, max_bytes: usize) -> Self {
+        self.state_snapshot_cache_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Enables the cache for the state read by requests on top of recent blocks
+    pub const fn state_read_cache(mut self, max_bytes: usize) -> Self {
+        self.state_read_cache_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Enables the cache for recently generated execution witnesses
+    pub const fn witness_cache(mut self, config: WitnessCacheConfig) -> Self {
+        self.witness_cache = Some(config);
+        self
+    }
+
+    /// Configures the maximum number of blocks of a `debug_executionWitnessRange` request
+    pub const fn max_execution_witness_range(mut self, max_blocks: u64) -> Self {
+        self.max_execution_witness_range = max_blocks;
+        self
+    }
+
+    /// Configures the maximum number of headers of a `debug_getRawHeaders` request
+    pub const fn max_raw_headers(mut self, max_headers: u64) -> Self {
+        self.max_raw_headers = max_headers;
+        self
+    }
+
+    /// Configures the maximum number of subscriptions of a connection
+    pub const fn max_subscriptions_per_connection(mut self, max_subscriptions: u32) -> Self {
+        self.max_subscriptions_per_connection = max_subscriptions;
+        self
+    }
+
+    /// Limits the number of concurrent requests of the given class to `permits`
+    pub fn trace_permits(mut self, class: TraceClass, permits: usize) -> Self {
+        self.trace_permits.insert(class, permits);
+        self
+    }
+
+    /// Reserves `permits` of the shared tracing permits for call and transaction traces
+    pub const fn reserved_call_permits(mut self, permits: usize) -> Self {
+        self.reserved_call_permits = Some(permits);
+        self
+    }
+
+    /// Configures the maximum time a request waits for a trace permit
+    pub const fn max_trace_permit_wait(mut self, max_wait: Duration) -> Self {
+        self.max_trace_permit_wait = max_wait;
+        self
+    }
+
+    /// Configures the maximum estimated memory of the traces of all in-flight requests, in bytes
+    pub const fn max_trace_memory(mut self, max_bytes: usize) -> Self {
+        self.max_trace_memory = max_bytes;
+        self
+    }
+
+    /// Configures the gas limit of traced calls, independent of the gas cap of `eth_call`
+    pub const fn gas_cap(mut self, gas_cap: u64) -> Self {
+        self.gas_cap = Some(gas_cap);
+        self
+    }
+
+    /// Configures whether transactions that are only in the pool are traced speculatively
+    pub const fn trace_pool_transactions(mut self, enabled: bool) -> Self {
+        self.trace_pool_transactions = enabled;
+        self
+    }
+
+    /// Configures whether `debug_dbTables` is enabled
+    pub const fn db_tables(mut self, enabled: bool) -> Self {
+        self.db_tables = enabled;
+        self
+    }
+
+    /// Limits the gas used of the blocks that are traced with tracers of the given kind to
+    /// `max_gas`
+    pub fn max_block_gas(mut self, kind: TracerKind, max_gas: u64) -> Self {
+        self.max_block_gas.insert(kind, max_gas);
+        self
+    }
+
+    /// Configures whether requests may force block traces beyond the gas limits of their tracers
+    pub const fn allow_block_gas_override(mut self, enabled: bool) -> Self {
+        self.allow_block_gas_override = enabled;
+        self
+    }
+
+    /// Enables the audit log of the tracing requests
+    pub fn audit_log(mut self, config: AuditLogConfig) -> Self {
+        self.audit_log = Some(config);
+        self
+    }
+
+    /// Limits the tracing requests of each client
+    pub fn client_limits(mut self, limits: ClientLimitsConfig) -> Self {
+        self.client_limits = limits;
+        self
+    }
+
+    /// Configures the handle to the persistence settings of the node
+    pub fn persistence_settings(mut self, settings: PersistenceSettingsHandle) -> Self {
+        self.persistence_settings = Some(settings);
+        self
+    }
+
+    /// Checks that the config is valid for a node with the given number of tracing permits.
+    pub const fn validate(&self, tracing_permits: usize) -> Result<(), DebugApiConfigError> {
+        match self.reserved_call_permits {
+            Some(reserved) => validate_reserved_call_permits(reserved, tracing_permits),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that `reserved` of the `tracing_permits` can be reserved for call traces.
+pub(crate) const fn validate_reserved_call_permits(
+    reserved: usize,
+    tracing_permits: usize,
+) -> Result<(), DebugApiConfigError> {
+    if reserved >= tracing_permits {
+        return Err(DebugApiConfigError::ReservedCallPermits { reserved, tracing_permits })
+    }
+    Ok(())
+}
+
+/// Errors of an invalid [`DebugApiConfig`].
+#[derive(Debug, thiserror::Error)]
+pub enum DebugApiConfigError {
+    /// The reserved call permits leave no tracing permits for the other requests.
+    #[error(
+        "{reserved} reserved call permits leave none of the {tracing_permits} tracing permits for \
+         other requests"
+    )]
+    ReservedCallPermits {
+        /// The number of reserved call permits.
+        reserved: usize,
+        /// The number of tracing permits of the node.
+        tracing_permits: usize,
+    },
+    /// The audit log can't be opened.
+    #[error("failed to open the audit log: {0}")]
+    AuditLog(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::trace::geth::GethDebugBuiltInTracerType;
+
+    fn custom_config() -> DebugApiConfig {
+        DebugApiConfig::default()
+            .default_tracer(
+                GethDebugBuiltInTracerType::CallTracer.into(),
+                GethDebugTracerConfig(serde_json::json!({ "onlyTopCall": true })),
+            )
+            .tracer_policy(TracerPolicy::Deny([TracerKind::StructLogger, TracerKind::Js].into()))
+            .trace_block_parallelism(4)
+            .trace_cache(TraceCacheConfig::default())
+            .state_snapshot_cache(1024)
+            .state_read_cache(4096)
+            .witness_cache(WitnessCacheConfig { max_entries: 2, max_bytes: 2048 })
+            .max_execution_witness_range(10)
+            .max_raw_headers(16)
+            .max_subscriptions_per_connection(2)
+            .trace_permits(TraceClass::Witness, 2)
+            .reserved_call_permits(1)
+            .max_trace_permit_wait(Duration::from_millis(1500))
+            .max_trace_memory(1 << 20)
+            .gas_cap(500_000_000)
+            .trace_pool_transactions(true)
+            .db_tables(true)
+            .max_block_gas(TracerKind::StructLogger, 10_000_000)
+            .allow_block_gas_override(true)
+            .audit_log(AuditLogConfig { path: "debug-audit.jsonl".into(), buffer: 16 })
+            .client_limits(ClientLimitsConfig {
+                max_concurrent_requests: Some(2),
+                max_requests_per_minute: Some(60),
+                api_key_header: Some("x-api-key".to_string()),
+                api_keys: ["client".to_string()].into(),
+                allowlist: ["trusted".to_string()].into(),
+            })
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        for config in [DebugApiConfig::default(), custom_config()] {
+            let json = serde_json::to_string(&config).unwrap();
+            assert_eq!(serde_json::from_str::<DebugApiConfig>(&json).unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn toml_roundtrip() {
+        for config in [DebugApiConfig::default(), custom_config()] {
+            let toml = toml::to_string(&config).unwrap();
+            assert_eq!(toml::from_str::<DebugApiConfig>(&toml).unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn reject_reserved_call_permits() {
+        let config = DebugApiConfig::default().reserved_call_permits(2);
+        assert!(config.validate(3).is_ok());
+        assert!(matches!(
+            config.validate(2),
+            Err(DebugApiConfigError::ReservedCallPermits { reserved: 2, tracing_permits: 2 })
+        ));
+        assert!(DebugApiConfig::default().validate(0).is_ok());
+    }
+
+    #[test]
+    fn deserialize_partial() {
+        let config: DebugApiConfig = toml::from_str(
+            r#"
+            max_trace_permit_wait = "30s"
+
+            [trace_permits]
+            block = 2
+
+            [max_block_gas]
+            structLogger = 10000000
+
+            [tracer_policy]
+            allow = ["callTracer", "prestateTracer"]
+
+            [audit_log]
+            path = "debug-audit.jsonl"
+
+            [client_limits]
+            max_requests_per_minute = 60
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            DebugApiConfig::default()
+                .max_trace_permit_wait(Duration::from_secs(30))
+                .trace_permits(TraceClass::Block, 2)
+                .max_block_gas(TracerKind::StructLogger, 10_000_000)
+                .tracer_policy(TracerPolicy::Allow(
+                    [TracerKind::CallTracer, TracerKind::PreStateTracer].into()
+                ))
+                .audit_log(AuditLogConfig::new("debug-audit.jsonl".into()))
+                .client_limits(ClientLimitsConfig {
+                    max_requests_per_minute: Some(60),
+                    ..Default::default()
+                })
+        );
+    }
+}
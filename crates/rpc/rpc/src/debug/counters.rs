@@ -0,0 +1,246 @@
+//! Counters of traced executions, as returned with `includeStats`.
+
+use alloy_primitives::{Address, U256};
+use reth_rpc_types::debug::TraceCounters;
+use revm::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+    },
+    primitives::Log,
+    Database, EvmContext, GetInspector, Inspector,
+};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Records the counters of the traced executions of a request.
+///
+/// Like the [`ExecutionStatsRecorder`](super::db_stats::ExecutionStatsRecorder), executions
+/// count locally and add their counters once they're done.
+#[derive(Debug, Default)]
+pub(crate) struct TraceCountersRecorder {
+    steps: AtomicU64,
+    struct_log_bytes: AtomicU64,
+    subcalls: AtomicU64,
+    database_reads: AtomicU64,
+    state_load_nanos: AtomicU64,
+    evm_execution_nanos: AtomicU64,
+}
+
+impl TraceCountersRecorder {
+    /// Returns the counters of all executions recorded so far.
+    pub(crate) fn counters(&self) -> TraceCounters {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        TraceCounters {
+            steps: load(&self.steps),
+            struct_log_bytes: load(&self.struct_log_bytes),
+            subcalls: load(&self.subcalls),
+            database_reads: load(&self.database_reads),
+            state_load_micros: load(&self.state_load_nanos) / 1_000,
+            evm_execution_micros: load(&self.evm_execution_nanos) / 1_000,
+        }
+    }
+
+    /// Adds the counters of an execution, given the reads from the state provider during the
+    /// execution and the time they took.
+    pub(crate) fn add(&self, counts: &ExecutionCounts, database_reads: u64, state_load: Duration) {
+        let add = |counter: &AtomicU64, n: u64| {
+            if n > 0 {
+                counter.fetch_add(n, Ordering::Relaxed);
+            }
+        };
+        add(&self.steps, counts.steps);
+        add(&self.struct_log_bytes, counts.struct_log_bytes);
+        add(&self.subcalls, counts.frames.saturating_sub(1));
+        add(&self.database_reads, database_reads);
+        add(&self.state_load_nanos, state_load.as_nanos() as u64);
+        add(
+            &self.evm_execution_nanos,
+            counts.execution.saturating_sub(state_load).as_nanos() as u64,
+        );
+    }
+}
+
+/// The counts of a single traced execution.
+#[derive(Debug, Default)]
+pub(crate) struct ExecutionCounts {
+    /// The number of executed steps.
+    pub(crate) steps: u64,
+    /// The number of executed calls and creations, including the top call.
+    pub(crate) frames: u64,
+    /// The size of the JSON encoding of the struct logs, if the struct logger traced the
+    /// execution.
+    pub(crate) struct_log_bytes: u64,
+    /// The wall-clock time of the execution, including the reads from the state provider.
+    pub(crate) execution: Duration,
+}
+
+/// An inspector that counts the steps and frames of an execution, and passes everything on to
+/// the wrapped inspector.
+#[derive(Debug)]
+pub(crate) struct CountingInspector<'a, I> {
+    inner: I,
+    counts: &'a mut ExecutionCounts,
+}
+
+impl<'a, I> CountingInspector<'a, I> {
+    /// Wraps the inspector, counting on the given counts.
+    pub(crate) fn new(inner: I, counts: &'a mut ExecutionCounts) -> Self {
+        Self { inner, counts }
+    }
+}
+
+impl<DB: Database, I: GetInspector<DB>> Inspector<DB> for CountingInspector<'_, I> {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.inner.get_inspector().initialize_interp(interp, context)
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.counts.steps += 1;
+        self.inner.get_inspector().step(interp, context)
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.inner.get_inspector().step_end(interp, context)
+    }
+
+    fn log(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>, log: &Log) {
+        self.inner.get_inspector().log(interp, context, log)
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.counts.frames += 1;
+        self.inner.get_inspector().call(context, inputs)
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.inner.get_inspector().call_end(context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.counts.frames += 1;
+        self.inner.get_inspector().create(context, inputs)
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.inner.get_inspector().create_end(context, inputs, outcome)
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.counts.frames += 1;
+        self.inner.get_inspector().eofcreate(context, inputs)
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.inner.get_inspector().eofcreate_end(context, inputs, outcome)
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        self.inner.get_inspector().selfdestruct(contract, target, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{hex, Bytes, TxKind};
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{AccountInfo, Bytecode, SpecId},
+        Evm,
+    };
+    use revm_inspectors::tracing::{TracingInspector, TracingInspectorConfig};
+
+    #[test]
+    fn counts_steps_and_subcalls() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // the callee only stops, the caller executes POP(CALL(GAS, callee, 0, 0, 0, 0, 0)) twice,
+        // then STOP
+        let callee = Address::with_last_byte(0x11);
+        let call =
+            [&hex!("6000600060006000600073")[..], callee.as_slice(), &hex!("5af150")].concat();
+        let caller = Address::with_last_byte(0x10);
+        for (address, code) in
+            [(callee, Bytes::from_static(&[0])), (caller, [&call[..], &call, &[0]].concat().into())]
+        {
+            let bytecode = Bytecode::new_raw(code);
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    code_hash: bytecode.hash_slow(),
+                    code: Some(bytecode),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut counts = ExecutionCounts::default();
+        let mut tracer = TracingInspector::new(TracingInspectorConfig::default_geth());
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(CountingInspector::new(&mut tracer, &mut counts))
+            .with_spec_id(SpecId::CANCUN)
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(0xca);
+                tx.transact_to = TxKind::Call(caller);
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        drop(evm);
+
+        // 2 * 9 steps for the calls, the STOP of the caller and the STOP of each callee
+        assert_eq!(counts.steps, 2 * 9 + 1 + 2);
+        assert_eq!(counts.frames, 3);
+        // all steps are passed on to the wrapped inspector
+        let steps =
+            tracer.traces().nodes().iter().map(|node| node.trace.steps.len()).sum::<usize>();
+        assert_eq!(steps as u64, counts.steps);
+
+        let recorder = TraceCountersRecorder::default();
+        counts.execution = Duration::from_micros(30);
+        recorder.add(&counts, 2, Duration::from_micros(10));
+        recorder.add(&counts, 1, Duration::from_micros(10));
+        assert_eq!(
+            recorder.counters(),
+            TraceCounters {
+                steps: 2 * counts.steps,
+                struct_log_bytes: 0,
+                subcalls: 4,
+                database_reads: 3,
+                state_load_micros: 20,
+                evm_execution_micros: 40,
+            }
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Statistics of the database served by the `debug` API.
+
+use super::{DebugApi, TraceClass};
+use jsonrpsee::core::RpcResult;
+use reth_chainspec::EthereumHardforks;
+use reth_errors::ProviderError;
+use reth_provider::{
+    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
+    StateProviderFactory, StorageStatsReader,
+};
+use reth_rpc_eth_api::{helpers::TraceExt, EthApiTypes, FromEthApiError};
+use reth_rpc_eth_types::EthApiError;
+use reth_rpc_types::debug::{BlockRange, DbTableStats, DbTables, StaticFileSegmentRanges};
+
+impl<Provider, Eth> DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + StorageStatsReader
+        + 'static,
+    Eth: EthApiTypes + TraceExt + 'static,
+{
+    /// Returns the statistics of the tables of the database and the block ranges of the static
+    /// files, unless `debug_dbTables` is disabled.
+    pub(super) async fn debug_db_tables(&self) -> RpcResult<DbTables> {
+        if !self.inner.db_tables {
+            return Err(EthApiError::Unsupported("debug_dbTables is disabled").into())
+        }
+
+        // the tables and the static files are scanned on the tracing pool, so that the scan holds
+        // a permit of the blocking task guard like the traces of whole blocks
+        let _permit = self.acquire_trace_permit(TraceClass::Block).await?;
+        let this = self.clone();
+        let db_tables = self.spawn_tracing(move |_| {
+            let provider_error = |err: ProviderError| Eth::Error::from_eth_err(err.into());
+            let mut tables = this
+                .inner
+                .provider
+                .table_stats()
+                .map_err(provider_error)?
+                .into_iter()
+                .map(|stats| {
+                    let size = |pages: usize| (pages * stats.page_size) as u64;
+                    DbTableStats {
+                        name: stats.name.to_string(),
+                        dupsort: stats.dupsort,
+                        entries: stats.entries as u64,
+                        leaf_size: size(stats.leaf_pages),
+                        branch_size: size(stats.branch_pages),
+                        overflow_size: size(stats.overflow_pages),
+                        total_size: stats.total_size() as u64,
+                    }
+                })
+                .collect::<Vec<_>>();
+            tables.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+            let static_files = this
+                .inner
+                .provider
+                .static_file_block_ranges()
+                .map_err(provider_error)?
+                .into_iter()
+                .map(|(segment, ranges)| StaticFileSegmentRanges {
+                    segment: segment.as_str().to_string(),
+                    block_ranges: ranges
+                        .into_iter()
+                        .map(|range| BlockRange { start: range.start(), end: range.end() })
+                        .collect(),
+                })
+                .collect();
+            Ok(DbTables { tables, static_files })
+        });
+        db_tables.await.map_err(Into::into)
+    }
+}
@@ -0,0 +1,247 @@
+//! Statistics of the database lookups of traced executions.
+
+use alloy_primitives::{Address, B256, U256};
+use reth_errors::ProviderError;
+use reth_rpc_eth_types::StateCacheDb;
+use reth_rpc_types::debug::ExecutionStats;
+use revm::{
+    db::AccountState,
+    primitives::{AccountInfo, Bytecode},
+    Database, DatabaseRef,
+};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Records the database lookups of the executions of a request.
+///
+/// Executions count their lookups locally and add them to the recorder once they're done, so the
+/// recorder can be shared by executions on multiple threads without contending on every lookup.
+#[derive(Debug, Default)]
+pub(crate) struct ExecutionStatsRecorder {
+    account_cache_hits: AtomicU64,
+    account_provider_reads: AtomicU64,
+    storage_cache_hits: AtomicU64,
+    storage_provider_reads: AtomicU64,
+    code_cache_hits: AtomicU64,
+    code_provider_reads: AtomicU64,
+    provider_read_nanos: AtomicU64,
+}
+
+impl ExecutionStatsRecorder {
+    /// Returns the statistics of all lookups recorded so far.
+    pub(crate) fn stats(&self) -> ExecutionStats {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        ExecutionStats {
+            account_cache_hits: load(&self.account_cache_hits),
+            account_provider_reads: load(&self.account_provider_reads),
+            storage_cache_hits: load(&self.storage_cache_hits),
+            storage_provider_reads: load(&self.storage_provider_reads),
+            code_cache_hits: load(&self.code_cache_hits),
+            code_provider_reads: load(&self.code_provider_reads),
+            provider_read_micros: load(&self.provider_read_nanos) / 1_000,
+        }
+    }
+
+    fn add(&self, lookups: &Lookups) {
+        let add = |counter: &AtomicU64, n: u64| {
+            if n > 0 {
+                counter.fetch_add(n, Ordering::Relaxed);
+            }
+        };
+        add(&self.account_cache_hits, lookups.account_cache_hits);
+        add(&self.account_provider_reads, lookups.account_provider_reads);
+        add(&self.storage_cache_hits, lookups.storage_cache_hits);
+        add(&self.storage_provider_reads, lookups.storage_provider_reads);
+        add(&self.code_cache_hits, lookups.code_cache_hits);
+        add(&self.code_provider_reads, lookups.code_provider_reads);
+        add(&self.provider_read_nanos, lookups.provider_read.as_nanos() as u64);
+    }
+}
+
+/// The lookups of a single execution.
+#[derive(Debug, Default)]
+struct Lookups {
+    account_cache_hits: u64,
+    account_provider_reads: u64,
+    storage_cache_hits: u64,
+    storage_provider_reads: u64,
+    code_cache_hits: u64,
+    code_provider_reads: u64,
+    provider_read: Duration,
+}
+
+/// A [`Database`] that counts the lookups of an execution on the wrapped cache database, and
+/// whether they're served by its cache or read from the state provider.
+///
+/// The counts are added to the recorder when this is dropped. Without a recorder, all lookups
+/// are passed through as is.
+pub(crate) struct StatsDb<'a, 'b> {
+    db: &'a mut StateCacheDb<'b>,
+    recorder: Option<&'a ExecutionStatsRecorder>,
+    lookups: Lookups,
+}
+
+impl<'a, 'b> StatsDb<'a, 'b> {
+    /// Wraps the database, recording its lookups on the given recorder, if any.
+    pub(crate) fn new(
+        db: &'a mut StateCacheDb<'b>,
+        recorder: Option<&'a ExecutionStatsRecorder>,
+    ) -> Self {
+        Self { db, recorder, lookups: Lookups::default() }
+    }
+
+    /// Returns the number of lookups read from the state provider so far.
+    pub(crate) const fn provider_reads(&self) -> u64 {
+        self.lookups.account_provider_reads +
+            self.lookups.storage_provider_reads +
+            self.lookups.code_provider_reads
+    }
+
+    /// Returns the time spent reading from the state provider so far.
+    pub(crate) const fn provider_read_time(&self) -> Duration {
+        self.lookups.provider_read
+    }
+
+    /// Executes a lookup that isn't served by the cache and measures the time of the read.
+    fn read<T>(&mut self, read: impl FnOnce(&mut StateCacheDb<'b>) -> T) -> T {
+        let started_at = Instant::now();
+        let res = read(self.db);
+        self.lookups.provider_read += started_at.elapsed();
+        res
+    }
+}
+
+impl Database for StatsDb<'_, '_> {
+    type Error = ProviderError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if self.recorder.is_none() {
+            return self.db.basic(address)
+        }
+        if self.db.accounts.contains_key(&address) {
+            self.lookups.account_cache_hits += 1;
+            return self.db.basic(address)
+        }
+        self.lookups.account_provider_reads += 1;
+        self.read(|db| db.basic(address))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if self.recorder.is_none() {
+            return self.db.code_by_hash(code_hash)
+        }
+        if self.db.contracts.contains_key(&code_hash) {
+            self.lookups.code_cache_hits += 1;
+            return self.db.code_by_hash(code_hash)
+        }
+        self.lookups.code_provider_reads += 1;
+        self.read(|db| db.code_by_hash(code_hash))
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if self.recorder.is_none() {
+            return self.db.storage(address, index)
+        }
+        // the storage of accounts that don't exist or whose storage was cleared isn't read either
+        let cached = self.db.accounts.get(&address).is_some_and(|account| {
+            account.storage.contains_key(&index) ||
+                matches!(
+                    account.account_state,
+                    AccountState::NotExisting | AccountState::StorageCleared
+                )
+        });
+        if cached {
+            self.lookups.storage_cache_hits += 1;
+            return self.db.storage(address, index)
+        }
+        self.lookups.storage_provider_reads += 1;
+        self.read(|db| db.storage(address, index))
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl DatabaseRef for StatsDb<'_, '_> {
+    type Error = ProviderError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl Drop for StatsDb<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(recorder) = self.recorder {
+            recorder.add(&self.lookups);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_revm::database::StateProviderDatabase;
+    use reth_rpc_eth_types::cache::db::StateProviderTraitObjWrapper;
+    use revm::db::CacheDB;
+
+    #[test]
+    fn counts_cache_hits_and_provider_reads() {
+        let provider = MockEthProvider::default();
+        let address = Address::random();
+        provider.add_account(
+            address,
+            ExtendedAccount::new(1, U256::ZERO).extend_storage([(B256::ZERO, U256::from(1))]),
+        );
+        let mut db =
+            CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(&provider)));
+
+        let recorder = ExecutionStatsRecorder::default();
+        {
+            let mut db = StatsDb::new(&mut db, Some(&recorder));
+            assert_eq!(db.basic(address).unwrap().map(|account| account.nonce), Some(1));
+            db.basic(address).unwrap();
+            // the slot is only read once, the account is already cached
+            assert_eq!(db.storage(address, U256::ZERO).unwrap(), U256::from(1));
+            db.storage(address, U256::ZERO).unwrap();
+            // accounts that don't exist have no storage to be read
+            let missing = Address::random();
+            db.basic(missing).unwrap();
+            db.storage(missing, U256::ZERO).unwrap();
+            assert_eq!(db.provider_reads(), 3);
+        }
+
+        let stats = recorder.stats();
+        assert_eq!(
+            stats,
+            ExecutionStats {
+                account_cache_hits: 1,
+                account_provider_reads: 2,
+                storage_cache_hits: 2,
+                storage_provider_reads: 1,
+                provider_read_micros: stats.provider_read_micros,
+                ..Default::default()
+            }
+        );
+
+        // lookups without a recorder aren't counted
+        StatsDb::new(&mut db, None).basic(Address::random()).unwrap();
+        assert_eq!(recorder.stats(), stats);
+    }
+}
@@ -0,0 +1,288 @@
+//! LRU cache for values of recent blocks that is bounded by the total size of the values.
+
+use alloy_primitives::B256;
+use parking_lot::Mutex;
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::HashSet,
+    fmt::{self, Debug, Formatter},
+    hash::Hash,
+    sync::Arc,
+};
+
+/// Key of a value of a block in a [`ByteBoundedLru`].
+pub(crate) trait BlockKey: Clone + Eq + Hash {
+    /// Returns the hash of the block the value belongs to.
+    fn block_hash(&self) -> B256;
+}
+
+impl<T: Clone + Eq + Hash> BlockKey for (B256, T) {
+    fn block_hash(&self) -> B256 {
+        self.0
+    }
+}
+
+/// An LRU cache for values of recent blocks.
+///
+/// The cache is bounded by the total size of the cached values, as estimated by the caller, and by
+/// the number of cached values. Clones share the cached values.
+pub(crate) struct ByteBoundedLru<K: BlockKey, V> {
+    inner: Arc<Mutex<ByteBoundedLruInner<K, V>>>,
+}
+
+impl<K: BlockKey, V: Clone> ByteBoundedLru<K, V> {
+    /// Creates a new, empty cache that holds at most `max_entries` values with a total size of at
+    /// most `max_bytes`, and records its metrics under the given scope.
+    pub(crate) fn new(scope: &str, max_entries: u32, max_bytes: usize) -> Self {
+        let inner = ByteBoundedLruInner {
+            entries: LruMap::new(ByLength::new(max_entries)),
+            memory_usage: 0,
+            max_entries,
+            max_bytes,
+            metrics: ByteBoundedLruMetrics::new(scope),
+        };
+        Self { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// Returns the cached value for the given key.
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.inner.lock();
+        let value = inner.entries.get(key).map(|entry| entry.value.clone());
+        inner.record_lookup(value.is_some());
+        value
+    }
+
+    /// Returns the greatest key that matches the given filter, together with its cached value.
+    pub(crate) fn get_max(&self, filter: impl Fn(&K) -> bool) -> Option<(K, V)>
+    where
+        K: Ord,
+    {
+        let mut inner = self.inner.lock();
+        let key = inner.entries.iter().map(|(key, _)| key).filter(|key| filter(key)).max().cloned();
+        let value = key.and_then(|key| {
+            let value = inner.entries.get(&key)?.value.clone();
+            Some((key, value))
+        });
+        inner.record_lookup(value.is_some());
+        value
+    }
+
+    /// Returns the size of the cached value for the given key, without marking it as recently
+    /// used.
+    pub(crate) fn size(&self, key: &K) -> Option<usize> {
+        self.inner.lock().entries.peek(key).map(|entry| entry.size)
+    }
+
+    /// Caches the value of the given size, evicting the least recently used values if the cache is
+    /// full.
+    ///
+    /// Values that exceed the size limit of the cache on their own are not cached.
+    pub(crate) fn insert(&self, key: K, value: V, size: usize) {
+        let mut inner = self.inner.lock();
+        if inner.max_entries == 0 || size > inner.max_bytes {
+            return
+        }
+
+        inner.remove(&key);
+        while inner.entries.len() >= inner.max_entries as usize ||
+            inner.memory_usage + size > inner.max_bytes
+        {
+            let Some((_, evicted)) = inner.entries.pop_oldest() else { break };
+            inner.memory_usage -= evicted.size;
+        }
+
+        inner.entries.insert(key, Entry { value, size });
+        inner.memory_usage += size;
+        inner.update_metrics();
+    }
+
+    /// Removes the cached values of the given blocks.
+    pub(crate) fn remove_blocks(&self, block_hashes: &HashSet<B256>) {
+        let mut inner = self.inner.lock();
+        let keys = inner
+            .entries
+            .iter()
+            .filter(|(key, _)| block_hashes.contains(&key.block_hash()))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            inner.remove(&key);
+        }
+    }
+
+    /// Returns the number of cached values.
+    pub(crate) fn len(&self) -> usize {
+        self.inner.lock().entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: BlockKey, V> Clone for ByteBoundedLru<K, V> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<K: BlockKey, V> Debug for ByteBoundedLru<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock();
+        f.debug_struct("ByteBoundedLru")
+            .field("max_entries", &inner.max_entries)
+            .field("max_bytes", &inner.max_bytes)
+            .field("cache_length", &inner.entries.len())
+            .field("cache_memory_usage", &inner.memory_usage)
+            .finish()
+    }
+}
+
+struct ByteBoundedLruInner<K: BlockKey, V> {
+    entries: LruMap<K, Entry<V>, ByLength>,
+    /// The total size of the cached values.
+    memory_usage: usize,
+    max_entries: u32,
+    max_bytes: usize,
+    metrics: ByteBoundedLruMetrics,
+}
+
+impl<K: BlockKey, V> ByteBoundedLruInner<K, V> {
+    fn remove(&mut self, key: &K) {
+        if let Some(removed) = self.entries.remove(key) {
+            self.memory_usage -= removed.size;
+            self.update_metrics();
+        }
+    }
+
+    fn record_lookup(&self, hit: bool) {
+        if hit {
+            self.metrics.hits_total.increment(1);
+        } else {
+            self.metrics.misses_total.increment(1);
+        }
+    }
+
+    fn update_metrics(&self) {
+        self.metrics.cached_count.set(self.entries.len() as f64);
+        self.metrics.memory_usage.set(self.memory_usage as f64);
+    }
+}
+
+/// A cached value.
+struct Entry<V> {
+    value: V,
+    /// The size of the value, as estimated when it was cached.
+    size: usize,
+}
+
+#[derive(Metrics)]
+#[metrics(dynamic = true)]
+struct ByteBoundedLruMetrics {
+    /// The number of cached values.
+    cached_count: Gauge,
+    /// The estimated total size of the cached values, in bytes.
+    memory_usage: Gauge,
+    /// The number of cache hits.
+    hits_total: Counter,
+    /// The number of cache misses.
+    misses_total: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Key = (B256, u8);
+
+    fn lru(max_entries: u32, max_bytes: usize) -> ByteBoundedLru<Key, u8> {
+        ByteBoundedLru::new("rpc.debug.test_cache", max_entries, max_bytes)
+    }
+
+    fn key(block: u8, value: u8) -> Key {
+        (B256::with_last_byte(block), value)
+    }
+
+    #[test]
+    fn cache_keyed_by_block_and_value() {
+        let cache = lru(10, 100);
+        assert!(cache.get(&key(1, 1)).is_none());
+        cache.insert(key(1, 1), 1, 10);
+        assert_eq!(cache.get(&key(1, 1)), Some(1));
+        assert_eq!(cache.size(&key(1, 1)), Some(10));
+
+        assert!(cache.get(&key(2, 1)).is_none());
+        assert!(cache.get(&key(1, 2)).is_none());
+
+        // replacing a value replaces its size
+        cache.insert(key(1, 1), 2, 20);
+        assert_eq!(cache.get(&key(1, 1)), Some(2));
+        assert_eq!(cache.inner.lock().memory_usage, 20);
+    }
+
+    #[test]
+    fn cache_get_max() {
+        let cache = lru(10, 100);
+        let block_hash = B256::with_last_byte(1);
+        cache.insert(key(1, 2), 2, 10);
+        cache.insert(key(1, 5), 5, 10);
+        cache.insert(key(2, 9), 9, 10);
+
+        let below = |index| move |key: &Key| key.0 == block_hash && key.1 <= index;
+        assert!(cache.get_max(below(1)).is_none());
+        assert_eq!(cache.get_max(below(4)), Some((key(1, 2), 2)));
+        assert_eq!(cache.get_max(below(9)), Some((key(1, 5), 5)));
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let cache = lru(10, 20);
+        cache.insert(key(0, 0), 0, 10);
+        cache.insert(key(1, 0), 1, 10);
+        // promote the first entry
+        assert!(cache.get(&key(0, 0)).is_some());
+        cache.insert(key(2, 0), 2, 10);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key(0, 0)).is_some());
+        assert!(cache.get(&key(1, 0)).is_none());
+        assert!(cache.get(&key(2, 0)).is_some());
+
+        // too large to be cached at all
+        cache.insert(key(3, 0), 3, 30);
+        assert!(cache.get(&key(3, 0)).is_none());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.inner.lock().memory_usage, 20);
+
+        // bounded by the number of entries as well
+        let cache = lru(2, 100);
+        for block in 0..3 {
+            cache.insert(key(block, 0), block, 1);
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key(0, 0)).is_none());
+
+        let disabled = lru(0, 100);
+        disabled.insert(key(0, 0), 0, 1);
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn cache_remove_blocks() {
+        let cache = lru(10, 100);
+        cache.insert(key(1, 1), 1, 10);
+        cache.insert(key(1, 2), 2, 10);
+        cache.insert(key(2, 1), 3, 10);
+        assert_eq!(cache.len(), 3);
+
+        cache.remove_blocks(&HashSet::from([B256::with_last_byte(1)]));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&key(2, 1)), Some(3));
+        assert_eq!(cache.inner.lock().memory_usage, 10);
+    }
+}
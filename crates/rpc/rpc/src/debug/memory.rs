@@ -0,0 +1,149 @@
+//! Accounting of the memory of in-flight trace responses.
+
+use reth_metrics::{metrics::Gauge, Metrics};
+use reth_rpc_eth_types::{DebugApiError, EthApiError};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Default limit of the estimated memory of all in-flight trace responses, in bytes.
+pub const DEFAULT_MAX_TRACE_MEMORY: usize = 4 * 1024 * 1024 * 1024;
+
+/// The estimated size of a struct log, including a typical stack, in bytes.
+pub(crate) const STRUCT_LOG_SIZE: usize = 512;
+
+/// The estimated average gas used per EVM step, which estimates the number of struct logs of a
+/// trace before it's traced.
+///
+/// This is on the low side, so that the number of struct logs is rather over- than
+/// underestimated.
+pub(crate) const GAS_PER_STEP: u64 = 4;
+
+/// The estimated size of a call frame, in bytes.
+pub(crate) const CALL_FRAME_SIZE: usize = 512;
+
+/// The memory budget shared by the traces of all in-flight requests.
+///
+/// The traces of a request register their estimated size while they're built, so that a request
+/// whose traces would exceed the budget fails instead of exhausting the memory of the node. The
+/// estimates are rough, e.g. the size of struct logs is derived from their number only.
+#[derive(Debug)]
+pub(crate) struct TraceMemoryBudget {
+    /// The maximum memory of all in-flight traces.
+    limit: AtomicUsize,
+    /// The memory of the currently reserved traces.
+    used: AtomicUsize,
+    metrics: TraceMemoryMetrics,
+}
+
+impl TraceMemoryBudget {
+    /// Configures the maximum memory of all in-flight traces.
+    pub(crate) fn set_limit(&self, limit: usize) {
+        self.limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// Returns the maximum memory of all in-flight traces.
+    pub(crate) fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Returns a new, empty reservation for the traces of a request.
+    pub(crate) fn reservation(self: &Arc<Self>) -> TraceMemory {
+        TraceMemory(Arc::new(Reservation { budget: self.clone(), reserved: AtomicUsize::new(0) }))
+    }
+}
+
+impl Default for TraceMemoryBudget {
+    fn default() -> Self {
+        Self {
+            limit: AtomicUsize::new(DEFAULT_MAX_TRACE_MEMORY),
+            used: AtomicUsize::new(0),
+            metrics: Default::default(),
+        }
+    }
+}
+
+/// The memory reserved by the traces of a request.
+///
+/// The memory is released once the last clone is dropped.
+#[derive(Clone, Debug)]
+pub(crate) struct TraceMemory(Arc<Reservation>);
+
+impl TraceMemory {
+    /// Reserves `bytes` of memory for traces of the request.
+    ///
+    /// Fails with [`DebugApiError::TraceMemoryBudgetExceeded`] if this would exceed the budget.
+    pub(crate) fn reserve(&self, bytes: usize) -> Result<(), EthApiError> {
+        let budget = &self.0.budget;
+        let used = budget.used.fetch_add(bytes, Ordering::Relaxed).saturating_add(bytes);
+        let limit = budget.limit.load(Ordering::Relaxed);
+        if used > limit {
+            budget.used.fetch_sub(bytes, Ordering::Relaxed);
+            return Err(DebugApiError::TraceMemoryBudgetExceeded { limit }.into())
+        }
+        self.0.reserved.fetch_add(bytes, Ordering::Relaxed);
+        budget.metrics.trace_memory_usage.increment(bytes as f64);
+        Ok(())
+    }
+
+    /// Returns the memory reserved by the traces of the request, which estimates the size of
+    /// its response.
+    pub(crate) fn reserved(&self) -> usize {
+        self.0.reserved.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+struct Reservation {
+    budget: Arc<TraceMemoryBudget>,
+    /// The memory reserved by the request.
+    reserved: AtomicUsize,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let reserved = *self.reserved.get_mut();
+        self.budget.used.fetch_sub(reserved, Ordering::Relaxed);
+        self.budget.metrics.trace_memory_usage.decrement(reserved as f64);
+    }
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "rpc.debug")]
+struct TraceMemoryMetrics {
+    /// The estimated memory of the traces of in-flight requests, in bytes.
+    trace_memory_usage: Gauge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_and_release() {
+        let budget = Arc::new(TraceMemoryBudget::default());
+        budget.set_limit(100);
+
+        let first = budget.reservation();
+        first.reserve(60).unwrap();
+        let second = budget.reservation();
+        assert!(matches!(
+            second.reserve(50),
+            Err(EthApiError::Debug(DebugApiError::TraceMemoryBudgetExceeded { limit: 100 }))
+        ));
+        // a failed reservation doesn't take memory of the budget
+        second.reserve(40).unwrap();
+
+        // the memory is released once all clones of the reservation are dropped
+        let clone = first.clone();
+        drop(first);
+        assert!(second.reserve(60).is_err());
+        drop(clone);
+        second.reserve(60).unwrap();
+        assert_eq!(budget.used.load(Ordering::Relaxed), 100);
+
+        drop(second);
+        assert_eq!(budget.used.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,161 @@
+//! Metrics of the tracing requests of the `debug` API.
+
+use super::TraceClass;
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
+use reth_rpc_types::debug::ExecutionStats;
+use std::time::Instant;
+
+/// Metrics of the `debug` API.
+#[derive(Debug)]
+pub(crate) struct DebugApiMetrics {
+    /// Metrics of `debug_traceBlock`.
+    pub(crate) trace_block: DebugMethodMetrics,
+    /// Metrics of `debug_traceBlockByHash`.
+    pub(crate) trace_block_by_hash: DebugMethodMetrics,
+    /// Metrics of `debug_traceBlockByNumber`.
+    pub(crate) trace_block_by_number: DebugMethodMetrics,
+    /// Metrics of `debug_traceBlockWithModifications`.
+    pub(crate) trace_block_with_modifications: DebugMethodMetrics,
+    /// Metrics of `debug_traceTransaction`.
+    pub(crate) trace_transaction: DebugMethodMetrics,
+    /// Metrics of `debug_traceTransactionInBlock`.
+    pub(crate) trace_transaction_in_block: DebugMethodMetrics,
+    /// Metrics of `debug_traceRawTransaction`.
+    pub(crate) trace_raw_transaction: DebugMethodMetrics,
+    /// Metrics of `debug_traceCall`.
+    pub(crate) trace_call: DebugMethodMetrics,
+    /// Metrics of `debug_traceCallMany`.
+    pub(crate) trace_call_many: DebugMethodMetrics,
+    /// Metrics of `debug_executionWitness`.
+    pub(crate) execution_witness: DebugMethodMetrics,
+    /// Metrics of `debug_executeWitness`.
+    pub(crate) execute_witness: DebugMethodMetrics,
+    /// Metrics of `debug_executionWitnessRaw`.
+    pub(crate) execution_witness_raw: DebugMethodMetrics,
+    /// Metrics of `debug_executionWitnessRange`.
+    pub(crate) execution_witness_range: DebugMethodMetrics,
+    /// Metrics of `debug_getBlockAccessList`.
+    pub(crate) block_access_list: DebugMethodMetrics,
+    /// Metrics of `debug_replayBlock`.
+    pub(crate) replay_block: DebugMethodMetrics,
+    /// Metrics of the blocks traced for `debug_subscribe`.
+    pub(crate) subscribe: DebugMethodMetrics,
+    /// Metrics of `debug_subscribeTraceBlock`.
+    pub(crate) subscribe_trace_block: DebugMethodMetrics,
+    /// The execution metrics of each [`TraceClass`].
+    execution: [DebugExecutionMetrics; 3],
+}
+
+impl DebugApiMetrics {
+    /// Starts timing the execution of a request of the class.
+    pub(crate) fn execution_timer(&self, class: TraceClass) -> ExecutionTimer {
+        let metrics = &self.execution[class as usize];
+        ExecutionTimer {
+            histogram: metrics.execution_time_seconds.clone(),
+            panics: metrics.panics_total.clone(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for DebugApiMetrics {
+    fn default() -> Self {
+        let method =
+            |method: &'static str| DebugMethodMetrics::new_with_labels(&[("method", method)]);
+        Self {
+            trace_block: method("debug_traceBlock"),
+            trace_block_by_hash: method("debug_traceBlockByHash"),
+            trace_block_by_number: method("debug_traceBlockByNumber"),
+            trace_block_with_modifications: method("debug_traceBlockWithModifications"),
+            trace_transaction: method("debug_traceTransaction"),
+            trace_transaction_in_block: method("debug_traceTransactionInBlock"),
+            trace_raw_transaction: method("debug_traceRawTransaction"),
+            trace_call: method("debug_traceCall"),
+            trace_call_many: method("debug_traceCallMany"),
+            execution_witness: method("debug_executionWitness"),
+            execute_witness: method("debug_executeWitness"),
+            execution_witness_raw: method("debug_executionWitnessRaw"),
+            execution_witness_range: method("debug_executionWitnessRange"),
+            block_access_list: method("debug_getBlockAccessList"),
+            replay_block: method("debug_replayBlock"),
+            subscribe: method("debug_subscribe"),
+            subscribe_trace_block: method("debug_subscribeTraceBlock"),
+            execution: TraceClass::ALL
+                .map(|class| DebugExecutionMetrics::new_with_labels(&[("class", class.as_str())])),
+        }
+    }
+}
+
+/// Metrics of a tracing method of the `debug` API.
+#[derive(Metrics)]
+#[metrics(scope = "rpc.debug")]
+pub(crate) struct DebugMethodMetrics {
+    /// The number of executed requests, excluding the coalesced ones.
+    pub(crate) requests_total: Counter,
+    /// The number of requests that awaited the result of an identical in-flight request instead
+    /// of being executed.
+    pub(crate) coalesced_total: Counter,
+    /// The number of failed requests.
+    pub(crate) errors_total: Counter,
+    /// The time requests waited for a trace permit, in seconds.
+    pub(crate) permit_wait_seconds: Histogram,
+    /// The estimated size of successful responses, in bytes, i.e. the trace memory they reserved.
+    pub(crate) response_size_bytes: Histogram,
+    /// The number of account lookups of executions that were served by the cache.
+    account_cache_hits_total: Counter,
+    /// The number of accounts executions read from the state provider.
+    account_provider_reads_total: Counter,
+    /// The number of storage slot lookups of executions that were served by the cache.
+    storage_cache_hits_total: Counter,
+    /// The number of storage slots executions read from the state provider.
+    storage_provider_reads_total: Counter,
+    /// The number of bytecode lookups of executions that were served by the cache.
+    code_cache_hits_total: Counter,
+    /// The number of bytecodes executions read from the state provider.
+    code_provider_reads_total: Counter,
+    /// The time the executions of a request spent reading from the state provider, in seconds.
+    provider_read_seconds: Histogram,
+}
+
+impl DebugMethodMetrics {
+    /// Records the database lookups of the executions of a request.
+    pub(crate) fn record_execution_stats(&self, stats: &ExecutionStats) {
+        self.account_cache_hits_total.increment(stats.account_cache_hits);
+        self.account_provider_reads_total.increment(stats.account_provider_reads);
+        self.storage_cache_hits_total.increment(stats.storage_cache_hits);
+        self.storage_provider_reads_total.increment(stats.storage_provider_reads);
+        self.code_cache_hits_total.increment(stats.code_cache_hits);
+        self.code_provider_reads_total.increment(stats.code_provider_reads);
+        self.provider_read_seconds.record(stats.provider_read_micros as f64 / 1_000_000.0);
+    }
+}
+
+/// Metrics of the execution of a [`TraceClass`] of requests.
+#[derive(Metrics)]
+#[metrics(scope = "rpc.debug")]
+struct DebugExecutionMetrics {
+    /// The time spent executing requests in the blocking task, in seconds.
+    execution_time_seconds: Histogram,
+    /// The number of requests whose blocking task panicked.
+    panics_total: Counter,
+}
+
+/// Records the execution time of a request when dropped, and whether its blocking task panicked.
+pub(crate) struct ExecutionTimer {
+    histogram: Histogram,
+    panics: Counter,
+    start: Instant,
+}
+
+impl Drop for ExecutionTimer {
+    fn drop(&mut self) {
+        self.histogram.record(self.start.elapsed().as_secs_f64());
+        // the timer is dropped while unwinding from a panic of the task
+        if std::thread::panicking() {
+            self.panics.increment(1);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! RPC middleware of the server for the `debug` API.
+
+use super::memory::TraceMemory;
+use futures::ready;
+use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request, MethodResponse};
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::Layer;
+
+/// A layer for the RPC middleware of the server that holds the trace memory of the responses of
+/// the `debug` API until they're serialized.
+///
+/// The methods of the `debug` API return their traces to the server, which serializes them after
+/// the method returned. Without this layer, the traces stop counting against the trace memory
+/// budget once the method returns.
+///
+/// The layer also passes the connection of each request on to the methods, so that the client
+/// limits of the `debug` API stop tracking a connection once it's closed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DebugRpcLayer {
+    /// Whether the requests are passed on as-is, see [`Self::disabled`].
+    disabled: bool,
+}
+
+impl DebugRpcLayer {
+    /// Returns a layer that passes the requests on as-is, for servers that don't serve the
+    /// `debug` API.
+    pub const fn disabled() -> Self {
+        Self { disabled: true }
+    }
+}
+
+impl<S> Layer<S> for DebugRpcLayer {
+    type Service = DebugRpcService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DebugRpcService { inner, connection: (!self.disabled).then(Default::default) }
+    }
+}
+
+/// The service of the [`DebugRpcLayer`].
+#[derive(Clone, Debug)]
+pub struct DebugRpcService<S> {
+    inner: S,
+    /// The connection of the requests, `None` if the layer is disabled.
+    connection: Option<RpcConnection>,
+}
+
+impl<'a, S> RpcServiceT<'a> for DebugRpcService<S>
+where
+    S: RpcServiceT<'a>,
+{
+    type Future = DebugRpcFuture<S::Future>;
+
+    fn call(&self, mut request: Request<'a>) -> Self::Future {
+        let Some(connection) = &self.connection else {
+            return DebugRpcFuture { fut: self.inner.call(request), trace_memory: None }
+        };
+        let trace_memory = ResponseTraceMemory::default();
+        request.extensions_mut().insert(trace_memory.clone());
+        request.extensions_mut().insert(connection.clone());
+        DebugRpcFuture { fut: self.inner.call(request), trace_memory: Some(trace_memory) }
+    }
+}
+
+/// The response future of the [`DebugRpcService`].
+#[pin_project::pin_project]
+#[derive(Debug)]
+pub struct DebugRpcFuture<F> {
+    #[pin]
+    fut: F,
+    trace_memory: Option<ResponseTraceMemory>,
+}
+
+impl<F: Future<Output = MethodResponse>> Future for DebugRpcFuture<F> {
+    type Output = MethodResponse;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.fut.poll(cx));
+        // the response is serialized, its traces no longer count against the budget
+        if let Some(trace_memory) = this.trace_memory {
+            trace_memory.release();
+        }
+        Poll::Ready(response)
+    }
+}
+
+/// The trace memory of a request, which the [`DebugRpcLayer`] holds until the response of the
+/// request is serialized.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ResponseTraceMemory(Arc<Mutex<Vec<TraceMemory>>>);
+
+impl ResponseTraceMemory {
+    /// Holds the trace memory until the response is serialized.
+    pub(crate) fn hold(&self, trace_memory: TraceMemory) {
+        self.0.lock().push(trace_memory);
+    }
+
+    /// Returns the memory reserved by the held traces, which estimates the size of the response,
+    /// or `None` if no traces are held.
+    pub(crate) fn reserved(&self) -> Option<usize> {
+        let held = self.0.lock();
+        (!held.is_empty()).then(|| held.iter().map(TraceMemory::reserved).sum())
+    }
+
+    /// Releases the held trace memory.
+    fn release(&self) {
+        self.0.lock().clear();
+    }
+}
+
+/// The connection of a request, which is closed once the [`DebugRpcService`] of the connection
+/// and all of its requests are dropped.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RpcConnection(Arc<OnClose>);
+
+impl RpcConnection {
+    /// Registers a callback that's called once the connection is closed.
+    pub(crate) fn on_close(&self, f: impl FnOnce() + Send + 'static) {
+        self.0 .0.lock().push(Box::new(f));
+    }
+}
+
+/// The callbacks of a [`RpcConnection`], which are called once it's closed.
+#[derive(Default)]
+struct OnClose(Mutex<Vec<Box<dyn FnOnce() + Send>>>);
+
+impl fmt::Debug for OnClose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnClose").field("callbacks", &self.0.lock().len()).finish()
+    }
+}
+
+impl Drop for OnClose {
+    fn drop(&mut self) {
+        for f in self.0.get_mut().drain(..) {
+            f()
+        }
+    }
+}
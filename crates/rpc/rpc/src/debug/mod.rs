@@ -0,0 +1,9774 @@
+use alloy_primitives::{hex, Address, BlockNumber, Bytes, B256, U256};
+use async_trait::async_trait;
+use futures::{future::Either, Stream, StreamExt};
+use jsonrpsee::{core::RpcResult, Extensions, PendingSubscriptionSink};
+use reth_chainspec::{Chain, EthChainSpec, EthereumHardforks, MIN_TRANSACTION_GAS};
+use reth_errors::{ProviderError, RethError};
+use reth_evm::{
+    system_calls::{
+        post_block_consolidation_requests_contract_call,
+        post_block_withdrawal_requests_contract_call, pre_block_beacon_root_contract_call,
+        pre_block_blockhashes_contract_call,
+    },
+    ConfigureEvmEnv,
+};
+use reth_primitives::{
+    constants::SLOT_DURATION, Block, BlockId, BlockNumberOrTag, BlockWithSenders, Header, Receipt,
+    Receipts, SealedBlockWithSenders, TransactionSigned, TransactionSignedEcRecovered, Withdrawal,
+    Withdrawals,
+};
+use reth_provider::{
+    AccountReader, BlockReaderIdExt, BlockSource, CanonStateNotification, CanonStateSubscriptions,
+    ChainSpecProvider, EvmEnvProvider, ExecutionOutcome, HeaderProvider, ProviderResult,
+    PruneCheckpointReader, StateProofProvider, StateProvider, StateProviderBox,
+    StateProviderFactory, StateRootProvider, StorageStatsReader, TransactionVariant,
+};
+use reth_prune_types::PruneSegment;
+use reth_revm::database::StateProviderDatabase;
+use reth_rpc_api::DebugApiServer;
+use reth_rpc_eth_api::{
+    helpers::{
+        blocking_task::blocking_task_error, Call, EthApiSpec, EthTransactions, LoadState,
+        SpawnBlocking, TraceExt,
+    },
+    AsEthApiError, EthApiTypes, FromEthApiError,
+};
+use reth_rpc_eth_types::{
+    cache::db::StateProviderTraitObjWrapper,
+    revm_utils::{
+        apply_block_hash_overrides, apply_block_overrides, apply_state_overrides, get_precompiles,
+    },
+    utils::recover_raw_signed_transaction,
+    DebugApiError, EthApiError, StateCacheDb,
+};
+use reth_rpc_server_types::{result::internal_rpc_err, ToRpcResult};
+use reth_rpc_types::{
+    debug::{
+        AccessStatus, AccessStatusFrame, AccountAccess, AccountDiff, BlockAccessList,
+        BlockReplayReport, BlockTraceContext, BlockTraceCursor, BlockTracePage, BlockTraceResult,
+        BlockTraceWithContext, BundleTransaction, DbTables, DebugBundle, DebugExecutionWitness,
+        DebugSubscriptionKind, DebugTraceBlockResponse, DebugTraceBundleResponse,
+        DebugTraceBundleResult, DebugTraceCallResponse, DebugTraceCallResult,
+        DebugTraceTransactionResponse, DebugTraceTransactionResult, DebugTracingBlockOptions,
+        DebugTracingCallManyOptions, DebugTracingCallOptions, DebugTracingTransactionOptions,
+        ExecutionWitnessOptions, ExecutionWitnessResponse, ExecutionWitnessResult,
+        ModifiedTraceResult, PostBlockTrace, ReceiptDivergence, ReplayedReceipt,
+        StatelessExecutionResult, TraceCallChecks, TraceCallExtras, TraceCostEstimate,
+        TraceCostTarget, TraceResultFormat, TraceResultWithStats, TransactionModification,
+        TransactionModificationKind, TransactionStateChanges, TrieFlushInterval, ValueChange,
+        WithdrawalCredit,
+    },
+    state::{EvmOverrides, StateOverride},
+    trace::geth::{
+        AccountState, DefaultFrame, DiffMode, GethDebugBuiltInTracerType, GethDebugTracerConfig,
+        GethDebugTracerType, GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
+        NoopFrame, TraceResult,
+    },
+    AccessList, AccessListItem, Block as RpcBlock, BlockOverrides, EIP1186AccountProofResponse,
+    StateContext, SyncStatus, TransactionRequest,
+};
+use reth_rpc_types_compat::proof::from_primitive_account_proof;
+use reth_tasks::{
+    pool::{BlockingTaskGuard, BlockingTaskPool},
+    TaskSpawner, TokioTaskExecutor,
+};
+use reth_trie::HashedPostState;
+use revm::{
+    db::{states::bundle_state::BundleRetention, BundleState, CacheAccount, CacheDB, State},
+    interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, OpCode},
+    primitives::{
+        db::DatabaseCommit, Account, AccountInfo, AccountStatus, BlockEnv, CfgEnvWithHandlerCfg,
+        Env, EnvWithHandlerCfg, EvmState, Log, SpecId, KECCAK_EMPTY,
+    },
+    Database, EvmContext, Inspector, StateBuilder,
+};
+use revm_inspectors::tracing::{
+    types::{CallKind, CallTraceNode},
+    OpcodeFilter, TracingInspector, TracingInspectorConfig, TransactionContext,
+};
+use revm_primitives::{keccak256, HashMap};
+use std::{
+    collections::{btree_map, BTreeMap, BTreeSet, HashSet},
+    fmt,
+    future::Future,
+    mem::size_of,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+mod audit;
+mod cache;
+mod cancel;
+mod cfg_overrides;
+mod client_limits;
+mod coalesce;
+mod config;
+mod counters;
+mod db;
+mod db_stats;
+mod lru;
+mod memory;
+mod metrics;
+mod middleware;
+mod permits;
+mod persistence;
+mod pool;
+mod precompiles;
+mod preimages;
+mod progress;
+mod raw;
+mod snapshot;
+mod state_cache;
+mod stateless;
+mod subscription;
+mod top_call;
+mod trace;
+mod tracer_policy;
+mod witness;
+mod witness_cache;
+
+pub use audit::{
+    AuditLogConfig, DebugAuditEntry, DebugAuditOutcome, DebugAuditParams, DebugAuditSink,
+    JsonlAuditSink, DEFAULT_AUDIT_LOG_BUFFER,
+};
+use cache::BlockTraces;
+pub use cache::{
+    TraceCache, TraceCacheConfig, DEFAULT_TRACE_CACHE_MAX_BYTES, DEFAULT_TRACE_CACHE_MAX_ENTRIES,
+};
+use cancel::{CancelOnDrop, Cancellation};
+use cfg_overrides::{apply_cfg_overrides, spec_name};
+use client_limits::ClientLimits;
+pub use client_limits::{ClientKey, ClientKeyLayer, ClientKeyService, ClientLimitsConfig};
+use coalesce::{InFlightRequests, RequestKey};
+pub use config::{DebugApiConfig, DebugApiConfigError};
+use counters::TraceCountersRecorder;
+use db_stats::{ExecutionStatsRecorder, StatsDb};
+pub use memory::DEFAULT_MAX_TRACE_MEMORY;
+use memory::{TraceMemory, TraceMemoryBudget, GAS_PER_STEP, STRUCT_LOG_SIZE};
+use metrics::{DebugApiMetrics, DebugMethodMetrics};
+use middleware::ResponseTraceMemory;
+pub use middleware::{DebugRpcFuture, DebugRpcLayer, DebugRpcService};
+pub use permits::{TraceClass, DEFAULT_MAX_TRACE_PERMIT_WAIT};
+use permits::{TracePermit, TracePermits};
+pub use persistence::{PersistenceSettings, PersistenceSettingsHandle};
+use precompiles::PrecompileOverrides;
+use progress::TraceProgressRecorder;
+use raw::decode_raw_block;
+pub use snapshot::{StateSnapshotCache, DEFAULT_STATE_SNAPSHOT_CACHE_MAX_BYTES};
+use state_cache::CachedStateProvider;
+pub use state_cache::{StateReadCache, DEFAULT_STATE_READ_CACHE_MAX_BYTES};
+use subscription::ConnectionSubscriptions;
+pub use subscription::DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION;
+pub use trace::DebugTraceExt;
+use tracer_policy::tracer_kinds;
+pub use tracer_policy::{TracerKind, TracerPolicy};
+pub use witness_cache::{
+    WitnessCache, WitnessCacheConfig, DEFAULT_WITNESS_CACHE_MAX_BYTES,
+    DEFAULT_WITNESS_CACHE_MAX_ENTRIES,
+};
+
+/// Default maximum number of blocks of a `debug_executionWitnessRange` request.
+pub const DEFAULT_MAX_EXECUTION_WITNESS_RANGE: u64 = 100;
+
+/// Default maximum number of headers of a `debug_getRawHeaders` request.
+pub const DEFAULT_MAX_RAW_HEADERS: u64 = 2048;
+
+/// The name of the execution stage in the sync status of the node.
+const EXECUTION_STAGE: &str = "Execution";
+
+/// `debug` API implementation.
+///
+/// This type provides the functionality for handling `debug` related requests. Transactions and
+/// blocks are traced with [`DebugTraceExt`], which can also be used without the RPC server.
+///
+/// The settings of the API are shared by its clones, so it's configured before it's cloned.
+pub struct DebugApi<Provider, Eth> {
+    inner: Arc<DebugApiInner<Provider, Eth>>,
+    /// The memory reserved for the traces of the request this instance is scoped to, if any.
+    trace_memory: Option<TraceMemory>,
+    /// The recorder of the database lookups of the request this instance is scoped to, if any.
+    execution_stats: Option<Arc<ExecutionStatsRecorder>>,
+    /// The recorder of the counters of the traced executions of the request this instance is
+    /// scoped to, if requested.
+    trace_counters: Option<Arc<TraceCountersRecorder>>,
+    /// The recorder of the progress of the block trace this instance is scoped to, if any.
+    trace_progress: Option<Arc<TraceProgressRecorder>>,
+    /// The precompiles changed by the state overrides of the request this instance is scoped to,
+    /// if any.
+    precompile_overrides: Option<Arc<PrecompileOverrides>>,
+    /// Whether the intermediate states of the block traced by the request this instance is scoped
+    /// to bypass the [`StateSnapshotCache`], e.g. because the block is traced on top of another
+    /// parent.
+    skip_state_snapshots: bool,
+}
+
+/// The caches of the [`DebugApi`].
+///
+/// The caches are shared by all instances they're configured on via [`DebugApi::with_caches`], so
+/// the tasks removing reorged blocks from them only need to be spawned once.
+#[derive(Clone, Debug, Default)]
+pub struct DebugApiCaches {
+    /// The cache for the traces of recently traced blocks, if enabled.
+    pub trace_cache: Option<TraceCache>,
+    /// The cache for the intermediate states of recently traced blocks, if enabled.
+    pub state_snapshot_cache: Option<StateSnapshotCache>,
+    /// The cache for the state read by requests on top of recent blocks, if enabled.
+    pub state_read_cache: Option<StateReadCache>,
+    /// The cache for recently generated execution witnesses, if enabled.
+    pub witness_cache: Option<WitnessCache>,
+}
+
+impl DebugApiCaches {
+    /// Creates the caches enabled by the config, empty.
+    pub fn new(config: &DebugApiConfig) -> Self {
+        Self {
+            trace_cache: config.trace_cache.map(TraceCache::new),
+            state_snapshot_cache: config
+                .state_snapshot_cache_max_bytes
+                .map(StateSnapshotCache::new),
+            state_read_cache: config.state_read_cache_max_bytes.map(StateReadCache::new),
+            witness_cache: config.witness_cache.map(WitnessCache::new),
+        }
+    }
+
+    /// Removes the cached values of the given blocks from the enabled caches.
+    pub fn remove_blocks(&self, block_hashes: &HashSet<B256>) {
+        let Self { trace_cache, state_snapshot_cache, state_read_cache, witness_cache } = self;
+        trace_cache.iter().for_each(|cache| cache.remove_blocks(block_hashes));
+        state_snapshot_cache.iter().for_each(|cache| cache.remove_blocks(block_hashes));
+        state_read_cache.iter().for_each(|cache| cache.remove_blocks(block_hashes));
+        witness_cache.iter().for_each(|cache| cache.remove_blocks(block_hashes));
+    }
+
+    /// Spawns the task that removes reorged blocks from the enabled caches, see
+    /// [`cache_reorg_task`].
+    pub fn spawn_reorg_task<Events>(&self, executor: &dyn TaskSpawner, events: &Events)
+    where
+        Events: CanonStateSubscriptions,
+    {
+        let events = events.canonical_state_stream();
+        executor.spawn(Box::pin(cache_reorg_task(self.clone(), events)));
+    }
+}
+
+/// Awaits canonical state notifications and removes the reorged blocks from the given caches.
+pub async fn cache_reorg_task<St>(caches: DebugApiCaches, mut events: St)
+where
+    St: Stream<Item = CanonStateNotification> + Unpin + 'static,
+{
+    while let Some(event) = events.next().await {
+        if let Some(reverted) = event.reverted() {
+            caches.remove_blocks(&reverted.blocks_iter().map(|block| block.hash()).collect());
+        }
+    }
+}
+
+// === impl DebugApi ===
+
+impl<Provider, Eth> DebugApi<Provider, Eth> {
+    /// Create a new instance of the [`DebugApi`]
+    pub fn new(provider: Provider, eth: Eth, blocking_task_guard: BlockingTaskGuard) -> Self {
+        Self::with_settings(provider, eth, blocking_task_guard, DebugApiConfig::default())
+            .expect("the default config reserves no permits")
+    }
+
+    /// Create a new instance of the [`DebugApi`] with the given settings, and opens the audit log
+    /// of the config if it's enabled.
+    ///
+    /// The caches enabled by the config are created empty, the tasks removing reorged blocks from
+    /// them can be spawned via [`Self::spawn_cache_reorg_task`]. Instances that should share
+    /// their caches can be configured with the same [`DebugApiCaches`] via [`Self::with_caches`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the config is invalid for the permits of the guard, see
+    /// [`DebugApiConfig::validate`], or if the audit log can't be opened.
+    pub fn with_config(
+        provider: Provider,
+        eth: Eth,
+        blocking_task_guard: BlockingTaskGuard,
+        config: DebugApiConfig,
+    ) -> Result<Self, DebugApiConfigError> {
+        let audit_sink = config.audit_log.as_ref().map(JsonlAuditSink::open).transpose()?;
+        let this = Self::with_settings(provider, eth, blocking_task_guard, config)?;
+        Ok(match audit_sink {
+            Some(sink) => this.with_audit_sink(Arc::new(sink)),
+            None => this,
+        })
+    }
+
+    /// Create a new instance of the [`DebugApi`] with the given settings like
+    /// [`Self::with_config`], without opening its audit log.
+    ///
+    /// Instances that should share an audit log can be configured with the same sink via
+    /// [`Self::with_audit_sink`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the config is invalid for the permits of the guard, see
+    /// [`DebugApiConfig::validate`].
+    pub fn with_settings(
+        provider: Provider,
+        eth: Eth,
+        blocking_task_guard: BlockingTaskGuard,
+        config: DebugApiConfig,
+    ) -> Result<Self, DebugApiConfigError> {
+        let caches = DebugApiCaches::new(&config);
+        let DebugApiConfig {
+            default_tracer,
+            default_tracer_config,
+            tracer_policy,
+            trace_block_parallelism,
+            trace_cache: _,
+            state_snapshot_cache_max_bytes: _,
+            state_read_cache_max_bytes: _,
+            witness_cache: _,
+            max_execution_witness_range,
+            max_raw_headers,
+            max_subscriptions_per_connection,
+            trace_permits,
+            reserved_call_permits,
+            max_trace_permit_wait,
+            max_trace_memory,
+            gas_cap,
+            trace_pool_transactions,
+            db_tables,
+            max_block_gas,
+            allow_block_gas_override,
+            audit_log: _,
+            client_limits,
+            persistence_settings,
+        } = config;
+
+        let inner = Arc::new(DebugApiInner {
+            provider,
+            eth_api: eth,
+            blocking_task_guard,
+            metrics: DebugApiMetrics::default(),
+            trace_memory_budget: Default::default(),
+            in_flight_transactions: Default::default(),
+            in_flight_blocks: Default::default(),
+            client_limits: Default::default(),
+            subscriptions: Default::default(),
+            default_tracer: default_tracer
+                .map(|tracer| (tracer, default_tracer_config.unwrap_or_default())),
+            tracer_policy,
+            trace_block_parallelism: trace_block_parallelism.max(1),
+            caches,
+            max_execution_witness_range,
+            max_raw_headers,
+            max_subscriptions_per_connection,
+            trace_permits: TracePermits::default(),
+            trace_pool: None,
+            gas_cap,
+            trace_pool_transactions,
+            db_tables,
+            max_block_gas,
+            allow_block_gas_override,
+            persistence_settings,
+            canonical_state_events: None,
+            subscription_task_spawner: Box::<TokioTaskExecutor>::default(),
+            audit_sink: None,
+        });
+        let mut this = Self {
+            inner,
+            trace_memory: None,
+            execution_stats: None,
+            trace_counters: None,
+            trace_progress: None,
+            precompile_overrides: None,
+            skip_state_snapshots: false,
+        }
+        .with_max_trace_permit_wait(max_trace_permit_wait)
+        .with_max_trace_memory(max_trace_memory)
+        .with_client_limits(client_limits);
+        for (class, permits) in trace_permits {
+            this = this.with_trace_permits(class, permits);
+        }
+        if let Some(permits) = reserved_call_permits {
+            this = this.with_reserved_call_permits(permits)?;
+        }
+        Ok(this)
+    }
+
+    /// Configures the tracer, and its config, that is used if no tracer is specified in the
+    /// tracing options of a request.
+    ///
+    /// If unset, the struct logger is used.
+    pub fn with_default_tracer(
+        mut self,
+        tracer: GethDebugTracerType,
+        config: GethDebugTracerConfig,
+    ) -> Self {
+        self.inner_mut().default_tracer = Some((tracer, config));
+        self
+    }
+
+    /// Restricts the tracers requests may use.
+    ///
+    /// Requests with a tracer the policy doesn't allow, including the default tracer if they
+    /// don't specify one, fail before any state is loaded. By default, all tracers are allowed.
+    pub fn with_tracer_policy(mut self, policy: TracerPolicy) -> Self {
+        self.inner_mut().tracer_policy = policy;
+        self
+    }
+
+    /// Configures the maximum number of threads the transactions of a block are traced on.
+    ///
+    /// Each additional thread takes a permit of the [`BlockingTaskGuard`], so fewer threads are
+    /// used if the tracing budget is exhausted. Defaults to `1`, tracing all transactions
+    /// sequentially.
+    pub fn with_trace_block_parallelism(mut self, parallelism: usize) -> Self {
+        self.inner_mut().trace_block_parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Configures a cache for the traces of recently traced blocks.
+    ///
+    /// Blocks traced by hash or number are looked up in the cache before they are traced. The
+    /// traces of reorged blocks should be removed via [`cache_reorg_task`].
+    pub fn with_trace_cache(mut self, cache: TraceCache) -> Self {
+        self.inner_mut().caches.trace_cache = Some(cache);
+        self
+    }
+
+    /// Configures a cache for the intermediate states of recently traced blocks.
+    ///
+    /// Tracing a transaction resumes from the cached state closest before it, instead of replaying
+    /// all preceding transactions of the block. The states of reorged blocks should be removed via
+    /// [`cache_reorg_task`].
+    pub fn with_state_snapshot_cache(mut self, cache: StateSnapshotCache) -> Self {
+        self.inner_mut().caches.state_snapshot_cache = Some(cache);
+        self
+    }
+
+    /// Configures a cache for the state read by requests on top of recent blocks.
+    ///
+    /// The accounts, storage slots and bytecodes that requests on top of the same block read from
+    /// the database are shared, e.g. by the traces of the transactions of the latest block. The
+    /// state of reorged blocks should be removed via [`cache_reorg_task`].
+    pub fn with_state_read_cache(mut self, cache: StateReadCache) -> Self {
+        self.inner_mut().caches.state_read_cache = Some(cache);
+        self
+    }
+
+    /// Configures a cache for recently generated execution witnesses.
+    ///
+    /// Witnesses of blocks requested by hash or number are looked up in the cache before they are
+    /// generated, and concurrent requests for the same block share one generation. The witnesses
+    /// of reorged blocks should be removed via [`cache_reorg_task`].
+    pub fn with_witness_cache(mut self, cache: WitnessCache) -> Self {
+        self.inner_mut().caches.witness_cache = Some(cache);
+        self
+    }
+
+    /// Configures the caches of the API, replacing all of its caches.
+    ///
+    /// Caches that aren't enabled in the given caches are disabled.
+    pub fn with_caches(mut self, caches: DebugApiCaches) -> Self {
+        self.inner_mut().caches = caches;
+        self
+    }
+
+    /// Configures the maximum number of blocks of a `debug_executionWitnessRange` request.
+    ///
+    /// Defaults to [`DEFAULT_MAX_EXECUTION_WITNESS_RANGE`].
+    pub fn with_max_execution_witness_range(mut self, max_blocks: u64) -> Self {
+        self.inner_mut().max_execution_witness_range = max_blocks;
+        self
+    }
+
+    /// Limits the number of concurrent requests of the given class to `permits`.
+    ///
+    /// By default, all classes share the permits of the [`BlockingTaskGuard`] of the API. A class
+    /// with its own limit doesn't take permits of the shared guard, so e.g. a burst of execution
+    /// witness requests doesn't delay call traces.
+    pub fn with_trace_permits(mut self, class: TraceClass, permits: usize) -> Self {
+        self.inner_mut().trace_permits.set_limit(class, permits);
+        self
+    }
+
+    /// Reserves `permits` of the permits of the [`BlockingTaskGuard`] of the API for call and
+    /// transaction traces.
+    ///
+    /// Block traces and execution witnesses that share the guard are restricted to the remaining
+    /// permits, so long running block traces can't starve lightweight requests like
+    /// `debug_traceCall`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `permits` isn't less than the permits of the guard, which would leave none for
+    /// the other requests.
+    pub fn with_reserved_call_permits(
+        mut self,
+        permits: usize,
+    ) -> Result<Self, DebugApiConfigError> {
+        let inner = self.inner_mut();
+        inner.trace_permits.set_reserved_call_permits(&inner.blocking_task_guard, permits)?;
+        Ok(self)
+    }
+
+    /// Executes the tracing work on a dedicated pool, instead of the tracing pool of the `eth` API,
+    /// so block traces don't delay `eth` calls and vice versa.
+    ///
+    /// The pool has `threads` threads named `debug-trace-N`, by default as many as the
+    /// [`BlockingTaskGuard`] has permits, regardless of how many of them are currently acquired.
+    /// The trace permits still limit the number of concurrent requests.
+    pub fn with_dedicated_trace_pool(
+        mut self,
+        threads: Option<usize>,
+    ) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let threads = threads.unwrap_or_else(|| self.inner.blocking_task_guard.max_permits());
+        let pool = BlockingTaskPool::builder()
+            .num_threads(threads.max(1))
+            .thread_name(|index| format!("debug-trace-{index}"))
+            .build()?;
+        self.inner_mut().trace_pool = Some(BlockingTaskPool::new(pool));
+        Ok(self)
+    }
+
+    /// Configures the maximum time a request waits for a trace permit before it's rejected.
+    ///
+    /// Defaults to [`DEFAULT_MAX_TRACE_PERMIT_WAIT`].
+    pub fn with_max_trace_permit_wait(mut self, max_wait: Duration) -> Self {
+        self.inner_mut().trace_permits.set_max_wait(max_wait);
+        self
+    }
+
+    /// Configures the maximum estimated memory, in bytes, of the traces of all in-flight
+    /// requests.
+    ///
+    /// A request whose traces would exceed the budget fails instead of exhausting the memory of
+    /// the node. The memory of a request is released once its response is serialized. Defaults to
+    /// [`DEFAULT_MAX_TRACE_MEMORY`].
+    pub fn with_max_trace_memory(self, max_bytes: usize) -> Self {
+        self.inner.trace_memory_budget.set_limit(max_bytes);
+        self
+    }
+
+    /// Configures the maximum number of headers of a `debug_getRawHeaders` request.
+    ///
+    /// Defaults to [`DEFAULT_MAX_RAW_HEADERS`].
+    pub fn with_max_raw_headers(mut self, max_headers: u64) -> Self {
+        self.inner_mut().max_raw_headers = max_headers;
+        self
+    }
+
+    /// Configures the maximum number of subscriptions of a connection.
+    ///
+    /// Each subscription also counts as an in-flight request against the limits of its client
+    /// until it ends. Defaults to [`DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION`].
+    pub fn with_max_subscriptions_per_connection(mut self, max_subscriptions: u32) -> Self {
+        self.inner_mut().max_subscriptions_per_connection = max_subscriptions;
+        self
+    }
+
+    /// Configures the gas limit of the calls traced by `debug_traceCall` and
+    /// `debug_traceCallMany`, independent of the gas cap of `eth_call`.
+    ///
+    /// Calls without a gas limit are executed with the cap, calls whose gas limit exceeds it are
+    /// rejected. Defaults to the gas cap of the `eth` API.
+    pub fn with_gas_cap(mut self, gas_cap: u64) -> Self {
+        self.inner_mut().gas_cap = Some(gas_cap);
+        self
+    }
+
+    /// Configures whether `debug_traceTransaction` traces transactions that are only in the pool,
+    /// instead of failing with [`DebugApiError::TransactionNotFound`].
+    ///
+    /// Such transactions are traced speculatively, see
+    /// [`DebugApi::debug_trace_transaction`]. Disabled by default.
+    pub fn with_trace_pool_transactions(mut self, enabled: bool) -> Self {
+        self.inner_mut().trace_pool_transactions = enabled;
+        self
+    }
+
+    /// Configures whether `debug_dbTables` is enabled.
+    ///
+    /// The sizes of the tables of the database may be considered sensitive, so that the method
+    /// has to be enabled explicitly. Disabled by default.
+    pub fn with_db_tables(mut self, enabled: bool) -> Self {
+        self.inner_mut().db_tables = enabled;
+        self
+    }
+
+    /// Limits the gas used of the blocks that are traced with tracers of the given kind.
+    ///
+    /// Blocks whose header reports more gas used are rejected by the block tracing methods before
+    /// they're executed, so that expensive tracers can be restricted to small blocks while
+    /// cheaper tracers can still trace all blocks. For mux tracers, the limits of the nested
+    /// tracers apply as well. Unlimited by default.
+    pub fn with_max_block_gas(mut self, kind: TracerKind, max_gas: u64) -> Self {
+        self.inner_mut().max_block_gas.insert(kind, max_gas);
+        self
+    }
+
+    /// Configures whether requests may trace blocks beyond the gas limits of their tracers by
+    /// setting `force`. Disabled by default.
+    pub fn with_block_gas_override(mut self, enabled: bool) -> Self {
+        self.inner_mut().allow_block_gas_override = enabled;
+        self
+    }
+
+    /// Configures the handle to the persistence settings of the node, which should be shared with
+    /// the components that persist the state.
+    ///
+    /// By default, `debug_getTrieFlushInterval` and `debug_setTrieFlushInterval` are unsupported.
+    pub fn with_persistence_settings(mut self, settings: PersistenceSettingsHandle) -> Self {
+        self.inner_mut().persistence_settings = Some(settings);
+        self
+    }
+
+    /// Returns the handle to the persistence settings of the node.
+    ///
+    /// Fails with [`EthApiError::Unsupported`] if it's not configured.
+    fn persistence_settings(&self) -> Result<&PersistenceSettingsHandle, EthApiError> {
+        self.inner.persistence_settings.as_ref().ok_or(EthApiError::Unsupported(
+            "the persistence settings of the node are not available",
+        ))
+    }
+
+    /// Configures the notifications of the canonical chain that subscriptions like
+    /// `newBlockTraces` are served from.
+    ///
+    /// Without them, `debug_subscribe` is rejected.
+    pub fn with_canonical_state_events<Events>(mut self, events: Events) -> Self
+    where
+        Events: CanonStateSubscriptions + 'static,
+    {
+        self.inner_mut().canonical_state_events = Some(Arc::new(events));
+        self
+    }
+
+    /// Configures the spawner of the tasks that serve the subscriptions.
+    ///
+    /// Subscription tasks are spawned via [`tokio::task::spawn`] by default.
+    pub fn with_subscription_task_spawner(mut self, spawner: Box<dyn TaskSpawner>) -> Self {
+        self.inner_mut().subscription_task_spawner = spawner;
+        self
+    }
+
+    /// Configures the sink the audit log of the tracing requests is recorded to.
+    ///
+    /// An entry is recorded at the end of each tracing request, with its method, the identifiers
+    /// of the traced block or transaction, the kind of its tracer, its duration and its outcome.
+    /// Calldata and the contents of overrides are never recorded. Disabled by default.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn DebugAuditSink>) -> Self {
+        self.inner_mut().audit_sink = Some(sink);
+        self
+    }
+
+    /// Limits the tracing requests of each client.
+    ///
+    /// Clients are identified by their connection, or by their API key if the config names the
+    /// header it's sent in and the server passes it on with the [`ClientKeyLayer`]. Requests that
+    /// exceed a limit are rejected with a rate limit error. The limits are shared by all clones of
+    /// the API, and the accounting so far is discarded. Unlimited by default.
+    pub fn with_client_limits(self, config: ClientLimitsConfig) -> Self {
+        self.inner.client_limits.configure(config);
+        self
+    }
+
+    /// Returns the cache for the traces of recently traced blocks, if enabled.
+    pub fn trace_cache(&self) -> Option<&TraceCache> {
+        self.inner.caches.trace_cache.as_ref()
+    }
+
+    /// Returns the cache for the intermediate states of recently traced blocks, if enabled.
+    pub fn state_snapshot_cache(&self) -> Option<&StateSnapshotCache> {
+        self.inner.caches.state_snapshot_cache.as_ref()
+    }
+
+    /// Returns the cache for the state read by requests on top of recent blocks, if enabled.
+    pub fn state_read_cache(&self) -> Option<&StateReadCache> {
+        self.inner.caches.state_read_cache.as_ref()
+    }
+
+    /// Returns the cache for recently generated execution witnesses, if enabled.
+    pub fn witness_cache(&self) -> Option<&WitnessCache> {
+        self.inner.caches.witness_cache.as_ref()
+    }
+
+    /// Returns the caches of the API.
+    pub fn caches(&self) -> DebugApiCaches {
+        self.inner.caches.clone()
+    }
+
+    /// Spawns the task that removes reorged blocks from the enabled caches.
+    ///
+    /// The task should be spawned once per set of caches, see [`DebugApiCaches`].
+    pub fn spawn_cache_reorg_task<Events>(&self, executor: &dyn TaskSpawner, events: &Events)
+    where
+        Events: CanonStateSubscriptions,
+    {
+        self.caches().spawn_reorg_task(executor, events)
+    }
+
+    /// Access the underlying `Eth` API.
+    pub fn eth_api(&self) -> &Eth {
+        &self.inner.eth_api
+    }
+
+    /// Returns the settings of the API, which are configured before the API is cloned.
+    ///
+    /// # Panics
+    ///
+    /// If the API is already shared with a clone.
+    fn inner_mut(&mut self) -> &mut DebugApiInner<Provider, Eth> {
+        Arc::get_mut(&mut self.inner).expect("the debug API is configured before it's cloned")
+    }
+}
+
+// === impl DebugApi ===
+
+impl<Provider, Eth> DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + 'static,
+    Eth: EthApiTypes + TraceExt + 'static,
+{
+    /// Acquires a permit of the given class to execute a tracing call.
+    ///
+    /// Fails if no permit became available within the configured maximum wait, or if the
+    /// [`BlockingTaskGuard`] was closed, e.g. because the node is shutting down.
+    async fn acquire_trace_permit(&self, class: TraceClass) -> Result<TracePermit, EthApiError> {
+        self.inner.trace_permits.acquire(class, &self.inner.blocking_task_guard).await
+    }
+
+    /// Returns the gas cap of traced calls.
+    ///
+    /// Fails if the gas limit of one of the calls exceeds the cap.
+    fn call_gas_cap<'a>(
+        &self,
+        calls: impl IntoIterator<Item = &'a TransactionRequest>,
+    ) -> Result<u64, EthApiError> {
+        let gas_cap = self.inner.gas_cap.unwrap_or_else(|| self.inner.eth_api.call_gas_limit());
+        for gas in calls.into_iter().filter_map(|call| call.gas) {
+            if gas > u128::from(gas_cap) {
+                return Err(EthApiError::InvalidParams(format!(
+                    "requested gas {gas} exceeds the configured gas cap of {gas_cap}"
+                )))
+            }
+        }
+        Ok(gas_cap)
+    }
+
+    /// Fails with [`EthApiError::HistoricalStateUnavailable`] if the state at the block with the
+    /// given number was pruned, according to the prune checkpoints of the provider.
+    ///
+    /// This is checked up front, because the state of a pruned block only fails with a generic
+    /// error once its missing history is read during execution.
+    fn ensure_state_available(&self, block: BlockNumber) -> Result<(), EthApiError> {
+        match earliest_available_state(&self.inner.provider)? {
+            Some(earliest) if block < earliest => {
+                Err(EthApiError::HistoricalStateUnavailable { block, earliest })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the error of a read of the state at the block with the given number that failed
+    /// because the state was pruned, like [`Self::ensure_state_available`].
+    fn state_pruned_error(&self, block: BlockNumber) -> EthApiError {
+        match earliest_available_state(&self.inner.provider) {
+            Ok(Some(earliest)) => EthApiError::HistoricalStateUnavailable { block, earliest },
+            Ok(None) => ProviderError::StateAtBlockPruned(block).into(),
+            Err(err) => err.into(),
+        }
+    }
+
+    /// Fails with [`EthApiError::NodeSyncing`] if the node is syncing and the block with the given
+    /// id is at or beyond the block the state is executed up to, since the sync head isn't the tip
+    /// of the canonical chain yet.
+    ///
+    /// The progress is taken from the sync status of the `eth` API, blocks the node doesn't know
+    /// are left to the request to reject.
+    fn ensure_not_syncing(&self, block_id: BlockId) -> Result<(), EthApiError>
+    where
+        Eth: EthApiSpec,
+    {
+        let SyncStatus::Info(info) = self.eth_api().sync_status()? else { return Ok(()) };
+        let stages = info.stages.unwrap_or_default();
+        let executed = stages
+            .iter()
+            .find(|stage| stage.name == EXECUTION_STAGE)
+            .map_or_else(|| info.current_block.saturating_to(), |stage| stage.block);
+
+        // the pending block is always beyond the executed state
+        let block = if block_id.is_pending() {
+            Some(BlockNumber::MAX)
+        } else {
+            match self.resolve_block_hash(block_id)? {
+                Some(hash) => self.inner.provider.block_number(hash)?,
+                None => None,
+            }
+        };
+        match block {
+            Some(block) if block >= executed => Err(EthApiError::NodeSyncing { executed, stages }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Executes a tracing request of the given class while holding a trace permit, and records the
+    /// metrics of its RPC method.
+    ///
+    /// The request is executed on an instance with its own reservation of the trace memory
+    /// budget and its own recorder of the database lookups of its executions. The reservation is
+    /// held until the server serialized the response if it has the [`DebugRpcLayer`], and
+    /// released once the request returns otherwise.
+    async fn traced<T, F, Fut>(
+        &self,
+        ext: &Extensions,
+        metrics: &DebugMethodMetrics,
+        class: TraceClass,
+        request: F,
+    ) -> RpcResult<T>
+    where
+        F: FnOnce(Self) -> Fut,
+        Fut: Future<Output = RpcResult<T>>,
+    {
+        metrics.requests_total.increment(1);
+        let start = Instant::now();
+        let permit = self.acquire_trace_permit(class).await;
+        metrics.permit_wait_seconds.record(start.elapsed().as_secs_f64());
+
+        let trace_memory = self.inner.trace_memory_budget.reservation();
+        let execution_stats = Arc::new(ExecutionStatsRecorder::default());
+        let this = Self {
+            trace_memory: Some(trace_memory.clone()),
+            execution_stats: Some(execution_stats.clone()),
+            ..self.clone()
+        };
+        let result = match permit {
+            Ok(_permit) => request(this).await,
+            Err(err) => Err(err.into()),
+        };
+        metrics.record_execution_stats(&execution_stats.stats());
+        match &result {
+            Ok(_) => metrics.response_size_bytes.record(trace_memory.reserved() as f64),
+            Err(_) => metrics.errors_total.increment(1),
+        }
+        // the traces count against the budget until the server serialized the response
+        if let Some(response_trace_memory) = ext.get::<ResponseTraceMemory>() {
+            response_trace_memory.hold(trace_memory);
+        }
+        result
+    }
+
+    /// Executes a request of the given RPC method, and records its entry in the audit log if
+    /// it's enabled.
+    ///
+    /// The size of the response is estimated by the trace memory the request holds until its
+    /// response is serialized, see [`Self::traced`].
+    async fn audited<T, Fut>(
+        &self,
+        ext: &Extensions,
+        method: &'static str,
+        params: DebugAuditParams,
+        request: Fut,
+    ) -> RpcResult<T>
+    where
+        Fut: Future<Output = RpcResult<T>>,
+    {
+        let Some(sink) = &self.inner.audit_sink else { return request.await };
+        let (arrived, start) = (SystemTime::now(), Instant::now());
+        let result = request.await;
+        let response_size = ext
+            .get::<ResponseTraceMemory>()
+            .and_then(ResponseTraceMemory::reserved)
+            .map(|size| size as u64);
+        let duration = start.elapsed();
+        sink.record(DebugAuditEntry::new(
+            method,
+            params,
+            arrived,
+            duration,
+            &result,
+            response_size,
+        ));
+        result
+    }
+
+    /// Returns the kind of the tracer a request with the tracing options traces with, as recorded
+    /// in the audit log.
+    fn audit_tracer(&self, opts: Option<&GethDebugTracingOptions>) -> Option<TracerKind> {
+        let tracer = opts.and_then(|opts| opts.tracer.as_ref());
+        Some(TracerKind::of(
+            tracer.or(self.inner.default_tracer.as_ref().map(|(tracer, _)| tracer)),
+        ))
+    }
+
+    /// Traces the transaction like [`DebugTraceExt::trace_transaction`], accounting the traces
+    /// against the memory reservation of the request, if any.
+    fn trace_transaction(
+        &self,
+        opts: &GethDebugTracingOptions,
+        env: EnvWithHandlerCfg,
+        db: &mut StateCacheDb<'_>,
+        transaction_context: Option<TransactionContext>,
+    ) -> Result<(GethTrace, EvmState, EnvWithHandlerCfg), Eth::Error> {
+        self.check_tracer(opts)?;
+        trace::trace_with_gas_used(
+            self.eth_api(),
+            opts,
+            env,
+            db,
+            transaction_context,
+            self.trace_memory.as_ref(),
+            self.execution_stats.as_deref(),
+            self.precompile_overrides.as_deref(),
+            self.trace_counters.as_deref(),
+        )
+        .map(|(trace, _, state, env)| (trace, state, env))
+    }
+
+    /// Traces the transaction like [`Self::trace_transaction`], and attaches its state changes
+    /// if requested, and its counters if they're recorded.
+    fn trace_transaction_response(
+        &self,
+        opts: &GethDebugTracingOptions,
+        env: EnvWithHandlerCfg,
+        db: &mut StateCacheDb<'_>,
+        transaction_context: Option<TransactionContext>,
+        return_state_changes: bool,
+    ) -> Result<DebugTraceTransactionResponse, Eth::Error> {
+        let stats = || self.trace_counters.as_ref().map(|recorder| recorder.counters());
+        if !return_state_changes {
+            let (trace, _, _) = self.trace_transaction(opts, env, db, transaction_context)?;
+            let Some(stats) = stats() else { return Ok(trace.into()) };
+            return Ok(DebugTraceTransactionResult {
+                trace,
+                state_changes: None,
+                stats: Some(stats),
+            }
+            .into());
+        }
+
+        let (trace, state) = if matches!(
+            opts.tracer,
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::NoopTracer))
+        ) {
+            // the noop tracer doesn't execute the transaction, but the state changes are still
+            // required
+            let (res, _) = trace::transact(
+                self.eth_api(),
+                self.stats_db(db),
+                env,
+                self.precompile_overrides.as_deref(),
+            )?;
+            (NoopFrame::default().into(), res.state)
+        } else {
+            let (trace, state, _) = self.trace_transaction(opts, env, db, transaction_context)?;
+            (trace, state)
+        };
+        let state_changes =
+            state_changes(&state, db).map_err(|err| Eth::Error::from_eth_err(err.into()))?;
+        let stats = stats();
+        Ok(DebugTraceTransactionResult { trace, state_changes: Some(state_changes), stats }.into())
+    }
+
+    /// Wraps the database to record its lookups on the recorder of the request, if any.
+    fn stats_db<'a, 'b>(&'a self, db: &'a mut StateCacheDb<'b>) -> StatsDb<'a, 'b> {
+        StatsDb::new(db, self.execution_stats.as_deref())
+    }
+
+    /// Returns the response of a block trace, with the statistics of the database lookups of the
+    /// request in its summary and the post-block changes if requested, or the page if the block is
+    /// traced in pages.
+    fn block_trace_response(
+        &self,
+        page: BlockTracePage,
+        paginated: bool,
+        format: TraceResultFormat,
+        verbose: bool,
+        include_execution_stats: bool,
+        context: Option<BlockTraceContext>,
+    ) -> DebugTraceBlockResponse {
+        if paginated {
+            return DebugTraceBlockResponse::Page(page)
+        }
+        let BlockTracePage { results, post_block, .. } = page;
+        let verbose = verbose || include_execution_stats;
+        if let Some(context) = context.filter(|_| !verbose) {
+            let results = without_stats(results);
+            return DebugTraceBlockResponse::WithContext(BlockTraceWithContext {
+                context,
+                results,
+                post_block,
+            })
+        }
+        let mut response = DebugTraceBlockResponse::with_stats(results, format, verbose);
+        if let DebugTraceBlockResponse::Verbose(trace) = &mut response {
+            trace.summary.execution_stats = self
+                .execution_stats
+                .as_ref()
+                .filter(|_| include_execution_stats)
+                .map(|recorder| recorder.stats());
+            trace.context = context;
+        }
+        response.with_post_block(post_block)
+    }
+
+    /// Returns the context of the block if the options request it, as returned with the traces of
+    /// the block.
+    async fn block_trace_context(
+        &self,
+        block_id: BlockId,
+        opts: &DebugTracingBlockOptions,
+    ) -> Result<Option<BlockTraceContext>, Eth::Error> {
+        if !opts.include_block_context {
+            return Ok(None)
+        }
+        let block = self
+            .inner
+            .eth_api
+            .block_with_senders(block_id)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        Ok(Some(block_trace_context(&block.header, block.hash(), block.body.len())))
+    }
+
+    /// Returns an instance that records the database lookups of its executions, on the recorder
+    /// of the request if it has one.
+    fn with_execution_stats(&self) -> Self {
+        Self {
+            execution_stats: Some(self.execution_stats.clone().unwrap_or_default()),
+            ..self.clone()
+        }
+    }
+
+    /// Returns an instance that counts its traced executions on a new recorder.
+    fn with_trace_counters(&self) -> Self {
+        Self { trace_counters: Some(Default::default()), ..self.clone() }
+    }
+
+    /// Returns an instance that executes with the precompiles changed by the state overrides of
+    /// its request, if they change any.
+    fn with_precompile_overrides(&self, overrides: Option<PrecompileOverrides>) -> Self {
+        Self { precompile_overrides: overrides.map(Arc::new), ..self.clone() }
+    }
+
+    /// Returns the cache for the intermediate states of recently traced blocks, unless the
+    /// request this instance is scoped to bypasses it.
+    fn scoped_state_snapshot_cache(&self) -> Option<&StateSnapshotCache> {
+        self.inner.caches.state_snapshot_cache.as_ref().filter(|_| !self.skip_state_snapshots)
+    }
+
+    /// Acquires up to `n` additional tracing permits of the class without waiting, for tracing
+    /// work that is spread across multiple threads.
+    fn try_acquire_trace_permits(&self, class: TraceClass, n: usize) -> Vec<TracePermit> {
+        let shared = &self.inner.blocking_task_guard;
+        std::iter::from_fn(|| self.inner.trace_permits.try_acquire(class, shared)).take(n).collect()
+    }
+
+    /// Executes the blocking tracing work on the dedicated trace pool, if configured, or the
+    /// tracing pool of the `eth` API.
+    fn spawn_tracing<F, R>(&self, f: F) -> impl Future<Output = Result<R, Eth::Error>> + Send
+    where
+        F: FnOnce(Eth) -> Result<R, Eth::Error> + Send + 'static,
+        R: Send + 'static,
+    {
+        let Some(pool) = &self.inner.trace_pool else {
+            return Either::Left(self.eth_api().spawn_tracing(f))
+        };
+        let eth_api = self.eth_api().clone();
+        let task = pool.spawn(move || f(eth_api));
+        Either::Right(async move { task.await.map_err(blocking_task_error)? })
+    }
+
+    /// Executes the blocking tracing work with the state of the given block, like
+    /// [`Call::spawn_with_state_at_block`], on the pool of [`Self::spawn_tracing`].
+    fn spawn_with_state_at_block<F, R>(
+        &self,
+        at: BlockId,
+        f: F,
+    ) -> impl Future<Output = Result<R, Eth::Error>> + Send
+    where
+        F: FnOnce(StateProviderTraitObjWrapper<'_>) -> Result<R, Eth::Error> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.spawn_with_call_state(CallState::At(at), f)
+    }
+
+    /// Executes the blocking tracing work with the given state, on the pool of
+    /// [`Self::spawn_tracing`].
+    fn spawn_with_call_state<F, R>(
+        &self,
+        state: CallState,
+        f: F,
+    ) -> impl Future<Output = Result<R, Eth::Error>> + Send
+    where
+        F: FnOnce(StateProviderTraitObjWrapper<'_>) -> Result<R, Eth::Error> + Send + 'static,
+        R: Send + 'static,
+    {
+        // the cached state is keyed by block hash, so e.g. calls on top of the latest block share
+        // the reads of calls on top of its hash
+        let cached = match (&state, &self.inner.caches.state_read_cache) {
+            (CallState::At(BlockId::Number(BlockNumberOrTag::Pending)), _) => None,
+            (CallState::At(at), Some(cache)) => {
+                self.resolve_block_hash(*at).ok().flatten().map(|hash| (cache.clone(), hash))
+            }
+            _ => None,
+        };
+        self.spawn_tracing(move |eth_api| {
+            let state: StateProviderBox = match (state, cached) {
+                (CallState::At(_), Some((cache, hash))) => {
+                    let state = eth_api.state_at_block_id(hash.into())?;
+                    Box::new(CachedStateProvider::new(state, cache, hash))
+                }
+                (CallState::At(at), None) => eth_api.state_at_block_id(at)?,
+                (CallState::Pending(state), _) => state,
+            };
+            f(StateProviderTraitObjWrapper(&state))
+        })
+    }
+
+    /// Wraps the state of the block with the given hash, so that its reads are shared with other
+    /// requests on top of the block if the state read cache is enabled.
+    fn with_cached_reads(&self, state: StateProviderBox, block_hash: B256) -> StateProviderBox {
+        match &self.inner.caches.state_read_cache {
+            Some(cache) => Box::new(CachedStateProvider::new(state, cache.clone(), block_hash)),
+            None => state,
+        }
+    }
+
+    /// Resolves the block id to the hash of the block it refers to.
+    ///
+    /// Tags resolve the same way for every debug endpoint: `pending` is the pending block of the
+    /// provider, or the latest block if there is none. `safe` and `finalized` are only known once
+    /// the node received forkchoice state, until then [`EthApiError::BlockTagNotAvailable`] is
+    /// returned.
+    ///
+    /// Returns `None` if there is no block with the requested number.
+    fn resolve_block_hash(&self, block_id: BlockId) -> Result<Option<B256>, EthApiError> {
+        let provider = &self.inner.provider;
+        let tag = match block_id {
+            BlockId::Hash(hash) => return Ok(Some(hash.block_hash)),
+            BlockId::Number(tag) => tag,
+        };
+
+        let hash = match tag {
+            BlockNumberOrTag::Latest => Some(provider.chain_info()?.best_hash),
+            BlockNumberOrTag::Pending => match provider.pending_block_num_hash()? {
+                Some(pending) => Some(pending.hash),
+                None => Some(provider.chain_info()?.best_hash),
+            },
+            BlockNumberOrTag::Safe => {
+                Some(provider.safe_block_hash()?.ok_or(EthApiError::BlockTagNotAvailable(tag))?)
+            }
+            BlockNumberOrTag::Finalized => Some(
+                provider.finalized_block_hash()?.ok_or(EthApiError::BlockTagNotAvailable(tag))?,
+            ),
+            BlockNumberOrTag::Earliest => provider.block_hash(0)?,
+            BlockNumberOrTag::Number(number) => provider.block_hash(number)?,
+        };
+        Ok(hash)
+    }
+
+    /// Resolves the block number or tag to the number of the block it refers to.
+    ///
+    /// Numbers are returned as is, even if there is no block with the number.
+    fn resolve_block_number(&self, block: BlockNumberOrTag) -> Result<u64, EthApiError> {
+        if let BlockNumberOrTag::Number(number) = block {
+            return Ok(number)
+        }
+        let hash = self
+            .resolve_block_hash(block.into())?
+            .ok_or(DebugApiError::BlockNotFound(block.into()))?;
+        self.inner
+            .provider
+            .block_number(hash)?
+            .ok_or_else(|| DebugApiError::BlockNotFound(hash.into()).into())
+    }
+
+    /// Returns the pending block of the node, which may be built locally, and its receipts.
+    ///
+    /// Fails if there is no pending block, e.g. because it can't be built.
+    async fn pending_block(&self) -> Result<(SealedBlockWithSenders, Vec<Receipt>), Eth::Error> {
+        Ok(self
+            .eth_api()
+            .local_pending_block()
+            .await?
+            .ok_or(EthApiError::BlockTagNotAvailable(BlockNumberOrTag::Pending))?)
+    }
+
+    /// Returns the block with the given id and its senders, with `pending` referring to the
+    /// pending block of the node.
+    async fn block_with_senders(
+        &self,
+        block_id: BlockId,
+        transaction_kind: TransactionVariant,
+    ) -> Result<BlockWithSenders, Eth::Error> {
+        if matches!(block_id, BlockId::Number(BlockNumberOrTag::Pending)) {
+            return Ok(self.pending_block().await?.0.unseal())
+        }
+
+        let block = match self.resolve_block_hash(block_id)? {
+            Some(hash) => self
+                .inner
+                .provider
+                .block_with_senders_by_id(hash.into(), transaction_kind)
+                .map_err(Eth::Error::from_eth_err)?,
+            None => None,
+        };
+        block.ok_or(DebugApiError::BlockNotFound(block_id)).map_err(Eth::Error::from_eth_err)
+    }
+
+    /// Traces the given block on top of the given state of its parent block, in the given
+    /// environment.
+    ///
+    /// Unlike [`Self::debug_trace_block`], this doesn't resolve anything via the provider: the
+    /// block, its environment and the parent state are used as given. This is useful if the block
+    /// is at hand already, e.g. in payload validation hooks or `ExEx` notifications, and may not be
+    /// persisted yet. The traces are not cached.
+    ///
+    /// If the configured parallelism allows it, and there are spare tracing permits, the block is
+    /// split into contiguous chunks of transactions that are traced in parallel. The state each
+    /// chunk starts on is captured by a fast replay of the preceding transactions without an
+    /// inspector.
+    ///
+    /// If the options select a subset of the transactions, only these are traced and the
+    /// transactions after the last selected one are not executed at all. Otherwise, if requested,
+    /// the post-block changes are applied after the last transaction, but only
+    /// [`Self::trace_block_page`] reports them.
+    ///
+    /// The statistics of the verbose mode are dropped, see [`Self::trace_block_with_stats`].
+    pub async fn trace_block_with(
+        &self,
+        block: BlockWithSenders,
+        state: impl StateProvider + 'static,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<Vec<TraceResult>, Eth::Error> {
+        let results = self.trace_block_with_stats(block, state, cfg, block_env, opts).await?;
+        Ok(without_stats(results))
+    }
+
+    /// Traces the given block like [`Self::trace_block_with`], and attaches the `TraceStats` of
+    /// each traced transaction to its result if verbose mode is enabled.
+    ///
+    /// The time is measured on the tracing task, around the execution and tracing of each
+    /// transaction, so it doesn't include waiting for a tracing permit or replaying the preceding
+    /// transactions.
+    ///
+    /// Fails before executing anything if the gas used of the block exceeds the limit of the
+    /// tracer, see [`Self::with_max_block_gas`]. If the block is traced in pages, the results of
+    /// the requested page are returned, see [`Self::trace_block_page`].
+    pub async fn trace_block_with_stats(
+        &self,
+        block: BlockWithSenders,
+        state: impl StateProvider + 'static,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<Vec<TraceResultWithStats>, Eth::Error> {
+        let page = self.trace_block_page(block, state, cfg, block_env, opts).await?;
+        Ok(page.results)
+    }
+
+    /// Traces all or the selected transactions of the given block, see
+    /// [`Self::trace_block_with_stats`].
+    async fn trace_whole_block(
+        &self,
+        block: BlockWithSenders,
+        state: impl StateProvider + 'static,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<BlockTracePage, Eth::Error> {
+        let block_hash = block.header.hash_slow();
+        let gas_used = block.header.gas_used;
+        let (block, senders) = block.into_components();
+        let withdrawals = block.withdrawals;
+        let mut transactions = block
+            .body
+            .into_iter()
+            .zip(senders)
+            .map(|(tx, sender)| tx.with_signer(sender))
+            .collect::<Vec<_>>();
+        let DebugTracingBlockOptions {
+            tracing_options: opts,
+            tx_hashes,
+            include_post_block,
+            verbose,
+            include_execution_stats,
+            include_stats,
+            force,
+            ..
+        } = opts;
+        let verbose = verbose || include_execution_stats || include_stats;
+        self.check_tracer(&opts)?;
+        let opts = self.apply_default_tracer(opts);
+        // reject blocks that are too expensive to trace before executing anything
+        self.check_block_gas(&opts, gas_used, force)?;
+
+        let mut selected = None;
+        let mut unknown: Vec<TraceResultWithStats> = Vec::new();
+        if !tx_hashes.is_empty() {
+            let hashes = tx_hashes.iter().copied().collect::<HashSet<_>>();
+            let last = transactions.iter().rposition(|tx| hashes.contains(&tx.hash));
+            transactions.truncate(last.map_or(0, |last| last + 1));
+
+            let known = transactions.iter().map(|tx| tx.hash).collect::<HashSet<_>>();
+            let mut reported = HashSet::new();
+            unknown = tx_hashes
+                .into_iter()
+                .filter(|hash| !known.contains(hash) && reported.insert(*hash))
+                .map(|hash| {
+                    TraceResult::Error {
+                        error: "transaction not found in block".to_string(),
+                        tx_hash: Some(hash),
+                    }
+                    .into()
+                })
+                .collect();
+            selected = Some(hashes);
+        }
+
+        let post_block = (include_post_block && selected.is_none())
+            .then(|| withdrawals.map(Withdrawals::into_inner).unwrap_or_default());
+        if transactions.is_empty() && post_block.is_none() {
+            // nothing to trace
+            return Ok(BlockTracePage { results: unknown, ..Default::default() })
+        }
+
+        // the request itself already holds a permit. If only a subset of the transactions is
+        // traced, most of the work is the replay of the preceding transactions, which is done
+        // sequentially anyway
+        let max_parallelism = if selected.is_some() {
+            1
+        } else {
+            self.inner.trace_block_parallelism.min(transactions.len())
+        };
+        let permits =
+            self.try_acquire_trace_permits(TraceClass::Block, max_parallelism.saturating_sub(1));
+        let parallelism = permits.len() + 1;
+
+        // replay all transactions of the block
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Block);
+            let _permits = permits;
+            let state = StateProviderTraitObjWrapper(&state);
+            let block_hash = Some(block_hash);
+            let transactions = transactions.into_iter().enumerate().collect::<Vec<_>>();
+
+            if parallelism == 1 {
+                let mut db = CacheDB::new(StateProviderDatabase::new(state));
+                let mut results = trace::trace_transactions(
+                    this.eth_api(),
+                    &mut db,
+                    transactions.iter().map(|(index, tx)| (*index, tx)),
+                    selected.as_ref(),
+                    &cfg,
+                    &block_env,
+                    &opts,
+                    block_hash,
+                    verbose,
+                    include_stats,
+                    &cancellation,
+                    this.trace_memory.as_ref(),
+                    this.execution_stats.as_deref(),
+                    this.trace_progress.as_deref(),
+                    None,
+                )?;
+                let post_block = post_block
+                    .map(|withdrawals| {
+                        this.apply_post_block(&mut db, &cfg, &block_env, &withdrawals)
+                    })
+                    .transpose()?;
+                results.extend(unknown);
+                return Ok(BlockTracePage { results, next_cursor: None, post_block });
+            }
+
+            // capture the state at the start of each chunk
+            let chunk_size = transactions.len().div_ceil(parallelism);
+            let mut chunks = Vec::with_capacity(parallelism);
+            let mut db =
+                CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(state.0)));
+            let mut env = EnvWithHandlerCfg::new_with_cfg_env(
+                cfg.clone(),
+                block_env.clone(),
+                Default::default(),
+            );
+            let mut remaining = transactions.chunks(chunk_size).peekable();
+            while let Some(chunk) = remaining.next() {
+                chunks.push((chunk, fork_db(&db, state.0)));
+                if remaining.peek().is_some() {
+                    for (_, tx) in chunk {
+                        cancellation.check()?;
+                        trace::fill_block_tx_env(this.eth_api(), &mut env, tx);
+                        let (res, used_env) =
+                            this.eth_api().transact(this.stats_db(&mut db), env)?;
+                        env = used_env;
+                        db.commit(res.state);
+                    }
+                }
+            }
+
+            std::thread::scope(|scope| {
+                let workers = chunks
+                    .into_iter()
+                    .map(|(chunk, mut db)| {
+                        let (this, cfg, block_env, opts, cancellation) =
+                            (&this, &cfg, &block_env, &opts, &cancellation);
+                        scope.spawn(move || {
+                            trace::trace_transactions(
+                                this.eth_api(),
+                                &mut db,
+                                chunk.iter().map(|(index, tx)| (*index, tx)),
+                                None,
+                                cfg,
+                                block_env,
+                                opts,
+                                block_hash,
+                                verbose,
+                                include_stats,
+                                cancellation,
+                                this.trace_memory.as_ref(),
+                                this.execution_stats.as_deref(),
+                                this.trace_progress.as_deref(),
+                                None,
+                            )
+                            .map(|traces| (traces, db))
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                // join in order to keep the results ordered by transaction index
+                let mut results = Vec::with_capacity(transactions.len());
+                let mut last_db = None;
+                for worker in workers {
+                    let (traces, db) =
+                        worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+                    results.extend(traces);
+                    last_db = Some(db);
+                }
+
+                // the database of the last chunk holds the state after the last transaction
+                let post_block = post_block
+                    .zip(last_db)
+                    .map(|(withdrawals, mut db)| {
+                        this.apply_post_block(&mut db, &cfg, &block_env, &withdrawals)
+                    })
+                    .transpose()?;
+                Ok(BlockTracePage { results, next_cursor: None, post_block })
+            })
+        })
+        .await
+    }
+
+    /// Traces the given block like [`Self::trace_block_with_stats`], and returns the results as a
+    /// page with the post-block changes, if requested, and the cursor of the next page if the
+    /// block is traced in pages, see [`DebugTracingBlockOptions::cursor`].
+    ///
+    /// The given state is expected to be the state of the parent of the block, since the state
+    /// after a page is cached for the next page if the state snapshot cache is enabled.
+    pub async fn trace_block_page(
+        &self,
+        block: BlockWithSenders,
+        state: impl StateProvider + 'static,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<BlockTracePage, Eth::Error> {
+        if opts.is_paginated() {
+            return self.trace_page(block, state, cfg, block_env, opts).await
+        }
+        self.trace_whole_block(block, state, cfg, block_env, opts).await
+    }
+
+    /// Traces the page of the given block that the options select.
+    ///
+    /// The page resumes from the closest cached state before its first transaction, if any, and
+    /// replays the remaining preceding transactions without tracing them. The state before the
+    /// first transaction of the next page is cached in turn.
+    async fn trace_page(
+        &self,
+        block: BlockWithSenders,
+        state: impl StateProvider + 'static,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<BlockTracePage, Eth::Error> {
+        let block_hash = block.header.hash_slow();
+        let gas_used = block.header.gas_used;
+        let (block, senders) = block.into_components();
+        let withdrawals = block.withdrawals;
+        let transactions = block
+            .body
+            .into_iter()
+            .zip(senders)
+            .map(|(tx, sender)| tx.with_signer(sender))
+            .collect::<Vec<_>>();
+        let DebugTracingBlockOptions {
+            tracing_options: opts,
+            tx_hashes,
+            include_post_block,
+            verbose,
+            include_execution_stats,
+            include_stats,
+            force,
+            cursor,
+            max_transactions,
+            max_response_bytes,
+            ..
+        } = opts;
+        let verbose = verbose || include_execution_stats || include_stats;
+        self.check_tracer(&opts)?;
+        let opts = self.apply_default_tracer(opts);
+        self.check_block_gas(&opts, gas_used, force)?;
+        if !tx_hashes.is_empty() {
+            return Err(EthApiError::InvalidParams(
+                "txHashes can't be combined with a paginated trace".to_string(),
+            )
+            .into())
+        }
+
+        let start = match cursor {
+            None => 0,
+            Some(cursor) if cursor.block_hash != block_hash => {
+                return Err(EthApiError::InvalidParams("cursor of another block".to_string()).into())
+            }
+            Some(cursor) if cursor.tx_index > transactions.len() as u64 => {
+                return Err(EthApiError::InvalidParams("invalid cursor".to_string()).into())
+            }
+            Some(cursor) => cursor.tx_index as usize,
+        };
+        let end = max_transactions.map_or(transactions.len(), |max| {
+            start.saturating_add(max.max(1) as usize).min(transactions.len())
+        });
+        let post_block = include_post_block
+            .then(|| withdrawals.map(Withdrawals::into_inner).unwrap_or_default());
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Block);
+            let state = StateProviderTraitObjWrapper(&state);
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+
+            let snapshot_cache = this.scoped_state_snapshot_cache();
+            let mut replayed = 0;
+            if let Some((index, snapshot)) =
+                snapshot_cache.and_then(|cache| cache.get(block_hash, start))
+            {
+                snapshot.apply(&mut db);
+                replayed = index;
+            }
+            let mut env = EnvWithHandlerCfg::new_with_cfg_env(
+                cfg.clone(),
+                block_env.clone(),
+                Default::default(),
+            );
+            for tx in &transactions[replayed..start] {
+                cancellation.check()?;
+                trace::fill_block_tx_env(this.eth_api(), &mut env, tx);
+                let (res, used_env) = this.eth_api().transact(this.stats_db(&mut db), env)?;
+                env = used_env;
+                db.commit(res.state);
+            }
+
+            let results = trace::trace_transactions(
+                this.eth_api(),
+                &mut db,
+                transactions[start..end].iter().enumerate().map(|(i, tx)| (start + i, tx)),
+                None,
+                &cfg,
+                &block_env,
+                &opts,
+                Some(block_hash),
+                verbose,
+                include_stats,
+                &cancellation,
+                this.trace_memory.as_ref(),
+                this.execution_stats.as_deref(),
+                this.trace_progress.as_deref(),
+                max_response_bytes,
+            )?;
+            let next = start + results.len();
+            if next < transactions.len() {
+                // the database holds the state before the first transaction of the next page
+                if let Some(cache) = snapshot_cache {
+                    cache.insert(block_hash, next, snapshot::StateSnapshot::new(&db));
+                }
+                let next_cursor = BlockTraceCursor { block_hash, tx_index: next as u64 };
+                return Ok(BlockTracePage {
+                    results,
+                    next_cursor: Some(next_cursor),
+                    post_block: None,
+                })
+            }
+            let post_block = post_block
+                .map(|withdrawals| this.apply_post_block(&mut db, &cfg, &block_env, &withdrawals))
+                .transpose()?;
+            Ok(BlockTracePage { results, next_cursor: None, post_block })
+        })
+        .await
+    }
+
+    /// Applies the post-block changes to the given database, which is expected to hold the state
+    /// after the last transaction of the block, and returns them.
+    ///
+    /// If Prague is active, this calls the EIP-7002 and EIP-7251 system contracts like the block
+    /// executor, and if Shanghai is active, the withdrawals of the block are credited. Block
+    /// rewards of pre-merge blocks are not applied.
+    fn apply_post_block(
+        &self,
+        db: &mut StateCacheDb<'_>,
+        cfg: &CfgEnvWithHandlerCfg,
+        block_env: &BlockEnv,
+        withdrawals: &[Withdrawal],
+    ) -> Result<PostBlockTrace, Eth::Error> {
+        let chain_spec = self.inner.provider.chain_spec();
+        let timestamp = block_env.timestamp.to::<u64>();
+
+        if chain_spec.is_prague_active_at_timestamp(timestamp) {
+            let evm_config = Call::evm_config(self.eth_api());
+            post_block_withdrawal_requests_contract_call(evm_config, db, cfg, block_env)
+                .map_err(|err| EthApiError::Internal(err.into()))?;
+            post_block_consolidation_requests_contract_call(evm_config, db, cfg, block_env)
+                .map_err(|err| EthApiError::Internal(err.into()))?;
+        }
+
+        let mut credits = Vec::new();
+        if chain_spec.is_shanghai_active_at_timestamp(timestamp) {
+            for withdrawal in withdrawals.iter().filter(|withdrawal| withdrawal.amount > 0) {
+                let amount = withdrawal.amount_wei();
+                let info = db
+                    .basic(withdrawal.address)
+                    .map_err(Eth::Error::from_eth_err)?
+                    .unwrap_or_default();
+                let mut account = Account::from(info);
+                account.info.balance = account.info.balance.saturating_add(amount);
+                account.mark_touch();
+                credits.push(WithdrawalCredit {
+                    address: withdrawal.address,
+                    amount,
+                    balance: account.info.balance,
+                });
+                // committed like a transaction so that credited accounts that didn't exist are
+                // created
+                db.commit(HashMap::from_iter([(withdrawal.address, account)]));
+            }
+        }
+
+        Ok(PostBlockTrace { withdrawals: credits })
+    }
+
+    /// Returns the environment and the state to trace calls in on top of the first `num_txs`
+    /// transactions of the given block, or on top of the whole block if unset.
+    ///
+    /// `pending` refers to the pending block of the node, which may be built locally. It's looked
+    /// up once, so that all calls of a request see the same pending block even if a new one is
+    /// built in the meantime. The calls are traced on the in-memory state of the pending block if
+    /// the provider has it, and otherwise the transactions of the pending block are replayed on
+    /// the state of its parent. Without a pending block, the calls are traced in the environment
+    /// of the next block on top of the latest state, or within the latest block if `num_txs` is
+    /// set.
+    ///
+    /// If `pending_from_pool` is set, `pending` refers to a pending block that is built from the
+    /// pool for the request instead, see [`Self::build_pending_from_pool`].
+    ///
+    /// Fails with [`EthApiError::InvalidParams`] if `num_txs` exceeds the number of transactions
+    /// of the block.
+    async fn call_context(
+        &self,
+        block_id: BlockId,
+        num_txs: Option<usize>,
+        pending_from_pool: bool,
+    ) -> Result<CallContext, Eth::Error> {
+        let pending = if !block_id.is_pending() {
+            None
+        } else if pending_from_pool {
+            Some(self.build_pending_from_pool().await?)
+        } else {
+            self.eth_api().local_pending_block().await?.map(|(block, _)| block)
+        };
+
+        let (block, cfg, block_env) = if let Some(block) = pending {
+            let (cfg, block_env) = self.evm_env_on_parent(&block.header, block.parent_hash).await?;
+            (block, cfg, block_env)
+        } else if num_txs.is_none() {
+            // if the whole block is to be replayed, the calls are traced on top of the state at
+            // the block
+            let (cfg, block_env, at) = self.eth_api().evm_env_at(block_id).await?;
+            if !block_id.is_pending() {
+                self.ensure_state_available(block_env.number.saturating_to())?;
+            }
+            let state = CallState::At(at);
+            return Ok(CallContext { cfg, block_env, state, transactions: Vec::new() })
+        } else {
+            // without a pending block, the position is within the latest block. The block is
+            // resolved once, so that the env and the transactions are of the same block even if
+            // the head advances in between
+            let block_id = if block_id.is_pending() { BlockId::latest() } else { block_id };
+            let block_hash = self
+                .resolve_block_hash(block_id)?
+                .ok_or(DebugApiError::BlockNotFound(block_id))
+                .map_err(Eth::Error::from_eth_err)?;
+            let ((cfg, block_env, _), block) = futures::try_join!(
+                self.eth_api().evm_env_at(block_hash.into()),
+                self.eth_api().block_with_senders(block_hash.into()),
+            )?;
+            let block = block
+                .ok_or(DebugApiError::BlockNotFound(block_id))
+                .map_err(Eth::Error::from_eth_err)?;
+            (block, cfg, block_env)
+        };
+
+        let block_txs = block.body.len();
+        let num_txs = num_txs.unwrap_or(block_txs);
+        if num_txs > block_txs {
+            return Err(EthApiError::InvalidParams(format!(
+                "transaction index {num_txs} out of range, block has {block_txs} transactions"
+            ))
+            .into())
+        }
+
+        if num_txs == block_txs {
+            // the calls can be traced on the state after the block, if it's available. A block
+            // built from the pool is only known to the request
+            let state = if pending_from_pool {
+                None
+            } else if block_id.is_pending() {
+                self.inner
+                    .provider
+                    .pending_state_by_hash(block.hash())
+                    .map_err(Eth::Error::from_eth_err)?
+                    .map(CallState::Pending)
+            } else {
+                self.ensure_state_available(block.number)?;
+                Some(CallState::At(block.hash().into()))
+            };
+            if let Some(state) = state {
+                return Ok(CallContext { cfg, block_env, state, transactions: Vec::new() })
+            }
+        }
+
+        // the transactions prior to the targeted position need to be replayed on top of the
+        // parent state
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let state = CallState::At(block.parent_hash.into());
+        let transactions = block.into_transactions_ecrecovered().take(num_txs).collect();
+        Ok(CallContext { cfg, block_env, state, transactions })
+    }
+
+    /// Returns the environment to execute the block with the given header in, on top of the given
+    /// parent block.
+    ///
+    /// This is the same as `evm_env_for_raw_block`, but with the cfg of the given parent, which
+    /// doesn't have to be the parent referenced by the header.
+    async fn evm_env_on_parent(
+        &self,
+        header: &Header,
+        parent: B256,
+    ) -> Result<(CfgEnvWithHandlerCfg, BlockEnv), Eth::Error> {
+        let (cfg, mut block_env, _) = self.eth_api().evm_env_at(parent.into()).await?;
+        let after_merge = cfg.handler_cfg.spec_id >= SpecId::MERGE;
+        Call::evm_config(self.eth_api()).fill_block_env(&mut block_env, header, after_merge);
+        Ok((cfg, block_env))
+    }
+
+    /// Returns the block with the given hash, together with the environment to execute it in,
+    /// regardless of whether the block is canonical.
+    ///
+    /// Blocks that aren't in the database, e.g. because they were reorged out, are looked up in
+    /// the blockchain tree and executed with the cfg of their parent.
+    async fn block_and_env_by_hash(
+        &self,
+        block_hash: B256,
+    ) -> Result<Option<(BlockWithSenders, CfgEnvWithHandlerCfg, BlockEnv)>, Eth::Error> {
+        if let Some(block) = self.inner.eth_api.block_with_senders(block_hash.into()).await? {
+            let (cfg, block_env, _) = self.inner.eth_api.evm_env_at(block_hash.into()).await?;
+            return Ok(Some((block.unseal(), cfg, block_env)))
+        }
+
+        let Some(block) = self
+            .inner
+            .provider
+            .find_block_by_hash(block_hash, BlockSource::Any)
+            .map_err(Eth::Error::from_eth_err)?
+        else {
+            return Ok(None)
+        };
+        let (cfg, block_env) = self.evm_env_on_parent(&block.header, block.parent_hash).await?;
+        let senders = self.recover_senders(&block)?;
+        Ok(Some((BlockWithSenders { block, senders }, cfg, block_env)))
+    }
+
+    /// Recovers the senders of the transactions of the given block.
+    fn recover_senders(&self, block: &Block) -> Result<Vec<Address>, Eth::Error> {
+        block
+            .body
+            .iter()
+            .map(|tx| self.recover_signer(tx, block.number).map_err(Eth::Error::from_eth_err))
+            .collect()
+    }
+
+    /// Recovers the signer of the given transaction of the block with the given number.
+    fn recover_signer(&self, tx: &TransactionSigned, number: u64) -> Result<Address, EthApiError> {
+        // Depending on EIP-2 we need to recover the signer differently
+        let is_homestead = self.inner.provider.chain_spec().is_homestead_active_at_block(number);
+        let signer = if is_homestead { tx.recover_signer() } else { tx.recover_signer_unchecked() };
+        signer.ok_or(EthApiError::InvalidTransactionSignature)
+    }
+
+    /// Decodes the raw signed transactions of the given bundles and recovers their signers, as
+    /// transactions of the block with the given number.
+    ///
+    /// Errors identify the transaction by its index and the index of its bundle.
+    fn decode_bundles(
+        &self,
+        bundles: Vec<DebugBundle>,
+        number: u64,
+    ) -> Result<Vec<(Vec<BundleCall>, Option<BlockOverrides>)>, EthApiError> {
+        let decode = |raw: Bytes| {
+            let tx = TransactionSigned::decode_enveloped(&mut raw.as_ref())
+                .map_err(|_| DebugApiError::InvalidRawTransaction)?;
+            let signer = self.recover_signer(&tx, number)?;
+            Ok::<_, EthApiError>(TransactionSignedEcRecovered::from_signed_transaction(tx, signer))
+        };
+
+        bundles
+            .into_iter()
+            .enumerate()
+            .map(|(bundle_index, bundle)| -> Result<_, EthApiError> {
+                let transactions = bundle
+                    .transactions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(tx_index, tx)| match tx {
+                        BundleTransaction::Request(request) => Ok(BundleCall::Request(*request)),
+                        BundleTransaction::Raw(raw) => {
+                            decode(raw).map(BundleCall::Signed).map_err(|err| {
+                                EthApiError::InvalidParams(format!(
+                                    "invalid raw transaction {tx_index} of bundle \
+                                     {bundle_index}: {err}"
+                                ))
+                            })
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok((transactions, bundle.block_override))
+            })
+            .collect()
+    }
+
+    /// Traces the transactions of a bundle of `debug_traceCallMany` in the given block
+    /// environment, with the block overrides of the bundle applied.
+    ///
+    /// The changes of the transactions are committed to the database, except for the changes of
+    /// the last one, unless `commit_last` is set or the state diff of the bundle is requested.
+    fn trace_bundle(
+        &self,
+        db: &mut StateCacheDb<'_>,
+        (transactions, block_override): (Vec<BundleCall>, Option<BlockOverrides>),
+        mut block_env: BlockEnv,
+        mut state_overrides: Option<StateOverride>,
+        settings: &BundleTraceSettings,
+        commit_last: bool,
+    ) -> Result<DebugTraceBundleResponse, Eth::Error> {
+        let BundleTraceSettings {
+            cfg,
+            tracing_options,
+            gas_limit,
+            return_state_diff,
+            spec,
+            cancellation,
+        } = settings;
+
+        // the block overrides apply to all transactions of the bundle, but not to the following
+        // bundles
+        if let Some(mut block_overrides) = block_override {
+            if let Some(block_hashes) = block_overrides.block_hash.take() {
+                apply_block_hash_overrides(block_hashes, cfg.handler_cfg.spec_id, db)?;
+            }
+            apply_block_overrides(block_overrides, &mut block_env);
+        }
+
+        let mut results = Vec::with_capacity(transactions.len());
+        let mut changes = return_state_diff.then(BundleChanges::default);
+        let mut transactions = transactions.into_iter().peekable();
+        while let Some(tx) = transactions.next() {
+            cancellation.check()?;
+            let (env, tx_context) = match tx {
+                BundleCall::Request(request) => {
+                    let overrides = EvmOverrides::new(state_overrides.take(), None);
+                    let env = self.eth_api().prepare_call_env(
+                        cfg.clone(),
+                        block_env.clone(),
+                        request,
+                        *gas_limit,
+                        db,
+                        overrides,
+                    )?;
+                    (env, None)
+                }
+                BundleCall::Signed(tx) => {
+                    // signed transactions are executed with their own fields, only the state
+                    // overrides apply
+                    if let Some(state_overrides) = state_overrides.take() {
+                        apply_state_overrides(state_overrides, db)?;
+                    }
+                    let env = EnvWithHandlerCfg {
+                        env: Env::boxed(
+                            cfg.cfg_env.clone(),
+                            block_env.clone(),
+                            Call::evm_config(self.eth_api()).tx_env(&tx),
+                        ),
+                        handler_cfg: cfg.handler_cfg,
+                    };
+                    let tx_context = TransactionContext {
+                        block_hash: None,
+                        tx_index: None,
+                        tx_hash: Some(tx.hash),
+                    };
+                    (env, Some(tx_context))
+                }
+            };
+            ensure_authorization_list_supported(&env).map_err(Eth::Error::from_eth_err)?;
+
+            let (trace, state, _) = self.trace_transaction(tracing_options, env, db, tx_context)?;
+
+            // If there is more transactions, commit the database
+            // The changes of the last transaction are needed for the state diff as well
+            if let Some(changes) = &mut changes {
+                changes.record(&state, db).map_err(|err| Eth::Error::from_eth_err(err.into()))?;
+                db.commit(state);
+            } else if transactions.peek().is_some() || commit_last {
+                db.commit(state);
+            }
+            results.push(trace);
+        }
+
+        let state_diff = changes
+            .map(|changes| changes.into_diff(db))
+            .transpose()
+            .map_err(|err| Eth::Error::from_eth_err(err.into()))?;
+        let response = if state_diff.is_none() && spec.is_none() {
+            results.into()
+        } else {
+            DebugTraceBundleResult { traces: results, state_diff, spec: spec.clone() }.into()
+        };
+        Ok(response)
+    }
+
+    /// Replays the given block and returns the trace of each transaction.
+    ///
+    /// This expects a rlp encoded block
+    ///
+    /// The block is traced on top of the given parent block, or by default on top of the parent
+    /// referenced by its header. The parent and its state must be present, or it will fail.
+    pub async fn debug_trace_raw_block(
+        &self,
+        rlp_block: Bytes,
+        opts: DebugTracingBlockOptions,
+        parent: Option<BlockId>,
+    ) -> Result<Vec<TraceResult>, Eth::Error> {
+        let results = self.debug_trace_raw_block_with_stats(rlp_block, opts, parent).await?;
+        Ok(without_stats(results))
+    }
+
+    /// Replays the given rlp encoded block like [`Self::debug_trace_raw_block`], with the
+    /// statistics of the verbose mode, see [`Self::trace_block_with_stats`].
+    pub async fn debug_trace_raw_block_with_stats(
+        &self,
+        rlp_block: Bytes,
+        opts: DebugTracingBlockOptions,
+        parent: Option<BlockId>,
+    ) -> Result<Vec<TraceResultWithStats>, Eth::Error> {
+        let page = self.debug_trace_raw_block_page(rlp_block, opts, parent).await?;
+        Ok(page.results)
+    }
+
+    /// Replays the given rlp encoded block like [`Self::debug_trace_raw_block_with_stats`], and
+    /// returns the results as a page, see [`Self::trace_block_page`].
+    pub async fn debug_trace_raw_block_page(
+        &self,
+        rlp_block: Bytes,
+        opts: DebugTracingBlockOptions,
+        parent: Option<BlockId>,
+    ) -> Result<BlockTracePage, Eth::Error> {
+        self.check_tracer(&opts.tracing_options)?;
+        let block = decode_raw_block(&rlp_block)
+            .map_err(|err| DebugApiError::InvalidRawBlock { reason: err.to_string() })
+            .map_err(Eth::Error::from_eth_err)?;
+
+        // we trace on top the block's parent block, unless another one is requested
+        let parent = match parent {
+            Some(parent) => self
+                .resolve_block_hash(parent)?
+                .ok_or(DebugApiError::BlockNotFound(parent))
+                .map_err(Eth::Error::from_eth_err)?,
+            None => block.parent_hash,
+        };
+        // check this up front, instead of failing with a generic error once the block is replayed
+        let Some(parent_header) =
+            self.inner.provider.header(&parent).map_err(Eth::Error::from_eth_err)?
+        else {
+            return Err(EthApiError::ParentBlockNotFound(parent).into())
+        };
+        self.ensure_state_available(parent_header.number)?;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(parent)
+            .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+        let state = self.with_cached_reads(state, parent);
+
+        let (cfg, block_env) = self.evm_env_on_parent(&block.header, parent).await?;
+        let senders = self.recover_senders(&block)?;
+
+        // the states of the block on top of another parent must not be cached for the block
+        let this = if parent == block.parent_hash {
+            self.clone()
+        } else {
+            Self { skip_state_snapshots: true, ..self.clone() }
+        };
+        let block = BlockWithSenders { block, senders };
+        this.trace_block_page(block, state, cfg, block_env, opts).await
+    }
+
+    /// Replays a block and returns the trace of each transaction.
+    pub async fn debug_trace_block(
+        &self,
+        block_id: BlockId,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<Vec<TraceResult>, Eth::Error> {
+        let results = self.debug_trace_block_with_stats(block_id, opts).await?;
+        Ok(without_stats(results))
+    }
+
+    /// Replays a block like [`Self::debug_trace_block`], with the statistics of the verbose mode,
+    /// see [`Self::trace_block_with_stats`].
+    ///
+    /// Verbose traces are neither served from nor inserted into the trace cache, since the
+    /// statistics are only meaningful for a fresh trace.
+    pub async fn debug_trace_block_with_stats(
+        &self,
+        block_id: BlockId,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<Vec<TraceResultWithStats>, Eth::Error> {
+        Ok(self.debug_trace_block_page(block_id, opts).await?.results)
+    }
+
+    /// Replays a block like [`Self::debug_trace_block_with_stats`], and returns the results as a
+    /// page, see [`Self::trace_block_page`].
+    ///
+    /// Paginated traces are neither served from nor inserted into the trace cache.
+    pub async fn debug_trace_block_page(
+        &self,
+        block_id: BlockId,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<BlockTracePage, Eth::Error> {
+        self.check_tracer(&opts.tracing_options)?;
+        let block_hash = self
+            .resolve_block_hash(block_id)?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+
+        let ((cfg, block_env, _), block) = futures::try_join!(
+            self.inner.eth_api.evm_env_at(block_hash.into()),
+            self.inner.eth_api.block_with_senders(block_hash.into()),
+        )?;
+
+        let block = block
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+
+        // the stats of a trace are specific to its execution
+        let cache = self.inner.caches.trace_cache.as_ref().filter(|_| {
+            !opts.no_cache &&
+                !opts.verbose &&
+                !opts.include_execution_stats &&
+                !opts.include_stats &&
+                !opts.is_paginated()
+        });
+        let cache_key = cache.and_then(|_| {
+            let tracing_options = self.apply_default_tracer(opts.tracing_options.clone());
+            TraceCache::key(block_hash, &tracing_options, &opts.tx_hashes, opts.include_post_block)
+        });
+        if let Some((cache, key)) = cache.zip(cache_key.as_ref()) {
+            if let Some(traces) = cache.get(key) {
+                let results = traces.results.iter().cloned().map(Into::into).collect();
+                let post_block = traces.post_block.clone();
+                return Ok(BlockTracePage { results, next_cursor: None, post_block })
+            }
+        }
+
+        // we need to get the state of the parent block because we're replaying this block on top of
+        // its parent block's state
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(block.parent_hash)
+            .map_err(Eth::Error::from_eth_err)?;
+        let state = self.with_cached_reads(state, block.parent_hash);
+
+        // cached traces are sized by the memory they reserve while they're traced, so traces that
+        // aren't accounted against the memory budget of a request get a reservation of their own
+        let this = if cache_key.is_some() && self.trace_memory.is_none() {
+            let trace_memory = self.inner.trace_memory_budget.reservation();
+            Self { trace_memory: Some(trace_memory), ..self.clone() }
+        } else {
+            self.clone()
+        };
+        let reserved = this.trace_memory.as_ref().map_or(0, TraceMemory::reserved);
+        let page = this.trace_block_page(block.unseal(), state, cfg, block_env, opts).await?;
+
+        // errors may be transient, so only complete traces are cached
+        if let Some((cache, key)) = cache.zip(cache_key) {
+            if page.results.iter().all(|res| matches!(res.result, TraceResult::Success { .. })) {
+                let results = page.results.iter().map(|res| res.result.clone()).collect::<Vec<_>>();
+                let size = this.trace_memory.as_ref().map_or(0, TraceMemory::reserved) - reserved +
+                    results.len() * size_of::<TraceResult>();
+                let traces = BlockTraces { results, post_block: page.post_block.clone() };
+                cache.insert(key, traces, size);
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// Traces the block with the given id with the given transactions replaced or dropped, on top
+    /// of the state of its parent.
+    ///
+    /// Replacements are executed like [`Self::debug_trace_call`], from the sender of the
+    /// transaction they replace. The state changes of every executed transaction are committed,
+    /// so the following transactions are executed on top of the modified state. Transactions that
+    /// fail, e.g. because a preceding transaction of the same sender was dropped, don't change the
+    /// state and are reported as an error result.
+    ///
+    /// Fails with [`EthApiError::InvalidParams`] before executing anything if a modification
+    /// refers to an index beyond the transactions of the block, or to the same index as another
+    /// modification, or if a replacement is sent by another account.
+    pub async fn debug_trace_block_with_modifications(
+        &self,
+        block_id: BlockId,
+        modifications: Vec<TransactionModification>,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<ModifiedTraceResult>, Eth::Error> {
+        self.check_tracer(&opts)?;
+        let opts = self.apply_default_tracer(opts);
+        let gas_limit =
+            self.call_gas_cap(modifications.iter().filter_map(|m| m.replacement.as_ref()))?;
+        let block_hash = self
+            .resolve_block_hash(block_id)?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        let (block, cfg, block_env) = self
+            .block_and_env_by_hash(block_hash)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        let mut modifications = modifications_by_index(modifications, &block.senders)?;
+        self.check_block_gas(&opts, block.gas_used, false)?;
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let parent = block.parent_hash;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(parent)
+            .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Block);
+            let eth_api = this.eth_api();
+            let mut db =
+                CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(&*state)));
+            let mut results = Vec::with_capacity(block.body.len());
+            for (index, tx) in block.into_transactions_ecrecovered().enumerate() {
+                cancellation.check()?;
+                let tx_hash = tx.hash;
+                let (env, tx_context, modification) = match modifications.remove(&index) {
+                    None => {
+                        let env = trace::block_tx_env(eth_api, &cfg, &block_env, &tx);
+                        let tx_context = TransactionContext {
+                            block_hash: Some(block_hash),
+                            tx_hash: Some(tx_hash),
+                            tx_index: Some(index),
+                        };
+                        (env, tx_context, None)
+                    }
+                    Some(None) => {
+                        results.push(ModifiedTraceResult {
+                            tx_hash,
+                            modification: Some(TransactionModificationKind::Dropped),
+                            result: None,
+                            error: None,
+                        });
+                        continue
+                    }
+                    Some(Some(request)) => {
+                        let env = eth_api.prepare_call_env(
+                            cfg.clone(),
+                            block_env.clone(),
+                            request,
+                            gas_limit,
+                            &mut db,
+                            Default::default(),
+                        )?;
+                        ensure_authorization_list_supported(&env)
+                            .map_err(Eth::Error::from_eth_err)?;
+                        let tx_context = TransactionContext {
+                            block_hash: Some(block_hash),
+                            tx_hash: None,
+                            tx_index: Some(index),
+                        };
+                        (env, tx_context, Some(TransactionModificationKind::Replaced))
+                    }
+                };
+
+                let (result, error) =
+                    match this.trace_transaction(&opts, env, &mut db, Some(tx_context)) {
+                        Ok((trace, state, _)) => {
+                            db.commit(state);
+                            (Some(trace), None)
+                        }
+                        Err(err)
+                            if matches!(
+                                err.as_err(),
+                                Some(EthApiError::Debug(
+                                    DebugApiError::TraceMemoryBudgetExceeded { .. }
+                                ))
+                            ) =>
+                        {
+                            return Err(err)
+                        }
+                        Err(err) => (None, Some(err.to_string())),
+                    };
+                results.push(ModifiedTraceResult { tx_hash, modification, result, error });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Trace the transaction according to the provided options.
+    ///
+    /// If enabled, a transaction that is only in the pool is traced speculatively, in the
+    /// environment of the pending block on top of the latest state, see
+    /// [`Self::with_trace_pool_transactions`]. If it's rejected there, its error is returned.
+    ///
+    /// Ref: <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
+    pub async fn debug_trace_transaction(
+        &self,
+        tx_hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<GethTrace, Eth::Error> {
+        self.debug_trace_transaction_with_state_changes(tx_hash, opts.into())
+            .await?
+            .into_trace()
+            .map_err(|err| Eth::Error::from_eth_err(EthApiError::EvmCustom(err)))
+    }
+
+    /// Traces the transaction like [`Self::debug_trace_transaction`], and returns its state
+    /// changes alongside the trace if requested.
+    pub async fn debug_trace_transaction_with_state_changes(
+        &self,
+        tx_hash: B256,
+        opts: DebugTracingTransactionOptions,
+    ) -> Result<DebugTraceTransactionResponse, Eth::Error> {
+        self.check_tracer(&opts.tracing_options)?;
+        let (_, block) = match self.inner.eth_api.transaction_and_block(tx_hash).await? {
+            None if self.inner.trace_pool_transactions => {
+                return self.trace_pool_transaction(tx_hash, opts).await
+            }
+            None => {
+                return Err(Eth::Error::from_eth_err(DebugApiError::TransactionNotFound(tx_hash)))
+            }
+            Some(res) => res,
+        };
+        let (cfg, block_env, _) = self.inner.eth_api.evm_env_at(block.hash().into()).await?;
+
+        // we need to get the state of the parent block because we're essentially replaying the
+        // block the transaction is included in
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(block.parent_hash)
+            .map_err(Eth::Error::from_eth_err)?;
+        let state = self.with_cached_reads(state, block.parent_hash);
+        let block_hash = block.hash();
+        let block_txs = block.into_transactions_ecrecovered().collect();
+
+        self.trace_block_transaction(tx_hash, block_hash, block_txs, state, cfg, block_env, opts)
+            .await
+    }
+
+    /// Traces the transaction with the given hash in the block with the given hash, regardless of
+    /// whether the block is canonical, e.g. after it was reorged out.
+    ///
+    /// The block is looked up in the database and the blockchain tree, and the transaction is
+    /// traced on top of the state of its parent block, which must be available.
+    pub async fn debug_trace_transaction_in_block(
+        &self,
+        tx_hash: B256,
+        block_hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<GethTrace, Eth::Error> {
+        self.check_tracer(&opts)?;
+        let (block, cfg, block_env) = self
+            .block_and_env_by_hash(block_hash)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_hash.into()))
+            .map_err(Eth::Error::from_eth_err)?;
+        if !block.body.iter().any(|tx| tx.hash == tx_hash) {
+            return Err(EthApiError::TransactionNotInBlock(tx_hash, block_hash).into())
+        }
+
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let parent = block.parent_hash;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(parent)
+            .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+        let state = self.with_cached_reads(state, parent);
+        let block_txs = block.into_transactions_ecrecovered().collect();
+        let opts = DebugTracingTransactionOptions::from(opts);
+
+        self.trace_block_transaction(tx_hash, block_hash, block_txs, state, cfg, block_env, opts)
+            .await?
+            .into_trace()
+            .map_err(|err| Eth::Error::from_eth_err(EthApiError::EvmCustom(err)))
+    }
+
+    /// Traces the transaction with the given hash of the given transactions of a block, on top of
+    /// the given state of the parent block, after replaying the preceding transactions.
+    #[allow(clippy::too_many_arguments)]
+    async fn trace_block_transaction(
+        &self,
+        tx_hash: B256,
+        block_hash: B256,
+        block_txs: Vec<TransactionSignedEcRecovered>,
+        state: impl StateProvider + 'static,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        opts: DebugTracingTransactionOptions,
+    ) -> Result<DebugTraceTransactionResponse, Eth::Error> {
+        let DebugTracingTransactionOptions { tracing_options, return_state_changes, include_stats } =
+            opts;
+        let opts = self.apply_default_tracer(tracing_options);
+        let target = block_txs
+            .iter()
+            .position(|block_tx| block_tx.hash == tx_hash)
+            .ok_or(EthApiError::TransactionNotInBlock(tx_hash, block_hash))?;
+        let tx = block_txs[target].clone();
+
+        let this = if include_stats { self.with_trace_counters() } else { self.clone() };
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Call);
+            let state = StateProviderTraitObjWrapper(&state);
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+
+            // resume from the closest cached state before the targeted transaction
+            let mut replayed = 0;
+            let snapshot_cache = this.scoped_state_snapshot_cache();
+            if let Some(cache) = snapshot_cache {
+                if let Some((index, snapshot)) = cache.get(block_hash, target) {
+                    snapshot.apply(&mut db);
+                    replayed = index;
+                }
+            }
+
+            // replay all transactions prior to the targeted transaction
+            let index = replayed +
+                this.eth_api().replay_transactions_until(
+                    &mut db,
+                    cfg.clone(),
+                    block_env.clone(),
+                    block_txs.into_iter().skip(replayed),
+                    tx.hash,
+                )?;
+
+            if let Some(cache) = snapshot_cache {
+                if index > replayed {
+                    cache.insert(block_hash, index, snapshot::StateSnapshot::new(&db));
+                }
+            }
+
+            let env = EnvWithHandlerCfg {
+                env: Env::boxed(
+                    cfg.cfg_env.clone(),
+                    block_env,
+                    Call::evm_config(this.eth_api()).tx_env(&tx),
+                ),
+                handler_cfg: cfg.handler_cfg,
+            };
+
+            this.trace_transaction_response(
+                &opts,
+                env,
+                &mut db,
+                Some(TransactionContext {
+                    block_hash: Some(block_hash),
+                    tx_index: Some(index),
+                    tx_hash: Some(tx.hash),
+                }),
+                return_state_changes,
+            )
+        })
+        .await
+    }
+
+    /// Estimates the cost of tracing the block, or the transaction, with the given options
+    /// without tracing it, see [`Self::estimate_trace_cost`].
+    ///
+    /// Only the header and the receipts of the block are read. If the traces of an identical
+    /// request are in the trace cache, their size is returned as well. For a transaction, these
+    /// are the traces of the block that only select the transaction.
+    pub async fn debug_estimate_trace_cost(
+        &self,
+        target: TraceCostTarget,
+        opts: DebugTracingBlockOptions,
+    ) -> Result<TraceCostEstimate, Eth::Error> {
+        let provider = &self.inner.provider;
+        let (block_hash, tx) = match target {
+            TraceCostTarget::Block(block_id) => {
+                let block_hash = self
+                    .resolve_block_hash(block_id)?
+                    .ok_or(DebugApiError::BlockNotFound(block_id))
+                    .map_err(Eth::Error::from_eth_err)?;
+                (block_hash, None)
+            }
+            TraceCostTarget::Transaction(tx_hash) => {
+                let (_, meta) = provider
+                    .transaction_by_hash_with_meta(tx_hash)
+                    .map_err(Eth::Error::from_eth_err)?
+                    .ok_or(DebugApiError::TransactionNotFound(tx_hash))
+                    .map_err(Eth::Error::from_eth_err)?;
+                (meta.block_hash, Some((tx_hash, meta.index as usize)))
+            }
+        };
+        let header = provider
+            .header(&block_hash)
+            .map_err(Eth::Error::from_eth_err)?
+            .ok_or(DebugApiError::BlockNotFound(block_hash.into()))
+            .map_err(Eth::Error::from_eth_err)?;
+        let receipts = provider
+            .receipts_by_block(block_hash.into())
+            .map_err(Eth::Error::from_eth_err)?
+            .ok_or(EthApiError::ReceiptsNotFound(block_hash.into()))?;
+
+        let (mut estimate, tx_hashes, include_post_block) = match tx {
+            None => {
+                let estimate =
+                    self.estimate_trace_cost(&opts, header.gas_used, receipts.len() as u64, true);
+                (estimate, opts.tx_hashes.clone(), opts.include_post_block)
+            }
+            Some((tx_hash, index)) => {
+                let receipt =
+                    receipts.get(index).ok_or(EthApiError::ReceiptsNotFound(block_hash.into()))?;
+                let preceding =
+                    index.checked_sub(1).map_or(0, |index| receipts[index].cumulative_gas_used);
+                let gas_used = receipt.cumulative_gas_used.saturating_sub(preceding);
+                (self.estimate_trace_cost(&opts, gas_used, 1, false), vec![tx_hash], false)
+            }
+        };
+        estimate.recorded_size = self.inner.caches.trace_cache.as_ref().and_then(|cache| {
+            let tracing_options = self.apply_default_tracer(opts.tracing_options);
+            let key =
+                TraceCache::key(block_hash, &tracing_options, &tx_hashes, include_post_block)?;
+            cache.size(&key).map(|size| size as u64)
+        });
+        Ok(estimate)
+    }
+
+    /// Decodes and recovers the given raw signed transaction and traces it on top of the state of
+    /// the given block.
+    ///
+    /// The transaction is executed with its own nonce and fee fields, so that nonce and balance
+    /// validation behave like regular execution.
+    pub async fn debug_trace_raw_transaction(
+        &self,
+        rlp_tx: Bytes,
+        block_id: Option<BlockId>,
+        opts: GethDebugTracingOptions,
+    ) -> Result<GethTrace, Eth::Error> {
+        self.check_tracer(&opts)?;
+        let tx = recover_raw_signed_transaction(rlp_tx)
+            .map_err(|err| match err {
+                EthApiError::EmptyRawTransactionData |
+                EthApiError::FailedToDecodeSignedTransaction => {
+                    DebugApiError::InvalidRawTransaction.into()
+                }
+                err => err,
+            })
+            .map_err(Eth::Error::from_eth_err)?;
+        let opts = self.apply_default_tracer(opts);
+        let block_id = block_id.unwrap_or_default();
+        let (cfg, block_env, at) = self.inner.eth_api.evm_env_at(block_id).await?;
+        if !block_id.is_pending() {
+            self.ensure_state_available(block_env.number.saturating_to())?;
+        }
+
+        let this = self.clone();
+        self.spawn_with_state_at_block(at, move |state| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Call);
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+            let env = EnvWithHandlerCfg {
+                env: Env::boxed(
+                    cfg.cfg_env.clone(),
+                    block_env,
+                    Call::evm_config(this.eth_api()).tx_env(&tx),
+                ),
+                handler_cfg: cfg.handler_cfg,
+            };
+
+            this.trace_transaction(
+                &opts,
+                env,
+                &mut db,
+                Some(TransactionContext {
+                    block_hash: None,
+                    tx_index: None,
+                    tx_hash: Some(tx.hash),
+                }),
+            )
+            .map(|(trace, _, _)| trace)
+        })
+        .await
+    }
+
+    /// The `debug_traceCall` method lets you run an `eth_call` within the context of the given
+    /// block execution using the final state of parent block as the base.
+    ///
+    /// Differences compare to `eth_call`:
+    ///  - `debug_traceCall` executes with __enabled__ basefee check, `eth_call` does not: <https://github.com/paradigmxyz/reth/issues/6240>
+    ///
+    /// The basefee and balance checks can be relaxed for the call via the [`TraceCallChecks`] of
+    /// the options.
+    ///
+    /// If a transaction index is configured, the call is executed on top of the state after the
+    /// first `tx_index` transactions of the block. An index of `0` executes the call on the parent
+    /// state of the block, an index equal to the number of transactions in the block executes the
+    /// call on top of all transactions. Greater indexes are rejected.
+    ///
+    /// `pending` refers to the pending block of the node, including the transactions of a locally
+    /// built pending block. If requested, the pending block is built from the pool for the call
+    /// instead, and the hashes of the pool transactions the call is traced on top of are returned
+    /// alongside the trace.
+    pub async fn debug_trace_call(
+        &self,
+        call: TransactionRequest,
+        block_id: Option<BlockId>,
+        opts: DebugTracingCallOptions,
+    ) -> Result<DebugTraceCallResponse, Eth::Error> {
+        self.check_tracer(&opts.call_options.tracing_options)?;
+        let gas_limit = self.call_gas_cap([&call])?;
+        let DebugTracingCallOptions {
+            precompile_moves,
+            call_options,
+            tx_index,
+            build_pending_from_pool,
+            extras,
+            checks,
+            cfg_overrides,
+            // the syncing guard is applied by the RPC handler
+            allow_while_syncing: _,
+        } = opts;
+        let at = match block_id {
+            Some(block_id) if build_pending_from_pool && !block_id.is_pending() => {
+                return Err(EthApiError::InvalidParams(
+                    "buildPendingFromPool requires the pending block".to_string(),
+                )
+                .into())
+            }
+            Some(block_id) => block_id,
+            None if build_pending_from_pool => BlockId::pending(),
+            None => BlockId::default(),
+        };
+        let GethDebugTracingCallOptions { tracing_options, state_overrides, block_overrides } =
+            call_options;
+        let overrides = EvmOverrides::new(state_overrides, block_overrides.map(Box::new));
+
+        let tx_index = tx_index.map(|index| usize::try_from(index).unwrap_or(usize::MAX));
+        let CallContext { cfg, block_env, state, transactions } =
+            self.call_context(at, tx_index, build_pending_from_pool).await?;
+        let pool_transactions = build_pending_from_pool
+            .then(|| transactions.iter().map(|tx| tx.hash).collect::<Vec<_>>());
+
+        // the cfg overrides only apply to the call, not the replayed transactions
+        let mut call_cfg = cfg.clone();
+        let mut call_block_env = block_env.clone();
+        if let Some(cfg_overrides) = &cfg_overrides {
+            apply_cfg_overrides(cfg_overrides, &mut call_cfg, &mut call_block_env)
+                .map_err(Eth::Error::from_eth_err)?;
+        }
+        let spec = cfg_overrides.map(|_| spec_name(call_cfg.handler_cfg.spec_id));
+        let precompile_overrides = PrecompileOverrides::new(
+            overrides.state.as_ref(),
+            &precompile_moves,
+            call_cfg.handler_cfg.spec_id,
+        )
+        .map_err(Eth::Error::from_eth_err)?;
+
+        let this =
+            if extras.include_execution_stats { self.with_execution_stats() } else { self.clone() };
+        let this = if extras.include_stats { this.with_trace_counters() } else { this };
+        let this = this.with_precompile_overrides(precompile_overrides);
+        self.spawn_with_call_state(state, move |state| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Call);
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+
+            for tx in transactions {
+                let env = EnvWithHandlerCfg {
+                    env: Env::boxed(
+                        cfg.cfg_env.clone(),
+                        block_env.clone(),
+                        Call::evm_config(this.eth_api()).tx_env(&tx),
+                    ),
+                    handler_cfg: cfg.handler_cfg,
+                };
+                let (res, _) = this.inner.eth_api.transact(this.stats_db(&mut db), env)?;
+                db.commit(res.state);
+            }
+
+            let mut cfg = call_cfg;
+            apply_call_checks(checks, &mut cfg);
+
+            // the request is needed again if the gas limit of the call should be estimated
+            let estimate_request =
+                (extras.estimate_gas && call.gas.is_none()).then(|| call.clone());
+
+            let mut env = this.eth_api().prepare_call_env(
+                cfg,
+                call_block_env,
+                call,
+                gas_limit,
+                &mut db,
+                overrides,
+            )?;
+            ensure_authorization_list_supported(&env).map_err(Eth::Error::from_eth_err)?;
+
+            if let Some(request) = estimate_request {
+                // estimate with the same environment and on the same state, including all
+                // overrides, the call is traced with
+                let cfg =
+                    CfgEnvWithHandlerCfg { cfg_env: env.cfg.clone(), handler_cfg: env.handler_cfg };
+                let gas = this.eth_api().estimate_gas_with_db(
+                    cfg,
+                    env.block.clone(),
+                    request,
+                    &mut db,
+                )?;
+                env.tx.gas_limit = gas.saturating_to();
+            }
+
+            let res = this.trace_call(tracing_options, extras, env, &mut db)?;
+            let execution_stats = this
+                .execution_stats
+                .as_ref()
+                .filter(|_| extras.include_execution_stats)
+                .map(|recorder| recorder.stats());
+            if pool_transactions.is_none() && execution_stats.is_none() && spec.is_none() {
+                return Ok(res)
+            }
+            let res = DebugTraceCallResult {
+                pool_transactions,
+                execution_stats,
+                spec,
+                ..res.into_result()
+            };
+            Ok(res.into())
+        })
+        .await
+    }
+
+    /// The `debug_traceCallMany` method lets you run an `eth_callMany` within the context of the
+    /// given block execution using the first n transactions in the given block as base.
+    ///
+    /// The transaction index of the state context is the number of transactions to replay, from
+    /// `0` up to the number of transactions in the block. An absent index or `-1` replays all
+    /// transactions, greater indexes are rejected.
+    ///
+    /// Each following bundle increments the block number by 1 and the block timestamp by the block
+    /// time of the chain, 12 seconds if it's unknown. The block number and timestamp overrides of a
+    /// bundle position it explicitly, the following bundles are positioned relative to it. A bundle
+    /// can't be positioned before its preceding bundle.
+    ///
+    /// `pending` refers to the pending block of the node, the bundles are traced on top of the
+    /// transactions of a locally built pending block as well.
+    ///
+    /// If requested, the net state changes each bundle committed are returned alongside its
+    /// traces.
+    ///
+    /// Independent bundles are each traced on a fork of the state after the replayed
+    /// transactions, at the given block unless overridden. They're spread across the trace
+    /// permits that are available without waiting, the results keep the order of the bundles.
+    pub async fn debug_trace_call_many(
+        &self,
+        bundles: Vec<DebugBundle>,
+        state_context: Option<StateContext>,
+        opts: Option<DebugTracingCallManyOptions>,
+    ) -> Result<Vec<DebugTraceBundleResponse>, Eth::Error> {
+        if bundles.is_empty() {
+            return Err(EthApiError::InvalidParams(String::from("bundles are empty.")).into())
+        }
+        let opts = opts.unwrap_or_default();
+        self.check_tracer(&opts.call_options.tracing_options)?;
+        let gas_limit = self.call_gas_cap(
+            bundles
+                .iter()
+                .flat_map(|bundle| bundle.transactions.iter())
+                .filter_map(BundleTransaction::as_request),
+        )?;
+
+        let StateContext { transaction_index, block_number } = state_context.unwrap_or_default();
+        // an absent index, like `-1`, positions the bundles after all transactions of the block
+        let num_txs = transaction_index.and_then(|index| index.index());
+
+        // we're essentially replaying the transactions in the block here, hence we need the state
+        // that points to the beginning of the block, unless all transactions are to be replayed
+        let target_block = block_number.unwrap_or_default();
+        let CallContext { cfg, block_env, state, transactions } =
+            self.call_context(target_block, num_txs, false).await?;
+
+        let DebugTracingCallManyOptions {
+            precompile_moves,
+            call_options,
+            checks,
+            state_overrides_per_bundle,
+            return_state_diff,
+            independent_bundles,
+            cfg_overrides,
+            // the syncing guard is applied by the RPC handler
+            allow_while_syncing: _,
+        } = opts;
+        let GethDebugTracingCallOptions { tracing_options, mut state_overrides, .. } = call_options;
+        let tracing_options = self.apply_default_tracer(tracing_options);
+
+        // the replayed transactions are executed with the original cfg and precompiles, the
+        // relaxed checks only apply to the traced calls as well
+        let mut call_cfg = cfg.clone();
+        let mut call_block_env = block_env.clone();
+        if let Some(cfg_overrides) = &cfg_overrides {
+            apply_cfg_overrides(cfg_overrides, &mut call_cfg, &mut call_block_env)
+                .map_err(Eth::Error::from_eth_err)?;
+        }
+        let spec = cfg_overrides.map(|_| spec_name(call_cfg.handler_cfg.spec_id));
+        apply_call_checks(checks, &mut call_cfg);
+        let precompile_overrides = PrecompileOverrides::new(
+            state_overrides.as_ref(),
+            &precompile_moves,
+            call_cfg.handler_cfg.spec_id,
+        )
+        .map_err(Eth::Error::from_eth_err)?;
+        // independent bundles are all positioned at the block, unless overridden
+        let positions = if independent_bundles {
+            Vec::new()
+        } else {
+            let block_time = block_time(self.inner.provider.chain_spec().chain());
+            bundle_positions(&bundles, &block_env, block_time)?
+        };
+        let bundles = self.decode_bundles(bundles, block_env.number.saturating_to())?;
+
+        // the request itself already holds a permit, independent bundles are spread across the
+        // available ones
+        let permits = if independent_bundles {
+            self.try_acquire_trace_permits(TraceClass::Call, bundles.len() - 1)
+        } else {
+            Vec::new()
+        };
+        let parallelism = permits.len() + 1;
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let settings = BundleTraceSettings {
+            cfg: call_cfg,
+            tracing_options,
+            gas_limit,
+            return_state_diff,
+            spec,
+            cancellation: cancel_on_drop.cancellation(),
+        };
+        let this = self.with_precompile_overrides(precompile_overrides);
+        self.spawn_with_call_state(state, move |state| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Call);
+            let _permits = permits;
+            let provider = state.0;
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+
+            // Execute all transactions until index
+            for tx in transactions {
+                settings.cancellation.check()?;
+                let env = EnvWithHandlerCfg {
+                    env: Env::boxed(
+                        cfg.cfg_env.clone(),
+                        block_env.clone(),
+                        Call::evm_config(this.eth_api()).tx_env(&tx),
+                    ),
+                    handler_cfg: cfg.handler_cfg,
+                };
+                let (res, _) = this.inner.eth_api.transact(this.stats_db(&mut db), env)?;
+                db.commit(res.state);
+            }
+
+            if independent_bundles {
+                // every bundle is traced on its own fork of the replayed state
+                let trace_bundle = |bundle| {
+                    this.trace_bundle(
+                        &mut fork_db(&db, provider),
+                        bundle,
+                        call_block_env.clone(),
+                        state_overrides.clone(),
+                        &settings,
+                        false,
+                    )
+                };
+                if parallelism == 1 {
+                    return bundles.into_iter().map(trace_bundle).collect()
+                }
+
+                let chunk_size = bundles.len().div_ceil(parallelism);
+                let mut chunks = Vec::with_capacity(parallelism);
+                let mut bundles = bundles.into_iter().peekable();
+                while bundles.peek().is_some() {
+                    chunks.push(bundles.by_ref().take(chunk_size).collect::<Vec<_>>());
+                }
+                return std::thread::scope(|scope| {
+                    let workers = chunks
+                        .into_iter()
+                        .map(|chunk| {
+                            let trace_bundle = &trace_bundle;
+                            scope.spawn(move || {
+                                chunk.into_iter().map(trace_bundle).collect::<Result<Vec<_>, _>>()
+                            })
+                        })
+                        .collect::<Vec<_>>();
+
+                    // join in order to keep the results ordered like the bundles
+                    let mut results = Vec::new();
+                    for worker in workers {
+                        results.extend(
+                            worker
+                                .join()
+                                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?,
+                        );
+                    }
+                    Ok(results)
+                })
+            }
+
+            // Trace all bundles
+            let mut all_bundles = Vec::with_capacity(bundles.len());
+            let mut bundles = bundles.into_iter().zip(positions).peekable();
+            while let Some((bundle, (number, timestamp))) = bundles.next() {
+                let mut bundle_block_env = call_block_env.clone();
+                bundle_block_env.number = number;
+                bundle_block_env.timestamp = timestamp;
+
+                // apply the state overrides before the first transaction of the first bundle, or
+                // of each bundle if requested
+                let bundle_state_overrides = if state_overrides_per_bundle {
+                    state_overrides.clone()
+                } else {
+                    state_overrides.take()
+                };
+
+                // If there are more bundles, commit the last transaction to the database too
+                let commit_last = bundles.peek().is_some();
+                all_bundles.push(this.trace_bundle(
+                    &mut db,
+                    bundle,
+                    bundle_block_env,
+                    bundle_state_overrides,
+                    &settings,
+                    commit_last,
+                )?);
+            }
+            Ok(all_bundles)
+        })
+        .await
+    }
+
+    /// Re-executes the block on top of the state of its parent, like
+    /// [`Self::debug_execution_witness`], and returns the accounts and storage slots accessed by
+    /// its transactions, system calls and withdrawals.
+    ///
+    /// The accesses are read from the state cached by the re-execution, and split by the changes
+    /// of the block, so no trie nodes are collected.
+    pub async fn debug_get_block_access_list(
+        &self,
+        block_id: BlockId,
+    ) -> Result<BlockAccessList, Eth::Error> {
+        let block_hash = self
+            .resolve_block_hash(block_id)?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        let (block, cfg, block_env) = self
+            .block_and_env_by_hash(block_hash)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let parent = block.parent_hash;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(parent)
+            .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Witness);
+            let mut db = StateBuilder::new()
+                .with_database(StateProviderDatabase::new(StateProviderTraitObjWrapper(&*state)))
+                .with_bundle_update()
+                .build();
+            this.execute_block_on(&mut db, block, &cfg, &block_env, false, &cancellation)?;
+            db.merge_transitions(BundleRetention::PlainState);
+            let bundle = db.take_bundle();
+            Ok(block_access_list(&db.cache.accounts, &bundle))
+        })
+        .await
+    }
+
+    /// Re-executes the block with the given id on top of the state of its parent, like
+    /// [`Self::debug_get_block_access_list`], and compares the results to the block.
+    ///
+    /// The receipts root is computed with the receipt encoding of the chain, see
+    /// [`Self::receipts_root`]. The accounts accessed by the re-execution are only compared to the
+    /// stored post-state of the block if the computed state root diverges from the header.
+    pub async fn debug_replay_block(
+        &self,
+        block_id: BlockId,
+    ) -> Result<BlockReplayReport, Eth::Error> {
+        let block_hash = self
+            .resolve_block_hash(block_id)?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        let (block, cfg, block_env) = self
+            .block_and_env_by_hash(block_hash)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let parent = block.parent_hash;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(parent)
+            .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Witness);
+            let header = block.header.clone();
+            let mut db = StateBuilder::new()
+                .with_database(StateProviderDatabase::new(StateProviderTraitObjWrapper(&*state)))
+                .with_bundle_update()
+                .build();
+            let (gas_used, receipts) =
+                this.execute_block_on(&mut db, block, &cfg, &block_env, true, &cancellation)?;
+            db.merge_transitions(BundleRetention::PlainState);
+            let bundle = db.take_bundle();
+            let state_root = state
+                .state_root(HashedPostState::from_bundle_state(&bundle.state))
+                .map_err(Eth::Error::from_eth_err)?;
+            this.replay_report(
+                block_hash,
+                &header,
+                &block_env,
+                state_root,
+                gas_used,
+                &receipts,
+                &db.cache.accounts,
+            )
+        })
+        .await
+    }
+
+    /// Computes the receipts root of the block with the given receipts, with the receipt encoding
+    /// of the chain of the `eth` API, see `LoadPendingBlock::receipts_root`.
+    ///
+    /// The encoding of the receipts differs between chains, e.g. the receipts of deposit
+    /// transactions on OP chains.
+    fn receipts_root(&self, block_env: &BlockEnv, block_number: u64, receipts: &[Receipt]) -> B256 {
+        let execution_outcome = ExecutionOutcome::new(
+            BundleState::default(),
+            Receipts::from(receipts.to_vec()),
+            block_number,
+            Vec::new(),
+        );
+        self.eth_api().receipts_root(block_env, &execution_outcome, block_number)
+    }
+
+    /// Compares the re-execution of the block with the given hash, its post-state root, gas used
+    /// and receipts, to the header of the block, and to the stored receipts and post-state of the
+    /// block, if the node has them.
+    #[allow(clippy::too_many_arguments)]
+    fn replay_report(
+        &self,
+        block_hash: B256,
+        header: &Header,
+        block_env: &BlockEnv,
+        state_root: B256,
+        gas_used: u64,
+        receipts: &[Receipt],
+        accounts: &HashMap<Address, CacheAccount>,
+    ) -> Result<BlockReplayReport, Eth::Error> {
+        let provider = &self.inner.provider;
+        let receipts_root = self.receipts_root(block_env, header.number, receipts);
+        let first_diverged_receipt = provider
+            .receipts_by_block(block_hash.into())
+            .map_err(Eth::Error::from_eth_err)?
+            .and_then(|stored| {
+                let mut preceding = (0, 0);
+                receipts.iter().zip(&stored).enumerate().find_map(|(index, (receipt, stored))| {
+                    let computed = replayed_receipt(receipt, preceding.0);
+                    let expected = replayed_receipt(stored, preceding.1);
+                    preceding = (receipt.cumulative_gas_used, stored.cumulative_gas_used);
+                    (computed != expected).then(|| ReceiptDivergence {
+                        transaction_index: index as u64,
+                        expected,
+                        computed,
+                    })
+                })
+            });
+        let diverged_accounts = if state_root == header.state_root {
+            Some(Vec::new())
+        } else {
+            match provider.state_by_block_hash(block_hash) {
+                Ok(post_state) => Some(
+                    diverged_accounts(&post_state, accounts).map_err(Eth::Error::from_eth_err)?,
+                ),
+                Err(_) => None,
+            }
+        };
+
+        Ok(BlockReplayReport {
+            matches: state_root == header.state_root &&
+                receipts_root == header.receipts_root &&
+                gas_used == header.gas_used &&
+                first_diverged_receipt.is_none(),
+            block_hash,
+            block_number: header.number,
+            expected_state_root: header.state_root,
+            computed_state_root: state_root,
+            expected_receipts_root: header.receipts_root,
+            computed_receipts_root: receipts_root,
+            expected_gas_used: header.gas_used,
+            computed_gas_used: gas_used,
+            first_diverged_receipt,
+            diverged_accounts,
+        })
+    }
+
+    /// Executes the block on the given database like the block executor: the pre-block system
+    /// calls, the transactions, and the post-block changes of the active hardforks. Block rewards
+    /// of pre-merge blocks are not applied.
+    ///
+    /// Returns the gas used by the block, and the receipts of its transactions if requested. Fails
+    /// with [`EthApiError::TracingCancelled`] before the next transaction once the cancellation is
+    /// cancelled.
+    fn execute_block_on<DB>(
+        &self,
+        db: &mut State<DB>,
+        block: BlockWithSenders,
+        cfg: &CfgEnvWithHandlerCfg,
+        block_env: &BlockEnv,
+        with_receipts: bool,
+        cancellation: &Cancellation,
+    ) -> Result<(u64, Vec<Receipt>), Eth::Error>
+    where
+        DB: Database,
+        DB::Error: fmt::Display,
+        EthApiError: From<DB::Error>,
+    {
+        let evm_config = Call::evm_config(self.eth_api()).clone();
+        let chain_spec = self.inner.provider.chain_spec();
+        let timestamp = block.timestamp;
+        let withdrawals =
+            block.withdrawals.clone().map(Withdrawals::into_inner).unwrap_or_default();
+
+        pre_block_beacon_root_contract_call(
+            db,
+            &evm_config,
+            chain_spec.as_ref(),
+            cfg,
+            block_env,
+            block.parent_beacon_block_root,
+        )
+        .map_err(|err| EthApiError::Internal(err.into()))?;
+
+        // apply eip-2935 blockhashes update
+        pre_block_blockhashes_contract_call(
+            db,
+            &evm_config,
+            chain_spec.as_ref(),
+            cfg,
+            block_env,
+            block.parent_hash,
+        )
+        .map_err(|err| EthApiError::Internal(err.into()))?;
+
+        let mut gas_used = 0;
+        let mut receipts = Vec::new();
+        for tx in block.into_transactions_ecrecovered() {
+            cancellation.check()?;
+            let env = EnvWithHandlerCfg {
+                env: Env::boxed(cfg.cfg_env.clone(), block_env.clone(), evm_config.tx_env(&tx)),
+                handler_cfg: cfg.handler_cfg,
+            };
+
+            let (res, _) = self.inner.eth_api.transact(&mut *db, env)?;
+            gas_used += res.result.gas_used();
+            if with_receipts {
+                #[allow(clippy::needless_update)]
+                let receipt = Receipt {
+                    tx_type: tx.tx_type(),
+                    success: res.result.is_success(),
+                    cumulative_gas_used: gas_used,
+                    logs: res.result.into_logs(),
+                    ..Default::default()
+                };
+                receipts.push(receipt);
+            }
+            db.commit(res.state);
+        }
+
+        if chain_spec.is_prague_active_at_timestamp(timestamp) {
+            post_block_withdrawal_requests_contract_call(&evm_config, db, cfg, block_env)
+                .map_err(|err| EthApiError::Internal(err.into()))?;
+            post_block_consolidation_requests_contract_call(&evm_config, db, cfg, block_env)
+                .map_err(|err| EthApiError::Internal(err.into()))?;
+        }
+        if chain_spec.is_shanghai_active_at_timestamp(timestamp) {
+            let credits = withdrawals
+                .iter()
+                .filter(|withdrawal| withdrawal.amount > 0)
+                .map(|withdrawal| (withdrawal.address, withdrawal.amount_wei().to::<u128>()));
+            db.increment_balances(credits).map_err(Eth::Error::from_eth_err)?;
+        }
+
+        Ok((gas_used, receipts))
+    }
+
+    /// Traces the call with the given environment and attaches the additional data requested by
+    /// the call options.
+    ///
+    /// The given database must hold the state the call is executed on, the state changes of the
+    /// call are not committed to it.
+    fn trace_call(
+        &self,
+        opts: GethDebugTracingOptions,
+        extras: TraceCallExtras,
+        env: EnvWithHandlerCfg,
+        db: &mut StateCacheDb<'_>,
+    ) -> Result<DebugTraceCallResponse, Eth::Error> {
+        let opts = self.apply_default_tracer(opts);
+        if extras.is_empty() {
+            return self.trace_transaction(&opts, env, db, None).map(|(trace, _, _)| trace.into());
+        }
+
+        let gas_limit = env.tx.gas_limit;
+        let caller = env.tx.caller;
+        let to = env.tx.transact_to.to().copied();
+        let coinbase = env.block.coinbase;
+        let spec_id = env.handler_cfg.spec_id;
+
+        let (trace, state) = if matches!(
+            opts.tracer,
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::NoopTracer))
+        ) {
+            // the noop tracer doesn't execute the call, but the state changes are still required
+            let (res, _) = trace::transact(
+                self.eth_api(),
+                self.stats_db(db),
+                env,
+                self.precompile_overrides.as_deref(),
+            )?;
+            (NoopFrame::default().into(), res.state)
+        } else {
+            let (trace, state, _) = self.trace_transaction(&opts, env, db, None)?;
+            (trace, state)
+        };
+
+        let mut res = DebugTraceCallResult::new(trace);
+        if extras.return_state_diff {
+            res.state_diff =
+                Some(state_diff(&state, db).map_err(|err| Eth::Error::from_eth_err(err.into()))?);
+        }
+        if extras.include_access_list {
+            // the sender, recipient, coinbase and precompiles are always warm
+            let warm = [caller, coinbase].into_iter().chain(to).chain(get_precompiles(spec_id));
+            res.access_list = Some(access_list(&state, warm.collect()));
+        }
+        if extras.estimate_gas {
+            res.gas_limit = Some(gas_limit);
+        }
+        if extras.with_proof {
+            let proofs = account_proofs(&state, &db.db).map_err(|err| match err {
+                ProviderError::StateAtBlockPruned(block) => self.state_pruned_error(block),
+                err => err.into(),
+            });
+            res.proofs = Some(proofs.map_err(Eth::Error::from_eth_err)?);
+        }
+        if extras.include_stats {
+            res.stats = self.trace_counters.as_ref().map(|recorder| recorder.counters());
+        }
+
+        Ok(res.into())
+    }
+
+    /// Checks that the tracer the given options end up with, once the default tracer is applied,
+    /// is allowed by the tracer policy.
+    fn check_tracer(&self, opts: &GethDebugTracingOptions) -> Result<(), EthApiError> {
+        if opts.tracer.is_none() && self.inner.default_tracer.is_some() {
+            return self.inner.tracer_policy.check(&self.apply_default_tracer(opts.clone()))
+        }
+        self.inner.tracer_policy.check(opts)
+    }
+
+    /// Checks that the gas used of a block doesn't exceed the limits of the tracers of the given
+    /// options, unless the trace is forced and the limits may be overridden.
+    ///
+    /// Fails with [`DebugApiError::BlockTooLargeForTracer`] otherwise.
+    fn check_block_gas(
+        &self,
+        opts: &GethDebugTracingOptions,
+        gas_used: u64,
+        force: bool,
+    ) -> Result<(), EthApiError> {
+        if self.inner.max_block_gas.is_empty() || (force && self.inner.allow_block_gas_override) {
+            return Ok(())
+        }
+        for kind in tracer_kinds(opts) {
+            if let Some(&limit) = self.inner.max_block_gas.get(&kind) {
+                if gas_used > limit {
+                    let tracer = kind.as_str();
+                    let err = DebugApiError::BlockTooLargeForTracer { tracer, limit, gas_used };
+                    return Err(err.into())
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Estimates the cost of tracing transactions with the given gas used with the given options,
+    /// and checks whether the trace would be rejected by the limits of the node.
+    ///
+    /// The number of struct logs is estimated from the gas used above the intrinsic gas of the
+    /// transactions, and their size like the memory budget of the traces does. The tracer policy
+    /// and the memory budget apply to all traces, the gas limits of the tracers only to the traces
+    /// of blocks, see [`Self::check_block_gas`].
+    fn estimate_trace_cost(
+        &self,
+        opts: &DebugTracingBlockOptions,
+        gas_used: u64,
+        transactions: u64,
+        block: bool,
+    ) -> TraceCostEstimate {
+        let estimated_struct_logs =
+            gas_used.saturating_sub(transactions * MIN_TRANSACTION_GAS) / GAS_PER_STEP;
+        let estimated_size = estimated_struct_logs.saturating_mul(STRUCT_LOG_SIZE as u64);
+        let rejected = self
+            .check_tracer(&opts.tracing_options)
+            .and_then(|()| {
+                let tracing_options = self.apply_default_tracer(opts.tracing_options.clone());
+                if block {
+                    self.check_block_gas(&tracing_options, gas_used, opts.force)?;
+                }
+                // only the memory of struct logs can be estimated without tracing
+                if tracing_options.tracer.is_none() &&
+                    estimated_size > self.inner.trace_memory_budget.limit() as u64
+                {
+                    return Err(DebugApiError::TraceMemoryBudgetExceeded {
+                        limit: self.inner.trace_memory_budget.limit(),
+                    }
+                    .into())
+                }
+                Ok(())
+            })
+            .err();
+        TraceCostEstimate {
+            gas_used,
+            transaction_count: transactions,
+            estimated_struct_logs,
+            estimated_size,
+            recorded_size: None,
+            would_be_rejected: rejected.is_some(),
+            reason: rejected.map(|err| err.to_string()),
+        }
+    }
+
+    /// Sets the configured default tracer if no tracer is specified in the given options.
+    fn apply_default_tracer(&self, mut opts: GethDebugTracingOptions) -> GethDebugTracingOptions {
+        if opts.tracer.is_none() {
+            if let Some((tracer, config)) = &self.inner.default_tracer {
+                opts.tracer = Some(tracer.clone());
+                if opts.tracer_config.0.is_null() {
+                    opts.tracer_config = config.clone();
+                }
+            }
+        }
+        opts
+    }
+}
+
+#[async_trait]
+impl<Provider, Eth> DebugApiServer for DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + StorageStatsReader
+        + 'static,
+    Eth: EthApiSpec + EthTransactions + TraceExt + 'static,
+{
+    /// Handler for `debug_getRawHeader`
+    async fn raw_header(&self, block_id: BlockId) -> RpcResult<Bytes> {
+        Self::raw_header(self, block_id).await
+    }
+
+    /// Handler for `debug_getRawHeaders`
+    async fn raw_headers(&self, start: BlockNumberOrTag, count: u64) -> RpcResult<Vec<Bytes>> {
+        Self::raw_headers(self, start, count).await
+    }
+
+    /// Handler for `debug_getRawBlock`
+    async fn raw_block(&self, block_id: BlockId) -> RpcResult<Bytes> {
+        Self::raw_block(self, block_id).await
+    }
+
+    /// Handler for `debug_getRawTransaction`
+    async fn raw_transaction(
+        &self,
+        hash: B256,
+        exclude_sidecar: Option<bool>,
+    ) -> RpcResult<Option<Bytes>> {
+        Self::raw_transaction(self, hash, exclude_sidecar).await
+    }
+
+    /// Handler for `debug_getRawTransactions`
+    async fn raw_transactions(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>> {
+        Self::raw_transactions(self, block_id).await
+    }
+
+    /// Handler for `debug_getRawReceipts`
+    async fn raw_receipts(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>> {
+        Self::raw_receipts(self, block_id).await
+    }
+
+    /// Handler for `debug_getBlobSidecars`
+    async fn raw_blob_sidecars(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>> {
+        Self::raw_blob_sidecars(self, block_id).await
+    }
+
+    /// Handler for `debug_getRawReceipt`
+    async fn raw_receipt(&self, hash: B256) -> RpcResult<Option<Bytes>> {
+        Self::raw_receipt(self, hash).await
+    }
+
+    /// Handler for `debug_getBadBlocks`
+    async fn bad_blocks(&self) -> RpcResult<Vec<RpcBlock>> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    /// Handler for `debug_traceChain`
+    async fn debug_trace_chain(
+        &self,
+        _start_exclusive: BlockNumberOrTag,
+        _end_inclusive: BlockNumberOrTag,
+    ) -> RpcResult<Vec<BlockTraceResult>> {
+        Err(internal_rpc_err("unimplemented"))
+    }
+
+    /// Handler for `debug_traceBlock`
+    async fn debug_trace_block(
+        &self,
+        ext: &Extensions,
+        rlp_block: Bytes,
+        opts: Option<DebugTracingBlockOptions>,
+        parent: Option<BlockId>,
+    ) -> RpcResult<DebugTraceBlockResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams {
+            block: parent,
+            tracer: self.audit_tracer(opts.as_ref().map(|opts| &opts.tracing_options)),
+            ..Default::default()
+        };
+        let metrics = &self.inner.metrics.trace_block;
+        let request = self.traced(ext, metrics, TraceClass::Block, |this| async move {
+            let opts = opts.unwrap_or_default();
+            let (format, verbose) = (opts.result_format, opts.verbose || opts.include_stats);
+            let include_execution_stats = opts.include_execution_stats;
+            let paginated = opts.is_paginated();
+            // the block is traced on top of another parent, but its context is its own
+            let context = opts.include_block_context.then(|| decode_raw_block(&rlp_block).ok());
+            let context = context.flatten().map(|block| {
+                block_trace_context(&block.header, block.header.hash_slow(), block.body.len())
+            });
+            let page = Self::debug_trace_raw_block_page(&this, rlp_block, opts, parent)
+                .await
+                .map_err(Into::into)?;
+            Ok(this.block_trace_response(
+                page,
+                paginated,
+                format,
+                verbose,
+                include_execution_stats,
+                context,
+            ))
+        });
+        self.audited(ext, "debug_traceBlock", params, request).await
+    }
+
+    /// Handler for `debug_traceBlockByHash`
+    async fn debug_trace_block_by_hash(
+        &self,
+        ext: &Extensions,
+        block: B256,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> RpcResult<DebugTraceBlockResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let opts = opts.unwrap_or_default();
+        let params = DebugAuditParams {
+            block: Some(block.into()),
+            tracer: self.audit_tracer(Some(&opts.tracing_options)),
+            ..Default::default()
+        };
+        let key = RequestKey::new("debug_traceBlockByHash", block, &opts);
+        let (api, request_ext) = (self.clone(), ext.clone());
+        let request = async move {
+            let metrics = &api.inner.metrics.trace_block_by_hash;
+            api.traced(&request_ext, metrics, TraceClass::Block, |this| async move {
+                if !opts.allow_while_syncing {
+                    this.ensure_not_syncing(block.into())?;
+                }
+                let (format, verbose) = (opts.result_format, opts.verbose || opts.include_stats);
+                let include_execution_stats = opts.include_execution_stats;
+                let paginated = opts.is_paginated();
+                let context =
+                    this.block_trace_context(block.into(), &opts).await.map_err(Into::into)?;
+                let page = Self::debug_trace_block_page(&this, block.into(), opts)
+                    .await
+                    .map_err(Into::into)?;
+                Ok(this.block_trace_response(
+                    page,
+                    paginated,
+                    format,
+                    verbose,
+                    include_execution_stats,
+                    context,
+                ))
+            })
+            .await
+        };
+        let coalesced = &self.inner.metrics.trace_block_by_hash.coalesced_total;
+        let request = self.inner.in_flight_blocks.coalesce(key, coalesced, request);
+        self.audited(ext, "debug_traceBlockByHash", params, request).await
+    }
+
+    /// Handler for `debug_traceBlockByNumber`
+    async fn debug_trace_block_by_number(
+        &self,
+        ext: &Extensions,
+        block: BlockNumberOrTag,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> RpcResult<DebugTraceBlockResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams {
+            block: Some(block.into()),
+            tracer: self.audit_tracer(opts.as_ref().map(|opts| &opts.tracing_options)),
+            ..Default::default()
+        };
+        let request = self.traced(
+            ext,
+            &self.inner.metrics.trace_block_by_number,
+            TraceClass::Block,
+            |this| async move {
+                let opts = opts.unwrap_or_default();
+                if !opts.allow_while_syncing {
+                    this.ensure_not_syncing(block.into())?;
+                }
+                let (format, verbose) = (opts.result_format, opts.verbose || opts.include_stats);
+                let include_execution_stats = opts.include_execution_stats;
+                let paginated = opts.is_paginated();
+                // the block is traced by the hash of its context, in case the number is reorged
+                let context =
+                    this.block_trace_context(block.into(), &opts).await.map_err(Into::into)?;
+                let block_id = context.map_or(block.into(), |context| context.hash.into());
+                let page = Self::debug_trace_block_page(&this, block_id, opts)
+                    .await
+                    .map_err(Into::into)?;
+                Ok(this.block_trace_response(
+                    page,
+                    paginated,
+                    format,
+                    verbose,
+                    include_execution_stats,
+                    context,
+                ))
+            },
+        );
+        self.audited(ext, "debug_traceBlockByNumber", params, request).await
+    }
+
+    /// Handler for `debug_traceBlockWithModifications`
+    async fn debug_trace_block_with_modifications(
+        &self,
+        ext: &Extensions,
+        block: BlockId,
+        modifications: Vec<TransactionModification>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> RpcResult<Vec<ModifiedTraceResult>> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams {
+            block: Some(block),
+            tracer: self.audit_tracer(opts.as_ref()),
+            ..Default::default()
+        };
+        let request = self.traced(
+            ext,
+            &self.inner.metrics.trace_block_with_modifications,
+            TraceClass::Block,
+            |this| async move {
+                Self::debug_trace_block_with_modifications(
+                    &this,
+                    block,
+                    modifications,
+                    opts.unwrap_or_default(),
+                )
+                .await
+                .map_err(Into::into)
+            },
+        );
+        self.audited(ext, "debug_traceBlockWithModifications", params, request).await
+    }
+
+    /// Handler for `debug_traceTransaction`
+    async fn debug_trace_transaction(
+        &self,
+        ext: &Extensions,
+        tx_hash: B256,
+        opts: Option<DebugTracingTransactionOptions>,
+    ) -> RpcResult<DebugTraceTransactionResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let opts = opts.unwrap_or_default();
+        let params = DebugAuditParams {
+            tx_hash: Some(tx_hash),
+            tracer: self.audit_tracer(Some(&opts.tracing_options)),
+            ..Default::default()
+        };
+        let key = RequestKey::new("debug_traceTransaction", tx_hash, &opts);
+        let (api, request_ext) = (self.clone(), ext.clone());
+        let request = async move {
+            let metrics = &api.inner.metrics.trace_transaction;
+            api.traced(&request_ext, metrics, TraceClass::Call, |this| async move {
+                Self::debug_trace_transaction_with_state_changes(&this, tx_hash, opts)
+                    .await
+                    .map_err(Into::into)
+            })
+            .await
+        };
+        let coalesced = &self.inner.metrics.trace_transaction.coalesced_total;
+        let request = self.inner.in_flight_transactions.coalesce(key, coalesced, request);
+        self.audited(ext, "debug_traceTransaction", params, request).await
+    }
+
+    /// Handler for `debug_traceTransactionInBlock`
+    async fn debug_trace_transaction_in_block(
+        &self,
+        ext: &Extensions,
+        tx_hash: B256,
+        block_hash: B256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> RpcResult<GethTrace> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams {
+            block: Some(block_hash.into()),
+            tx_hash: Some(tx_hash),
+            tracer: self.audit_tracer(opts.as_ref()),
+        };
+        let request = self.traced(
+            ext,
+            &self.inner.metrics.trace_transaction_in_block,
+            TraceClass::Call,
+            |this| async move {
+                Self::debug_trace_transaction_in_block(
+                    &this,
+                    tx_hash,
+                    block_hash,
+                    opts.unwrap_or_default(),
+                )
+                .await
+                .map_err(Into::into)
+            },
+        );
+        self.audited(ext, "debug_traceTransactionInBlock", params, request).await
+    }
+
+    /// Handler for `debug_traceRawTransaction`
+    async fn debug_trace_raw_transaction(
+        &self,
+        ext: &Extensions,
+        rlp_tx: Bytes,
+        block_id: Option<BlockId>,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> RpcResult<GethTrace> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams {
+            block: block_id,
+            tx_hash: Some(keccak256(&rlp_tx)),
+            tracer: self.audit_tracer(opts.as_ref()),
+        };
+        let request = self.traced(
+            ext,
+            &self.inner.metrics.trace_raw_transaction,
+            TraceClass::Call,
+            |this| async move {
+                Self::debug_trace_raw_transaction(&this, rlp_tx, block_id, opts.unwrap_or_default())
+                    .await
+                    .map_err(Into::into)
+            },
+        );
+        self.audited(ext, "debug_traceRawTransaction", params, request).await
+    }
+
+    /// Handler for `debug_estimateTraceCost`
+    async fn debug_estimate_trace_cost(
+        &self,
+        target: TraceCostTarget,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> RpcResult<TraceCostEstimate> {
+        // nothing is traced, so the estimate doesn't wait for a tracing permit
+        Self::debug_estimate_trace_cost(self, target, opts.unwrap_or_default())
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Handler for `debug_executionWitness`
+    async fn debug_execution_witness(
+        &self,
+        ext: &Extensions,
+        block: BlockId,
+        include_preimages: bool,
+        opts: Option<ExecutionWitnessOptions>,
+    ) -> RpcResult<ExecutionWitnessResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams { block: Some(block), ..Default::default() };
+        let metrics = &self.inner.metrics.execution_witness;
+        let request = self.traced(ext, metrics, TraceClass::Witness, |this| async move {
+            let ExecutionWitnessOptions {
+                include_codes,
+                verify,
+                no_cache,
+                encoding,
+                verify_preimages,
+            } = opts.unwrap_or_default();
+            let witness = Self::debug_execution_witness(
+                &this,
+                block,
+                include_preimages,
+                include_codes,
+                verify,
+                verify_preimages,
+                no_cache,
+            )
+            .await
+            .map_err(Into::into)?;
+            Ok(witness
+                .encode(encoding)
+                .map_err(|err| EthApiError::Internal(RethError::other(err)))?)
+        });
+        self.audited(ext, "debug_executionWitness", params, request).await
+    }
+
+    /// Handler for `debug_executeWitness`
+    async fn debug_execute_witness(
+        &self,
+        ext: &Extensions,
+        rlp_block: Bytes,
+        witness: DebugExecutionWitness,
+    ) -> RpcResult<StatelessExecutionResult> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let metrics = &self.inner.metrics.execute_witness;
+        let request = self.traced(ext, metrics, TraceClass::Witness, |this| async move {
+            Self::debug_execute_witness(&this, rlp_block, witness).await.map_err(Into::into)
+        });
+        self.audited(ext, "debug_executeWitness", Default::default(), request).await
+    }
+
+    /// Handler for `debug_executionWitnessRaw`
+    async fn debug_execution_witness_raw(
+        &self,
+        ext: &Extensions,
+        rlp_block: Bytes,
+        include_preimages: bool,
+        opts: Option<ExecutionWitnessOptions>,
+    ) -> RpcResult<ExecutionWitnessResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let metrics = &self.inner.metrics.execution_witness_raw;
+        let request = self.traced(ext, metrics, TraceClass::Witness, |this| async move {
+            // raw blocks are never cached
+            let ExecutionWitnessOptions {
+                include_codes,
+                verify,
+                no_cache: _,
+                encoding,
+                verify_preimages,
+            } = opts.unwrap_or_default();
+            let witness = Self::debug_execution_witness_raw_block(
+                &this,
+                rlp_block,
+                include_preimages,
+                include_codes,
+                verify,
+                verify_preimages,
+            )
+            .await
+            .map_err(Into::into)?;
+            Ok(witness
+                .encode(encoding)
+                .map_err(|err| EthApiError::Internal(RethError::other(err)))?)
+        });
+        self.audited(ext, "debug_executionWitnessRaw", Default::default(), request).await
+    }
+
+    /// Handler for `debug_executionWitnessRange`
+    async fn debug_execution_witness_range(
+        &self,
+        ext: &Extensions,
+        start: BlockNumberOrTag,
+        end: BlockNumberOrTag,
+        include_preimages: bool,
+    ) -> RpcResult<Vec<ExecutionWitnessResult>> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        // the range is recorded by its end
+        let params = DebugAuditParams { block: Some(end.into()), ..Default::default() };
+        let request = self.traced(
+            ext,
+            &self.inner.metrics.execution_witness_range,
+            TraceClass::Witness,
+            |this| async move {
+                Self::debug_execution_witness_range(&this, start, end, include_preimages)
+                    .await
+                    .map_err(Into::into)
+            },
+        );
+        self.audited(ext, "debug_executionWitnessRange", params, request).await
+    }
+
+    /// Handler for `debug_getBlockAccessList`
+    async fn debug_get_block_access_list(
+        &self,
+        ext: &Extensions,
+        block: BlockId,
+    ) -> RpcResult<BlockAccessList> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams { block: Some(block), ..Default::default() };
+        let metrics = &self.inner.metrics.block_access_list;
+        let request = self.traced(ext, metrics, TraceClass::Witness, |this| async move {
+            Self::debug_get_block_access_list(&this, block).await.map_err(Into::into)
+        });
+        self.audited(ext, "debug_getBlockAccessList", params, request).await
+    }
+
+    /// Handler for `debug_replayBlock`
+    async fn debug_replay_block(
+        &self,
+        ext: &Extensions,
+        block: BlockId,
+    ) -> RpcResult<BlockReplayReport> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams { block: Some(block), ..Default::default() };
+        let metrics = &self.inner.metrics.replay_block;
+        let request = self.traced(ext, metrics, TraceClass::Witness, |this| async move {
+            Self::debug_replay_block(&this, block).await.map_err(Into::into)
+        });
+        self.audited(ext, "debug_replayBlock", params, request).await
+    }
+
+    /// Handler for `debug_subscribe`
+    async fn debug_subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        ext: &Extensions,
+        kind: DebugSubscriptionKind,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        Self::debug_subscribe(self, pending, ext, kind, opts).await
+    }
+
+    /// Handler for `debug_subscribeTraceBlock`
+    async fn debug_subscribe_trace_block(
+        &self,
+        pending: PendingSubscriptionSink,
+        ext: &Extensions,
+        block: BlockId,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        Self::debug_subscribe_trace_block(self, pending, ext, block, opts).await
+    }
+
+    /// Handler for `debug_traceCall`
+    async fn debug_trace_call(
+        &self,
+        ext: &Extensions,
+        request: TransactionRequest,
+        block_id: Option<BlockId>,
+        opts: Option<DebugTracingCallOptions>,
+    ) -> RpcResult<DebugTraceCallResponse> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        // neither the call nor the overrides are recorded
+        let params = DebugAuditParams {
+            block: block_id,
+            tracer: self.audit_tracer(opts.as_ref().map(|opts| &opts.call_options.tracing_options)),
+            ..Default::default()
+        };
+        let metrics = &self.inner.metrics.trace_call;
+        let trace = self.traced(ext, metrics, TraceClass::Call, |this| async move {
+            let opts = opts.unwrap_or_default();
+            if !opts.allow_while_syncing {
+                this.ensure_not_syncing(block_id.unwrap_or_default())?;
+            }
+            Self::debug_trace_call(&this, request, block_id, opts).await.map_err(Into::into)
+        });
+        self.audited(ext, "debug_traceCall", params, trace).await
+    }
+
+    async fn debug_trace_call_many(
+        &self,
+        ext: &Extensions,
+        bundles: Vec<DebugBundle>,
+        state_context: Option<StateContext>,
+        opts: Option<DebugTracingCallManyOptions>,
+    ) -> RpcResult<Vec<DebugTraceBundleResponse>> {
+        let _client = self.inner.client_limits.acquire(ext)?;
+        let params = DebugAuditParams {
+            block: state_context.as_ref().and_then(|context| context.block_number),
+            tracer: self.audit_tracer(opts.as_ref().map(|opts| &opts.call_options.tracing_options)),
+            ..Default::default()
+        };
+        let metrics = &self.inner.metrics.trace_call_many;
+        let request = self.traced(ext, metrics, TraceClass::Call, |this| async move {
+            if !opts.as_ref().is_some_and(|opts| opts.allow_while_syncing) {
+                let block_id = state_context.as_ref().and_then(|context| context.block_number);
+                this.ensure_not_syncing(block_id.unwrap_or_default())?;
+            }
+            Self::debug_trace_call_many(&this, bundles, state_context, opts)
+                .await
+                .map_err(Into::into)
+        });
+        self.audited(ext, "debug_traceCallMany", params, request).await
+    }
+
+    async fn debug_backtrace_at(&self, _location: &str) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_account_range(
+        &self,
+        _block_number: BlockNumberOrTag,
+        _start: Bytes,
+        _max_results: u64,
+        _nocode: bool,
+        _nostorage: bool,
+        _incompletes: bool,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_block_profile(&self, _file: String, _seconds: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_chaindb_compact(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_chaindb_property(&self, _property: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    /// Handler for `debug_codeByHash`
+    async fn debug_code_by_hash(
+        &self,
+        hash: B256,
+        _block_id: Option<BlockId>,
+    ) -> RpcResult<Option<Bytes>> {
+        // the empty bytecode isn't stored
+        if hash == KECCAK_EMPTY {
+            return Ok(Some(Bytes::new()))
+        }
+
+        let state = self.inner.provider.latest().to_rpc_result()?;
+        Ok(state.bytecode_by_hash(hash).to_rpc_result()?.map(|code| code.original_bytes()))
+    }
+
+    async fn debug_cpu_profile(&self, _file: String, _seconds: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_db_ancient(&self, _kind: String, _number: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_db_ancients(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_db_get(&self, _key: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    /// Handler for `debug_dbTables`
+    async fn debug_db_tables(&self) -> RpcResult<DbTables> {
+        Self::debug_db_tables(self).await
+    }
+
+    async fn debug_dump_block(&self, _number: BlockId) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_free_os_memory(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_freeze_client(&self, _node: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_gc_stats(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_get_accessible_state(
+        &self,
+        _from: BlockNumberOrTag,
+        _to: BlockNumberOrTag,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_get_modified_accounts_by_hash(
+        &self,
+        _start_hash: B256,
+        _end_hash: B256,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_get_modified_accounts_by_number(
+        &self,
+        _start_number: u64,
+        _end_number: u64,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_go_trace(&self, _file: String, _seconds: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_intermediate_roots(
+        &self,
+        _block_hash: B256,
+        _opts: Option<GethDebugTracingCallOptions>,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_mem_stats(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_mutex_profile(&self, _file: String, _nsec: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_preimage(&self, _hash: B256) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_print_block(&self, _number: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_seed_hash(&self, _number: u64) -> RpcResult<B256> {
+        Ok(Default::default())
+    }
+
+    async fn debug_set_block_profile_rate(&self, _rate: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_set_gc_percent(&self, _v: i32) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_set_head(&self, _number: u64) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_set_mutex_profile_fraction(&self, _rate: i32) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_set_trie_flush_interval(&self, interval: String) -> RpcResult<()> {
+        Ok(self.persistence_settings()?.set_flush_interval(&interval)?)
+    }
+
+    async fn debug_get_trie_flush_interval(&self) -> RpcResult<TrieFlushInterval> {
+        Ok(self.persistence_settings()?.trie_flush_interval())
+    }
+
+    async fn debug_stacks(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_standard_trace_bad_block_to_file(
+        &self,
+        _block: BlockNumberOrTag,
+        _opts: Option<GethDebugTracingCallOptions>,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_standard_trace_block_to_file(
+        &self,
+        _block: BlockNumberOrTag,
+        _opts: Option<GethDebugTracingCallOptions>,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_start_cpu_profile(&self, _file: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_start_go_trace(&self, _file: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_stop_cpu_profile(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_stop_go_trace(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_storage_range_at(
+        &self,
+        _block_hash: B256,
+        _tx_idx: usize,
+        _contract_address: Address,
+        _key_start: B256,
+        _max_result: u64,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_trace_bad_block(
+        &self,
+        _block_hash: B256,
+        _opts: Option<GethDebugTracingCallOptions>,
+    ) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_verbosity(&self, _level: usize) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_vmodule(&self, _pattern: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_write_block_profile(&self, _file: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_write_mem_profile(&self, _file: String) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn debug_write_mutex_profile(&self, _file: String) -> RpcResult<()> {
+        Ok(())
+    }
+}
+
+/// Builds the [`OpcodeFilter`] that enables all of the given opcodes, by name.
+fn opcode_filter(names: &[String]) -> Result<OpcodeFilter, EthApiError> {
+    names.iter().try_fold(OpcodeFilter::new(), |filter, name| {
+        (0..=u8::MAX)
+            .filter_map(OpCode::new)
+            .find(|opcode| opcode.as_str().eq_ignore_ascii_case(name))
+            .map(|opcode| filter.enabled(opcode))
+            .ok_or_else(|| EthApiError::InvalidParams(format!("unknown opcode {name}")))
+    })
+}
+
+/// The opcodes that access an account or storage slot and are therefore subject to
+/// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929) gas costs.
+const ACCESS_OPCODES: [OpCode; 11] = [
+    OpCode::SLOAD,
+    OpCode::SSTORE,
+    OpCode::BALANCE,
+    OpCode::EXTCODESIZE,
+    OpCode::EXTCODECOPY,
+    OpCode::EXTCODEHASH,
+    OpCode::CALL,
+    OpCode::CALLCODE,
+    OpCode::DELEGATECALL,
+    OpCode::STATICCALL,
+    OpCode::SELFDESTRUCT,
+];
+
+/// A [`TracingInspector`] that additionally records the access status of every recorded step that
+/// executes one of the [`ACCESS_OPCODES`].
+///
+/// The status is looked up in the journaled state before the step is executed, so accounts and
+/// slots that were pre-warmed via the access list of the transaction are reported as warm.
+#[derive(Debug)]
+struct AccessStatusInspector {
+    inner: TracingInspector,
+    /// The opcode filter of the inner inspector, steps that are filtered out are not recorded.
+    opcode_filter: Option<OpcodeFilter>,
+    /// The access status of each recorded step, in execution order.
+    ///
+    /// This is `None` for steps that fail before accessing anything, e.g. on stack underflow.
+    access_status: Vec<Option<AccessStatus>>,
+}
+
+impl AccessStatusInspector {
+    fn new(config: TracingInspectorConfig) -> Self {
+        Self {
+            opcode_filter: config.record_opcodes_filter,
+            inner: TracingInspector::new(config),
+            access_status: Vec::new(),
+        }
+    }
+
+    fn into_parts(self) -> (TracingInspector, Vec<Option<AccessStatus>>) {
+        (self.inner, self.access_status)
+    }
+
+    /// Records the access status of the step that is about to be executed, if it accesses an
+    /// account or storage slot.
+    fn record_access_status<DB: Database>(
+        &mut self,
+        interp: &Interpreter,
+        context: &EvmContext<DB>,
+    ) {
+        let Some(opcode) = OpCode::new(interp.current_opcode()) else { return };
+        if !ACCESS_OPCODES.contains(&opcode) ||
+            self.opcode_filter.as_ref().is_some_and(|filter| !filter.is_enabled(opcode))
+        {
+            return
+        }
+
+        let journal = &context.journaled_state;
+        let is_address_warm = |address: Address| {
+            journal.warm_preloaded_addresses.contains(&address) ||
+                journal
+                    .state
+                    .get(&address)
+                    .is_some_and(|account| !account.status.contains(AccountStatus::Cold))
+        };
+        let stack = interp.stack();
+
+        let status = match opcode {
+            OpCode::SLOAD | OpCode::SSTORE => stack.peek(0).ok().map(|slot| {
+                AccessStatus::new(
+                    journal
+                        .state
+                        .get(&interp.contract.target_address)
+                        .and_then(|account| account.storage.get(&slot))
+                        .is_some_and(|slot| !slot.is_cold),
+                )
+            }),
+            _ => {
+                // the call opcodes take the address as their second argument
+                let index = match opcode {
+                    OpCode::CALL | OpCode::CALLCODE | OpCode::DELEGATECALL | OpCode::STATICCALL => {
+                        1
+                    }
+                    _ => 0,
+                };
+                stack.peek(index).ok().map(|address| {
+                    AccessStatus::new(is_address_warm(Address::from_word(address.into())))
+                })
+            }
+        };
+
+        self.access_status.push(status);
+    }
+}
+
+impl<DB: Database> Inspector<DB> for AccessStatusInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.inner.initialize_interp(interp, context)
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.record_access_status(interp, context);
+        self.inner.step(interp, context)
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.inner.step_end(interp, context)
+    }
+
+    fn log(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>, log: &Log) {
+        self.inner.log(interp, context, log)
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.inner.call(context, inputs)
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.inner.call_end(context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.inner.create(context, inputs)
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.inner.create_end(context, inputs, outcome)
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        Inspector::<DB>::selfdestruct(&mut self.inner, contract, target, value)
+    }
+}
+
+/// Annotates the struct logs of the given frame with the recorded access status of the steps that
+/// execute one of the [`ACCESS_OPCODES`].
+fn annotate_access_status(
+    frame: DefaultFrame,
+    access_status: Vec<Option<AccessStatus>>,
+) -> Result<GethTrace, EthApiError> {
+    let mut access_status = access_status.into_iter();
+    let access = frame
+        .struct_logs
+        .iter()
+        .map(|log| {
+            let is_access = ACCESS_OPCODES.iter().any(|opcode| opcode.as_str() == log.op);
+            if is_access {
+                access_status.next().flatten()
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let frame = AccessStatusFrame::new(frame, access);
+    let value =
+        serde_json::to_value(frame).map_err(|err| EthApiError::Internal(RethError::other(err)))?;
+    Ok(GethTrace::JS(value))
+}
+
+/// Returns the context of the block with the given header, hash and number of transactions.
+const fn block_trace_context(
+    header: &Header,
+    hash: B256,
+    transaction_count: usize,
+) -> BlockTraceContext {
+    BlockTraceContext {
+        hash,
+        number: header.number,
+        timestamp: header.timestamp,
+        base_fee_per_gas: header.base_fee_per_gas,
+        gas_used: header.gas_used,
+        transaction_count: transaction_count as u64,
+    }
+}
+
+/// Drops the statistics of the given results.
+fn without_stats(results: Vec<TraceResultWithStats>) -> Vec<TraceResult> {
+    results.into_iter().map(|res| res.result).collect()
+}
+
+/// Creates a database on top of the given state that starts out with the cached state of the
+/// given database.
+fn fork_db<'a>(db: &StateCacheDb<'_>, state: &'a dyn StateProvider) -> StateCacheDb<'a> {
+    let mut fork = CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(state)));
+    fork.accounts.clone_from(&db.accounts);
+    fork.contracts.clone_from(&db.contracts);
+    fork.block_hashes.clone_from(&db.block_hashes);
+    fork
+}
+
+/// Folds the given call trace nodes into flamegraph stack lines, `frame;frame;frame gas`.
+///
+/// Frames are named `address:selector`, or just `address` if the call has no selector. The
+/// address is the one of the executed code, so delegate calls are attributed to the library that
+/// is called rather than to the calling contract. Each line is weighted by the gas consumed in the
+/// frame itself, excluding its subcalls, identical stacks are merged.
+fn folded_stacks(nodes: &[CallTraceNode]) -> Vec<String> {
+    // parents are always recorded before their children
+    let mut stacks: Vec<String> = Vec::with_capacity(nodes.len());
+    let mut folded = BTreeMap::<String, u64>::new();
+
+    for node in nodes {
+        let trace = &node.trace;
+        let frame = if matches!(trace.kind, CallKind::Create | CallKind::Create2) {
+            format!("{}:create", trace.address)
+        } else if trace.data.len() >= 4 {
+            format!("{}:{}", trace.address, hex::encode_prefixed(&trace.data[..4]))
+        } else {
+            trace.address.to_string()
+        };
+        let stack = match node.parent {
+            Some(parent) => format!("{};{frame}", stacks[parent]),
+            None => frame,
+        };
+
+        let subcalls_gas: u64 =
+            node.children.iter().map(|&child| nodes[child].trace.gas_used).sum();
+        *folded.entry(stack.clone()).or_default() += trace.gas_used.saturating_sub(subcalls_gas);
+        stacks.push(stack);
+    }
+
+    folded
+        .into_iter()
+        .filter(|(_, gas)| *gas > 0)
+        .map(|(stack, gas)| format!("{stack} {gas}"))
+        .collect()
+}
+
+/// Relaxes the validation of a traced call according to the given [`TraceCallChecks`].
+fn apply_call_checks(checks: TraceCallChecks, cfg: &mut CfgEnvWithHandlerCfg) {
+    if checks.disable_base_fee_check {
+        cfg.disable_base_fee = true;
+    }
+    if checks.disable_balance_check {
+        cfg.disable_balance_check = true;
+    }
+}
+
+/// Returns the block time of the chain in seconds, which the bundles of a `debug_traceCallMany`
+/// request are apart by default.
+///
+/// Falls back to the slot duration of Ethereum for chains without a known block time.
+fn block_time(chain: Chain) -> u64 {
+    chain
+        .named()
+        .and_then(|chain| chain.average_blocktime_hint())
+        .map_or(SLOT_DURATION.as_secs(), |block_time| block_time.as_secs().max(1))
+}
+
+/// Returns the block number and timestamp each of the bundles of a `debug_traceCallMany` request
+/// is executed at.
+///
+/// The first bundle is executed at the given block, each following bundle one block and
+/// `block_time` seconds after its predecessor, unless the block overrides of a bundle position it
+/// explicitly. Fails if a bundle is positioned before its predecessor.
+fn bundle_positions(
+    bundles: &[DebugBundle],
+    block_env: &BlockEnv,
+    block_time: u64,
+) -> Result<Vec<(U256, U256)>, EthApiError> {
+    let mut positions: Vec<(U256, U256)> = Vec::with_capacity(bundles.len());
+    for (index, bundle) in bundles.iter().enumerate() {
+        let previous = positions.last().copied();
+        let (mut number, mut timestamp) = previous
+            .map_or((block_env.number, block_env.timestamp), |(number, timestamp)| {
+                (number + U256::from(1), timestamp + U256::from(block_time))
+            });
+        if let Some(overrides) = &bundle.block_override {
+            number = overrides.number.unwrap_or(number);
+            timestamp = overrides.time.map_or(timestamp, U256::from);
+        }
+        if let Some((previous_number, previous_timestamp)) = previous {
+            if number < previous_number {
+                return Err(EthApiError::InvalidParams(format!(
+                    "block number {number} of bundle {index} is before block number \
+                     {previous_number} of the previous bundle"
+                )))
+            }
+            if timestamp < previous_timestamp {
+                return Err(EthApiError::InvalidParams(format!(
+                    "timestamp {timestamp} of bundle {index} is before timestamp \
+                     {previous_timestamp} of the previous bundle"
+                )))
+            }
+        }
+        positions.push((number, timestamp));
+    }
+    Ok(positions)
+}
+
+/// Ensures that a traced call that carries an [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702)
+/// authorization list is executed with a spec that supports it.
+///
+/// Delegated-code transactions can be simulated on chains that have not activated Prague yet by
+/// selecting its spec with the cfg overrides of the call.
+fn ensure_authorization_list_supported(env: &EnvWithHandlerCfg) -> Result<(), EthApiError> {
+    let spec_id = env.handler_cfg.spec_id;
+    if env.tx.authorization_list.is_some() && !spec_id.is_enabled_in(SpecId::PRAGUE) {
+        return Err(EthApiError::InvalidParams(format!(
+            "authorization lists require the Prague spec, not {}, see cfgOverrides",
+            spec_name(spec_id)
+        )))
+    }
+    Ok(())
+}
+
+/// Returns the replacements of the given modifications of the transactions of a block, with the
+/// given senders, keyed by the index of the transaction; `None` drops the transaction.
+///
+/// The replacements are sent by the sender of the transaction they replace. Fails if an index is
+/// beyond the transactions of the block or modified more than once, or if a replacement is sent
+/// by another account.
+fn modifications_by_index(
+    modifications: Vec<TransactionModification>,
+    senders: &[Address],
+) -> Result<HashMap<usize, Option<TransactionRequest>>, EthApiError> {
+    let mut by_index = HashMap::with_capacity(modifications.len());
+    for TransactionModification { index, replacement } in modifications {
+        let Some(&sender) = usize::try_from(index).ok().and_then(|index| senders.get(index)) else {
+            return Err(EthApiError::InvalidParams(format!(
+                "transaction index {index} out of range, the block has {} transactions",
+                senders.len()
+            )))
+        };
+        let replacement = replacement
+            .map(|mut request| {
+                if request.from.is_some_and(|from| from != sender) {
+                    return Err(EthApiError::InvalidParams(format!(
+                        "replacement of transaction {index} must be sent by {sender}"
+                    )))
+                }
+                request.from = Some(sender);
+                Ok(request)
+            })
+            .transpose()?;
+        if by_index.insert(index as usize, replacement).is_some() {
+            return Err(EthApiError::InvalidParams(format!(
+                "transaction {index} is modified more than once"
+            )))
+        }
+    }
+    Ok(by_index)
+}
+
+/// Computes the changes of the given post-execution state against the state of the database, in
+/// the shape of the prestate tracer's diff mode.
+///
+/// Accounts that were only read are not included. Accounts that did not exist before execution are
+/// omitted from the `pre` state, selfdestructed accounts from the `post` state.
+fn state_diff<DB: Database>(state: &EvmState, db: &mut DB) -> Result<DiffMode, DB::Error> {
+    let mut pre = BTreeMap::new();
+    let mut post = BTreeMap::new();
+
+    for (address, account) in state {
+        if !account.is_touched() {
+            continue
+        }
+
+        let (pre_storage, post_storage): (BTreeMap<_, _>, BTreeMap<_, _>) = account
+            .storage
+            .iter()
+            .filter(|(_, slot)| slot.is_changed())
+            .map(|(key, slot)| {
+                let key = B256::from(*key);
+                ((key, B256::from(slot.original_value)), (key, B256::from(slot.present_value)))
+            })
+            .unzip();
+
+        let pre_info = db.basic(*address)?;
+        let prev = pre_info.clone().unwrap_or_default();
+        let info = &account.info;
+
+        let changes = AccountState {
+            balance: (prev.balance != info.balance).then_some(info.balance),
+            nonce: (prev.nonce != info.nonce).then_some(info.nonce),
+            code: (prev.code_hash != info.code_hash)
+                .then(|| info.code.as_ref().map(|code| code.original_bytes()))
+                .flatten(),
+            storage: post_storage,
+        };
+        let is_changed = changes.balance.is_some() ||
+            changes.nonce.is_some() ||
+            changes.code.is_some() ||
+            !changes.storage.is_empty();
+        if !is_changed && !account.is_selfdestructed() {
+            continue
+        }
+
+        if let Some(info) = pre_info {
+            let code = if info.code_hash == KECCAK_EMPTY {
+                None
+            } else if let Some(code) = info.code {
+                Some(code.original_bytes())
+            } else {
+                Some(db.code_by_hash(info.code_hash)?.original_bytes())
+            };
+            pre.insert(
+                *address,
+                AccountState {
+                    balance: Some(info.balance),
+                    nonce: Some(info.nonce),
+                    code,
+                    storage: pre_storage,
+                },
+            );
+        }
+
+        if !account.is_selfdestructed() {
+            post.insert(*address, changes);
+        }
+    }
+
+    Ok(DiffMode { pre, post })
+}
+
+/// Returns the [`state_diff`] of the given post-execution state, along with the accounts that were
+/// created and selfdestructed.
+fn state_changes<DB: Database>(
+    state: &EvmState,
+    db: &mut DB,
+) -> Result<TransactionStateChanges, DB::Error> {
+    let diff = state_diff(state, db)?;
+    let touched = || state.iter().filter(|(_, account)| account.is_touched());
+    let created =
+        touched().filter(|(_, account)| account.is_created()).map(|(address, _)| *address);
+    let selfdestructed =
+        touched().filter(|(_, account)| account.is_selfdestructed()).map(|(address, _)| *address);
+    Ok(TransactionStateChanges {
+        diff,
+        created: created.collect(),
+        selfdestructed: selfdestructed.collect(),
+    })
+}
+
+/// Builds the access list of all accounts and storage slots accessed during execution, as recorded
+/// in the given post-execution state.
+///
+/// Accounts created during execution and the given warm accounts are only included if any of
+/// their storage slots were accessed.
+fn access_list(state: &EvmState, warm: HashSet<Address>) -> AccessList {
+    let accessed = state
+        .iter()
+        .filter(|(address, account)| {
+            !account.storage.is_empty() || !(account.is_created() || warm.contains(*address))
+        })
+        .map(|(address, account)| {
+            (*address, account.storage.keys().map(|key| B256::from(*key)).collect::<BTreeSet<_>>())
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    accessed
+        .into_iter()
+        .map(|(address, storage_keys)| AccessListItem {
+            address,
+            storage_keys: storage_keys.into_iter().collect(),
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Builds the access list of a re-executed block from the accounts and storage slots in the cache
+/// of its execution, in ascending order.
+///
+/// An account or slot counts as written if the changes of the block in `bundle` change it. Slots
+/// of destroyed accounts are no longer cached, they're taken from the changes instead.
+fn block_access_list(
+    accounts: &HashMap<Address, CacheAccount>,
+    bundle: &BundleState,
+) -> BlockAccessList {
+    let accounts = accounts
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(address, account)| {
+            let changes = bundle.state.get(address);
+            let written = changes.is_some_and(|changes| {
+                changes.info != changes.original_info || changes.status.was_destroyed()
+            });
+            let cached = account.account.iter().flat_map(|account| account.storage.keys());
+            let changed = changes.into_iter().flat_map(|changes| changes.storage.keys());
+            let (writes, reads): (Vec<_>, Vec<_>) =
+                cached.chain(changed).collect::<BTreeSet<_>>().into_iter().partition(|slot| {
+                    changes
+                        .and_then(|changes| changes.storage.get(*slot))
+                        .is_some_and(|slot| slot.is_changed())
+                });
+            AccountAccess {
+                address: *address,
+                written,
+                storage_reads: reads.into_iter().map(|slot| B256::from(*slot)).collect(),
+                storage_writes: writes.into_iter().map(|slot| B256::from(*slot)).collect(),
+            }
+        })
+        .collect();
+    BlockAccessList { accounts }
+}
+
+/// Returns the number of the earliest block whose state is available, or `None` if the history of
+/// the state isn't pruned.
+///
+/// The state at a historical block is served from the changesets of the blocks after it, so it's
+/// available unless the account or storage history was pruned beyond the block.
+fn earliest_available_state(
+    provider: &impl PruneCheckpointReader,
+) -> ProviderResult<Option<BlockNumber>> {
+    let mut earliest = None;
+    for segment in [PruneSegment::AccountHistory, PruneSegment::StorageHistory] {
+        let checkpoint = provider.get_prune_checkpoint(segment)?;
+        earliest = earliest.max(checkpoint.and_then(|checkpoint| checkpoint.block_number));
+    }
+    Ok(earliest)
+}
+
+/// Generates the merkle proofs of all accounts and storage slots accessed during execution, as
+/// recorded in the given post-execution state, against the state root of the given provider.
+fn account_proofs(
+    state: &EvmState,
+    provider: &impl StateProofProvider,
+) -> ProviderResult<Vec<EIP1186AccountProofResponse>> {
+    let accessed = state
+        .iter()
+        .map(|(address, account)| {
+            (*address, account.storage.keys().map(|key| B256::from(*key)).collect::<BTreeSet<_>>())
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    accessed
+        .into_iter()
+        .map(|(address, slots)| {
+            let slots = slots.into_iter().collect::<Vec<_>>();
+            provider.proof(Default::default(), address, &slots).map(from_primitive_account_proof)
+        })
+        .collect()
+}
+
+/// Returns the accounts of the cache whose account info or cached storage differs from the given
+/// post-state, in ascending order.
+///
+/// Empty accounts are treated like accounts that don't exist.
+fn diverged_accounts(
+    post_state: &dyn StateProvider,
+    accounts: &HashMap<Address, CacheAccount>,
+) -> ProviderResult<Vec<Address>> {
+    let empty = (0, U256::ZERO, KECCAK_EMPTY);
+    let mut diverged = Vec::new();
+    for (address, account) in accounts {
+        let computed = account
+            .account
+            .as_ref()
+            .map(|account| (account.info.nonce, account.info.balance, account.info.code_hash))
+            .filter(|info| *info != empty);
+        let stored = post_state
+            .basic_account(*address)?
+            .map(|account| (account.nonce, account.balance, account.get_bytecode_hash()))
+            .filter(|info| *info != empty);
+
+        let mut is_diverged = computed != stored;
+        if let Some(account) = account.account.as_ref().filter(|_| !is_diverged) {
+            for (slot, value) in &account.storage {
+                if post_state.storage(*address, B256::from(*slot))?.unwrap_or_default() != *value {
+                    is_diverged = true;
+                    break
+                }
+            }
+        }
+        if is_diverged {
+            diverged.push(*address);
+        }
+    }
+    diverged.sort_unstable();
+    Ok(diverged)
+}
+
+/// Returns the fields of the receipt that are compared by `debug_replayBlock`, given the gas used
+/// by the preceding transactions of the block.
+fn replayed_receipt(receipt: &Receipt, preceding_gas_used: u64) -> ReplayedReceipt {
+    ReplayedReceipt {
+        status: receipt.success,
+        gas_used: receipt.cumulative_gas_used.saturating_sub(preceding_gas_used),
+        cumulative_gas_used: receipt.cumulative_gas_used,
+        logs_bloom: receipt.bloom_slow(),
+    }
+}
+
+impl<Provider, Eth> std::fmt::Debug for DebugApi<Provider, Eth> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, Eth> Clone for DebugApi<Provider, Eth> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            trace_memory: self.trace_memory.clone(),
+            execution_stats: self.execution_stats.clone(),
+            trace_counters: self.trace_counters.clone(),
+            trace_progress: self.trace_progress.clone(),
+            precompile_overrides: self.precompile_overrides.clone(),
+            skip_state_snapshots: self.skip_state_snapshots,
+        }
+    }
+}
+
+/// Records the net state changes of a bundle of `debug_traceCallMany` across the commits of its
+/// transactions.
+#[derive(Debug, Default)]
+struct BundleChanges {
+    /// The states of the touched accounts before the bundle.
+    accounts: BTreeMap<Address, AccountBefore>,
+}
+
+/// The state of an account before a bundle.
+#[derive(Debug)]
+struct AccountBefore {
+    info: Option<AccountInfo>,
+    /// The values of the changed storage slots before the bundle.
+    storage: BTreeMap<U256, U256>,
+}
+
+impl BundleChanges {
+    /// Records the state before the given changes of a transaction, which are about to be
+    /// committed to the database.
+    ///
+    /// Only the first change of an account or slot is recorded, so that the recorded state is the
+    /// one before the first transaction of the bundle.
+    fn record<DB: Database>(&mut self, state: &EvmState, db: &mut DB) -> Result<(), DB::Error> {
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue
+            }
+            let before = match self.accounts.entry(*address) {
+                btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                btree_map::Entry::Vacant(entry) => entry
+                    .insert(AccountBefore { info: db.basic(*address)?, storage: BTreeMap::new() }),
+            };
+            for (key, slot) in account.storage.iter().filter(|(_, slot)| slot.is_changed()) {
+                before.storage.entry(*key).or_insert(slot.original_value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the net changes of the recorded accounts, by comparing their recorded states to
+    /// their states in the database.
+    ///
+    /// Accounts whose state is unchanged, e.g. because the bundle reverted its changes, are
+    /// omitted.
+    fn into_diff<DB: Database>(
+        self,
+        db: &mut DB,
+    ) -> Result<BTreeMap<Address, AccountDiff>, DB::Error> {
+        let mut diff = BTreeMap::new();
+        for (address, before) in self.accounts {
+            let pre = before.info.unwrap_or_default();
+            let post = db.basic(address)?.unwrap_or_default();
+
+            let mut changes = AccountDiff {
+                balance: ValueChange::new(pre.balance, post.balance),
+                nonce: ValueChange::new(pre.nonce, post.nonce),
+                ..Default::default()
+            };
+            if pre.code_hash != post.code_hash {
+                changes.code =
+                    Some(ValueChange { from: account_code(pre, db)?, to: account_code(post, db)? });
+            }
+            for (key, from) in before.storage {
+                let to = db.storage(address, key)?;
+                if let Some(change) = ValueChange::new(B256::from(from), B256::from(to)) {
+                    changes.storage.insert(key.into(), change);
+                }
+            }
+
+            if !changes.is_empty() {
+                diff.insert(address, changes);
+            }
+        }
+        Ok(diff)
+    }
+}
+
+/// Returns the code of the account, which is empty if it has none.
+fn account_code<DB: Database>(info: AccountInfo, db: &mut DB) -> Result<Bytes, DB::Error> {
+    if info.code_hash == KECCAK_EMPTY {
+        return Ok(Bytes::new())
+    }
+    let code = match info.code {
+        Some(code) => code,
+        None => db.code_by_hash(info.code_hash)?,
+    };
+    Ok(code.original_bytes())
+}
+
+/// The settings of the traced bundles of a `debug_traceCallMany` request.
+struct BundleTraceSettings {
+    /// The environment of the traced calls, with the relaxed checks applied.
+    cfg: CfgEnvWithHandlerCfg,
+    tracing_options: GethDebugTracingOptions,
+    /// The gas limit of calls that don't specify one.
+    gas_limit: u64,
+    /// Whether to return the net state changes of each bundle.
+    return_state_diff: bool,
+    /// The name of the spec the calls are executed with, returned if the cfg is overridden.
+    spec: Option<String>,
+    cancellation: Cancellation,
+}
+
+/// A transaction of a bundle of `debug_traceCallMany`, see [`BundleTransaction`].
+enum BundleCall {
+    /// A call, executed like `eth_call`.
+    Request(TransactionRequest),
+    /// A signed transaction, executed as is.
+    Signed(TransactionSignedEcRecovered),
+}
+
+/// The state traced calls are executed on.
+enum CallState {
+    /// The state after the block with the given id.
+    At(BlockId),
+    /// The in-memory state after the pending block.
+    Pending(StateProviderBox),
+}
+
+/// The environment and state of traced calls, see [`DebugApi::call_context`].
+struct CallContext {
+    cfg: CfgEnvWithHandlerCfg,
+    block_env: BlockEnv,
+    /// The state the transactions are replayed on, followed by the calls.
+    state: CallState,
+    /// The transactions of the block to replay before the calls.
+    transactions: Vec<TransactionSignedEcRecovered>,
+}
+
+struct DebugApiInner<Provider, Eth> {
+    /// The provider that can interact with the chain.
+    provider: Provider,
+    /// The implementation of `eth` API
+    eth_api: Eth,
+    // restrict the number of concurrent calls to blocking calls
+    blocking_task_guard: BlockingTaskGuard,
+    /// The metrics of the tracing requests.
+    metrics: DebugApiMetrics,
+    /// The memory budget of the traces of all in-flight requests.
+    trace_memory_budget: Arc<TraceMemoryBudget>,
+    /// The in-flight `debug_traceTransaction` requests, awaited by identical requests.
+    in_flight_transactions: InFlightRequests<DebugTraceTransactionResponse>,
+    /// The in-flight `debug_traceBlockByHash` requests, awaited by identical requests.
+    in_flight_blocks: InFlightRequests<DebugTraceBlockResponse>,
+    /// The accounting of the tracing requests of each client.
+    client_limits: ClientLimits,
+    /// The number of subscriptions of each connection.
+    subscriptions: ConnectionSubscriptions,
+    /// The tracer to use if none is specified in the tracing options.
+    default_tracer: Option<(GethDebugTracerType, GethDebugTracerConfig)>,
+    /// The tracers requests may use.
+    tracer_policy: TracerPolicy,
+    /// The maximum number of threads a block is traced on.
+    trace_block_parallelism: usize,
+    /// The enabled caches.
+    caches: DebugApiCaches,
+    /// The maximum number of blocks of a `debug_executionWitnessRange` request.
+    max_execution_witness_range: u64,
+    /// The maximum number of headers of a `debug_getRawHeaders` request.
+    max_raw_headers: u64,
+    /// The maximum number of subscriptions of a connection.
+    max_subscriptions_per_connection: u32,
+    /// The trace permits of the request classes that are limited separately.
+    trace_permits: TracePermits,
+    /// The dedicated pool the tracing work is executed on, instead of the tracing pool of the
+    /// `eth` API.
+    trace_pool: Option<BlockingTaskPool>,
+    /// The gas limit of traced calls, the gas cap of the `eth` API if unset.
+    gas_cap: Option<u64>,
+    /// Whether transactions that are only in the pool are traced speculatively.
+    trace_pool_transactions: bool,
+    /// Whether `debug_dbTables` is enabled.
+    db_tables: bool,
+    /// The maximum gas used of the blocks traced with the tracers of each kind.
+    max_block_gas: BTreeMap<TracerKind, u64>,
+    /// Whether requests may force block traces beyond the gas limits of their tracers.
+    allow_block_gas_override: bool,
+    /// The persistence settings of the node, as reported and adjusted by
+    /// `debug_getTrieFlushInterval` and `debug_setTrieFlushInterval`, if configured.
+    persistence_settings: Option<PersistenceSettingsHandle>,
+    /// The notifications of the canonical chain the subscriptions are served from, if
+    /// configured.
+    canonical_state_events: Option<Arc<dyn CanonStateSubscriptions>>,
+    /// The type that's used to spawn subscription tasks.
+    subscription_task_spawner: Box<dyn TaskSpawner>,
+    /// The sink the audit log of the tracing requests is recorded to, if enabled.
+    audit_sink: Option<Arc<dyn DebugAuditSink>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, TxKind};
+    use alloy_rlp::Decodable;
+    use jsonrpsee::{types::ErrorObjectOwned, ConnectionId};
+    use metrics_util::{
+        debugging::{DebugValue, DebuggingRecorder},
+        CompositeKey, MetricKind,
+    };
+    use reth_chainspec::{Chain, ChainHardforks, EthChainSpec, ForkCondition, Hardfork, Hardforks};
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_network_api::{noop::NoopNetwork, NetworkError, NetworkInfo, NetworkStatus};
+    use reth_primitives::{
+        constants::{
+            eip4844::{DATA_GAS_PER_BLOB, MAX_BLOBS_PER_BLOCK, VERSIONED_HASH_VERSION_KZG},
+            EMPTY_ROOT_HASH, ETHEREUM_BLOCK_GAS_LIMIT, GWEI_TO_WEI,
+        },
+        eip7702::{Authorization, SignedAuthorization},
+        public_key_to_address, sign_message,
+        static_file::SegmentRangeInclusive,
+        BlobTransactionSidecar, BlockNumHash, Signature, StaticFileSegment, Transaction, TxEip2930,
+        TxEip4844, TxLegacy, TxType,
+    };
+    use reth_provider::{
+        test_utils::{ExtendedAccount, MockEthProvider},
+        BlockHashReader, BlockReader, CanonStateNotification, TableStats,
+    };
+    use reth_prune_types::{PruneCheckpoint, PruneMode};
+    use reth_rpc_eth_types::{
+        DebugRpcErrorCode, EthStateCache, FeeHistoryCache, FeeHistoryCacheConfig, GasPriceOracle,
+        RpcInvalidTransactionError, HISTORICAL_STATE_UNAVAILABLE_CODE, RATE_LIMIT_EXCEEDED_CODE,
+    };
+    use reth_rpc_server_types::constants::{DEFAULT_ETH_PROOF_WINDOW, DEFAULT_PROOF_PERMITS};
+    use reth_rpc_types::{
+        debug::{
+            BlockRange, BlockTraceSummary, CfgOverrides, DbTableStats, DebugSubscriptionResult,
+            ExecutionStats, MissingWitnessKey, PendingTransactionTrace, PrecompileMoves,
+            StaticFileSegmentRanges, TraceBlockNotification, WitnessEncoding, WitnessVerification,
+            FLAMEGRAPH_TRACER,
+        },
+        error::EthRpcErrorCode,
+        state::{AccountOverride, StateOverride},
+        trace::geth::{CallFrame, PreStateFrame},
+        Stage, TransactionIndex, TransactionInput,
+    };
+    use reth_stages_types::{StageCheckpoint, StageId};
+    use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+    use reth_transaction_pool::{
+        test_utils::{testing_pool, MockTransaction, TestPool},
+        PoolTransaction, TransactionPool, TransactionPoolExt,
+    };
+    use std::net::SocketAddr;
+
+    use super::raw::encode_raw_block;
+    use crate::EthApi;
+
+    type TestEthApi = EthApi<MockEthProvider, TestPool, NoopNetwork, EthEvmConfig>;
+
+    fn mock_debug_api() -> DebugApi<MockEthProvider, TestEthApi> {
+        mock_debug_api_with_network(NoopNetwork::default())
+    }
+
+    fn mock_debug_api_with_network<Network>(
+        network: Network,
+    ) -> DebugApi<MockEthProvider, EthApi<MockEthProvider, TestPool, Network, EthEvmConfig>> {
+        let provider = MockEthProvider::default();
+        let header = Header { gas_limit: ETHEREUM_BLOCK_GAS_LIMIT, ..Default::default() };
+        provider.add_block(header.hash_slow(), Block { header, ..Default::default() });
+
+        let evm_config = EthEvmConfig::new(provider.chain_spec());
+        let cache = EthStateCache::spawn(provider.clone(), Default::default(), evm_config.clone());
+        let eth_api = EthApi::new(
+            provider.clone(),
+            testing_pool(),
+            network,
+            cache.clone(),
+            GasPriceOracle::new(provider.clone(), Default::default(), cache.clone()),
+            ETHEREUM_BLOCK_GAS_LIMIT,
+            DEFAULT_ETH_PROOF_WINDOW,
+            BlockingTaskPool::build().expect("failed to build tracing pool"),
+            FeeHistoryCache::new(cache, FeeHistoryCacheConfig::default()),
+            evm_config,
+            DEFAULT_PROOF_PERMITS,
+        );
+
+        DebugApi::new(provider, eth_api, BlockingTaskGuard::new(4))
+    }
+
+    #[tokio::test]
+    async fn trace_call_disable_balance_check() {
+        let debug_api = mock_debug_api();
+
+        // the sender has no balance
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(Address::random())),
+            value: Some(U256::from(u128::MAX)),
+            ..Default::default()
+        };
+
+        let res = debug_api.debug_trace_call(call.clone(), None, Default::default()).await;
+        assert!(matches!(
+            res,
+            Err(EthApiError::InvalidTransaction(
+                RpcInvalidTransactionError::InsufficientFunds { .. }
+            ))
+        ));
+
+        let mut opts = DebugTracingCallOptions::default();
+        opts.checks.disable_balance_check = true;
+        let res = debug_api.debug_trace_call(call, None, opts).await.unwrap();
+        let DebugTraceCallResponse::Trace(GethTrace::Default(frame)) = res else {
+            panic!("expected struct logs, got {res:?}")
+        };
+        assert!(!frame.failed);
+    }
+
+    #[tokio::test]
+    async fn trace_call_gas_cap() {
+        let debug_api = mock_debug_api();
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!("00"))),
+        );
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+
+        // falls back to the gas cap of the eth api
+        let above_eth_cap = TransactionRequest {
+            gas: Some(u128::from(ETHEREUM_BLOCK_GAS_LIMIT) + 1),
+            ..call.clone()
+        };
+        let err = debug_api.debug_trace_call(above_eth_cap, None, Default::default()).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg))
+        if msg == format!(
+            "requested gas {} exceeds the configured gas cap of {ETHEREUM_BLOCK_GAS_LIMIT}",
+            ETHEREUM_BLOCK_GAS_LIMIT + 1
+        )));
+
+        let debug_api = debug_api.with_gas_cap(100_000);
+        let above_cap = TransactionRequest { gas: Some(100_001), ..call.clone() };
+        let err = debug_api.debug_trace_call(above_cap.clone(), None, Default::default()).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg))
+            if msg == "requested gas 100001 exceeds the configured gas cap of 100000"));
+        let bundles = vec![DebugBundle {
+            transactions: vec![call.clone().into(), above_cap.into()],
+            ..Default::default()
+        }];
+        let err = debug_api.debug_trace_call_many(bundles, None, None).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(_))));
+
+        // calls without a gas limit are executed with the cap
+        let res = debug_api.debug_trace_call(call, None, Default::default()).await.unwrap();
+        let DebugTraceCallResponse::Trace(GethTrace::Default(frame)) = res else {
+            panic!("expected struct logs, got {res:?}")
+        };
+        assert_eq!(frame.struct_logs[0].gas, 100_000 - 21_000);
+    }
+
+    #[tokio::test]
+    async fn trace_call_record_access_status() {
+        let debug_api = mock_debug_api();
+
+        let contract = deploy_double_read(&debug_api.inner.provider);
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let mut opts = DebugTracingCallOptions::default();
+        opts.call_options.tracing_options.tracer_config =
+            GethDebugTracerConfig(serde_json::json!({ "recordAccessStatus": true }));
+
+        let res = debug_api.debug_trace_call(call, None, opts).await.unwrap();
+        let DebugTraceCallResponse::Trace(GethTrace::JS(trace)) = res else {
+            panic!("expected annotated struct logs, got {res:?}")
+        };
+        let frame = serde_json::from_value::<AccessStatusFrame>(trace).unwrap();
+        let access = frame
+            .struct_logs
+            .iter()
+            .filter(|log| log.log.op == "SLOAD")
+            .map(|log| log.access)
+            .collect::<Vec<_>>();
+        assert_eq!(access, vec![Some(AccessStatus::Cold), Some(AccessStatus::Warm)]);
+        // steps that don't access an account or slot aren't annotated
+        assert!(frame
+            .struct_logs
+            .iter()
+            .filter(|log| log.log.op == "POP")
+            .all(|log| log.access.is_none()));
+    }
+
+    #[tokio::test]
+    async fn trace_call_execution_stats() {
+        let debug_api = mock_debug_api();
+
+        let contract = deploy_double_read(&debug_api.inner.provider);
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let res = debug_api.debug_trace_call(call.clone(), None, Default::default()).await.unwrap();
+        assert!(matches!(res, DebugTraceCallResponse::Trace(_)));
+
+        let mut opts = DebugTracingCallOptions::default();
+        opts.extras.include_execution_stats = true;
+        let res = debug_api.debug_trace_call(call.clone(), None, opts.clone()).await.unwrap();
+        let stats = res.into_result().execution_stats.unwrap();
+        assert!(stats.account_provider_reads > 0);
+        // the slot is read from the provider once, the second load is warm
+        assert_eq!(stats.storage_provider_reads, 1);
+        assert_eq!(stats.code_provider_reads, 1);
+
+        // the statistics are collected per request
+        let res = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call,
+            None,
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        let served = res.into_result().execution_stats.unwrap();
+        assert_eq!(
+            ExecutionStats { provider_read_micros: 0, ..served },
+            ExecutionStats { provider_read_micros: 0, ..stats }
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_call_counters() {
+        let debug_api = mock_debug_api();
+        let (contract, _) = deploy_delegate_call(&debug_api.inner.provider);
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let mut opts = DebugTracingCallOptions::default();
+        opts.extras.include_stats = true;
+        let res = debug_api.debug_trace_call(call.clone(), None, opts.clone()).await.unwrap();
+        let res = res.into_result();
+        let GethTrace::Default(frame) = &res.trace else { panic!("expected struct logs") };
+        let stats = res.stats.unwrap();
+        // 8 steps of the contract and 3 of the library
+        assert_eq!(stats.steps, 11);
+        assert_eq!(stats.subcalls, 1);
+        assert_eq!(
+            stats.struct_log_bytes,
+            serde_json::to_vec(&frame.struct_logs).unwrap().len() as u64
+        );
+        // the sender, both contracts, their code and the slot
+        assert!(stats.database_reads >= 5, "{stats:?}");
+
+        // the trace itself is unchanged
+        let plain = debug_api.debug_trace_call(call.clone(), None, Default::default()).await;
+        assert_eq!(plain.unwrap().into_trace(), res.trace);
+
+        // other tracers don't record struct logs
+        opts.call_options.tracing_options.tracer =
+            Some(GethDebugBuiltInTracerType::CallTracer.into());
+        let res = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call,
+            None,
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        let served = res.into_result().stats.unwrap();
+        assert_eq!((served.steps, served.subcalls, served.struct_log_bytes), (11, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn trace_call_flamegraph() {
+        let debug_api = mock_debug_api();
+        let (contract, library) = deploy_delegate_call(&debug_api.inner.provider);
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let mut opts = DebugTracingCallOptions::default();
+        opts.call_options.tracing_options.tracer =
+            Some(GethDebugTracerType::JsTracer(FLAMEGRAPH_TRACER.to_string()));
+
+        let res = debug_api.debug_trace_call(call, None, opts).await.unwrap();
+        let DebugTraceCallResponse::Trace(GethTrace::JS(stacks)) = res else {
+            panic!("expected folded stacks, got {res:?}")
+        };
+        let stacks = serde_json::from_value::<Vec<String>>(stacks).unwrap();
+        assert_eq!(stacks.len(), 2);
+        assert!(stacks[0].starts_with(&format!("{contract} ")));
+        // the cold SLOAD of the delegate call is charged to the library
+        assert_eq!(stacks[1], format!("{contract};{library} 2103"));
+    }
+
+    /// Deploys contracts that each call the contract of the next level twice, the contract of the
+    /// last level reads a storage slot. Returns the contract of the top level.
+    fn deploy_call_tree(provider: &MockEthProvider, levels: usize) -> Address {
+        // PUSH1 0 SLOAD STOP
+        let mut code = Bytes::from_static(&hex!("60005400"));
+        let mut contract = Address::random();
+        for _ in 0..levels {
+            provider.add_account(contract, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code));
+            // POP(CALL(GAS, contract, 0, 0, 0, 0, 0)) twice, then RETURN(0, 32)
+            let call = [&hex!("6000600060006000600073")[..], contract.as_slice(), &hex!("5af150")]
+                .concat();
+            code = [&call[..], &call, &hex!("60206000f3")].concat().into();
+            contract = Address::random();
+        }
+        provider.add_account(contract, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code));
+        contract
+    }
+
+    /// Returns the top call of a call trace that includes the subcalls.
+    fn top_call(trace: &GethTrace) -> String {
+        let GethTrace::CallTracer(frame) = trace else {
+            panic!("expected call frame, got {trace:?}")
+        };
+        let frame = CallFrame { calls: Vec::new(), ..frame.clone() };
+        serde_json::to_string(&frame).unwrap()
+    }
+
+    #[tokio::test]
+    async fn trace_only_top_call() {
+        let debug_api = mock_debug_api();
+        let contract = deploy_call_tree(&debug_api.inner.provider, 6);
+        let tracing_options = |only_top_call: bool| GethDebugTracingOptions {
+            tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+            tracer_config: GethDebugTracerConfig(
+                serde_json::json!({ "onlyTopCall": only_top_call }),
+            ),
+            ..Default::default()
+        };
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let mut traces = Vec::new();
+        for only_top_call in [false, true] {
+            let mut opts = DebugTracingCallOptions::default();
+            opts.call_options.tracing_options = tracing_options(only_top_call);
+            let res = debug_api.debug_trace_call(call.clone(), None, opts).await.unwrap();
+            let DebugTraceCallResponse::Trace(trace) = res else {
+                panic!("expected trace, got {res:?}")
+            };
+            traces.push(trace);
+        }
+        let GethTrace::CallTracer(frame) = &traces[0] else { panic!("expected call frame") };
+        assert_eq!(frame.calls.len(), 2);
+        // the top call is the same as the one of the trace with all calls
+        assert_eq!(serde_json::to_string(&traces[1]).unwrap(), top_call(&traces[0]));
+
+        // the transactions of a block are traced the same way
+        let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::CANCUN);
+        let block_env =
+            BlockEnv { gas_limit: U256::from(ETHEREUM_BLOCK_GAS_LIMIT), ..Default::default() };
+        let mut block_traces = Vec::new();
+        for only_top_call in [false, true] {
+            let results = debug_api
+                .trace_block_with_stats(
+                    block_with_transactions(counter_transactions(contract, 3), None),
+                    debug_api.inner.provider.latest().unwrap(),
+                    cfg.clone(),
+                    block_env.clone(),
+                    tracing_options(only_top_call).into(),
+                )
+                .await
+                .unwrap();
+            let traces = without_stats(results)
+                .into_iter()
+                .map(|res| match res {
+                    TraceResult::Success { result, .. } => result,
+                    res => panic!("expected trace, got {res:?}"),
+                })
+                .collect::<Vec<_>>();
+            block_traces.push(traces);
+        }
+        assert_eq!(block_traces[1].len(), 3);
+        for (all_calls, only_top_call) in block_traces[0].iter().zip(&block_traces[1]) {
+            assert_eq!(serde_json::to_string(only_top_call).unwrap(), top_call(all_calls));
+        }
+    }
+
+    /// Returns transactions of a random sender that each increment the counter at the given
+    /// address.
+    fn counter_transactions(counter: Address, n: u64) -> Vec<TransactionSignedEcRecovered> {
+        let sender = Address::random();
+        (0..n)
+            .map(|nonce| {
+                let tx = Transaction::Legacy(TxLegacy {
+                    nonce,
+                    gas_limit: 100_000,
+                    to: TxKind::Call(counter),
+                    ..Default::default()
+                });
+                TransactionSignedEcRecovered::from_signed_transaction(
+                    TransactionSigned::from_transaction_and_signature(tx, Signature::default()),
+                    sender,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns a block with the given transactions and withdrawals.
+    fn block_with_transactions(
+        transactions: Vec<TransactionSignedEcRecovered>,
+        withdrawals: Option<Withdrawals>,
+    ) -> BlockWithSenders {
+        let (body, senders) = transactions.into_iter().map(|tx| tx.to_components()).unzip();
+        BlockWithSenders { block: Block { body, withdrawals, ..Default::default() }, senders }
+    }
+
+    /// Deploys the counter and traces the given transactions in a block on top of it.
+    async fn trace_counter_block(
+        debug_api: DebugApi<MockEthProvider, TestEthApi>,
+        counter: Address,
+        transactions: Vec<TransactionSignedEcRecovered>,
+        opts: DebugTracingBlockOptions,
+    ) -> Vec<TraceResult> {
+        without_stats(trace_counter_block_with_stats(debug_api, counter, transactions, opts).await)
+    }
+
+    /// Same as [`trace_counter_block`], but with the statistics of the verbose mode.
+    async fn trace_counter_block_with_stats(
+        debug_api: DebugApi<MockEthProvider, TestEthApi>,
+        counter: Address,
+        transactions: Vec<TransactionSignedEcRecovered>,
+        opts: DebugTracingBlockOptions,
+    ) -> Vec<TraceResultWithStats> {
+        deploy_counter_at(&debug_api.inner.provider, counter);
+        let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::CANCUN);
+        let block_env =
+            BlockEnv { gas_limit: U256::from(ETHEREUM_BLOCK_GAS_LIMIT), ..Default::default() };
+
+        let state = debug_api.inner.provider.latest().unwrap();
+        debug_api
+            .trace_block_with_stats(
+                block_with_transactions(transactions, None),
+                state,
+                cfg,
+                block_env,
+                opts,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn trace_block_parallel() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 8);
+
+        let sequential = trace_counter_block(
+            mock_debug_api(),
+            counter,
+            transactions.clone(),
+            Default::default(),
+        )
+        .await;
+        let parallel = trace_counter_block(
+            mock_debug_api().with_trace_block_parallelism(4),
+            counter,
+            transactions,
+            Default::default(),
+        )
+        .await;
+
+        assert_eq!(sequential.len(), 8);
+        assert!(sequential.iter().all(|res| matches!(res, TraceResult::Success { .. })));
+        assert_eq!(
+            serde_json::to_string(&parallel).unwrap(),
+            serde_json::to_string(&sequential).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_block_tx_hashes() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 4);
+
+        let all = trace_counter_block(
+            mock_debug_api(),
+            counter,
+            transactions.clone(),
+            Default::default(),
+        )
+        .await;
+
+        let unknown = B256::random();
+        let opts = DebugTracingBlockOptions {
+            tx_hashes: vec![unknown, transactions[2].hash],
+            ..Default::default()
+        };
+        let selected = trace_counter_block(mock_debug_api(), counter, transactions, opts).await;
+
+        assert_eq!(selected.len(), 2);
+        // the selected transaction is traced on top of the state of the preceding transactions
+        assert_eq!(
+            serde_json::to_string(&selected[0]).unwrap(),
+            serde_json::to_string(&all[2]).unwrap()
+        );
+        assert!(matches!(
+            &selected[1],
+            TraceResult::Error { tx_hash: Some(hash), .. } if *hash == unknown
+        ));
+    }
+
+    #[tokio::test]
+    async fn trace_block_verbose() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 4);
+
+        let plain = trace_counter_block_with_stats(
+            mock_debug_api(),
+            counter,
+            transactions.clone(),
+            Default::default(),
+        )
+        .await;
+        assert!(plain.iter().all(|res| res.stats.is_none()));
+
+        let opts = DebugTracingBlockOptions { verbose: true, ..Default::default() };
+        let verbose = trace_counter_block_with_stats(
+            mock_debug_api().with_trace_block_parallelism(2),
+            counter,
+            transactions.clone(),
+            opts,
+        )
+        .await;
+        assert_eq!(
+            serde_json::to_string(&without_stats(verbose.clone())).unwrap(),
+            serde_json::to_string(&without_stats(plain)).unwrap()
+        );
+        for res in &verbose {
+            let stats = res.stats.unwrap();
+            assert!(stats.gas_used > 21_000);
+            assert_eq!(stats.trace_size, serde_json::to_vec(&res.result).unwrap().len() as u64);
+        }
+
+        let summary = BlockTraceSummary::new(&verbose);
+        let slowest = summary.slowest_tx_hash.unwrap();
+        assert!(transactions.iter().any(|tx| tx.hash == slowest));
+        assert_eq!(
+            summary.total_duration_micros,
+            verbose.iter().map(|res| res.stats.unwrap().duration_micros).sum::<u64>()
+        );
+        assert!(verbose.iter().all(|res| res.stats.unwrap().counters.is_none()));
+    }
+
+    #[tokio::test]
+    async fn trace_block_counters() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 3);
+
+        let opts = DebugTracingBlockOptions { include_stats: true, ..Default::default() };
+        let results =
+            trace_counter_block_with_stats(mock_debug_api(), counter, transactions, opts).await;
+        assert_eq!(results.len(), 3);
+        for (index, res) in results.iter().enumerate() {
+            // the stats are included even without verbose mode
+            let counters = res.stats.unwrap().counters.unwrap();
+            let TraceResult::Success { result: GethTrace::Default(frame), .. } = &res.result else {
+                panic!("expected struct logs, got {:?}", res.result)
+            };
+            assert_eq!(counters.steps, 7);
+            assert_eq!(counters.subcalls, 0);
+            assert_eq!(
+                counters.struct_log_bytes,
+                serde_json::to_vec(&frame.struct_logs).unwrap().len() as u64
+            );
+            // the state read by the first transaction is cached for the following ones
+            assert_eq!(counters.database_reads > 0, index == 0, "{index}");
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_block_gas_limits() {
+        let debug_api = mock_debug_api()
+            .with_max_block_gas(TracerKind::StructLogger, 10_000_000)
+            .with_max_block_gas(TracerKind::FourByteTracer, 10_000_000)
+            .with_max_block_gas(TracerKind::CallTracer, 30_000_000);
+        let provider = &debug_api.inner.provider;
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            gas_used: 20_000_000,
+            ..Default::default()
+        };
+        let rlp_block: Bytes = alloy_rlp::encode(Block { header, ..Default::default() }).into();
+        let trace = |debug_api: &DebugApi<_, _>, opts| {
+            let (debug_api, rlp_block) = (debug_api.clone(), rlp_block.clone());
+            async move {
+                DebugApiServer::debug_trace_block(
+                    &debug_api,
+                    &Extensions::new(),
+                    rlp_block,
+                    Some(opts),
+                    None,
+                )
+                .await
+            }
+        };
+        let call_tracer = DebugTracingBlockOptions::from(
+            GethDebugTracingOptions::default()
+                .with_tracer(GethDebugBuiltInTracerType::CallTracer.into()),
+        );
+        let mux = |tracers: serde_json::Value| {
+            DebugTracingBlockOptions::from(GethDebugTracingOptions {
+                tracer: Some(GethDebugBuiltInTracerType::MuxTracer.into()),
+                tracer_config: GethDebugTracerConfig(tracers),
+                ..Default::default()
+            })
+        };
+
+        // the call tracer is allowed on the block the struct logger is rejected on
+        trace(&debug_api, call_tracer.clone()).await.unwrap();
+        let err = trace(&debug_api, Default::default()).await.unwrap_err();
+        assert_eq!(err.message(), "block too large for this tracer, limit is 10000000");
+        assert_eq!(err.code(), DebugRpcErrorCode::BlockTooLargeForTracer.code());
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "kind": "blockTooLargeForTracer",
+                "tracer": "structLogger",
+                "limit": 10_000_000,
+                "gasUsed": 20_000_000
+            })
+        );
+        // the limits of the tracers nested in a mux tracer apply as well
+        trace(&debug_api, mux(serde_json::json!({ "callTracer": {} }))).await.unwrap();
+        let opts = mux(serde_json::json!({ "callTracer": {}, "4byteTracer": {} }));
+        assert!(trace(&debug_api, opts).await.is_err());
+
+        // the trace can only be forced if the node allows it
+        let forced = DebugTracingBlockOptions { force: true, ..Default::default() };
+        assert!(trace(&debug_api, forced.clone()).await.is_err());
+        let debug_api = debug_api.with_block_gas_override(true);
+        trace(&debug_api, forced).await.unwrap();
+        assert!(trace(&debug_api, Default::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn estimate_trace_cost() {
+        let debug_api = mock_debug_api()
+            .with_trace_cache(TraceCache::new(Default::default()))
+            .with_max_block_gas(TracerKind::StructLogger, 10_000_000);
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, tx_hashes) = add_block_with_calls(provider, counter, 2);
+        let receipt = |cumulative_gas_used| Receipt {
+            tx_type: TxType::Legacy,
+            success: true,
+            cumulative_gas_used,
+            ..Default::default()
+        };
+        provider.add_receipts(block_hash, vec![receipt(61_000), receipt(101_000)]);
+
+        let target = TraceCostTarget::Transaction(tx_hashes[1]);
+        let estimate = debug_api.debug_estimate_trace_cost(target, Default::default()).await;
+        let estimate = estimate.unwrap();
+        assert_eq!((estimate.gas_used, estimate.transaction_count), (40_000, 1));
+        assert_eq!(estimate.estimated_struct_logs, (40_000 - MIN_TRANSACTION_GAS) / GAS_PER_STEP);
+        assert_eq!(
+            estimate.estimated_size,
+            estimate.estimated_struct_logs * STRUCT_LOG_SIZE as u64
+        );
+        assert!(!estimate.would_be_rejected);
+        assert_eq!(estimate.recorded_size, None);
+
+        // the size of an identical trace is known once it's cached
+        let opts = DebugTracingBlockOptions { tx_hashes: vec![tx_hashes[1]], ..Default::default() };
+        let page = debug_api.debug_trace_block_page(block_hash.into(), opts).await.unwrap();
+        let TraceResult::Success { result: GethTrace::Default(frame), .. } =
+            &page.results[0].result
+        else {
+            panic!("expected a struct log trace")
+        };
+        let size = frame.struct_logs.len() * STRUCT_LOG_SIZE + size_of::<TraceResult>();
+        let estimate = debug_api.debug_estimate_trace_cost(target, Default::default()).await;
+        assert_eq!(estimate.unwrap().recorded_size, Some(size as u64));
+        let block = TraceCostTarget::Block(block_hash.into());
+        let estimate = debug_api.debug_estimate_trace_cost(block, Default::default()).await;
+        assert_eq!(estimate.unwrap().recorded_size, None);
+
+        // the same limits that reject a trace are reported
+        let header = Header {
+            number: 2,
+            parent_hash: block_hash,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            gas_used: 20_000_000,
+            ..Default::default()
+        };
+        let large_block_hash = header.hash_slow();
+        provider.add_block(large_block_hash, Block { header, ..Default::default() });
+        provider.add_receipts(large_block_hash, Vec::new());
+        let block = TraceCostTarget::Block(large_block_hash.into());
+        let estimate = debug_api.debug_estimate_trace_cost(block, Default::default()).await;
+        let estimate = estimate.unwrap();
+        assert_eq!((estimate.gas_used, estimate.transaction_count), (20_000_000, 0));
+        assert!(estimate.would_be_rejected);
+        assert_eq!(
+            estimate.reason.as_deref(),
+            Some("block too large for this tracer, limit is 10000000")
+        );
+        let call_tracer = DebugTracingBlockOptions::from(
+            GethDebugTracingOptions::default()
+                .with_tracer(GethDebugBuiltInTracerType::CallTracer.into()),
+        );
+        let estimate = debug_api.debug_estimate_trace_cost(block, call_tracer).await.unwrap();
+        assert!(!estimate.would_be_rejected);
+
+        let debug_api = debug_api.with_max_trace_memory(1_000 * STRUCT_LOG_SIZE);
+        let estimate = debug_api.debug_estimate_trace_cost(target, Default::default()).await;
+        assert_eq!(estimate.unwrap().reason.as_deref(), Some("trace memory budget exceeded"));
+        let debug_api =
+            debug_api.with_tracer_policy(TracerPolicy::Deny([TracerKind::StructLogger].into()));
+        let estimate = debug_api.debug_estimate_trace_cost(target, Default::default()).await;
+        assert_eq!(
+            estimate.unwrap().reason.as_deref(),
+            Some("tracer disabled by operator: structLogger")
+        );
+
+        let missing = TraceCostTarget::Transaction(B256::random());
+        let err = debug_api.debug_estimate_trace_cost(missing, Default::default()).await;
+        assert!(matches!(err, Err(EthApiError::Debug(DebugApiError::TransactionNotFound(_)))));
+    }
+
+    #[tokio::test]
+    async fn trace_block_with_context() {
+        let debug_api = mock_debug_api();
+        let header = Header {
+            number: 1,
+            parent_hash: debug_api.inner.provider.block_hash(0).unwrap().unwrap(),
+            timestamp: 12,
+            base_fee_per_gas: Some(7),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let context = BlockTraceContext {
+            hash: header.hash_slow(),
+            number: 1,
+            timestamp: 12,
+            base_fee_per_gas: Some(7),
+            gas_used: 0,
+            transaction_count: 0,
+        };
+        let rlp_block: Bytes = alloy_rlp::encode(Block { header, ..Default::default() }).into();
+        let trace = |opts| {
+            let (debug_api, rlp_block) = (debug_api.clone(), rlp_block.clone());
+            async move {
+                DebugApiServer::debug_trace_block(
+                    &debug_api,
+                    &Extensions::new(),
+                    rlp_block,
+                    opts,
+                    None,
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        // the bare array is returned by default
+        assert_eq!(trace(None).await, DebugTraceBlockResponse::Array(Vec::new()));
+        let opts = DebugTracingBlockOptions { include_block_context: true, ..Default::default() };
+        assert_eq!(
+            trace(Some(opts.clone())).await,
+            DebugTraceBlockResponse::WithContext(BlockTraceWithContext {
+                context,
+                results: Vec::new(),
+                post_block: None,
+            })
+        );
+        // the context is added to the verbose trace
+        let DebugTraceBlockResponse::Verbose(trace) =
+            trace(Some(DebugTracingBlockOptions { verbose: true, ..opts })).await
+        else {
+            panic!("expected verbose response")
+        };
+        assert_eq!(trace.context, Some(context));
+    }
+
+    #[tokio::test]
+    async fn audit_log_entries() {
+        #[derive(Debug, Default)]
+        struct RecordingSink(parking_lot::Mutex<Vec<DebugAuditEntry>>);
+
+        impl DebugAuditSink for RecordingSink {
+            fn record(&self, entry: DebugAuditEntry) {
+                self.0.lock().push(entry);
+            }
+        }
+
+        let sink = Arc::new(RecordingSink::default());
+        let debug_api = mock_debug_api().with_audit_sink(sink.clone());
+        let parent = debug_api.inner.provider.block_hash(0).unwrap().unwrap();
+        let header = Header {
+            number: 1,
+            parent_hash: parent,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let rlp_block: Bytes = alloy_rlp::encode(Block { header, ..Default::default() }).into();
+        let call_tracer = GethDebugTracingOptions::default()
+            .with_tracer(GethDebugBuiltInTracerType::CallTracer.into());
+        let opts =
+            DebugTracingBlockOptions { tracing_options: call_tracer.clone(), ..Default::default() };
+        let mut ext = Extensions::new();
+        ext.insert(ResponseTraceMemory::default());
+        DebugApiServer::debug_trace_block(
+            &debug_api,
+            &ext,
+            rlp_block,
+            Some(opts),
+            Some(parent.into()),
+        )
+        .await
+        .unwrap();
+        let tx_hash = B256::random();
+        let opts =
+            DebugTracingTransactionOptions { tracing_options: call_tracer, ..Default::default() };
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hash,
+            Some(opts),
+        )
+        .await
+        .unwrap_err();
+
+        let entries = std::mem::take(&mut *sink.0.lock());
+        let [traced, failed] = &entries[..] else { panic!("expected two entries: {entries:?}") };
+        assert_eq!(traced.method, "debug_traceBlock");
+        assert_eq!(
+            traced.params,
+            DebugAuditParams {
+                block: Some(parent.into()),
+                tracer: Some(TracerKind::CallTracer),
+                ..Default::default()
+            }
+        );
+        assert_eq!(traced.outcome, DebugAuditOutcome::Success);
+        // the block has no transactions, its traces reserve no memory
+        assert_eq!(traced.response_size, Some(0));
+
+        assert_eq!(failed.method, "debug_traceTransaction");
+        assert_eq!(
+            failed.params,
+            DebugAuditParams {
+                tx_hash: Some(tx_hash),
+                tracer: Some(TracerKind::CallTracer),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            failed.outcome,
+            DebugAuditOutcome::Error { code: err.code(), message: err.message().to_string() }
+        );
+        assert_eq!(failed.response_size, None);
+
+        // without options, the default tracer is recorded
+        let debug_api = debug_api.with_default_tracer(
+            GethDebugBuiltInTracerType::PreStateTracer.into(),
+            Default::default(),
+        );
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hash, None)
+            .await
+            .unwrap_err();
+        let entries = std::mem::take(&mut *sink.0.lock());
+        assert_eq!(entries[0].params.tracer, Some(TracerKind::PreStateTracer));
+    }
+
+    #[tokio::test]
+    async fn trace_block_in_pages() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 5);
+        let full = trace_counter_block(
+            mock_debug_api(),
+            counter,
+            transactions.clone(),
+            Default::default(),
+        )
+        .await;
+        let size = |res: &TraceResult| serde_json::to_vec(res).unwrap().len() as u64;
+        let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::CANCUN);
+        let block_env =
+            BlockEnv { gas_limit: U256::from(ETHEREUM_BLOCK_GAS_LIMIT), ..Default::default() };
+        let block = block_with_transactions(transactions, None);
+
+        // without a cache, the preceding transactions of each page are replayed
+        let snapshot_cache = StateSnapshotCache::new(1 << 20);
+        for (debug_api, opts) in [
+            (
+                mock_debug_api(),
+                DebugTracingBlockOptions { max_transactions: Some(2), ..Default::default() },
+            ),
+            (
+                mock_debug_api().with_state_snapshot_cache(snapshot_cache.clone()),
+                DebugTracingBlockOptions { max_transactions: Some(2), ..Default::default() },
+            ),
+            (
+                mock_debug_api(),
+                DebugTracingBlockOptions {
+                    max_response_bytes: Some(size(&full[0]) + size(&full[1])),
+                    ..Default::default()
+                },
+            ),
+        ] {
+            deploy_counter_at(&debug_api.inner.provider, counter);
+            let mut opts = opts;
+            let (mut pages, mut results) = (Vec::new(), Vec::new());
+            loop {
+                let state = debug_api.inner.provider.latest().unwrap();
+                let page = debug_api
+                    .trace_block_page(
+                        block.clone(),
+                        state,
+                        cfg.clone(),
+                        block_env.clone(),
+                        opts.clone(),
+                    )
+                    .await
+                    .unwrap();
+                pages.push(page.results.len());
+                results.extend(without_stats(page.results));
+                let Some(cursor) = page.next_cursor else { break };
+                assert_eq!(cursor.block_hash, block.header.hash_slow());
+                opts.cursor = Some(cursor);
+            }
+            assert_eq!(pages, [2, 2, 1]);
+            assert_eq!(results, full);
+        }
+        // the states before the second and third page are cached
+        assert_eq!(snapshot_cache.len(), 2);
+
+        let debug_api = mock_debug_api();
+        let trace = |opts| {
+            let state = debug_api.inner.provider.latest().unwrap();
+            debug_api.trace_block_page(block.clone(), state, cfg.clone(), block_env.clone(), opts)
+        };
+        let cursor = BlockTraceCursor { block_hash: B256::random(), tx_index: 1 };
+        let err = trace(DebugTracingBlockOptions { cursor: Some(cursor), ..Default::default() })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(msg) if msg == "cursor of another block"));
+        let err = trace(DebugTracingBlockOptions {
+            tx_hashes: vec![block.body[0].hash],
+            max_transactions: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(_)));
+    }
+
+    #[tokio::test]
+    async fn trace_block_post_block() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 4);
+        let recipient = Address::random();
+        let withdrawals = Withdrawals::new(vec![
+            Withdrawal { address: recipient, amount: 1, ..Default::default() },
+            Withdrawal { address: Address::random(), amount: 0, ..Default::default() },
+            Withdrawal { address: recipient, amount: 2, ..Default::default() },
+        ]);
+        let expected = PostBlockTrace {
+            withdrawals: vec![
+                WithdrawalCredit {
+                    address: recipient,
+                    amount: U256::from(GWEI_TO_WEI),
+                    balance: U256::from(GWEI_TO_WEI),
+                },
+                WithdrawalCredit {
+                    address: recipient,
+                    amount: U256::from(2 * GWEI_TO_WEI),
+                    balance: U256::from(3 * GWEI_TO_WEI),
+                },
+            ],
+        };
+
+        let trace = |debug_api: DebugApi<MockEthProvider, TestEthApi>,
+                     transactions: Vec<TransactionSignedEcRecovered>,
+                     opts: DebugTracingBlockOptions| {
+            let withdrawals = withdrawals.clone();
+            async move {
+                deploy_counter_at(&debug_api.inner.provider, counter);
+                let cfg =
+                    CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::CANCUN);
+                // shanghai is active on mainnet at this timestamp
+                let block_env = BlockEnv {
+                    gas_limit: U256::from(ETHEREUM_BLOCK_GAS_LIMIT),
+                    timestamp: U256::from(1_710_338_135),
+                    ..Default::default()
+                };
+                let state = debug_api.inner.provider.latest().unwrap();
+                let block = block_with_transactions(transactions, Some(withdrawals));
+                debug_api.trace_block_page(block, state, cfg, block_env, opts).await.unwrap()
+            }
+        };
+        let opts = DebugTracingBlockOptions { include_post_block: true, ..Default::default() };
+
+        for (debug_api, transactions) in [
+            (mock_debug_api(), transactions.clone()),
+            (mock_debug_api().with_trace_block_parallelism(4), transactions.clone()),
+            (mock_debug_api(), Vec::new()),
+        ] {
+            let num_txs = transactions.len();
+            let page = trace(debug_api.clone(), transactions, opts.clone()).await;
+
+            // the post-block changes aren't mixed into the results
+            assert_eq!(page.results.len(), num_txs);
+            assert!(page
+                .results
+                .iter()
+                .all(|res| matches!(res.result, TraceResult::Success { .. })));
+            assert_eq!(page.post_block, Some(expected.clone()));
+
+            // and are kept in the map format, which only has room for transaction results
+            let res = debug_api.block_trace_response(
+                page,
+                false,
+                TraceResultFormat::Map,
+                false,
+                false,
+                None,
+            );
+            assert_eq!(res.post_block(), Some(&expected));
+            assert_eq!(res.into_results().len(), num_txs);
+        }
+
+        // reported with the last page only
+        let paginated = DebugTracingBlockOptions { max_transactions: Some(3), ..opts.clone() };
+        let page = trace(mock_debug_api(), transactions.clone(), paginated.clone()).await;
+        assert_eq!(page.results.len(), 3);
+        assert_eq!(page.post_block, None);
+        let opts_next = DebugTracingBlockOptions { cursor: page.next_cursor, ..paginated };
+        let page = trace(mock_debug_api(), transactions.clone(), opts_next).await;
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.post_block, Some(expected));
+
+        // not reported by default
+        let page = trace(mock_debug_api(), transactions.clone(), Default::default()).await;
+        assert_eq!(page.results.len(), 4);
+        assert_eq!(page.post_block, None);
+
+        // nor if only some transactions are traced
+        let opts = DebugTracingBlockOptions {
+            tx_hashes: vec![transactions[1].hash],
+            include_post_block: true,
+            ..Default::default()
+        };
+        let page = trace(mock_debug_api(), transactions, opts).await;
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.post_block, None);
+    }
+
+    #[tokio::test]
+    async fn trace_block_tracer_error() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 4);
+
+        let mut opts = DebugTracingBlockOptions::default();
+        opts.tracing_options.tracer_config =
+            GethDebugTracerConfig(serde_json::json!({ "opcodeFilter": ["NOT_AN_OPCODE"] }));
+        let results =
+            trace_counter_block(mock_debug_api(), counter, transactions.clone(), opts).await;
+
+        // the transactions are still executed on top of each other, even though they can't be
+        // traced
+        assert_eq!(results.len(), 4);
+        for (res, tx) in results.iter().zip(&transactions) {
+            assert!(matches!(
+                res,
+                TraceResult::Error { tx_hash: Some(hash), .. } if *hash == tx.hash
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_block_matches_transaction_traces() {
+        let counter = Address::random();
+        let sender = Address::random();
+        // alternate the transaction types, so that fields of a previous transaction would leak
+        // into the reused environment
+        let transactions = (0..4)
+            .map(|nonce| {
+                let tx = if nonce % 2 == 0 {
+                    Transaction::Eip2930(TxEip2930 {
+                        chain_id: 1,
+                        nonce,
+                        gas_limit: 100_000,
+                        to: TxKind::Call(counter),
+                        access_list: AccessList(vec![AccessListItem {
+                            address: counter,
+                            storage_keys: vec![B256::ZERO],
+                        }]),
+                        ..Default::default()
+                    })
+                } else {
+                    Transaction::Legacy(TxLegacy {
+                        nonce,
+                        gas_limit: 100_000,
+                        to: TxKind::Call(counter),
+                        ..Default::default()
+                    })
+                };
+                TransactionSignedEcRecovered::from_signed_transaction(
+                    TransactionSigned::from_transaction_and_signature(tx, Signature::default()),
+                    sender,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let debug_api = mock_debug_api();
+        let block = trace_counter_block(
+            debug_api.clone(),
+            counter,
+            transactions.clone(),
+            Default::default(),
+        )
+        .await;
+
+        // trace each transaction in its own environment
+        let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::CANCUN);
+        let block_env =
+            BlockEnv { gas_limit: U256::from(ETHEREUM_BLOCK_GAS_LIMIT), ..Default::default() };
+        let this = debug_api.clone();
+        let traces = debug_api
+            .eth_api()
+            .spawn_with_state_at_block(BlockId::default(), move |state| {
+                let mut db = CacheDB::new(StateProviderDatabase::new(state));
+                let opts = GethDebugTracingOptions::default();
+                transactions
+                    .iter()
+                    .map(|tx| {
+                        let env = trace::block_tx_env(this.eth_api(), &cfg, &block_env, tx);
+                        let (trace, state, _) =
+                            this.eth_api().trace_transaction(&opts, env, &mut db, None)?;
+                        db.commit(state);
+                        Ok(trace)
+                    })
+                    .collect::<Result<Vec<_>, EthApiError>>()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(block.len(), traces.len());
+        for (res, trace) in block.iter().zip(&traces) {
+            let TraceResult::Success { result, .. } = res else {
+                panic!("expected trace, got {res:?}")
+            };
+            assert_eq!(serde_json::to_value(result).unwrap(), serde_json::to_value(trace).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_block_without_debug_api() {
+        let counter = Address::random();
+        let transactions = counter_transactions(counter, 4);
+        let debug_api = mock_debug_api();
+        let expected = trace_counter_block(
+            debug_api.clone(),
+            counter,
+            transactions.clone(),
+            Default::default(),
+        )
+        .await;
+
+        // trace the block directly on top of the state, without the RPC layer
+        let state = debug_api.inner.provider.latest().unwrap();
+        let mut db =
+            CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(state.as_ref())));
+        let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(Default::default(), SpecId::CANCUN);
+        let block_env =
+            BlockEnv { gas_limit: U256::from(ETHEREUM_BLOCK_GAS_LIMIT), ..Default::default() };
+        let results = debug_api
+            .eth_api()
+            .trace_block(&mut db, &transactions, &cfg, &block_env, &Default::default(), None)
+            .unwrap();
+
+        assert_eq!(serde_json::to_value(results).unwrap(), serde_json::to_value(expected).unwrap());
+        // the database holds the state after the last transaction
+        assert_eq!(db.storage(counter, U256::ZERO).unwrap(), U256::from(4));
+    }
+
+    /// Code of a contract that increments its storage slot 0.
+    ///
+    /// SSTORE(0, SLOAD(0) + 1) STOP
+    const COUNTER_CODE: &[u8] = &hex!("60005460010160005500");
+
+    /// Deploys a counter contract, see [`COUNTER_CODE`], at a random address and returns it.
+    fn deploy_counter(provider: &MockEthProvider) -> Address {
+        let counter = Address::random();
+        deploy_counter_at(provider, counter);
+        counter
+    }
+
+    /// Deploys a counter contract, see [`COUNTER_CODE`], at the given address.
+    fn deploy_counter_at(provider: &MockEthProvider, counter: Address) {
+        let code = Bytes::from_static(COUNTER_CODE);
+        provider.add_account(counter, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code));
+    }
+
+    /// Deploys a contract that reads its storage slot 0 twice, and returns it.
+    fn deploy_double_read(provider: &MockEthProvider) -> Address {
+        // PUSH1 0 SLOAD POP PUSH1 0 SLOAD POP STOP
+        let contract = Address::random();
+        let code = Bytes::from_static(&hex!("600054506000545000"));
+        provider.add_account(contract, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code));
+        contract
+    }
+
+    /// Deploys a contract that delegate calls a library contract that reads a storage slot.
+    /// Returns the contract and the library.
+    fn deploy_delegate_call(provider: &MockEthProvider) -> (Address, Address) {
+        // PUSH1 0 SLOAD STOP
+        let library = Address::random();
+        let code = Bytes::from_static(&hex!("60005400"));
+        provider.add_account(library, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code));
+        // DELEGATECALL(GAS, library, 0, 0, 0, 0) STOP
+        let contract = Address::random();
+        let code = [&hex!("600060006000600073")[..], library.as_slice(), &hex!("5af400")].concat();
+        provider
+            .add_account(contract, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code.into()));
+        (contract, library)
+    }
+
+    /// Adds a block at number 1 on top of the genesis block of the mock provider, with `n` signed
+    /// transactions of a random sender that each call the given address, and returns the hashes
+    /// of the block and the transactions.
+    fn add_block_with_calls(provider: &MockEthProvider, to: Address, n: u64) -> (B256, Vec<B256>) {
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let body = (0..n)
+            .map(|nonce| {
+                let tx = Transaction::Legacy(TxLegacy {
+                    nonce,
+                    gas_limit: 100_000,
+                    to: TxKind::Call(to),
+                    ..Default::default()
+                });
+                sign_tx_with_key_pair(key_pair, tx)
+            })
+            .collect::<Vec<_>>();
+        let tx_hashes = body.iter().map(|tx| tx.hash).collect();
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let block_hash = header.hash_slow();
+        provider.add_block(block_hash, Block { header, body, ..Default::default() });
+        (block_hash, tx_hashes)
+    }
+
+    #[tokio::test]
+    async fn trace_transaction_with_state_read_cache() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let code = Bytes::from_static(COUNTER_CODE);
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, counter, 2);
+
+        let cache = StateReadCache::new(DEFAULT_STATE_READ_CACHE_MAX_BYTES);
+        let provider = debug_api.inner.provider.clone();
+        let cached_api =
+            DebugApi::new(provider, debug_api.eth_api().clone(), BlockingTaskGuard::new(4))
+                .with_state_read_cache(cache.clone());
+        let tx_hash = tx_hashes[1];
+        let trace = |debug_api: DebugApi<_, _>| async move {
+            DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hash, None)
+                .await
+                .unwrap()
+        };
+        let expected = trace(debug_api.clone()).await;
+        assert_eq!(trace(cached_api.clone()).await, expected);
+        assert!(!cache.is_empty());
+
+        // the state of the parent block is read from the cache, not from the database
+        debug_api.inner.provider.add_account(
+            counter,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(code)
+                .extend_storage([(B256::ZERO, U256::from(10))]),
+        );
+        assert_ne!(trace(debug_api).await, expected);
+        assert_eq!(trace(cached_api).await, expected);
+    }
+
+    #[tokio::test]
+    async fn trace_permit_of_closed_guard() {
+        let debug_api = mock_debug_api();
+        let (block_hash, tx_hashes) =
+            add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hashes[0], None)
+            .await
+            .unwrap();
+
+        // requests are rejected instead of being executed without a permit
+        debug_api.inner.blocking_task_guard.close();
+        let unavailable = ErrorObjectOwned::from(DebugApiError::TracingUnavailable);
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[0],
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), unavailable);
+        let err = DebugApiServer::debug_trace_block_by_hash(
+            &debug_api,
+            &Extensions::new(),
+            block_hash,
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), unavailable);
+        let call = TransactionRequest::default();
+        let err =
+            DebugApiServer::debug_trace_call(&debug_api, &Extensions::new(), call, None, None)
+                .await;
+        assert_eq!(err.unwrap_err(), unavailable);
+    }
+
+    #[tokio::test]
+    async fn debug_error_codes() {
+        let debug_api = mock_debug_api();
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+        let ext = Extensions::new();
+        let kind = |err: &ErrorObjectOwned| {
+            let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+            data["kind"].as_str().unwrap().to_string()
+        };
+
+        let err = DebugApiServer::debug_trace_block_by_hash(&debug_api, &ext, B256::random(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), DebugRpcErrorCode::BlockNotFound.code());
+        assert_eq!(kind(&err), "blockNotFound");
+        let err = DebugApiServer::debug_trace_transaction(&debug_api, &ext, B256::random(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), DebugRpcErrorCode::TransactionNotFound.code());
+        assert_eq!(err.message(), "transaction not found");
+
+        let opts = GethDebugTracingOptions {
+            tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+            tracer_config: GethDebugTracerConfig(serde_json::json!({ "onlyTopCall": "yes" })),
+            ..Default::default()
+        };
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &ext,
+            tx_hashes[0],
+            Some(opts.into()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), DebugRpcErrorCode::InvalidTracerConfig.code());
+        assert_eq!(
+            err.data().unwrap().get(),
+            r#"{"kind":"invalidTracerConfig","tracer":"callTracer"}"#
+        );
+
+        let err = DebugApiServer::debug_trace_block(
+            &debug_api,
+            &ext,
+            Bytes::from_static(&[0x01]),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), DebugRpcErrorCode::InvalidRlp.code());
+        assert_eq!(kind(&err), "invalidRlp");
+
+        let debug_api =
+            debug_api.with_tracer_policy(TracerPolicy::Deny([TracerKind::StructLogger].into()));
+        let err = DebugApiServer::debug_trace_transaction(&debug_api, &ext, tx_hashes[0], None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), DebugRpcErrorCode::UnsupportedTracer.code());
+        assert_eq!(err.message(), "tracer disabled by operator: structLogger");
+
+        debug_api.inner.blocking_task_guard.close();
+        let opts = GethDebugTracingOptions {
+            tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+            ..Default::default()
+        };
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &ext,
+            tx_hashes[0],
+            Some(opts.into()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), DebugRpcErrorCode::TracingUnavailable.code());
+        assert_eq!(kind(&err), "tracingUnavailable");
+    }
+
+    #[tokio::test]
+    async fn tracer_policy() {
+        let disabled = |kind: TracerKind| {
+            ErrorObjectOwned::from(DebugApiError::TracerDisabled { tracer: kind.as_str() })
+        };
+        let tracer = |tracer: GethDebugTracerType| GethDebugTracingOptions {
+            tracer: Some(tracer),
+            ..Default::default()
+        };
+        let call_tracer = tracer(GethDebugBuiltInTracerType::CallTracer.into());
+        let js_tracer = tracer(GethDebugTracerType::JsTracer("{}".to_string()));
+
+        let policy = TracerPolicy::Deny([TracerKind::StructLogger, TracerKind::Js].into());
+        let debug_api = mock_debug_api().with_tracer_policy(policy);
+        let (block_hash, tx_hashes) =
+            add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+        let opts = DebugTracingTransactionOptions::from(call_tracer.clone());
+        DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[0],
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[0],
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), disabled(TracerKind::StructLogger));
+        // the tracer is checked before the transaction is looked up
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            B256::random(),
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), disabled(TracerKind::StructLogger));
+
+        let opts = DebugTracingBlockOptions::from(js_tracer.clone());
+        let err = DebugApiServer::debug_trace_block_by_hash(
+            &debug_api,
+            &Extensions::new(),
+            block_hash,
+            Some(opts),
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), disabled(TracerKind::Js));
+        let opts = DebugTracingBlockOptions::from(call_tracer.clone());
+        DebugApiServer::debug_trace_block_by_hash(
+            &debug_api,
+            &Extensions::new(),
+            block_hash,
+            Some(opts),
+        )
+        .await
+        .unwrap();
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(Address::random())),
+            ..Default::default()
+        };
+        let err = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call.clone(),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), disabled(TracerKind::StructLogger));
+        let bundles =
+            vec![DebugBundle { transactions: vec![call.clone().into()], ..Default::default() }];
+        let err = debug_api.debug_trace_call_many(bundles, None, None).await;
+        assert!(matches!(
+            err,
+            Err(EthApiError::Debug(DebugApiError::TracerDisabled { tracer: "structLogger" }))
+        ));
+
+        // the policy applies to the default tracer of requests that don't specify one
+        let policy = TracerPolicy::Allow([TracerKind::CallTracer].into());
+        let debug_api = debug_api
+            .with_tracer_policy(policy)
+            .with_default_tracer(GethDebugBuiltInTracerType::CallTracer.into(), Default::default());
+        DebugApiServer::debug_trace_call(&debug_api, &Extensions::new(), call.clone(), None, None)
+            .await
+            .unwrap();
+        let opts = DebugTracingCallOptions::from(GethDebugTracingCallOptions {
+            tracing_options: tracer(GethDebugBuiltInTracerType::PreStateTracer.into()),
+            ..Default::default()
+        });
+        let err = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call,
+            None,
+            Some(opts),
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), disabled(TracerKind::PreStateTracer));
+    }
+
+    #[tokio::test]
+    async fn trace_pool_transaction() {
+        let debug_api = mock_debug_api();
+        let sender = Address::random();
+        let ether = U256::from(GWEI_TO_WEI) * U256::from(GWEI_TO_WEI);
+        debug_api.inner.provider.add_account(sender, ExtendedAccount::new(0, ether));
+        let transfer = |nonce| {
+            MockTransaction::legacy()
+                .with_sender(sender)
+                .with_nonce(nonce)
+                .with_gas_limit(21_000)
+                .with_gas_price(GWEI_TO_WEI as u128)
+        };
+        let pool = debug_api.eth_api().pool();
+        let next = pool.add_external_transaction(transfer(0)).await.unwrap();
+        // a transfer whose preceding transaction of the sender is missing
+        let gapped = pool.add_external_transaction(transfer(2)).await.unwrap();
+
+        // pool transactions aren't traced by default
+        let err = debug_api.debug_trace_transaction(next, Default::default()).await;
+        assert!(matches!(err, Err(EthApiError::Debug(DebugApiError::TransactionNotFound(_)))));
+
+        let debug_api = debug_api.with_trace_pool_transactions(true);
+        let res = debug_api
+            .debug_trace_transaction_with_state_changes(next, Default::default())
+            .await
+            .unwrap();
+        let DebugTraceTransactionResponse::Pending(pending) = res else {
+            panic!("expected a pending trace, got {res:?}")
+        };
+        assert!(pending.pending);
+        assert_eq!(pending.error, None);
+        let Some(GethTrace::Default(frame)) = pending.result else {
+            panic!("expected struct logs, got {:?}", pending.result)
+        };
+        assert!(!frame.failed);
+        assert_eq!(frame.gas, 21_000);
+
+        // the nonce gap fails the transaction
+        let res = debug_api
+            .debug_trace_transaction_with_state_changes(gapped, Default::default())
+            .await
+            .unwrap();
+        let nonce_too_high =
+            EthApiError::InvalidTransaction(RpcInvalidTransactionError::NonceTooHigh).to_string();
+        assert_eq!(
+            res,
+            DebugTraceTransactionResponse::Pending(PendingTransactionTrace {
+                pending: true,
+                result: None,
+                error: Some(nonce_too_high.clone()),
+                state_changes: None,
+                stats: None,
+            })
+        );
+        let err = debug_api.debug_trace_transaction(gapped, Default::default()).await.unwrap_err();
+        assert!(err.to_string().contains(&nonce_too_high), "{err}");
+
+        // transactions that are neither in a block nor in the pool are still unknown
+        let err = debug_api.debug_trace_transaction(B256::random(), Default::default()).await;
+        assert!(matches!(err, Err(EthApiError::Debug(DebugApiError::TransactionNotFound(_)))));
+    }
+
+    #[tokio::test]
+    async fn trace_memory_budget() {
+        // the struct logs of a single transaction fit into the budget, but not those of two
+        let debug_api = mock_debug_api().with_max_trace_memory(10 * memory::STRUCT_LOG_SIZE);
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let (block_hash, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, counter, 2);
+
+        // the memory of a request is released once it's done
+        for _ in 0..2 {
+            DebugApiServer::debug_trace_transaction(
+                &debug_api,
+                &Extensions::new(),
+                tx_hashes[0],
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        // the whole request fails instead of returning an error result for the last transaction
+        let exceeded = ErrorObjectOwned::from(DebugApiError::TraceMemoryBudgetExceeded {
+            limit: 10 * memory::STRUCT_LOG_SIZE,
+        });
+        let err = DebugApiServer::debug_trace_block_by_hash(
+            &debug_api,
+            &Extensions::new(),
+            block_hash,
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), exceeded);
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hashes[0], None)
+            .await
+            .unwrap();
+
+        // with the RPC middleware, the memory is held until the server serialized the response
+        let mut ext = Extensions::new();
+        ext.insert(ResponseTraceMemory::default());
+        DebugApiServer::debug_trace_transaction(&debug_api, &ext, tx_hashes[0], None)
+            .await
+            .unwrap();
+        let err = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[1],
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), exceeded);
+        drop(ext);
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hashes[1], None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn client_rate_limits() {
+        let debug_api = mock_debug_api().with_client_limits(ClientLimitsConfig {
+            max_requests_per_minute: Some(1),
+            ..Default::default()
+        });
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+        let connection = |id| {
+            let mut ext = Extensions::new();
+            ext.insert(ConnectionId(id));
+            ext
+        };
+        let (first, second) = (connection(1), connection(2));
+
+        DebugApiServer::debug_trace_transaction(&debug_api, &first, tx_hashes[0], None)
+            .await
+            .unwrap();
+        let err = DebugApiServer::debug_trace_transaction(&debug_api, &first, tx_hashes[0], None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), RATE_LIMIT_EXCEEDED_CODE);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data["limit"], serde_json::json!({ "perMinute": 1 }));
+        assert!(data["retryAfterMs"].as_u64().unwrap() <= 60_000);
+        // the limits count the requests of all methods
+        let err = DebugApiServer::debug_replay_block(&debug_api, &first, BlockId::latest()).await;
+        assert_eq!(err.unwrap_err().code(), RATE_LIMIT_EXCEEDED_CODE);
+
+        // the other connection isn't throttled, nor are requests without a connection
+        DebugApiServer::debug_trace_transaction(&debug_api, &second, tx_hashes[0], None)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            DebugApiServer::debug_trace_transaction(
+                &debug_api,
+                &Extensions::new(),
+                tx_hashes[0],
+                None,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_transaction_state_changes() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, counter, 2);
+
+        // the plain trace is returned by default
+        let plain = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[1],
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(plain, DebugTraceTransactionResponse::Trace(_)));
+
+        let opts =
+            DebugTracingTransactionOptions { return_state_changes: true, ..Default::default() };
+        let noop = DebugTracingTransactionOptions {
+            tracing_options: GethDebugTracingOptions::default()
+                .with_tracer(GethDebugBuiltInTracerType::NoopTracer.into()),
+            ..opts.clone()
+        };
+        let res = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[1],
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        let DebugTraceTransactionResponse::WithExtras(res) = res else {
+            panic!("expected state changes, got {res:?}")
+        };
+        assert_eq!(res.trace, plain.into_trace().unwrap());
+        assert_eq!(res.stats, None);
+
+        // the changes are relative to the state after the preceding transaction
+        let changes = res.state_changes.unwrap();
+        let slot = |account: &AccountState| account.storage[&B256::ZERO];
+        assert_eq!(slot(&changes.diff.pre[&counter]), B256::with_last_byte(1));
+        assert_eq!(slot(&changes.diff.post[&counter]), B256::with_last_byte(2));
+        assert!(changes.created.is_empty() && changes.selfdestructed.is_empty());
+
+        // the noop tracer still executes the transaction
+        let res = debug_api.debug_trace_transaction_with_state_changes(tx_hashes[1], noop).await;
+        let DebugTraceTransactionResponse::WithExtras(res) = res.unwrap() else {
+            panic!("expected state changes")
+        };
+        assert_eq!(res.state_changes, Some(changes));
+    }
+
+    #[tokio::test]
+    async fn trace_transaction_counters() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, counter, 2);
+        let plain = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[1],
+            None,
+        )
+        .await
+        .unwrap();
+
+        let opts = DebugTracingTransactionOptions { include_stats: true, ..Default::default() };
+        let res = DebugApiServer::debug_trace_transaction(
+            &debug_api,
+            &Extensions::new(),
+            tx_hashes[1],
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        let DebugTraceTransactionResponse::WithExtras(res) = res else {
+            panic!("expected stats, got {res:?}")
+        };
+        assert_eq!(res.trace, plain.into_trace().unwrap());
+        assert_eq!(res.state_changes, None);
+        let stats = res.stats.unwrap();
+        // only the traced transaction is counted, not the replay of the preceding one
+        assert_eq!((stats.steps, stats.subcalls), (7, 0));
+        assert!(stats.struct_log_bytes > 0);
+    }
+
+    #[test]
+    fn transaction_state_changes_status() {
+        let provider = MockEthProvider::default();
+        let destroyed = Address::random();
+        provider.add_account(destroyed, ExtendedAccount::new(1, U256::from(10)));
+        let mut db =
+            CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(&provider)));
+
+        let created = Address::random();
+        let mut state = EvmState::default();
+        let mut account = Account::from(AccountInfo { nonce: 1, ..Default::default() });
+        account.mark_touch();
+        account.mark_created();
+        state.insert(created, account);
+        let mut account =
+            Account::from(AccountInfo { nonce: 1, balance: U256::from(10), ..Default::default() });
+        account.mark_touch();
+        account.mark_selfdestruct();
+        state.insert(destroyed, account);
+        // accounts that were only loaded aren't changed
+        let mut account = Account::from(AccountInfo::default());
+        account.mark_created();
+        state.insert(Address::random(), account);
+
+        let changes = state_changes(&state, &mut db).unwrap();
+        assert_eq!(changes.created, BTreeSet::from([created]));
+        assert_eq!(changes.selfdestructed, BTreeSet::from([destroyed]));
+        // created accounts have no prior state, selfdestructed accounts no posterior state
+        assert_eq!(changes.diff.pre.keys().collect::<Vec<_>>(), [&destroyed]);
+        assert_eq!(changes.diff.post.keys().collect::<Vec<_>>(), [&created]);
+    }
+
+    #[tokio::test]
+    async fn debug_api_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let debug_api = reth_metrics::metrics::with_local_recorder(&recorder, mock_debug_api);
+        let (block_hash, tx_hashes) =
+            add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hashes[0], None)
+            .await
+            .unwrap();
+        DebugApiServer::debug_trace_block_by_hash(&debug_api, &Extensions::new(), block_hash, None)
+            .await
+            .unwrap();
+        DebugApiServer::debug_trace_block_by_hash(
+            &debug_api,
+            &Extensions::new(),
+            B256::random(),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        let snapshot = snapshotter.snapshot().into_hashmap();
+        let value = |kind, name: &'static str, label: (&'static str, &'static str)| {
+            let key = reth_metrics::metrics::Key::from_parts(
+                name,
+                vec![reth_metrics::metrics::Label::new(label.0, label.1)],
+            );
+            snapshot.get(&CompositeKey::new(kind, key)).map(|(_, _, value)| value.clone())
+        };
+        let samples = |name, label| match value(MetricKind::Histogram, name, label) {
+            Some(DebugValue::Histogram(samples)) => samples.len(),
+            _ => 0,
+        };
+
+        let transaction = ("method", "debug_traceTransaction");
+        assert_eq!(
+            value(MetricKind::Counter, "rpc.debug.requests_total", transaction),
+            Some(DebugValue::Counter(1))
+        );
+        assert_eq!(
+            value(MetricKind::Counter, "rpc.debug.errors_total", transaction),
+            Some(DebugValue::Counter(0))
+        );
+        assert_eq!(samples("rpc.debug.permit_wait_seconds", transaction), 1);
+        assert_eq!(samples("rpc.debug.response_size_bytes", transaction), 1);
+
+        // only the sizes of successful responses are recorded
+        let block = ("method", "debug_traceBlockByHash");
+        assert_eq!(
+            value(MetricKind::Counter, "rpc.debug.requests_total", block),
+            Some(DebugValue::Counter(2))
+        );
+        assert_eq!(
+            value(MetricKind::Counter, "rpc.debug.errors_total", block),
+            Some(DebugValue::Counter(1))
+        );
+        assert_eq!(samples("rpc.debug.permit_wait_seconds", block), 2);
+        assert_eq!(samples("rpc.debug.response_size_bytes", block), 1);
+
+        assert_ne!(samples("rpc.debug.execution_time_seconds", ("class", "call")), 0);
+        assert_ne!(samples("rpc.debug.execution_time_seconds", ("class", "block")), 0);
+        assert_eq!(samples("rpc.debug.execution_time_seconds", ("class", "witness")), 0);
+    }
+
+    #[tokio::test]
+    async fn blocking_task_panic() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let debug_api = reth_metrics::metrics::with_local_recorder(&recorder, mock_debug_api);
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+
+        // a bug of a tracer panics on the blocking task of the request
+        let metrics = &debug_api.inner.metrics;
+        let err = debug_api
+            .traced(&Extensions::new(), &metrics.trace_call, TraceClass::Call, |this| async move {
+                let eth_api = this.eth_api().clone();
+                eth_api
+                    .spawn_tracing(move |_| -> Result<(), EthApiError> {
+                        let _timer = this.inner.metrics.execution_timer(TraceClass::Call);
+                        panic!("tracer bug")
+                    })
+                    .await
+                    .map_err(Into::into)
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), jsonrpsee::types::error::INTERNAL_ERROR_CODE);
+        assert_eq!(err.message(), "blocking task panicked: tracer bug");
+
+        // the permit of the request is released and the API keeps serving requests
+        let permits = (0..4)
+            .map(|_| debug_api.inner.blocking_task_guard.clone().try_acquire_owned())
+            .collect::<Result<Vec<_>, _>>();
+        assert!(permits.is_ok());
+        drop(permits);
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hashes[0], None)
+            .await
+            .unwrap();
+
+        let snapshot = snapshotter.snapshot().into_hashmap();
+        let counter = |name: &'static str, label: (&'static str, &'static str)| {
+            let key = reth_metrics::metrics::Key::from_parts(
+                name,
+                vec![reth_metrics::metrics::Label::new(label.0, label.1)],
+            );
+            snapshot
+                .get(&CompositeKey::new(MetricKind::Counter, key))
+                .map(|(_, _, value)| value.clone())
+        };
+        assert_eq!(
+            counter("rpc.debug.errors_total", ("method", "debug_traceCall")),
+            Some(DebugValue::Counter(1))
+        );
+        assert_eq!(
+            counter("rpc.debug.panics_total", ("class", "call")),
+            Some(DebugValue::Counter(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_bundle_positions() {
+        let debug_api = mock_debug_api();
+        // NUMBER TIMESTAMP STOP
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!("434200"))),
+        );
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let bundle = |block_override: Option<BlockOverrides>| DebugBundle {
+            transactions: vec![call.clone().into()],
+            block_override,
+        };
+        let positioned = BlockOverrides {
+            number: Some(U256::from(100)),
+            time: Some(5_000),
+            ..Default::default()
+        };
+
+        let bundles =
+            vec![bundle(None), bundle(None), bundle(Some(positioned.clone())), bundle(None)];
+        let traces = debug_api.debug_trace_call_many(bundles, None, None).await.unwrap();
+        let positions = traces
+            .into_iter()
+            .map(|traces| {
+                let GethTrace::Default(frame) = &traces.traces()[0] else {
+                    panic!("expected struct logs, got {traces:?}")
+                };
+                frame.struct_logs.last().unwrap().stack.clone().unwrap()
+            })
+            .collect::<Vec<_>>();
+        // the mock chain is mainnet, with a block time of 12 seconds
+        assert_eq!(
+            positions,
+            [[0, 1], [1, 13], [100, 5_000], [101, 5_012]].map(|position| position.map(U256::from))
+        );
+
+        // a bundle can't be positioned before its predecessor
+        let earlier = BlockOverrides { time: Some(4_999), ..Default::default() };
+        let bundles = vec![bundle(Some(positioned)), bundle(Some(earlier))];
+        let err = debug_api.debug_trace_call_many(bundles, None, None).await.unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(msg)
+            if msg == "timestamp 4999 of bundle 1 is before timestamp 5000 of the previous bundle"));
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_bundle_block_overrides() {
+        let debug_api = mock_debug_api();
+        // BASEFEE COINBASE STOP
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!("484100"))),
+        );
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let bundle = |block_override: Option<BlockOverrides>| DebugBundle {
+            transactions: vec![call.clone().into(), call.clone().into()],
+            block_override,
+        };
+        let coinbase = Address::random();
+        let overrides = BlockOverrides {
+            base_fee: Some(U256::from(7)),
+            coinbase: Some(coinbase),
+            ..Default::default()
+        };
+
+        let bundles = vec![bundle(None), bundle(Some(overrides)), bundle(None)];
+        let traces = debug_api.debug_trace_call_many(bundles, None, None).await.unwrap();
+        let stacks = traces
+            .iter()
+            .map(|traces| {
+                traces
+                    .traces()
+                    .iter()
+                    .map(|trace| {
+                        let GethTrace::Default(frame) = trace else {
+                            panic!("expected struct logs, got {trace:?}")
+                        };
+                        frame.struct_logs.last().unwrap().stack.clone().unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // all transactions of the overridden bundle, and only those, see the overrides
+        let overridden = vec![U256::from(7), U256::from_be_slice(coinbase.as_slice())];
+        let default = vec![U256::ZERO, U256::ZERO];
+        assert_eq!(
+            stacks,
+            [
+                [default.clone(), default.clone()],
+                [overridden.clone(), overridden],
+                [default.clone(), default]
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_state_overrides_per_bundle() {
+        let debug_api = mock_debug_api();
+        // two counters
+        let (overridden, other) = (Address::random(), Address::random());
+        for counter in [overridden, other] {
+            deploy_counter_at(&debug_api.inner.provider, counter);
+        }
+        let call = |to| TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(to)),
+            ..Default::default()
+        };
+        let bundle = DebugBundle {
+            transactions: vec![call(overridden).into(), call(other).into()],
+            ..Default::default()
+        };
+        let state_overrides = StateOverride::from_iter([(
+            overridden,
+            AccountOverride {
+                state_diff: Some([(B256::ZERO, B256::with_last_byte(10))].into_iter().collect()),
+                ..Default::default()
+            },
+        )]);
+
+        // returns the values the counters loaded in each bundle
+        let loaded = |per_bundle: bool| {
+            let opts = DebugTracingCallManyOptions {
+                call_options: GethDebugTracingCallOptions {
+                    state_overrides: Some(state_overrides.clone()),
+                    ..Default::default()
+                },
+                state_overrides_per_bundle: per_bundle,
+                ..Default::default()
+            };
+            let bundles = vec![bundle.clone(), bundle.clone()];
+            let debug_api = debug_api.clone();
+            async move {
+                let traces =
+                    debug_api.debug_trace_call_many(bundles, None, Some(opts)).await.unwrap();
+                traces
+                    .iter()
+                    .map(|traces| {
+                        traces
+                            .traces()
+                            .iter()
+                            .map(|trace| {
+                                let GethTrace::Default(frame) = trace else {
+                                    panic!("expected struct logs, got {trace:?}")
+                                };
+                                // the stack after the SLOAD
+                                frame.struct_logs[2].stack.as_ref().unwrap()[0].to::<u64>()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // the second bundle observes the writes of the first one
+        assert_eq!(loaded(false).await, [[10, 0], [11, 1]]);
+        // the overrides are applied again, on top of the writes to other accounts
+        assert_eq!(loaded(true).await, [[10, 0], [10, 1]]);
+    }
+
+    #[tokio::test]
+    async fn trace_call_full_state_override() {
+        let debug_api = mock_debug_api();
+        // PUSH1 1 SLOAD STOP
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("60015400")))
+                .extend_storage([(B256::with_last_byte(1), U256::from(7))]),
+        );
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        // only slot 0 is listed
+        let slots = || [(B256::ZERO, B256::with_last_byte(10))].into_iter().collect();
+        let state = AccountOverride { state: Some(slots()), ..Default::default() };
+        let state_diff = AccountOverride { state_diff: Some(slots()), ..Default::default() };
+        let call_options = |account_override| GethDebugTracingCallOptions {
+            state_overrides: Some(StateOverride::from_iter([(contract, account_override)])),
+            ..Default::default()
+        };
+
+        // returns the value of slot 1 loaded by the call, traced alone and in a bundle
+        let loaded = |account_override: AccountOverride| {
+            let debug_api = debug_api.clone();
+            let call = call.clone();
+            let call_options = call_options(account_override);
+            async move {
+                let loaded = |trace: &GethTrace| {
+                    let GethTrace::Default(frame) = trace else {
+                        panic!("expected struct logs, got {trace:?}")
+                    };
+                    // the stack after the SLOAD
+                    frame.struct_logs[2].stack.as_ref().unwrap()[0].to::<u64>()
+                };
+
+                let opts = DebugTracingCallOptions {
+                    call_options: call_options.clone(),
+                    ..Default::default()
+                };
+                let res = debug_api.debug_trace_call(call.clone(), None, opts).await.unwrap();
+                let DebugTraceCallResponse::Trace(trace) = res else {
+                    panic!("expected a plain trace, got {res:?}")
+                };
+
+                let bundle = DebugBundle { transactions: vec![call.into()], ..Default::default() };
+                let opts = DebugTracingCallManyOptions { call_options, ..Default::default() };
+                let traces =
+                    debug_api.debug_trace_call_many(vec![bundle], None, Some(opts)).await.unwrap();
+                (loaded(&trace), loaded(&traces[0].traces()[0]))
+            }
+        };
+
+        // unlisted slots read through the patched storage, but are zero in the replaced storage
+        assert_eq!(loaded(state_diff.clone()).await, (7, 7));
+        assert_eq!(loaded(state.clone()).await, (0, 0));
+
+        // an account can't be overridden with both forms
+        let both = AccountOverride { state: state.state, ..state_diff };
+        let opts =
+            DebugTracingCallOptions { call_options: call_options(both), ..Default::default() };
+        let err = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call,
+            None,
+            Some(opts),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), jsonrpsee::types::error::INVALID_PARAMS_CODE);
+    }
+
+    #[tokio::test]
+    async fn trace_call_moved_precompile() {
+        let debug_api = mock_debug_api();
+        let identity = Address::with_last_byte(4);
+        let moved = Address::random();
+        // MSTORE(0, 42) RETURN(0, 32)
+        let code = Bytes::from_static(&hex!("602a60005260206000f3"));
+        let state_overrides = StateOverride::from_iter([(
+            identity,
+            AccountOverride { code: Some(code), ..Default::default() },
+        )]);
+        let call_options = GethDebugTracingCallOptions {
+            tracing_options: GethDebugTracingOptions {
+                tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+                ..Default::default()
+            },
+            state_overrides: Some(state_overrides),
+            ..Default::default()
+        };
+        let input = Bytes::from_static(&hex!("deadbeef"));
+        // the word returned by the injected code
+        let injected = Bytes::from(B256::with_last_byte(42).0);
+        let call = |to| TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(to)),
+            input: input.clone().into(),
+            ..Default::default()
+        };
+        let output = |res: DebugTraceCallResponse| {
+            let DebugTraceCallResponse::Trace(GethTrace::CallTracer(frame)) = res else {
+                panic!("expected a call frame, got {res:?}")
+            };
+            frame.output.unwrap()
+        };
+
+        let moves = PrecompileMoves::from_iter([(identity, moved)]);
+        let opts = DebugTracingCallOptions {
+            precompile_moves: moves.clone(),
+            call_options: call_options.clone(),
+            ..Default::default()
+        };
+        // the injected code is executed at the address of the precompile
+        let res = debug_api.debug_trace_call(call(identity), None, opts.clone()).await.unwrap();
+        assert_eq!(output(res), injected);
+        // while the identity precompile echoes the input at its new address
+        let res = debug_api.debug_trace_call(call(moved), None, opts).await.unwrap();
+        assert_eq!(output(res), input);
+
+        // in bundles as well
+        let bundle = DebugBundle {
+            transactions: vec![call(identity).into(), call(moved).into()],
+            ..Default::default()
+        };
+        let opts = DebugTracingCallManyOptions {
+            precompile_moves: moves,
+            call_options: call_options.clone(),
+            ..Default::default()
+        };
+        let traces = debug_api.debug_trace_call_many(vec![bundle], None, Some(opts)).await.unwrap();
+        let outputs = traces[0]
+            .traces()
+            .iter()
+            .map(|trace| {
+                let GethTrace::CallTracer(frame) = trace else {
+                    panic!("expected a call frame, got {trace:?}")
+                };
+                frame.output.clone().unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(outputs, [injected.clone(), input.clone()]);
+
+        // moving onto an overridden account, or two precompiles to the same address, is rejected
+        let sha256 = Address::with_last_byte(2);
+        for moves in [vec![(sha256, identity)], vec![(identity, moved), (sha256, moved)]] {
+            let opts = DebugTracingCallOptions {
+                precompile_moves: moves.into_iter().collect(),
+                call_options: call_options.clone(),
+                ..Default::default()
+            };
+            let err = DebugApiServer::debug_trace_call(
+                &debug_api,
+                &Extensions::new(),
+                call(moved),
+                None,
+                Some(opts),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.code(), jsonrpsee::types::error::INVALID_PARAMS_CODE);
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_call_cfg_overrides() {
+        let debug_api = mock_debug_api();
+        // PUSH0 CHAINID PUSH0 MSTORE RETURN(0, 32), with PUSH0 since Shanghai
+        let chain_id = Address::random();
+        debug_api.inner.provider.add_account(
+            chain_id,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("5f465f5260205ff3"))),
+        );
+        // BASEFEE STOP, with BASEFEE since London
+        let base_fee = Address::random();
+        debug_api.inner.provider.add_account(
+            base_fee,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!("4800"))),
+        );
+        let call = |to| TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(to)),
+            ..Default::default()
+        };
+        let cfg_overrides = |spec: &str, chain_id| {
+            Some(CfgOverrides { spec: Some(spec.to_string()), chain_id, ..Default::default() })
+        };
+        let trace_call = |to, cfg_overrides| {
+            let opts = DebugTracingCallOptions { cfg_overrides, ..Default::default() };
+            debug_api.debug_trace_call(call(to), None, opts)
+        };
+        let frame = |trace: &GethTrace| {
+            let GethTrace::Default(frame) = trace else {
+                panic!("expected struct logs, got {trace:?}")
+            };
+            frame.clone()
+        };
+
+        // the block predates Shanghai
+        let res = trace_call(chain_id, None).await.unwrap();
+        assert!(matches!(res, DebugTraceCallResponse::Trace(_)));
+        assert!(frame(res.trace()).failed);
+        let res = trace_call(chain_id, cfg_overrides("prague", Some(7))).await.unwrap();
+        let DebugTraceCallResponse::WithExtras(res) = res else {
+            panic!("expected the spec, got {res:?}")
+        };
+        assert_eq!(res.spec.as_deref(), Some("Prague"));
+        assert_eq!(frame(&res.trace).return_value, Bytes::from(B256::with_last_byte(7).0));
+
+        // specs older than the one of the block can be used as well
+        assert!(!frame(trace_call(base_fee, None).await.unwrap().trace()).failed);
+        let res = trace_call(base_fee, cfg_overrides("berlin", None)).await.unwrap();
+        assert!(frame(res.trace()).failed);
+
+        let err = trace_call(base_fee, cfg_overrides("osaka", None)).await;
+        assert!(
+            matches!(err, Err(EthApiError::InvalidParams(msg)) if msg == "unknown spec: osaka")
+        );
+
+        // the bundles of a call many are executed with the overridden cfg
+        let bundle =
+            DebugBundle { transactions: vec![call(chain_id).into()], ..Default::default() };
+        let opts = DebugTracingCallManyOptions {
+            cfg_overrides: cfg_overrides("shanghai", Some(7)),
+            ..Default::default()
+        };
+        let traces = debug_api.debug_trace_call_many(vec![bundle], None, Some(opts)).await.unwrap();
+        let DebugTraceBundleResponse::WithExtras(res) = &traces[0] else {
+            panic!("expected the spec, got {traces:?}")
+        };
+        assert_eq!(res.spec.as_deref(), Some("Shanghai"));
+        assert_eq!(frame(&res.traces[0]).return_value, Bytes::from(B256::with_last_byte(7).0));
+    }
+
+    #[tokio::test]
+    async fn trace_call_blob_transaction() {
+        let debug_api = mock_debug_api();
+        // MSTORE(0, BLOBHASH(0)) RETURN(0, 32)
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("60004960005260206000f3"))),
+        );
+        let from = Address::random();
+        let balance = U256::from(GWEI_TO_WEI);
+        debug_api.inner.provider.add_account(from, ExtendedAccount::new(0, balance));
+        let blob_hash = |n: u8| {
+            let mut hash = B256::with_last_byte(n);
+            hash[0] = VERSIONED_HASH_VERSION_KZG;
+            hash
+        };
+        let call = |blobs: u8| TransactionRequest {
+            from: Some(from),
+            to: Some(TxKind::Call(contract)),
+            blob_versioned_hashes: Some((1..=blobs).map(blob_hash).collect()),
+            ..Default::default()
+        };
+        let cfg_overrides = Some(CfgOverrides {
+            spec: Some("cancun".to_string()),
+            blob_base_fee: Some(7),
+            ..Default::default()
+        });
+
+        // the call sees the versioned hashes of the request
+        let mut opts = DebugTracingCallOptions { cfg_overrides, ..Default::default() };
+        let res = debug_api.debug_trace_call(call(2), None, opts.clone()).await.unwrap();
+        let GethTrace::Default(frame) = res.trace() else {
+            panic!("expected struct logs, got {res:?}")
+        };
+        assert!(!frame.failed);
+        assert_eq!(frame.return_value, Bytes::from(blob_hash(1).0));
+
+        // the blob gas is charged at the overridden blob base fee
+        opts.call_options.tracing_options.tracer =
+            Some(GethDebugBuiltInTracerType::PreStateTracer.into());
+        opts.call_options.tracing_options.tracer_config =
+            GethDebugTracerConfig(serde_json::json!({ "diffMode": true }));
+        let res = debug_api.debug_trace_call(call(2), None, opts.clone()).await.unwrap();
+        let GethTrace::PreStateTracer(PreStateFrame::Diff(diff)) = res.trace() else {
+            panic!("expected prestate diff, got {res:?}")
+        };
+        let blob_fee = U256::from(2 * DATA_GAS_PER_BLOB * 7);
+        assert_eq!(diff.pre[&from].balance, Some(balance));
+        assert_eq!(diff.post[&from].balance, Some(balance - blob_fee));
+
+        // the number of blobs is limited to the blobs of a block
+        let err = debug_api
+            .debug_trace_call(call(MAX_BLOBS_PER_BLOCK as u8 + 1), None, opts)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::InvalidTransaction(RpcInvalidTransactionError::TooManyBlobs { max, have })
+                if max == MAX_BLOBS_PER_BLOCK && have == MAX_BLOBS_PER_BLOCK + 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn trace_call_block_hash_overrides() {
+        let debug_api = mock_debug_api();
+        let genesis_hash = debug_api.inner.provider.block_hash(0).unwrap().unwrap();
+        // MSTORE(0, BLOCKHASH(CALLDATALOAD(0))) RETURN(0, 32)
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("6000354060005260206000f3"))),
+        );
+        let overridden = B256::random();
+        let block_overrides = || BlockOverrides {
+            number: Some(U256::from(10)),
+            block_hash: Some(BTreeMap::from([(5, overridden)])),
+            ..Default::default()
+        };
+        let call = |number: u8| TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            input: TransactionInput::new(Bytes::from(B256::with_last_byte(number).0)),
+            ..Default::default()
+        };
+        let cfg_overrides = |spec: Option<&str>| {
+            spec.map(|spec| CfgOverrides { spec: Some(spec.to_string()), ..Default::default() })
+        };
+        let block_hash = |trace: &GethTrace| {
+            let GethTrace::Default(frame) = trace else {
+                panic!("expected struct logs, got {trace:?}")
+            };
+            B256::from_slice(&frame.return_value)
+        };
+
+        // before EIP-2935, BLOCKHASH reads the hashes of the database, since Prague the storage of
+        // the history contract
+        for spec in [None, Some("prague")] {
+            let opts = DebugTracingCallOptions {
+                call_options: GethDebugTracingCallOptions {
+                    block_overrides: Some(block_overrides()),
+                    ..Default::default()
+                },
+                cfg_overrides: cfg_overrides(spec),
+                ..Default::default()
+            };
+            let res = debug_api.debug_trace_call(call(5), None, opts).await.unwrap();
+            assert_eq!(block_hash(res.trace()), overridden, "{spec:?}");
+
+            let bundle = DebugBundle {
+                transactions: vec![call(5).into()],
+                block_override: Some(block_overrides()),
+                ..Default::default()
+            };
+            let opts = DebugTracingCallManyOptions {
+                cfg_overrides: cfg_overrides(spec),
+                ..Default::default()
+            };
+            let traces =
+                debug_api.debug_trace_call_many(vec![bundle], None, Some(opts)).await.unwrap();
+            assert_eq!(block_hash(&traces[0].traces()[0]), overridden, "{spec:?}");
+        }
+
+        // hashes that aren't overridden are read from the provider
+        let opts = DebugTracingCallOptions {
+            call_options: GethDebugTracingCallOptions {
+                block_overrides: Some(block_overrides()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let res = debug_api.debug_trace_call(call(0), None, opts).await.unwrap();
+        assert_eq!(block_hash(res.trace()), genesis_hash);
+        let bundle = DebugBundle {
+            transactions: vec![call(0).into()],
+            block_override: Some(block_overrides()),
+            ..Default::default()
+        };
+        let traces = debug_api.debug_trace_call_many(vec![bundle], None, None).await.unwrap();
+        assert_eq!(block_hash(&traces[0].traces()[0]), genesis_hash);
+    }
+
+    /// Returns the address of a new account and its authorization, valid on any chain, to
+    /// delegate to the code of the given address.
+    fn sign_authorization(delegate: Address) -> (Address, SignedAuthorization) {
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let authorization = Authorization { chain_id: U256::ZERO, address: delegate, nonce: 0 };
+        let secret = B256::from_slice(&key_pair.secret_bytes());
+        let signature = sign_message(secret, authorization.signature_hash()).unwrap();
+        let signature = alloy_primitives::Signature::from_rs_and_parity(
+            signature.r,
+            signature.s,
+            signature.odd_y_parity,
+        )
+        .unwrap();
+        (public_key_to_address(key_pair.public_key()), authorization.into_signed(signature))
+    }
+
+    #[tokio::test]
+    async fn trace_call_authorization_list() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let (authority, authorization) = sign_authorization(counter);
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(authority)),
+            authorization_list: Some(vec![authorization]),
+            ..Default::default()
+        };
+        let mut opts = DebugTracingCallOptions::default();
+        opts.call_options.tracing_options.tracer =
+            Some(GethDebugBuiltInTracerType::PreStateTracer.into());
+        opts.call_options.tracing_options.tracer_config =
+            GethDebugTracerConfig(serde_json::json!({ "diffMode": true }));
+
+        // the block predates Prague, which has to be selected explicitly
+        let err = debug_api.debug_trace_call(call.clone(), None, opts.clone()).await.unwrap_err();
+        let expected =
+            "authorization lists require the Prague spec, not Frontier, see cfgOverrides";
+        assert!(matches!(err, EthApiError::InvalidParams(msg) if msg == expected));
+
+        // the prestate tracer reports the delegation of the authority, and the changes of the
+        // delegated code to its storage
+        opts.cfg_overrides =
+            Some(CfgOverrides { spec: Some("prague".to_string()), ..Default::default() });
+        let res = debug_api.debug_trace_call(call, None, opts).await.unwrap();
+        let GethTrace::PreStateTracer(PreStateFrame::Diff(diff)) = res.trace() else {
+            panic!("expected prestate diff, got {res:?}")
+        };
+        let delegation = [&hex!("ef0100")[..], counter.as_slice()].concat();
+        assert_eq!(diff.pre.get(&authority).and_then(|account| account.code.clone()), None);
+        let post = &diff.post[&authority];
+        assert_eq!(post.code, Some(Bytes::from(delegation)));
+        assert_eq!(post.nonce, Some(1));
+        assert_eq!(post.storage.get(&B256::ZERO), Some(&B256::with_last_byte(1)));
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_authorization_list() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let (authority, authorization) = sign_authorization(counter);
+        let call = |authorization_list| TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(authority)),
+            authorization_list,
+            ..Default::default()
+        };
+        let bundle = DebugBundle {
+            transactions: vec![call(Some(vec![authorization])).into(), call(None).into()],
+            ..Default::default()
+        };
+        let mut opts = DebugTracingCallManyOptions::default();
+        opts.call_options.tracing_options.tracer =
+            Some(GethDebugBuiltInTracerType::PreStateTracer.into());
+        opts.call_options.tracing_options.tracer_config =
+            GethDebugTracerConfig(serde_json::json!({ "diffMode": true }));
+
+        let err = debug_api
+            .debug_trace_call_many(vec![bundle.clone()], None, Some(opts.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(_)));
+
+        // the delegation of the first transaction applies to the following ones
+        opts.cfg_overrides =
+            Some(CfgOverrides { spec: Some("prague".to_string()), ..Default::default() });
+        let traces = debug_api.debug_trace_call_many(vec![bundle], None, Some(opts)).await.unwrap();
+        let slots = traces[0]
+            .traces()
+            .iter()
+            .map(|trace| {
+                let GethTrace::PreStateTracer(PreStateFrame::Diff(diff)) = trace else {
+                    panic!("expected prestate diff, got {trace:?}")
+                };
+                diff.post[&authority].storage[&B256::ZERO]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(slots, vec![B256::with_last_byte(1), B256::with_last_byte(2)]);
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_independent_bundles() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let call = || TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(counter)),
+            ..Default::default()
+        };
+        // more bundles than permits, the bundle with index `i` increments the counter `i + 1` times
+        let bundles = (0..5)
+            .map(|i| DebugBundle {
+                transactions: (0..=i).map(|_| call().into()).collect(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let loaded = |traces: &[GethTrace]| {
+            traces
+                .iter()
+                .map(|trace| {
+                    let GethTrace::Default(frame) = trace else {
+                        panic!("expected struct logs, got {trace:?}")
+                    };
+                    // the stack after the SLOAD
+                    frame.struct_logs[2].stack.as_ref().unwrap()[0].to::<u64>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let opts = DebugTracingCallManyOptions { independent_bundles: true, ..Default::default() };
+        let traces =
+            debug_api.debug_trace_call_many(bundles.clone(), None, Some(opts)).await.unwrap();
+        assert_eq!(traces.len(), bundles.len());
+
+        for (i, (bundle, traces)) in bundles.into_iter().zip(traces).enumerate() {
+            // no bundle observes the writes of the preceding ones
+            assert_eq!(loaded(traces.traces()), (0..=i as u64).collect::<Vec<_>>());
+            // which is the same as tracing the bundle alone
+            let alone = debug_api.debug_trace_call_many(vec![bundle], None, None).await.unwrap();
+            assert_eq!(alone, [traces]);
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_state_diff() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let (first, second) = (Address::random(), Address::random());
+        let call = |from| TransactionRequest {
+            from: Some(from),
+            to: Some(TxKind::Call(counter)),
+            ..Default::default()
+        };
+        let bundles = || {
+            vec![
+                DebugBundle {
+                    transactions: vec![call(first).into(), call(first).into()],
+                    ..Default::default()
+                },
+                DebugBundle { transactions: vec![call(second).into()], ..Default::default() },
+            ]
+        };
+
+        // the response shape is unchanged unless requested
+        let traces = debug_api.debug_trace_call_many(bundles(), None, None).await.unwrap();
+        assert!(traces.iter().all(|res| matches!(res, DebugTraceBundleResponse::Traces(_))));
+
+        let opts = DebugTracingCallManyOptions { return_state_diff: true, ..Default::default() };
+        let traces = debug_api.debug_trace_call_many(bundles(), None, Some(opts)).await.unwrap();
+        let diffs = traces
+            .into_iter()
+            .map(|res| {
+                let DebugTraceBundleResponse::WithExtras(res) = res else {
+                    panic!("expected extras, got {res:?}")
+                };
+                res.state_diff.unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let nonce =
+            |from, to| AccountDiff { nonce: ValueChange::new(from, to), ..Default::default() };
+        let counter_diff = |from: u8, to: u8| AccountDiff {
+            storage: BTreeMap::from([(
+                B256::ZERO,
+                ValueChange { from: B256::with_last_byte(from), to: B256::with_last_byte(to) },
+            )]),
+            ..Default::default()
+        };
+        // the diff covers all commits of the bundle, on top of the preceding bundles
+        assert_eq!(
+            diffs,
+            [
+                BTreeMap::from([(first, nonce(0, 2)), (counter, counter_diff(0, 2))]),
+                BTreeMap::from([(second, nonce(0, 1)), (counter, counter_diff(2, 3))]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_call_many_raw_transactions() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(counter)),
+            ..Default::default()
+        };
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let raw = sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Legacy(TxLegacy {
+                gas_limit: 100_000,
+                to: TxKind::Call(counter),
+                ..Default::default()
+            }),
+        )
+        .envelope_encoded();
+
+        // raw transactions and calls can be mixed
+        let bundle = DebugBundle {
+            transactions: vec![call.clone().into(), raw.clone().into(), call.clone().into()],
+            ..Default::default()
+        };
+        let traces = debug_api.debug_trace_call_many(vec![bundle], None, None).await.unwrap();
+        let loaded = traces[0]
+            .traces()
+            .iter()
+            .map(|trace| {
+                let GethTrace::Default(frame) = trace else {
+                    panic!("expected struct logs, got {trace:?}")
+                };
+                // the stack after the SLOAD
+                frame.struct_logs[2].stack.as_ref().unwrap()[0].to::<u64>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(loaded, [0, 1, 2]);
+
+        // decoding failures identify the transaction
+        let bundles = vec![
+            DebugBundle { transactions: vec![raw.into()], ..Default::default() },
+            DebugBundle {
+                transactions: vec![call.into(), Bytes::from_static(&[0x01]).into()],
+                ..Default::default()
+            },
+        ];
+        let err = debug_api.debug_trace_call_many(bundles, None, None).await.unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(msg)
+            if msg == "invalid raw transaction 1 of bundle 1: failed to decode signed transaction"));
+    }
+
+    #[tokio::test]
+    async fn trace_calls_on_pending_block() {
+        let debug_api = mock_debug_api();
+        let hashes = extend_mock_chain(&debug_api.inner.provider);
+        // NUMBER STOP
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!("4300"))),
+        );
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        let number = |trace: &GethTrace| {
+            let GethTrace::Default(frame) = trace else {
+                panic!("expected struct logs, got {trace:?}")
+            };
+            frame.struct_logs.last().unwrap().stack.as_ref().unwrap()[0].to::<u64>()
+        };
+
+        // the calls are traced in the environment of the locally built pending block
+        let res = debug_api
+            .debug_trace_call(call.clone(), Some(BlockId::pending()), Default::default())
+            .await
+            .unwrap();
+        let DebugTraceCallResponse::Trace(trace) = res else {
+            panic!("expected a trace, got {res:?}")
+        };
+        assert_eq!(number(&trace), hashes.len() as u64);
+
+        let pending = |transaction_index| StateContext {
+            transaction_index,
+            block_number: Some(BlockId::pending()),
+        };
+        let bundles =
+            || vec![DebugBundle { transactions: vec![call.clone().into()], ..Default::default() }];
+        let traces =
+            debug_api.debug_trace_call_many(bundles(), Some(pending(None)), None).await.unwrap();
+        assert_eq!(number(&traces[0].traces()[0]), hashes.len() as u64);
+
+        // the transactions to replay are those of the pending block, which is empty
+        let state_context = pending(Some(TransactionIndex::Index(1)));
+        let err = debug_api.debug_trace_call_many(bundles(), Some(state_context), None).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg))
+            if msg == "transaction index 1 out of range, block has 0 transactions"));
+    }
+
+    /// A network that reports that the node is syncing.
+    #[derive(Clone, Debug)]
+    struct SyncingNetwork;
+
+    impl NetworkInfo for SyncingNetwork {
+        fn local_addr(&self) -> SocketAddr {
+            NoopNetwork::default().local_addr()
+        }
+
+        async fn network_status(&self) -> Result<NetworkStatus, NetworkError> {
+            NoopNetwork::default().network_status().await
+        }
+
+        fn chain_id(&self) -> u64 {
+            NoopNetwork::default().chain_id()
+        }
+
+        fn is_syncing(&self) -> bool {
+            true
+        }
+
+        fn is_initially_syncing(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_while_syncing() {
+        let debug_api = mock_debug_api_with_network(SyncingNetwork);
+        let provider = &debug_api.inner.provider;
+        let genesis = Some(provider.block_hash(0).unwrap().unwrap().into());
+        add_block_with_calls(provider, Address::random(), 1);
+        let call = TransactionRequest { from: Some(Address::random()), ..Default::default() };
+        let bundles =
+            vec![DebugBundle { transactions: vec![call.clone().into()], ..Default::default() }];
+        let syncing = |executed, stages| {
+            ErrorObjectOwned::from(EthApiError::NodeSyncing { executed, stages })
+        };
+        let latest = BlockNumberOrTag::Latest;
+
+        // without stage checkpoints, the state is executed up to the best block
+        let err = DebugApiServer::debug_trace_block_by_number(
+            &debug_api,
+            &Extensions::new(),
+            latest,
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), syncing(1, Vec::new()));
+        let err = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call.clone(),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), syncing(1, Vec::new()));
+        let err = DebugApiServer::debug_trace_call_many(
+            &debug_api,
+            &Extensions::new(),
+            bundles.clone(),
+            None,
+            None,
+        );
+        assert_eq!(err.await.unwrap_err(), syncing(1, Vec::new()));
+        // unless the caller explicitly accepts the sync head
+        let opts = DebugTracingBlockOptions { allow_while_syncing: true, ..Default::default() };
+        DebugApiServer::debug_trace_block_by_number(
+            &debug_api,
+            &Extensions::new(),
+            latest,
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        let opts = DebugTracingCallManyOptions { allow_while_syncing: true, ..Default::default() };
+        DebugApiServer::debug_trace_call_many(
+            &debug_api,
+            &Extensions::new(),
+            bundles,
+            None,
+            Some(opts),
+        )
+        .await
+        .unwrap();
+        // historical blocks are fully executed
+        DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call.clone(),
+            genesis,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // the pipeline executes the state behind the downloaded headers
+        provider.set_stage_checkpoint(StageId::Headers, StageCheckpoint::new(5));
+        provider.set_stage_checkpoint(StageId::Execution, StageCheckpoint::new(0));
+        let stages = vec![
+            Stage { name: StageId::Execution.to_string(), block: 0 },
+            Stage { name: StageId::Headers.to_string(), block: 5 },
+        ];
+        let err = DebugApiServer::debug_trace_call(
+            &debug_api,
+            &Extensions::new(),
+            call.clone(),
+            genesis,
+            None,
+        )
+        .await;
+        assert_eq!(err.unwrap_err(), syncing(0, stages));
+        let opts = DebugTracingCallOptions { allow_while_syncing: true, ..Default::default() };
+        DebugApiServer::debug_trace_call(&debug_api, &Extensions::new(), call, genesis, Some(opts))
+            .await
+            .unwrap();
+
+        // a synced node traces the latest block
+        let debug_api = mock_debug_api();
+        add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+        DebugApiServer::debug_trace_block_by_number(&debug_api, &Extensions::new(), latest, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn pruned_historical_state() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let genesis = provider.block_hash(0).unwrap().unwrap();
+        let (block_hash, tx_hashes) = add_block_with_calls(provider, Address::random(), 1);
+        let trace_block = || debug_api.debug_trace_block(block_hash.into(), Default::default());
+        let trace_call = |block: B256| {
+            let call = TransactionRequest { from: Some(Address::random()), ..Default::default() };
+            debug_api.debug_trace_call(call, Some(block.into()), Default::default())
+        };
+        trace_block().await.unwrap();
+
+        // the account history is pruned up to block 1, the storage history less far
+        for (segment, block_number) in
+            [(PruneSegment::AccountHistory, 1), (PruneSegment::StorageHistory, 0)]
+        {
+            let checkpoint = PruneCheckpoint {
+                block_number: Some(block_number),
+                tx_number: None,
+                prune_mode: PruneMode::Distance(64),
+            };
+            provider.set_prune_checkpoint(segment, checkpoint);
+        }
+        let pruned = |res: Result<_, EthApiError>| {
+            matches!(res, Err(EthApiError::HistoricalStateUnavailable { block: 0, earliest: 1 }))
+        };
+
+        // the block and its transactions are replayed on top of the pruned state of the parent
+        assert!(pruned(trace_block().await.map(drop)));
+        let opts = Default::default();
+        assert!(pruned(debug_api.debug_trace_transaction(tx_hashes[0], opts).await.map(drop)));
+        let witness =
+            debug_api.debug_execution_witness(block_hash.into(), false, false, false, false, false);
+        assert!(pruned(witness.await.map(drop)));
+        assert!(pruned(trace_call(genesis).await.map(drop)));
+        // the state at the earliest available block can be traced
+        trace_call(block_hash).await.unwrap();
+
+        let err = DebugApiServer::debug_trace_block_by_number(
+            &debug_api,
+            &Extensions::new(),
+            1.into(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), HISTORICAL_STATE_UNAVAILABLE_CODE);
+        assert_eq!(
+            err.message(),
+            "historical state for block 0 is unavailable; earliest available state is block 1"
+        );
+        assert_eq!(err.data().unwrap().get(), r#"{"block":0,"earliestAvailableBlock":1}"#);
+
+        // state that turns out to be pruned while it's read, e.g. by the proofs of a traced call,
+        // is reported the same way
+        let err = ErrorObjectOwned::from(debug_api.state_pruned_error(0));
+        assert_eq!(err.code(), HISTORICAL_STATE_UNAVAILABLE_CODE);
+        assert_eq!(err.data().unwrap().get(), r#"{"block":0,"earliestAvailableBlock":1}"#);
+    }
+
+    #[tokio::test]
+    async fn trace_calls_at_transaction_index() {
+        let debug_api = mock_debug_api();
+        let counter = deploy_counter(&debug_api.inner.provider);
+        let genesis = debug_api.inner.provider.block_hash(0).unwrap().unwrap();
+        let (block_hash, _) = add_block_with_calls(&debug_api.inner.provider, counter, 2);
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(counter)),
+            ..Default::default()
+        };
+        // the value of the counter the call loaded
+        let loaded = |trace: &GethTrace| {
+            let GethTrace::Default(frame) = trace else {
+                panic!("expected struct logs, got {trace:?}")
+            };
+            frame.struct_logs[2].stack.as_ref().unwrap()[0].to::<u64>()
+        };
+        let out_of_range = |index: usize, block_txs: usize| {
+            format!("transaction index {index} out of range, block has {block_txs} transactions")
+        };
+
+        let trace_call = |block: B256, tx_index| {
+            let opts = DebugTracingCallOptions { tx_index, ..Default::default() };
+            debug_api.debug_trace_call(call.clone(), Some(block.into()), opts)
+        };
+        for tx_index in 0..2 {
+            let res = trace_call(block_hash, Some(tx_index)).await.unwrap();
+            let DebugTraceCallResponse::Trace(trace) = res else {
+                panic!("expected a trace, got {res:?}")
+            };
+            assert_eq!(loaded(&trace), tx_index);
+        }
+        // the number of transactions positions the call after all of them, like no index
+        let after_all = trace_call(block_hash, None).await.unwrap();
+        assert_eq!(trace_call(block_hash, Some(2)).await.unwrap(), after_all);
+        let err = trace_call(block_hash, Some(3)).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg)) if msg == out_of_range(3, 2)));
+        let err = trace_call(block_hash, Some(u64::MAX)).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg))
+            if msg == out_of_range(usize::MAX, 2)));
+        // the only position within an empty block is the start
+        trace_call(genesis, Some(0)).await.unwrap();
+        let err = trace_call(genesis, Some(1)).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg)) if msg == out_of_range(1, 0)));
+
+        let trace_call_many = |block: B256, transaction_index| {
+            let bundles =
+                vec![DebugBundle { transactions: vec![call.clone().into()], ..Default::default() }];
+            let state_context =
+                StateContext { transaction_index, block_number: Some(block.into()) };
+            debug_api.debug_trace_call_many(bundles, Some(state_context), None)
+        };
+        let traces = trace_call_many(block_hash, Some(TransactionIndex::Index(1))).await.unwrap();
+        assert_eq!(loaded(&traces[0].traces()[0]), 1);
+        // `-1`, no index and the number of transactions all replay the whole block
+        let after_all = trace_call_many(block_hash, None).await.unwrap();
+        for index in [TransactionIndex::All, TransactionIndex::Index(2)] {
+            assert_eq!(trace_call_many(block_hash, Some(index)).await.unwrap(), after_all);
+        }
+        let err = trace_call_many(block_hash, Some(TransactionIndex::Index(3))).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg)) if msg == out_of_range(3, 2)));
+        trace_call_many(genesis, Some(TransactionIndex::Index(0))).await.unwrap();
+        let err = trace_call_many(genesis, Some(TransactionIndex::Index(1))).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg)) if msg == out_of_range(1, 0)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn trace_call_many_while_head_advances() {
+        let debug_api = mock_debug_api();
+        let provider = debug_api.inner.provider.clone();
+        // SSTORE(0, CALLDATALOAD(0)) with calldata, otherwise RETURN(SLOAD(0), NUMBER)
+        let contract = Address::random();
+        provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!(
+                "366013576000546000524360205260406000f35b60003560005500"
+            ))),
+        );
+
+        // each block has a single transaction that stores the number of the block
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let add_block = move |number: u64| {
+            let tx = Transaction::Legacy(TxLegacy {
+                gas_limit: 100_000,
+                to: TxKind::Call(contract),
+                input: U256::from(number).to_be_bytes::<32>().into(),
+                ..Default::default()
+            });
+            let header = Header {
+                number,
+                parent_hash: provider.block_hash(number - 1).unwrap().unwrap(),
+                gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+                ..Default::default()
+            };
+            let body = vec![sign_tx_with_key_pair(key_pair, tx)];
+            provider.add_block(header.hash_slow(), Block { header, body, ..Default::default() });
+        };
+        add_block(1);
+        let head = tokio::spawn(async move {
+            for number in 2..=200 {
+                add_block(number);
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let call = TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            ..Default::default()
+        };
+        while !head.is_finished() {
+            let bundles =
+                vec![DebugBundle { transactions: vec![call.clone().into()], ..Default::default() }];
+            let state_context = StateContext {
+                transaction_index: Some(TransactionIndex::Index(1)),
+                block_number: Some(BlockId::latest()),
+            };
+            let traces =
+                debug_api.debug_trace_call_many(bundles, Some(state_context), None).await.unwrap();
+            let GethTrace::Default(frame) = &traces[0].traces()[0] else {
+                panic!("expected struct logs, got {traces:?}")
+            };
+            // the replayed transaction is the one of the block the call is executed in
+            let output = &frame.return_value;
+            assert_eq!(output[..32], output[32..], "{output}");
+        }
+        head.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn trace_call_on_pending_block_from_pool() {
+        let debug_api = mock_debug_api();
+        // BALANCE(CALLDATALOAD(0)) STOP
+        let contract = Address::random();
+        debug_api.inner.provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("6000353100"))),
+        );
+        let balance_of = |account: Address| TransactionRequest {
+            from: Some(Address::random()),
+            to: Some(TxKind::Call(contract)),
+            input: TransactionInput::new(B256::left_padding_from(account.as_slice()).into()),
+            ..Default::default()
+        };
+        let opts =
+            || DebugTracingCallOptions { build_pending_from_pool: true, ..Default::default() };
+
+        let err = debug_api.debug_trace_call(balance_of(Address::random()), None, opts()).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg)) if msg ==
+            "can't build the pending block, the transaction pool has no pending transactions"));
+
+        // a transfer that is only in the pool
+        let sender = Address::random();
+        let ether = U256::from(GWEI_TO_WEI) * U256::from(GWEI_TO_WEI);
+        debug_api.inner.provider.add_account(sender, ExtendedAccount::new(0, ether));
+        let transfer = MockTransaction::legacy()
+            .with_sender(sender)
+            .with_gas_limit(21_000)
+            .with_gas_price(GWEI_TO_WEI as u128)
+            .with_value(U256::from(5));
+        let recipient = transfer.to().unwrap();
+        let tx_hash = debug_api.eth_api().pool().add_external_transaction(transfer).await.unwrap();
+
+        // the call observes the transfer, and the transfer is reported
+        let res = debug_api.debug_trace_call(balance_of(recipient), None, opts()).await.unwrap();
+        let DebugTraceCallResponse::WithExtras(res) = res else {
+            panic!("expected extras, got {res:?}")
+        };
+        assert_eq!(res.pool_transactions, Some(vec![tx_hash]));
+        let GethTrace::Default(frame) = res.trace else {
+            panic!("expected struct logs, got {:?}", res.trace)
+        };
+        assert_eq!(frame.struct_logs.last().unwrap().stack.as_ref().unwrap()[0], U256::from(5));
+
+        // the block is built on top of the latest block, so it can't be combined with another one
+        let at = Some(BlockId::latest());
+        let err = debug_api.debug_trace_call(balance_of(recipient), at, opts()).await;
+        assert!(matches!(err, Err(EthApiError::InvalidParams(msg))
+            if msg == "buildPendingFromPool requires the pending block"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn trace_call_many_cancelled() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let debug_api = reth_metrics::metrics::with_local_recorder(&recorder, mock_debug_api);
+        // JUMPDEST PUSH1 0 JUMP, which loops until the call runs out of gas
+        let looping = Address::random();
+        debug_api.inner.provider.add_account(
+            looping,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("5b600056"))),
+        );
+        let call = TransactionRequest {
+            to: Some(TxKind::Call(looping)),
+            gas: Some(10_000_000),
+            ..Default::default()
+        };
+        let bundle = DebugBundle { transactions: vec![call.into(); 10_000], ..Default::default() };
+        let tracing_options = GethDebugTracingOptions::default()
+            .with_tracer(GethDebugBuiltInTracerType::FourByteTracer.into());
+        let opts = GethDebugTracingCallOptions { tracing_options, ..Default::default() };
+
+        // the client gives up on the request while the calls are traced
+        let trace = DebugApiServer::debug_trace_call_many(
+            &debug_api,
+            &Extensions::new(),
+            vec![bundle],
+            None,
+            Some(opts.into()),
+        );
+        assert!(tokio::time::timeout(Duration::from_millis(500), trace).await.is_err());
+
+        // the blocking task stops after the current call instead of tracing all of them
+        let key = CompositeKey::new(
+            MetricKind::Histogram,
+            reth_metrics::metrics::Key::from_parts(
+                "rpc.debug.execution_time_seconds",
+                vec![reth_metrics::metrics::Label::new("class", "call")],
+            ),
+        );
+        let finished = async {
+            loop {
+                let snapshot = snapshotter.snapshot().into_hashmap();
+                if let Some((_, _, DebugValue::Histogram(samples))) = snapshot.get(&key) {
+                    if !samples.is_empty() {
+                        return
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), finished).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn trace_permit_max_wait() {
+        let debug_api = mock_debug_api().with_max_trace_permit_wait(Duration::from_millis(10));
+        let (block_hash, tx_hashes) =
+            add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+
+        // requests are rejected instead of waiting for a permit indefinitely
+        let _permits =
+            debug_api.inner.blocking_task_guard.clone().acquire_many_owned(4).await.unwrap();
+        let busy = |err: ErrorObjectOwned| {
+            assert_eq!(err.code(), jsonrpsee::types::error::SERVER_IS_BUSY_CODE);
+            assert!(err.message().starts_with("tracer capacity exceeded, 1 requests queued"));
+        };
+        busy(
+            DebugApiServer::debug_trace_transaction(
+                &debug_api,
+                &Extensions::new(),
+                tx_hashes[0],
+                None,
+            )
+            .await
+            .unwrap_err(),
+        );
+        busy(
+            DebugApiServer::debug_trace_block_by_hash(
+                &debug_api,
+                &Extensions::new(),
+                block_hash,
+                None,
+            )
+            .await
+            .unwrap_err(),
+        );
+        let call = TransactionRequest::default();
+        busy(
+            DebugApiServer::debug_trace_call(&debug_api, &Extensions::new(), call, None, None)
+                .await
+                .unwrap_err(),
+        );
+        busy(
+            DebugApiServer::debug_execution_witness(
+                &debug_api,
+                &Extensions::new(),
+                block_hash.into(),
+                false,
+                None,
+            )
+            .await
+            .unwrap_err(),
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_permits_of_separate_classes() {
+        let debug_api = mock_debug_api().with_trace_permits(TraceClass::Witness, 1);
+        let wait = Duration::from_millis(100);
+
+        // saturate witness generation
+        let _witness = debug_api.acquire_trace_permit(TraceClass::Witness).await.unwrap();
+        let witness = DebugApiServer::debug_execution_witness(
+            &debug_api,
+            &Extensions::new(),
+            BlockId::latest(),
+            false,
+            None,
+        );
+        assert!(tokio::time::timeout(wait, witness).await.is_err());
+
+        // call traces are still served
+        let call = TransactionRequest::default();
+        let trace =
+            DebugApiServer::debug_trace_call(&debug_api, &Extensions::new(), call, None, None);
+        assert!(tokio::time::timeout(wait, trace).await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn reserved_call_permits() {
+        // all 4 permits of the guard can't be reserved
+        assert!(mock_debug_api().with_reserved_call_permits(4).is_err());
+        let debug_api = mock_debug_api().with_reserved_call_permits(1).unwrap();
+        let (block_hash, _) = add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+        let wait = Duration::from_millis(100);
+
+        // block traces occupy all permits they may take
+        let mut block_traces = Vec::new();
+        for _ in 0..3 {
+            block_traces.push(debug_api.acquire_trace_permit(TraceClass::Block).await.unwrap());
+        }
+        let block = DebugApiServer::debug_trace_block_by_hash(
+            &debug_api,
+            &Extensions::new(),
+            block_hash,
+            None,
+        );
+        assert!(tokio::time::timeout(wait, block).await.is_err());
+
+        // the reserved permit serves call traces
+        let call = TransactionRequest::default();
+        let trace =
+            DebugApiServer::debug_trace_call(&debug_api, &Extensions::new(), call, None, None);
+        assert!(tokio::time::timeout(wait, trace).await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn dedicated_trace_pool() {
+        let debug_api = mock_debug_api().with_dedicated_trace_pool(Some(1)).unwrap();
+        let (_, tx_hashes) = add_block_with_calls(&debug_api.inner.provider, Address::random(), 1);
+
+        let thread = debug_api
+            .spawn_tracing(|_| {
+                Ok::<_, EthApiError>(std::thread::current().name().map(String::from))
+            })
+            .await
+            .unwrap();
+        assert_eq!(thread.as_deref(), Some("debug-trace-0"));
+        DebugApiServer::debug_trace_transaction(&debug_api, &Extensions::new(), tx_hashes[0], None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn trace_transaction_in_block() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let genesis_hash = provider.block_hash(0).unwrap().unwrap();
+        let (block_hash, tx_hashes) = add_block_with_calls(provider, counter, 2);
+        let tx_hash = tx_hashes[1];
+
+        let opts = GethDebugTracingOptions::default()
+            .with_tracer(GethDebugBuiltInTracerType::PreStateTracer.into());
+        let trace = debug_api
+            .debug_trace_transaction_in_block(tx_hash, block_hash, opts.clone())
+            .await
+            .unwrap();
+        // the transaction is traced on top of the preceding transaction
+        let GethTrace::PreStateTracer(PreStateFrame::Default(prestate)) = &trace else {
+            panic!("expected prestate trace, got {trace:?}")
+        };
+        let storage = &prestate.0[&counter].storage;
+        assert_eq!(storage[&B256::ZERO], B256::with_last_byte(1));
+        assert_eq!(trace, debug_api.debug_trace_transaction(tx_hash, opts.clone()).await.unwrap());
+
+        let err = debug_api
+            .debug_trace_transaction_in_block(tx_hash, genesis_hash, opts)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("transaction {tx_hash} not found in block {genesis_hash}")
+        );
+    }
+
+    #[tokio::test]
+    async fn execution_witness_codes() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        // the code of this contract is only inspected, never executed
+        let inspected = Address::random();
+        let inspected_code = Bytes::from_static(COUNTER_CODE);
+        provider.add_account(
+            inspected,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(inspected_code.clone()),
+        );
+        // EXTCODESIZE(inspected) POP STOP
+        let caller = Address::random();
+        let caller_code =
+            Bytes::from([&hex!("73")[..], inspected.as_slice(), &hex!("3b5000")].concat());
+        provider.add_account(
+            caller,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(caller_code.clone()),
+        );
+        add_block_with_calls(provider, caller, 2);
+
+        let block = BlockNumberOrTag::Number(1).into();
+        let witness = debug_api
+            .debug_execution_witness(block, false, false, false, false, false)
+            .await
+            .unwrap();
+        assert!(witness.codes.is_none());
+
+        // the code of the caller is loaded by both transactions, but only included once
+        let witness = debug_api
+            .debug_execution_witness(block, false, true, false, false, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            witness.codes.unwrap(),
+            BTreeMap::from_iter([
+                (keccak256(&caller_code), caller_code),
+                (keccak256(&inspected_code), inspected_code),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn execution_witness_encoding() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        add_block_with_calls(provider, counter, 2);
+        let block = BlockNumberOrTag::Number(1).into();
+        let witness = debug_api
+            .debug_execution_witness(block, true, true, false, false, false)
+            .await
+            .unwrap();
+
+        let res = DebugApiServer::debug_execution_witness(
+            &debug_api,
+            &Extensions::new(),
+            block,
+            true,
+            Some(ExecutionWitnessOptions { include_codes: true, ..Default::default() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(res, ExecutionWitnessResponse::Witness(witness.clone()));
+
+        let res = DebugApiServer::debug_execution_witness(
+            &debug_api,
+            &Extensions::new(),
+            block,
+            true,
+            Some(ExecutionWitnessOptions {
+                include_codes: true,
+                encoding: WitnessEncoding::Snappy,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let ExecutionWitnessResponse::Encoded(encoded) = &res else {
+            panic!("expected encoded witness, got {res:?}")
+        };
+        assert_eq!(encoded.codes, Some(1));
+        assert_eq!(res.into_witness().unwrap(), witness);
+    }
+
+    #[tokio::test]
+    async fn execution_witness_deterministic() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        // PUSH1(i) POP STOP, a distinct code for each of the callees
+        let callees = (0..4).map(|_| Address::random()).collect::<Vec<_>>();
+        // POP(CALL(GAS, callee, 0, 0, 0, 0, 0)) for each of the callees
+        let mut caller_code = Vec::new();
+        for (i, callee) in callees.iter().enumerate() {
+            provider.add_account(
+                *callee,
+                ExtendedAccount::new(0, U256::ZERO)
+                    .with_bytecode(Bytes::from(vec![0x60, i as u8, 0x50, 0x00])),
+            );
+            caller_code.extend(hex!("6000600060006000600073"));
+            caller_code.extend_from_slice(callee.as_slice());
+            caller_code.extend(hex!("5af150"));
+        }
+        let caller = Address::random();
+        provider.add_account(
+            caller,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(caller_code.into()),
+        );
+        add_block_with_calls(provider, caller, 2);
+
+        // the witness is generated twice, each time with newly built maps
+        let block = BlockNumberOrTag::Number(1).into();
+        let first =
+            debug_api.debug_execution_witness(block, true, true, false, false, true).await.unwrap();
+        let second =
+            debug_api.debug_execution_witness(block, true, true, false, false, true).await.unwrap();
+        assert_eq!(first.codes.as_ref().map(BTreeMap::len), Some(callees.len() + 1));
+        assert_eq!(serde_json::to_vec(&first).unwrap(), serde_json::to_vec(&second).unwrap());
+    }
+
+    #[tokio::test]
+    async fn execution_witness_headers() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        // POP(BLOCKHASH(0)) STOP
+        let contract = Address::random();
+        provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("6000405000"))),
+        );
+        add_block_with_calls(provider, contract, 1);
+
+        // the hash of the genesis block is proven by its header
+        let witness = debug_api
+            .debug_execution_witness(
+                BlockNumberOrTag::Number(1).into(),
+                false,
+                false,
+                false,
+                false,
+                false,
+            )
+            .await
+            .unwrap();
+        let genesis = provider.header_by_number(0).unwrap().unwrap();
+        assert_eq!(witness.headers, vec![Bytes::from(alloy_rlp::encode(genesis))]);
+    }
+
+    #[tokio::test]
+    async fn trace_new_canonical_blocks() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, tx_hashes) = add_block_with_calls(provider, counter, 2);
+        let block = provider.block(block_hash.into()).unwrap().unwrap();
+        let block = block.seal(block_hash).try_seal_with_senders().unwrap();
+        let chain = Arc::new(reth_provider::Chain::new([block], Default::default(), None));
+        let notifications = [
+            CanonStateNotification::Commit { new: chain.clone() },
+            CanonStateNotification::Reorg { old: chain.clone(), new: chain },
+        ];
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let opts = GethDebugTracingOptions {
+            tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+            ..Default::default()
+        };
+        debug_api
+            .trace_new_blocks(
+                futures::stream::iter(notifications),
+                opts,
+                subscription::BlockTracesSender::new(tx),
+            )
+            .await;
+
+        for reorg in [false, true] {
+            let Some(DebugSubscriptionResult::BlockTraces(traces)) = rx.recv().await else {
+                panic!("expected block traces")
+            };
+            assert_eq!(
+                (traces.block_hash, traces.block_number, traces.reorg),
+                (block_hash, 1, reorg)
+            );
+            let traced = traces.traces.iter().map(|res| match res {
+                TraceResult::Success { result: GethTrace::CallTracer(_), tx_hash } => {
+                    tx_hash.unwrap()
+                }
+                res => panic!("expected a call trace, got {res:?}"),
+            });
+            assert_eq!(traced.collect::<Vec<_>>(), tx_hashes);
+        }
+        // the notifications ended
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn trace_block_progress() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, tx_hashes) = add_block_with_calls(provider, counter, 4);
+        let opts = DebugTracingBlockOptions {
+            tracing_options: GethDebugTracingOptions {
+                tracer: Some(GethDebugBuiltInTracerType::CallTracer.into()),
+                ..Default::default()
+            },
+            progress: true,
+            ..Default::default()
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        debug_api.trace_block_with_progress(block_hash.into(), opts.clone(), tx).await;
+        let mut notifications = Vec::new();
+        while let Some(notification) = rx.recv().await {
+            notifications.push(notification);
+        }
+
+        // the progress precedes the result
+        let Some(TraceBlockNotification::Result(DebugTraceBlockResponse::Array(results))) =
+            notifications.pop()
+        else {
+            panic!("expected the trace result last, got {notifications:?}")
+        };
+        assert_eq!(results.len(), 4);
+        assert!(!notifications.is_empty());
+        let mut traced = 0;
+        for notification in notifications {
+            let TraceBlockNotification::Progress(progress) = notification else {
+                panic!("expected progress, got {notification:?}")
+            };
+            assert!(progress.traced_transactions >= traced);
+            assert!(progress.traced_transactions <= 4);
+            assert!(tx_hashes.contains(&progress.current_transaction.unwrap()));
+            traced = progress.traced_transactions;
+        }
+
+        // without the flag, only the result is sent
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let opts = DebugTracingBlockOptions { progress: false, ..opts };
+        debug_api.trace_block_with_progress(block_hash.into(), opts, tx).await;
+        assert!(matches!(rx.recv().await, Some(TraceBlockNotification::Result(_))));
+        assert!(rx.recv().await.is_none());
+
+        // errors end the subscription as well
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        debug_api.trace_block_with_progress(B256::random().into(), Default::default(), tx).await;
+        assert!(matches!(rx.recv().await, Some(TraceBlockNotification::Error(_))));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn block_access_list() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        // POP(SLOAD(1)) SSTORE(0, SLOAD(0) + 1) STOP
+        let contract = Address::random();
+        provider.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&hex!("6001545060005460010160005500"))),
+        );
+        let (block_hash, _) = add_block_with_calls(provider, contract, 2);
+
+        let list = debug_api.debug_get_block_access_list(block_hash.into()).await.unwrap();
+        let access = list.accounts.iter().find(|account| account.address == contract).unwrap();
+        assert!(!access.written);
+        assert_eq!(access.storage_reads, vec![B256::with_last_byte(1)]);
+        assert_eq!(access.storage_writes, vec![B256::ZERO]);
+
+        // the accesses are the union of the prestate traces of the transactions, and the writes
+        // the union of their post-states in diff mode
+        let prestates = |diff_mode: bool| {
+            let mut opts = DebugTracingBlockOptions::default();
+            opts.tracing_options.tracer = Some(GethDebugBuiltInTracerType::PreStateTracer.into());
+            opts.tracing_options.tracer_config =
+                GethDebugTracerConfig(serde_json::json!({ "diffMode": diff_mode }));
+            debug_api.debug_trace_block(block_hash.into(), opts)
+        };
+        let mut accessed = BTreeMap::<Address, BTreeSet<B256>>::new();
+        for trace in prestates(false).await.unwrap() {
+            let TraceResult::Success {
+                result: GethTrace::PreStateTracer(PreStateFrame::Default(prestate)),
+                ..
+            } = trace
+            else {
+                panic!("expected prestate trace, got {trace:?}")
+            };
+            for (address, account) in prestate.0 {
+                accessed.entry(address).or_default().extend(account.storage.into_keys());
+            }
+        }
+        let mut written = BTreeMap::<Address, BTreeSet<B256>>::new();
+        for trace in prestates(true).await.unwrap() {
+            let TraceResult::Success {
+                result: GethTrace::PreStateTracer(PreStateFrame::Diff(diff)),
+                ..
+            } = trace
+            else {
+                panic!("expected prestate diff, got {trace:?}")
+            };
+            for (address, account) in diff.post {
+                written.entry(address).or_default().extend(account.storage.into_keys());
+            }
+        }
+        written.retain(|_, slots| !slots.is_empty());
+
+        let slots = |account: &AccountAccess| {
+            account.storage_reads.iter().chain(&account.storage_writes).copied().collect()
+        };
+        assert_eq!(
+            list.accounts
+                .iter()
+                .map(|account| (account.address, slots(account)))
+                .collect::<BTreeMap<_, _>>(),
+            accessed
+        );
+        assert_eq!(
+            list.accounts
+                .iter()
+                .filter(|account| !account.storage_writes.is_empty())
+                .map(|account| (account.address, account.storage_writes.iter().copied().collect()))
+                .collect::<BTreeMap<_, _>>(),
+            written
+        );
+        // the nonce of the sender is written
+        let sender = provider.block_by_hash(block_hash).unwrap().unwrap().senders().unwrap()[0];
+        assert!(list.accounts.iter().any(|account| account.address == sender && account.written));
+
+        let res = DebugApiServer::debug_get_block_access_list(
+            &debug_api,
+            &Extensions::new(),
+            block_hash.into(),
+        )
+        .await;
+        assert_eq!(res.unwrap(), list);
+    }
+
+    #[tokio::test]
+    async fn execution_witness_block_ids() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, _) = add_block_with_calls(provider, counter, 2);
+
+        // the block is resolved the same by hash, number and tag
+        let witness = debug_api
+            .debug_execution_witness(block_hash.into(), false, true, false, false, false)
+            .await
+            .unwrap();
+        for id in [BlockNumberOrTag::Number(1).into(), BlockId::latest()] {
+            assert_eq!(
+                debug_api
+                    .debug_execution_witness(id, false, true, false, false, false)
+                    .await
+                    .unwrap(),
+                witness
+            );
+        }
+
+        let unknown = B256::random();
+        let err = debug_api
+            .debug_execution_witness(unknown.into(), false, false, false, false, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), DebugApiError::BlockNotFound(unknown.into()).to_string());
+    }
+
+    #[tokio::test]
+    async fn execution_witness_withdrawals() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let recipient = Address::random();
+        let withdrawals = Withdrawals::new(vec![Withdrawal {
+            address: recipient,
+            amount: 1,
+            ..Default::default()
+        }]);
+        // shanghai is active on mainnet at this timestamp
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            timestamp: 1_700_000_000,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let block_hash = header.hash_slow();
+        provider.add_block(
+            block_hash,
+            Block { header, withdrawals: Some(withdrawals), ..Default::default() },
+        );
+
+        // the block has no transactions, the recipient is only touched by the withdrawal
+        let witness = debug_api
+            .debug_execution_witness(block_hash.into(), true, false, false, false, false)
+            .await
+            .unwrap();
+        let preimages = witness.witness.state_preimages.unwrap();
+        assert_eq!(preimages[&keccak256(recipient)], Bytes::from(alloy_rlp::encode(recipient)));
+        // the witness is generated for the credited balance of the recipient
+        let targets = provider.witness_targets.lock();
+        let account = targets.last().unwrap().accounts[&keccak256(recipient)].unwrap();
+        assert_eq!(account.balance, U256::from(GWEI_TO_WEI));
+    }
+
+    #[tokio::test]
+    async fn execution_witness_preimages_of_deleted_storage() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let slot = |slot: u8| B256::with_last_byte(slot);
+        // POP(SLOAD(1)) SSTORE(0, 0) STOP
+        let clearer = Address::random();
+        provider.add_account(
+            clearer,
+            ExtendedAccount::new(0, U256::ZERO)
+                .extend_storage([(slot(0), U256::from(1)), (slot(1), U256::from(2))])
+                .with_bytecode(Bytes::from_static(&hex!("60015450600060005500"))),
+        );
+        // POP(SLOAD(5)) SELFDESTRUCT(CALLER)
+        let destroyed = Address::random();
+        provider.add_account(
+            destroyed,
+            ExtendedAccount::new(0, U256::ZERO)
+                .extend_storage([(slot(5), U256::from(3))])
+                .with_bytecode(Bytes::from_static(&hex!("6005545033ff"))),
+        );
+        // POP(CALL(GAS, callee, 0, 0, 0, 0, 0)) for both callees, then STOP
+        let mut caller_code = Vec::new();
+        for callee in [clearer, destroyed] {
+            caller_code.extend(hex!("6000600060006000600073"));
+            caller_code.extend_from_slice(callee.as_slice());
+            caller_code.extend(hex!("5af150"));
+        }
+        caller_code.push(0x00);
+        let caller = Address::random();
+        provider.add_account(
+            caller,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(caller_code.into()),
+        );
+        let (block_hash, _) = add_block_with_calls(provider, caller, 1);
+
+        let witness = debug_api
+            .debug_execution_witness(block_hash.into(), true, false, false, false, false)
+            .await
+            .unwrap();
+        assert!(witness.missing_preimages.is_none());
+        let preimages = witness.witness.state_preimages.unwrap();
+        // the destroyed account and the slot it read are covered, as are the zeroed and the read
+        // slot of the clearer
+        for address in [caller, clearer, destroyed] {
+            assert_eq!(preimages[&keccak256(address)], Bytes::from(alloy_rlp::encode(address)));
+        }
+        for slot in [slot(0), slot(1), slot(5)] {
+            assert_eq!(preimages[&keccak256(slot)], Bytes::from(alloy_rlp::encode(slot)));
+        }
+
+        // the witness of the mock has no trie nodes, so no preimage is missing, and the verified
+        // preimages aren't included unless requested
+        let witness = debug_api
+            .debug_execution_witness(block_hash.into(), false, false, false, true, false)
+            .await
+            .unwrap();
+        assert_eq!(witness.missing_preimages, Some(Vec::new()));
+        assert!(witness.witness.state_preimages.is_none());
+    }
+
+    #[tokio::test]
+    async fn execution_witness_cache() {
+        let cache = WitnessCache::new(Default::default());
+        let debug_api = mock_debug_api().with_witness_cache(cache.clone());
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, _) = add_block_with_calls(provider, counter, 2);
+
+        let block = BlockNumberOrTag::Number(1).into();
+        let witness = debug_api
+            .debug_execution_witness(block, false, true, false, false, false)
+            .await
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+
+        // the block is resolved before the lookup, so the witness is cached by hash
+        let cached = debug_api
+            .debug_execution_witness(block_hash.into(), false, true, false, false, false)
+            .await
+            .unwrap();
+        assert_eq!(cached, witness);
+        let uncached = debug_api
+            .debug_execution_witness(block, false, true, false, false, true)
+            .await
+            .unwrap();
+        assert_eq!(uncached, witness);
+        assert_eq!(cache.len(), 1);
+
+        // other options are cached separately
+        debug_api.debug_execution_witness(block, false, false, false, false, false).await.unwrap();
+        assert_eq!(cache.len(), 2);
+
+        cache.remove_blocks(&HashSet::from([block_hash]));
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn execution_witness_pending_and_raw_block() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, _) = add_block_with_calls(provider, counter, 2);
+        let witness = debug_api
+            .debug_execution_witness(block_hash.into(), true, true, false, false, false)
+            .await
+            .unwrap();
+        assert_eq!(witness.block_hash, None);
+
+        // the same block, submitted raw
+        let block = provider.block_by_hash(block_hash).unwrap().unwrap();
+        let rlp_block: Bytes = alloy_rlp::encode(block).into();
+        let raw = debug_api
+            .debug_execution_witness_raw_block(rlp_block.clone(), true, true, false, false)
+            .await
+            .unwrap();
+        assert_eq!(raw, DebugExecutionWitness { block_hash: Some(block_hash), ..witness });
+
+        // with the options of `debug_executionWitness`
+        let res = DebugApiServer::debug_execution_witness_raw(
+            &debug_api,
+            &Extensions::new(),
+            rlp_block,
+            true,
+            Some(ExecutionWitnessOptions {
+                include_codes: true,
+                encoding: WitnessEncoding::Snappy,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.into_witness().unwrap(), raw);
+
+        // the pending block is built on top of the canonical head
+        let pending = debug_api
+            .debug_execution_witness(BlockId::pending(), false, false, false, false, false)
+            .await
+            .unwrap();
+        let pending_hash = pending.block_hash.expect("hash of the pending block");
+        assert_ne!(pending_hash, block_hash);
+    }
+
+    #[tokio::test]
+    async fn execution_witness_raw_block_errors() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+
+        // the signature can't be recovered
+        let tx = Transaction::Legacy(TxLegacy { gas_limit: 21_000, ..Default::default() });
+        let body =
+            vec![TransactionSigned::from_transaction_and_signature(tx, Signature::default())];
+        let block = Block { header: header.clone(), body, ..Default::default() };
+        let rlp_block = alloy_rlp::encode(block).into();
+        let err = debug_api
+            .debug_execution_witness_raw_block(rlp_block, false, false, false, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), EthApiError::InvalidTransactionSignature.to_string());
+
+        // the parent is unknown
+        let parent = B256::random();
+        let header_on_unknown = Header { parent_hash: parent, ..header.clone() };
+        let rlp_block =
+            alloy_rlp::encode(Block { header: header_on_unknown, ..Default::default() }).into();
+        let err = debug_api
+            .debug_execution_witness_raw_block(rlp_block, false, false, false, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), EthApiError::ParentBlockNotFound(parent).to_string());
+
+        // witnessed blocks aren't imported
+        let rlp_block = alloy_rlp::encode(Block { header, ..Default::default() }).into();
+        let witness = debug_api
+            .debug_execution_witness_raw_block(rlp_block, false, false, false, false)
+            .await
+            .unwrap();
+        assert!(provider.block_by_hash(witness.block_hash.unwrap()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn execute_witness() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let parent = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            state_root: EMPTY_ROOT_HASH,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let parent_hash = parent.hash_slow();
+        provider.add_block(parent_hash, Block { header: parent, ..Default::default() });
+
+        // an empty block doesn't change the empty state
+        let header = Header {
+            number: 2,
+            parent_hash,
+            state_root: EMPTY_ROOT_HASH,
+            receipts_root: EMPTY_ROOT_HASH,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let rlp_block = alloy_rlp::encode(Block { header: header.clone(), ..Default::default() });
+        let result = debug_api
+            .debug_execute_witness(rlp_block.into(), DebugExecutionWitness::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            StatelessExecutionResult::Valid {
+                state_root: EMPTY_ROOT_HASH,
+                receipts_root: EMPTY_ROOT_HASH,
+                gas_used: 0,
+            }
+        );
+
+        let header = Header { gas_used: 21_000, ..header };
+        let rlp_block = alloy_rlp::encode(Block { header, ..Default::default() });
+        let result = debug_api
+            .debug_execute_witness(rlp_block.into(), DebugExecutionWitness::default())
+            .await
+            .unwrap();
+        let StatelessExecutionResult::Mismatch(mismatch) = result else {
+            panic!("expected mismatch, got {result:?}")
+        };
+        assert_eq!((mismatch.expected_gas_used, mismatch.computed_gas_used), (21_000, 0));
+        assert_eq!(mismatch.computed_state_root, mismatch.expected_state_root);
+    }
+
+    #[tokio::test]
+    async fn execute_incomplete_witness() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let (block_hash, _) = add_block_with_calls(provider, Address::random(), 1);
+        let block = provider.block_by_hash(block_hash).unwrap().unwrap();
+        let sender = block.body[0].recover_signer().unwrap();
+
+        // the root of the state of the genesis block isn't in the witness
+        let rlp_block = alloy_rlp::encode(block);
+        let result = debug_api
+            .debug_execute_witness(rlp_block.into(), DebugExecutionWitness::default())
+            .await
+            .unwrap();
+        let StatelessExecutionResult::Incomplete { missing_keys } = result else {
+            panic!("expected incomplete witness, got {result:?}")
+        };
+        assert_eq!(
+            missing_keys[0],
+            MissingWitnessKey::AccountNode { address: sender, hash: B256::ZERO }
+        );
+    }
+
+    #[tokio::test]
+    async fn execution_witness_verify() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let state_root = B256::with_last_byte(1);
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            state_root,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let empty_block_hash = header.hash_slow();
+        provider.add_block(empty_block_hash, Block { header, ..Default::default() });
+
+        let witness = debug_api
+            .debug_execution_witness(empty_block_hash.into(), false, false, false, false, false)
+            .await
+            .unwrap();
+        assert!(witness.verification.is_none());
+
+        // the mock computes the queued state root
+        provider.add_state_root(state_root);
+        let witness = debug_api
+            .debug_execution_witness(empty_block_hash.into(), false, false, true, false, false)
+            .await
+            .unwrap();
+        assert_eq!(witness.verification, Some(WitnessVerification::Verified { state_root }));
+
+        let counter = deploy_counter(provider);
+        let (block_hash, _) = add_block_with_calls(provider, counter, 2);
+        let witness = debug_api
+            .debug_execution_witness(block_hash.into(), false, false, true, false, false)
+            .await
+            .unwrap();
+        let Some(WitnessVerification::Mismatch(mismatch)) = witness.verification else {
+            panic!("expected mismatch, got {:?}", witness.verification)
+        };
+        // the header of the block doesn't account for the gas used by the transactions
+        assert_eq!(mismatch.expected_gas_used, 0);
+        assert!(mismatch.computed_gas_used > 0);
+        // the mock returns its unchanged state as the post-state of the block, which diverges in
+        // the counter and the nonce of the sender
+        let diverged = mismatch.diverged_accounts.unwrap();
+        assert_eq!(diverged.len(), 2);
+        assert!(diverged.contains(&counter));
+        // the mock has no receipts
+        assert_eq!(mismatch.first_diverged_transaction, None);
+    }
+
+    #[tokio::test]
+    async fn replay_block() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let state_root = B256::with_last_byte(1);
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            state_root,
+            receipts_root: EMPTY_ROOT_HASH,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let empty_block_hash = header.hash_slow();
+        provider.add_block(empty_block_hash, Block { header, ..Default::default() });
+
+        // the mock computes the queued state root
+        provider.add_state_root(state_root);
+        let report = debug_api.debug_replay_block(empty_block_hash.into()).await.unwrap();
+        assert!(report.matches, "{report:?}");
+        assert_eq!((report.block_hash, report.block_number), (empty_block_hash, 1));
+        assert_eq!(report.diverged_accounts, Some(Vec::new()));
+
+        let counter = deploy_counter(provider);
+        let (block_hash, _) = add_block_with_calls(provider, counter, 2);
+        let report = debug_api.debug_replay_block(block_hash.into()).await.unwrap();
+        assert!(!report.matches);
+        assert_eq!(report.expected_gas_used, 0);
+        assert!(report.computed_gas_used > 0);
+        // the mock has no receipts, and returns its unchanged state as the post-state of the block
+        assert_eq!(report.first_diverged_receipt, None);
+        let diverged = report.diverged_accounts.unwrap();
+        assert_eq!(diverged.len(), 2);
+        assert!(diverged.contains(&counter));
+
+        // the stored receipts don't account for the gas used by the transactions
+        let stored = Receipt { tx_type: TxType::Legacy, success: true, ..Default::default() };
+        provider.add_receipts(block_hash, vec![stored.clone(), stored]);
+        let report = debug_api.debug_replay_block(block_hash.into()).await.unwrap();
+        let divergence = report.first_diverged_receipt.unwrap();
+        assert_eq!(divergence.transaction_index, 0);
+        assert_eq!(divergence.expected.gas_used, 0);
+        assert!(divergence.computed.status);
+        assert_eq!(divergence.computed.gas_used, divergence.computed.cumulative_gas_used);
+        assert!(divergence.computed.gas_used > 0);
+
+        let err =
+            DebugApiServer::debug_replay_block(&debug_api, &Extensions::new(), BlockId::number(5))
+                .await;
+        let expected = ErrorObjectOwned::from(DebugApiError::BlockNotFound(BlockId::number(5)));
+        assert_eq!(err.unwrap_err(), expected);
+    }
+
+    #[tokio::test]
+    async fn trace_block_with_modifications() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        // a pool whose price in slot 0 is raised by trades, i.e. calls with calldata. Without
+        // calldata, an arbitrage lowers the price again if it was raised, and does nothing
+        // otherwise
+        let dex = Address::random();
+        provider.add_account(
+            dex,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&hex!(
+                "3660155760005480600c57005b60019003600055005b60005460010160005500"
+            ))),
+        );
+        let keys = generators::generate_keys(&mut generators::rng(), 2);
+        let (trader, arbitrageur) = (keys[0], keys[1]);
+        let call = |key_pair, input: &'static [u8]| {
+            let tx = TxLegacy {
+                gas_limit: 100_000,
+                to: TxKind::Call(dex),
+                input: Bytes::from_static(input),
+                ..Default::default()
+            };
+            sign_tx_with_key_pair(key_pair, Transaction::Legacy(tx))
+        };
+        let body = vec![call(trader, &[1]), call(arbitrageur, &[])];
+        let tx_hashes = body.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let block_hash = header.hash_slow();
+        provider.add_block(block_hash, Block { header, body, ..Default::default() });
+        let trace = |modifications| {
+            debug_api.debug_trace_block_with_modifications(
+                block_hash.into(),
+                modifications,
+                Default::default(),
+            )
+        };
+        let arbitrages = |result: &ModifiedTraceResult| {
+            let Some(GethTrace::Default(frame)) = &result.result else {
+                panic!("expected struct logs, got {result:?}")
+            };
+            frame.struct_logs.iter().any(|log| log.op == "SSTORE")
+        };
+
+        // without modifications, the block is traced as is
+        let results = trace(Vec::new()).await.unwrap();
+        let expected = debug_api.debug_trace_block(block_hash.into(), Default::default()).await;
+        for (result, expected) in results.iter().zip(expected.unwrap()) {
+            let TraceResult::Success { result: expected, tx_hash } = expected else {
+                panic!("expected a trace, got {expected:?}")
+            };
+            assert_eq!(result.result.as_ref(), Some(&expected));
+            assert_eq!((Some(result.tx_hash), result.modification), (tx_hash, None));
+        }
+        assert!(arbitrages(&results[1]));
+
+        // without the trade, there's nothing to arbitrage
+        let drop_trade = TransactionModification { index: 0, replacement: None };
+        let results = trace(vec![drop_trade.clone()]).await.unwrap();
+        assert_eq!(
+            results[0],
+            ModifiedTraceResult {
+                tx_hash: tx_hashes[0],
+                modification: Some(TransactionModificationKind::Dropped),
+                result: None,
+                error: None,
+            }
+        );
+        assert_eq!((results[1].tx_hash, results[1].modification), (tx_hashes[1], None));
+        assert!(!arbitrages(&results[1]));
+
+        // the replacement is executed from the sender of the trade, here without calldata
+        let quote = TransactionModification {
+            index: 0,
+            replacement: Some(TransactionRequest {
+                to: Some(TxKind::Call(dex)),
+                ..Default::default()
+            }),
+        };
+        let results = trace(vec![quote.clone()]).await.unwrap();
+        assert_eq!(results[0].tx_hash, tx_hashes[0]);
+        assert_eq!(results[0].modification, Some(TransactionModificationKind::Replaced));
+        assert!(!arbitrages(&results[0]));
+        assert!(!arbitrages(&results[1]));
+
+        let out_of_range = TransactionModification { index: 2, replacement: None };
+        let err = trace(vec![out_of_range]).await.unwrap_err();
+        assert!(err.to_string().contains("transaction index 2 out of range"), "{err}");
+        let err = trace(vec![drop_trade, quote.clone()]).await.unwrap_err();
+        assert!(err.to_string().contains("transaction 0 is modified more than once"), "{err}");
+        let mut other_sender = quote;
+        other_sender.replacement.as_mut().unwrap().from = Some(Address::random());
+        let err = trace(vec![other_sender]).await.unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn execution_witness_range() {
+        let debug_api = mock_debug_api().with_max_execution_witness_range(3);
+        let provider = &debug_api.inner.provider;
+        let counter = deploy_counter(provider);
+        let (block_hash, _) = add_block_with_calls(provider, counter, 2);
+        let header = Header {
+            number: 2,
+            parent_hash: block_hash,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        provider.add_block(header.hash_slow(), Block { header, ..Default::default() });
+
+        let (start, end) = (BlockNumberOrTag::Number(1), BlockNumberOrTag::Number(3));
+        let results = debug_api.debug_execution_witness_range(start, end, true).await.unwrap();
+        assert_eq!(
+            results.iter().map(ExecutionWitnessResult::block_number).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        // the blocks are witnessed the same as on their own
+        for (block_number, result) in (1..).zip(&results[..2]) {
+            let witness = debug_api
+                .debug_execution_witness(block_number.into(), true, false, false, false, false)
+                .await
+                .unwrap();
+            assert_eq!(*result, ExecutionWitnessResult::Success { block_number, result: witness });
+        }
+        // there is no block 3
+        assert!(matches!(results[2], ExecutionWitnessResult::Error { .. }));
+
+        let err = debug_api
+            .debug_execution_witness_range(BlockNumberOrTag::Earliest, end, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "block range too large; currently limited to 3 blocks");
+        let err = debug_api.debug_execution_witness_range(end, start, false).await.unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidParams(_)));
+    }
+
+    #[tokio::test]
+    async fn raw_receipt() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let (block_hash, tx_hashes) = add_block_with_calls(provider, Address::random(), 2);
+        #[allow(clippy::needless_update)]
+        let receipts = (1..=2)
+            .map(|i| Receipt {
+                tx_type: TxType::Legacy,
+                success: true,
+                cumulative_gas_used: 21_000 * i,
+                ..Default::default()
+            })
+            .collect();
+        provider.add_receipts(block_hash, receipts);
+
+        let raw_receipts =
+            DebugApiServer::raw_receipts(&debug_api, block_hash.into()).await.unwrap();
+        assert_eq!(raw_receipts.len(), 2);
+        for (tx_hash, raw) in tx_hashes.into_iter().zip(raw_receipts) {
+            let receipt = DebugApiServer::raw_receipt(&debug_api, tx_hash).await.unwrap();
+            assert_eq!(receipt, Some(raw));
+        }
+
+        // unknown transaction
+        assert_eq!(DebugApiServer::raw_receipt(&debug_api, B256::random()).await.unwrap(), None);
+
+        // the transaction is only in the mempool
+        let pool = debug_api.eth_api().pool();
+        let tx_hash = pool.add_external_transaction(MockTransaction::eip1559()).await.unwrap();
+        assert_eq!(DebugApiServer::raw_receipt(&debug_api, tx_hash).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn raw_blob_sidecars() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let pool = debug_api.eth_api().pool();
+
+        // the genesis block has no blob transactions
+        let sidecars = DebugApiServer::raw_blob_sidecars(&debug_api, BlockId::number(0));
+        assert!(sidecars.await.unwrap().is_empty());
+
+        // the sidecars of blob transactions are kept in the blob store of the pool, by the hash of
+        // the pooled transaction
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let blob_tx = |nonce: u8| async move {
+            let sidecar = BlobTransactionSidecar {
+                blobs: Vec::new(),
+                commitments: vec![FixedBytes::repeat_byte(nonce)],
+                proofs: Vec::new(),
+            };
+            let hash = pool
+                .add_external_transaction(MockTransaction::eip4844_with_sidecar(sidecar.clone()))
+                .await
+                .unwrap();
+            let tx = Transaction::Eip4844(TxEip4844 { nonce: nonce.into(), ..Default::default() });
+            let tx = TransactionSigned { hash, ..sign_tx_with_key_pair(key_pair, tx) };
+            (tx, Bytes::from(alloy_rlp::encode(sidecar)))
+        };
+        let (first, first_sidecar) = blob_tx(0).await;
+        let (second, second_sidecar) = blob_tx(2).await;
+        let legacy =
+            Transaction::Legacy(TxLegacy { nonce: 1, gas_limit: 21_000, ..Default::default() });
+        let body = vec![first.clone(), sign_tx_with_key_pair(key_pair, legacy), second];
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let block_hash = header.hash_slow();
+        provider.add_block(block_hash, Block { header, body, ..Default::default() });
+
+        let sidecars =
+            DebugApiServer::raw_blob_sidecars(&debug_api, block_hash.into()).await.unwrap();
+        assert_eq!(sidecars, vec![first_sidecar, second_sidecar]);
+
+        // the sidecar was removed from the blob store, e.g. after the block was finalized
+        pool.delete_blob(first.hash);
+        let err =
+            DebugApiServer::raw_blob_sidecars(&debug_api, block_hash.into()).await.unwrap_err();
+        assert_eq!(err.code(), EthRpcErrorCode::ResourceNotFound.code());
+        assert_eq!(err.message(), EthApiError::BlobSidecarUnavailable(first.hash).to_string());
+
+        // an unknown block
+        let res = DebugApiServer::raw_blob_sidecars(&debug_api, B256::random().into()).await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn raw_transaction_exclude_sidecar() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let pool = debug_api.eth_api().pool();
+
+        // a blob transaction that is only in the mempool is returned in the pooled format, with
+        // its sidecar, unless the sidecar is excluded
+        let sidecar = BlobTransactionSidecar {
+            blobs: Vec::new(),
+            commitments: vec![FixedBytes::repeat_byte(1)],
+            proofs: Vec::new(),
+        };
+        let hash = pool
+            .add_external_transaction(MockTransaction::eip4844_with_sidecar(sidecar))
+            .await
+            .unwrap();
+        let pooled = pool.get_pooled_transaction_element(hash).unwrap();
+        let canonical = pooled.clone().into_transaction().envelope_encoded();
+        let pooled = pooled.envelope_encoded();
+        assert_ne!(pooled, canonical);
+        for (exclude_sidecar, expected) in
+            [(None, &pooled), (Some(false), &pooled), (Some(true), &canonical)]
+        {
+            let raw = DebugApiServer::raw_transaction(&debug_api, hash, exclude_sidecar);
+            assert_eq!(raw.await.unwrap().as_ref(), Some(expected), "{exclude_sidecar:?}");
+        }
+
+        // a mined blob transaction is always returned in the canonical format
+        let key_pair = generators::generate_keys(&mut generators::rng(), 1)[0];
+        let tx = sign_tx_with_key_pair(key_pair, Transaction::Eip4844(TxEip4844::default()));
+        let header = Header {
+            number: 1,
+            parent_hash: provider.block_hash(0).unwrap().unwrap(),
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let body = vec![tx.clone()];
+        provider.add_block(header.hash_slow(), Block { header, body, ..Default::default() });
+        for exclude_sidecar in [None, Some(false), Some(true)] {
+            let raw = DebugApiServer::raw_transaction(&debug_api, tx.hash, exclude_sidecar);
+            assert_eq!(raw.await.unwrap(), Some(tx.envelope_encoded()), "{exclude_sidecar:?}");
+        }
+
+        let raw = DebugApiServer::raw_transaction(&debug_api, B256::random(), Some(true));
+        assert_eq!(raw.await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn code_by_hash() {
+        let debug_api = mock_debug_api();
+        let code = Bytes::from_static(COUNTER_CODE);
+        debug_api.inner.provider.add_account(
+            Address::random(),
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(code.clone()),
+        );
+
+        // the block id doesn't matter
+        for block_id in [None, Some(BlockId::number(0)), Some(BlockId::from(B256::random()))] {
+            let res = DebugApiServer::debug_code_by_hash(&debug_api, keccak256(&code), block_id);
+            assert_eq!(res.await.unwrap(), Some(code.clone()));
+        }
+
+        let res = DebugApiServer::debug_code_by_hash(&debug_api, KECCAK_EMPTY, None);
+        assert_eq!(res.await.unwrap(), Some(Bytes::new()));
+        let res = DebugApiServer::debug_code_by_hash(&debug_api, B256::random(), None);
+        assert_eq!(res.await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn raw_transactions_and_receipts_of_unknown_block() {
+        let debug_api = mock_debug_api();
+        let hashes = extend_mock_chain(&debug_api.inner.provider);
+
+        for id in [BlockId::from(B256::random()), BlockNumberOrTag::Number(4).into()] {
+            let not_found: ErrorObjectOwned = DebugApiError::BlockNotFound(id).into();
+            let err = DebugApiServer::raw_transactions(&debug_api, id).await.unwrap_err();
+            assert_eq!(err, not_found);
+            let err = DebugApiServer::raw_receipts(&debug_api, id).await.unwrap_err();
+            assert_eq!(err, not_found);
+        }
+
+        // an empty block has no transactions and receipts
+        for id in [BlockId::from(hashes[1]), BlockNumberOrTag::Number(1).into()] {
+            assert!(DebugApiServer::raw_transactions(&debug_api, id).await.unwrap().is_empty());
+            assert!(DebugApiServer::raw_receipts(&debug_api, id).await.unwrap().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_header_not_found() {
+        let debug_api = mock_debug_api();
+        extend_mock_chain(&debug_api.inner.provider);
+
+        for id in [BlockId::from(B256::random()), BlockNumberOrTag::Number(4).into()] {
+            let err = DebugApiServer::raw_header(&debug_api, id).await.unwrap_err();
+            assert_eq!(err, ErrorObjectOwned::from(DebugApiError::BlockNotFound(id)));
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_headers() {
+        let debug_api = mock_debug_api().with_max_raw_headers(3);
+        let hashes = extend_mock_chain(&debug_api.inner.provider);
+        let debug_api = &debug_api;
+        let raw_headers = |start: BlockNumberOrTag, count: u64| async move {
+            let raw = DebugApiServer::raw_headers(debug_api, start, count).await?;
+            RpcResult::Ok(
+                raw.iter()
+                    .map(|raw| Header::decode(&mut raw.as_ref()).unwrap().hash_slow())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        assert_eq!(raw_headers(BlockNumberOrTag::Earliest, 3).await.unwrap(), &hashes[..3]);
+        assert_eq!(raw_headers(BlockNumberOrTag::Number(1), 3).await.unwrap(), &hashes[1..]);
+        assert!(raw_headers(BlockNumberOrTag::Number(1), 0).await.unwrap().is_empty());
+
+        // the range stops at the chain tip
+        assert_eq!(raw_headers(BlockNumberOrTag::Number(2), 3).await.unwrap(), &hashes[2..]);
+        assert_eq!(raw_headers(BlockNumberOrTag::Latest, 3).await.unwrap(), &hashes[3..]);
+
+        let err = raw_headers(BlockNumberOrTag::Earliest, 4).await.unwrap_err();
+        assert_eq!(err.message(), "too many headers requested; currently limited to 3 headers");
+        let err = raw_headers(BlockNumberOrTag::Number(4), 1).await.unwrap_err();
+        assert_eq!(err, ErrorObjectOwned::from(DebugApiError::BlockNotFound(BlockId::number(4))));
+    }
+
+    #[tokio::test]
+    async fn raw_data_pending() {
+        let debug_api = mock_debug_api();
+        let hashes = extend_mock_chain(&debug_api.inner.provider);
+        let id = BlockId::pending();
+
+        // the pending block is built locally on top of the latest block
+        let raw = DebugApiServer::raw_header(&debug_api, id).await.unwrap();
+        let header = Header::decode(&mut raw.as_ref()).unwrap();
+        assert_eq!((header.number, header.parent_hash), (4, hashes[3]));
+        let raw = DebugApiServer::raw_block(&debug_api, id).await.unwrap();
+        assert_eq!(Block::decode(&mut raw.as_ref()).unwrap().header, header);
+        assert!(DebugApiServer::raw_transactions(&debug_api, id).await.unwrap().is_empty());
+        assert!(DebugApiServer::raw_receipts(&debug_api, id).await.unwrap().is_empty());
+
+        // without a latest block, there is no pending block either
+        debug_api.inner.provider.blocks.lock().clear();
+        debug_api.inner.provider.headers.lock().clear();
+        let expected = ErrorObjectOwned::from(EthApiError::HeaderNotFound(BlockId::latest()));
+        let err = DebugApiServer::raw_header(&debug_api, id).await.unwrap_err();
+        assert_eq!(err, expected);
+        let err = DebugApiServer::raw_block(&debug_api, id).await.unwrap_err();
+        assert_eq!(err, expected);
+        let err = DebugApiServer::raw_transactions(&debug_api, id).await.unwrap_err();
+        assert_eq!(err, expected);
+        let err = DebugApiServer::raw_receipts(&debug_api, id).await.unwrap_err();
+        assert_eq!(err, expected);
+    }
+
+    #[tokio::test]
+    async fn raw_block_prague() {
+        let debug_api = mock_debug_api();
+        let provider = &debug_api.inner.provider;
+        let genesis = provider.block_hash(0).unwrap().unwrap();
+        let header = Header {
+            number: 1,
+            parent_hash: genesis,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            withdrawals_root: Some(EMPTY_ROOT_HASH),
+            parent_beacon_block_root: Some(B256::ZERO),
+            requests_root: Some(EMPTY_ROOT_HASH),
+            ..Default::default()
+        };
+        let hash = header.hash_slow();
+        // the empty withdrawals and requests of the block weren't loaded
+        provider.add_block(hash, Block { header, ..Default::default() });
+
+        let raw = DebugApiServer::raw_block(&debug_api, hash.into()).await.unwrap();
+        let block = decode_raw_block(&raw).unwrap();
+        assert_eq!(block.header.hash_slow(), hash);
+        assert_eq!(block.withdrawals, Some(Withdrawals::default()));
+        assert_eq!(block.requests, Some(Default::default()));
+        assert_eq!(encode_raw_block(block), raw);
+    }
+
+    /// Adds three blocks on top of the genesis block of the mock provider and returns the hashes of
+    /// all blocks, by number.
+    fn extend_mock_chain(provider: &MockEthProvider) -> Vec<B256> {
+        let mut hashes = vec![provider.block_hash(0).unwrap().unwrap()];
+        for number in 1..=3 {
+            let header = Header {
+                number,
+                parent_hash: hashes[number as usize - 1],
+                gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+                ..Default::default()
+            };
+            let hash = header.hash_slow();
+            provider.add_block(hash, Block { header, ..Default::default() });
+            hashes.push(hash);
+        }
+        for hash in &hashes {
+            provider.add_receipts(*hash, Vec::new());
+        }
+        hashes
+    }
+
+    /// Resolves the tag with every endpoint that takes a block id, and returns the hash of the
+    /// resolved block for the endpoints that respond with the block.
+    async fn resolve_tag(
+        debug_api: &DebugApi<MockEthProvider, TestEthApi>,
+        tag: BlockNumberOrTag,
+    ) -> Vec<RpcResult<Option<B256>>> {
+        let id = BlockId::from(tag);
+        vec![
+            DebugApiServer::raw_header(debug_api, id)
+                .await
+                .map(|raw| Some(Header::decode(&mut raw.as_ref()).unwrap().hash_slow())),
+            DebugApiServer::raw_block(debug_api, id)
+                .await
+                .map(|raw| Some(Block::decode(&mut raw.as_ref()).unwrap().header.hash_slow())),
+            DebugApiServer::raw_transactions(debug_api, id).await.map(|_| None),
+            DebugApiServer::raw_receipts(debug_api, id).await.map(|_| None),
+            DebugApiServer::debug_trace_block_by_number(debug_api, &Extensions::new(), tag, None)
+                .await
+                .map(|_| None),
+            DebugApiServer::debug_execution_witness(debug_api, &Extensions::new(), id, false, None)
+                .await
+                .map(|_| None),
+        ]
+    }
+
+    #[tokio::test]
+    async fn resolve_block_tags() {
+        let debug_api = mock_debug_api();
+        let hashes = extend_mock_chain(&debug_api.inner.provider);
+
+        let resolved = [
+            (BlockNumberOrTag::Latest, hashes[3]),
+            (BlockNumberOrTag::Earliest, hashes[0]),
+            (BlockNumberOrTag::Number(1), hashes[1]),
+        ];
+        for (tag, hash) in resolved {
+            for res in resolve_tag(&debug_api, tag).await {
+                let res = res.unwrap_or_else(|err| panic!("failed to resolve {tag}: {err:?}"));
+                assert!(res.map_or(true, |res| res == hash), "{tag} resolved to {res:?}");
+            }
+        }
+
+        // the header and block of `pending` are the ones of the locally built pending block, see
+        // `raw_data_pending`
+        let tag = BlockNumberOrTag::Pending;
+        for res in resolve_tag(&debug_api, tag).await.into_iter().skip(2) {
+            let res = res.unwrap_or_else(|err| panic!("failed to resolve {tag}: {err:?}"));
+            assert!(res.map_or(true, |res| res == hashes[3]), "{tag} resolved to {res:?}");
+        }
+
+        // the node hasn't received forkchoice state yet
+        for tag in [BlockNumberOrTag::Safe, BlockNumberOrTag::Finalized] {
+            for res in resolve_tag(&debug_api, tag).await {
+                let err = res.unwrap_err();
+                assert_eq!(err.code(), EthRpcErrorCode::ResourceNotFound.code());
+                assert_eq!(err.message(), format!("{tag} block not available"));
+            }
+        }
+
+        debug_api.inner.provider.set_safe_block(BlockNumHash::new(2, hashes[2]));
+        debug_api.inner.provider.set_finalized_block(BlockNumHash::new(1, hashes[1]));
+        for (tag, hash) in
+            [(BlockNumberOrTag::Safe, hashes[2]), (BlockNumberOrTag::Finalized, hashes[1])]
+        {
+            for res in resolve_tag(&debug_api, tag).await {
+                let res = res.unwrap_or_else(|err| panic!("failed to resolve {tag}: {err:?}"));
+                assert!(res.map_or(true, |res| res == hash), "{tag} resolved to {res:?}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_raw_block_parent() {
+        let debug_api = mock_debug_api();
+        let hashes = extend_mock_chain(&debug_api.inner.provider);
+
+        // a candidate block on top of a parent this node doesn't know
+        let unknown_parent = B256::random();
+        let header = Header {
+            number: 4,
+            parent_hash: unknown_parent,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            ..Default::default()
+        };
+        let rlp_block = Bytes::from(alloy_rlp::encode(Block { header, ..Default::default() }));
+
+        let err = debug_api
+            .debug_trace_raw_block(rlp_block.clone(), Default::default(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthApiError::ParentBlockNotFound(hash) if hash == unknown_parent));
+        let err = DebugApiServer::debug_trace_block(
+            &debug_api,
+            &Extensions::new(),
+            rlp_block.clone(),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), EthRpcErrorCode::ResourceNotFound.code());
+        assert_eq!(err.message(), format!("parent block {unknown_parent} not found"));
+
+        // an explicit parent overrides the one of the header
+        for parent in [BlockId::hash(hashes[3]), BlockId::latest()] {
+            let results = debug_api
+                .debug_trace_raw_block(rlp_block.clone(), Default::default(), Some(parent))
+                .await
+                .unwrap();
+            assert!(results.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn trie_flush_interval() {
+        let startup = PersistenceSettings {
+            flush_interval: Some(Duration::from_secs(3600)),
+            memory_block_buffer_target: 4,
+            persistence_threshold: 16,
+        };
+        let handle = PersistenceSettingsHandle::new(startup);
+        let debug_api = mock_debug_api().with_persistence_settings(handle.clone());
+
+        // the startup configuration until the setter is called
+        let interval = debug_api.debug_get_trie_flush_interval().await.unwrap();
+        assert_eq!(
+            interval,
+            TrieFlushInterval {
+                interval: Some("1h0m0s".to_string()),
+                memory_block_buffer_target: 4,
+                persistence_threshold: 16,
+                read_only: false,
+            }
+        );
+
+        debug_api.debug_set_trie_flush_interval("1m30s".to_string()).await.unwrap();
+        let interval = debug_api.debug_get_trie_flush_interval().await.unwrap();
+        assert_eq!(interval.interval.as_deref(), Some("1m30s"));
+        assert_eq!(interval.persistence_threshold, 16);
+        assert_eq!(handle.settings().flush_interval, Some(Duration::from_secs(90)));
+
+        let err = debug_api.debug_set_trie_flush_interval("90".to_string()).await.unwrap_err();
+        assert_eq!(err.message(), "invalid interval: 90");
+        let interval = debug_api.debug_get_trie_flush_interval().await.unwrap();
+        assert_eq!(interval.interval.as_deref(), Some("1m30s"));
+
+        // without runtime adjustment, the settings can still be read
+        let debug_api = mock_debug_api()
+            .with_persistence_settings(PersistenceSettingsHandle::read_only(startup));
+        assert!(debug_api.debug_set_trie_flush_interval("1m".to_string()).await.is_err());
+        let interval = debug_api.debug_get_trie_flush_interval().await.unwrap();
+        assert_eq!(interval.interval.as_deref(), Some("1h0m0s"));
+        assert!(interval.read_only);
+
+        // without the settings of the node, neither method is supported
+        let debug_api = mock_debug_api();
+        let err = debug_api.debug_get_trie_flush_interval().await.unwrap_err();
+        assert_eq!(err.message(), "the persistence settings of the node are not available");
+        assert!(debug_api.debug_set_trie_flush_interval("1m".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn db_tables() {
+        let debug_api = mock_debug_api().with_db_tables(true);
+        let table = |name, dupsort, entries, leaf_pages| TableStats {
+            name,
+            dupsort,
+            entries,
+            page_size: 4096,
+            leaf_pages,
+            branch_pages: 1,
+            overflow_pages: 0,
+        };
+        debug_api.inner.provider.set_table_stats(vec![
+            table("PlainStorageState", true, 100, 3),
+            table("Headers", false, 10, 1),
+        ]);
+        debug_api.inner.provider.set_static_file_block_ranges(
+            StaticFileSegment::Headers,
+            vec![
+                SegmentRangeInclusive::new(0, 499_999),
+                SegmentRangeInclusive::new(500_000, 500_010),
+            ],
+        );
+
+        let db_tables = debug_api.debug_db_tables().await.unwrap();
+        assert_eq!(
+            db_tables.tables,
+            vec![
+                DbTableStats {
+                    name: "Headers".to_string(),
+                    dupsort: false,
+                    entries: 10,
+                    leaf_size: 4096,
+                    branch_size: 4096,
+                    overflow_size: 0,
+                    total_size: 8192,
+                },
+                DbTableStats {
+                    name: "PlainStorageState".to_string(),
+                    dupsort: true,
+                    entries: 100,
+                    leaf_size: 3 * 4096,
+                    branch_size: 4096,
+                    overflow_size: 0,
+                    total_size: 4 * 4096,
+                },
+            ]
+        );
+        assert_eq!(
+            db_tables.static_files,
+            vec![StaticFileSegmentRanges {
+                segment: "headers".to_string(),
+                block_ranges: vec![
+                    BlockRange { start: 0, end: 499_999 },
+                    BlockRange { start: 500_000, end: 500_010 },
+                ],
+            }]
+        );
+
+        // disabled by default
+        let debug_api = mock_debug_api();
+        let err = debug_api.debug_db_tables().await.unwrap_err();
+        assert_eq!(err.message(), "debug_dbTables is disabled");
+    }
+
+    /// A chain spec of a chain that is not Ethereum, e.g. an L2 with its own hardforks.
+    #[derive(Debug, Default, Clone)]
+    struct CustomChainSpec {
+        chain: Chain,
+        hardforks: ChainHardforks,
+    }
+
+    impl EthChainSpec for CustomChainSpec {
+        fn chain(&self) -> Chain {
+            self.chain
+        }
+    }
+
+    impl Hardforks for CustomChainSpec {
+        fn fork<H: Hardfork>(&self, fork: H) -> ForkCondition {
+            self.hardforks.fork(fork)
+        }
+
+        fn forks_iter(&self) -> impl Iterator<Item = (&dyn Hardfork, ForkCondition)> {
+            self.hardforks.forks_iter()
+        }
+    }
+
+    impl EthereumHardforks for CustomChainSpec {}
+
+    /// The debug API can be mounted on top of a provider with a custom chain spec.
+    const fn _assert_debug_api_with_custom_chain_spec<Provider, Eth>()
+    where
+        Provider: BlockReaderIdExt
+            + HeaderProvider
+            + ChainSpecProvider<ChainSpec = CustomChainSpec>
+            + StateProviderFactory
+            + EvmEnvProvider
+            + PruneCheckpointReader
+            + StorageStatsReader
+            + 'static,
+        Eth: EthApiSpec + EthTransactions + TraceExt + 'static,
+    {
+        const fn assert_debug_api_server<T: DebugApiServer>() {}
+        assert_debug_api_server::<DebugApi<Provider, Eth>>();
+    }
+}
@@ -0,0 +1,320 @@
+//! Concurrency limits for the tracing requests of the `debug` API.
+
+use super::config::{validate_reserved_call_permits, DebugApiConfigError};
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_rpc_eth_types::{DebugApiError, EthApiError};
+use reth_tasks::pool::BlockingTaskGuard;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{AcquireError, OwnedSemaphorePermit};
+
+/// Default maximum time a `debug` request waits for a trace permit.
+pub const DEFAULT_MAX_TRACE_PERMIT_WAIT: Duration = Duration::from_secs(10);
+
+/// The class of a `debug` request that needs a trace permit.
+///
+/// The permits of each class can be limited separately, so that e.g. a burst of execution witness
+/// requests doesn't starve call traces. Classes without their own limit share the
+/// [`BlockingTaskGuard`] of the `debug` API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceClass {
+    /// Tracing of transactions and calls, e.g. `debug_traceCall`.
+    Call,
+    /// Tracing of whole blocks, e.g. `debug_traceBlockByNumber`.
+    Block,
+    /// Generation and execution of execution witnesses, e.g. `debug_executionWitness`.
+    Witness,
+}
+
+impl TraceClass {
+    /// All trace classes.
+    pub const ALL: [Self; 3] = [Self::Call, Self::Block, Self::Witness];
+
+    /// Returns the name of the class, as used in metric labels.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::Block => "block",
+            Self::Witness => "witness",
+        }
+    }
+}
+
+impl fmt::Display for TraceClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The trace permits of each [`TraceClass`].
+#[derive(Clone)]
+pub(crate) struct TracePermits {
+    classes: [ClassPermits; 3],
+    /// Limits the requests of the classes other than [`TraceClass::Call`] that share the guard of
+    /// the `debug` API, if some of its permits are reserved for calls.
+    shared_lane: Option<BlockingTaskGuard>,
+    /// The maximum time a request waits for a permit.
+    max_wait: Duration,
+}
+
+/// A permit to execute a tracing request.
+#[derive(Debug)]
+pub(crate) struct TracePermit {
+    /// The permit of the lane of the request, if its class is restricted to one.
+    _lane: Option<OwnedSemaphorePermit>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl TracePermits {
+    /// Limits the number of concurrent requests of the class to `permits`, instead of sharing the
+    /// guard of the `debug` API.
+    pub(crate) fn set_limit(&mut self, class: TraceClass, permits: usize) {
+        self.classes[class as usize].guard = Some(BlockingTaskGuard::new(permits));
+    }
+
+    /// Reserves `reserved` of the permits of the shared guard for requests of
+    /// [`TraceClass::Call`], so that requests of other classes sharing the guard never take them.
+    ///
+    /// Fails if no permits would be left for the other classes.
+    pub(crate) fn set_reserved_call_permits(
+        &mut self,
+        shared: &BlockingTaskGuard,
+        reserved: usize,
+    ) -> Result<(), DebugApiConfigError> {
+        validate_reserved_call_permits(reserved, shared.max_permits())?;
+        self.shared_lane = Some(BlockingTaskGuard::new(shared.max_permits() - reserved));
+        Ok(())
+    }
+
+    /// Configures the maximum time a request waits for a permit.
+    pub(crate) fn set_max_wait(&mut self, max_wait: Duration) {
+        self.max_wait = max_wait;
+    }
+
+    /// Returns the guard of the class, or the shared guard if the class isn't limited separately.
+    pub(crate) fn guard<'a>(
+        &'a self,
+        class: TraceClass,
+        shared: &'a BlockingTaskGuard,
+    ) -> &'a BlockingTaskGuard {
+        self.classes[class as usize].guard.as_ref().unwrap_or(shared)
+    }
+
+    /// Returns the lane the requests of the class are restricted to, if any.
+    fn lane(&self, class: TraceClass) -> Option<&BlockingTaskGuard> {
+        let restricted = class != TraceClass::Call && self.classes[class as usize].guard.is_none();
+        self.shared_lane.as_ref().filter(|_| restricted)
+    }
+
+    /// Returns the number of requests waiting for the guard of the class, including requests of
+    /// other classes that share it.
+    pub(crate) fn queued(&self, class: TraceClass) -> usize {
+        let separate = self.classes[class as usize].guard.is_some();
+        let queued = |permits: &ClassPermits| permits.queued.load(Ordering::Relaxed);
+        if separate {
+            queued(&self.classes[class as usize])
+        } else {
+            self.classes.iter().filter(|permits| permits.guard.is_none()).map(queued).sum()
+        }
+    }
+
+    /// Acquires a permit of the class, waiting at most the configured maximum time for one to
+    /// become available.
+    ///
+    /// Fails with [`DebugApiError::TracerCapacityExceeded`] if no permit became available in
+    /// time, and with [`DebugApiError::TracingUnavailable`] if the guard was closed.
+    pub(crate) async fn acquire(
+        &self,
+        class: TraceClass,
+        shared: &BlockingTaskGuard,
+    ) -> Result<TracePermit, EthApiError> {
+        let permits = &self.classes[class as usize];
+        let _queued = Queued::new(permits);
+        let acquire = async {
+            let lane = match self.lane(class) {
+                Some(lane) => Some(lane.clone().acquire_owned().await?),
+                None => None,
+            };
+            let permit = self.guard(class, shared).clone().acquire_owned().await?;
+            Ok::<_, AcquireError>(TracePermit { _lane: lane, _permit: permit })
+        };
+        let acquired = tokio::time::timeout(self.max_wait, acquire).await;
+
+        match acquired {
+            Ok(Ok(permit)) => Ok(permit),
+            Ok(Err(_)) => Err(DebugApiError::TracingUnavailable.into()),
+            Err(_) => {
+                permits.metrics.timeouts.increment(1);
+                // the queue length includes this request
+                Err(DebugApiError::TracerCapacityExceeded {
+                    queued: self.queued(class),
+                    retry_after: self.max_wait,
+                }
+                .into())
+            }
+        }
+    }
+
+    /// Acquires a permit of the class if one is available without waiting.
+    pub(crate) fn try_acquire(
+        &self,
+        class: TraceClass,
+        shared: &BlockingTaskGuard,
+    ) -> Option<TracePermit> {
+        let lane = match self.lane(class) {
+            Some(lane) => Some(lane.clone().try_acquire_owned().ok()?),
+            None => None,
+        };
+        let permit = self.guard(class, shared).clone().try_acquire_owned().ok()?;
+        Some(TracePermit { _lane: lane, _permit: permit })
+    }
+}
+
+impl Default for TracePermits {
+    fn default() -> Self {
+        Self {
+            classes: TraceClass::ALL.map(ClassPermits::new),
+            shared_lane: None,
+            max_wait: DEFAULT_MAX_TRACE_PERMIT_WAIT,
+        }
+    }
+}
+
+impl fmt::Debug for TracePermits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("TracePermits");
+        for class in TraceClass::ALL {
+            f.field(class.as_str(), &self.classes[class as usize].guard);
+        }
+        f.field("shared_lane", &self.shared_lane).field("max_wait", &self.max_wait).finish()
+    }
+}
+
+#[derive(Clone)]
+struct ClassPermits {
+    /// The guard of the class, if it's limited separately.
+    guard: Option<BlockingTaskGuard>,
+    /// The number of requests of the class waiting for a permit.
+    queued: Arc<AtomicUsize>,
+    metrics: TracePermitMetrics,
+}
+
+impl ClassPermits {
+    fn new(class: TraceClass) -> Self {
+        Self {
+            guard: None,
+            queued: Default::default(),
+            metrics: TracePermitMetrics::new_with_labels(&[("class", class.as_str())]),
+        }
+    }
+}
+
+/// Counts a request as waiting for a permit until it's dropped, including when the request is
+/// cancelled while waiting.
+struct Queued<'a>(&'a ClassPermits);
+
+impl<'a> Queued<'a> {
+    fn new(permits: &'a ClassPermits) -> Self {
+        permits.queued.fetch_add(1, Ordering::Relaxed);
+        permits.metrics.queued.increment(1.0);
+        Self(permits)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.queued.fetch_sub(1, Ordering::Relaxed);
+        self.0.metrics.queued.decrement(1.0);
+    }
+}
+
+#[derive(Metrics, Clone)]
+#[metrics(scope = "rpc.debug.trace_permits")]
+struct TracePermitMetrics {
+    /// The number of requests waiting for a trace permit.
+    queued: Gauge,
+    /// The number of requests that gave up waiting for a trace permit.
+    timeouts: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn class_limits() {
+        let shared = BlockingTaskGuard::new(1);
+        let mut permits = TracePermits::default();
+        permits.set_limit(TraceClass::Witness, 1);
+
+        // the witness permit is separate from the shared one
+        let _witness = permits.acquire(TraceClass::Witness, &shared).await.unwrap();
+        let call = permits.acquire(TraceClass::Call, &shared).await.unwrap();
+        assert!(permits.guard(TraceClass::Witness, &shared).clone().try_acquire_owned().is_err());
+
+        // classes without their own limit share the permits
+        assert!(permits.guard(TraceClass::Block, &shared).clone().try_acquire_owned().is_err());
+        drop(call);
+        assert!(permits.guard(TraceClass::Block, &shared).clone().try_acquire_owned().is_ok());
+    }
+
+    #[tokio::test]
+    async fn max_wait() {
+        let shared = BlockingTaskGuard::new(1);
+        let mut permits = TracePermits::default();
+        permits.set_max_wait(Duration::from_millis(10));
+
+        let _permit = permits.acquire(TraceClass::Call, &shared).await.unwrap();
+        let err = permits.acquire(TraceClass::Block, &shared).await.unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::Debug(DebugApiError::TracerCapacityExceeded { queued: 1, retry_after })
+                if retry_after == Duration::from_millis(10)
+        ));
+        // requests are no longer counted after giving up
+        assert_eq!(permits.queued(TraceClass::Block), 0);
+
+        shared.close();
+        let err = permits.acquire(TraceClass::Call, &shared).await.unwrap_err();
+        assert!(matches!(err, EthApiError::Debug(DebugApiError::TracingUnavailable)));
+    }
+
+    #[tokio::test]
+    async fn reserved_call_permits() {
+        let shared = BlockingTaskGuard::new(3);
+        let mut permits = TracePermits::default();
+        // all permits can't be reserved
+        assert!(permits.set_reserved_call_permits(&shared, 3).is_err());
+        // the lane is sized by the capacity of the guard, not by its available permits
+        let acquired = shared.clone().try_acquire_owned().unwrap();
+        permits.set_reserved_call_permits(&shared, 1).unwrap();
+        drop(acquired);
+        permits.set_max_wait(Duration::from_millis(10));
+
+        // block traces and witnesses never take the reserved permit
+        let _block = permits.acquire(TraceClass::Block, &shared).await.unwrap();
+        let witness = permits.try_acquire(TraceClass::Witness, &shared).unwrap();
+        assert!(permits.try_acquire(TraceClass::Block, &shared).is_none());
+        let err = permits.acquire(TraceClass::Block, &shared).await.unwrap_err();
+        assert!(matches!(err, EthApiError::Debug(DebugApiError::TracerCapacityExceeded { .. })));
+        let call = permits.acquire(TraceClass::Call, &shared).await.unwrap();
+
+        // calls can take all permits of the shared guard
+        drop(witness);
+        let _second_call = permits.try_acquire(TraceClass::Call, &shared).unwrap();
+        assert!(permits.try_acquire(TraceClass::Block, &shared).is_none());
+        drop(call);
+        assert!(permits.try_acquire(TraceClass::Block, &shared).is_some());
+    }
+}
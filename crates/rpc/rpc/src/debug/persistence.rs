@@ -0,0 +1,247 @@
+//! The persistence settings of the node, as reported and adjusted by the `debug` API.
+
+use parking_lot::RwLock;
+use reth_rpc_eth_types::EthApiError;
+use reth_rpc_types::debug::TrieFlushInterval;
+use std::{sync::Arc, time::Duration};
+
+/// The settings of when the in-memory state of the node is persisted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PersistenceSettings {
+    /// The accumulated block processing time after which the in-memory state is persisted, unset
+    /// if it's persisted based on the block thresholds only.
+    pub flush_interval: Option<Duration>,
+    /// The number of blocks that are kept in memory once the in-memory state is persisted.
+    pub memory_block_buffer_target: u64,
+    /// The number of blocks beyond the buffer target after which the in-memory state is persisted.
+    pub persistence_threshold: u64,
+}
+
+/// A handle to the persistence settings, shared by the `debug` API and the components that
+/// persist the state.
+///
+/// The handle starts with the settings the node was started with. If it's read-only,
+/// `debug_setTrieFlushInterval` is rejected, but the settings can still be read. The engines of the
+/// node don't read the settings back, so the node always configures a read-only handle; adjustable
+/// handles are only useful to components that persist the state based on [`Self::settings`].
+///
+/// Handles are equal if they share the same settings.
+#[derive(Clone, Debug)]
+pub struct PersistenceSettingsHandle {
+    settings: Arc<RwLock<PersistenceSettings>>,
+    read_only: bool,
+}
+
+impl PartialEq for PersistenceSettingsHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.settings, &other.settings) && self.read_only == other.read_only
+    }
+}
+
+impl Eq for PersistenceSettingsHandle {}
+
+impl PersistenceSettingsHandle {
+    /// Creates a handle with the startup settings, which can be adjusted at runtime.
+    ///
+    /// The components that persist the state must read the adjusted settings via
+    /// [`Self::settings`], otherwise adjusting them has no effect.
+    pub fn new(settings: PersistenceSettings) -> Self {
+        Self { settings: Arc::new(RwLock::new(settings)), read_only: false }
+    }
+
+    /// Creates a handle with the startup settings, which can't be adjusted at runtime.
+    pub fn read_only(settings: PersistenceSettings) -> Self {
+        Self { read_only: true, ..Self::new(settings) }
+    }
+
+    /// Returns the currently effective settings.
+    pub fn settings(&self) -> PersistenceSettings {
+        *self.settings.read()
+    }
+
+    /// Returns whether the settings can't be adjusted at runtime.
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Sets the flush interval, given as a Go duration string like `1h0m0s`.
+    ///
+    /// Fails with [`EthApiError::InvalidParams`] if the interval can't be parsed, and with
+    /// [`EthApiError::Unsupported`] if the handle is read-only.
+    pub(crate) fn set_flush_interval(&self, interval: &str) -> Result<(), EthApiError> {
+        let flush_interval = parse_go_duration(interval)
+            .ok_or_else(|| EthApiError::InvalidParams(format!("invalid interval: {interval}")))?;
+        if self.read_only {
+            return Err(EthApiError::Unsupported(
+                "runtime adjustment of the persistence settings is disabled",
+            ))
+        }
+        self.settings.write().flush_interval = Some(flush_interval);
+        Ok(())
+    }
+
+    /// Returns the currently effective settings, as returned by `debug_getTrieFlushInterval`.
+    pub(crate) fn trie_flush_interval(&self) -> TrieFlushInterval {
+        let settings = self.settings();
+        TrieFlushInterval {
+            interval: settings.flush_interval.map(format_go_duration),
+            memory_block_buffer_target: settings.memory_block_buffer_target,
+            persistence_threshold: settings.persistence_threshold,
+            read_only: self.read_only,
+        }
+    }
+}
+
+/// The units of Go durations, in nanoseconds.
+const UNITS: [(&str, u128); 8] = [
+    ("ns", 1),
+    ("us", 1_000),
+    ("µs", 1_000),
+    ("μs", 1_000),
+    ("ms", 1_000_000),
+    ("s", 1_000_000_000),
+    ("m", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+];
+
+/// Parses a non-negative duration in the format of Go's `time.ParseDuration`, i.e. a sequence of
+/// decimal numbers with an optional fraction and a unit each, like `1h30m` or `1.5s`.
+fn parse_go_duration(s: &str) -> Option<Duration> {
+    let mut rest = s.strip_prefix('+').unwrap_or(s);
+    if rest == "0" {
+        return Some(Duration::ZERO)
+    }
+    if rest.is_empty() {
+        return None
+    }
+
+    let mut nanos = 0u128;
+    while !rest.is_empty() {
+        let int_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let (int, tail) = rest.split_at(int_len);
+        let (frac, tail) = match tail.strip_prefix('.') {
+            Some(tail) => {
+                let frac_len = tail.find(|c: char| !c.is_ascii_digit()).unwrap_or(tail.len());
+                tail.split_at(frac_len)
+            }
+            None => ("", tail),
+        };
+        if int.is_empty() && frac.is_empty() {
+            return None
+        }
+
+        let unit_len = tail.find(|c: char| c == '.' || c.is_ascii_digit()).unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let (_, unit) = UNITS.iter().find(|(name, _)| *name == unit)?;
+
+        let int = if int.is_empty() { 0 } else { int.parse::<u128>().ok()? };
+        nanos = nanos.checked_add(int.checked_mul(*unit)?)?;
+        // digits beyond nanosecond precision are truncated
+        let mut scale = *unit;
+        for digit in frac.bytes() {
+            scale /= 10;
+            nanos += (digit - b'0') as u128 * scale;
+        }
+        rest = tail;
+    }
+
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Formats a duration like Go's `time.Duration.String`, e.g. `1h0m0s` or `1.5s`.
+fn format_go_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string()
+    }
+    if nanos < 1_000 {
+        return format!("{nanos}ns")
+    }
+    if nanos < 1_000_000 {
+        return format!("{}µs", format_decimal(nanos, 3))
+    }
+    if nanos < 1_000_000_000 {
+        return format!("{}ms", format_decimal(nanos, 6))
+    }
+
+    let secs = format_decimal(nanos % 60_000_000_000, 9);
+    let mins = nanos / 60_000_000_000 % 60;
+    let hours = nanos / 3_600_000_000_000;
+    if hours > 0 {
+        format!("{hours}h{mins}m{secs}s")
+    } else if mins > 0 {
+        format!("{mins}m{secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Formats `value / 10^decimals` without trailing zeros of the fraction.
+fn format_decimal(value: u128, decimals: u32) -> String {
+    let scale = 10u128.pow(decimals);
+    let (int, frac) = (value / scale, value % scale);
+    if frac == 0 {
+        return int.to_string()
+    }
+    let frac = format!("{frac:0width$}", width = decimals as usize);
+    format!("{int}.{}", frac.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn go_durations() {
+        for (s, duration, formatted) in [
+            ("0", Duration::ZERO, "0s"),
+            ("0s", Duration::ZERO, "0s"),
+            ("1h", Duration::from_secs(3600), "1h0m0s"),
+            ("1h0m0s", Duration::from_secs(3600), "1h0m0s"),
+            ("90s", Duration::from_secs(90), "1m30s"),
+            ("+1.5h", Duration::from_secs(5400), "1h30m0s"),
+            ("1.5s", Duration::from_millis(1500), "1.5s"),
+            ("500ms", Duration::from_millis(500), "500ms"),
+            ("1500us", Duration::from_micros(1500), "1.5ms"),
+            ("2µs", Duration::from_micros(2), "2µs"),
+            ("7ns", Duration::from_nanos(7), "7ns"),
+            (".5m", Duration::from_secs(30), "30s"),
+            ("1h2m3.004s", Duration::new(3723, 4_000_000), "1h2m3.004s"),
+        ] {
+            assert_eq!(parse_go_duration(s), Some(duration), "{s}");
+            assert_eq!(format_go_duration(duration), formatted, "{s}");
+        }
+
+        for s in ["", "1", "-1s", "1d", "s", ".s", "1.2.3s", "1h 30m"] {
+            assert_eq!(parse_go_duration(s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn adjust_persistence_settings() {
+        let startup = PersistenceSettings {
+            flush_interval: None,
+            memory_block_buffer_target: 4,
+            persistence_threshold: 8,
+        };
+        let handle = PersistenceSettingsHandle::new(startup);
+        let shared = handle.clone();
+        assert_eq!(handle.trie_flush_interval().interval, None);
+
+        handle.set_flush_interval("1m30s").unwrap();
+        assert_eq!(
+            shared.settings(),
+            PersistenceSettings { flush_interval: Some(Duration::from_secs(90)), ..startup }
+        );
+        assert_eq!(shared.trie_flush_interval().interval.as_deref(), Some("1m30s"));
+        assert!(matches!(
+            handle.set_flush_interval("1d"),
+            Err(EthApiError::InvalidParams(msg)) if msg == "invalid interval: 1d"
+        ));
+
+        let handle = PersistenceSettingsHandle::read_only(startup);
+        assert!(matches!(handle.set_flush_interval("1m"), Err(EthApiError::Unsupported(_))));
+        assert_eq!(handle.settings(), startup);
+    }
+}
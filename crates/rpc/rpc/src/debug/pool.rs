@@ -0,0 +1,146 @@
+//! Tracing of the transactions of the pool by the `debug` API.
+
+use super::{DebugApi, TraceClass};
+use alloy_primitives::B256;
+use reth_chainspec::EthereumHardforks;
+use reth_evm::ConfigureEvmEnv;
+use reth_primitives::{IntoRecoveredTransaction, SealedBlockWithSenders};
+use reth_provider::{
+    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
+    StateProviderFactory,
+};
+use reth_revm::database::StateProviderDatabase;
+use reth_rpc_eth_api::{
+    helpers::{Call, LoadPendingBlock, TraceExt},
+    AsEthApiError, EthApiTypes, FromEthApiError,
+};
+use reth_rpc_eth_types::{
+    cache::db::StateProviderTraitObjWrapper, DebugApiError, EthApiError, PendingBlockEnv,
+};
+use reth_rpc_types::debug::{
+    DebugTraceTransactionResponse, DebugTracingTransactionOptions, PendingTransactionTrace,
+};
+use reth_transaction_pool::TransactionPool;
+use revm::{
+    db::CacheDB,
+    primitives::{Env, EnvWithHandlerCfg},
+};
+use revm_inspectors::tracing::TransactionContext;
+
+impl<Provider, Eth> DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + 'static,
+    Eth: EthApiTypes + TraceExt + 'static,
+{
+    /// Builds a pending block from the best transactions of the pool, the same way the pending
+    /// block of the `eth` API is built locally, but always anew and without caching it.
+    ///
+    /// Fails with [`EthApiError::InvalidParams`] if the pool has no pending transactions, or none
+    /// of them could be included.
+    pub(super) async fn build_pending_from_pool(
+        &self,
+    ) -> Result<SealedBlockWithSenders, Eth::Error> {
+        if LoadPendingBlock::pool(self.eth_api()).pool_size().pending == 0 {
+            return Err(EthApiError::InvalidParams(
+                "can't build the pending block, the transaction pool has no pending transactions"
+                    .to_string(),
+            )
+            .into())
+        }
+        let env = self.eth_api().pending_block_env_and_cfg()?;
+        let (block, _) =
+            self.eth_api().spawn_blocking_io(move |eth_api| eth_api.build_block(env)).await?;
+        if block.body.is_empty() {
+            return Err(EthApiError::InvalidParams(
+                "can't build the pending block, none of the pending transactions of the pool can \
+                 be included"
+                    .to_string(),
+            )
+            .into())
+        }
+        Ok(block)
+    }
+
+    /// Traces the transaction with the given hash from the pool, in the environment of the
+    /// pending block on top of the latest state.
+    ///
+    /// The trace is speculative, the transaction is executed without the transactions that would
+    /// precede it in the pending block, and the response is marked as pending. If the transaction
+    /// is rejected, e.g. because its sender has preceding transactions that are still pending, the
+    /// response carries the error instead of the trace.
+    pub(super) async fn trace_pool_transaction(
+        &self,
+        tx_hash: B256,
+        opts: DebugTracingTransactionOptions,
+    ) -> Result<DebugTraceTransactionResponse, Eth::Error> {
+        let tx = LoadPendingBlock::pool(self.eth_api())
+            .get(&tx_hash)
+            .ok_or(DebugApiError::TransactionNotFound(tx_hash))
+            .map_err(Eth::Error::from_eth_err)?
+            .to_recovered_transaction();
+        let PendingBlockEnv { cfg, block_env, origin } =
+            self.eth_api().pending_block_env_and_cfg()?;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(origin.build_target_hash())
+            .map_err(Eth::Error::from_eth_err)?;
+        let state = self.with_cached_reads(state, origin.build_target_hash());
+        let DebugTracingTransactionOptions { tracing_options, return_state_changes, include_stats } =
+            opts;
+        let opts = self.apply_default_tracer(tracing_options);
+
+        let this = if include_stats { self.with_trace_counters() } else { self.clone() };
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Call);
+            let mut db =
+                CacheDB::new(StateProviderDatabase::new(StateProviderTraitObjWrapper(&state)));
+
+            let env = EnvWithHandlerCfg {
+                env: Env::boxed(
+                    cfg.cfg_env.clone(),
+                    block_env,
+                    Call::evm_config(this.eth_api()).tx_env(&tx),
+                ),
+                handler_cfg: cfg.handler_cfg,
+            };
+
+            let tx_context =
+                TransactionContext { block_hash: None, tx_index: None, tx_hash: Some(tx_hash) };
+            let mut pending = PendingTransactionTrace {
+                pending: true,
+                result: None,
+                error: None,
+                state_changes: None,
+                stats: None,
+            };
+            match this.trace_transaction_response(
+                &opts,
+                env,
+                &mut db,
+                Some(tx_context),
+                return_state_changes,
+            ) {
+                Ok(DebugTraceTransactionResponse::WithExtras(res)) => {
+                    pending.result = Some(res.trace);
+                    pending.state_changes = res.state_changes;
+                    pending.stats = res.stats;
+                }
+                Ok(res) => pending.result = res.into_trace().ok(),
+                // the transaction is rejected, e.g. because of a nonce gap
+                Err(err) if matches!(err.as_err(), Some(EthApiError::InvalidTransaction(_))) => {
+                    pending.error = Some(err.to_string());
+                }
+                Err(err) => return Err(err),
+            }
+            Ok(pending.into())
+        })
+        .await
+    }
+}
@@ -0,0 +1,143 @@
+//! Precompiles changed by the state overrides of traced calls.
+
+use alloy_primitives::Address;
+use reth_rpc_eth_types::{revm_utils::get_precompiles, EthApiError};
+use reth_rpc_types::{debug::PrecompileMoves, state::StateOverride};
+use revm::{handler::register::EvmHandler, primitives::SpecId, Database};
+use std::{collections::HashSet, sync::Arc};
+
+/// The changes the state overrides of a traced call make to the precompiles of the EVM.
+///
+/// Like in geth, overriding the account at the address of a precompile replaces the precompile, so
+/// that e.g. the overridden code is executed instead. A precompile can be moved to another address
+/// with `movePrecompileToAddress`, so that it's still available while its original address is
+/// overridden.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PrecompileOverrides {
+    /// The addresses of the precompiles that are replaced by overridden accounts.
+    replaced: Vec<Address>,
+    /// The moved precompiles, from their original address to the address they're moved to.
+    moved: Vec<(Address, Address)>,
+}
+
+impl PrecompileOverrides {
+    /// Returns the changes the state overrides and precompile moves make to the precompiles of the
+    /// spec, or `None` if they don't affect any precompile.
+    ///
+    /// Fails with [`EthApiError::InvalidParams`] if a moved account isn't a precompile, if a
+    /// precompile is moved to an overridden account, or if multiple precompiles are moved to the
+    /// same address.
+    pub(crate) fn new(
+        state_overrides: Option<&StateOverride>,
+        moves: &PrecompileMoves,
+        spec_id: SpecId,
+    ) -> Result<Option<Self>, EthApiError> {
+        let precompiles = get_precompiles(spec_id).into_iter().collect::<HashSet<_>>();
+        let overridden = state_overrides
+            .into_iter()
+            .flat_map(|overrides| overrides.keys())
+            .chain(moves.0.keys())
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let mut destinations = HashSet::with_capacity(moves.0.len());
+        for (from, to) in &moves.0 {
+            if !precompiles.contains(from) {
+                return Err(EthApiError::InvalidParams(format!(
+                    "account {from} is not a precompile"
+                )));
+            }
+            if overridden.contains(to) {
+                return Err(EthApiError::InvalidParams(format!(
+                    "account {to} is already overridden"
+                )));
+            }
+            if !destinations.insert(*to) {
+                return Err(EthApiError::InvalidParams(format!(
+                    "multiple precompiles are moved to {to}"
+                )))
+            }
+        }
+
+        // moved precompiles are replaced at their original address as well
+        let replaced = overridden
+            .into_iter()
+            .filter(|address| precompiles.contains(address))
+            .collect::<Vec<_>>();
+        if replaced.is_empty() {
+            return Ok(None)
+        }
+        let moved = moves.0.iter().map(|(from, to)| (*from, *to)).collect();
+        Ok(Some(Self { replaced, moved }))
+    }
+
+    /// Installs the changed precompiles in the handler of an EVM.
+    pub(crate) fn apply<EXT, DB: Database>(&self, handler: &mut EvmHandler<'_, EXT, DB>) {
+        let load_precompiles = handler.pre_execution.load_precompiles.clone();
+        let overrides = self.clone();
+        handler.pre_execution.load_precompiles = Arc::new(move || {
+            let mut precompiles = load_precompiles();
+            let precompiles_mut = precompiles.to_mut();
+            let moved = overrides
+                .moved
+                .iter()
+                .filter_map(|(from, to)| Some((*to, precompiles_mut.get(from)?.clone())))
+                .collect::<Vec<_>>();
+            for address in &overrides.replaced {
+                precompiles_mut.remove(address);
+            }
+            precompiles_mut.extend(moved);
+            precompiles
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::state::AccountOverride;
+
+    #[test]
+    fn precompile_overrides() {
+        let identity = Address::with_last_byte(4);
+        let sha256 = Address::with_last_byte(2);
+        let (target, other) = (Address::with_last_byte(0x99), Address::with_last_byte(0x98));
+        let overrides = |accounts: &[Address]| {
+            StateOverride::from_iter(
+                accounts.iter().map(|account| (*account, AccountOverride::default())),
+            )
+        };
+        let new = |state_overrides: &StateOverride, moves: &[(Address, Address)]| {
+            PrecompileOverrides::new(
+                Some(state_overrides),
+                &moves.iter().copied().collect(),
+                SpecId::CANCUN,
+            )
+        };
+
+        // overrides of other accounts don't affect the precompiles
+        assert_eq!(new(&overrides(&[other]), &[]).unwrap(), None);
+        let res = new(&overrides(&[identity]), &[(identity, target)]).unwrap().unwrap();
+        assert_eq!(
+            res,
+            PrecompileOverrides { replaced: vec![identity], moved: vec![(identity, target)] }
+        );
+        // the code of a precompile can be overridden without moving it
+        let res = new(&overrides(&[identity]), &[]).unwrap().unwrap();
+        assert_eq!(res, PrecompileOverrides { replaced: vec![identity], moved: Vec::new() });
+
+        for (state_overrides, moves) in [
+            // not a precompile
+            (overrides(&[other]), vec![(other, target)]),
+            // onto an overridden account
+            (overrides(&[identity, target]), vec![(identity, target)]),
+            // onto another moved precompile
+            (overrides(&[identity, sha256]), vec![(identity, sha256), (sha256, target)]),
+            // multiple precompiles to the same address
+            (overrides(&[identity, sha256]), vec![(identity, target), (sha256, target)]),
+        ] {
+            let err = new(&state_overrides, &moves).unwrap_err();
+            assert!(matches!(err, EthApiError::InvalidParams(_)), "{moves:?}: {err:?}");
+        }
+    }
+}
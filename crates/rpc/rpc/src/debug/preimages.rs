@@ -0,0 +1,172 @@
+//! Preimages of the hashed keys of execution witnesses.
+
+use super::stateless::witness_keys;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use revm::{
+    db::BundleState,
+    primitives::{AccountInfo, Bytecode},
+    Database,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A [`Database`] that records the accounts and storage slots read from the wrapped database.
+///
+/// Unlike the cache of the [`State`](revm::db::State) on top of it, the recorded keys outlive the
+/// destruction of accounts, so the slots of accounts that are selfdestructed by the block are
+/// still known, as are the keys read by the system calls of the block.
+#[derive(Debug)]
+pub(crate) struct AccessRecorder<DB> {
+    inner: DB,
+    accessed: HashMap<Address, HashSet<U256>>,
+}
+
+impl<DB> AccessRecorder<DB> {
+    /// Wraps the database.
+    pub(crate) fn new(inner: DB) -> Self {
+        Self { inner, accessed: HashMap::new() }
+    }
+
+    /// Returns the accounts read so far, and the storage slots read of each of them.
+    pub(crate) const fn accessed(&self) -> &HashMap<Address, HashSet<U256>> {
+        &self.accessed
+    }
+
+    /// Returns the wrapped database.
+    pub(crate) fn into_inner(self) -> DB {
+        self.inner
+    }
+}
+
+impl<DB: Database> Database for AccessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.accessed.entry(address).or_default();
+        self.inner.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.inner.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.accessed.entry(address).or_default().insert(index);
+        self.inner.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.inner.block_hash(number)
+    }
+}
+
+/// The preimages of the hashed keys of the accounts and storage slots accessed by a block, keyed
+/// by hashed key.
+#[derive(Debug, Default)]
+pub(crate) struct StatePreimages {
+    preimages: HashMap<B256, Bytes>,
+}
+
+impl StatePreimages {
+    /// Adds the preimage of the hashed address of the account.
+    pub(crate) fn insert_account(&mut self, address: &Address) {
+        self.preimages
+            .entry(keccak256(address))
+            .or_insert_with(|| alloy_rlp::encode(address).into());
+    }
+
+    /// Adds the preimage of the hashed key of the storage slot.
+    pub(crate) fn insert_slot(&mut self, slot: &U256) {
+        let slot = B256::from(*slot);
+        self.preimages.entry(keccak256(slot)).or_insert_with(|| alloy_rlp::encode(slot).into());
+    }
+
+    /// Adds the preimages of the keys read from the database and of the keys changed by the
+    /// block.
+    ///
+    /// The cache of the block's state drops the slots of destroyed accounts, so these are only
+    /// known from the reads, or from the changes if they were served by the changes of preceding
+    /// blocks.
+    pub(crate) fn extend(
+        &mut self,
+        accessed: &HashMap<Address, HashSet<U256>>,
+        changes: &BundleState,
+    ) {
+        for (address, slots) in accessed {
+            self.insert_account(address);
+            slots.iter().for_each(|slot| self.insert_slot(slot));
+        }
+        for (address, account) in &changes.state {
+            self.insert_account(address);
+            account.storage.keys().for_each(|slot| self.insert_slot(slot));
+        }
+    }
+
+    /// Returns the hashed keys of the leaves of the trie nodes of the witness, rooted in the given
+    /// state root, that lack a preimage, in ascending order.
+    pub(crate) fn missing(&self, witness: &HashMap<B256, Bytes>, state_root: B256) -> Vec<B256> {
+        witness_keys(witness, state_root)
+            .into_iter()
+            .filter(|key| !self.preimages.contains_key(key))
+            .collect()
+    }
+
+    /// Returns the preimages.
+    pub(crate) fn into_inner(self) -> HashMap<B256, Bytes> {
+        self.preimages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_revm::database::StateProviderDatabase;
+    use reth_rpc_eth_types::cache::db::StateProviderTraitObjWrapper;
+    use revm::db::{states::StorageSlot, BundleAccount, StateBuilder};
+
+    #[test]
+    fn preimages_of_reads_and_changes() {
+        let provider = MockEthProvider::default();
+        let (read, changed) = (Address::random(), Address::random());
+        provider.add_account(
+            read,
+            ExtendedAccount::new(1, U256::ZERO).extend_storage([(B256::ZERO, U256::from(1))]),
+        );
+        let mut db = StateBuilder::new()
+            .with_database(AccessRecorder::new(StateProviderDatabase::new(
+                StateProviderTraitObjWrapper(&provider),
+            )))
+            .build();
+        db.storage(read, U256::ZERO).unwrap();
+        db.storage(read, U256::from(7)).unwrap();
+        // reads that are served by the cache aren't passed on to the recorder
+        db.basic(read).unwrap();
+        assert_eq!(
+            db.database.accessed(),
+            &HashMap::from([(read, HashSet::from([U256::ZERO, U256::from(7)]))])
+        );
+
+        let mut changes = BundleState::default();
+        let slot = StorageSlot::new_changed(U256::from(1), U256::ZERO);
+        changes.state.insert(
+            changed,
+            BundleAccount {
+                info: None,
+                original_info: None,
+                storage: HashMap::from([(U256::from(9), slot)]),
+                status: Default::default(),
+            },
+        );
+        let mut preimages = StatePreimages::default();
+        preimages.extend(db.database.accessed(), &changes);
+        let preimages = preimages.into_inner();
+        for address in [read, changed] {
+            assert_eq!(preimages[&keccak256(address)], Bytes::from(alloy_rlp::encode(address)));
+        }
+        for slot in [0, 7, 9] {
+            let slot = B256::from(U256::from(slot));
+            assert_eq!(preimages[&keccak256(slot)], Bytes::from(alloy_rlp::encode(slot)));
+        }
+        assert_eq!(preimages.len(), 5);
+    }
+}
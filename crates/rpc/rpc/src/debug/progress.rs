@@ -0,0 +1,37 @@
+//! Progress of block traces, as pushed by `debug_subscribeTraceBlock`.
+
+use alloy_primitives::B256;
+use reth_rpc_types::debug::TraceProgress;
+use tokio::sync::watch;
+
+/// Records the progress of a block trace.
+///
+/// The recorder can be shared by the threads a block is traced on. The elapsed time of the
+/// recorded progress is left to the receiver.
+#[derive(Debug)]
+pub(crate) struct TraceProgressRecorder {
+    progress: watch::Sender<TraceProgress>,
+}
+
+impl TraceProgressRecorder {
+    /// Returns a receiver that is notified of every change of the progress.
+    pub(crate) fn subscribe(&self) -> watch::Receiver<TraceProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Records that the transaction with the given hash is traced next.
+    pub(crate) fn start(&self, tx_hash: B256) {
+        self.progress.send_modify(|progress| progress.current_transaction = Some(tx_hash));
+    }
+
+    /// Records that a transaction was traced.
+    pub(crate) fn finish(&self) {
+        self.progress.send_modify(|progress| progress.traced_transactions += 1);
+    }
+}
+
+impl Default for TraceProgressRecorder {
+    fn default() -> Self {
+        Self { progress: watch::channel(TraceProgress::default()).0 }
+    }
+}
@@ -0,0 +1,303 @@
+//! Raw data of the chain served by the `debug` API, and the encoding of its raw blocks.
+
+use super::DebugApi;
+use alloy_primitives::{Bytes, B256};
+use alloy_rlp::{Decodable, Encodable};
+use jsonrpsee::core::RpcResult;
+use reth_chainspec::EthereumHardforks;
+use reth_errors::RethError;
+use reth_primitives::{Block, BlockId, BlockNumberOrTag, Requests, Withdrawals};
+use reth_provider::{
+    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
+    StateProviderFactory, TransactionVariant,
+};
+use reth_rpc_eth_api::helpers::{EthTransactions, LoadTransaction, TraceExt};
+use reth_rpc_eth_types::{DebugApiError, EthApiError};
+use reth_rpc_server_types::ToRpcResult;
+use reth_transaction_pool::TransactionPool;
+use revm_primitives::HashMap;
+
+impl<Provider, Eth> DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + 'static,
+    Eth: EthTransactions + TraceExt + 'static,
+{
+    /// Returns the RLP encoding of the header of the given block.
+    pub(super) async fn raw_header(&self, block_id: BlockId) -> RpcResult<Bytes> {
+        if matches!(block_id, BlockId::Number(BlockNumberOrTag::Pending)) {
+            let (block, _) = self.pending_block().await.map_err(Into::into)?;
+            return Ok(alloy_rlp::encode(block.header.header()).into())
+        }
+
+        let header = match self.resolve_block_hash(block_id)? {
+            Some(hash) => self.inner.provider.header(&hash).to_rpc_result()?,
+            None => None,
+        }
+        .ok_or(DebugApiError::BlockNotFound(block_id))?;
+        let mut res = Vec::new();
+        header.encode(&mut res);
+        Ok(res.into())
+    }
+
+    /// Returns the RLP encodings of up to `count` consecutive headers, starting at the given block.
+    pub(super) async fn raw_headers(
+        &self,
+        start: BlockNumberOrTag,
+        count: u64,
+    ) -> RpcResult<Vec<Bytes>> {
+        if count > self.inner.max_raw_headers {
+            return Err(EthApiError::InvalidParams(format!(
+                "too many headers requested; currently limited to {} headers",
+                self.inner.max_raw_headers
+            ))
+            .into())
+        }
+
+        let start = self.resolve_block_number(start)?;
+        let best_number = self.inner.provider.best_block_number().to_rpc_result()?;
+        let end = start.saturating_add(count).min(best_number + 1);
+        let headers = self.inner.provider.headers_range(start..end).to_rpc_result()?;
+        Ok(headers.iter().map(|header| alloy_rlp::encode(header).into()).collect())
+    }
+
+    /// Returns the RLP encoding of the given block, see [`encode_raw_block`].
+    pub(super) async fn raw_block(&self, block_id: BlockId) -> RpcResult<Bytes> {
+        if matches!(block_id, BlockId::Number(BlockNumberOrTag::Pending)) {
+            let (block, _) = self.pending_block().await.map_err(Into::into)?;
+            return Ok(encode_raw_block(block.unseal().block))
+        }
+
+        let block = match self.resolve_block_hash(block_id)? {
+            Some(hash) => self.inner.provider.block_by_hash(hash).to_rpc_result()?,
+            None => None,
+        }
+        .ok_or(DebugApiError::BlockNotFound(block_id))?;
+        Ok(encode_raw_block(block))
+    }
+
+    /// Returns the EIP-2718 encoding of the transaction with the given hash.
+    ///
+    /// If this is a pooled EIP-4844 transaction, the blob sidecar is included, unless
+    /// `exclude_sidecar` is set.
+    pub(super) async fn raw_transaction(
+        &self,
+        hash: B256,
+        exclude_sidecar: Option<bool>,
+    ) -> RpcResult<Option<Bytes>> {
+        if !exclude_sidecar.unwrap_or_default() {
+            return self.inner.eth_api.raw_transaction_by_hash(hash).await.map_err(Into::into)
+        }
+
+        // the canonical encoding of pooled transactions is the one of the consensus transaction
+        if let Some(tx) = LoadTransaction::pool(self.eth_api()).get_pooled_transaction_element(hash)
+        {
+            return Ok(Some(tx.into_transaction().envelope_encoded()))
+        }
+        let tx = self.inner.provider.transaction_by_hash(hash).to_rpc_result()?;
+        Ok(tx.map(|tx| tx.envelope_encoded()))
+    }
+
+    /// Returns the EIP-2718 encodings of the transactions of the given block, or an error if the
+    /// block is unknown.
+    pub(super) async fn raw_transactions(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>> {
+        let block = self
+            .block_with_senders(block_id, TransactionVariant::NoHash)
+            .await
+            .map_err(Into::into)?;
+        Ok(block.into_transactions_ecrecovered().map(|tx| tx.envelope_encoded()).collect())
+    }
+
+    /// Returns the EIP-2718 encodings of the receipts of the given block, or an error if the block
+    /// or its receipts are unknown.
+    pub(super) async fn raw_receipts(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>> {
+        let receipts = if matches!(block_id, BlockId::Number(BlockNumberOrTag::Pending)) {
+            self.pending_block().await.map_err(Into::into)?.1
+        } else {
+            match self.resolve_block_hash(block_id)? {
+                Some(hash) => {
+                    self.inner.provider.receipts_by_block_id(hash.into()).to_rpc_result()?
+                }
+                None => None,
+            }
+            .ok_or(DebugApiError::BlockNotFound(block_id))?
+        };
+        Ok(receipts.into_iter().map(|receipt| receipt.with_bloom().envelope_encoded()).collect())
+    }
+
+    /// Returns the RLP encodings of the blob sidecars of the EIP-4844 transactions of the given
+    /// block.
+    ///
+    /// Sidecars are read from the blob store of the pool, which keeps the sidecars of mined
+    /// transactions until their block is finalized.
+    pub(super) async fn raw_blob_sidecars(&self, block_id: BlockId) -> RpcResult<Vec<Bytes>> {
+        let block = self
+            .block_with_senders(block_id, TransactionVariant::WithHash)
+            .await
+            .map_err(Into::into)?;
+        let tx_hashes =
+            block.body.iter().filter(|tx| tx.is_eip4844()).map(|tx| tx.hash).collect::<Vec<_>>();
+        if tx_hashes.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let mut sidecars = LoadTransaction::pool(self.eth_api())
+            .get_all_blobs(tx_hashes.clone())
+            .map_err(|err| EthApiError::Internal(RethError::other(err)))?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        tx_hashes
+            .into_iter()
+            .map(|hash| {
+                let sidecar =
+                    sidecars.remove(&hash).ok_or(EthApiError::BlobSidecarUnavailable(hash))?;
+                Ok(alloy_rlp::encode(sidecar).into())
+            })
+            .collect()
+    }
+
+    /// Returns the EIP-2718 encoding of the receipt of the transaction with the given hash.
+    pub(super) async fn raw_receipt(&self, hash: B256) -> RpcResult<Option<Bytes>> {
+        let receipt = self.inner.provider.receipt_by_hash(hash).to_rpc_result()?;
+        Ok(receipt.map(|receipt| receipt.with_bloom().envelope_encoded()))
+    }
+}
+
+/// Returns the RLP encoding of the block, with the body fields the hardforks active at the block
+/// require.
+///
+/// The header commits to the withdrawals since Shanghai and to the EIP-7685 requests since Prague,
+/// so that the block is encoded with these lists, even if they're empty or weren't loaded with the
+/// block.
+pub(crate) fn encode_raw_block(mut block: Block) -> Bytes {
+    if block.header.withdrawals_root.is_some() {
+        block.withdrawals.get_or_insert_with(Withdrawals::default);
+    }
+    if block.header.requests_root.is_some() {
+        block.requests.get_or_insert_with(Requests::default);
+    }
+    alloy_rlp::encode(block).into()
+}
+
+/// Decodes a block encoded with the body fields the hardforks active at the block require, see
+/// [`encode_raw_block`].
+///
+/// Fails if the body has withdrawals or requests the header doesn't commit to, which is also the
+/// case if the requests of a Prague block are decoded as the withdrawals of an older block.
+pub(crate) fn decode_raw_block(mut buf: &[u8]) -> alloy_rlp::Result<Block> {
+    let mut block = Block::decode(&mut buf)?;
+    if block.withdrawals.is_some() && block.header.withdrawals_root.is_none() {
+        return Err(alloy_rlp::Error::Custom("block has withdrawals but no withdrawals root"))
+    }
+    if block.requests.is_some() && block.header.requests_root.is_none() {
+        return Err(alloy_rlp::Error::Custom("block has requests but no requests root"))
+    }
+    if block.header.withdrawals_root.is_some() {
+        block.withdrawals.get_or_insert_with(Withdrawals::default);
+    }
+    if block.header.requests_root.is_some() {
+        block.requests.get_or_insert_with(Requests::default);
+    }
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eips::{eip6110::DepositRequest, eip7002::WithdrawalRequest};
+    use alloy_primitives::{Address, FixedBytes, B256};
+    use reth_primitives::{
+        proofs::{calculate_requests_root, calculate_withdrawals_root},
+        Header, Request, Withdrawal,
+    };
+
+    /// A block of a Prague devnet, with withdrawals and deposit and withdrawal requests.
+    fn prague_block() -> Block {
+        let withdrawals = Withdrawals::new(vec![Withdrawal {
+            index: 7,
+            validator_index: 12,
+            address: Address::with_last_byte(0x42),
+            amount: 32_000_000_000,
+        }]);
+        let requests = Requests(vec![
+            Request::DepositRequest(DepositRequest {
+                pubkey: FixedBytes::from([1; 48]),
+                withdrawal_credentials: B256::with_last_byte(1),
+                amount: 32_000_000_000,
+                signature: FixedBytes::from([2; 96]),
+                index: 0,
+            }),
+            Request::WithdrawalRequest(WithdrawalRequest {
+                source_address: Address::with_last_byte(0x43),
+                validator_pubkey: FixedBytes::from([3; 48]),
+                amount: 1_000_000_000,
+            }),
+        ]);
+        let header = Header {
+            number: 10,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            withdrawals_root: Some(calculate_withdrawals_root(&withdrawals)),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(B256::with_last_byte(0xbe)),
+            requests_root: Some(calculate_requests_root(&requests.0)),
+            ..Default::default()
+        };
+        Block {
+            header,
+            body: Vec::new(),
+            ommers: Vec::new(),
+            withdrawals: Some(withdrawals),
+            requests: Some(requests),
+        }
+    }
+
+    #[test]
+    fn prague_block_roundtrip() {
+        let block = prague_block();
+        let raw = encode_raw_block(block.clone());
+        let decoded = decode_raw_block(&raw).unwrap();
+        assert_eq!(decoded, block);
+        assert_eq!(decoded.header.hash_slow(), block.header.hash_slow());
+        assert_eq!(encode_raw_block(decoded), raw);
+    }
+
+    #[test]
+    fn encodes_lists_required_by_header() {
+        // the requests of a Prague block weren't loaded
+        let block = prague_block();
+        let raw = encode_raw_block(Block { requests: None, ..block.clone() });
+        let decoded = decode_raw_block(&raw).unwrap();
+        assert_eq!(decoded.requests, Some(Requests::default()));
+        assert_eq!(decoded.withdrawals, block.withdrawals);
+
+        // without any withdrawals, the empty list precedes the requests
+        let raw = encode_raw_block(Block { withdrawals: None, ..block.clone() });
+        let decoded = decode_raw_block(&raw).unwrap();
+        assert_eq!(decoded.withdrawals, Some(Withdrawals::default()));
+        assert_eq!(decoded.requests, block.requests);
+    }
+
+    #[test]
+    fn rejects_lists_not_committed_to() {
+        let block = prague_block();
+        // requests of a Prague block decoded as withdrawals of a pre-Shanghai block
+        let header = Header { withdrawals_root: None, requests_root: None, ..block.header.clone() };
+        let raw = alloy_rlp::encode(Block {
+            header,
+            withdrawals: Some(Withdrawals::default()),
+            ..block.clone()
+        });
+        assert!(decode_raw_block(&raw).is_err());
+
+        let header = Header { requests_root: None, ..block.header.clone() };
+        let raw = alloy_rlp::encode(Block { header, ..block });
+        assert!(decode_raw_block(&raw).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Cache for the intermediate states of recently traced blocks.
+
+use super::lru::ByteBoundedLru;
+use alloy_primitives::{Address, B256, U256};
+use revm::{
+    db::{CacheDB, DbAccount},
+    primitives::{Bytecode, HashMap},
+};
+use std::{collections::HashSet, mem::size_of, sync::Arc};
+
+/// Default maximum total size of the cached state snapshots, in bytes.
+pub const DEFAULT_STATE_SNAPSHOT_CACHE_MAX_BYTES: usize = 128 * 1024 * 1024;
+
+/// Key of a cached state snapshot: the block hash and the index of the first transaction that is
+/// not applied to the snapshot.
+type StateSnapshotKey = (B256, usize);
+
+/// The cached state of a [`CacheDB`] after replaying some of the transactions of a block on top of
+/// the state of its parent.
+#[derive(Debug)]
+pub(crate) struct StateSnapshot {
+    accounts: HashMap<Address, DbAccount>,
+    contracts: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<U256, B256>,
+}
+
+impl StateSnapshot {
+    /// Captures the cached state of the given database.
+    pub(crate) fn new<DB>(db: &CacheDB<DB>) -> Self {
+        Self {
+            accounts: db.accounts.clone(),
+            contracts: db.contracts.clone(),
+            block_hashes: db.block_hashes.clone(),
+        }
+    }
+
+    /// Replaces the cached state of the given database with the snapshot.
+    ///
+    /// The database is expected to be on top of the same state the snapshot was captured on.
+    pub(crate) fn apply<DB>(&self, db: &mut CacheDB<DB>) {
+        db.accounts.clone_from(&self.accounts);
+        db.contracts.clone_from(&self.contracts);
+        db.block_hashes.clone_from(&self.block_hashes);
+    }
+
+    /// Returns an estimate of the memory used by the snapshot, in bytes.
+    fn size(&self) -> usize {
+        let accounts = self
+            .accounts
+            .values()
+            .map(|account| {
+                size_of::<(Address, DbAccount)>() +
+                    account.storage.len() * size_of::<(U256, U256)>()
+            })
+            .sum::<usize>();
+        let contracts = self
+            .contracts
+            .values()
+            .map(|code| size_of::<(B256, Bytecode)>() + code.len())
+            .sum::<usize>();
+        accounts + contracts + self.block_hashes.len() * size_of::<(U256, B256)>()
+    }
+}
+
+/// An LRU cache for the intermediate states of recently traced blocks.
+///
+/// Tracing a transaction requires replaying all preceding transactions of its block. The state
+/// right before a traced transaction is cached, so tracing a later transaction of the same block
+/// only replays the transactions in between. The cache is bounded by the total size of the cached
+/// states.
+#[derive(Clone, Debug)]
+pub struct StateSnapshotCache {
+    entries: ByteBoundedLru<StateSnapshotKey, Arc<StateSnapshot>>,
+}
+
+impl StateSnapshotCache {
+    /// Creates a new, empty cache that holds at most `max_bytes` of state.
+    pub fn new(max_bytes: usize) -> Self {
+        Self { entries: ByteBoundedLru::new("rpc.debug.state_snapshot_cache", u32::MAX, max_bytes) }
+    }
+
+    /// Returns the cached snapshot of the given block that is closest to, but not after, the
+    /// transaction at the given index, together with the index of the first transaction that is
+    /// not applied to the snapshot.
+    pub(crate) fn get(
+        &self,
+        block_hash: B256,
+        index: usize,
+    ) -> Option<(usize, Arc<StateSnapshot>)> {
+        self.entries
+            .get_max(|(hash, snapshot_index)| *hash == block_hash && *snapshot_index <= index)
+            .map(|((_, snapshot_index), snapshot)| (snapshot_index, snapshot))
+    }
+
+    /// Caches the state of the given block before the transaction at the given index, evicting the
+    /// least recently used snapshots if the cache is full.
+    ///
+    /// Snapshots that exceed the size limit of the cache on their own are not cached.
+    pub(crate) fn insert(&self, block_hash: B256, index: usize, snapshot: StateSnapshot) {
+        let size = snapshot.size();
+        self.entries.insert((block_hash, index), Arc::new(snapshot), size);
+    }
+
+    /// Removes the cached snapshots of the given blocks.
+    pub fn remove_blocks(&self, block_hashes: &HashSet<B256>) {
+        self.entries.remove_blocks(block_hashes);
+    }
+
+    /// Returns the number of cached snapshots.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::db::EmptyDB;
+
+    /// Returns a snapshot with `n` cached accounts.
+    fn snapshot(n: u8) -> StateSnapshot {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for i in 0..n {
+            db.insert_account_info(Address::with_last_byte(i), Default::default());
+        }
+        StateSnapshot::new(&db)
+    }
+
+    #[test]
+    fn snapshot_nearest_before_index() {
+        let cache = StateSnapshotCache::new(DEFAULT_STATE_SNAPSHOT_CACHE_MAX_BYTES);
+        let block_hash = B256::with_last_byte(1);
+        cache.insert(block_hash, 2, snapshot(1));
+        cache.insert(block_hash, 5, snapshot(2));
+
+        assert!(cache.get(block_hash, 1).is_none());
+        assert_eq!(cache.get(block_hash, 2).unwrap().0, 2);
+        assert_eq!(cache.get(block_hash, 4).unwrap().0, 2);
+        let (index, snapshot) = cache.get(block_hash, 9).unwrap();
+        assert_eq!(index, 5);
+        assert_eq!(snapshot.accounts.len(), 2);
+        assert!(cache.get(B256::with_last_byte(2), 9).is_none());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        snapshot.apply(&mut db);
+        assert!(db.accounts.contains_key(&Address::with_last_byte(1)));
+    }
+}
@@ -0,0 +1,287 @@
+//! Shared cache for the state read by requests on top of recent blocks.
+
+use super::lru::ByteBoundedLru;
+use alloy_primitives::{Address, BlockNumber, Bytes, StorageKey, StorageValue, B256};
+use reth_errors::ProviderResult;
+use reth_primitives::{Account, BlockHashOrNumber, Bytecode};
+use reth_provider::{
+    AccountReader, BlockHashReader, StateProofProvider, StateProvider, StateRootProvider,
+    StorageRootProvider,
+};
+use reth_trie::{
+    updates::TrieUpdates, AccountProof, HashedPostState, HashedStorage, MultiProof, TrieInput,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    mem::size_of,
+};
+
+/// Default maximum total size of the cached state reads, in bytes.
+pub const DEFAULT_STATE_READ_CACHE_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Key of a cached state read: the hash of the block the state belongs to and the read value.
+type StateReadCacheKey = (B256, StateReadKey);
+
+/// A value of the state of a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum StateReadKey {
+    Account(Address),
+    Storage(Address, StorageKey),
+    Code(B256),
+}
+
+/// The result of reading a [`StateReadKey`] from the database.
+#[derive(Clone, Debug)]
+enum StateRead {
+    Account(Option<Account>),
+    Storage(Option<StorageValue>),
+    Code(Option<Bytecode>),
+}
+
+impl StateRead {
+    /// Returns an estimate of the memory used by the cached read, in bytes.
+    fn size(&self) -> usize {
+        let code = match self {
+            Self::Code(Some(code)) => code.0.len(),
+            _ => 0,
+        };
+        size_of::<(StateReadCacheKey, Self)>() + code
+    }
+}
+
+/// An LRU cache for the state read by requests on top of recent blocks.
+///
+/// The accounts, storage slots and bytecodes read from the database are cached by the hash of the
+/// block whose state they were read from, so requests on top of the same block, e.g. traces of the
+/// transactions of the latest block, share their reads instead of reading the same hot accounts
+/// from the database again. Only the unmodified state of the database is cached, the changes of a
+/// request stay in its own [`CacheDB`](revm::db::CacheDB). The cache is bounded by the total size
+/// of the cached reads.
+#[derive(Clone, Debug)]
+pub struct StateReadCache {
+    entries: ByteBoundedLru<StateReadCacheKey, StateRead>,
+}
+
+impl StateReadCache {
+    /// Creates a new, empty cache that holds at most `max_bytes` of state.
+    pub fn new(max_bytes: usize) -> Self {
+        Self { entries: ByteBoundedLru::new("rpc.debug.state_read_cache", u32::MAX, max_bytes) }
+    }
+
+    /// Returns the cached value of the state of the given block, or reads and caches it.
+    ///
+    /// The lock of the cache isn't held while the value is read.
+    fn get_or_read(
+        &self,
+        block_hash: B256,
+        key: StateReadKey,
+        read: impl FnOnce() -> ProviderResult<StateRead>,
+    ) -> ProviderResult<StateRead> {
+        let key = (block_hash, key);
+        if let Some(value) = self.entries.get(&key) {
+            return Ok(value)
+        }
+
+        let value = read()?;
+        self.entries.insert(key, value.clone(), value.size());
+        Ok(value)
+    }
+
+    /// Removes the cached state of the given blocks.
+    pub fn remove_blocks(&self, block_hashes: &HashSet<B256>) {
+        self.entries.remove_blocks(block_hashes);
+    }
+
+    /// Returns the number of cached values.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A [`StateProvider`] for the state of a block that looks up accounts, storage slots and
+/// bytecodes in the [`StateReadCache`] before reading them from the wrapped provider.
+///
+/// Everything else, e.g. state roots and proofs, is always computed by the wrapped provider.
+pub(crate) struct CachedStateProvider<S> {
+    state: S,
+    cache: StateReadCache,
+    /// The hash of the block the state belongs to.
+    block_hash: B256,
+}
+
+impl<S> CachedStateProvider<S> {
+    /// Wraps the state of the block with the given hash.
+    pub(crate) const fn new(state: S, cache: StateReadCache, block_hash: B256) -> Self {
+        Self { state, cache, block_hash }
+    }
+
+    /// Reads the value from the cache, or with the given function if it isn't cached.
+    fn read(
+        &self,
+        key: StateReadKey,
+        read: impl FnOnce(&S) -> ProviderResult<StateRead>,
+    ) -> ProviderResult<StateRead> {
+        self.cache.get_or_read(self.block_hash, key, || read(&self.state))
+    }
+}
+
+impl<S: StateProvider> AccountReader for CachedStateProvider<S> {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        let read = self.read(StateReadKey::Account(address), |state| {
+            state.basic_account(address).map(StateRead::Account)
+        })?;
+        let StateRead::Account(account) = read else { unreachable!("read of an account") };
+        Ok(account)
+    }
+}
+
+impl<S: StateProvider> StateProvider for CachedStateProvider<S> {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        let read = self.read(StateReadKey::Storage(account, storage_key), |state| {
+            state.storage(account, storage_key).map(StateRead::Storage)
+        })?;
+        let StateRead::Storage(value) = read else { unreachable!("read of a storage slot") };
+        Ok(value)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        let read = self.read(StateReadKey::Code(code_hash), |state| {
+            state.bytecode_by_hash(code_hash).map(StateRead::Code)
+        })?;
+        let StateRead::Code(code) = read else { unreachable!("read of a bytecode") };
+        Ok(code)
+    }
+}
+
+impl<S: StateProvider> BlockHashReader for CachedStateProvider<S> {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.state.block_hash(number)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        self.state.canonical_hashes_range(start, end)
+    }
+
+    fn convert_block_hash(
+        &self,
+        hash_or_number: BlockHashOrNumber,
+    ) -> ProviderResult<Option<B256>> {
+        self.state.convert_block_hash(hash_or_number)
+    }
+}
+
+impl<S: StateProvider> StateRootProvider for CachedStateProvider<S> {
+    fn state_root(&self, hashed_state: HashedPostState) -> ProviderResult<B256> {
+        self.state.state_root(hashed_state)
+    }
+
+    fn state_root_from_nodes(&self, input: TrieInput) -> ProviderResult<B256> {
+        self.state.state_root_from_nodes(input)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        hashed_state: HashedPostState,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        self.state.state_root_with_updates(hashed_state)
+    }
+
+    fn state_root_from_nodes_with_updates(
+        &self,
+        input: TrieInput,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        self.state.state_root_from_nodes_with_updates(input)
+    }
+}
+
+impl<S: StateProvider> StorageRootProvider for CachedStateProvider<S> {
+    fn storage_root(
+        &self,
+        address: Address,
+        hashed_storage: HashedStorage,
+    ) -> ProviderResult<B256> {
+        self.state.storage_root(address, hashed_storage)
+    }
+}
+
+impl<S: StateProvider> StateProofProvider for CachedStateProvider<S> {
+    fn proof(
+        &self,
+        input: TrieInput,
+        address: Address,
+        slots: &[B256],
+    ) -> ProviderResult<AccountProof> {
+        self.state.proof(input, address, slots)
+    }
+
+    fn multiproof(
+        &self,
+        input: TrieInput,
+        targets: HashMap<B256, HashSet<B256>>,
+    ) -> ProviderResult<MultiProof> {
+        self.state.multiproof(input, targets)
+    }
+
+    fn witness(
+        &self,
+        input: TrieInput,
+        target: HashedPostState,
+    ) -> ProviderResult<HashMap<B256, Bytes>> {
+        self.state.witness(input, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+
+    #[test]
+    fn serves_cached_reads_of_the_same_block() {
+        let provider = MockEthProvider::default();
+        let address = Address::random();
+        let slot = B256::ZERO;
+        provider.add_account(
+            address,
+            ExtendedAccount::new(1, U256::ZERO).extend_storage([(slot, U256::from(1))]),
+        );
+
+        let cache = StateReadCache::new(DEFAULT_STATE_READ_CACHE_MAX_BYTES);
+        let block_hash = B256::with_last_byte(1);
+        let state =
+            |block_hash| CachedStateProvider::new(provider.clone(), cache.clone(), block_hash);
+        assert_eq!(state(block_hash).basic_account(address).unwrap().unwrap().nonce, 1);
+        assert_eq!(state(block_hash).storage(address, slot).unwrap(), Some(U256::from(1)));
+        assert_eq!(cache.len(), 2);
+
+        // the reads of the block are served from the cache, regardless of the database
+        provider.add_account(
+            address,
+            ExtendedAccount::new(2, U256::ZERO).extend_storage([(slot, U256::from(2))]),
+        );
+        assert_eq!(state(block_hash).basic_account(address).unwrap().unwrap().nonce, 1);
+        assert_eq!(state(block_hash).storage(address, slot).unwrap(), Some(U256::from(1)));
+        // but not those of other blocks
+        let other = B256::with_last_byte(2);
+        assert_eq!(state(other).basic_account(address).unwrap().unwrap().nonce, 2);
+
+        // and no longer once the block is reorged out
+        cache.remove_blocks(&HashSet::from([block_hash]));
+        assert_eq!(state(block_hash).storage(address, slot).unwrap(), Some(U256::from(2)));
+        assert_eq!(cache.len(), 2);
+    }
+}
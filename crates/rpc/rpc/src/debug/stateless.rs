@@ -0,0 +1,709 @@
+//! Stateless execution of blocks on the state of an execution witness.
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Decodable, Encodable, EMPTY_STRING_CODE};
+use reth_errors::RethError;
+use reth_primitives::Header;
+use reth_rpc_eth_types::EthApiError;
+use reth_rpc_types::debug::{DebugExecutionWitness, MissingWitnessKey};
+use reth_trie::{Nibbles, TrieAccount, TrieNode, EMPTY_ROOT_HASH};
+use revm::{
+    db::BundleState,
+    primitives::{AccountInfo, Bytecode, KECCAK_EMPTY},
+    Database,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    mem,
+};
+
+/// The error of a [`WitnessDatabase`] if the execution accesses data that is missing in the
+/// witness.
+#[derive(Debug, thiserror::Error)]
+#[error("{0} is missing in the execution witness")]
+pub(crate) struct MissingInWitness(MissingWitnessKey);
+
+impl From<MissingInWitness> for EthApiError {
+    fn from(err: MissingInWitness) -> Self {
+        Self::Internal(RethError::other(err))
+    }
+}
+
+/// A database that resolves all state of an execution purely from an execution witness.
+///
+/// Accounts and storage slots are looked up in the state and storage tries, which are rebuilt
+/// from the trie nodes of the witness, starting at the state root of the parent block. Codes are
+/// looked up by code hash, and the hashes of ancestors via the headers of the witness. Nodes and
+/// codes are keyed by the hash of their content, so the keys of the witness aren't trusted.
+///
+/// Any data that is missing in the witness is recorded and fails the execution.
+#[derive(Debug)]
+pub(crate) struct WitnessDatabase {
+    nodes: HashMap<B256, Bytes>,
+    codes: HashMap<B256, Bytes>,
+    block_hashes: HashMap<u64, B256>,
+    state: WitnessTrie,
+    /// The storage tries of the accessed accounts.
+    storages: HashMap<Address, WitnessTrie>,
+    /// The data missing in the witness, in the order it was accessed.
+    missing: Vec<MissingWitnessKey>,
+}
+
+impl WitnessDatabase {
+    /// Creates a database on the state of the given witness, for the execution of a block on top
+    /// of the given parent.
+    ///
+    /// Only the headers of the witness that form a chain of ancestors of the parent are used.
+    pub(crate) fn new(parent: &Header, witness: DebugExecutionWitness) -> Self {
+        let by_hash = |value: Bytes| (keccak256(&value), value);
+
+        let mut block_hashes = HashMap::from([(parent.number, parent.hash_slow())]);
+        let mut headers = witness
+            .headers
+            .iter()
+            .filter_map(|header| Header::decode(&mut header.as_ref()).ok())
+            .collect::<Vec<_>>();
+        headers.sort_unstable_by_key(|header| std::cmp::Reverse(header.number));
+        let mut next = parent.clone();
+        for header in headers {
+            if header.number + 1 == next.number && header.hash_slow() == next.parent_hash {
+                block_hashes.insert(header.number, next.parent_hash);
+                next = header;
+            }
+        }
+        if let Some(number) = next.number.checked_sub(1) {
+            block_hashes.insert(number, next.parent_hash);
+        }
+
+        Self {
+            nodes: witness.witness.witness.into_values().map(by_hash).collect(),
+            codes: witness.codes.into_iter().flatten().map(|(_, code)| by_hash(code)).collect(),
+            block_hashes,
+            state: WitnessTrie::new(parent.state_root),
+            storages: HashMap::new(),
+            missing: Vec::new(),
+        }
+    }
+
+    /// Returns the data missing in the witness that was accessed so far.
+    pub(crate) fn missing(&self) -> &[MissingWitnessKey] {
+        &self.missing
+    }
+
+    /// Takes the data missing in the witness that was accessed so far.
+    pub(crate) fn take_missing(&mut self) -> Vec<MissingWitnessKey> {
+        mem::take(&mut self.missing)
+    }
+
+    /// Applies the changes of the executed block to the tries and returns the post-state root.
+    ///
+    /// Nodes that are missing in the witness but required to apply the changes are recorded, and
+    /// the root is meaningless if any is missing.
+    pub(crate) fn state_root(&mut self, bundle: &BundleState) -> B256 {
+        for (address, account) in &bundle.state {
+            let key = Nibbles::unpack(keccak256(address)).to_vec();
+            let Some(info) = &account.info else {
+                if let Err(hash) = self.state.remove(&self.nodes, &key) {
+                    self.missing.push(MissingWitnessKey::AccountNode { address: *address, hash });
+                }
+                continue;
+            };
+
+            if account.was_destroyed() {
+                self.storages.insert(*address, WitnessTrie::new(EMPTY_ROOT_HASH));
+            } else if !self.storages.contains_key(address) && self.account(*address).is_err() {
+                continue;
+            }
+            let storage =
+                self.storages.entry(*address).or_insert_with(|| WitnessTrie::new(EMPTY_ROOT_HASH));
+            for (slot, value) in account.storage.iter().filter(|(_, value)| value.is_changed()) {
+                let slot = B256::from(*slot);
+                let key = Nibbles::unpack(keccak256(slot)).to_vec();
+                let updated = if value.present_value.is_zero() {
+                    storage.remove(&self.nodes, &key)
+                } else {
+                    storage.insert(&self.nodes, &key, alloy_rlp::encode(value.present_value))
+                };
+                if let Err(hash) = updated {
+                    self.missing.push(MissingWitnessKey::StorageNode {
+                        address: *address,
+                        slot,
+                        hash,
+                    });
+                }
+            }
+
+            let account = TrieAccount {
+                nonce: info.nonce,
+                balance: info.balance,
+                storage_root: storage.root(),
+                code_hash: info.code_hash,
+            };
+            if let Err(hash) = self.state.insert(&self.nodes, &key, alloy_rlp::encode(account)) {
+                self.missing.push(MissingWitnessKey::AccountNode { address: *address, hash });
+            }
+        }
+        self.state.root()
+    }
+
+    /// Looks up the account in the state trie, and opens its storage trie.
+    fn account(&mut self, address: Address) -> Result<Option<TrieAccount>, MissingInWitness> {
+        let key = Nibbles::unpack(keccak256(address)).to_vec();
+        let account = match self.state.get(&self.nodes, &key) {
+            Ok(value) => value.and_then(|value| TrieAccount::decode(&mut value.as_slice()).ok()),
+            Err(hash) => return Err(self.record(MissingWitnessKey::AccountNode { address, hash })),
+        };
+        let storage_root = account.map_or(EMPTY_ROOT_HASH, |account| account.storage_root);
+        self.storages.entry(address).or_insert_with(|| WitnessTrie::new(storage_root));
+        Ok(account)
+    }
+
+    /// Records the missing data and returns the error for it.
+    fn record(&mut self, key: MissingWitnessKey) -> MissingInWitness {
+        self.missing.push(key.clone());
+        MissingInWitness(key)
+    }
+}
+
+impl Database for WitnessDatabase {
+    type Error = MissingInWitness;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok(self.account(address)?.map(|account| AccountInfo {
+            balance: account.balance,
+            nonce: account.nonce,
+            code_hash: account.code_hash,
+            code: None,
+        }))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        match self.codes.get(&code_hash) {
+            Some(code) => Ok(Bytecode::new_raw(code.clone())),
+            None => Err(self.record(MissingWitnessKey::Code { code_hash })),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if !self.storages.contains_key(&address) {
+            self.account(address)?;
+        }
+        let slot = B256::from(index);
+        let key = Nibbles::unpack(keccak256(slot)).to_vec();
+        let storage = self.storages.get_mut(&address).expect("storage trie is opened");
+        match storage.get(&self.nodes, &key) {
+            Ok(value) => Ok(value
+                .and_then(|value| U256::decode(&mut value.as_slice()).ok())
+                .unwrap_or_default()),
+            Err(hash) => Err(self.record(MissingWitnessKey::StorageNode { address, slot, hash })),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => Err(self.record(MissingWitnessKey::BlockHash { number })),
+        }
+    }
+}
+
+/// Returns the hashed keys of all accounts and storage slots whose leaves are part of the trie
+/// nodes of a witness, in ascending order.
+///
+/// The state trie is walked from the given state root, and the storage tries from the storage
+/// roots of the account leaves. Subtries whose nodes are missing in the witness are skipped.
+pub(crate) fn witness_keys(nodes: &HashMap<B256, Bytes>, state_root: B256) -> BTreeSet<B256> {
+    let mut keys = BTreeSet::new();
+    let mut storage_roots = BTreeSet::new();
+    Node::Hash(state_root).visit_leaves(nodes, &mut Vec::new(), &mut |key, value| {
+        if let Ok(account) = TrieAccount::decode(&mut &value[..]) {
+            storage_roots.insert(account.storage_root);
+        }
+        keys.insert(key);
+    });
+    for storage_root in storage_roots {
+        Node::Hash(storage_root).visit_leaves(nodes, &mut Vec::new(), &mut |key, _| {
+            keys.insert(key);
+        });
+    }
+    keys
+}
+
+/// A Merkle Patricia trie that is only partially known.
+///
+/// Nodes are resolved from the trie nodes of a witness by their hash when they are accessed.
+/// Operations that access a node that isn't in the witness fail with the hash of the node.
+#[derive(Debug)]
+struct WitnessTrie {
+    root: Node,
+}
+
+impl WitnessTrie {
+    /// Creates a trie with the given root.
+    fn new(root: B256) -> Self {
+        Self { root: if root == EMPTY_ROOT_HASH { Node::Empty } else { Node::Hash(root) } }
+    }
+
+    /// Returns the value of the leaf with the given key, in nibbles.
+    fn get(&mut self, nodes: &HashMap<B256, Bytes>, key: &[u8]) -> Result<Option<Vec<u8>>, B256> {
+        self.root.get(nodes, key)
+    }
+
+    /// Inserts or updates the leaf with the given key, in nibbles.
+    fn insert(
+        &mut self,
+        nodes: &HashMap<B256, Bytes>,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Result<(), B256> {
+        self.root.insert(nodes, key, value)
+    }
+
+    /// Removes the leaf with the given key, in nibbles.
+    fn remove(&mut self, nodes: &HashMap<B256, Bytes>, key: &[u8]) -> Result<(), B256> {
+        self.root.remove(nodes, key)
+    }
+
+    /// Returns the root hash of the trie.
+    fn root(&self) -> B256 {
+        match &self.root {
+            Node::Empty => EMPTY_ROOT_HASH,
+            Node::Hash(hash) => *hash,
+            node => keccak256(node.encode()),
+        }
+    }
+}
+
+/// A node of a [`WitnessTrie`]. Keys are stored as nibbles.
+#[derive(Clone, Debug, Default)]
+enum Node {
+    #[default]
+    Empty,
+    /// A node that is only known by its hash, until it is resolved from the witness.
+    Hash(B256),
+    Leaf {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        key: Vec<u8>,
+        child: Box<Node>,
+    },
+    Branch {
+        children: Box<[Node; 16]>,
+    },
+}
+
+impl Node {
+    /// Decodes the RLP encoded node.
+    fn decode(mut rlp: &[u8]) -> alloy_rlp::Result<Self> {
+        Ok(match TrieNode::decode(&mut rlp)? {
+            TrieNode::Branch(branch) => {
+                let mut children = empty_children();
+                let mut stack = branch.stack.iter();
+                for index in 0..16u8 {
+                    if branch.state_mask.is_bit_set(index) {
+                        let child =
+                            stack.next().ok_or(alloy_rlp::Error::Custom("missing child"))?;
+                        children[index as usize] = Self::from_reference(child)?;
+                    }
+                }
+                Self::Branch { children }
+            }
+            TrieNode::Extension(extension) => Self::Extension {
+                key: extension.key.to_vec(),
+                child: Box::new(Self::from_reference(&extension.child)?),
+            },
+            TrieNode::Leaf(leaf) => Self::Leaf { key: leaf.key.to_vec(), value: leaf.value },
+        })
+    }
+
+    /// Decodes the reference to a child node, which is either the hash of the node, or the node
+    /// itself if its encoding is shorter than 32 bytes.
+    fn from_reference(reference: &[u8]) -> alloy_rlp::Result<Self> {
+        if reference.len() == B256::len_bytes() + 1 {
+            Ok(Self::Hash(B256::from_slice(&reference[1..])))
+        } else {
+            Self::decode(reference)
+        }
+    }
+
+    /// Returns the given node behind an extension with the given key, unless the key is empty.
+    fn with_prefix(key: &[u8], node: Self) -> Self {
+        if key.is_empty() {
+            node
+        } else {
+            Self::Extension { key: key.to_vec(), child: Box::new(node) }
+        }
+    }
+
+    /// Calls `f` with the key and value of every leaf below the node whose nodes are part of the
+    /// witness, given the nibbles of the path to the node. Leaves whose keys aren't 32 bytes long
+    /// are skipped.
+    fn visit_leaves(
+        &self,
+        nodes: &HashMap<B256, Bytes>,
+        path: &mut Vec<u8>,
+        f: &mut impl FnMut(B256, &[u8]),
+    ) {
+        let len = path.len();
+        match self {
+            Self::Empty => {}
+            Self::Hash(hash) => {
+                if let Some(node) = nodes.get(hash).and_then(|rlp| Self::decode(rlp).ok()) {
+                    node.visit_leaves(nodes, path, f);
+                }
+            }
+            Self::Leaf { key, value } => {
+                path.extend_from_slice(key);
+                if path.len() == 2 * B256::len_bytes() {
+                    f(B256::from_slice(&Nibbles::from_nibbles_unchecked(&path[..]).pack()), value);
+                }
+            }
+            Self::Extension { key, child } => {
+                path.extend_from_slice(key);
+                child.visit_leaves(nodes, path, f);
+            }
+            Self::Branch { children } => {
+                for (index, child) in children.iter().enumerate() {
+                    path.push(index as u8);
+                    child.visit_leaves(nodes, path, f);
+                    path.pop();
+                }
+            }
+        }
+        path.truncate(len);
+    }
+
+    /// Resolves the node from the witness if it's only known by its hash.
+    fn resolve(&mut self, nodes: &HashMap<B256, Bytes>) -> Result<(), B256> {
+        if let Self::Hash(hash) = self {
+            let hash = *hash;
+            let rlp = nodes.get(&hash).ok_or(hash)?;
+            *self = Self::decode(rlp).map_err(|_| hash)?;
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, nodes: &HashMap<B256, Bytes>, key: &[u8]) -> Result<Option<Vec<u8>>, B256> {
+        self.resolve(nodes)?;
+        match self {
+            Self::Empty => Ok(None),
+            Self::Hash(hash) => Err(*hash),
+            Self::Leaf { key: leaf_key, value } => {
+                Ok((leaf_key.as_slice() == key).then(|| value.clone()))
+            }
+            Self::Extension { key: extension_key, child } => {
+                match key.strip_prefix(extension_key.as_slice()) {
+                    Some(rest) => child.get(nodes, rest),
+                    None => Ok(None),
+                }
+            }
+            Self::Branch { children } => match key.split_first() {
+                Some((index, rest)) => children[*index as usize].get(nodes, rest),
+                None => Ok(None),
+            },
+        }
+    }
+
+    fn insert(
+        &mut self,
+        nodes: &HashMap<B256, Bytes>,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Result<(), B256> {
+        self.resolve(nodes)?;
+        match self {
+            Self::Empty => *self = Self::Leaf { key: key.to_vec(), value },
+            Self::Hash(hash) => return Err(*hash),
+            Self::Leaf { key: leaf_key, value: leaf_value } => {
+                if leaf_key.as_slice() == key {
+                    *leaf_value = value;
+                    return Ok(());
+                }
+                // keys have the same length, so they differ before either ends
+                let common = common_prefix_length(leaf_key, key);
+                let mut children = empty_children();
+                children[leaf_key[common] as usize] = Self::Leaf {
+                    key: leaf_key[common + 1..].to_vec(),
+                    value: mem::take(leaf_value),
+                };
+                children[key[common] as usize] =
+                    Self::Leaf { key: key[common + 1..].to_vec(), value };
+                *self = Self::with_prefix(&key[..common], Self::Branch { children });
+            }
+            Self::Extension { key: extension_key, child } => {
+                if let Some(rest) = key.strip_prefix(extension_key.as_slice()) {
+                    return child.insert(nodes, rest, value);
+                }
+                let common = common_prefix_length(extension_key, key);
+                let mut children = empty_children();
+                children[extension_key[common] as usize] =
+                    Self::with_prefix(&extension_key[common + 1..], mem::take(child.as_mut()));
+                children[key[common] as usize] =
+                    Self::Leaf { key: key[common + 1..].to_vec(), value };
+                *self = Self::with_prefix(&key[..common], Self::Branch { children });
+            }
+            Self::Branch { children } => {
+                if let Some((index, rest)) = key.split_first() {
+                    return children[*index as usize].insert(nodes, rest, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, nodes: &HashMap<B256, Bytes>, key: &[u8]) -> Result<(), B256> {
+        self.resolve(nodes)?;
+        match self {
+            Self::Empty => {}
+            Self::Hash(hash) => return Err(*hash),
+            Self::Leaf { key: leaf_key, .. } => {
+                if leaf_key.as_slice() == key {
+                    *self = Self::Empty;
+                }
+            }
+            Self::Extension { key: extension_key, child } => {
+                let Some(rest) = key.strip_prefix(extension_key.as_slice()) else { return Ok(()) };
+                child.remove(nodes, rest)?;
+                // the child branch may have collapsed into a leaf or an extension
+                let prefix = mem::take(extension_key);
+                *self = match mem::take(child.as_mut()) {
+                    Self::Empty => Self::Empty,
+                    Self::Leaf { key, value } => Self::Leaf { key: [prefix, key].concat(), value },
+                    Self::Extension { key, child } => {
+                        Self::Extension { key: [prefix, key].concat(), child }
+                    }
+                    node => Self::Extension { key: prefix, child: Box::new(node) },
+                };
+            }
+            Self::Branch { children } => {
+                let Some((index, rest)) = key.split_first() else { return Ok(()) };
+                children[*index as usize].remove(nodes, rest)?;
+
+                let mut remaining =
+                    children.iter().enumerate().filter(|(_, child)| !matches!(child, Self::Empty));
+                let only_child = match (remaining.next(), remaining.next()) {
+                    (Some((child_index, _)), None) => child_index,
+                    (None, _) => {
+                        *self = Self::Empty;
+                        return Ok(());
+                    }
+                    _ => return Ok(()),
+                };
+
+                // the branch collapses into its only child, which has to be resolved to be merged
+                children[only_child].resolve(nodes)?;
+                let nibble = only_child as u8;
+                *self = match mem::take(&mut children[only_child]) {
+                    Self::Leaf { key, value } => {
+                        Self::Leaf { key: [vec![nibble], key].concat(), value }
+                    }
+                    Self::Extension { key, child } => {
+                        Self::Extension { key: [vec![nibble], key].concat(), child }
+                    }
+                    node => Self::Extension { key: vec![nibble], child: Box::new(node) },
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the RLP encoding of the node.
+    fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Self::Empty => return vec![EMPTY_STRING_CODE],
+            Self::Hash(hash) => return alloy_rlp::encode(hash),
+            Self::Leaf { key, value } => {
+                hex_prefix(key, true).as_slice().encode(&mut payload);
+                value.as_slice().encode(&mut payload);
+            }
+            Self::Extension { key, child } => {
+                hex_prefix(key, false).as_slice().encode(&mut payload);
+                payload.extend(child.reference());
+            }
+            Self::Branch { children } => {
+                for child in children.iter() {
+                    payload.extend(child.reference());
+                }
+                // branches of the state and storage tries have no value
+                payload.push(EMPTY_STRING_CODE);
+            }
+        }
+        let mut rlp = Vec::with_capacity(payload.len() + 3);
+        alloy_rlp::Header { list: true, payload_length: payload.len() }.encode(&mut rlp);
+        rlp.extend(payload);
+        rlp
+    }
+
+    /// Returns the reference to the node from its parent, which is the node itself if its
+    /// encoding is shorter than 32 bytes, and the hash of its encoding otherwise.
+    fn reference(&self) -> Vec<u8> {
+        match self {
+            Self::Empty => vec![EMPTY_STRING_CODE],
+            Self::Hash(hash) => alloy_rlp::encode(hash),
+            node => {
+                let rlp = node.encode();
+                if rlp.len() < B256::len_bytes() {
+                    rlp
+                } else {
+                    alloy_rlp::encode(keccak256(rlp))
+                }
+            }
+        }
+    }
+}
+
+fn empty_children() -> Box<[Node; 16]> {
+    Box::new(std::array::from_fn(|_| Node::Empty))
+}
+
+fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Returns the hex-prefix encoding of the given nibbles.
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_trie::HashBuilder;
+    use std::collections::BTreeMap;
+
+    fn key(index: u64) -> B256 {
+        keccak256(B256::from(U256::from(index)))
+    }
+
+    fn value(value: u64) -> Vec<u8> {
+        alloy_rlp::encode(U256::from(value))
+    }
+
+    fn expected_root(leaves: &BTreeMap<B256, Vec<u8>>) -> B256 {
+        let mut hash_builder = HashBuilder::default();
+        for (key, value) in leaves {
+            hash_builder.add_leaf(Nibbles::unpack(key), value);
+        }
+        hash_builder.root()
+    }
+
+    /// Collects the encodings of all resolved nodes of the trie, like a witness.
+    fn collect_nodes(node: &Node, nodes: &mut HashMap<B256, Bytes>) {
+        match node {
+            Node::Empty | Node::Hash(_) => return,
+            Node::Leaf { .. } => {}
+            Node::Extension { child, .. } => collect_nodes(child, nodes),
+            Node::Branch { children } => {
+                children.iter().for_each(|child| collect_nodes(child, nodes))
+            }
+        }
+        let rlp = node.encode();
+        nodes.insert(keccak256(&rlp), rlp.into());
+    }
+
+    #[test]
+    fn trie_matches_hash_builder() {
+        let no_nodes = HashMap::new();
+        let mut leaves = (0..64).map(|i| (key(i), value(i + 1))).collect::<BTreeMap<_, _>>();
+        let mut trie = WitnessTrie::new(EMPTY_ROOT_HASH);
+        for (key, value) in &leaves {
+            trie.insert(&no_nodes, &Nibbles::unpack(key), value.clone()).unwrap();
+        }
+        assert_eq!(trie.root(), expected_root(&leaves));
+
+        // the same trie, resolved from its nodes
+        let mut nodes = HashMap::new();
+        collect_nodes(&trie.root, &mut nodes);
+        let mut trie = WitnessTrie::new(expected_root(&leaves));
+        for (key, value) in &leaves {
+            assert_eq!(trie.get(&nodes, &Nibbles::unpack(key)).unwrap().as_ref(), Some(value));
+        }
+        assert_eq!(trie.get(&nodes, &Nibbles::unpack(key(64))).unwrap(), None);
+
+        for i in 0..16 {
+            // update, remove and insert leaves
+            trie.insert(&nodes, &Nibbles::unpack(key(i)), value(i + 2)).unwrap();
+            leaves.insert(key(i), value(i + 2));
+            trie.remove(&nodes, &Nibbles::unpack(key(i + 16))).unwrap();
+            leaves.remove(&key(i + 16));
+            trie.insert(&nodes, &Nibbles::unpack(key(i + 64)), value(i)).unwrap();
+            leaves.insert(key(i + 64), value(i));
+        }
+        assert_eq!(trie.root(), expected_root(&leaves));
+
+        for key in leaves.keys() {
+            trie.remove(&nodes, &Nibbles::unpack(key)).unwrap();
+        }
+        assert_eq!(trie.root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn trie_reports_missing_nodes() {
+        let no_nodes = HashMap::new();
+        let (first, second) = (B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+        let mut trie = WitnessTrie::new(EMPTY_ROOT_HASH);
+        trie.insert(&no_nodes, &Nibbles::unpack(first), value(1)).unwrap();
+        trie.insert(&no_nodes, &Nibbles::unpack(second), value(2)).unwrap();
+        let mut nodes = HashMap::new();
+        collect_nodes(&trie.root, &mut nodes);
+
+        // the leaf of the second key is missing in the witness
+        let Node::Branch { children } = &trie.root else { panic!("expected branch root") };
+        let missing = keccak256(children[2].encode());
+        assert!(nodes.remove(&missing).is_some());
+
+        let mut trie = WitnessTrie::new(trie.root());
+        assert_eq!(trie.get(&nodes, &Nibbles::unpack(first)).unwrap(), Some(value(1)));
+        assert_eq!(trie.get(&nodes, &Nibbles::unpack(second)), Err(missing));
+        // removing the first leaf collapses the branch into the missing leaf
+        assert_eq!(trie.remove(&nodes, &Nibbles::unpack(first)), Err(missing));
+    }
+
+    #[test]
+    fn witness_keys_of_state_and_storage_tries() {
+        let no_nodes = HashMap::new();
+        let mut storage = WitnessTrie::new(EMPTY_ROOT_HASH);
+        for i in 0..4 {
+            storage.insert(&no_nodes, &Nibbles::unpack(key(i)), value(i + 1)).unwrap();
+        }
+        // all accounts share the same storage
+        let account = TrieAccount {
+            nonce: 1,
+            balance: U256::ZERO,
+            storage_root: storage.root(),
+            code_hash: KECCAK_EMPTY,
+        };
+        let accounts = (16..20).map(key).collect::<BTreeSet<_>>();
+        let mut state = WitnessTrie::new(EMPTY_ROOT_HASH);
+        for key in &accounts {
+            state.insert(&no_nodes, &Nibbles::unpack(key), alloy_rlp::encode(account)).unwrap();
+        }
+        let mut nodes = HashMap::new();
+        collect_nodes(&state.root, &mut nodes);
+        collect_nodes(&storage.root, &mut nodes);
+
+        let slots = (0..4).map(key).collect::<BTreeSet<_>>();
+        assert_eq!(witness_keys(&nodes, state.root()), &accounts | &slots);
+        assert!(witness_keys(&nodes, EMPTY_ROOT_HASH).is_empty());
+
+        // the leaves below nodes that are missing in the witness are skipped
+        nodes.remove(&storage.root());
+        assert_eq!(witness_keys(&nodes, state.root()), accounts);
+    }
+}
@@ -0,0 +1,556 @@
+//! Subscriptions of the `debug` API.
+
+use super::{client_limits::ClientRequest, progress::TraceProgressRecorder, DebugApi, TraceClass};
+use futures::{Stream, StreamExt};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    server::SubscriptionMessage,
+    ConnectionId, Extensions, PendingSubscriptionSink, SubscriptionSink,
+};
+use parking_lot::Mutex;
+use reth_chainspec::EthereumHardforks;
+use reth_primitives::{BlockId, SealedBlockWithSenders};
+use reth_provider::{
+    BlockReaderIdExt, CanonStateNotification, ChainSpecProvider, EvmEnvProvider, HeaderProvider,
+    PruneCheckpointReader, StateProviderFactory,
+};
+use reth_rpc_eth_api::{
+    helpers::{EthApiSpec, LoadState, TraceExt},
+    EthApiTypes,
+};
+use reth_rpc_eth_types::{EthApiError, RequestLimit};
+use reth_rpc_server_types::result::internal_rpc_err;
+use reth_rpc_types::{
+    debug::{
+        BlockTraces, DebugSubscriptionKind, DebugSubscriptionResult, DebugTraceBlockResponse,
+        DebugTracingBlockOptions, SkippedBlocks, TraceBlockNotification, TraceProgress,
+    },
+    trace::geth::{GethDebugTracingOptions, TraceResult},
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// The number of notifications that are buffered for a subscriber, before new blocks are skipped.
+const SUBSCRIPTION_BUFFER: usize = 16;
+
+/// The minimum time between two progress notifications of `debug_subscribeTraceBlock`.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Default maximum number of `debug` subscriptions of a connection.
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 4;
+
+/// The suggested time to wait before retrying a subscription that exceeded the subscriptions of
+/// its connection.
+const SUBSCRIPTIONS_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+impl<Provider, Eth> DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + 'static,
+    Eth: EthApiTypes + TraceExt + 'static,
+{
+    /// Accepts a `debug_subscribe` subscription of the given kind and spawns the task serving it,
+    /// or rejects it, e.g. if the node doesn't serve subscriptions.
+    pub(super) async fn debug_subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        ext: &Extensions,
+        kind: DebugSubscriptionKind,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> SubscriptionResult {
+        let Some(events) = &self.inner.canonical_state_events else {
+            pending.reject(internal_rpc_err("subscriptions are not supported")).await;
+            return Ok(())
+        };
+        let opts = opts.unwrap_or_default();
+        if let Err(err) = self.check_tracer(&opts) {
+            pending.reject(err).await;
+            return Ok(())
+        }
+        // the subscription counts against the limits of its client until it ends
+        let guard = match self.acquire_subscription(ext) {
+            Ok(guard) => guard,
+            Err(err) => {
+                pending.reject(err).await;
+                return Ok(())
+            }
+        };
+        match kind {
+            DebugSubscriptionKind::NewBlockTraces => {
+                // subscribe before accepting, so that no block is missed
+                let notifications = events.canonical_state_stream();
+                let sink = pending.accept().await?;
+                let traces = self.clone().pipe_block_traces(sink, notifications, opts);
+                self.inner.subscription_task_spawner.spawn(Box::pin(async move {
+                    let _guard = guard;
+                    traces.await
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Accepts a `debug_subscribeTraceBlock` subscription and spawns the task tracing the block,
+    /// or rejects it, e.g. if the tracer isn't allowed.
+    pub(super) async fn debug_subscribe_trace_block(
+        &self,
+        pending: PendingSubscriptionSink,
+        ext: &Extensions,
+        block: BlockId,
+        opts: Option<DebugTracingBlockOptions>,
+    ) -> SubscriptionResult {
+        let opts = opts.unwrap_or_default();
+        if let Err(err) = self.check_tracer(&opts.tracing_options) {
+            pending.reject(err).await;
+            return Ok(())
+        }
+        // the trace counts against the limits of its client until the subscription ends
+        let guard = match self.acquire_subscription(ext) {
+            Ok(guard) => guard,
+            Err(err) => {
+                pending.reject(err).await;
+                return Ok(())
+            }
+        };
+        let sink = pending.accept().await?;
+        let trace = self.clone().pipe_block_trace(sink, block, opts);
+        self.inner.subscription_task_spawner.spawn(Box::pin(async move {
+            let _guard = guard;
+            trace.await
+        }));
+        Ok(())
+    }
+
+    /// Accounts a subscription against the subscriptions of its connection and the limits of its
+    /// client, until the returned guard is dropped.
+    ///
+    /// Fails with [`EthApiError::RateLimitExceeded`] if the connection has the maximum number of
+    /// subscriptions, or the client exceeds one of its limits.
+    pub(super) fn acquire_subscription(
+        &self,
+        ext: &Extensions,
+    ) -> Result<SubscriptionGuard, EthApiError> {
+        let slot =
+            self.inner.subscriptions.acquire(ext, self.inner.max_subscriptions_per_connection)?;
+        let client = self.inner.client_limits.acquire(ext)?;
+        Ok(SubscriptionGuard { _slot: slot, _client: client })
+    }
+
+    /// Traces the new canonical blocks of the notifications with the given options, and pushes
+    /// their traces to the subscriber until it unsubscribes.
+    pub(super) async fn pipe_block_traces<St>(
+        self,
+        sink: SubscriptionSink,
+        notifications: St,
+        opts: GethDebugTracingOptions,
+    ) where
+        St: Stream<Item = CanonStateNotification> + Unpin,
+    {
+        let (tx, rx) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let traces = self.trace_new_blocks(notifications, opts, BlockTracesSender::new(tx));
+        futures::future::join(traces, push_notifications(sink, rx)).await;
+    }
+
+    /// Traces the block with the given options, and pushes the result to the subscriber,
+    /// preceded by the progress of the trace if requested.
+    ///
+    /// The trace is cancelled if the subscriber unsubscribes before the result is pushed.
+    pub(super) async fn pipe_block_trace(
+        self,
+        sink: SubscriptionSink,
+        block_id: BlockId,
+        opts: DebugTracingBlockOptions,
+    ) where
+        Eth: EthApiSpec,
+    {
+        let (tx, rx) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let trace = self.trace_block_with_progress(block_id, opts, tx);
+        futures::future::join(trace, push_notifications(sink, rx)).await;
+    }
+
+    /// Traces the block with the given options, and sends its result once it's traced.
+    ///
+    /// If the options request the progress, the [`TraceProgress`] is sent whenever it changed,
+    /// at most once per [`PROGRESS_INTERVAL`], while the block is traced. The trace is dropped,
+    /// and thereby cancelled, once the receiver is dropped.
+    pub(super) async fn trace_block_with_progress(
+        &self,
+        block_id: BlockId,
+        opts: DebugTracingBlockOptions,
+        tx: mpsc::Sender<TraceBlockNotification>,
+    ) where
+        Eth: EthApiSpec,
+    {
+        let started_at = Instant::now();
+        let recorder = opts.progress.then(|| Arc::new(TraceProgressRecorder::default()));
+        let mut updates = recorder.as_ref().map(|recorder| recorder.subscribe());
+        let this = Self { trace_progress: recorder, ..self.clone() };
+        let trace = this.trace_block_response(block_id, opts);
+        tokio::pin!(trace);
+
+        let mut next_progress = Instant::now();
+        let result = loop {
+            let throttle = tokio::time::sleep_until(next_progress.into());
+            let progress = async {
+                let Some(updates) = updates.as_mut() else { return std::future::pending().await };
+                throttle.await;
+                if updates.changed().await.is_err() {
+                    return std::future::pending().await
+                }
+                TraceProgress {
+                    elapsed_micros: started_at.elapsed().as_micros() as u64,
+                    ..*updates.borrow_and_update()
+                }
+            };
+            tokio::select! {
+                biased;
+                _ = tx.closed() => return,
+                progress = progress => {
+                    if tx.send(TraceBlockNotification::Progress(progress)).await.is_err() {
+                        return
+                    }
+                    next_progress = Instant::now() + PROGRESS_INTERVAL;
+                }
+                result = &mut trace => break result,
+            }
+        };
+        let notification = match result {
+            Ok(response) => TraceBlockNotification::Result(response),
+            Err(err) => TraceBlockNotification::Error(err.message().to_string()),
+        };
+        let _ = tx.send(notification).await;
+    }
+
+    /// Traces the block like `debug_traceBlockByHash`, while holding a trace permit.
+    async fn trace_block_response(
+        &self,
+        block_id: BlockId,
+        opts: DebugTracingBlockOptions,
+    ) -> RpcResult<DebugTraceBlockResponse>
+    where
+        Eth: EthApiSpec,
+    {
+        // notifications are serialized when they're pushed, not by the middleware of the server
+        self.traced(
+            &Extensions::new(),
+            &self.inner.metrics.subscribe_trace_block,
+            TraceClass::Block,
+            |this| async move {
+                if !opts.allow_while_syncing {
+                    this.ensure_not_syncing(block_id)?;
+                }
+                let (format, verbose) = (opts.result_format, opts.verbose || opts.include_stats);
+                let include_execution_stats = opts.include_execution_stats;
+                let paginated = opts.is_paginated();
+                let context =
+                    this.block_trace_context(block_id, &opts).await.map_err(Into::into)?;
+                let block_id = context.map_or(block_id, |context| context.hash.into());
+                let page = this.debug_trace_block_page(block_id, opts).await.map_err(Into::into)?;
+                Ok(this.block_trace_response(
+                    page,
+                    paginated,
+                    format,
+                    verbose,
+                    include_execution_stats,
+                    context,
+                ))
+            },
+        )
+        .await
+    }
+
+    /// Traces the new canonical blocks of the notifications with the given options, and sends
+    /// their traces until the notifications end or the receiver is dropped.
+    ///
+    /// The blocks of a reorg are marked as such. Blocks that can't be traced are reported as
+    /// skipped.
+    pub(super) async fn trace_new_blocks<St>(
+        &self,
+        mut notifications: St,
+        opts: GethDebugTracingOptions,
+        mut sender: BlockTracesSender,
+    ) where
+        St: Stream<Item = CanonStateNotification> + Unpin,
+    {
+        loop {
+            let notification = tokio::select! {
+                _ = sender.closed() => return,
+                notification = notifications.next() => match notification {
+                    Some(notification) => notification,
+                    None => return,
+                },
+            };
+            let reorg = notification.reverted().is_some();
+            let committed = notification.committed();
+            for block in committed.blocks_iter() {
+                if !sender.reserve() {
+                    continue
+                }
+                let traces = match self.trace_new_block(block, opts.clone()).await {
+                    Ok(traces) => traces,
+                    Err(err) => {
+                        debug!(
+                            target: "rpc::debug",
+                            %err,
+                            block = %block.hash(),
+                            "failed to trace new canonical block"
+                        );
+                        sender.skip();
+                        continue
+                    }
+                };
+                let traces = BlockTraces {
+                    block_hash: block.hash(),
+                    block_number: block.number,
+                    traces,
+                    reorg,
+                };
+                if !sender.send(traces) {
+                    return
+                }
+            }
+        }
+    }
+
+    /// Traces a new canonical block on top of the state of its parent, while holding a trace
+    /// permit.
+    async fn trace_new_block(
+        &self,
+        block: &SealedBlockWithSenders,
+        opts: GethDebugTracingOptions,
+    ) -> RpcResult<Vec<TraceResult>> {
+        let block = block.clone();
+        let (ext, metrics) = (Extensions::new(), &self.inner.metrics.subscribe);
+        self.traced(&ext, metrics, TraceClass::Block, |this| async move {
+            let (cfg, block_env, _) =
+                this.inner.eth_api.evm_env_at(block.hash().into()).await.map_err(Into::into)?;
+            let state = this
+                .inner
+                .provider
+                .state_by_block_hash(block.parent_hash)
+                .map_err(EthApiError::from)?;
+            let state = this.with_cached_reads(state, block.parent_hash);
+            this.trace_block_with(block.unseal(), state, cfg, block_env, opts.into())
+                .await
+                .map_err(Into::into)
+        })
+        .await
+    }
+}
+
+/// The number of `debug` subscriptions of each connection.
+#[derive(Clone, Debug, Default)]
+pub(super) struct ConnectionSubscriptions(Arc<Mutex<HashMap<ConnectionId, u32>>>);
+
+impl ConnectionSubscriptions {
+    /// Takes one of the `max` subscription slots of the connection of the request with the given
+    /// extensions.
+    ///
+    /// Returns `None` if the request has no connection because it's called in-process.
+    fn acquire(&self, ext: &Extensions, max: u32) -> Result<Option<SubscriptionSlot>, EthApiError> {
+        let Some(conn_id) = ext.get::<ConnectionId>().copied() else { return Ok(None) };
+        let mut subscriptions = self.0.lock();
+        let count = subscriptions.entry(conn_id).or_default();
+        if *count >= max {
+            return Err(EthApiError::RateLimitExceeded {
+                limit: RequestLimit::Subscriptions(max),
+                retry_after: SUBSCRIPTIONS_RETRY_AFTER,
+            })
+        }
+        *count += 1;
+        Ok(Some(SubscriptionSlot { subscriptions: self.clone(), conn_id }))
+    }
+}
+
+/// A subscription slot of a connection, which is released once it's dropped.
+#[derive(Debug)]
+struct SubscriptionSlot {
+    subscriptions: ConnectionSubscriptions,
+    conn_id: ConnectionId,
+}
+
+impl Drop for SubscriptionSlot {
+    fn drop(&mut self) {
+        let mut subscriptions = self.subscriptions.0.lock();
+        if let Some(count) = subscriptions.get_mut(&self.conn_id) {
+            *count -= 1;
+            if *count == 0 {
+                subscriptions.remove(&self.conn_id);
+            }
+        }
+    }
+}
+
+/// The accounting of a subscription against the subscriptions of its connection and the limits
+/// of its client, which is released once the subscription ends.
+#[derive(Debug)]
+pub(super) struct SubscriptionGuard {
+    _slot: Option<SubscriptionSlot>,
+    _client: Option<ClientRequest>,
+}
+
+/// Pushes the notifications received on the channel to the subscriber, until the subscriber
+/// unsubscribes or the channel is closed.
+async fn push_notifications<T: Serialize>(sink: SubscriptionSink, mut rx: mpsc::Receiver<T>) {
+    loop {
+        tokio::select! {
+            _ = sink.closed() => break,
+            item = rx.recv() => {
+                let Some(item) = item else { break };
+                let msg = match SubscriptionMessage::from_json(&item) {
+                    Ok(msg) => msg,
+                    Err(err) => {
+                        debug!(target: "rpc::debug", %err, "failed to serialize notification");
+                        break
+                    }
+                };
+                if sink.send(msg).await.is_err() {
+                    break
+                }
+            }
+        }
+    }
+}
+
+/// Sends the notifications of a subscription to the task that pushes them to the subscriber.
+///
+/// Instead of buffering notifications indefinitely for a subscriber that doesn't keep up, new
+/// blocks are skipped while the buffer is full, and the number of skipped blocks is sent before
+/// the traces of the next block.
+#[derive(Debug)]
+pub(super) struct BlockTracesSender {
+    tx: mpsc::Sender<DebugSubscriptionResult>,
+    skipped: u64,
+}
+
+impl BlockTracesSender {
+    /// Creates a sender on the given channel.
+    pub(super) const fn new(tx: mpsc::Sender<DebugSubscriptionResult>) -> Self {
+        Self { tx, skipped: 0 }
+    }
+
+    /// Returns whether there's room for the traces of the next block, preceded by the number of
+    /// skipped blocks if any. Otherwise, the block is counted as skipped.
+    fn reserve(&mut self) -> bool {
+        let required = if self.skipped > 0 { 2 } else { 1 };
+        if self.tx.capacity() < required {
+            self.skipped += 1;
+            return false
+        }
+        true
+    }
+
+    /// Counts a block whose room was reserved as skipped.
+    fn skip(&mut self) {
+        self.skipped += 1;
+    }
+
+    /// Sends the traces of a block after the room for them was reserved, preceded by the number
+    /// of skipped blocks.
+    ///
+    /// Returns `false` if the receiver was dropped.
+    fn send(&mut self, traces: BlockTraces) -> bool {
+        if self.skipped > 0 {
+            let skipped = SkippedBlocks { skipped_blocks: std::mem::take(&mut self.skipped) };
+            if self.tx.try_send(DebugSubscriptionResult::SkippedBlocks(skipped)).is_err() {
+                return false
+            }
+        }
+        self.tx.try_send(DebugSubscriptionResult::BlockTraces(traces)).is_ok()
+    }
+
+    /// Resolves once the receiver was dropped.
+    async fn closed(&self) {
+        self.tx.closed().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    fn traces(number: u64) -> BlockTraces {
+        BlockTraces {
+            block_hash: B256::with_last_byte(number as u8),
+            block_number: number,
+            traces: Vec::new(),
+            reorg: false,
+        }
+    }
+
+    #[test]
+    fn limit_subscriptions_per_connection() {
+        let subscriptions = ConnectionSubscriptions::default();
+        let connection = |id| {
+            let mut ext = Extensions::new();
+            ext.insert(ConnectionId(id));
+            ext
+        };
+        let first = subscriptions.acquire(&connection(1), 2).unwrap();
+        let second = subscriptions.acquire(&connection(1), 2).unwrap();
+        assert!(first.is_some() && second.is_some());
+        let err = subscriptions.acquire(&connection(1), 2).unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::RateLimitExceeded { limit: RequestLimit::Subscriptions(2), .. }
+        ));
+        // other connections aren't affected, and subscriptions without a connection aren't limited
+        assert!(subscriptions.acquire(&connection(2), 2).unwrap().is_some());
+        assert!(subscriptions.acquire(&Extensions::new(), 2).unwrap().is_none());
+
+        drop(first);
+        let third = subscriptions.acquire(&connection(1), 2).unwrap();
+        drop((second, third));
+        assert!(subscriptions.0.lock().is_empty());
+    }
+
+    #[test]
+    fn skips_blocks_while_buffer_is_full() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut sender = BlockTracesSender::new(tx);
+        for number in 1..=2 {
+            assert!(sender.reserve());
+            assert!(sender.send(traces(number)));
+        }
+        // the buffer is full
+        assert!(!sender.reserve());
+        assert!(!sender.reserve());
+
+        // there's no room for the traces after the number of skipped blocks
+        rx.try_recv().unwrap();
+        assert!(!sender.reserve());
+        rx.try_recv().unwrap();
+        assert!(sender.reserve());
+        assert!(sender.send(traces(6)));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            DebugSubscriptionResult::SkippedBlocks(SkippedBlocks { skipped_blocks: 3 })
+        );
+        assert_eq!(rx.try_recv().unwrap(), DebugSubscriptionResult::BlockTraces(traces(6)));
+
+        // blocks that fail to be traced are skipped as well
+        assert!(sender.reserve());
+        sender.skip();
+        assert!(sender.reserve());
+        assert!(sender.send(traces(8)));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            DebugSubscriptionResult::SkippedBlocks(SkippedBlocks { skipped_blocks: 1 })
+        );
+
+        drop(rx);
+        assert!(!sender.send(traces(9)));
+    }
+}
@@ -0,0 +1,265 @@
+//! Tracing of only the top call of a transaction.
+
+use alloy_primitives::{Address, U256};
+use revm::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+    },
+    primitives::Log,
+    Database, EvmContext, Inspector,
+};
+use revm_inspectors::tracing::{TracingInspector, TracingInspectorConfig};
+
+/// A [`TracingInspector`] that only records the top call of a transaction.
+///
+/// The subcalls of the top call are executed without invoking the inner inspector, so that no trace
+/// nodes are allocated for them. This is used for the `callTracer` with `onlyTopCall`, which
+/// discards the subcalls anyway. The recorded top call is the same as the one of an inspector that
+/// records all calls, so are the traces built from it.
+#[derive(Debug)]
+pub(crate) struct TopCallInspector {
+    inner: TracingInspector,
+    /// The depth of the call that is currently executed, the top call has depth 1.
+    depth: usize,
+}
+
+impl TopCallInspector {
+    /// Creates an inspector that records the top call with the given config.
+    pub(crate) fn new(config: TracingInspectorConfig) -> Self {
+        Self { inner: TracingInspector::new(config), depth: 0 }
+    }
+
+    /// Returns the inner inspector, which recorded the top call.
+    pub(crate) fn into_inner(self) -> TracingInspector {
+        self.inner
+    }
+
+    /// Returns whether the top call is currently executed, outside of its subcalls.
+    const fn in_top_call(&self) -> bool {
+        self.depth == 1
+    }
+
+    /// Enters a call, and returns whether it's the top call.
+    fn enter(&mut self) -> bool {
+        self.depth += 1;
+        self.in_top_call()
+    }
+
+    /// Exits a call, and returns whether it was the top call.
+    fn exit(&mut self) -> bool {
+        let top_call = self.in_top_call();
+        self.depth -= 1;
+        top_call
+    }
+}
+
+impl<DB: Database> Inspector<DB> for TopCallInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if self.in_top_call() {
+            self.inner.initialize_interp(interp, context)
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if self.in_top_call() {
+            self.inner.step(interp, context)
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if self.in_top_call() {
+            self.inner.step_end(interp, context)
+        }
+    }
+
+    fn log(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>, log: &Log) {
+        if self.in_top_call() {
+            self.inner.log(interp, context, log)
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if !self.enter() {
+            return None
+        }
+        let outcome = self.inner.call(context, inputs);
+        // the call isn't executed, so it doesn't end either
+        if outcome.is_some() {
+            self.depth -= 1;
+        }
+        outcome
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        if !self.exit() {
+            return outcome
+        }
+        self.inner.call_end(context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if !self.enter() {
+            return None
+        }
+        let outcome = self.inner.create(context, inputs);
+        if outcome.is_some() {
+            self.depth -= 1;
+        }
+        outcome
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if !self.exit() {
+            return outcome
+        }
+        self.inner.create_end(context, inputs, outcome)
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        if !self.enter() {
+            return None
+        }
+        let outcome = self.inner.eofcreate(context, inputs);
+        if outcome.is_some() {
+            self.depth -= 1;
+        }
+        outcome
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if !self.exit() {
+            return outcome
+        }
+        self.inner.eofcreate_end(context, inputs, outcome)
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.in_top_call() {
+            Inspector::<DB>::selfdestruct(&mut self.inner, contract, target, value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{hex, Bytes, TxKind};
+    use reth_rpc_types::trace::geth::{CallConfig, CallFrame};
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{AccountInfo, Bytecode, ResultAndState, SpecId},
+        Evm, GetInspector,
+    };
+
+    const GAS_LIMIT: u64 = 10_000_000;
+
+    /// Deploys contracts that each call the contract of the next level twice, and returns the
+    /// contracts from the top level down. The contract of the last level reverts.
+    fn deploy_call_tree(db: &mut CacheDB<EmptyDB>, levels: u8) -> Vec<Address> {
+        let mut contracts = Vec::new();
+        // REVERT(0, 0)
+        let mut code = Bytes::from_static(&hex!("60006000fd"));
+        for level in (0..levels).rev() {
+            let contract = Address::with_last_byte(0x10 + level);
+            let bytecode = Bytecode::new_raw(code);
+            db.insert_account_info(
+                contract,
+                AccountInfo {
+                    code_hash: bytecode.hash_slow(),
+                    code: Some(bytecode),
+                    ..Default::default()
+                },
+            );
+            contracts.push(contract);
+            // POP(CALL(GAS, contract, 0, 0, 0, 0, 0)) twice, then RETURN(0, 32)
+            let call = [&hex!("6000600060006000600073")[..], contract.as_slice(), &hex!("5af150")]
+                .concat();
+            code = [&call[..], &call, &hex!("60206000f3")].concat().into();
+        }
+        contracts.reverse();
+        contracts
+    }
+
+    fn transact<I: GetInspector<CacheDB<EmptyDB>>>(
+        db: &CacheDB<EmptyDB>,
+        to: Address,
+        inspector: I,
+    ) -> ResultAndState {
+        let mut evm = Evm::builder()
+            .with_db(db.clone())
+            .with_external_context(inspector)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(0xca);
+                tx.transact_to = TxKind::Call(to);
+                tx.gas_limit = GAS_LIMIT;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap()
+    }
+
+    fn call_frame(inspector: TracingInspector, res: &ResultAndState) -> CallFrame {
+        let config = CallConfig { only_top_call: Some(true), with_log: None };
+        inspector
+            .with_transaction_gas_limit(GAS_LIMIT)
+            .into_geth_builder()
+            .geth_call_traces(config, res.result.gas_used())
+    }
+
+    #[test]
+    fn top_call_of_call_tree() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let contracts = deploy_call_tree(&mut db, 8);
+        let config = TracingInspectorConfig::from_geth_call_config(&CallConfig {
+            only_top_call: Some(true),
+            with_log: None,
+        });
+
+        // the top call returns, or reverts if it's the leaf
+        for to in [contracts[0], contracts[5], contracts[7]] {
+            let mut all_calls = TracingInspector::new(config);
+            let res = transact(&db, to, &mut all_calls);
+            let expected = call_frame(all_calls, &res);
+
+            let mut top_call = TopCallInspector::new(config);
+            let res = transact(&db, to, &mut top_call);
+            let top_call = top_call.into_inner();
+            assert_eq!(top_call.traces().nodes().len(), 1);
+            let frame = call_frame(top_call, &res);
+
+            assert_eq!(
+                serde_json::to_string(&frame).unwrap(),
+                serde_json::to_string(&expected).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,584 @@
+//! Geth-style tracing of transactions and blocks, independent of the RPC server.
+
+use super::{
+    annotate_access_status,
+    cancel::Cancellation,
+    counters::{CountingInspector, ExecutionCounts, TraceCountersRecorder},
+    db_stats::{ExecutionStatsRecorder, StatsDb},
+    folded_stacks,
+    memory::{TraceMemory, CALL_FRAME_SIZE, STRUCT_LOG_SIZE},
+    opcode_filter,
+    precompiles::PrecompileOverrides,
+    progress::TraceProgressRecorder,
+    top_call::TopCallInspector,
+    AccessStatusInspector,
+};
+use alloy_primitives::B256;
+use reth_evm::{ConfigureEvm, ConfigureEvmEnv};
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_rpc_eth_api::{
+    helpers::{Call, Trace},
+    AsEthApiError, FromEthApiError, FromEvmError,
+};
+use reth_rpc_eth_types::{DebugApiError, EthApiError, StateCacheDb};
+use reth_rpc_types::{
+    debug::{StructLoggerConfig, TraceResultWithStats, TraceStats, FLAMEGRAPH_TRACER},
+    trace::geth::{
+        FourByteFrame, GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingOptions,
+        GethTrace, NoopFrame, TraceResult,
+    },
+};
+use revm::{
+    primitives::{
+        db::DatabaseCommit, BlockEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg, EvmState,
+        ResultAndState,
+    },
+    Database, GetInspector,
+};
+use revm_inspectors::tracing::{
+    FourByteInspector, MuxInspector, TracingInspector, TracingInspectorConfig, TransactionContext,
+};
+use std::{collections::HashSet, time::Instant};
+
+/// Geth-style tracing of transactions and blocks on top of a given database.
+///
+/// This is the tracing core of [`DebugApi`](super::DebugApi), which wraps it for the RPC server:
+/// the RPC layer resolves blocks, their environments and states, limits the number of concurrent
+/// traces and applies the configured default tracer. None of that is required here, so this can
+/// be used outside of an RPC server, e.g. to trace every new canonical block in an `ExEx`.
+///
+/// This is implemented for every type that can execute transactions, like the `eth` API.
+///
+/// The methods of this trait are part of the stable API of this crate: their signatures and
+/// semantics only change in breaking releases.
+///
+/// Caution: all methods are blocking and should be performed on a blocking task.
+pub trait DebugTraceExt: Trace + Call {
+    /// Executes the configured transaction with the environment on the given database and traces
+    /// it with the tracer of the options, or the struct logger if no tracer is specified.
+    ///
+    /// Returns the trace frame, the state that got updated after executing the transaction and the
+    /// environment, so it can be reused for the next transaction. The state changes are not
+    /// committed to the database.
+    ///
+    /// The transaction context is made available to JS tracers.
+    ///
+    /// Note: this does not apply any state overrides if they're configured in the `opts`.
+    fn trace_transaction(
+        &self,
+        opts: &GethDebugTracingOptions,
+        env: EnvWithHandlerCfg,
+        db: &mut StateCacheDb<'_>,
+        transaction_context: Option<TransactionContext>,
+    ) -> Result<(GethTrace, EvmState, EnvWithHandlerCfg), Self::Error> {
+        trace_with_gas_used(self, opts, env, db, transaction_context, None, None, None, None)
+            .map(|(trace, _, state, env)| (trace, state, env))
+    }
+
+    /// Traces the transactions of a block in order, on top of the given database, which is
+    /// expected to hold the state of the parent block.
+    ///
+    /// The state changes of each transaction are committed to the database, so it holds the state
+    /// after the last transaction once this returns. Pre- and post-block changes, like system
+    /// calls and withdrawals, are not applied. The block hash is made available to JS tracers.
+    ///
+    /// If tracing a transaction fails, a [`TraceResult::Error`] is returned for it and the
+    /// remaining transactions are still traced. This only fails if a transaction can't be executed
+    /// at all.
+    fn trace_block(
+        &self,
+        db: &mut StateCacheDb<'_>,
+        transactions: &[TransactionSignedEcRecovered],
+        cfg: &CfgEnvWithHandlerCfg,
+        block_env: &BlockEnv,
+        opts: &GethDebugTracingOptions,
+        block_hash: Option<B256>,
+    ) -> Result<Vec<TraceResult>, Self::Error> {
+        let results = trace_transactions(
+            self,
+            db,
+            transactions.iter().enumerate(),
+            None,
+            cfg,
+            block_env,
+            opts,
+            block_hash,
+            false,
+            false,
+            &Cancellation::default(),
+            None,
+            None,
+            None,
+            None,
+        )?;
+        Ok(results.into_iter().map(|res| res.result).collect())
+    }
+}
+
+impl<T> DebugTraceExt for T where T: Trace + Call {}
+
+/// Traces the transaction like [`DebugTraceExt::trace_transaction`], and additionally returns the
+/// gas used by the transaction, or zero if the tracer didn't execute it.
+///
+/// If a memory reservation is given, the estimated size of call traces and struct logs is
+/// reserved before their frame is built, failing with
+/// [`DebugApiError::TraceMemoryBudgetExceeded`] if the budget is exhausted. If a recorder is given,
+/// the database lookups of the execution are recorded on it. The precompiles of the EVM are
+/// changed by the given overrides, if any.
+///
+/// If a counters recorder is given, the execution is counted and timed, and its counters are
+/// added to the recorder once it's traced. Otherwise, the execution isn't instrumented at all.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn trace_with_gas_used<Eth>(
+    eth: &Eth,
+    opts: &GethDebugTracingOptions,
+    env: EnvWithHandlerCfg,
+    db: &mut StateCacheDb<'_>,
+    transaction_context: Option<TransactionContext>,
+    memory: Option<&TraceMemory>,
+    stats: Option<&ExecutionStatsRecorder>,
+    precompiles: Option<&PrecompileOverrides>,
+    counters: Option<&TraceCountersRecorder>,
+) -> Result<(GethTrace, u64, EvmState, EnvWithHandlerCfg), Eth::Error>
+where
+    Eth: DebugTraceExt,
+{
+    // the database reads are only counted with a recorder
+    let fallback_stats = ExecutionStatsRecorder::default();
+    let stats = stats.or_else(|| counters.map(|_| &fallback_stats));
+    let mut db = StatsDb::new(db, stats);
+    let Some(counters) = counters else {
+        let execution = Execution { precompiles, counts: None };
+        return trace_on(eth, opts, env, &mut db, transaction_context, memory, execution)
+    };
+
+    let mut counts = ExecutionCounts::default();
+    let execution = Execution { precompiles, counts: Some(&mut counts) };
+    let traced = trace_on(eth, opts, env, &mut db, transaction_context, memory, execution);
+    counters.add(&counts, db.provider_reads(), db.provider_read_time());
+    traced
+}
+
+/// How the EVM executes a traced transaction.
+struct Execution<'a> {
+    /// The precompiles changed by the state overrides of the request, if any.
+    precompiles: Option<&'a PrecompileOverrides>,
+    /// The counts of the execution, if it's counted.
+    counts: Option<&'a mut ExecutionCounts>,
+}
+
+/// Traces the transaction on the wrapped database like [`trace_with_gas_used`].
+fn trace_on<Eth>(
+    eth: &Eth,
+    opts: &GethDebugTracingOptions,
+    env: EnvWithHandlerCfg,
+    db: &mut StatsDb<'_, '_>,
+    #[cfg(not(feature = "js-tracer"))] _transaction_context: Option<TransactionContext>,
+    #[cfg(feature = "js-tracer")] transaction_context: Option<TransactionContext>,
+    memory: Option<&TraceMemory>,
+    mut execution: Execution<'_>,
+) -> Result<(GethTrace, u64, EvmState, EnvWithHandlerCfg), Eth::Error>
+where
+    Eth: DebugTraceExt,
+{
+    let config = &opts.config;
+    let tracer_config = &opts.tracer_config;
+    let reserve = |bytes: usize| {
+        memory.map_or(Ok(()), |memory| memory.reserve(bytes)).map_err(Eth::Error::from_eth_err)
+    };
+
+    if let Some(tracer) = &opts.tracer {
+        return match tracer {
+            GethDebugTracerType::BuiltInTracer(tracer) => match tracer {
+                GethDebugBuiltInTracerType::FourByteTracer => {
+                    let mut inspector = FourByteInspector::default();
+                    let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+                    let gas_used = res.result.gas_used();
+                    return Ok((FourByteFrame::from(&inspector).into(), gas_used, res.state, env))
+                }
+                GethDebugBuiltInTracerType::CallTracer => {
+                    let call_config = tracer_config
+                        .clone()
+                        .into_call_config()
+                        .map_err(|_| DebugApiError::InvalidTracerConfig { tracer: "callTracer" })
+                        .map_err(Eth::Error::from_eth_err)?;
+
+                    let inspector_config =
+                        TracingInspectorConfig::from_geth_call_config(&call_config);
+                    let (res, env, inspector) = if call_config.only_top_call.unwrap_or_default() &&
+                        !call_config.with_log.unwrap_or_default()
+                    {
+                        // the subcalls are discarded, so they don't need to be recorded
+                        let mut inspector = TopCallInspector::new(inspector_config);
+                        let (res, env) =
+                            inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+                        (res, env, inspector.into_inner())
+                    } else {
+                        let mut inspector = TracingInspector::new(inspector_config);
+                        let (res, env) =
+                            inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+                        (res, env, inspector)
+                    };
+                    reserve(inspector.traces().nodes().len() * CALL_FRAME_SIZE)?;
+
+                    let frame = inspector
+                        .with_transaction_gas_limit(env.tx.gas_limit)
+                        .into_geth_builder()
+                        .geth_call_traces(call_config, res.result.gas_used());
+
+                    return Ok((frame.into(), res.result.gas_used(), res.state, env))
+                }
+                GethDebugBuiltInTracerType::PreStateTracer => {
+                    let prestate_config = tracer_config
+                        .clone()
+                        .into_pre_state_config()
+                        .map_err(|_| DebugApiError::InvalidTracerConfig {
+                            tracer: "prestateTracer",
+                        })
+                        .map_err(Eth::Error::from_eth_err)?;
+
+                    let mut inspector = TracingInspector::new(
+                        TracingInspectorConfig::from_geth_prestate_config(&prestate_config),
+                    );
+                    let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+
+                    let frame = inspector
+                        .with_transaction_gas_limit(env.tx.gas_limit)
+                        .into_geth_builder()
+                        .geth_prestate_traces(&res, &prestate_config, &*db)
+                        .map_err(Eth::Error::from_eth_err)?;
+
+                    return Ok((frame.into(), res.result.gas_used(), res.state, env))
+                }
+                GethDebugBuiltInTracerType::NoopTracer => {
+                    Ok((NoopFrame::default().into(), 0, Default::default(), env))
+                }
+                GethDebugBuiltInTracerType::MuxTracer => {
+                    let mux_config = tracer_config
+                        .clone()
+                        .into_mux_config()
+                        .map_err(|_| DebugApiError::InvalidTracerConfig { tracer: "muxTracer" })
+                        .map_err(Eth::Error::from_eth_err)?;
+
+                    let mut inspector = MuxInspector::try_from_config(mux_config)
+                        .map_err(Eth::Error::from_eth_err)?;
+
+                    let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+                    let frame = inspector
+                        .try_into_mux_frame(&res, &*db)
+                        .map_err(Eth::Error::from_eth_err)?;
+                    return Ok((frame.into(), res.result.gas_used(), res.state, env))
+                }
+            },
+            GethDebugTracerType::JsTracer(name) if name == FLAMEGRAPH_TRACER => {
+                let mut inspector = TracingInspector::new(TracingInspectorConfig::none());
+                let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+
+                let stacks = folded_stacks(inspector.traces().nodes());
+                Ok((GethTrace::JS(stacks.into()), res.result.gas_used(), res.state, env))
+            }
+            #[cfg(not(feature = "js-tracer"))]
+            GethDebugTracerType::JsTracer(_) => {
+                Err(Eth::Error::from_eth_err(DebugApiError::JsTracerNotEnabled))
+            }
+            #[cfg(feature = "js-tracer")]
+            GethDebugTracerType::JsTracer(code) => {
+                let config = tracer_config.clone().into_json();
+                let mut inspector =
+                    revm_inspectors::tracing::js::JsInspector::with_transaction_context(
+                        code.clone(),
+                        config,
+                        transaction_context.unwrap_or_default(),
+                    )
+                    .map_err(Eth::Error::from_eth_err)?;
+                let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+
+                let gas_used = res.result.gas_used();
+                let state = res.state.clone();
+                let result =
+                    inspector.json_result(res, &env, &*db).map_err(Eth::Error::from_eth_err)?;
+                Ok((GethTrace::JS(result), gas_used, state, env))
+            }
+        }
+    }
+
+    // default structlog tracer
+    let logger_config: StructLoggerConfig = if tracer_config.0.is_null() {
+        Default::default()
+    } else {
+        tracer_config
+            .clone()
+            .from_value()
+            .map_err(|_| DebugApiError::InvalidTracerConfig { tracer: "structLogger" })
+            .map_err(Eth::Error::from_eth_err)?
+    };
+
+    let mut inspector_config = TracingInspectorConfig::from_geth_config(config);
+    if !logger_config.opcode_filter.is_empty() {
+        // only record the steps of the configured opcodes
+        inspector_config.record_opcodes_filter = Some(opcode_filter(&logger_config.opcode_filter)?);
+    }
+
+    let (res, env, inspector, access_status) = if logger_config.record_access_status {
+        let mut inspector = AccessStatusInspector::new(inspector_config);
+        let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+        let (inspector, access_status) = inspector.into_parts();
+        (res, env, inspector, Some(access_status))
+    } else {
+        let mut inspector = TracingInspector::new(inspector_config);
+        let (res, env) = inspect(eth, &mut *db, env, &mut inspector, &mut execution)?;
+        (res, env, inspector, None)
+    };
+
+    let steps = inspector.traces().nodes().iter().map(|node| node.trace.steps.len()).sum::<usize>();
+    reserve(steps * STRUCT_LOG_SIZE)?;
+
+    let gas_used = res.result.gas_used();
+    let return_value = res.result.into_output().unwrap_or_default();
+    let frame = inspector
+        .with_transaction_gas_limit(env.tx.gas_limit)
+        .into_geth_builder()
+        .geth_traces(gas_used, return_value, config.clone());
+    if let Some(counts) = execution.counts {
+        counts.struct_log_bytes =
+            serde_json::to_vec(&frame.struct_logs).map_or(0, |logs| logs.len() as u64);
+    }
+
+    if let Some(access_status) = access_status {
+        return Ok((annotate_access_status(frame, access_status)?, gas_used, res.state, env))
+    }
+
+    Ok((frame.into(), gas_used, res.state, env))
+}
+
+/// Executes the environment with the inspector like [`Trace::inspect`], with the precompiles of
+/// the EVM changed by the overrides of the execution, if any.
+///
+/// If the execution is counted, it's counted and timed on its counts.
+fn inspect<Eth, DB, I>(
+    eth: &Eth,
+    db: DB,
+    env: EnvWithHandlerCfg,
+    inspector: I,
+    execution: &mut Execution<'_>,
+) -> Result<(ResultAndState, EnvWithHandlerCfg), Eth::Error>
+where
+    Eth: Trace,
+    DB: Database,
+    EthApiError: From<DB::Error>,
+    I: GetInspector<DB>,
+{
+    let Some(counts) = execution.counts.as_deref_mut() else {
+        return execute_with_inspector(eth, db, env, inspector, execution.precompiles)
+    };
+    let started_at = Instant::now();
+    let inspector = CountingInspector::new(inspector, &mut *counts);
+    let res = execute_with_inspector(eth, db, env, inspector, execution.precompiles);
+    counts.execution += started_at.elapsed();
+    res
+}
+
+/// Executes the environment with the inspector, with the precompiles of the EVM changed by the
+/// given overrides, if any.
+fn execute_with_inspector<Eth, DB, I>(
+    eth: &Eth,
+    db: DB,
+    env: EnvWithHandlerCfg,
+    inspector: I,
+    precompiles: Option<&PrecompileOverrides>,
+) -> Result<(ResultAndState, EnvWithHandlerCfg), Eth::Error>
+where
+    Eth: Trace,
+    DB: Database,
+    EthApiError: From<DB::Error>,
+    I: GetInspector<DB>,
+{
+    let Some(precompiles) = precompiles else { return eth.inspect(db, env, inspector) };
+    let mut evm = Trace::evm_config(eth).evm_with_env_and_inspector(db, env, inspector);
+    precompiles.apply(&mut evm.handler);
+    let res = evm.transact().map_err(Eth::Error::from_evm_err)?;
+    let (_, env) = evm.into_db_and_env_with_handler_cfg();
+    Ok((res, env))
+}
+
+/// Executes the environment like [`Call::transact`], with the precompiles of the EVM changed by
+/// the given overrides, if any.
+pub(super) fn transact<Eth, DB>(
+    eth: &Eth,
+    db: DB,
+    env: EnvWithHandlerCfg,
+    precompiles: Option<&PrecompileOverrides>,
+) -> Result<(ResultAndState, EnvWithHandlerCfg), Eth::Error>
+where
+    Eth: Call,
+    DB: Database,
+    EthApiError: From<DB::Error>,
+{
+    let Some(precompiles) = precompiles else { return eth.transact(db, env) };
+    let mut evm = Call::evm_config(eth).evm_with_env(db, env);
+    precompiles.apply(&mut evm.handler);
+    let res = evm.transact().map_err(Eth::Error::from_evm_err)?;
+    let (_, env) = evm.into_db_and_env_with_handler_cfg();
+    Ok((res, env))
+}
+
+/// Traces the given transactions of a block, together with their index in the block, like
+/// [`DebugTraceExt::trace_block`].
+///
+/// If a set of selected transactions is given, the other transactions are only executed. In
+/// verbose mode, the [`TraceStats`] of each traced transaction are attached to its result.
+///
+/// Fails with [`EthApiError::TracingCancelled`] before the next transaction once the cancellation
+/// is cancelled, and with [`DebugApiError::TraceMemoryBudgetExceeded`] if the traces exceed the
+/// memory budget, instead of returning an error result for the transaction. The database lookups
+/// of all executed transactions are recorded on the given recorder, if any, and the progress of
+/// the traced transactions on the given progress recorder. If `include_stats` is set, the
+/// [`TraceStats`] additionally hold the counters of the execution of each traced transaction.
+///
+/// If a maximum size of the results is given, tracing stops before the first transaction whose
+/// result would exceed it, unless it's the first traced transaction. The state changes of each
+/// traced transaction are committed, so the database holds the state before the first
+/// transaction that isn't traced once this returns.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn trace_transactions<'a, Eth>(
+    eth: &Eth,
+    db: &mut StateCacheDb<'_>,
+    transactions: impl ExactSizeIterator<Item = (usize, &'a TransactionSignedEcRecovered)>,
+    selected: Option<&HashSet<B256>>,
+    cfg: &CfgEnvWithHandlerCfg,
+    block_env: &BlockEnv,
+    opts: &GethDebugTracingOptions,
+    block_hash: Option<B256>,
+    verbose: bool,
+    include_stats: bool,
+    cancellation: &Cancellation,
+    memory: Option<&TraceMemory>,
+    stats: Option<&ExecutionStatsRecorder>,
+    progress: Option<&TraceProgressRecorder>,
+    max_response_bytes: Option<u64>,
+) -> Result<Vec<TraceResultWithStats>, Eth::Error>
+where
+    Eth: DebugTraceExt,
+{
+    let mut results = Vec::with_capacity(transactions.len());
+    let mut response_bytes = 0;
+    // the environment is reused for all transactions, only the transaction env is replaced
+    let mut env =
+        EnvWithHandlerCfg::new_with_cfg_env(cfg.clone(), block_env.clone(), Default::default());
+    for (index, tx) in transactions {
+        cancellation.check()?;
+        let tx_hash = tx.hash;
+
+        fill_block_tx_env(eth, &mut env, tx);
+        if selected.is_some_and(|selected| !selected.contains(&tx_hash)) {
+            let (res, used_env) = eth.transact(StatsDb::new(db, stats), env)?;
+            env = used_env;
+            db.commit(res.state);
+            continue
+        }
+
+        if let Some(progress) = progress {
+            progress.start(tx_hash);
+        }
+        let mut state_changes = None;
+        let counters = include_stats.then(TraceCountersRecorder::default);
+        let started_at = Instant::now();
+        let traced = trace_with_gas_used(
+            eth,
+            opts,
+            env,
+            db,
+            Some(TransactionContext { block_hash, tx_hash: Some(tx_hash), tx_index: Some(index) }),
+            memory,
+            stats,
+            None,
+            counters.as_ref(),
+        );
+        let duration = started_at.elapsed();
+        let (result, gas_used) = match traced {
+            Ok((result, gas_used, state, used_env)) => {
+                env = used_env;
+                // executed transactions always touch the sender, so there are no state
+                // changes if the tracer didn't execute the transaction, e.g. the noop tracer
+                if !state.is_empty() {
+                    state_changes = Some(state);
+                }
+                (TraceResult::Success { result, tx_hash: Some(tx_hash) }, gas_used)
+            }
+            Err(err)
+                if matches!(
+                    err.as_err(),
+                    Some(EthApiError::Debug(DebugApiError::TraceMemoryBudgetExceeded { .. }))
+                ) =>
+            {
+                return Err(err)
+            }
+            Err(err) => {
+                env = block_tx_env(eth, cfg, block_env, tx);
+                (TraceResult::Error { error: err.to_string(), tx_hash: Some(tx_hash) }, 0)
+            }
+        };
+
+        let trace_size = (verbose || max_response_bytes.is_some())
+            .then(|| serde_json::to_vec(&result).map_or(0, |res| res.len() as u64));
+        if let Some(max_response_bytes) = max_response_bytes {
+            let trace_size = trace_size.unwrap_or_default();
+            if !results.is_empty() && response_bytes + trace_size > max_response_bytes {
+                break
+            }
+            response_bytes += trace_size;
+        }
+        let trace_stats = verbose.then(|| TraceStats {
+            duration_micros: duration.as_micros() as u64,
+            gas_used,
+            trace_size: trace_size.unwrap_or_default(),
+            counters: counters.map(|counters| counters.counters()),
+        });
+        results.push(TraceResultWithStats { result, stats: trace_stats });
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        // need to apply the state changes of this transaction before executing the next
+        // transaction or the post-block changes
+        let state_changes = match state_changes {
+            Some(state_changes) => state_changes,
+            None => {
+                // if the transaction can't be executed at all, the following transactions can't
+                // be traced either
+                let (res, used_env) = eth.transact(StatsDb::new(db, stats), env)?;
+                env = used_env;
+                res.state
+            }
+        };
+        db.commit(state_changes)
+    }
+
+    Ok(results)
+}
+
+/// Replaces the transaction env of the given environment with the one of the given transaction.
+pub(super) fn fill_block_tx_env<Eth>(
+    eth: &Eth,
+    env: &mut EnvWithHandlerCfg,
+    tx: &TransactionSignedEcRecovered,
+) where
+    Eth: Call,
+{
+    Call::evm_config(eth).fill_tx_env(&mut env.tx, tx, tx.signer());
+}
+
+/// Creates the environment to execute the given transaction of a block in.
+pub(super) fn block_tx_env<Eth>(
+    eth: &Eth,
+    cfg: &CfgEnvWithHandlerCfg,
+    block_env: &BlockEnv,
+    tx: &TransactionSignedEcRecovered,
+) -> EnvWithHandlerCfg
+where
+    Eth: Call,
+{
+    EnvWithHandlerCfg {
+        env: Env::boxed(cfg.cfg_env.clone(), block_env.clone(), Call::evm_config(eth).tx_env(tx)),
+        handler_cfg: cfg.handler_cfg,
+    }
+}
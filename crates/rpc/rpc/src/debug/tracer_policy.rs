@@ -0,0 +1,221 @@
+//! Restrictions of the tracers that requests of the `debug` API may use.
+
+use reth_rpc_eth_types::{DebugApiError, EthApiError};
+use reth_rpc_types::{
+    debug::FLAMEGRAPH_TRACER,
+    trace::geth::{GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingOptions},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt};
+
+/// The kind of a tracer requested in the tracing options of a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TracerKind {
+    /// The struct logger, which is used if no tracer is specified.
+    #[serde(rename = "structLogger")]
+    StructLogger,
+    /// The built-in `4byteTracer`.
+    #[serde(rename = "4byteTracer")]
+    FourByteTracer,
+    /// The built-in `callTracer`.
+    #[serde(rename = "callTracer")]
+    CallTracer,
+    /// The built-in `prestateTracer`.
+    #[serde(rename = "prestateTracer")]
+    PreStateTracer,
+    /// The built-in `noopTracer`.
+    #[serde(rename = "noopTracer")]
+    NoopTracer,
+    /// The built-in `muxTracer`.
+    #[serde(rename = "muxTracer")]
+    MuxTracer,
+    /// The flamegraph tracer, see [`FLAMEGRAPH_TRACER`].
+    #[serde(rename = "flamegraphTracer")]
+    FlamegraphTracer,
+    /// JS tracers, which execute code of the request.
+    #[serde(rename = "js")]
+    Js,
+}
+
+impl TracerKind {
+    /// Returns the kind of the given tracer, the struct logger if unset.
+    pub fn of(tracer: Option<&GethDebugTracerType>) -> Self {
+        match tracer {
+            None => Self::StructLogger,
+            Some(GethDebugTracerType::BuiltInTracer(tracer)) => tracer.into(),
+            Some(GethDebugTracerType::JsTracer(name)) if name == FLAMEGRAPH_TRACER => {
+                Self::FlamegraphTracer
+            }
+            Some(GethDebugTracerType::JsTracer(_)) => Self::Js,
+        }
+    }
+
+    /// Returns the name of the kind, as used in the config.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::StructLogger => "structLogger",
+            Self::FourByteTracer => "4byteTracer",
+            Self::CallTracer => "callTracer",
+            Self::PreStateTracer => "prestateTracer",
+            Self::NoopTracer => "noopTracer",
+            Self::MuxTracer => "muxTracer",
+            Self::FlamegraphTracer => "flamegraphTracer",
+            Self::Js => "js",
+        }
+    }
+}
+
+impl From<&GethDebugBuiltInTracerType> for TracerKind {
+    fn from(tracer: &GethDebugBuiltInTracerType) -> Self {
+        match tracer {
+            GethDebugBuiltInTracerType::FourByteTracer => Self::FourByteTracer,
+            GethDebugBuiltInTracerType::CallTracer => Self::CallTracer,
+            GethDebugBuiltInTracerType::PreStateTracer => Self::PreStateTracer,
+            GethDebugBuiltInTracerType::NoopTracer => Self::NoopTracer,
+            GethDebugBuiltInTracerType::MuxTracer => Self::MuxTracer,
+        }
+    }
+}
+
+impl fmt::Display for TracerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The tracers that requests may use.
+///
+/// The policy applies to the tracer a request ends up with, i.e. the default tracer if the request
+/// doesn't specify one, and to the tracers nested in a mux tracer. By default, all tracers are
+/// allowed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TracerPolicy {
+    /// Only the listed tracers are allowed.
+    Allow(BTreeSet<TracerKind>),
+    /// All tracers but the listed ones are allowed.
+    Deny(BTreeSet<TracerKind>),
+}
+
+impl TracerPolicy {
+    /// Returns a policy that allows all tracers.
+    pub const fn allow_all() -> Self {
+        Self::Deny(BTreeSet::new())
+    }
+
+    /// Returns whether tracers of the kind are allowed.
+    pub fn is_allowed(&self, kind: TracerKind) -> bool {
+        match self {
+            Self::Allow(allowed) => allowed.contains(&kind),
+            Self::Deny(denied) => !denied.contains(&kind),
+        }
+    }
+
+    /// Checks that the tracer of the given options, and the tracers nested in it, are allowed.
+    ///
+    /// Fails with [`DebugApiError::TracerDisabled`] otherwise.
+    pub(crate) fn check(&self, opts: &GethDebugTracingOptions) -> Result<(), EthApiError> {
+        tracer_kinds(opts).into_iter().try_for_each(|kind| self.ensure_allowed(kind))
+    }
+
+    fn ensure_allowed(&self, kind: TracerKind) -> Result<(), EthApiError> {
+        if !self.is_allowed(kind) {
+            return Err(DebugApiError::TracerDisabled { tracer: kind.as_str() }.into())
+        }
+        Ok(())
+    }
+}
+
+impl Default for TracerPolicy {
+    fn default() -> Self {
+        Self::allow_all()
+    }
+}
+
+/// Returns the kind of the tracer of the given options, followed by the kinds of the tracers
+/// nested in it, if it's a mux tracer.
+pub(crate) fn tracer_kinds(opts: &GethDebugTracingOptions) -> Vec<TracerKind> {
+    let kind = TracerKind::of(opts.tracer.as_ref());
+    let mut kinds = vec![kind];
+    if kind == TracerKind::MuxTracer {
+        // an invalid config is rejected once the tracer is created
+        if let Ok(config) = opts.tracer_config.clone().into_mux_config() {
+            kinds.extend(config.0.keys().map(TracerKind::from));
+        }
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::trace::geth::GethDebugTracerConfig;
+
+    fn opts(tracer: Option<GethDebugTracerType>) -> GethDebugTracingOptions {
+        GethDebugTracingOptions { tracer, ..Default::default() }
+    }
+
+    #[test]
+    fn tracer_kinds() {
+        assert_eq!(TracerKind::of(None), TracerKind::StructLogger);
+        let call_tracer = GethDebugBuiltInTracerType::CallTracer.into();
+        assert_eq!(TracerKind::of(Some(&call_tracer)), TracerKind::CallTracer);
+        let flamegraph = GethDebugTracerType::JsTracer(FLAMEGRAPH_TRACER.to_string());
+        assert_eq!(TracerKind::of(Some(&flamegraph)), TracerKind::FlamegraphTracer);
+        let js = GethDebugTracerType::JsTracer("{}".to_string());
+        assert_eq!(TracerKind::of(Some(&js)), TracerKind::Js);
+
+        // the names match the serialization
+        for kind in [TracerKind::StructLogger, TracerKind::FourByteTracer, TracerKind::Js] {
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+        }
+    }
+
+    #[test]
+    fn check_tracer_policy() {
+        let call_tracer = opts(Some(GethDebugBuiltInTracerType::CallTracer.into()));
+        let struct_logger = opts(None);
+        let js = opts(Some(GethDebugTracerType::JsTracer("{}".to_string())));
+
+        assert!(TracerPolicy::default().check(&struct_logger).is_ok());
+        assert!(TracerPolicy::default().check(&js).is_ok());
+
+        let deny = TracerPolicy::Deny([TracerKind::StructLogger, TracerKind::Js].into());
+        assert!(deny.check(&call_tracer).is_ok());
+        for opts in [&struct_logger, &js] {
+            let err = deny.check(opts).unwrap_err();
+            assert!(err.to_string().contains("tracer disabled by operator"), "{err}");
+            assert!(matches!(err, EthApiError::Debug(DebugApiError::TracerDisabled { .. })));
+        }
+
+        let allow = TracerPolicy::Allow([TracerKind::CallTracer, TracerKind::MuxTracer].into());
+        assert!(allow.check(&call_tracer).is_ok());
+        assert!(allow.check(&struct_logger).is_err());
+        assert!(allow.check(&js).is_err());
+
+        // the tracers nested in a mux tracer must be allowed as well
+        let mux = |config| GethDebugTracingOptions {
+            tracer: Some(GethDebugBuiltInTracerType::MuxTracer.into()),
+            tracer_config: GethDebugTracerConfig(config),
+            ..Default::default()
+        };
+        assert!(allow.check(&mux(serde_json::json!({ "callTracer": {} }))).is_ok());
+        let err = allow
+            .check(&mux(serde_json::json!({ "callTracer": {}, "prestateTracer": {} })))
+            .unwrap_err();
+        assert!(err.to_string().contains("prestateTracer"), "{err}");
+    }
+
+    #[test]
+    fn deserialize_tracer_policy() {
+        let policy: TracerPolicy =
+            serde_json::from_str(r#"{"deny":["structLogger","js"]}"#).unwrap();
+        assert_eq!(policy, TracerPolicy::Deny([TracerKind::StructLogger, TracerKind::Js].into()));
+        let policy: TracerPolicy =
+            serde_json::from_str(r#"{"allow":["callTracer","4byteTracer"]}"#).unwrap();
+        assert_eq!(
+            policy,
+            TracerPolicy::Allow([TracerKind::CallTracer, TracerKind::FourByteTracer].into())
+        );
+    }
+}
@@ -0,0 +1,658 @@
+//! Execution witnesses of the `debug` API.
+
+use super::{
+    cancel::{CancelOnDrop, Cancellation},
+    diverged_accounts,
+    preimages::{AccessRecorder, StatePreimages},
+    raw::decode_raw_block,
+    stateless::WitnessDatabase,
+    witness_cache::WitnessCacheKey,
+    DebugApi, TraceClass,
+};
+use alloy_primitives::{Address, Bytes, B256};
+use reth_chainspec::EthereumHardforks;
+use reth_primitives::{BlockId, BlockNumberOrTag, BlockWithSenders, Header, Receipt};
+use reth_provider::{
+    BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
+    StateProofProvider, StateProvider, StateProviderBox, StateProviderFactory,
+};
+use reth_revm::database::StateProviderDatabase;
+use reth_rpc_eth_api::{helpers::TraceExt, EthApiTypes, FromEthApiError};
+use reth_rpc_eth_types::{cache::db::StateProviderTraitObjWrapper, DebugApiError, EthApiError};
+use reth_rpc_types::debug::{
+    DebugExecutionWitness, ExecutionWitness, ExecutionWitnessResult, StatelessExecutionMismatch,
+    StatelessExecutionResult, WitnessMismatch, WitnessVerification,
+};
+use reth_trie::{HashedPostState, HashedStorage, TrieInput};
+use revm::{
+    db::{states::bundle_state::BundleRetention, BundleState, CacheAccount},
+    primitives::{BlockEnv, CfgEnvWithHandlerCfg},
+    StateBuilder,
+};
+use revm_primitives::{keccak256, HashMap};
+use std::sync::Arc;
+
+impl<Provider, Eth> DebugApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + HeaderProvider
+        + ChainSpecProvider<ChainSpec: EthereumHardforks>
+        + StateProviderFactory
+        + EvmEnvProvider
+        + PruneCheckpointReader
+        + 'static,
+    Eth: EthApiTypes + TraceExt + 'static,
+{
+    /// The `debug_executionWitness` method allows for re-execution of a block with the purpose of
+    /// generating an execution witness. The witness comprises of a map of all hashed trie nodes
+    /// to their preimages that were required during the execution of the block, including during
+    /// state root recomputation.
+    ///
+    /// The block can be given by hash even if it isn't canonical, as long as it's known locally
+    /// and the state of its parent is available.
+    ///
+    /// The block is re-executed like the block executor does, including the pre-block system
+    /// calls, the withdrawals and the post-block system calls of the active hardforks, so that
+    /// the witness covers all accounts the block touches.
+    ///
+    /// If requested, the witness additionally includes the bytecode of every contract whose code
+    /// was loaded during the execution. If the block accesses the hashes of ancestors via
+    /// `BLOCKHASH` before the EIP-2935 history contract is active, the headers of the ancestors
+    /// are included as well, since the state doesn't prove these hashes.
+    ///
+    /// If verification is requested, the post-state root and gas used of the re-execution are
+    /// compared to the header of the block, and the response includes the verified state root or
+    /// diagnostics of the divergence.
+    ///
+    /// The preimages cover the addresses of all accessed accounts, including accounts that don't
+    /// exist or are destroyed by the block, and all accessed storage slots, including slots that
+    /// are only read or zeroed, and those accessed by the system calls. If `verify_preimages` is
+    /// set, the hashed keys of all leaves of the trie nodes of the witness are checked against
+    /// them, and the response lists the keys lacking a preimage.
+    ///
+    /// `pending` refers to the pending block of the `eth` API, which is built locally if the node
+    /// didn't receive one, on top of the canonical head. Since the pending block may be rebuilt
+    /// between requests, the witness includes the hash of the block it is for.
+    ///
+    /// Unless `no_cache` is set, the witnesses of blocks that aren't pending are served from the
+    /// witness cache, if configured.
+    pub async fn debug_execution_witness(
+        &self,
+        block_id: BlockId,
+        include_preimages: bool,
+        include_codes: bool,
+        verify: bool,
+        verify_preimages: bool,
+        no_cache: bool,
+    ) -> Result<DebugExecutionWitness, Eth::Error> {
+        if matches!(block_id, BlockId::Number(BlockNumberOrTag::Pending)) {
+            if let Some((block, _)) = self.eth_api().local_pending_block().await? {
+                let block_hash = block.hash();
+                let (cfg, block_env) =
+                    self.evm_env_on_parent(&block.header, block.parent_hash).await?;
+                let mut witness = self
+                    .execution_witness_of(
+                        block.unseal(),
+                        cfg,
+                        block_env,
+                        include_preimages,
+                        include_codes,
+                        verify,
+                        verify_preimages,
+                    )
+                    .await?;
+                witness.block_hash = Some(block_hash);
+                return Ok(witness)
+            }
+        }
+
+        let block_hash = self
+            .resolve_block_hash(block_id)?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        let generate = move || {
+            self.execution_witness_by_hash(
+                block_id,
+                block_hash,
+                include_preimages,
+                include_codes,
+                verify,
+                verify_preimages,
+            )
+        };
+        match self.inner.caches.witness_cache.as_ref().filter(|_| !no_cache) {
+            Some(cache) => {
+                let key = WitnessCacheKey {
+                    block_hash,
+                    include_preimages,
+                    include_codes,
+                    verify,
+                    verify_preimages,
+                };
+                cache.get_or_generate(key, generate).await.map(Arc::unwrap_or_clone)
+            }
+            None => generate().await,
+        }
+    }
+
+    /// Generates the execution witness of the block with the given hash, which was requested as
+    /// `block_id`.
+    async fn execution_witness_by_hash(
+        &self,
+        block_id: BlockId,
+        block_hash: B256,
+        include_preimages: bool,
+        include_codes: bool,
+        verify: bool,
+        verify_preimages: bool,
+    ) -> Result<DebugExecutionWitness, Eth::Error> {
+        let (block, cfg, block_env) = self
+            .block_and_env_by_hash(block_hash)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        self.execution_witness_of(
+            block,
+            cfg,
+            block_env,
+            include_preimages,
+            include_codes,
+            verify,
+            verify_preimages,
+        )
+        .await
+    }
+
+    /// Generates the execution witness of the given RLP encoded block on top of the state of its
+    /// parent, like [`Self::debug_execution_witness`].
+    ///
+    /// This allows witnessing blocks that aren't known to the node, e.g. externally built
+    /// payloads, without importing them. The witness includes the hash of the decoded block.
+    ///
+    /// Fails if the parent of the block or its state isn't available, or if the sender of a
+    /// transaction can't be recovered.
+    pub async fn debug_execution_witness_raw_block(
+        &self,
+        rlp_block: Bytes,
+        include_preimages: bool,
+        include_codes: bool,
+        verify: bool,
+        verify_preimages: bool,
+    ) -> Result<DebugExecutionWitness, Eth::Error> {
+        let block = decode_raw_block(&rlp_block)
+            .map_err(|err| DebugApiError::InvalidRawBlock { reason: err.to_string() })
+            .map_err(Eth::Error::from_eth_err)?;
+        let parent = block.parent_hash;
+        if self.inner.provider.header(&parent).map_err(Eth::Error::from_eth_err)?.is_none() {
+            return Err(EthApiError::ParentBlockNotFound(parent).into())
+        }
+
+        let (cfg, block_env) = self.evm_env_on_parent(&block.header, parent).await?;
+        let senders = self.recover_senders(&block)?;
+        let block_hash = block.header.hash_slow();
+        let block = BlockWithSenders { block, senders };
+        let mut witness = self
+            .execution_witness_of(
+                block,
+                cfg,
+                block_env,
+                include_preimages,
+                include_codes,
+                verify,
+                verify_preimages,
+            )
+            .await?;
+        witness.block_hash = Some(block_hash);
+        Ok(witness)
+    }
+
+    /// Executes the given RLP encoded block on the state of the given execution witness only, and
+    /// compares the resulting state root, receipts root and gas used to the header of the block.
+    ///
+    /// No state of the node is accessed, this validates that the witness is sufficient for the
+    /// stateless execution of the block. Only the header of the parent has to be known to the
+    /// node, for the environment of the block and the state root the witness is rooted in. Any
+    /// data that is missing in the witness is reported instead of a root. The receipts root is
+    /// computed with the receipt encoding of the chain, see [`Self::receipts_root`].
+    pub async fn debug_execute_witness(
+        &self,
+        rlp_block: Bytes,
+        witness: DebugExecutionWitness,
+    ) -> Result<StatelessExecutionResult, Eth::Error> {
+        let block = decode_raw_block(&rlp_block)
+            .map_err(|err| DebugApiError::InvalidRawBlock { reason: err.to_string() })
+            .map_err(Eth::Error::from_eth_err)?;
+        let parent = block.parent_hash;
+        let parent_header = self
+            .inner
+            .provider
+            .header(&parent)
+            .map_err(Eth::Error::from_eth_err)?
+            .ok_or(EthApiError::ParentBlockNotFound(parent))?;
+
+        let (cfg, block_env) = self.evm_env_on_parent(&block.header, parent).await?;
+        let senders = self.recover_senders(&block)?;
+        let block = BlockWithSenders { block, senders };
+        let header = block.header.clone();
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Witness);
+            let mut db = StateBuilder::new()
+                .with_database(WitnessDatabase::new(&parent_header, witness))
+                .with_bundle_update()
+                .build();
+            let executed =
+                this.execute_block_on(&mut db, block, &cfg, &block_env, true, &cancellation);
+            let (gas_used, receipts) = match executed {
+                Ok(executed) => executed,
+                Err(_) if !db.database.missing().is_empty() => {
+                    let missing_keys = db.database.take_missing();
+                    return Ok(StatelessExecutionResult::Incomplete { missing_keys });
+                }
+                Err(err) => return Err(err),
+            };
+
+            db.merge_transitions(BundleRetention::PlainState);
+            let bundle = db.take_bundle();
+            let state_root = db.database.state_root(&bundle);
+            if !db.database.missing().is_empty() {
+                let missing_keys = db.database.take_missing();
+                return Ok(StatelessExecutionResult::Incomplete { missing_keys });
+            }
+            let receipts_root = this.receipts_root(&block_env, header.number, &receipts);
+
+            if state_root == header.state_root &&
+                receipts_root == header.receipts_root &&
+                gas_used == header.gas_used
+            {
+                return Ok(StatelessExecutionResult::Valid { state_root, receipts_root, gas_used });
+            }
+            Ok(StatelessExecutionResult::Mismatch(Box::new(StatelessExecutionMismatch {
+                expected_state_root: header.state_root,
+                computed_state_root: state_root,
+                expected_receipts_root: header.receipts_root,
+                computed_receipts_root: receipts_root,
+                expected_gas_used: header.gas_used,
+                computed_gas_used: gas_used,
+            })))
+        })
+        .await
+    }
+
+    /// Generates the execution witness of the block on top of the state of its parent.
+    async fn execution_witness_of(
+        &self,
+        block: BlockWithSenders,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        include_preimages: bool,
+        include_codes: bool,
+        verify: bool,
+        verify_preimages: bool,
+    ) -> Result<DebugExecutionWitness, Eth::Error> {
+        self.ensure_state_available(block.number.saturating_sub(1))?;
+        let parent = block.parent_hash;
+        let state = self
+            .inner
+            .provider
+            .state_by_block_hash(parent)
+            .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Witness);
+            let (witness, _) = this.execution_witness_on(
+                &state,
+                BundleState::default(),
+                block,
+                cfg,
+                block_env,
+                include_preimages,
+                include_codes,
+                verify,
+                verify_preimages,
+                &cancellation,
+            )?;
+            Ok(witness)
+        })
+        .await
+    }
+
+    /// Generates the execution witnesses of all blocks in the inclusive range, like
+    /// [`Self::debug_execution_witness`].
+    ///
+    /// The blocks are executed one after another. Each block is executed on top of the state of
+    /// the parent of the first block and the changes of the preceding blocks of the range, so the
+    /// historical state is only opened once. A block whose witness can't be generated is reported
+    /// with its error, and the following block is executed on the state of its own parent
+    /// instead.
+    ///
+    /// Since no block is executed before the previous one is done, dropping the returned future
+    /// stops the generation after the block that is currently executed.
+    pub async fn debug_execution_witness_range(
+        &self,
+        start: BlockNumberOrTag,
+        end: BlockNumberOrTag,
+        include_preimages: bool,
+    ) -> Result<Vec<ExecutionWitnessResult>, Eth::Error> {
+        let start = self.resolve_block_number(start)?;
+        let end = self.resolve_block_number(end)?;
+        if start > end {
+            return Err(EthApiError::InvalidParams(
+                "invalid parameters: start block cannot be greater than end block".to_string(),
+            )
+            .into())
+        }
+        if end - start >= self.inner.max_execution_witness_range {
+            return Err(EthApiError::InvalidParams(format!(
+                "block range too large; currently limited to {} blocks",
+                self.inner.max_execution_witness_range
+            ))
+            .into())
+        }
+
+        let mut results = Vec::with_capacity((end - start + 1) as usize);
+        let mut executed = None;
+        for block_number in start..=end {
+            let witness =
+                self.execution_witness_in_range(block_number, executed.take(), include_preimages);
+            let result = match witness.await {
+                Ok((result, state)) => {
+                    executed = Some(state);
+                    ExecutionWitnessResult::Success { block_number, result }
+                }
+                Err(err) => ExecutionWitnessResult::Error { block_number, error: err.to_string() },
+            };
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Generates the execution witness of a block of a `debug_executionWitnessRange` request.
+    ///
+    /// The block is executed on top of the state of the previously executed block if it's its
+    /// parent, otherwise on the state of its parent.
+    async fn execution_witness_in_range(
+        &self,
+        block_number: u64,
+        executed: Option<ExecutedWitnessState>,
+        include_preimages: bool,
+    ) -> Result<(DebugExecutionWitness, ExecutedWitnessState), Eth::Error> {
+        let block_id = BlockId::from(block_number);
+        let block_hash = self
+            .resolve_block_hash(block_id)?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+        let (block, cfg, block_env) = self
+            .block_and_env_by_hash(block_hash)
+            .await?
+            .ok_or(DebugApiError::BlockNotFound(block_id))
+            .map_err(Eth::Error::from_eth_err)?;
+
+        let (state, prestate) = match executed {
+            Some(executed) if executed.block_hash == block.parent_hash => {
+                (executed.state, executed.changes)
+            }
+            _ => {
+                self.ensure_state_available(block.number.saturating_sub(1))?;
+                let parent = block.parent_hash;
+                let state = self
+                    .inner
+                    .provider
+                    .state_by_block_hash(parent)
+                    .map_err(|_| EthApiError::ParentStateUnavailable(parent))?;
+                (state, BundleState::default())
+            }
+        };
+
+        // stop the blocking task once the request is dropped
+        let cancel_on_drop = CancelOnDrop::default();
+        let cancellation = cancel_on_drop.cancellation();
+        let this = self.clone();
+        self.spawn_tracing(move |_| {
+            let _timer = this.inner.metrics.execution_timer(TraceClass::Witness);
+            let (witness, changes) = this.execution_witness_on(
+                &state,
+                prestate,
+                block,
+                cfg,
+                block_env,
+                include_preimages,
+                false,
+                false,
+                false,
+                &cancellation,
+            )?;
+            Ok((witness, ExecutedWitnessState { block_hash, state, changes }))
+        })
+        .await
+    }
+
+    /// Re-executes the block on top of the given state and the changes of the preceding blocks in
+    /// `prestate`, and generates the execution witness of the block against the state they lead
+    /// to.
+    ///
+    /// Returns the witness, together with the changes of the preceding blocks and the block
+    /// itself. The preimages can only be verified without preceding changes, since the trie nodes
+    /// are looked up from the state root of the parent.
+    #[allow(clippy::too_many_arguments)]
+    fn execution_witness_on(
+        &self,
+        state: &dyn StateProvider,
+        prestate: BundleState,
+        block: BlockWithSenders,
+        cfg: CfgEnvWithHandlerCfg,
+        block_env: BlockEnv,
+        include_preimages: bool,
+        include_codes: bool,
+        verify: bool,
+        verify_preimages: bool,
+        cancellation: &Cancellation,
+    ) -> Result<(DebugExecutionWitness, BundleState), Eth::Error> {
+        // the trie nodes of the witness are rooted in the state root of the parent
+        let parent_state_root = if verify_preimages {
+            let parent = block.parent_hash;
+            let header = self.inner.provider.header(&parent).map_err(Eth::Error::from_eth_err)?;
+            Some(header.ok_or(EthApiError::ParentBlockNotFound(parent))?.state_root)
+        } else {
+            None
+        };
+        // the changes of the preceding blocks are hashed before they're moved into the database,
+        // the witness is generated against the state they lead to
+        let overlay = HashedPostState::from_bundle_state(&prestate.state);
+        let mut db = StateBuilder::new()
+            .with_database(AccessRecorder::new(StateProviderDatabase::new(
+                StateProviderTraitObjWrapper(state),
+            )))
+            .with_bundle_prestate(prestate)
+            .with_bundle_update()
+            .build();
+        let block_number = block.number;
+        let is_prague =
+            self.inner.provider.chain_spec().is_prague_active_at_timestamp(block.timestamp);
+        // the header the re-execution is verified against
+        let header = verify.then(|| block.header.clone());
+
+        // Re-execute the block to load all touched accounts into the cache DB.
+        let (gas_used, receipts) =
+            self.execute_block_on(&mut db, block, &cfg, &block_env, verify, cancellation)?;
+        cancellation.check()?;
+
+        // The accessed state is read from Revm's cache directly, the bundle state only carries
+        // the changes of the block over to the blocks executed on top of it.
+        db.merge_transitions(BundleRetention::PlainState);
+        let bundle = db.take_bundle();
+
+        // The cache holds the code of every contract that was executed or inspected, e.g.
+        // via `EXTCODESIZE` or `EXTCODECOPY`, keyed by code hash.
+        let codes = include_codes.then(|| {
+            db.cache
+                .contracts
+                .iter()
+                .filter(|(_, code)| !code.is_empty())
+                .map(|(hash, code)| (*hash, code.original_bytes()))
+                .collect()
+        });
+
+        // Before EIP-2935, the hashes accessed via `BLOCKHASH` can only be proven by the
+        // chain of headers from the oldest accessed block to the parent.
+        let mut headers = Vec::new();
+        let oldest_accessed = db.block_hashes.keys().next().copied();
+        if let Some(oldest) = oldest_accessed.filter(|_| !is_prague) {
+            headers = self
+                .inner
+                .provider
+                .headers_range(oldest..block_number)
+                .map_err(Eth::Error::from_eth_err)?
+                .iter()
+                .map(|header| alloy_rlp::encode(header).into())
+                .collect();
+        }
+
+        // Initialize a map of preimages, which are collected to verify them even if they aren't
+        // included.
+        let collect_preimages = include_preimages || verify_preimages;
+        let mut state_preimages = StatePreimages::default();
+
+        // Grab all account proofs for the data accessed during block execution.
+        //
+        // Note: We grab *all* accounts in the cache here, as the `BundleState` prunes
+        // referenced accounts + storage slots. Cache is a superset of `BundleState`, so we
+        // can just query it to get the latest state of all accounts and storage slots.
+        let mut hashed_state = HashedPostState::default();
+        for (address, account) in &db.cache.accounts {
+            let hashed_address = keccak256(address);
+            hashed_state
+                .accounts
+                .insert(hashed_address, account.account.as_ref().map(|a| a.info.clone().into()));
+
+            let storage = hashed_state
+                .storages
+                .entry(hashed_address)
+                .or_insert_with(|| HashedStorage::new(account.status.was_destroyed()));
+
+            // accounts that don't exist are proven absent, so their preimages are included too
+            if collect_preimages {
+                state_preimages.insert_account(address);
+            }
+
+            if let Some(account) = &account.account {
+                for (slot, value) in &account.storage {
+                    storage.storage.insert(keccak256(B256::from(*slot)), *value);
+
+                    if collect_preimages {
+                        state_preimages.insert_slot(slot);
+                    }
+                }
+            }
+        }
+        // The cache drops the storage of destroyed accounts, so the slots they accessed before,
+        // like those of the system calls, are added from the reads of the database.
+        if collect_preimages {
+            state_preimages.extend(db.database.accessed(), &bundle);
+        }
+
+        let verification = header
+            .map(|header| {
+                let mut input = TrieInput::from_state(overlay.clone());
+                input.append_ref(&hashed_state);
+                self.verify_execution(
+                    state,
+                    input,
+                    &db.cache.accounts,
+                    &header,
+                    gas_used,
+                    &receipts,
+                )
+            })
+            .transpose()?;
+
+        // Generate an execution witness for the aggregated state of accessed accounts.
+        // Destruct the cache database to retrieve the state provider.
+        let state_provider = db.database.into_inner().into_inner();
+        let witness = state_provider
+            .witness(TrieInput::from_state(overlay), hashed_state)
+            .map_err(Into::into)?;
+        let missing_preimages =
+            parent_state_root.map(|state_root| state_preimages.missing(&witness, state_root));
+
+        let witness = DebugExecutionWitness {
+            witness: ExecutionWitness {
+                witness,
+                state_preimages: include_preimages.then(|| state_preimages.into_inner()),
+            },
+            codes,
+            headers,
+            verification,
+            block_hash: None,
+            missing_preimages,
+        };
+        Ok((witness, bundle))
+    }
+
+    /// Verifies the re-execution of a block against the header of the block.
+    ///
+    /// The post-state root is computed from the state accessed by the re-execution on top of the
+    /// trie input. On mismatch, the accessed accounts are compared to the stored post-state of the
+    /// block, and the re-executed receipts to the stored receipts, if the node has them.
+    fn verify_execution(
+        &self,
+        state: &dyn StateProvider,
+        input: TrieInput,
+        accounts: &HashMap<Address, CacheAccount>,
+        header: &Header,
+        gas_used: u64,
+        receipts: &[Receipt],
+    ) -> Result<WitnessVerification, Eth::Error> {
+        let state_root = state.state_root_from_nodes(input).map_err(Eth::Error::from_eth_err)?;
+        if state_root == header.state_root && gas_used == header.gas_used {
+            return Ok(WitnessVerification::Verified { state_root })
+        }
+
+        let block_hash = header.hash_slow();
+        let provider = &self.inner.provider;
+        let diverged_accounts = match provider.state_by_block_hash(block_hash) {
+            Ok(post_state) => {
+                Some(diverged_accounts(&post_state, accounts).map_err(Eth::Error::from_eth_err)?)
+            }
+            Err(_) => None,
+        };
+        let first_diverged_transaction = provider
+            .receipts_by_block(block_hash.into())
+            .map_err(Eth::Error::from_eth_err)?
+            .and_then(|stored| {
+                receipts.iter().zip(&stored).position(|(receipt, stored)| {
+                    receipt.success != stored.success ||
+                        receipt.cumulative_gas_used != stored.cumulative_gas_used ||
+                        receipt.logs != stored.logs
+                })
+            });
+
+        Ok(WitnessVerification::Mismatch(Box::new(WitnessMismatch {
+            expected_state_root: header.state_root,
+            computed_state_root: state_root,
+            expected_gas_used: header.gas_used,
+            computed_gas_used: gas_used,
+            diverged_accounts,
+            first_diverged_transaction: first_diverged_transaction.map(|index| index as u64),
+        })))
+    }
+}
+
+/// The state after the execution of a block of a `debug_executionWitnessRange` request, which
+/// the next block of the range is executed on.
+struct ExecutedWitnessState {
+    /// The hash of the executed block.
+    block_hash: B256,
+    /// The state the first block of the range was executed on.
+    state: StateProviderBox,
+    /// The changes of all blocks executed on top of `state`, including the executed block.
+    changes: BundleState,
+}
@@ -0,0 +1,229 @@
+//! Cache for recently generated execution witnesses.
+
+use super::lru::{BlockKey, ByteBoundedLru};
+use alloy_primitives::B256;
+use parking_lot::Mutex;
+use reth_rpc_types::debug::DebugExecutionWitness;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    sync::Arc,
+};
+use tokio::sync::OnceCell;
+
+/// Default maximum number of cached execution witnesses.
+pub const DEFAULT_WITNESS_CACHE_MAX_ENTRIES: u32 = 16;
+
+/// Default maximum total size of the cached execution witnesses, in bytes.
+pub const DEFAULT_WITNESS_CACHE_MAX_BYTES: usize = 256 * 1024 * 1024;
+
+/// Configuration of the [`WitnessCache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WitnessCacheConfig {
+    /// The maximum number of cached execution witnesses.
+    pub max_entries: u32,
+    /// The maximum total size of the cached execution witnesses, measured by the size of their
+    /// trie nodes, preimages, codes and headers.
+    pub max_bytes: usize,
+}
+
+impl Default for WitnessCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_WITNESS_CACHE_MAX_ENTRIES,
+            max_bytes: DEFAULT_WITNESS_CACHE_MAX_BYTES,
+        }
+    }
+}
+
+/// Key of a cached execution witness: the block hash and the options the witness was generated
+/// with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct WitnessCacheKey {
+    pub(crate) block_hash: B256,
+    pub(crate) include_preimages: bool,
+    pub(crate) include_codes: bool,
+    pub(crate) verify: bool,
+    pub(crate) verify_preimages: bool,
+}
+
+impl BlockKey for WitnessCacheKey {
+    fn block_hash(&self) -> B256 {
+        self.block_hash
+    }
+}
+
+/// A witness that is generated once and shared by all requests that wait for it.
+type InFlightWitness = Arc<OnceCell<Arc<DebugExecutionWitness>>>;
+
+/// An LRU cache for recently generated execution witnesses.
+///
+/// The witnesses are keyed by the block hash and the generation options, so retried requests for
+/// the same block are only generated once. Concurrent requests for a witness that isn't cached
+/// yet wait for the same generation. The cache is bounded by the number of entries and by the
+/// total size of the cached witnesses.
+#[derive(Clone)]
+pub struct WitnessCache {
+    entries: ByteBoundedLru<WitnessCacheKey, Arc<DebugExecutionWitness>>,
+    /// The witnesses that are currently generated.
+    ///
+    /// This is locked before the cached witnesses, so that a witness is either cached or in
+    /// flight for every request that looks it up.
+    in_flight: Arc<Mutex<HashMap<WitnessCacheKey, InFlightWitness>>>,
+}
+
+impl WitnessCache {
+    /// Creates a new, empty cache with the given limits.
+    pub fn new(config: WitnessCacheConfig) -> Self {
+        let entries =
+            ByteBoundedLru::new("rpc.debug.witness_cache", config.max_entries, config.max_bytes);
+        Self { entries, in_flight: Default::default() }
+    }
+
+    /// Returns the cached witness for the given key, or generates it with the given closure.
+    ///
+    /// If a witness for the key is already being generated, this waits for it instead. If that
+    /// generation fails or is dropped, the next waiting request generates the witness itself.
+    pub(crate) async fn get_or_generate<F, Fut, E>(
+        &self,
+        key: WitnessCacheKey,
+        generate: F,
+    ) -> Result<Arc<DebugExecutionWitness>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<DebugExecutionWitness, E>>,
+    {
+        let in_flight = {
+            let mut in_flight = self.in_flight.lock();
+            if let Some(witness) = self.entries.get(&key) {
+                return Ok(witness)
+            }
+            in_flight.entry(key).or_default().clone()
+        };
+
+        let result =
+            in_flight.get_or_try_init(|| async { generate().await.map(Arc::new) }).await.cloned();
+
+        // the first request that is done with the generation moves the witness into the cache
+        let mut generating = self.in_flight.lock();
+        if generating.get(&key).is_some_and(|current| Arc::ptr_eq(current, &in_flight)) {
+            generating.remove(&key);
+            if let Ok(witness) = &result {
+                self.entries.insert(key, witness.clone(), witness_size(witness));
+            }
+        }
+        result
+    }
+
+    /// Removes the cached witnesses of the given blocks.
+    pub fn remove_blocks(&self, block_hashes: &HashSet<B256>) {
+        self.entries.remove_blocks(block_hashes);
+    }
+
+    /// Returns the number of cached witnesses.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Debug for WitnessCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WitnessCache")
+            .field("entries", &self.entries)
+            .field("in_flight", &self.in_flight.lock().len())
+            .finish()
+    }
+}
+
+/// Returns the size of the trie nodes, preimages, codes and headers of the witness, including
+/// their keys.
+fn witness_size(witness: &DebugExecutionWitness) -> usize {
+    let preimages = witness.witness.state_preimages.iter().flat_map(HashMap::values);
+    let codes = witness.codes.iter().flat_map(BTreeMap::values);
+    let entries = witness.witness.witness.values().chain(preimages).chain(codes);
+    let headers = witness.headers.iter().map(|header| header.len()).sum::<usize>();
+    entries.map(|value| B256::len_bytes() + value.len()).sum::<usize>() + headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Bytes;
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn key(block: u8) -> WitnessCacheKey {
+        WitnessCacheKey {
+            block_hash: B256::with_last_byte(block),
+            include_preimages: false,
+            include_codes: false,
+            verify: false,
+            verify_preimages: false,
+        }
+    }
+
+    fn witness(nodes: u8) -> DebugExecutionWitness {
+        let mut witness = DebugExecutionWitness::default();
+        witness.witness.witness =
+            (0..nodes).map(|i| (B256::with_last_byte(i), Bytes::from(vec![i; 32]))).collect();
+        witness
+    }
+
+    async fn get(cache: &WitnessCache, key: WitnessCacheKey, nodes: u8) -> DebugExecutionWitness {
+        let generated =
+            cache.get_or_generate(key, move || async move { Ok::<_, Infallible>(witness(nodes)) });
+        (*generated.await.unwrap()).clone()
+    }
+
+    #[tokio::test]
+    async fn cache_keyed_by_block_and_options() {
+        let cache = WitnessCache::new(Default::default());
+        assert_eq!(get(&cache, key(1), 1).await, witness(1));
+        // cached, not generated again
+        assert_eq!(get(&cache, key(1), 2).await, witness(1));
+
+        assert_eq!(get(&cache, key(2), 2).await, witness(2));
+        let with_preimages = WitnessCacheKey { include_preimages: true, ..key(1) };
+        assert_eq!(get(&cache, with_preimages, 2).await, witness(2));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_generate_once() {
+        let cache = WitnessCache::new(Default::default());
+        let generated = &AtomicUsize::new(0);
+        let generate = move || async move {
+            generated.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok::<_, Infallible>(witness(1))
+        };
+
+        let (first, second) = futures::join!(
+            cache.get_or_generate(key(1), generate),
+            cache.get_or_generate(key(1), generate)
+        );
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert_eq!(generated.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_generation_is_not_cached() {
+        let cache = WitnessCache::new(Default::default());
+        let failed =
+            cache.get_or_generate(key(1), || async { Err::<DebugExecutionWitness, _>(()) });
+        assert!(failed.await.is_err());
+        assert!(cache.is_empty());
+        assert_eq!(get(&cache, key(1), 1).await, witness(1));
+    }
+}
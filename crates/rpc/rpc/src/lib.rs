@@ -44,7 +44,20 @@ mod trace;
 mod txpool;
 mod web3;
 pub use admin::AdminApi;
-pub use debug::DebugApi;
+pub use debug::{
+    cache_reorg_task, AuditLogConfig, ClientKey, ClientKeyLayer, ClientKeyService,
+    ClientLimitsConfig, DebugApi, DebugApiCaches, DebugApiConfig, DebugApiConfigError,
+    DebugAuditEntry, DebugAuditOutcome, DebugAuditParams, DebugAuditSink, DebugRpcFuture,
+    DebugRpcLayer, DebugRpcService, DebugTraceExt, JsonlAuditSink, PersistenceSettings,
+    PersistenceSettingsHandle, StateReadCache, StateSnapshotCache, TraceCache, TraceCacheConfig,
+    TraceClass, TracerKind, TracerPolicy, WitnessCache, WitnessCacheConfig,
+    DEFAULT_AUDIT_LOG_BUFFER, DEFAULT_MAX_EXECUTION_WITNESS_RANGE, DEFAULT_MAX_RAW_HEADERS,
+    DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION, DEFAULT_MAX_TRACE_MEMORY,
+    DEFAULT_MAX_TRACE_PERMIT_WAIT, DEFAULT_STATE_READ_CACHE_MAX_BYTES,
+    DEFAULT_STATE_SNAPSHOT_CACHE_MAX_BYTES, DEFAULT_TRACE_CACHE_MAX_BYTES,
+    DEFAULT_TRACE_CACHE_MAX_ENTRIES, DEFAULT_WITNESS_CACHE_MAX_BYTES,
+    DEFAULT_WITNESS_CACHE_MAX_ENTRIES,
+};
 pub use engine::{EngineApi, EngineEthApi};
 pub use eth::{EthApi, EthBundle, EthFilter, EthPubSub};
 pub use net::NetApi;
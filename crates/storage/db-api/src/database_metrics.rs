@@ -1,3 +1,4 @@
+use crate::DatabaseError;
 use metrics::{counter, gauge, histogram, Label};
 use std::sync::Arc;
 
@@ -60,15 +61,53 @@ impl DatabaseMetadataValue {
     }
 }
 
+/// The statistics of a table of the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStats {
+    /// The name of the table.
+    pub name: &'static str,
+    /// Whether the table stores multiple values per key.
+    pub dupsort: bool,
+    /// The number of entries of the table.
+    pub entries: usize,
+    /// The size of the pages of the database, in bytes.
+    pub page_size: usize,
+    /// The number of leaf pages, which hold the keys and values of the table.
+    pub leaf_pages: usize,
+    /// The number of branch pages, which hold the keys of the index of the table.
+    pub branch_pages: usize,
+    /// The number of overflow pages, which hold values that don't fit into a leaf page.
+    pub overflow_pages: usize,
+}
+
+impl TableStats {
+    /// Returns the total size of the pages of the table, in bytes.
+    pub const fn total_size(&self) -> usize {
+        (self.leaf_pages + self.branch_pages + self.overflow_pages) * self.page_size
+    }
+}
+
 /// Includes a method to return a [`DatabaseMetadataValue`] type, which can be used to dynamically
 /// retrieve information about the database.
 pub trait DatabaseMetadata {
     /// Returns a metadata type, [`DatabaseMetadataValue`] for the database.
     fn metadata(&self) -> DatabaseMetadataValue;
+
+    /// Returns the statistics of all tables of the database, read in a single read-only
+    /// transaction.
+    ///
+    /// Empty if the database doesn't keep statistics of its tables.
+    fn table_stats(&self) -> Result<Vec<TableStats>, DatabaseError> {
+        Ok(Vec::new())
+    }
 }
 
 impl<DB: DatabaseMetadata> DatabaseMetadata for Arc<DB> {
     fn metadata(&self) -> DatabaseMetadataValue {
         <DB as DatabaseMetadata>::metadata(self)
     }
+
+    fn table_stats(&self) -> Result<Vec<TableStats>, DatabaseError> {
+        <DB as DatabaseMetadata>::table_stats(self)
+    }
 }
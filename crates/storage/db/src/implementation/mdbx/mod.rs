@@ -12,7 +12,7 @@ use metrics::{gauge, Label};
 use reth_db_api::{
     cursor::{DbCursorRO, DbCursorRW},
     database::Database,
-    database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics},
+    database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics, TableStats},
     models::ClientVersion,
     transaction::{DbTx, DbTxMut},
 };
@@ -244,6 +244,30 @@ impl DatabaseMetadata for DatabaseEnv {
     fn metadata(&self) -> DatabaseMetadataValue {
         DatabaseMetadataValue::new(self.freelist().ok())
     }
+
+    fn table_stats(&self) -> Result<Vec<TableStats>, DatabaseError> {
+        self.view(|tx| {
+            Tables::ALL
+                .iter()
+                .map(|table| {
+                    let stats = tx
+                        .inner
+                        .open_db(Some(table.name()))
+                        .and_then(|table_db| tx.inner.db_stat(&table_db))
+                        .map_err(|e| DatabaseError::Stats(e.into()))?;
+                    Ok(TableStats {
+                        name: table.name(),
+                        dupsort: table.is_dupsort(),
+                        entries: stats.entries(),
+                        page_size: stats.page_size() as usize,
+                        leaf_pages: stats.leaf_pages(),
+                        branch_pages: stats.branch_pages(),
+                        overflow_pages: stats.overflow_pages(),
+                    })
+                })
+                .collect()
+        })?
+    }
 }
 
 impl DatabaseEnv {
@@ -539,6 +563,27 @@ mod tests {
         tx.commit().expect(ERROR_COMMIT);
     }
 
+    #[test]
+    fn db_table_stats() {
+        let env = create_test_db(DatabaseEnvKind::RW);
+
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        for key in 0..3u64 {
+            tx.put::<Headers>(key, Header::default()).expect(ERROR_PUT);
+        }
+        tx.commit().expect(ERROR_COMMIT);
+
+        let stats = env.table_stats().unwrap();
+        assert_eq!(stats.len(), Tables::COUNT);
+        let headers = stats.iter().find(|stats| stats.name == Headers::NAME).unwrap();
+        assert_eq!(headers.entries, 3);
+        assert!(!headers.dupsort);
+        assert_eq!(headers.total_size(), headers.page_size * headers.leaf_pages);
+        let storage = stats.iter().find(|stats| stats.name == PlainStorageState::NAME).unwrap();
+        assert_eq!(storage.entries, 0);
+        assert!(storage.dupsort);
+    }
+
     #[test]
     fn db_dup_cursor_delete_first() {
         let db: Arc<DatabaseEnv> = create_test_db(DatabaseEnvKind::RW);
@@ -46,7 +46,7 @@ pub mod test_utils {
     use crate::mdbx::DatabaseArguments;
     use reth_db_api::{
         database::Database,
-        database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics},
+        database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics, TableStats},
         models::ClientVersion,
     };
     use reth_fs_util;
@@ -123,6 +123,10 @@ pub mod test_utils {
         fn metadata(&self) -> DatabaseMetadataValue {
             self.db().metadata()
         }
+
+        fn table_stats(&self) -> Result<Vec<TableStats>, DatabaseError> {
+            self.db().table_stats()
+        }
     }
 
     /// Create `static_files` path for testing
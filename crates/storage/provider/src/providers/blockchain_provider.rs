@@ -5,7 +5,8 @@ use crate::{
     DatabaseProviderRO, EvmEnvProvider, FinalizedBlockReader, HeaderProvider, ProviderError,
     ProviderFactory, PruneCheckpointReader, ReceiptProvider, ReceiptProviderIdExt,
     RequestsProvider, StageCheckpointReader, StateProviderBox, StateProviderFactory, StateReader,
-    StaticFileProviderFactory, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    StaticFileProviderFactory, StorageStatsReader, TransactionVariant, TransactionsProvider,
+    WithdrawalsProvider,
 };
 use alloy_rpc_types_engine::ForkchoiceState;
 use reth_chain_state::{
@@ -14,15 +15,19 @@ use reth_chain_state::{
 };
 use reth_chainspec::ChainInfo;
 use reth_db::Database;
-use reth_db_api::models::{AccountBeforeTx, StoredBlockBodyIndices};
+use reth_db_api::{
+    database_metrics::TableStats,
+    models::{AccountBeforeTx, StoredBlockBodyIndices},
+};
 use reth_evm::ConfigureEvmEnv;
 use reth_execution_types::ExecutionOutcome;
 use reth_node_types::NodeTypesWithDB;
 use reth_primitives::{
-    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber,
-    BlockNumberOrTag, BlockWithSenders, EthereumHardforks, Header, Receipt, SealedBlock,
-    SealedBlockWithSenders, SealedHeader, TransactionMeta, TransactionSigned,
-    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256, U256,
+    static_file::SegmentRangeInclusive, Account, Address, Block, BlockHash, BlockHashOrNumber,
+    BlockId, BlockNumHash, BlockNumber, BlockNumberOrTag, BlockWithSenders, EthereumHardforks,
+    Header, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment,
+    TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal,
+    Withdrawals, B256, U256,
 };
 use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
@@ -282,6 +287,18 @@ impl<N: ProviderNodeTypes> StaticFileProviderFactory for BlockchainProvider2<N>
     }
 }
 
+impl<N: ProviderNodeTypes> StorageStatsReader for BlockchainProvider2<N> {
+    fn table_stats(&self) -> ProviderResult<Vec<TableStats>> {
+        self.database.table_stats()
+    }
+
+    fn static_file_block_ranges(
+        &self,
+    ) -> ProviderResult<Vec<(StaticFileSegment, Vec<SegmentRangeInclusive>)>> {
+        self.database.static_file_block_ranges()
+    }
+}
+
 impl<N: ProviderNodeTypes> HeaderProvider for BlockchainProvider2<N> {
     fn header(&self, block_hash: &BlockHash) -> ProviderResult<Option<Header>> {
         if let Some(block_state) = self.canonical_in_memory_state.state_by_hash(*block_hash) {
@@ -5,20 +5,25 @@ use crate::{
     BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, DatabaseProviderFactory,
     EvmEnvProvider, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, ProviderError,
     PruneCheckpointReader, RequestsProvider, StageCheckpointReader, StateProviderBox,
-    StaticFileProviderFactory, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    StaticFileProviderFactory, StorageStatsReader, TransactionVariant, TransactionsProvider,
+    WithdrawalsProvider,
 };
 use core::fmt;
 use reth_chainspec::ChainInfo;
 use reth_db::{init_db, mdbx::DatabaseArguments, DatabaseEnv};
-use reth_db_api::{database::Database, models::StoredBlockBodyIndices};
+use reth_db_api::{
+    database::Database,
+    database_metrics::{DatabaseMetadata, TableStats},
+    models::StoredBlockBodyIndices,
+};
 use reth_errors::{RethError, RethResult};
 use reth_evm::ConfigureEvmEnv;
 use reth_node_types::NodeTypesWithDB;
 use reth_primitives::{
-    Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Header, Receipt,
-    SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment, TransactionMeta,
-    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256,
-    U256,
+    static_file::SegmentRangeInclusive, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber,
+    BlockWithSenders, Header, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    StaticFileSegment, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash,
+    TxNumber, Withdrawal, Withdrawals, B256, U256,
 };
 use reth_prune_types::{PruneCheckpoint, PruneModes, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
@@ -204,6 +209,18 @@ impl<N: NodeTypesWithDB> StaticFileProviderFactory for ProviderFactory<N> {
     }
 }
 
+impl<N: NodeTypesWithDB> StorageStatsReader for ProviderFactory<N> {
+    fn table_stats(&self) -> ProviderResult<Vec<TableStats>> {
+        Ok(self.db.table_stats()?)
+    }
+
+    fn static_file_block_ranges(
+        &self,
+    ) -> ProviderResult<Vec<(StaticFileSegment, Vec<SegmentRangeInclusive>)>> {
+        self.static_file_provider.static_file_block_ranges()
+    }
+}
+
 impl<N: ProviderNodeTypes> HeaderSyncGapProvider for ProviderFactory<N> {
     fn sync_gap(
         &self,
@@ -641,10 +658,13 @@ mod tests {
         tables,
         test_utils::{create_test_static_files_dir, ERROR_TEMPDIR},
     };
+    use reth_db_api::transaction::DbTxMut;
     use reth_primitives::{StaticFileSegment, TxNumber, B256, U256};
     use reth_prune_types::{PruneMode, PruneModes};
     use reth_storage_errors::provider::ProviderError;
-    use reth_testing_utils::generators::{self, random_block, random_header, BlockParams};
+    use reth_testing_utils::generators::{
+        self, random_block, random_header, random_header_range, BlockParams,
+    };
     use std::{ops::RangeInclusive, sync::Arc};
     use tokio::sync::watch;
 
@@ -801,4 +821,68 @@ mod tests {
         assert_eq!(gap.local_head, head);
         assert_eq!(gap.target.tip(), consensus_tip.into());
     }
+
+    #[test]
+    fn headers_range_from_static_files_and_database() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let headers = random_header_range(&mut generators::rng(), 0..10, B256::ZERO);
+
+        // the first headers were moved to static files, the others are still in the database
+        let mut static_file_writer =
+            provider.static_file_provider().latest_writer(StaticFileSegment::Headers).unwrap();
+        for header in &headers[..5] {
+            static_file_writer.append_header(header.header(), U256::ZERO, &header.hash()).unwrap();
+        }
+        static_file_writer.commit().unwrap();
+        drop(static_file_writer);
+        for header in &headers[5..] {
+            provider
+                .tx_ref()
+                .put::<tables::Headers>(header.number, header.header().clone())
+                .unwrap();
+        }
+        provider.commit().unwrap();
+
+        let headers = headers.into_iter().map(|header| header.unseal()).collect::<Vec<_>>();
+        assert_eq!(factory.headers_range(3..8).unwrap(), headers[3..8]);
+        assert_eq!(factory.headers_range(0..5).unwrap(), headers[..5]);
+        assert_eq!(factory.headers_range(5..10).unwrap(), headers[5..]);
+        // the range is cut off at the last header
+        assert_eq!(factory.headers_range(7..20).unwrap(), headers[7..]);
+    }
+
+    #[test]
+    fn storage_stats() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let headers = random_header_range(&mut generators::rng(), 0..10, B256::ZERO);
+
+        let mut static_file_writer =
+            provider.static_file_provider().latest_writer(StaticFileSegment::Headers).unwrap();
+        for header in &headers[..5] {
+            static_file_writer.append_header(header.header(), U256::ZERO, &header.hash()).unwrap();
+        }
+        static_file_writer.commit().unwrap();
+        drop(static_file_writer);
+        for header in &headers[5..] {
+            provider
+                .tx_ref()
+                .put::<tables::Headers>(header.number, header.header().clone())
+                .unwrap();
+        }
+        provider.commit().unwrap();
+
+        let table_stats = factory.table_stats().unwrap();
+        assert_eq!(table_stats.len(), tables::Tables::COUNT);
+        let headers_stats = table_stats.iter().find(|stats| stats.name == "Headers").unwrap();
+        assert_eq!(headers_stats.entries, 5);
+
+        let static_files = factory.static_file_block_ranges().unwrap();
+        let (_, ranges) = static_files
+            .iter()
+            .find(|(segment, _)| *segment == StaticFileSegment::Headers)
+            .unwrap();
+        assert_eq!(ranges, &[SegmentRangeInclusive::new(0, 4)]);
+    }
 }
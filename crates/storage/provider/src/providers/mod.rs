@@ -5,7 +5,7 @@ use crate::{
     EvmEnvProvider, FinalizedBlockReader, FullExecutionDataProvider, HeaderProvider, ProviderError,
     PruneCheckpointReader, ReceiptProvider, ReceiptProviderIdExt, RequestsProvider,
     StageCheckpointReader, StateProviderBox, StateProviderFactory, StaticFileProviderFactory,
-    TransactionVariant, TransactionsProvider, TreeViewer, WithdrawalsProvider,
+    StorageStatsReader, TransactionVariant, TransactionsProvider, TreeViewer, WithdrawalsProvider,
 };
 use reth_blockchain_tree_api::{
     error::{CanonicalError, InsertBlockError},
@@ -15,14 +15,18 @@ use reth_blockchain_tree_api::{
 use reth_chain_state::{ChainInfoTracker, ForkChoiceNotifications, ForkChoiceSubscriptions};
 use reth_chainspec::{ChainInfo, ChainSpec};
 use reth_db::Database;
-use reth_db_api::models::{AccountBeforeTx, StoredBlockBodyIndices};
+use reth_db_api::{
+    database_metrics::TableStats,
+    models::{AccountBeforeTx, StoredBlockBodyIndices},
+};
 use reth_evm::ConfigureEvmEnv;
 use reth_node_types::NodeTypesWithDB;
 use reth_primitives::{
-    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber,
-    BlockNumberOrTag, BlockWithSenders, Header, Receipt, SealedBlock, SealedBlockWithSenders,
-    SealedHeader, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
-    Withdrawal, Withdrawals, B256, U256,
+    static_file::SegmentRangeInclusive, Account, Address, Block, BlockHash, BlockHashOrNumber,
+    BlockId, BlockNumHash, BlockNumber, BlockNumberOrTag, BlockWithSenders, Header, Receipt,
+    SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment, TransactionMeta,
+    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256,
+    U256,
 };
 use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
@@ -190,6 +194,18 @@ impl<N: ProviderNodeTypes> StaticFileProviderFactory for BlockchainProvider<N> {
     }
 }
 
+impl<N: ProviderNodeTypes> StorageStatsReader for BlockchainProvider<N> {
+    fn table_stats(&self) -> ProviderResult<Vec<TableStats>> {
+        self.database.table_stats()
+    }
+
+    fn static_file_block_ranges(
+        &self,
+    ) -> ProviderResult<Vec<(StaticFileSegment, Vec<SegmentRangeInclusive>)>> {
+        self.database.static_file_block_ranges()
+    }
+}
+
 impl<N: ProviderNodeTypes> HeaderProvider for BlockchainProvider<N> {
     fn header(&self, block_hash: &BlockHash) -> ProviderResult<Option<Header>> {
         self.database.header(block_hash)
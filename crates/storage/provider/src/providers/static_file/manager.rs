@@ -879,6 +879,23 @@ impl StaticFileProvider {
         }
     }
 
+    /// Returns the block ranges of the static files of each segment on disk, ordered by segment
+    /// and block.
+    pub fn static_file_block_ranges(
+        &self,
+    ) -> ProviderResult<Vec<(StaticFileSegment, Vec<SegmentRangeInclusive>)>> {
+        let static_files =
+            iter_static_files(&self.path).map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+        let mut segments = static_files
+            .into_iter()
+            .map(|(segment, ranges)| {
+                (segment, ranges.into_iter().map(|(block_range, _)| block_range).collect())
+            })
+            .collect::<Vec<_>>();
+        segments.sort_unstable_by_key(|(segment, _)| *segment);
+        Ok(segments)
+    }
+
     /// Iterates through segment `static_files` in reverse order, executing a function until it
     /// returns some object. Useful for finding objects by [`TxHash`] or [`BlockHash`].
     pub fn find_static_file<T>(
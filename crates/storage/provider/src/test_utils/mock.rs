@@ -3,25 +3,30 @@ use crate::{
     AccountReader, BlockExecutionReader, BlockHashReader, BlockIdReader, BlockNumReader,
     BlockReader, BlockReaderIdExt, ChainSpecProvider, ChangeSetReader, DatabaseProvider,
     EvmEnvProvider, HeaderProvider, ReceiptProviderIdExt, RequestsProvider, StateProvider,
-    StateProviderBox, StateProviderFactory, StateReader, StateRootProvider, TransactionVariant,
-    TransactionsProvider, WithdrawalsProvider,
+    StateProviderBox, StateProviderFactory, StateReader, StateRootProvider, StorageStatsReader,
+    TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use parking_lot::Mutex;
 use reth_chainspec::{ChainInfo, ChainSpec};
 use reth_db::mock::{DatabaseMock, TxMock};
-use reth_db_api::models::{AccountBeforeTx, StoredBlockBodyIndices};
+use reth_db_api::{
+    database_metrics::TableStats,
+    models::{AccountBeforeTx, StoredBlockBodyIndices},
+};
 use reth_evm::ConfigureEvmEnv;
 use reth_execution_types::{Chain, ExecutionOutcome};
 use reth_primitives::{
-    keccak256, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumber,
-    BlockNumberOrTag, BlockWithSenders, Bytecode, Bytes, GotExpected, Header, Receipt, SealedBlock,
-    SealedBlockWithSenders, SealedHeader, StorageKey, StorageValue, TransactionMeta,
-    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256,
-    U256,
+    keccak256, static_file::SegmentRangeInclusive, Account, Address, Block, BlockHash,
+    BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber, BlockNumberOrTag, BlockWithSenders,
+    Bytecode, Bytes, GotExpected, Header, Receipt, SealedBlock, SealedBlockWithSenders,
+    SealedHeader, StaticFileSegment, StorageKey, StorageValue, TransactionMeta, TransactionSigned,
+    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256, U256,
 };
+use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
 use reth_storage_api::{
-    DatabaseProviderFactory, StageCheckpointReader, StateProofProvider, StorageRootProvider,
+    DatabaseProviderFactory, PruneCheckpointReader, StageCheckpointReader, StateProofProvider,
+    StorageRootProvider,
 };
 use reth_storage_errors::provider::{ConsistentViewError, ProviderError, ProviderResult};
 use reth_trie::{
@@ -47,6 +52,22 @@ pub struct MockEthProvider {
     pub chain_spec: Arc<ChainSpec>,
     /// Local state roots
     pub state_roots: Arc<Mutex<Vec<B256>>>,
+    /// Local safe block, as set by forkchoice state
+    pub safe_block: Arc<Mutex<Option<BlockNumHash>>>,
+    /// Local finalized block, as set by forkchoice state
+    pub finalized_block: Arc<Mutex<Option<BlockNumHash>>>,
+    /// Local receipt store, by block hash
+    pub receipts: Arc<Mutex<HashMap<B256, Vec<Receipt>>>>,
+    /// Local prune checkpoint store
+    pub prune_checkpoints: Arc<Mutex<BTreeMap<PruneSegment, PruneCheckpoint>>>,
+    /// Local stage checkpoint store, by stage name
+    pub stage_checkpoints: Arc<Mutex<BTreeMap<String, StageCheckpoint>>>,
+    /// Local database table statistics
+    pub table_stats: Arc<Mutex<Vec<TableStats>>>,
+    /// Local static file block ranges, by segment
+    pub static_files: Arc<Mutex<BTreeMap<StaticFileSegment, Vec<SegmentRangeInclusive>>>>,
+    /// The hashed states that witnesses were requested for, in order
+    pub witness_targets: Arc<Mutex<Vec<HashedPostState>>>,
 }
 
 impl Default for MockEthProvider {
@@ -57,6 +78,14 @@ impl Default for MockEthProvider {
             accounts: Default::default(),
             chain_spec: Arc::new(reth_chainspec::ChainSpecBuilder::mainnet().build()),
             state_roots: Default::default(),
+            safe_block: Default::default(),
+            finalized_block: Default::default(),
+            receipts: Default::default(),
+            prune_checkpoints: Default::default(),
+            stage_checkpoints: Default::default(),
+            table_stats: Default::default(),
+            static_files: Default::default(),
+            witness_targets: Default::default(),
         }
     }
 }
@@ -141,6 +170,45 @@ impl MockEthProvider {
     pub fn add_state_root(&self, state_root: B256) {
         self.state_roots.lock().push(state_root);
     }
+
+    /// Set the safe block
+    pub fn set_safe_block(&self, num_hash: BlockNumHash) {
+        *self.safe_block.lock() = Some(num_hash);
+    }
+
+    /// Set the finalized block
+    pub fn set_finalized_block(&self, num_hash: BlockNumHash) {
+        *self.finalized_block.lock() = Some(num_hash);
+    }
+
+    /// Add the receipts of a block to local receipt store
+    pub fn add_receipts(&self, block_hash: B256, receipts: Vec<Receipt>) {
+        self.receipts.lock().insert(block_hash, receipts);
+    }
+
+    /// Set the prune checkpoint of a segment
+    pub fn set_prune_checkpoint(&self, segment: PruneSegment, checkpoint: PruneCheckpoint) {
+        self.prune_checkpoints.lock().insert(segment, checkpoint);
+    }
+
+    /// Set the checkpoint of a stage
+    pub fn set_stage_checkpoint(&self, id: StageId, checkpoint: StageCheckpoint) {
+        self.stage_checkpoints.lock().insert(id.to_string(), checkpoint);
+    }
+
+    /// Set the statistics of the database tables
+    pub fn set_table_stats(&self, stats: Vec<TableStats>) {
+        *self.table_stats.lock() = stats;
+    }
+
+    /// Set the block ranges of the static files of a segment
+    pub fn set_static_file_block_ranges(
+        &self,
+        segment: StaticFileSegment,
+        ranges: Vec<SegmentRangeInclusive>,
+    ) {
+        self.static_files.lock().insert(segment, ranges);
+    }
 }
 
 impl DatabaseProviderFactory for MockEthProvider {
@@ -280,7 +348,7 @@ impl TransactionsProvider for MockEthProvider {
                         excess_blob_gas: block.header.excess_blob_gas,
                         timestamp: block.header.timestamp,
                     };
-                    return Ok(Some((tx.clone(), meta)))
+                    return Ok(Some((tx.clone(), meta)));
                 }
             }
         }
@@ -292,7 +360,7 @@ impl TransactionsProvider for MockEthProvider {
         let mut current_tx_number: TxNumber = 0;
         for block in lock.values() {
             if current_tx_number + (block.body.len() as TxNumber) > id {
-                return Ok(Some(block.header.number))
+                return Ok(Some(block.header.number));
             }
             current_tx_number += block.body.len() as TxNumber;
         }
@@ -373,12 +441,21 @@ impl ReceiptProvider for MockEthProvider {
         Ok(None)
     }
 
-    fn receipt_by_hash(&self, _hash: TxHash) -> ProviderResult<Option<Receipt>> {
-        Ok(None)
+    fn receipt_by_hash(&self, hash: TxHash) -> ProviderResult<Option<Receipt>> {
+        let Some((_, meta)) = self.transaction_by_hash_with_meta(hash)? else { return Ok(None) };
+        let receipts = self.receipts.lock();
+        Ok(receipts
+            .get(&meta.block_hash)
+            .and_then(|receipts| receipts.get(meta.index as usize))
+            .cloned())
     }
 
-    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> ProviderResult<Option<Vec<Receipt>>> {
-        Ok(None)
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> ProviderResult<Option<Vec<Receipt>>> {
+        let hash = match block {
+            BlockHashOrNumber::Hash(hash) => Some(hash),
+            BlockHashOrNumber::Number(number) => self.block_hash(number)?,
+        };
+        Ok(hash.and_then(|hash| self.receipts.lock().get(&hash).cloned()))
     }
 
     fn receipts_by_tx_range(
@@ -452,11 +529,11 @@ impl BlockIdReader for MockEthProvider {
     }
 
     fn safe_block_num_hash(&self) -> ProviderResult<Option<reth_primitives::BlockNumHash>> {
-        Ok(None)
+        Ok(*self.safe_block.lock())
     }
 
     fn finalized_block_num_hash(&self) -> ProviderResult<Option<reth_primitives::BlockNumHash>> {
-        Ok(None)
+        Ok(*self.finalized_block.lock())
     }
 }
 
@@ -499,18 +576,18 @@ impl BlockReader for MockEthProvider {
 
     fn block_with_senders(
         &self,
-        _id: BlockHashOrNumber,
+        id: BlockHashOrNumber,
         _transaction_kind: TransactionVariant,
     ) -> ProviderResult<Option<BlockWithSenders>> {
-        Ok(None)
+        Ok(self.block(id)?.and_then(|block| block.with_recovered_senders()))
     }
 
     fn sealed_block_with_senders(
         &self,
-        _id: BlockHashOrNumber,
+        id: BlockHashOrNumber,
         _transaction_kind: TransactionVariant,
     ) -> ProviderResult<Option<SealedBlockWithSenders>> {
-        Ok(None)
+        Ok(self.block(id)?.and_then(|block| block.seal_slow().seal_with_senders()))
     }
 
     fn block_range(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Vec<Block>> {
@@ -572,8 +649,8 @@ impl AccountReader for MockEthProvider {
 }
 
 impl StageCheckpointReader for MockEthProvider {
-    fn get_stage_checkpoint(&self, _id: StageId) -> ProviderResult<Option<StageCheckpoint>> {
-        Ok(None)
+    fn get_stage_checkpoint(&self, id: StageId) -> ProviderResult<Option<StageCheckpoint>> {
+        Ok(self.stage_checkpoints.lock().get(id.as_str()).copied())
     }
 
     fn get_stage_checkpoint_progress(&self, _id: StageId) -> ProviderResult<Option<Vec<u8>>> {
@@ -581,7 +658,35 @@ impl StageCheckpointReader for MockEthProvider {
     }
 
     fn get_all_checkpoints(&self) -> ProviderResult<Vec<(String, StageCheckpoint)>> {
-        Ok(vec![])
+        let checkpoints = self.stage_checkpoints.lock();
+        Ok(checkpoints.iter().map(|(name, checkpoint)| (name.clone(), *checkpoint)).collect())
+    }
+}
+
+impl PruneCheckpointReader for MockEthProvider {
+    fn get_prune_checkpoint(
+        &self,
+        segment: PruneSegment,
+    ) -> ProviderResult<Option<PruneCheckpoint>> {
+        Ok(self.prune_checkpoints.lock().get(&segment).copied())
+    }
+
+    fn get_prune_checkpoints(&self) -> ProviderResult<Vec<(PruneSegment, PruneCheckpoint)>> {
+        let checkpoints = self.prune_checkpoints.lock();
+        Ok(checkpoints.iter().map(|(segment, checkpoint)| (*segment, *checkpoint)).collect())
+    }
+}
+
+impl StorageStatsReader for MockEthProvider {
+    fn table_stats(&self) -> ProviderResult<Vec<TableStats>> {
+        Ok(self.table_stats.lock().clone())
+    }
+
+    fn static_file_block_ranges(
+        &self,
+    ) -> ProviderResult<Vec<(StaticFileSegment, Vec<SegmentRangeInclusive>)>> {
+        let static_files = self.static_files.lock();
+        Ok(static_files.iter().map(|(segment, ranges)| (*segment, ranges.clone())).collect())
     }
 }
 
@@ -642,8 +747,9 @@ impl StateProofProvider for MockEthProvider {
     fn witness(
         &self,
         _input: TrieInput,
-        _target: HashedPostState,
+        target: HashedPostState,
     ) -> ProviderResult<HashMap<B256, Bytes>> {
+        self.witness_targets.lock().push(target);
         Ok(HashMap::default())
     }
 }
@@ -33,7 +33,7 @@ mod static_file_provider;
 pub use static_file_provider::StaticFileProviderFactory;
 
 mod stats;
-pub use stats::StatsReader;
+pub use stats::{StatsReader, StorageStatsReader, TableStats};
 
 mod full;
 pub use full::{FullProvider, FullRpcProvider};
@@ -1,6 +1,9 @@
 use reth_db_api::table::Table;
+use reth_primitives::{static_file::SegmentRangeInclusive, StaticFileSegment};
 use reth_storage_errors::provider::ProviderResult;
 
+pub use reth_db_api::database_metrics::TableStats;
+
 /// The trait for fetching provider statistics.
 #[auto_impl::auto_impl(&, Arc)]
 pub trait StatsReader: Send + Sync {
@@ -8,3 +11,15 @@ pub trait StatsReader: Send + Sync {
     /// route to different data sources other than [Table].
     fn count_entries<T: Table>(&self) -> ProviderResult<usize>;
 }
+
+/// The trait for fetching the statistics of the database tables and static files of the storage.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait StorageStatsReader: Send + Sync {
+    /// Returns the statistics of all tables of the database.
+    fn table_stats(&self) -> ProviderResult<Vec<TableStats>>;
+
+    /// Returns the block ranges of the static files of each segment, ordered by segment and block.
+    fn static_file_block_ranges(
+        &self,
+    ) -> ProviderResult<Vec<(StaticFileSegment, Vec<SegmentRangeInclusive>)>>;
+}
@@ -8,7 +8,7 @@ use std::{
     task::{ready, Context, Poll},
     thread,
 };
-use tokio::sync::{oneshot, AcquireError, OwnedSemaphorePermit, Semaphore};
+use tokio::sync::{oneshot, AcquireError, OwnedSemaphorePermit, Semaphore, TryAcquireError};
 
 /// RPC Tracing call guard semaphore.
 ///
@@ -19,13 +19,19 @@ use tokio::sync::{oneshot, AcquireError, OwnedSemaphorePermit, Semaphore};
 /// This types serves as an entry guard for the [`BlockingTaskPool`] and is used to rate limit
 /// parallel blocking tasks in the pool.
 #[derive(Clone, Debug)]
-pub struct BlockingTaskGuard(Arc<Semaphore>);
+pub struct BlockingTaskGuard(Arc<Semaphore>, usize);
 
 impl BlockingTaskGuard {
     /// Create a new `BlockingTaskGuard` with the given maximum number of blocking tasks in
     /// parallel.
     pub fn new(max_blocking_tasks: usize) -> Self {
-        Self(Arc::new(Semaphore::new(max_blocking_tasks)))
+        Self(Arc::new(Semaphore::new(max_blocking_tasks)), max_blocking_tasks)
+    }
+
+    /// Returns the maximum number of blocking tasks in parallel the guard was created with,
+    /// regardless of how many permits are currently acquired.
+    pub const fn max_permits(&self) -> usize {
+        self.1
     }
 
     /// See also [`Semaphore::acquire_owned`]
@@ -37,6 +43,23 @@ impl BlockingTaskGuard {
     pub async fn acquire_many_owned(self, n: u32) -> Result<OwnedSemaphorePermit, AcquireError> {
         self.0.acquire_many_owned(n).await
     }
+
+    /// See also [`Semaphore::try_acquire_owned`]
+    pub fn try_acquire_owned(self) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.0.try_acquire_owned()
+    }
+
+    /// See also [`Semaphore::available_permits`]
+    pub fn available_permits(&self) -> usize {
+        self.0.available_permits()
+    }
+
+    /// Closes the guard, so that no more permits can be acquired, e.g. on shutdown.
+    ///
+    /// See also [`Semaphore::close`]
+    pub fn close(&self) {
+        self.0.close()
+    }
 }
 
 /// Used to execute blocking tasks on a rayon threadpool from within a tokio runtime.
@@ -171,4 +194,12 @@ mod tests {
         let res = res.await;
         assert!(res.is_err());
     }
+
+    #[test]
+    fn guard_max_permits() {
+        let guard = BlockingTaskGuard::new(2);
+        let _permit = guard.clone().try_acquire_owned().unwrap();
+        assert_eq!(guard.available_permits(), 1);
+        assert_eq!(guard.max_permits(), 2);
+    }
 }